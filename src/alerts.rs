@@ -0,0 +1,306 @@
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::AlertsConfig;
+use crate::metrics::MetricsSnapshot;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlertMetric {
+    Latency,
+    Jitter,
+    Loss,
+    WanRx,
+    WanTx,
+    LanRx,
+    LanTx,
+    Connections,
+}
+
+impl AlertMetric {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s.to_lowercase().as_str() {
+            "latency" | "rtt" | "ping" => Self::Latency,
+            "jitter" => Self::Jitter,
+            "loss" | "packet_loss" => Self::Loss,
+            "wan_rx" | "download" => Self::WanRx,
+            "wan_tx" | "upload" => Self::WanTx,
+            "lan_rx" => Self::LanRx,
+            "lan_tx" => Self::LanTx,
+            "connections" | "conns" => Self::Connections,
+            _ => return None,
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Latency => "latency",
+            Self::Jitter => "jitter",
+            Self::Loss => "loss",
+            Self::WanRx => "wan_rx",
+            Self::WanTx => "wan_tx",
+            Self::LanRx => "lan_rx",
+            Self::LanTx => "lan_tx",
+            Self::Connections => "connections",
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Self::Latency | Self::Jitter => "ms",
+            Self::Loss => "%",
+            Self::WanRx | Self::WanTx | Self::LanRx | Self::LanTx => "Mbps",
+            Self::Connections => "",
+        }
+    }
+
+    fn is_ping(&self) -> bool {
+        matches!(self, Self::Latency | Self::Jitter | Self::Loss)
+    }
+
+    /// Current readings: one per dashboard ping target for ping metrics, otherwise a single one.
+    fn values<'a>(&self, m: &'a MetricsSnapshot) -> Vec<(f64, Option<&'a str>)> {
+        let single = match self {
+            Self::Latency => return m.pings.iter().map(|p| (p.latency_ms, Some(p.target.as_str()))).collect(),
+            Self::Jitter => return m.pings.iter().map(|p| (p.jitter_ms, Some(p.target.as_str()))).collect(),
+            Self::Loss => return m.pings.iter().map(|p| (p.loss_ratio * 100.0, Some(p.target.as_str()))).collect(),
+            Self::WanRx => m.wan_rx_mbps,
+            Self::WanTx => m.wan_tx_mbps,
+            Self::LanRx => m.lan_rx_mbps,
+            Self::LanTx => m.lan_tx_mbps,
+            Self::Connections => m.connections as f64,
+        };
+        vec![(single, None)]
+    }
+}
+
+/// A threshold rule, written as e.g. `latency to 1.1.1.1 > 150ms for 30s` or `loss > 5%`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct AlertRule {
+    pub metric: AlertMetric,
+    pub target: Option<String>,
+    pub above: bool,
+    pub threshold: f64,
+    pub hold: Duration,
+}
+
+impl FromStr for AlertRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = s.split_whitespace().peekable();
+        let usage = "expected: <metric> [to <target>] <>|<> <value> [for <N>s]";
+
+        let metric_str = tokens.next().ok_or(usage)?;
+        let metric = AlertMetric::parse(metric_str)
+            .ok_or_else(|| format!("Unknown metric '{}' (latency, jitter, loss, wan_rx, wan_tx, lan_rx, lan_tx, connections)", metric_str))?;
+
+        let mut target = None;
+        if tokens.peek() == Some(&"to") {
+            tokens.next();
+            if !metric.is_ping() {
+                return Err(format!("'{}' does not take a target", metric.name()));
+            }
+            target = Some(tokens.next().ok_or(usage)?.to_string());
+        }
+
+        let above = match tokens.next() {
+            Some(">") => true,
+            Some("<") => false,
+            _ => return Err(usage.to_string()),
+        };
+
+        let value_str = tokens.next().ok_or(usage)?;
+        let threshold = value_str
+            .trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '%')
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid threshold '{}'", value_str))?;
+
+        let mut hold = Duration::ZERO;
+        if let Some(kw) = tokens.next() {
+            if kw != "for" {
+                return Err(usage.to_string());
+            }
+            hold = parse_duration(tokens.next().ok_or(usage)?)?;
+        }
+        if tokens.next().is_some() {
+            return Err(usage.to_string());
+        }
+
+        Ok(AlertRule { metric, target, above, threshold, hold })
+    }
+}
+
+impl TryFrom<String> for AlertRule {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<AlertRule> for String {
+    fn from(rule: AlertRule) -> String {
+        rule.to_string()
+    }
+}
+
+impl fmt::Display for AlertRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.metric.name())?;
+        if let Some(target) = &self.target {
+            write!(f, " to {}", target)?;
+        }
+        write!(f, " {} {}{}", if self.above { ">" } else { "<" }, self.threshold, self.metric.unit())?;
+        if !self.hold.is_zero() {
+            write!(f, " for {}s", self.hold.as_secs())?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let (num, mult) = if let Some(n) = s.strip_suffix('m') {
+        (n, 60)
+    } else {
+        (s.trim_end_matches('s'), 1)
+    };
+    num.parse::<u64>().ok()
+        .and_then(|n| n.checked_mul(mult))
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("Invalid duration '{}'", s))
+}
+
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub rule: String,
+    pub message: String,
+    pub since: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum AlertChange {
+    Raised(Alert),
+    Cleared(Alert),
+}
+
+#[derive(Default, Clone)]
+struct RuleState {
+    breach_since: Option<Instant>,
+    firing: Option<Alert>,
+}
+
+/// Tracks how long each rule has been in breach and reports when it starts or stops firing.
+#[derive(Default)]
+pub struct AlertEngine {
+    states: Vec<RuleState>,
+}
+
+impl AlertEngine {
+    /// Drops per-rule state; call after the rule list changes.
+    pub fn reset(&mut self) {
+        self.states.clear();
+    }
+
+    pub fn active(&self) -> impl Iterator<Item = &Alert> {
+        self.states.iter().filter_map(|s| s.firing.as_ref())
+    }
+
+    pub fn evaluate(&mut self, rules: &[AlertRule], m: &MetricsSnapshot) -> Vec<AlertChange> {
+        self.states.resize(rules.len(), RuleState::default());
+        let mut changes = vec![];
+
+        for (rule, state) in rules.iter().zip(self.states.iter_mut()) {
+            // Ping rules only apply to targets the dashboard actually probes; without a target the
+            // worst one counts
+            let readings = rule.metric.values(m).into_iter()
+                .filter(|(_, target)| rule.target.as_ref().is_none_or(|t| Some(t.as_str()) == *target));
+            let worst = if rule.above {
+                readings.max_by(|a, b| a.0.total_cmp(&b.0))
+            } else {
+                readings.min_by(|a, b| a.0.total_cmp(&b.0))
+            };
+            let breached = worst.filter(|(value, _)| if rule.above { *value > rule.threshold } else { *value < rule.threshold });
+
+            let Some((value, target)) = breached else {
+                state.breach_since = None;
+                if let Some(alert) = state.firing.take() {
+                    changes.push(AlertChange::Cleared(alert));
+                }
+                continue;
+            };
+
+            let since = *state.breach_since.get_or_insert_with(Instant::now);
+            if state.firing.is_none() && since.elapsed() >= rule.hold {
+                let subject = match target {
+                    Some(target) => format!("{} to {}", rule.metric.name(), target),
+                    None => rule.metric.name().to_string(),
+                };
+                let alert = Alert {
+                    rule: rule.to_string(),
+                    message: format!("{} is {:.1}{} ({})", subject, value, rule.metric.unit(), rule),
+                    since: timestamp(),
+                };
+                state.firing = Some(alert.clone());
+                changes.push(AlertChange::Raised(alert));
+            }
+        }
+
+        changes
+    }
+}
+
+pub fn timestamp() -> String {
+    time::OffsetDateTime::now_utc().time().format(&time::format_description::parse("[hour]:[minute]:[second]").unwrap()).unwrap_or_default()
+}
+
+/// Fires the configured side channels for a newly raised alert.
+pub fn notify(config: &AlertsConfig, alert: &Alert) {
+    if config.bell {
+        let mut out = std::io::stdout();
+        let _ = out.write_all(b"\x07");
+        let _ = out.flush();
+    }
+
+    if config.desktop {
+        notify_desktop(&alert.message);
+    }
+
+    if let Some(url) = config.webhook.clone().filter(|u| !u.is_empty()) {
+        let body = serde_json::json!({
+            "source": "netops",
+            "rule": alert.rule,
+            "message": alert.message,
+            "since": alert.since,
+        });
+        tokio::spawn(async move {
+            let client = reqwest::Client::new();
+            let _ = client.post(&url)
+                .json(&body)
+                .timeout(Duration::from_secs(5))
+                .send()
+                .await;
+        });
+    }
+}
+
+fn notify_desktop(message: &str) {
+    let mut cmd = if cfg!(target_os = "macos") {
+        let mut c = std::process::Command::new("osascript");
+        let script = format!("display notification \"{}\" with title \"NetOps alert\"", message.replace('"', "'"));
+        c.args(["-e", &script]);
+        c
+    } else {
+        let mut c = std::process::Command::new("notify-send");
+        c.args(["NetOps alert", message]);
+        c
+    };
+    let _ = cmd
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn();
+}
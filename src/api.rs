@@ -0,0 +1,315 @@
+use std::time::Duration;
+
+use base64::Engine;
+use crossbeam::channel::Sender;
+use serde_json::{Value, json};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+use crate::shutdown;
+
+// Requests are small JSON bodies; anything bigger is a mistake or abuse
+const MAX_REQUEST: usize = 64 * 1024;
+// For the whole request head and body, so a client that stops sending doesn't hold a task forever
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+const WS_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// What a client asked for. Handled on the UI side in `App::tick`, like a key press.
+#[derive(Debug)]
+pub enum ApiCommand {
+    Status,
+    /// `args` replaces the tool's input first, exactly as if typed
+    Start { tool: String, args: Option<String> },
+    Stop { tool: String },
+}
+
+pub struct ApiRequest {
+    pub command: ApiCommand,
+    pub reply: oneshot::Sender<Result<Value, String>>,
+}
+
+pub type ApiEvents = broadcast::Sender<String>;
+
+/// Sends one event to every connected WebSocket client; a no-op with the API off or nobody listening.
+pub fn emit(events: &Option<ApiEvents>, tool: &str, event: Value) {
+    let Some(events) = events else { return };
+    if events.receiver_count() == 0 {
+        return;
+    }
+    let at = time::OffsetDateTime::now_utc().unix_timestamp();
+    let _ = events.send(json!({ "tool": tool, "time": at, "event": event }).to_string());
+}
+
+/// Serves the API; every request must carry `token`.
+pub fn spawn_server(listener: std::net::TcpListener, token: String, requests: Sender<ApiRequest>, events: ApiEvents) -> std::io::Result<()> {
+    listener.set_nonblocking(true)?;
+    let listener = TcpListener::from_std(listener)?;
+
+    tokio::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    // A lasting error (EMFILE) would otherwise come straight back
+                    tracing::warn!("API: accept failed: {}", e);
+                    if !shutdown::sleep_async(Duration::from_millis(100)).await {
+                        return;
+                    }
+                    continue;
+                }
+            };
+            let (token, requests, events) = (token.clone(), requests.clone(), events.clone());
+            tokio::spawn(async move {
+                let _ = serve(stream, token, requests, events).await;
+            });
+        }
+    });
+    Ok(())
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+
+    /// Bearer header, or `?token=` for WebSocket clients that can't set headers.
+    fn token(&self) -> Option<&str> {
+        self.header("authorization").and_then(|v| v.strip_prefix("Bearer "))
+            .or_else(|| self.query.split('&').find_map(|kv| kv.strip_prefix("token=")))
+    }
+}
+
+async fn read_request(stream: &mut tokio::net::TcpStream) -> Option<Request> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        let n = stream.read(&mut chunk).await.ok().filter(|n| *n > 0)?;
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(i) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break i;
+        }
+        if buf.len() > MAX_REQUEST {
+            return None;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_end]).into_owned();
+    let mut lines = head.lines();
+    let mut start = lines.next()?.split_whitespace();
+    let method = start.next()?.to_string();
+    let target = start.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|l| l.split_once(':'))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect();
+
+    let mut request = Request { method, path: path.to_string(), query: query.to_string(), headers, body: buf[head_end + 4..].to_vec() };
+    let length: usize = request.header("content-length").and_then(|v| v.parse().ok()).unwrap_or(0).min(MAX_REQUEST);
+    while request.body.len() < length {
+        let n = stream.read(&mut chunk).await.ok().filter(|n| *n > 0)?;
+        request.body.extend_from_slice(&chunk[..n]);
+    }
+    Some(request)
+}
+
+async fn serve(mut stream: tokio::net::TcpStream, token: String, requests: Sender<ApiRequest>, events: ApiEvents) -> std::io::Result<()> {
+    let Ok(Some(request)) = tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream)).await else { return Ok(()) };
+
+    // Browsers send Origin on cross-site requests; scripts and dashboards calling from a backend don't
+    if request.header("origin").is_some() {
+        return respond(&mut stream, 403, &json!({ "error": "cross-origin requests are not allowed" })).await;
+    }
+    // A page can POST text/plain (or no type at all) without a preflight; only JSON bodies are taken
+    let json = request.header("content-type").is_some_and(|v| v.split(';').next().unwrap_or("").trim().eq_ignore_ascii_case("application/json"));
+    if !json && (request.header("content-type").is_some() || !request.body.is_empty()) {
+        return respond(&mut stream, 415, &json!({ "error": "expected Content-Type: application/json" })).await;
+    }
+    if request.token() != Some(token.as_str()) {
+        return respond(&mut stream, 401, &json!({ "error": "missing or wrong token" })).await;
+    }
+
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    let command = match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["api", "events"]) => return websocket(stream, &request, events.subscribe()).await,
+        ("GET", ["api", "status"]) => ApiCommand::Status,
+        ("POST", ["api", "tools", tool, "start"]) => {
+            let args = serde_json::from_slice::<Value>(&request.body).ok()
+                .and_then(|body| body.get("args").and_then(Value::as_str).map(str::to_string));
+            ApiCommand::Start { tool: tool.to_string(), args }
+        }
+        ("POST", ["api", "tools", tool, "stop"]) => ApiCommand::Stop { tool: tool.to_string() },
+        _ => {
+            let routes = "GET /api/status, POST /api/tools/<tool>/start {\"args\": \"...\"}, POST /api/tools/<tool>/stop, GET /api/events (WebSocket)";
+            return respond(&mut stream, 404, &json!({ "error": format!("Not found. Routes: {}", routes) })).await;
+        }
+    };
+
+    let (reply_tx, reply_rx) = oneshot::channel();
+    if requests.send(ApiRequest { command, reply: reply_tx }).is_err() {
+        return respond(&mut stream, 503, &json!({ "error": "shutting down" })).await;
+    }
+    match reply_rx.await {
+        Ok(Ok(body)) => respond(&mut stream, 200, &body).await,
+        Ok(Err(e)) => respond(&mut stream, 400, &json!({ "error": e })).await,
+        Err(_) => respond(&mut stream, 503, &json!({ "error": "shutting down" })).await,
+    }
+}
+
+async fn respond(stream: &mut tokio::net::TcpStream, status: u16, body: &Value) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        415 => "Unsupported Media Type",
+        _ => "Service Unavailable",
+    };
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, reason, body.len(), body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Upgrades to a WebSocket (RFC 6455) and forwards every event as a text frame until the client leaves.
+async fn websocket(mut stream: tokio::net::TcpStream, request: &Request, mut events: broadcast::Receiver<String>) -> std::io::Result<()> {
+    let Some(key) = request.header("sec-websocket-key") else {
+        return respond(&mut stream, 400, &json!({ "error": "expected a WebSocket upgrade" })).await;
+    };
+    let accept = base64::engine::general_purpose::STANDARD.encode(sha1(format!("{}{}", key, WS_GUID).as_bytes()));
+    let handshake = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept
+    );
+    stream.write_all(handshake.as_bytes()).await?;
+
+    let (mut reader, mut writer) = stream.into_split();
+    // Client frames are only read for ping and close; the reader hands those to the writer
+    let (control_tx, mut control) = mpsc::channel::<(u8, Vec<u8>)>(4);
+    tokio::spawn(async move {
+        while let Ok((opcode, payload)) = read_frame(&mut reader).await {
+            if matches!(opcode, 0x8 | 0x9) && (control_tx.send((opcode, payload)).await.is_err() || opcode == 0x8) {
+                break;
+            }
+        }
+    });
+
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(text) => writer.write_all(&frame(0x1, text.as_bytes())).await?,
+                // A slow client misses events rather than stalling everyone else
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            control = control.recv() => match control {
+                Some((0x9, payload)) => writer.write_all(&frame(0xA, &payload)).await?,
+                _ => {
+                    let _ = writer.write_all(&frame(0x8, &[])).await;
+                    break;
+                }
+            },
+        }
+    }
+    Ok(())
+}
+
+/// One (unfragmented) client frame: opcode and unmasked payload.
+async fn read_frame(reader: &mut (impl AsyncRead + Unpin)) -> std::io::Result<(u8, Vec<u8>)> {
+    let mut head = [0u8; 2];
+    reader.read_exact(&mut head).await?;
+    let len = match head[1] & 0x7f {
+        126 => reader.read_u16().await? as u64,
+        127 => reader.read_u64().await?,
+        n => n as u64,
+    };
+    if len > MAX_REQUEST as u64 {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "frame too large"));
+    }
+    let mut mask = [0u8; 4];
+    if head[1] & 0x80 != 0 {
+        reader.read_exact(&mut mask).await?;
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload).await?;
+    for (i, b) in payload.iter_mut().enumerate() {
+        *b ^= mask[i % 4];
+    }
+    Ok((head[0] & 0x0f, payload))
+}
+
+/// A final, unmasked server frame.
+fn frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x80 | opcode];
+    match payload.len() {
+        n if n < 126 => out.push(n as u8),
+        n if n <= u16::MAX as usize => {
+            out.push(126);
+            out.extend_from_slice(&(n as u16).to_be_bytes());
+        }
+        n => {
+            out.push(127);
+            out.extend_from_slice(&(n as u64).to_be_bytes());
+        }
+    }
+    out.extend_from_slice(payload);
+    out
+}
+
+/// SHA-1, needed only for the WebSocket handshake's `Sec-WebSocket-Accept`.
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut msg = data.to_vec();
+    msg.push(0x80);
+    while msg.len() % 64 != 56 {
+        msg.push(0);
+    }
+    msg.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in msg.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, wi) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*wi);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (hv, v) in h.iter_mut().zip([a, b, c, d, e]) {
+            *hv = hv.wrapping_add(v);
+        }
+    }
+
+    let mut out = [0u8; 20];
+    for (i, v) in h.iter().enumerate() {
+        out[i * 4..i * 4 + 4].copy_from_slice(&v.to_be_bytes());
+    }
+    out
+}
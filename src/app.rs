@@ -1,19 +1,56 @@
-use std::collections::{VecDeque, HashMap};
+use std::collections::{BTreeMap, VecDeque, HashMap};
 use std::net::IpAddr;
+use std::sync::Arc;
 // use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
-use ratatui::widgets::TableState;
+use ratatui::widgets::{ListState, TableState};
 use anyhow::Result;
 
 use pnet_datalink::NetworkInterface;
 use crate::tools::ping::{PingResult, PingTask};
-use crate::tools::{interfaces, dns, sniffer, mtr, nmap, arpscan, geoip, connections};
+use crate::tools::{interfaces, asn, dns, dnsbl, mailcheck, subdomains, sniffer, mtr, nmap, arpscan, geoip, connections, wol, mdns, ssdp, flows, whois, listeners, neighbors, oui, rdns, hostnames, reputation, pcap, wifi, dhcp, traceroute, sweep, banner, syslog, gateway, diagnose};
+use crate::tools::terminate::KillTarget;
+use crate::tools::process::ProcessHandle;
+use crate::tools::args;
+use crate::tools::capture_filter::{self, CaptureFilter};
+use crate::shutdown;
 use crate::tools::dns::DnsResult;
+use crate::config::Config;
+use crate::dashboard::{self, LatencyProbe, LayoutCursor, Panel, HISTORY};
+use crate::metrics::{self, MetricsSnapshot, PingMetrics, SharedMetrics};
+use crate::api::{self, ApiCommand, ApiEvents, ApiRequest};
+use serde_json::{Value, json};
+use crate::alerts::{self, Alert, AlertChange, AlertEngine, AlertRule};
+use crate::scheduler::{FinishedRun, RunOutcome, Scheduler};
+use crate::history::{HistoryEntry, HistoryKind, HistoryStore};
+use crate::inventory::{self, Inventory};
+use crate::tools::ping::{LatencyHeatmap, PingStats};
+use crate::histogram::Histogram;
+use crate::ingest::{self, GeoIpLookups, PeerInfo, SnifferIngest};
+use crate::ring::Ring;
+use crate::series::{TieredSeries, TimeRange};
+use crate::talkers::{TalkerStats, TalkerWindow, TopTalker};
+use crate::export::{self, ExportTable};
+use crate::screens::{self, command, ToolRegistry};
+use crate::palette::{self, PaletteAction, PaletteEntry};
+use crate::keymap::Keymap;
+use crate::clipboard::Clipboard;
+use crate::search::{self, TableSearch};
+use crate::focus::{self, Field};
+use crate::completion::{self, InputHistory};
+use crate::flags::{self, FlagEditor};
+use crate::help::HelpBrowser;
+use crate::privileges::Privileges;
+use crate::eventlog::EventLog;
+use crate::profiles::{ProfileTool, ScanProfile};
+use crate::session::{self, Session};
+use crate::theme;
 
 use tokio::sync::mpsc::{self, Receiver, error::TryRecvError};
 use tui_input::Input;
 
 use hickory_resolver::proto::rr::RecordType;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CurrentScreen {
     Dashboard,
     Ping,
@@ -23,7 +60,111 @@ pub enum CurrentScreen {
     Nmap,
     Connections,
     ArpScan,
-    // Traceroute,
+    Mdns,
+    Ssdp,
+    Flows,
+    History,
+    Wifi,
+    Dhcp,
+    Ntp,
+    Traceroute,
+    Sweep,
+    Syslog,
+    Diagnose,
+    Devices,
+    Asn,
+    Runner,
+    HttpLoad,
+    /// A command tool from `[[plugins]]` in the config, by its place there
+    Plugin(usize),
+}
+
+impl CurrentScreen {
+    pub const ALL: [CurrentScreen; 23] = [
+        CurrentScreen::Dashboard,
+        CurrentScreen::Ping,
+        CurrentScreen::Dns,
+        CurrentScreen::Sniffer,
+        CurrentScreen::Mtr,
+        CurrentScreen::Nmap,
+        CurrentScreen::ArpScan,
+        CurrentScreen::Connections,
+        CurrentScreen::Mdns,
+        CurrentScreen::Ssdp,
+        CurrentScreen::Flows,
+        CurrentScreen::History,
+        CurrentScreen::Wifi,
+        CurrentScreen::Dhcp,
+        CurrentScreen::Ntp,
+        CurrentScreen::Traceroute,
+        CurrentScreen::Sweep,
+        CurrentScreen::Syslog,
+        CurrentScreen::Diagnose,
+        CurrentScreen::Devices,
+        CurrentScreen::Asn,
+        CurrentScreen::Runner,
+        CurrentScreen::HttpLoad,
+    ];
+
+    /// The built-in screens, then one per configured plugin.
+    pub fn all() -> Vec<CurrentScreen> {
+        Self::ALL.into_iter().chain((0..command::count()).map(CurrentScreen::Plugin)).collect()
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            CurrentScreen::Dashboard => "Dashboard",
+            CurrentScreen::Ping => "Ping",
+            CurrentScreen::Dns => "DNS",
+            CurrentScreen::Sniffer => "Sniffer",
+            CurrentScreen::Mtr => "MTR",
+            CurrentScreen::Nmap => "Nmap Scan",
+            CurrentScreen::ArpScan => "ARP Scan",
+            CurrentScreen::Connections => "Connections",
+            CurrentScreen::Mdns => "mDNS Discovery",
+            CurrentScreen::Ssdp => "SSDP / UPnP Discovery",
+            CurrentScreen::Flows => "Flow Collector",
+            CurrentScreen::History => "History",
+            CurrentScreen::Wifi => "Wi-Fi",
+            CurrentScreen::Dhcp => "DHCP Diagnostics",
+            CurrentScreen::Traceroute => "Traceroute",
+            CurrentScreen::Sweep => "Ping Sweep",
+            CurrentScreen::Syslog => "Syslog",
+            CurrentScreen::Diagnose => "Diagnose",
+            CurrentScreen::Devices => "Devices",
+            CurrentScreen::Asn => "ASN",
+            CurrentScreen::Plugin(i) => command::title(*i),
+            screen => screens::info(*screen).map_or("", |t| t.title),
+        }
+    }
+
+    /// Name in `goto_<name>` key actions and API paths.
+    pub fn slug(&self) -> &'static str {
+        match self {
+            CurrentScreen::Dashboard => "dashboard",
+            CurrentScreen::Ping => "ping",
+            CurrentScreen::Dns => "dns",
+            CurrentScreen::Sniffer => "sniffer",
+            CurrentScreen::Mtr => "mtr",
+            CurrentScreen::Nmap => "scan",
+            CurrentScreen::ArpScan => "arp",
+            CurrentScreen::Connections => "connections",
+            CurrentScreen::Mdns => "mdns",
+            CurrentScreen::Ssdp => "upnp",
+            CurrentScreen::Flows => "flows",
+            CurrentScreen::History => "history",
+            CurrentScreen::Wifi => "wifi",
+            CurrentScreen::Dhcp => "dhcp",
+            CurrentScreen::Traceroute => "traceroute",
+            CurrentScreen::Sweep => "sweep",
+            CurrentScreen::Syslog => "syslog",
+            CurrentScreen::Diagnose => "diagnose",
+            CurrentScreen::Devices => "devices",
+            CurrentScreen::Asn => "asn",
+            CurrentScreen::Plugin(i) => command::slug_of(*i),
+            screen => screens::info(*screen).map_or("", |t| t.slug),
+        }
+    }
 }
 
 pub struct ConnectionInfo {
@@ -31,23 +172,102 @@ pub struct ConnectionInfo {
     pub asn_num: u32,
     pub asn_org: String,
     pub last_seen: std::time::Instant,
-    pub packet_count: u64,
+    pub packet_count: u64, // Counted from sniffed packets
+    pub bytes: u64,
+    pub rate_bps: f64,     // Bytes/sec over the last netstat refresh
+    pub rate_mark: u64,    // `bytes` at the last refresh
+    pub rate_history: Ring<f64>, // `rate_bps` at each refresh, for the trend sparkline
     pub protocol: String,
+    pub local_port: Option<u16>,
+    pub remote_port: Option<u16>,
+    pub state: String,                // TCP state; empty for UDP
+    pub sockets: usize,               // Connections to this peer; the ports and state are of one of them
     pub location: Option<(f64, f64)>, // Lat, Lon
+    pub country: Option<String>,      // ISO code, with a country database loaded
+    pub pid: Option<u32>,             // Owning process, when the OS tells us
+    pub process: Option<String>,
+}
+
+/// Rates derived from successive OS counter samples of one interface.
+pub struct InterfaceHistory {
+    pub counters: interfaces::InterfaceCounters,
+    pub sampled: std::time::Instant,
+    pub rx_mbps: Ring<f64>,
+    pub tx_mbps: Ring<f64>,
+    pub rx_pps: Ring<f64>,
+    pub tx_pps: Ring<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConnectionSort {
+    #[default]
+    LastSeen,
+    Bandwidth,
+    Packets,
+}
+
+impl ConnectionSort {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::LastSeen => "last seen",
+            Self::Bandwidth => "bandwidth",
+            Self::Packets => "packets",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Self::LastSeen => Self::Bandwidth,
+            Self::Bandwidth => Self::Packets,
+            Self::Packets => Self::LastSeen,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConnectionsView {
+    #[default]
+    Established,
+    Listening,
+    /// Peers by sniffed bytes over the last few minutes
+    Talkers,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArpView {
+    #[default]
+    Scan,
+    /// The OS ARP/NDP cache, watched for changed bindings
+    Neighbors,
 }
 
+/// Row actions offered by Enter on the Connections screen.
+// Connection refreshes kept per peer for the bandwidth trend
+const RATE_HISTORY: usize = 32;
+
+pub const CONNECTION_ACTIONS: [&str; 7] = ["Ping", "MTR", "WHOIS", "ASN", "Copy IP", "Kill process", "Reset connection"];
+
 pub struct App {
     pub current_screen: CurrentScreen,
+    // Split view: the unfocused pane's screen; keys always go to current_screen
+    pub split_screen: Option<CurrentScreen>,
+    focus: HashMap<CurrentScreen, usize>, // index into focus::fields; absent means the first
+    pub split_focus_left: bool,
     pub should_quit: bool,
-    pub show_help: bool,
-    pub show_options: bool,
-    pub options_scroll: usize,
+    pub help: Option<HelpBrowser>,
+    pub flag_editor: Option<FlagEditor>,
     pub interfaces: Vec<NetworkInterface>,
+    pub privileges: Privileges,
+    pub iface_stats_rx: Option<crossbeam::channel::Receiver<HashMap<String, interfaces::InterfaceCounters>>>,
+    pub iface_history: HashMap<String, InterfaceHistory>,
+    pub dash_iface_selected: usize,
+    /// Interface shown full-screen on the dashboard, with link info read when opened
+    pub iface_detail: Option<(String, interfaces::LinkInfo)>,
     
     // Ping State
     pub ping_input: Input,
-    pub ping_history: VecDeque<Result<PingResult, String>>,
-    pub ping_rtt_history: VecDeque<f64>,
+    pub ping_history: Ring<Result<PingResult, String>>,
+    pub ping_rtt_history: Ring<f64>,
     pub ping_rx: Option<Receiver<Result<PingResult, String>>>,
     pub is_pinging: bool,
 
@@ -57,59 +277,279 @@ pub struct App {
     pub dns_input: Input,
     pub dns_record_type: RecordType,
     pub dns_result: Option<Result<DnsResult, String>>,
-    pub dns_rx: Option<Receiver<Result<DnsResult, String>>>,
+    pub dns_rx: Option<Receiver<(Result<DnsResult, String>, std::time::Duration)>>, // with how long the query took
+    pub dns_watch: Option<dns::DnsWatch>,
+    pub dns_mail: Option<Result<mailcheck::MailCheck, String>>,
+    pub dns_mail_rx: Option<Receiver<Result<mailcheck::MailCheck, String>>>,
+    pub dns_blocklist: Option<dnsbl::BlocklistCheck>,
+    pub dns_blocklist_rx: Option<Receiver<dnsbl::DnsblEvent>>,
+    pub dns_subdomains: Option<subdomains::SubdomainScan>,
+    pub dns_subdomains_rx: Option<crossbeam::channel::Receiver<subdomains::SubdomainEvent>>,
 
     // Sniffer State
     pub sniffer: sniffer::Sniffer,
-    pub sniffer_rx: Option<crossbeam::channel::Receiver<sniffer::PacketSummary>>,
-    pub sniffer_packets: VecDeque<sniffer::PacketSummary>,
+    pub sniffer_ingest: Option<SnifferIngest>,
+    pub sniffer_packets: Ring<sniffer::PacketSummary>,
     pub sniffer_active: bool,
+    pub sniffer_error: Option<String>,
     pub sniffer_filter_input: Input,
-    pub selected_interface_index: usize,
+    pub filter_presets: Option<usize>, // selected row of the Ctrl+F list of presets and recent filters
+    pub selected_interface_index: usize, // == interfaces.len() means "All"
+    pub sniffer_capture: Vec<String>, // interfaces ticked with Tab; empty means just the cursor one
+    pub sniffer_table_state: TableState,
+    pub sniffer_selected: usize, // index into the newest-first view
+    pub sniffer_seen: u64, // packets received from the capture threads since start
+    pub sniffer_evicted: u64, // packets pushed out of the ring buffer since start
+    pub sniffer_paused_at: Option<u64>, // sniffer_seen when the display was frozen
+    pub sniffer_autoscroll: bool,
+    /// ICMP messages per type since the capture started (or the list was cleared)
+    pub sniffer_icmp: HashMap<sniffer::IcmpKind, u64>,
 
     // MTR State
     pub mtr_input: Input,
     pub mtr_task: mtr::MtrTask,
-    pub mtr_rx: Option<crossbeam::channel::Receiver<mtr::MtrResult>>,
+    pub mtr_rx: Option<crossbeam::channel::Receiver<mtr::MtrEvent>>,
     pub mtr_hops: Vec<mtr::HopStats>,
     pub mtr_active: bool,
+    pub mtr_error: Option<String>,
     pub mtr_table_state: TableState,
     pub mtr_selected_hop: usize,
+    /// Typed into since the last run: Up/Down recall earlier targets rather than move through the hops
+    pub mtr_editing: bool,
+    pub mtr_paths: mtr::PathTracker,
+    /// Every flow's path, when `--paths` enumerates ECMP routes
+    pub mtr_flows: mtr::FlowPaths,
+    pub show_mtr_paths: bool,
+    pub mtr_paths_scroll: u16,
 
     // Nmap State
     pub nmap_input: Input,
     pub nmap_active: bool,
-    pub nmap_rx: Option<crossbeam::channel::Receiver<String>>,
-    pub nmap_output: VecDeque<String>,
+    pub nmap_rx: Option<crossbeam::channel::Receiver<Result<String, String>>>,
+    pub nmap_process: Option<ProcessHandle>, // Stops the running nmap
+    pub nmap_error: Option<String>,
+    pub nmap_output: Ring<String>,
     pub nmap_scroll: u16,
+    pub nmap_list_state: ListState,
+    /// As `mtr_editing`, for the scan output
+    pub nmap_editing: bool,
+    pub nmap_ports: Vec<nmap::ScanPort>, // Open TCP ports of the last finished scan
+    pub nmap_grab_banners: bool, // `--banners` given: connect to open ports afterwards
+    pub nmap_banner_rx: Option<crossbeam::channel::Receiver<banner::BannerResult>>,
+    pub nmap_banners: HashMap<(String, u16), banner::BannerResult>,
+    pub nmap_ports_table_state: TableState,
 
     // ArpScan State
+    /// Tools that own their state and screen; see `screens::Tool`
+    pub tools: ToolRegistry,
     pub arpscan_input: Input,
     pub arpscan_active: bool,
-    pub arpscan_rx: Option<crossbeam::channel::Receiver<String>>,
-    pub arpscan_output: VecDeque<String>,
+    pub arpscan_rx: Option<crossbeam::channel::Receiver<Result<String, String>>>,
+    pub arpscan_process: Option<ProcessHandle>,
+    pub arpscan_error: Option<String>,
+    pub arpscan_output: Ring<String>,
     pub arpscan_results: Vec<arpscan::ArpEntry>,
+    /// Continuous discovery (`--watch`), running in place of arp-scan
+    pub arpwatch_task: Option<arpscan::DiscoveryTask>,
+    pub arpwatch_rx: Option<crossbeam::channel::Receiver<arpscan::DiscoveryEvent>>,
+    /// Time between sweeps while watching; decides when a host shows offline
+    pub arpwatch_every: Option<std::time::Duration>,
+    pub arpwatch_sweeps: u64,
+    /// Names for ARP Scan hosts by reverse DNS, mDNS or NetBIOS; `None` while asking or
+    /// when nothing answered
+    pub arp_names: HashMap<IpAddr, Option<hostnames::HostName>>,
+    arp_namer: Option<hostnames::HostNamer>,
+    arp_names_rx: Option<crossbeam::channel::Receiver<(IpAddr, Option<hostnames::HostName>)>>,
     pub arpscan_scroll: u16,
+    pub arpscan_table_state: TableState,
+    pub arpscan_selected: usize,
+    pub arp_view: ArpView,
+    pub neighbors: neighbors::NeighborTable,
+    pub neighbors_rx: Option<crossbeam::channel::Receiver<Result<Vec<neighbors::Neighbor>, String>>>,
+    pub neighbors_error: Option<String>,
+    pub neighbors_table_state: TableState,
+    pub neighbors_selected: usize,
+
+    // Wake-on-LAN Popup
+    pub show_wol: bool,
+    pub wol_input: Input,
+    pub wol_interface_index: usize, // 0 = global broadcast, n = interfaces[n - 1]
+    pub wol_status: Option<Result<String, String>>,
+    pub oui: Option<oui::OuiDb>,
+    pub oui_rx: Option<crossbeam::channel::Receiver<Result<oui::OuiDb, String>>>,
+    pub oui_error: Option<String>,
+    pub show_oui_lookup: bool,
+    pub oui_input: Input,
+
+    // mDNS Discovery
+    pub mdns_task: mdns::MdnsTask,
+    pub mdns_rx: Option<crossbeam::channel::Receiver<mdns::MdnsEvent>>,
+    pub mdns_services: Vec<mdns::MdnsService>,
+    pub mdns_active: bool,
+    pub mdns_error: Option<String>,
+    pub mdns_table_state: TableState,
+    pub mdns_selected: usize,
+
+    // SSDP / UPnP Discovery
+    pub ssdp_task: ssdp::SsdpTask,
+    pub ssdp_rx: Option<crossbeam::channel::Receiver<ssdp::SsdpEvent>>,
+    pub ssdp_devices: Vec<ssdp::SsdpDevice>,
+    pub ssdp_active: bool,
+    pub ssdp_error: Option<String>,
+    pub ssdp_table_state: TableState,
+    pub ssdp_selected: usize,
+
+    // Wi-Fi
+    pub wifi_task: wifi::WifiTask,
+    pub wifi_rx: Option<crossbeam::channel::Receiver<wifi::WifiEvent>>,
+    pub wifi_interface: Option<String>,
+    pub wifi_networks: Vec<wifi::WifiNetwork>,
+    pub wifi_link: Option<wifi::WifiLink>,
+    pub wifi_rssi_history: Ring<f64>, // dBm, one sample a second
+    pub wifi_active: bool,
+    pub wifi_error: Option<String>,
+    pub wifi_table_state: TableState,
+    pub wifi_selected: usize,
+
+    // DHCP diagnostics
+    pub dhcp_rx: Option<crossbeam::channel::Receiver<dhcp::DhcpEvent>>,
+    pub dhcp_replies: Vec<dhcp::DhcpReply>,
+    pub dhcp_active: bool,
+    pub dhcp_error: Option<String>,
+    pub dhcp_inform: bool, // INFORM from our current address instead of DISCOVER
+    pub dhcp_interface_index: usize,
+    pub dhcp_table_state: TableState,
+    pub dhcp_selected: usize,
+
+
+    pub asn_input: Input,
+    pub asn_rx: Option<Receiver<Result<asn::AsnResult, String>>>,
+    pub asn_result: Option<asn::AsnResult>,
+    pub asn_error: Option<String>,
+    pub asn_table_state: TableState,
+    pub asn_selected: usize,
+
+    // Traceroute
+    pub trace_input: Input,
+    pub trace_rx: Option<crossbeam::channel::Receiver<Result<traceroute::TraceResult, String>>>,
+    pub trace_result: Option<traceroute::TraceResult>,
+    pub trace_active: bool,
+    pub trace_error: Option<String>,
+    pub trace_table_state: TableState,
+    pub trace_selected: usize,
+
+    // Ping Sweep
+    pub sweep_input: Input,
+    pub sweep_task: sweep::SweepTask,
+    pub sweep_rx: Option<crossbeam::channel::Receiver<sweep::SweepEvent>>,
+    pub sweep_hosts: Vec<sweep::SweepHost>, // Sorted by address
+    pub sweep_total: usize,
+    pub sweep_probed: usize,
+    pub sweep_active: bool,
+    pub sweep_error: Option<String>,
+    pub sweep_table_state: TableState,
+    pub sweep_selected: usize,
+
+    // Syslog listener
+    pub syslog_input: Input, // Port
+    pub syslog_filter_input: Input,
+    pub syslog_listener: syslog::SyslogListener,
+    pub syslog_rx: Option<crossbeam::channel::Receiver<syslog::SyslogEvent>>,
+    pub syslog_messages: Ring<syslog::SyslogMessage>, // Newest last
+    pub syslog_received: u64,
+    pub syslog_min_severity: u8, // Show messages at least this severe (7 = everything)
+    pub syslog_active: bool,
+    pub syslog_error: Option<String>,
+    pub syslog_table_state: TableState,
+    pub syslog_selected: usize,
+
+    // Internet health check
+    pub diagnose_rx: Option<crossbeam::channel::Receiver<diagnose::DiagnoseEvent>>,
+    pub diagnose_results: Vec<diagnose::CheckResult>,
+    pub diagnose_running: Option<diagnose::Check>,
+    pub diagnose_conclusion: Option<String>,
+    pub diagnose_active: bool,
+    pub inventory: Option<Inventory>,
+    pub inventory_error: Option<String>,
+    pub devices_table_state: TableState,
+    pub devices_selected: usize,
+
+    // Flow Collector (NetFlow/sFlow/IPFIX)
+    pub flows_input: Input,
+    pub flow_collector: flows::FlowCollector,
+    pub flows_rx: Option<crossbeam::channel::Receiver<flows::FlowEvent>>,
+    pub flows_snapshot: flows::FlowSnapshot,
+    pub flows_active: bool,
+    pub flows_error: Option<String>,
+    pub flows_rate_history: Ring<f64>, // Mbps
+    pub flows_fps_history: Ring<f64>,
 
     // ASN / Connections
-    pub geoip_reader: Option<geoip::GeoIpReader>,
+    pub geoip_reader: Option<Arc<geoip::GeoIpReader>>,
+    pub geoip_country: Option<Arc<geoip::GeoIpReader>>,
+    pub geoip_rx: Option<crossbeam::channel::Receiver<geoip::GeoIpEvent>>,
+    pub geoip_error: Option<String>,
     pub active_connections: HashMap<IpAddr, ConnectionInfo>,
-    pub connections_rx: Option<crossbeam::channel::Receiver<Vec<connections::RawConnection>>>,
+    pub connections_rx: Option<crossbeam::channel::Receiver<Vec<(connections::RawConnection, PeerInfo)>>>,
+    /// Cached lookups for the connections thread and the UI
+    pub geoip_lookups: GeoIpLookups,
     pub globe_rotation: f64,
+    pub connections_globe: bool,
+    /// Show peers by reverse-DNS name instead of IP
+    pub connections_hostnames: bool,
+    rdns: Option<rdns::ReverseResolver>,
+    rdns_rx: Option<crossbeam::channel::Receiver<(IpAddr, Option<String>)>>,
+    /// PTR name per peer IP; `None` while the lookup is pending or when there is none
+    pub hostnames: HashMap<IpAddr, Option<String>>,
+    reputation: Option<reputation::ReputationChecker>,
+    reputation_rx: Option<crossbeam::channel::Receiver<(IpAddr, reputation::Reputation)>>,
+    /// Threat feed verdicts per peer; `None` while the lookup is out
+    pub peer_reputation: HashMap<IpAddr, Option<reputation::Reputation>>,
+    pub reputation_error: Option<String>,
+    pub connections_table_state: TableState,
+    /// The highlighted peer. Rows re-sort every tick, so its row is looked up again each time.
+    pub connections_selected: Option<IpAddr>,
+    pub connections_sort: ConnectionSort,
+    /// Connections per TCP state at the last refresh
+    pub connection_states: BTreeMap<String, usize>,
+    pub connections_refreshed: std::time::Instant,
+    pub show_conn_actions: bool,
+    pub conn_action_selected: usize,
+    pub connections_view: ConnectionsView,
+    pub listeners: Vec<listeners::ListeningSocket>,
+    pub listeners_rx: Option<crossbeam::channel::Receiver<Result<Vec<listeners::ListeningSocket>, String>>>,
+    pub listeners_error: Option<String>,
+    pub listeners_table_state: TableState,
+    pub listeners_selected: usize,
+    pub talkers: TalkerStats,
+    pub talkers_window: TalkerWindow,
+    pub talkers_table_state: TableState,
+    pub talkers_selected: usize,
+    pub confirm_kill: Option<KillTarget>,
+
+    // WHOIS popup (from a Connections row)
+    pub show_whois: bool,
+    pub whois_target: String,
+    pub whois_result: Option<Result<String, String>>, // None while running
+    pub whois_rx: Option<crossbeam::channel::Receiver<Result<String, String>>>,
+    pub whois_scroll: u16,
 
     // Dashboard Graph
-    pub traffic_history: VecDeque<u64>,
-    pub rx_history: VecDeque<u64>,
-    pub tx_history: VecDeque<u64>,
+    pub traffic_history: Ring<u64>,
+    pub rx_history: Ring<u64>,
+    pub tx_history: Ring<u64>,
     pub last_packet_count: u64,
+    // Per-tick packets as [tcp, udp, icmp, other]
+    pub protocol_history: Ring<[u64; 4]>,
+    pub last_protocol_counts: [u64; 4],
     pub last_rx_count: u64,
     pub last_tx_count: u64,
 
     // Bandwidth History (Mbps - f64)
-    pub wan_rx_history: VecDeque<f64>,
-    pub wan_tx_history: VecDeque<f64>,
-    pub lan_rx_history: VecDeque<f64>,
-    pub lan_tx_history: VecDeque<f64>,
+    pub wan_rx_history: Ring<f64>,
+    pub wan_tx_history: Ring<f64>,
+    pub lan_rx_history: Ring<f64>,
+    pub lan_tx_history: Ring<f64>,
 
     pub last_wan_rx_bytes: u64,
     pub last_wan_tx_bytes: u64,
@@ -117,29 +557,143 @@ pub struct App {
     pub last_lan_tx_bytes: u64,
 
     // Protocol History (PPS)
-    pub connection_count_history: VecDeque<u64>,
+    pub connection_count_history: Ring<u64>,
 
     pub last_tick_time: std::time::Instant,
+    /// Dashboard bandwidth comes from interface counters rather than the sniffer
+    pub bandwidth_from_os: bool,
+
+    // Long-term dashboard series (zoomable)
+    pub dash_range: TimeRange,
+    pub dash_wan_rx: TieredSeries,
+    pub dash_wan_tx: TieredSeries,
+    pub dash_conns: TieredSeries,
+    pub dash_lan_rx: TieredSeries,
+    pub dash_lan_tx: TieredSeries,
+    pub dash_pps: TieredSeries,
+    // Dashboard layout editor
+    pub show_layout: bool,
+    pub layout_cursor: LayoutCursor,
+    pub layout_status: Option<String>,
+
+    // Dashboard background pings, one per `[dashboard] ping_targets` entry
+    pub dash_probes: Vec<LatencyProbe>,
+    pub show_probe_targets: bool,
+    pub probe_targets_input: Input,
+    pub probe_targets_status: Option<String>,
+    // Gateway panel: default route, its own ping and the public address
+    pub gateway_info: Option<gateway::GatewayInfo>,
+    pub gateway_rx: Option<crossbeam::channel::Receiver<gateway::GatewayInfo>>,
+    pub gateway_probe: Option<LatencyProbe>,
+
+    // Config & Prometheus exporter
+    pub config: Config,
+    /// A theme chosen from the palette (or restored), which the session keeps
+    pub theme_picked: bool,
+    pub metrics: Option<SharedMetrics>,
+    pub metrics_error: Option<String>,
+
+    // API server
+    pub api_rx: Option<crossbeam::channel::Receiver<ApiRequest>>,
+    pub api_events: Option<ApiEvents>,
+    pub api_error: Option<String>,
+
+    // Alerting
+    pub alert_engine: AlertEngine,
+    /// The metric conditions among `[capture] triggers`
+    trigger_engine: AlertEngine,
+    /// When a triggered recording stops, unless a trigger fires again
+    pub trigger_until: Option<std::time::Instant>,
+    /// The trigger started the capture too, so it stops with the recording
+    trigger_started_sniffer: bool,
+    pub alert_log: Ring<String>,
+    pub scheduler: Scheduler,
+    pub show_alerts: bool,
+    pub alerts_input: Input,
+    pub alerts_selected: usize,
+    pub alerts_status: Option<String>,
+
+    // Scan profiles popup
+    pub show_profiles: bool,
+    pub profiles_input: Input, // Name to save the current tool's input under
+    pub profiles_selected: usize,
+    pub profiles_status: Option<String>,
+
+    // Result history (SQLite)
+    pub history: Option<HistoryStore>,
+    pub input_history: HashMap<CurrentScreen, InputHistory>, // Up/Down recall on the target inputs
+    hosts_file: Vec<String>, // /etc/hosts names, for autocomplete
+    pub history_error: Option<String>,
+    pub history_entries: Vec<HistoryEntry>,
+    pub history_input: Input,
+    pub history_table_state: TableState,
+    pub history_selected: usize,
+    pub history_compare: Option<HistoryEntry>,
+    pub history_trend: Vec<(i64, f64)>,
+    /// Open-port changes from the marked scan to the selected one, when both are scans
+    pub history_scan_diff: Option<Vec<(nmap::PortChange, nmap::ScanPort)>>,
+    pub ping_stats: PingStats,
+    pub ping_heatmap: LatencyHeatmap,
+    /// The heatmap in place of the RTT chart
+    pub ping_heatmap_view: bool,
+    pub ping_target: String,
+    pub dns_query: String,
+    pub nmap_target: String,
+    pub mtr_target: String,
+
+    // Export popup
+    pub show_export: bool,
+    pub export_input: Input,
+    pub export_status: Option<Result<String, String>>,
 
-    // Dashboard Background Ping
-    pub db_ping_history: VecDeque<u64>,
-    pub db_jitter_history: VecDeque<u64>,
-    pub db_ping_rx: Option<tokio::sync::mpsc::Receiver<Result<PingResult, String>>>,
+    // Command palette
+    pub show_palette: bool,
+    pub palette_input: Input,
+    pub palette_matches: Vec<PaletteEntry>,
+    pub palette_selected: usize,
+
+    pub keymap: Keymap,
+
+    // Yank to clipboard
+    pub clipboard: Clipboard,
+    // Short-lived footer message (clipboard, kill results)
+    pub flash: Option<(Result<String, String>, std::time::Instant)>,
+
+    // `/` table search
+    pub search: Option<TableSearch>,
+
+    // "finished (N results)" for tools that ended on their own; cleared when they start again
+    pub finished: HashMap<CurrentScreen, String>,
+
+    // Event log pane; transitions are remembered so each is logged once
+    pub event_log: EventLog,
+    pub show_log: bool,
+    logged_active: HashMap<CurrentScreen, bool>,
+    logged_errors: HashMap<&'static str, String>,
 }
 
 impl App {
-    pub fn new() -> App {
+    pub fn new(config: Config, keymap: Keymap, event_log: EventLog) -> App {
+        let retention = config.retention.clone();
+        command::install(&config.plugins);
         App {
             current_screen: CurrentScreen::Dashboard,
+            split_screen: None,
+            focus: HashMap::new(),
+            split_focus_left: true,
             should_quit: false,
-            show_help: false,
-            show_options: false,
-            options_scroll: 0,
+            help: None,
+            flag_editor: None,
             interfaces: interfaces::get_interfaces(),
+            privileges: Privileges::detect(),
+            iface_stats_rx: None,
+            iface_history: HashMap::new(),
+            dash_iface_selected: 0,
+            iface_detail: None,
             
             ping_input: Input::default(),
-            ping_history: VecDeque::with_capacity(50),
-            ping_rtt_history: VecDeque::with_capacity(100),
+            ping_history: Ring::new(retention.ping),
+            ping_rtt_history: Ring::new(retention.ping),
             ping_rx: None,
             is_pinging: false,
 
@@ -147,67 +701,344 @@ impl App {
             dns_record_type: RecordType::A,
             dns_result: None,
             dns_rx: None,
+            dns_watch: None,
+            dns_mail: None,
+            dns_mail_rx: None,
+            dns_blocklist: None,
+            dns_blocklist_rx: None,
+            dns_subdomains: None,
+            dns_subdomains_rx: None,
 
             sniffer: sniffer::Sniffer::new(),
-            sniffer_rx: None,
-            sniffer_packets: VecDeque::with_capacity(1000),
+            sniffer_ingest: None,
+            sniffer_packets: Ring::new(retention.packets),
             sniffer_active: false,
+            sniffer_error: None,
             sniffer_filter_input: Input::default(),
+            filter_presets: None,
             selected_interface_index: 0,
+            sniffer_capture: Vec::new(),
+            sniffer_table_state: TableState::default(),
+            sniffer_selected: 0,
+            sniffer_seen: 0,
+            sniffer_icmp: HashMap::new(),
+            sniffer_evicted: 0,
+            sniffer_paused_at: None,
+            sniffer_autoscroll: true,
 
             mtr_input: Input::default(),
             mtr_task: mtr::MtrTask::new(),
             mtr_rx: None,
             mtr_hops: Vec::new(),
             mtr_active: false,
+            mtr_error: None,
             mtr_selected_hop: 0,
+            mtr_editing: false,
+            mtr_paths: mtr::PathTracker::default(),
+            mtr_flows: mtr::FlowPaths::default(),
+            show_mtr_paths: false,
+            mtr_paths_scroll: 0,
             mtr_table_state: TableState::default(),
 
             nmap_input: Input::default(),
             nmap_active: false,
+            nmap_error: None,
             nmap_rx: None,
-            nmap_output: VecDeque::with_capacity(1000),
+            nmap_process: None,
+            nmap_output: Ring::new(retention.scan_output),
             nmap_scroll: 0,
+            nmap_list_state: ListState::default(),
+            nmap_editing: false,
+            nmap_ports: Vec::new(),
+            nmap_grab_banners: false,
+            nmap_banner_rx: None,
+            nmap_banners: HashMap::new(),
+            nmap_ports_table_state: TableState::default(),
 
 
 
+            tools: ToolRegistry::new(&config),
             arpscan_input: Input::default(),
             arpscan_active: false,
+            arpscan_error: None,
             arpscan_rx: None,
-            arpscan_output: VecDeque::with_capacity(100), // Keep for logs
+            arpscan_process: None,
+            arpscan_output: Ring::new(retention.scan_output), // Keep for logs
             arpscan_results: Vec::new(), // Structured data
+            arpwatch_task: None,
+            arpwatch_rx: None,
+            arpwatch_every: None,
+            arpwatch_sweeps: 0,
+            arp_names: HashMap::new(),
+            arp_namer: None,
+            arp_names_rx: None,
             arpscan_scroll: 0,
+            arpscan_table_state: TableState::default(),
+            arpscan_selected: 0,
+            arp_view: ArpView::default(),
+            neighbors: neighbors::NeighborTable::default(),
+            neighbors_rx: None,
+            neighbors_error: None,
+            neighbors_table_state: TableState::default(),
+            neighbors_selected: 0,
+
+            show_wol: false,
+            wol_input: Input::default(),
+            wol_interface_index: 0,
+            wol_status: None,
+            oui: None,
+            oui_rx: None,
+            oui_error: None,
+            show_oui_lookup: false,
+            oui_input: Input::default(),
+
+            mdns_task: mdns::MdnsTask::new(),
+            mdns_rx: None,
+            mdns_services: Vec::new(),
+            mdns_active: false,
+            mdns_error: None,
+            mdns_table_state: TableState::default(),
+            mdns_selected: 0,
+
+            ssdp_task: ssdp::SsdpTask::new(),
+            ssdp_rx: None,
+            ssdp_devices: Vec::new(),
+            ssdp_active: false,
+            ssdp_error: None,
+            ssdp_table_state: TableState::default(),
+            ssdp_selected: 0,
+
+            wifi_task: wifi::WifiTask::new(),
+            wifi_rx: None,
+            wifi_interface: None,
+            wifi_networks: Vec::new(),
+            wifi_link: None,
+            wifi_rssi_history: Ring::new(120),
+            wifi_active: false,
+            wifi_error: None,
+            wifi_table_state: TableState::default(),
+            wifi_selected: 0,
+
+            dhcp_rx: None,
+            dhcp_replies: Vec::new(),
+            dhcp_active: false,
+            dhcp_error: None,
+            dhcp_inform: false,
+            // Start on the first interface a DHCP client could be running on
+            dhcp_interface_index: interfaces::get_interfaces().iter()
+                .position(|i| i.is_up() && !i.is_loopback() && i.mac.is_some())
+                .unwrap_or(0),
+            dhcp_table_state: TableState::default(),
+            dhcp_selected: 0,
+
+
+            asn_input: Input::default(),
+            asn_rx: None,
+            asn_result: None,
+            asn_error: None,
+            asn_table_state: TableState::default(),
+            asn_selected: 0,
+
+            trace_input: Input::default(),
+            trace_rx: None,
+            trace_result: None,
+            trace_active: false,
+            trace_error: None,
+            trace_table_state: TableState::default(),
+            trace_selected: 0,
+
+            sweep_input: Input::new(sweep::default_range(&interfaces::get_interfaces()).unwrap_or_default()),
+            sweep_task: sweep::SweepTask::new(),
+            sweep_rx: None,
+            sweep_hosts: Vec::new(),
+            sweep_total: 0,
+            sweep_probed: 0,
+            sweep_active: false,
+            sweep_error: None,
+            sweep_table_state: TableState::default(),
+            sweep_selected: 0,
+
+            syslog_input: Input::new(syslog::DEFAULT_SYSLOG_PORT.to_string()),
+            syslog_filter_input: Input::default(),
+            syslog_listener: syslog::SyslogListener::new(),
+            syslog_rx: None,
+            syslog_messages: Ring::new(retention.syslog),
+            syslog_received: 0,
+            syslog_min_severity: 7,
+            syslog_active: false,
+            syslog_error: None,
+            syslog_table_state: TableState::default(),
+            syslog_selected: 0,
+            diagnose_rx: None,
+            diagnose_results: Vec::new(),
+            diagnose_running: None,
+            diagnose_conclusion: None,
+            diagnose_active: false,
+            inventory: None,
+            inventory_error: None,
+            devices_table_state: TableState::default(),
+            devices_selected: 0,
+
+            flows_input: Input::new(flows::DEFAULT_FLOW_PORT.to_string()),
+            flow_collector: flows::FlowCollector::new(),
+            flows_rx: None,
+            flows_snapshot: flows::FlowSnapshot::default(),
+            flows_active: false,
+            flows_error: None,
+            flows_rate_history: Ring::filled(HISTORY, 0.0),
+            flows_fps_history: Ring::filled(HISTORY, 0.0),
 
-            geoip_reader: geoip::GeoIpReader::new(include_bytes!("../GeoLite2-ASN_20251224/GeoLite2-ASN.mmdb")).ok(),
+            geoip_reader: None,
+            geoip_country: None,
+            geoip_rx: None,
+            geoip_error: None,
             active_connections: HashMap::new(),
             connections_rx: None,
+            geoip_lookups: GeoIpLookups::start(),
             globe_rotation: 0.0,
+            connections_globe: false,
+            connections_hostnames: false,
+            rdns: None,
+            rdns_rx: None,
+            hostnames: HashMap::new(),
+            reputation: None,
+            reputation_rx: None,
+            peer_reputation: HashMap::new(),
+            reputation_error: None,
+            connections_table_state: TableState::default(),
+            connections_selected: None,
+            connections_sort: ConnectionSort::default(),
+            connection_states: BTreeMap::new(),
+            connections_refreshed: std::time::Instant::now(),
+            show_conn_actions: false,
+            conn_action_selected: 0,
+            connections_view: ConnectionsView::default(),
+            listeners: Vec::new(),
+            listeners_rx: None,
+            listeners_error: None,
+            listeners_table_state: TableState::default(),
+            listeners_selected: 0,
+            talkers: TalkerStats::default(),
+            talkers_window: TalkerWindow::default(),
+            talkers_table_state: TableState::default(),
+            talkers_selected: 0,
+            confirm_kill: None,
 
-            traffic_history: VecDeque::from(vec![0; 100]), 
-            rx_history: VecDeque::from(vec![0; 100]),
-            tx_history: VecDeque::from(vec![0; 100]),
+            show_whois: false,
+            whois_target: String::new(),
+            whois_result: None,
+            whois_rx: None,
+            whois_scroll: 0,
+
+            traffic_history: Ring::filled(HISTORY, 0),
+            rx_history: Ring::filled(HISTORY, 0),
+            tx_history: Ring::filled(HISTORY, 0),
             last_packet_count: 0,
+            protocol_history: Ring::filled(HISTORY, [0; 4]),
+            last_protocol_counts: [0; 4],
             last_rx_count: 0,
             last_tx_count: 0,
 
-            wan_rx_history: VecDeque::from(vec![0.0; 100]),
-            wan_tx_history: VecDeque::from(vec![0.0; 100]),
-            lan_rx_history: VecDeque::from(vec![0.0; 100]),
-            lan_tx_history: VecDeque::from(vec![0.0; 100]),
+            wan_rx_history: Ring::filled(HISTORY, 0.0),
+            wan_tx_history: Ring::filled(HISTORY, 0.0),
+            lan_rx_history: Ring::filled(HISTORY, 0.0),
+            lan_tx_history: Ring::filled(HISTORY, 0.0),
 
             last_wan_rx_bytes: 0,
             last_wan_tx_bytes: 0,
             last_lan_rx_bytes: 0,
             last_lan_tx_bytes: 0,
 
-            connection_count_history: VecDeque::from(vec![0; 100]),
+            connection_count_history: Ring::filled(HISTORY, 0),
 
 
             last_tick_time: std::time::Instant::now(),
+            bandwidth_from_os: true,
             
-            db_ping_history: VecDeque::from(vec![0; 100]),
-            db_jitter_history: VecDeque::from(vec![0; 100]),
-            db_ping_rx: None,
+            dash_range: TimeRange::Live,
+            dash_wan_rx: TieredSeries::new(),
+            dash_wan_tx: TieredSeries::new(),
+            dash_conns: TieredSeries::new(),
+            dash_lan_rx: TieredSeries::new(),
+            dash_lan_tx: TieredSeries::new(),
+            dash_pps: TieredSeries::new(),
+            show_layout: false,
+            layout_cursor: LayoutCursor::default(),
+            layout_status: None,
+
+            dash_probes: vec![],
+            show_probe_targets: false,
+            probe_targets_input: Input::default(),
+            probe_targets_status: None,
+            gateway_info: None,
+            gateway_rx: None,
+            gateway_probe: None,
+
+            config,
+            theme_picked: false,
+            metrics: None,
+            metrics_error: None,
+            api_rx: None,
+            api_events: None,
+            api_error: None,
+
+            alert_engine: AlertEngine::default(),
+            trigger_engine: AlertEngine::default(),
+            trigger_until: None,
+            trigger_started_sniffer: false,
+            alert_log: Ring::new(retention.alerts),
+            scheduler: Scheduler::new(),
+            show_alerts: false,
+            alerts_input: Input::default(),
+            alerts_selected: 0,
+            alerts_status: None,
+
+            show_profiles: false,
+            profiles_input: Input::default(),
+            profiles_selected: 0,
+            profiles_status: None,
+
+            history: None,
+            input_history: HashMap::new(),
+            hosts_file: completion::hosts_file(),
+            history_error: None,
+            history_entries: Vec::new(),
+            history_input: Input::default(),
+            history_table_state: TableState::default(),
+            history_selected: 0,
+            history_compare: None,
+            history_trend: Vec::new(),
+            history_scan_diff: None,
+            ping_stats: PingStats::default(),
+            ping_heatmap: LatencyHeatmap::default(),
+            ping_heatmap_view: false,
+            ping_target: String::new(),
+            dns_query: String::new(),
+            nmap_target: String::new(),
+            mtr_target: String::new(),
+
+            show_export: false,
+            export_input: Input::default(),
+            export_status: None,
+
+            show_palette: false,
+            palette_input: Input::default(),
+            palette_matches: Vec::new(),
+            palette_selected: 0,
+
+            keymap,
+
+            clipboard: Clipboard::default(),
+            flash: None,
+
+            search: None,
+
+            finished: HashMap::new(),
+
+            event_log,
+            show_log: false,
+            logged_active: HashMap::new(),
+            logged_errors: HashMap::new(),
         }
     }
 
@@ -216,102 +1047,271 @@ impl App {
             loop {
                 match rx.try_recv() {
                     Ok(result) => {
+                         api::emit(&self.api_events, "ping", match &result {
+                             Ok(r) => json!({ "seq": r.seq, "target": r.target, "probe": r.probe.label(), "ttl": r.ttl, "time_ms": r.time.as_secs_f64() * 1000.0 }),
+                             Err(e) => json!({ "error": e }),
+                         });
+                         self.ping_stats.record(&result);
+                         self.ping_heatmap.record(&result);
                          if let Ok(ref res) = result {
-                             self.ping_rtt_history.push_back(res.time.as_secs_f64() * 1000.0);
-                             if self.ping_rtt_history.len() > 100 {
-                                 self.ping_rtt_history.pop_front();
-                             }
-                         }
-                         if result.is_ok() {
-                             self.ping_history.push_back(result.clone());
-                         } else {
-                              self.ping_history.push_back(result);
-                         }
-                         
-                         if self.ping_history.len() > 50 {
-                             self.ping_history.pop_front();
-                         }
-                         #[cfg(debug_assertions)]
-                         {
-                            if self.ping_history.len() > 50 {
-                                eprintln!("Ping history exceeded 50 items despite pop");
-                            }
+                             self.ping_rtt_history.push(res.time.as_secs_f64() * 1000.0);
                          }
+                         self.ping_history.push(result);
                     }
                     Err(TryRecvError::Empty) => break,
                     Err(TryRecvError::Disconnected) => {
+                        // Sent all `-c` pings (or failed before the first, which the list shows)
+                        if self.ping_stats.sent > 0 {
+                            let note = format!("finished ({} of {} replies)", self.ping_stats.recv, self.ping_stats.sent);
+                            self.finished.insert(CurrentScreen::Ping, note);
+                        } else {
+                            self.finished.remove(&CurrentScreen::Ping);
+                        }
                         self.is_pinging = false;
                         self.ping_rx = None;
+                        self.finish_ping_session();
                         break;
                     }
                 }
             }
         }
         
-        if let Some(rx) = &mut self.dns_rx {
-            if let Ok(result) = rx.try_recv() {
-               self.dns_result = Some(result);
-               self.dns_rx = None; // One-shot
+        if let Some(rx) = &mut self.dns_rx
+            && let Ok((result, elapsed)) = rx.try_recv()
+        {
+            let query = self.dns_query.clone();
+            api::emit(&self.api_events, "dns", match &result {
+                Ok(res) => {
+                    let records: Vec<Value> = res.records().1.iter().map(|r| json!({ "value": r.value, "ttl": r.ttl })).collect();
+                    json!({ "query": query, "records": records })
+                }
+                Err(e) => json!({ "query": query, "error": e }),
+            });
+            self.dns_rx = None;
+            match self.dns_watch.as_mut() {
+                // Only the first answer and changes go to the history, not every repeat
+                Some(watch) => {
+                    let first = watch.queries == 0;
+                    if let Some(change) = watch.record(&result, elapsed) {
+                        self.raise_event_alert(Alert {
+                            rule: "dns change".to_string(),
+                            message: format!("DNS answer for {} changed: {}", query, change),
+                            since: alerts::timestamp(),
+                        });
+                        self.record_dns_result(&query, &result);
+                    } else if first {
+                        self.record_dns_result(&query, &result);
+                    }
+                }
+                None => self.record_dns_result(&query, &result),
+            }
+            // A failed repeat leaves the last answer on screen; the failure count shows it
+            if result.is_ok() || self.dns_watch.is_none() || self.dns_result.is_none() {
+                self.dns_result = Some(result);
+            }
+        }
+        if let Some(rx) = &mut self.dns_mail_rx
+            && let Ok(check) = rx.try_recv()
+        {
+            self.dns_mail = Some(check);
+            self.dns_mail_rx = None;
+        }
+        if let Some(rx) = &mut self.asn_rx
+            && let Ok(result) = rx.try_recv()
+        {
+            match result {
+                Ok(result) => self.asn_result = Some(result),
+                Err(e) => self.asn_error = Some(e),
+            }
+            self.asn_rx = None;
+        }
+        if let (Some(rx), Some(check)) = (&mut self.dns_blocklist_rx, &mut self.dns_blocklist) {
+            while let Ok(event) = rx.try_recv() {
+                check.apply(event);
+            }
+            if check.done() {
+                self.dns_blocklist_rx = None;
+            }
+        }
+        if let (Some(rx), Some(scan)) = (&self.dns_subdomains_rx, &mut self.dns_subdomains) {
+            for event in rx.try_iter() {
+                scan.apply(event);
             }
+            if scan.done {
+                self.dns_subdomains_rx = None;
+            }
+        }
+        if self.dns_rx.is_none()
+            && let Some(watch) = &self.dns_watch
+            && watch.due()
+        {
+            let (domain, record_type) = (watch.domain.clone(), watch.record_type);
+            self.query_dns(domain, record_type);
         }
 
-        if let Some(rx) = &self.sniffer_rx {
-             while let Ok(packet) = rx.try_recv() {
-                 self.sniffer_packets.push_back(packet.clone());
-                 
-                // (Connection tracking moved to dedicated netstat task)
+        // Packets were counted and summed on the ingest thread; this only applies the totals
+        let batch = self.sniffer_ingest.as_ref().map(|i| i.take()).unwrap_or_default();
+        if let Some(e) = batch.error {
+            self.sniffer_error = Some(e);
+        }
+        for (trigger, packet) in batch.triggers {
+            self.fire_capture_trigger(&trigger, &packet);
+        }
+        for message in batch.arp_alerts {
+            self.raise_event_alert(Alert {
+                rule: "arp spoofing".to_string(),
+                message,
+                since: alerts::timestamp(),
+            });
+        }
+        for hello in batch.dhcp {
+            let mac = hello.mac.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":");
+            self.see_device(&mac, hello.ip.map(IpAddr::V4), hello.hostname.clone());
+        }
+        // Netstat decides which connections exist; sniffed packets only add traffic counters
+        for (ip, (count, bytes)) in batch.traffic {
+            if let Some(conn) = self.active_connections.get_mut(&ip) {
+                conn.packet_count += count;
+                conn.bytes += bytes;
+                conn.last_seen = std::time::Instant::now();
+                self.talkers.record(conn.remote_ip, bytes, &conn.asn_org, conn.country.as_deref());
+            }
+        }
+        self.sniffer_seen += batch.seen;
+        self.sniffer_evicted += batch.dropped;
+        for (kind, count) in batch.icmp {
+            *self.sniffer_icmp.entry(kind).or_default() += count;
+        }
+        let packets = batch.packets;
+        // Every interface failed to open (or lost its capture)
+        if self.sniffer_active && !self.sniffer.running() {
+            self.sniffer_active = false;
+        }
+        if !packets.is_empty() && !self.sniffer_autoscroll && self.sniffer_paused_at.is_none()
+            && self.sniffer_table_state.selected().is_some()
+        {
+            // Keep the selected row (and everything around it) in place as new packets land on top
+            self.sniffer_selected += packets.len();
+            *self.sniffer_table_state.offset_mut() += packets.len();
+            self.sniffer_table_state.select(Some(self.sniffer_selected));
+        }
+        for packet in packets {
+            if self.sniffer_packets.push(packet).is_some() {
+                self.sniffer_evicted += 1;
+            }
+        }
+        self.talkers.prune();
+        let view_len = self.sniffer_view().count();
+        if self.sniffer_selected >= view_len && self.sniffer_table_state.selected().is_some() {
+            self.sniffer_selected = view_len.saturating_sub(1);
+            self.sniffer_table_state.select((view_len > 0).then_some(self.sniffer_selected));
+        }
 
-                if self.sniffer_packets.len() > 1000 {
-                    self.sniffer_packets.pop_front();
+        // Interface counter samples -> per-interface rates
+        let samples: Vec<_> = self.iface_stats_rx.as_ref().map(|rx| rx.try_iter().collect()).unwrap_or_default();
+        for sample in samples {
+            self.record_interface_counters(sample);
+        }
+
+        // Swap in (re)loaded GeoIP databases; peers pick them up on the next netstat refresh
+        let geoip_events: Vec<_> = self.geoip_rx.as_ref().map(|rx| rx.try_iter().collect()).unwrap_or_default();
+        for event in geoip_events {
+            match event.result {
+                Ok(reader) => {
+                    let reader = Arc::new(reader);
+                    self.geoip_lookups.set_reader(reader.clone(), event.edition == geoip::COUNTRY_EDITION);
+                    let slot = if event.edition == geoip::COUNTRY_EDITION { &mut self.geoip_country } else { &mut self.geoip_reader };
+                    if slot.is_some() {
+                        self.flash = Some((Ok(format!("Reloaded {} database", event.edition)), std::time::Instant::now()));
+                    }
+                    *slot = Some(reader);
+                    self.geoip_error = None;
+                }
+                Err(e) => {
+                    self.flash = Some((Err(e.clone()), std::time::Instant::now()));
+                    self.geoip_error = Some(e);
                 }
-                debug_assert!(self.sniffer_packets.len() <= 1000, "Sniffer packet history exceeded limit");
             }
         }
 
+        if let Some(rx) = &self.rdns_rx {
+            self.hostnames.extend(rx.try_iter());
+        }
+        let named: Vec<_> = self.arp_names_rx.as_ref().map(|rx| rx.try_iter().collect()).unwrap_or_default();
+        for (ip, name) in named {
+            if let (Some(name), Some(inventory)) = (&name, &mut self.inventory)
+                && let Err(e) = inventory.name_ip(ip, &name.name)
+            {
+                self.inventory_error = Some(format!("Inventory: {:#}", e));
+            }
+            self.arp_names.insert(ip, name);
+        }
+        let verdicts: Vec<_> = self.reputation_rx.as_ref().map(|rx| rx.try_iter().collect()).unwrap_or_default();
+        for (ip, verdict) in verdicts {
+            self.apply_reputation(ip, verdict);
+        }
+
         // Handle Netstat connections
         if let Some(rx) = &self.connections_rx {
              if let Ok(conns) = rx.try_recv() {
-                 let mut new_map = HashMap::new();
+                 let mut new_map: HashMap<IpAddr, ConnectionInfo> = HashMap::new();
                  
-                 for c in conns {
-                     let clean_remote = c.remote_addr.replace(":", "."); 
-                     let parts: Vec<&str> = clean_remote.rsplitn(2, '.').collect();
-                     
-                     if parts.len() == 2 {
-                         let ip_str = parts[1];
-                         if let Ok(ip) = ip_str.parse::<IpAddr>() {
-                             if !ip.is_loopback() && !ip.is_unspecified() {
-                                 let (asn_num, asn_org, location) = if let Some(existing) = self.active_connections.get(&ip) {
-                                     (existing.asn_num, existing.asn_org.clone(), existing.location)
-                                 } else {
-                                      if let Some(reader) = &self.geoip_reader {
-                                         reader.lookup_info(ip).unwrap_or((0, "Unknown".to_string(), None))
-                                     } else {
-                                         (0, "-".to_string(), None)
-                                     }
-                                 };
-                                 
-                                 new_map.insert(ip, ConnectionInfo {
-                                     remote_ip: ip,
-                                     asn_num,
-                                     asn_org,
-                                     last_seen: std::time::Instant::now(),
-                                     packet_count: 0, 
-                                     protocol: c.protocol,
-                                     location,
-                                 });
-                             }
+                 let mut states: BTreeMap<String, usize> = BTreeMap::new();
+                 for (c, peer) in conns {
+                     let Some(ip) = c.remote_ip else { continue };
+                     if ip.is_loopback() || ip.is_unspecified() {
+                         continue;
+                     }
+                     if !c.state.is_empty() {
+                         *states.entry(c.state.clone()).or_default() += 1;
+                     }
+                     if let Some(existing) = new_map.get_mut(&ip) {
+                         existing.sockets += 1;
+                         // Show an established connection over ones closing or opening
+                         if existing.state != "ESTABLISHED" && c.state == "ESTABLISHED" {
+                             (existing.local_port, existing.remote_port, existing.state) = (c.local_port, c.remote_port, c.state);
                          }
+                         continue;
                      }
+                     let PeerInfo { asn_num, asn_org, location, country } = peer;
+                     // Carry traffic counters over from the previous refresh
+                     let (last_seen, packet_count, bytes, rate_mark, mut rate_history) = self.active_connections.remove(&ip)
+                         .map(|e| (e.last_seen, e.packet_count, e.bytes, e.rate_mark, e.rate_history))
+                         .unwrap_or_else(|| (std::time::Instant::now(), 0, 0, 0, Ring::new(RATE_HISTORY)));
+                     let elapsed = self.connections_refreshed.elapsed().as_secs_f64().max(0.001);
+                     let rate_bps = (bytes - rate_mark) as f64 / elapsed;
+                     rate_history.push(rate_bps);
+                     
+                     new_map.insert(ip, ConnectionInfo {
+                         remote_ip: ip,
+                         asn_num,
+                         asn_org,
+                         last_seen,
+                         packet_count,
+                         bytes,
+                         rate_bps,
+                         rate_mark: bytes,
+                         rate_history,
+                         protocol: c.protocol,
+                         local_port: c.local_port,
+                         remote_port: c.remote_port,
+                         state: c.state,
+                         sockets: 1,
+                         location,
+                         country,
+                         pid: c.pid,
+                         process: c.process,
+                     });
                  }
                  self.active_connections = new_map;
+                 self.connection_states = states;
+                 self.resolve_peer_names();
+                 self.check_peer_reputation();
+                 self.connections_refreshed = std::time::Instant::now();
                  
                  // Update history
-                 self.connection_count_history.push_back(self.active_connections.len() as u64);
-                 if self.connection_count_history.len() > 100 {
-                     self.connection_count_history.pop_front();
-                 }
-                 debug_assert!(self.connection_count_history.len() <= 100, "Connection count history exceeded limit");
+                 self.connection_count_history.push(self.active_connections.len() as u64);
+                 self.dash_conns.push(self.active_connections.len() as f64);
              }
         }
         
@@ -323,13 +1323,57 @@ impl App {
         debug_assert!(self.globe_rotation >= 0.0, "Globe rotation should differ from negative");
         debug_assert!(self.globe_rotation < std::f64::consts::PI * 4.0, "Globe rotation growing unbounded");
 
+        let mut mtr_finished = None;
         if let Some(rx) = &self.mtr_rx {
-            while let Ok(res) = rx.try_recv() {
+            while let Ok(event) = rx.try_recv() {
+                let res = match event {
+                    mtr::MtrEvent::Hop(res) => res,
+                    mtr::MtrEvent::Error(e) => {
+                        api::emit(&self.api_events, "mtr", json!({ "error": e }));
+                        self.mtr_error = Some(e);
+                        self.mtr_active = false;
+                        continue;
+                    }
+                    mtr::MtrEvent::Finished { cycles } => {
+                        api::emit(&self.api_events, "mtr", json!({ "finished": true, "cycles": cycles }));
+                        mtr_finished = Some(cycles);
+                        continue;
+                    }
+                    mtr::MtrEvent::CycleDone => {
+                        if let Some(change) = self.mtr_paths.end_cycle(alerts::timestamp())
+                            && !change.ttls.is_empty()
+                        {
+                            for ttl in &change.ttls {
+                                if let Some(hop) = self.mtr_hops.get_mut(*ttl as usize - 1) {
+                                    hop.changes += 1;
+                                }
+                            }
+                            let ttls: Vec<String> = change.ttls.iter().map(|t| t.to_string()).collect();
+                            api::emit(&self.api_events, "mtr", json!({ "path_change": change.path + 1, "cycle": change.cycle, "ttls": change.ttls }));
+                            tracing::info!("MTR to {}: path changed at TTL {} (path #{})", self.mtr_target, ttls.join(", "), change.path + 1);
+                        }
+                        continue;
+                    }
+                };
+                self.mtr_paths.record(&res);
+                api::emit(&self.api_events, "mtr", json!({
+                    "ttl": res.ttl,
+                    "host": res.host.map(|h| h.to_string()),
+                    "ok": res.successful,
+                    "time_ms": res.rtt.as_secs_f64() * 1000.0,
+                    "flow": res.flow,
+                }));
+                self.mtr_flows.record(&res);
+                // The table and path changes follow the first flow; the others only map paths
+                if res.flow != 0 {
+                    continue;
+                }
                 // Update hop stats
                 // Check if we have an entry for this TTL
-                 if self.mtr_hops.len() < res.ttl as usize {
-                    self.mtr_hops.resize(res.ttl as usize, mtr::HopStats {
-                        ttl: res.ttl,
+                 // Hops skipped by -f stay as unprobed rows, so the table still lines up by TTL
+                 while self.mtr_hops.len() < res.ttl as usize {
+                    self.mtr_hops.push(mtr::HopStats {
+                        ttl: self.mtr_hops.len() as u8 + 1,
                         host: "???".to_string(),
                         sent: 0,
                         recv: 0,
@@ -338,8 +1382,10 @@ impl App {
                         worst: 0,
                         avg: 0,
                         loss: 0.0,
-                        history: VecDeque::new(),
+                        history: Ring::new(self.config.retention.hop_history),
                         jitter: 0,
+                        rtt: Histogram::default(),
+                        changes: 0,
                     });
                 }
                 
@@ -366,53 +1412,162 @@ impl App {
                         if time > hop.worst { hop.worst = time; }
                         hop.avg = ((hop.avg * (hop.recv - 1)) + time) / hop.recv;
                         
-                        hop.history.push_back(time);
-                        if hop.history.len() > 100 {
-                            hop.history.pop_front();
-                        }
+                        hop.rtt.record(res.rtt.as_secs_f64() * 1000.0);
+                        hop.history.push(time);
                     }
                     hop.loss = ((hop.sent - hop.recv) as f64 / hop.sent as f64) * 100.0;
                 }
             }
         }
-        if let Some(rx) = &mut self.db_ping_rx {
-             while let Ok(result) = rx.try_recv() {
-                if let Ok(res) = result {
-                     let time = res.time.as_millis() as u64;
-                     
-                     // Calc Jitter
-            let prev_time = *self.db_ping_history.back().unwrap_or(&0);
-                     // Careful with u64 sub
-                     let jitter = if time > prev_time { time - prev_time } else { prev_time - time };
-                     
-                     self.db_jitter_history.push_back(jitter);
-                      if self.db_jitter_history.len() > 100 {
-                         self.db_jitter_history.pop_front();
-                     }
+        if let Some(cycles) = mtr_finished
+            && self.mtr_active
+        {
+            self.mtr_active = false;
+            self.record_mtr_run();
+            self.finished.insert(CurrentScreen::Mtr, format!("finished ({} cycles, {} hops)", cycles, self.mtr_hops.len()));
+        }
+        for probe in self.dash_probes.iter_mut().chain(self.gateway_probe.as_mut()) {
+            probe.poll();
+        }
+        let gateway_updates: Vec<_> = self.gateway_rx.as_ref().map(|rx| rx.try_iter().collect()).unwrap_or_default();
+        for info in gateway_updates {
+            self.update_gateway(info);
+        }
 
-                     self.db_ping_history.push_back(time);
-                     if self.db_ping_history.len() > 100 {
-                         self.db_ping_history.pop_front();
+        let banners: Vec<_> = self.nmap_banner_rx.as_ref().map(|rx| rx.try_iter().collect()).unwrap_or_default();
+        for result in banners {
+            self.nmap_banners.insert((result.host.clone(), result.port), result);
+        }
+        if self.nmap_banner_rx.is_some() && self.nmap_banners.len() >= self.nmap_ports.len() {
+            self.nmap_banner_rx = None;
+        }
+
+        if let Some(rx) = &self.nmap_rx {
+             loop {
+                 match rx.try_recv() {
+                     Ok(Ok(line)) => {
+                         api::emit(&self.api_events, "scan", json!({ "line": line }));
+                         self.nmap_output.push(line);
                      }
-                }
+                     Ok(Err(e)) => {
+                         api::emit(&self.api_events, "scan", json!({ "error": e }));
+                         self.nmap_error = Some(e);
+                     }
+                     Err(crossbeam::channel::TryRecvError::Empty) => break,
+                     Err(crossbeam::channel::TryRecvError::Disconnected) => {
+                         // nmap exited and both output readers are done
+                         self.nmap_active = false;
+                         self.nmap_rx = None;
+                         // A stopped scan's partial results don't go in the history
+                         let stopped = self.nmap_process.take().is_some_and(|p| p.stopped());
+                         if !stopped {
+                             self.record_nmap_scan();
+                         }
+                         self.finish_nmap_ports();
+                         if self.nmap_error.is_none() && !stopped {
+                             let hosts = self.nmap_ports.iter().map(|p| p.host.as_str()).collect::<std::collections::HashSet<_>>().len();
+                             let note = format!("finished ({} open ports on {} hosts)", self.nmap_ports.len(), hosts);
+                             self.finished.insert(CurrentScreen::Nmap, note);
+                         }
+                         let open: Vec<Value> = self.nmap_ports.iter().map(|p| json!({ "host": p.host, "port": p.port, "proto": p.proto, "service": p.service })).collect();
+                         api::emit(&self.api_events, "scan", json!({ "finished": true, "open_ports": open }));
+                         break;
+                     }
+                 }
              }
         }
 
-        if let Some(rx) = &self.nmap_rx {
-             while let Ok(line) = rx.try_recv() {
-                 self.nmap_output.push_back(line);
-                 if self.nmap_output.len() > 1000 {
-                     self.nmap_output.pop_front();
+        if let Some(rx) = &self.listeners_rx
+            && let Ok(result) = rx.try_recv()
+        {
+            match result {
+                Ok(sockets) => {
+                    self.listeners = sockets;
+                    self.listeners_error = None;
+                    if self.listeners_selected >= self.listeners.len() {
+                        self.listeners_selected = self.listeners.len().saturating_sub(1);
+                        self.listeners_table_state.select(self.listeners_table_state.selected().map(|_| self.listeners_selected));
+                    }
                 }
-             }
+                Err(e) => self.listeners_error = Some(e),
+            }
         }
 
-        if let Some(rx) = &self.arpscan_rx {
-             while let Ok(line) = rx.try_recv() {
-                 self.arpscan_output.push_back(line.clone());
-                 if self.arpscan_output.len() > 100 {
-                     self.arpscan_output.pop_front();
-                 }
+        if let Some(rx) = &self.neighbors_rx
+            && let Ok(result) = rx.try_recv()
+        {
+            match result {
+                Ok(list) => {
+                    for (n, previous) in self.neighbors.update(list) {
+                        let mac = n.mac.clone().unwrap_or_default();
+                        tracing::warn!("{} moved from {} to {} on {}", n.ip, previous, mac, n.interface);
+                        api::emit(&self.api_events, "neighbors", json!({ "ip": n.ip, "mac": mac, "previous_mac": previous, "interface": n.interface }));
+                    }
+                    self.neighbors_error = None;
+                    let seen: Vec<_> = self.neighbors.entries.iter()
+                        .filter_map(|e| Some((e.neighbor.mac.clone()?, e.neighbor.ip)))
+                        .collect();
+                    for (mac, ip) in seen {
+                        self.see_device(&mac, Some(ip), None);
+                    }
+                    let len = self.neighbors.entries.len();
+                    if self.neighbors_selected >= len {
+                        self.neighbors_selected = len.saturating_sub(1);
+                        self.neighbors_table_state.select(self.neighbors_table_state.selected().map(|_| self.neighbors_selected));
+                    }
+                }
+                Err(e) => self.neighbors_error = Some(e),
+            }
+        }
+
+        if let Some(rx) = &self.oui_rx
+            && let Ok(result) = rx.try_recv()
+        {
+            match result {
+                Ok(db) => {
+                    tracing::info!("Loaded {} MAC vendor prefixes from {}", db.len(), db.path.display());
+                    // Rows arp-scan couldn't name, or that came from the ping sweep
+                    for e in &mut self.arpscan_results {
+                        if let Some(vendor) = db.lookup(&e.mac) {
+                            e.vendor = vendor.to_string();
+                        }
+                    }
+                    self.oui = Some(db);
+                    self.oui_error = None;
+                }
+                Err(e) => self.oui_error = Some(e),
+            }
+        }
+
+        if let Some(rx) = &self.whois_rx
+            && let Ok(result) = rx.try_recv()
+        {
+            self.whois_result = Some(result);
+            self.whois_rx = None;
+        }
+
+        if let Some(rx) = self.arpscan_rx.clone() {
+             loop {
+                 let line = match rx.try_recv() {
+                     Ok(Ok(line)) => line,
+                     Ok(Err(e)) => {
+                         api::emit(&self.api_events, "arp", json!({ "error": e }));
+                         self.arpscan_error = Some(e);
+                         continue;
+                     }
+                     Err(crossbeam::channel::TryRecvError::Empty) => break,
+                     Err(crossbeam::channel::TryRecvError::Disconnected) => {
+                         // arp-scan exited and both output readers are done
+                         self.arpscan_active = false;
+                         self.arpscan_rx = None;
+                         let stopped = self.arpscan_process.take().is_some_and(|p| p.stopped());
+                         if self.arpscan_error.is_none() && !stopped {
+                             self.finished.insert(CurrentScreen::ArpScan, format!("finished ({} hosts)", self.arpscan_results.len()));
+                         }
+                         break;
+                     }
+                 };
+                 self.arpscan_output.push(line.clone());
                  
                  // Parse for structured data
                  // Output format: <IP>\t<MAC>\t<Vendor>
@@ -421,18 +1576,265 @@ impl App {
                      if let Ok(_) = parts[0].parse::<IpAddr>() {
                          let ip = parts[0].to_string();
                          let mac = parts[1].to_string();
-                         let vendor = if parts.len() > 2 {
-                             parts[2..].join(" ")
-                         } else {
-                             "Unknown".to_string()
+                         let vendor = match self.mac_vendor(&mac) {
+                             v if !v.is_empty() => v,
+                             _ if parts.len() > 2 => parts[2..].join(" "),
+                             _ => "Unknown".to_string(),
                          };
                          
-                         self.arpscan_results.push(arpscan::ArpEntry { ip, mac, vendor });
+                         api::emit(&self.api_events, "arp", json!({ "ip": ip, "mac": mac, "vendor": vendor }));
+                         self.see_device(&mac, ip.parse().ok(), None);
+                         if let Ok(addr) = ip.parse() {
+                             self.name_arp_host(addr);
+                         }
+                         self.arpscan_results.push(arpscan::ArpEntry { ip, mac, vendor, last_seen: None });
                      }
                  }
              }
         }
 
+        if let Some(rx) = self.arpwatch_rx.clone() {
+            loop {
+                match rx.try_recv() {
+                    Ok(arpscan::DiscoveryEvent::Seen { ip, mac, passive }) => self.arpwatch_seen(ip, mac, passive),
+                    Ok(arpscan::DiscoveryEvent::SweepStarted(n)) => {
+                        self.arpwatch_sweeps += 1;
+                        self.arpscan_output.push(format!("{} sweep {} of {} addresses", alerts::timestamp(), self.arpwatch_sweeps, n));
+                    }
+                    Ok(arpscan::DiscoveryEvent::SweepDone) => {
+                        let online = self.arpscan_results.iter().filter(|e| self.arpwatch_every.and_then(|every| e.online(every)).unwrap_or(false)).count();
+                        self.arpscan_output.push(format!("{} sweep {} done, {} of {} hosts online", alerts::timestamp(), self.arpwatch_sweeps, online, self.arpscan_results.len()));
+                    }
+                    Ok(arpscan::DiscoveryEvent::Error(e)) => {
+                        api::emit(&self.api_events, "arp", json!({ "error": e }));
+                        self.arpscan_output.push(format!("ERR: {}", e));
+                        self.arpscan_error = Some(e);
+                    }
+                    Err(crossbeam::channel::TryRecvError::Empty) => break,
+                    Err(crossbeam::channel::TryRecvError::Disconnected) => {
+                        // Both the sweeping and listening threads have stopped
+                        self.arpscan_active = false;
+                        self.arpwatch_rx = None;
+                        self.arpwatch_task = None;
+                        break;
+                    }
+                }
+            }
+        }
+
+        if let Some(rx) = &self.mdns_rx {
+            while let Ok(event) = rx.try_recv() {
+                match event {
+                    mdns::MdnsEvent::Update(svc) => {
+                        if let Some(inventory) = &mut self.inventory {
+                            let host = svc.host.trim_end_matches('.').trim_end_matches(".local");
+                            for ip in &svc.ips {
+                                if let Err(e) = inventory.name_ip(*ip, host) {
+                                    self.inventory_error = Some(format!("Inventory: {:#}", e));
+                                }
+                            }
+                        }
+                        if let Some(existing) = self.mdns_services.iter_mut().find(|s| s.fqdn == svc.fqdn) {
+                            *existing = svc;
+                        } else {
+                            self.mdns_services.push(svc);
+                        }
+                    }
+                    mdns::MdnsEvent::Remove(fqdn) => {
+                        self.mdns_services.retain(|s| s.fqdn != fqdn);
+                    }
+                    mdns::MdnsEvent::Error(e) => {
+                        self.mdns_error = Some(e);
+                        self.mdns_active = false;
+                    }
+                }
+            }
+            self.mdns_services.sort_by(|a, b| a.service_type.cmp(&b.service_type).then(a.instance.cmp(&b.instance)));
+            if self.mdns_selected >= self.mdns_services.len() {
+                self.mdns_selected = self.mdns_services.len().saturating_sub(1);
+            }
+        }
+
+        if let Some(rx) = &self.ssdp_rx {
+            while let Ok(event) = rx.try_recv() {
+                match event {
+                    ssdp::SsdpEvent::Device(dev) => {
+                        if let Some(existing) = self.ssdp_devices.iter_mut().find(|d| d.location == dev.location) {
+                            existing.merge(*dev);
+                        } else {
+                            self.ssdp_devices.push(*dev);
+                        }
+                    }
+                    ssdp::SsdpEvent::Error(e) => {
+                        self.ssdp_error = Some(e);
+                    }
+                }
+            }
+            self.ssdp_devices.sort_by(|a, b| a.ip.cmp(&b.ip).then(a.location.cmp(&b.location)));
+        }
+
+        let wifi_events: Vec<_> = self.wifi_rx.as_ref().map(|rx| rx.try_iter().collect()).unwrap_or_default();
+        for event in wifi_events {
+            match event {
+                wifi::WifiEvent::Interface(name) => self.wifi_interface = Some(name),
+                wifi::WifiEvent::Scan(networks) => {
+                    self.wifi_error = None;
+                    self.wifi_networks = networks;
+                    if self.wifi_selected >= self.wifi_networks.len() {
+                        self.wifi_selected = self.wifi_networks.len().saturating_sub(1);
+                    }
+                }
+                wifi::WifiEvent::Link(link) => {
+                    if let Some(signal) = link.as_ref().and_then(|l| l.signal_dbm) {
+                        self.wifi_rssi_history.push(signal as f64);
+                    }
+                    // Scans don't always flag the associated BSS
+                    if let Some(l) = &link {
+                        for n in &mut self.wifi_networks {
+                            n.connected |= !l.bssid.is_empty() && n.bssid.eq_ignore_ascii_case(&l.bssid);
+                        }
+                    }
+                    self.wifi_link = link;
+                }
+                wifi::WifiEvent::Error(e) => self.wifi_error = Some(e),
+            }
+        }
+
+        let dhcp_events: Vec<_> = self.dhcp_rx.as_ref().map(|rx| rx.try_iter().collect()).unwrap_or_default();
+        for event in dhcp_events {
+            match event {
+                dhcp::DhcpEvent::Reply(reply) => self.dhcp_replies.push(*reply),
+                dhcp::DhcpEvent::Done => {
+                    self.dhcp_active = false;
+                    self.dhcp_rx = None;
+                }
+                dhcp::DhcpEvent::Error(e) => self.dhcp_error = Some(e),
+            }
+        }
+
+        self.tools.tick();
+
+        if let Some(result) = self.trace_rx.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            api::emit(&self.api_events, "traceroute", match &result {
+                Ok(trace) => {
+                    let hops: Vec<Value> = trace.hops.iter().map(|h| json!({
+                        "ttl": h.ttl,
+                        "host": h.host.map(|ip| ip.to_string()),
+                        "times_ms": h.rtts.iter().map(|r| r.map(|d| d.as_secs_f64() * 1000.0)).collect::<Vec<_>>(),
+                    })).collect();
+                    json!({ "target": trace.target.to_string(), "reached": trace.reached, "hops": hops })
+                }
+                Err(e) => json!({ "error": e }),
+            });
+            match result {
+                Ok(trace) => self.trace_result = Some(trace),
+                Err(e) => self.trace_error = Some(e),
+            }
+            self.trace_active = false;
+            self.trace_rx = None;
+        }
+
+        let sweep_events: Vec<_> = self.sweep_rx.as_ref().map(|rx| rx.try_iter().collect()).unwrap_or_default();
+        for event in sweep_events {
+            match event {
+                sweep::SweepEvent::Alive(ip, rtt) => {
+                    api::emit(&self.api_events, "sweep", json!({ "alive": ip.to_string(), "time_ms": rtt.as_secs_f64() * 1000.0 }));
+                    let at = self.sweep_hosts.partition_point(|h| h.ip < ip);
+                    self.sweep_hosts.insert(at, sweep::SweepHost { ip, rtt, mac: None });
+                }
+                sweep::SweepEvent::Probed => self.sweep_probed += 1,
+                sweep::SweepEvent::Done(macs) => {
+                    for host in &mut self.sweep_hosts {
+                        host.mac = macs.get(&host.ip).cloned();
+                    }
+                    self.merge_sweep_into_arp();
+                    api::emit(&self.api_events, "sweep", json!({ "finished": true, "alive": self.sweep_hosts.len() }));
+                    self.sweep_active = false;
+                    self.sweep_rx = None;
+                }
+                sweep::SweepEvent::Error(e) => {
+                    self.sweep_error = Some(e);
+                    self.sweep_active = false;
+                    self.sweep_rx = None;
+                }
+            }
+        }
+
+        let syslog_events: Vec<_> = self.syslog_rx.as_ref().map(|rx| rx.try_iter().collect()).unwrap_or_default();
+        for event in syslog_events {
+            match event {
+                syslog::SyslogEvent::Message(msg) => {
+                    api::emit(&self.api_events, "syslog", json!({
+                        "from": msg.from.to_string(),
+                        "facility": msg.facility_name(),
+                        "severity": msg.severity_name(),
+                        "hostname": msg.hostname,
+                        "app": msg.app,
+                        "text": msg.text,
+                    }));
+                    self.syslog_received += 1;
+                    let shown = msg.severity <= self.syslog_min_severity && msg.matches(self.syslog_filter_input.value());
+                    self.syslog_messages.push(*msg);
+                    // Newest is on top; keep a selected row under the cursor
+                    if shown && self.syslog_table_state.selected().is_some() {
+                        self.syslog_selected += 1;
+                        self.syslog_table_state.select(Some(self.syslog_selected));
+                    }
+                }
+                syslog::SyslogEvent::Warning(e) => self.syslog_error = Some(e),
+                syslog::SyslogEvent::Error(e) => {
+                    self.syslog_error = Some(e);
+                    self.syslog_active = false;
+                    self.syslog_rx = None;
+                }
+            }
+        }
+
+        let diagnose_events: Vec<_> = self.diagnose_rx.as_ref().map(|rx| rx.try_iter().collect()).unwrap_or_default();
+        for event in diagnose_events {
+            match event {
+                diagnose::DiagnoseEvent::Running(check) => self.diagnose_running = Some(check),
+                diagnose::DiagnoseEvent::Done(result) => {
+                    api::emit(&self.api_events, "diagnose", json!({
+                        "check": result.check.name(),
+                        "status": result.status.label(),
+                        "detail": result.detail,
+                    }));
+                    self.diagnose_results.push(result);
+                }
+                diagnose::DiagnoseEvent::Finished(conclusion) => {
+                    api::emit(&self.api_events, "diagnose", json!({ "finished": true, "conclusion": conclusion }));
+                    let failed = self.diagnose_results.iter().filter(|r| r.status == diagnose::Status::Fail).count();
+                    if failed > 0 {
+                        tracing::warn!("Diagnose: {}", conclusion);
+                    } else {
+                        tracing::info!("Diagnose: {}", conclusion);
+                    }
+                    self.finished.insert(CurrentScreen::Diagnose, format!("finished ({} checks, {} failed)", self.diagnose_results.len(), failed));
+                    self.diagnose_conclusion = Some(conclusion);
+                    self.diagnose_running = None;
+                    self.diagnose_active = false;
+                    self.diagnose_rx = None;
+                }
+            }
+        }
+
+        if let Some(rx) = &self.flows_rx {
+            while let Ok(event) = rx.try_recv() {
+                match event {
+                    flows::FlowEvent::Snapshot(snap) => {
+                        self.flows_rate_history.push(snap.bytes_per_sec * 8.0 / 1_000_000.0);
+                        self.flows_fps_history.push(snap.flows_per_sec);
+                        self.flows_snapshot = snap;
+                    }
+                    flows::FlowEvent::Error(e) => {
+                        self.flows_error = Some(e);
+                        self.flows_active = false;
+                    }
+                }
+            }
+        }
+
         // Update Traffic Graph (Total, Rx, Tx)
         let current_count = self.sniffer.packet_count.load(std::sync::atomic::Ordering::Relaxed);
         let current_rx = self.sniffer.in_packets.load(std::sync::atomic::Ordering::Relaxed);
@@ -445,14 +1847,21 @@ impl App {
         self.last_packet_count = current_count;
         self.last_rx_count = current_rx;
         self.last_tx_count = current_tx;
+
+        // Protocol mix; "other" is whatever the TCP/UDP/ICMP counters didn't claim
+        let relaxed = std::sync::atomic::Ordering::Relaxed;
+        let (tcp, udp, icmp) = (self.sniffer.tcp_packets.load(relaxed), self.sniffer.udp_packets.load(relaxed), self.sniffer.icmp_packets.load(relaxed));
+        let counts = [tcp, udp, icmp, current_count.saturating_sub(tcp + udp + icmp)];
+        let delta: [u64; 4] = std::array::from_fn(|i| counts[i].saturating_sub(self.last_protocol_counts[i]));
+        self.last_protocol_counts = counts;
+        self.protocol_history.push(delta);
+
         
-        self.traffic_history.push_back(pps);
-        self.rx_history.push_back(rx_pps);
-        self.tx_history.push_back(tx_pps);
+        self.traffic_history.push(pps);
+        self.dash_pps.push(pps as f64);
+        self.rx_history.push(rx_pps);
+        self.tx_history.push(tx_pps);
         
-        if self.traffic_history.len() > 100 { self.traffic_history.pop_front(); }
-        if self.rx_history.len() > 100 { self.rx_history.pop_front(); }
-        if self.tx_history.len() > 100 { self.tx_history.pop_front(); }
 
         // Update Bandwidth (Mbps)
         let now = std::time::Instant::now();
@@ -474,177 +1883,2557 @@ impl App {
             self.last_lan_tx_bytes = lan_tx;
 
             let mbps_factor = 8.0 / 1_000_000.0 / elapsed;
+            let (mut wan_rx_mbps, mut wan_tx_mbps) = (wan_rx_bytes as f64 * mbps_factor, wan_tx_bytes as f64 * mbps_factor);
+            let (lan_rx_mbps, lan_tx_mbps) = (lan_rx_bytes as f64 * mbps_factor, lan_tx_bytes as f64 * mbps_factor);
+
+            // Without a working capture, fall back to the OS counters (no WAN/LAN split)
+            self.bandwidth_from_os = !self.sniffer_active || self.sniffer.packet_count.load(std::sync::atomic::Ordering::Relaxed) == 0;
+            if self.bandwidth_from_os {
+                (wan_rx_mbps, wan_tx_mbps) = self.os_bandwidth();
+            }
             
-            self.wan_rx_history.push_back(wan_rx_bytes as f64 * mbps_factor);
-            self.wan_tx_history.push_back(wan_tx_bytes as f64 * mbps_factor);
-            self.lan_rx_history.push_back(lan_rx_bytes as f64 * mbps_factor);
-            self.lan_tx_history.push_back(lan_tx_bytes as f64 * mbps_factor);
+            self.wan_rx_history.push(wan_rx_mbps);
+            self.wan_tx_history.push(wan_tx_mbps);
+            self.dash_wan_rx.push(wan_rx_mbps);
+            self.dash_wan_tx.push(wan_tx_mbps);
+            self.lan_rx_history.push(lan_rx_mbps);
+            self.lan_tx_history.push(lan_tx_mbps);
+            self.dash_lan_rx.push(lan_rx_mbps);
+            self.dash_lan_tx.push(lan_tx_mbps);
             
-             if self.wan_rx_history.len() > 100 { self.wan_rx_history.pop_front(); }
-             if self.wan_tx_history.len() > 100 { self.wan_tx_history.pop_front(); }
-             if self.lan_rx_history.len() > 100 { self.lan_rx_history.pop_front(); }
-             if self.lan_tx_history.len() > 100 { self.lan_tx_history.pop_front(); }
         }
         self.last_tick_time = now;
-    }
 
-    pub fn start_background_tasks(&mut self) {
-        // 1. Start Sniffer (Auto-select first interface or loopback)
-        if !self.interfaces.is_empty() {
-            self.start_sniffer();
+        let requests: Vec<_> = self.api_rx.as_ref().map(|rx| rx.try_iter().collect()).unwrap_or_default();
+        for request in requests {
+            let reply = self.handle_api(request.command);
+            let _ = request.reply.send(reply);
         }
 
-        // 2. Start Background Ping (to 1.1.1.1)
-        let (tx, rx) = mpsc::channel(100);
-        self.db_ping_rx = Some(rx);
-        let task = PingTask { target: "1.1.1.1".to_string(), tx };
-        tokio::spawn(async move {
-            task.run().await;
+        let mut runs = self.scheduler.run_due(&self.config);
+        runs.extend(self.scheduler.finished());
+        for run in runs {
+            self.finish_scheduled_run(run);
+        }
+
+        let snapshot = self.metrics_snapshot();
+        for change in self.alert_engine.evaluate(&self.config.alerts.rules, &snapshot) {
+            let line = match change {
+                AlertChange::Raised(alert) => {
+                    tracing::warn!("Alert raised: {}", alert.message);
+                    alerts::notify(&self.config.alerts, &alert);
+                    api::emit(&self.api_events, "alerts", json!({ "raised": alert.message, "rule": alert.rule }));
+                    format!("{} RAISED  {}", alert.since, alert.message)
+                }
+                AlertChange::Cleared(alert) => {
+                    tracing::info!("Alert cleared: {}", alert.rule);
+                    api::emit(&self.api_events, "alerts", json!({ "cleared": alert.rule }));
+                    format!("{} CLEARED {}", alert.since, alert.rule)
+                }
+            };
+            self.alert_log.push(line);
+        }
+        let rules: Vec<AlertRule> = self.config.capture.triggers.iter().filter_map(|t| t.metric_rule().cloned()).collect();
+        for change in self.trigger_engine.evaluate(&rules, &snapshot) {
+            if let AlertChange::Raised(alert) = change {
+                self.fire_capture_trigger(&alert.rule, &alert.message);
+            }
+        }
+        if self.trigger_until.is_some_and(|until| std::time::Instant::now() >= until) {
+            self.end_triggered_recording();
+        }
+
+        if let Some(shared) = &self.metrics && let Ok(mut m) = shared.lock() {
+            *m = snapshot;
+        }
+
+        self.log_changes();
+        self.event_log.drain(self.show_log);
+    }
+
+    /// Why the screen's tool last failed, shown as a banner above it until the next start.
+    pub fn tool_error(&self, screen: CurrentScreen) -> Option<&String> {
+        if let Some(tool) = self.tools.get(screen) {
+            return tool.error();
+        }
+        match screen {
+            CurrentScreen::Sniffer => self.sniffer_error.as_ref(),
+            CurrentScreen::Mtr => self.mtr_error.as_ref(),
+            CurrentScreen::Nmap => self.nmap_error.as_ref(),
+            CurrentScreen::ArpScan => self.arpscan_error.as_ref(),
+            CurrentScreen::Mdns => self.mdns_error.as_ref(),
+            CurrentScreen::Ssdp => self.ssdp_error.as_ref(),
+            CurrentScreen::Flows => self.flows_error.as_ref(),
+            CurrentScreen::Wifi => self.wifi_error.as_ref(),
+            CurrentScreen::Dhcp => self.dhcp_error.as_ref(),
+            CurrentScreen::Traceroute => self.trace_error.as_ref(),
+            CurrentScreen::Sweep => self.sweep_error.as_ref(),
+            CurrentScreen::Syslog => self.syslog_error.as_ref(),
+            CurrentScreen::Asn => self.asn_error.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Every current error, named for the log. Most are overwritten rather than raised, so
+    /// `log_changes` compares against what it last logged.
+    fn errors(&self) -> Vec<(&'static str, &String)> {
+        let others = [
+            ("GeoIP", &self.geoip_error),
+            ("OUI", &self.oui_error),
+            ("Reputation", &self.reputation_error),
+            ("Listeners", &self.listeners_error),
+            ("Neighbors", &self.neighbors_error),
+            ("Metrics", &self.metrics_error),
+            ("API", &self.api_error),
+            ("History", &self.history_error),
+            ("Inventory", &self.inventory_error),
+        ];
+        CurrentScreen::all().into_iter()
+            .filter_map(|screen| self.tool_error(screen).map(|e| (screen.title(), e)))
+            .chain(others.into_iter().filter_map(|(name, e)| e.as_ref().map(|e| (name, e))))
+            .collect()
+    }
+
+    /// Logs tools starting and stopping, and errors as they appear.
+    fn log_changes(&mut self) {
+        for screen in CurrentScreen::all() {
+            let Some(active) = self.tool_active(screen) else { continue };
+            let was = self.logged_active.insert(screen, active).unwrap_or(false);
+            if active && !was {
+                self.finished.remove(&screen);
+                match self.screen_input(screen).map(|i| i.value().trim().to_string()).filter(|i| !i.is_empty()) {
+                    Some(input) => tracing::info!("{} started: {}", screen.title(), input),
+                    None => tracing::info!("{} started", screen.title()),
+                }
+            } else if was && !active {
+                match self.finished.get(&screen) {
+                    Some(note) => tracing::info!("{} {}", screen.title(), note),
+                    None => tracing::info!("{} stopped", screen.title()),
+                }
+            }
+        }
+
+        let errors: Vec<(&'static str, String)> = self.errors().into_iter().map(|(name, e)| (name, e.clone())).collect();
+        self.logged_errors.retain(|name, _| errors.iter().any(|(n, _)| n == name));
+        for (name, error) in errors {
+            if self.logged_errors.get(name) != Some(&error) {
+                tracing::error!("{}: {}", name, error);
+                self.logged_errors.insert(name, error);
+            }
+        }
+    }
+
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        let interface = self.sniffer_targets().join(",");
+        let relaxed = std::sync::atomic::Ordering::Relaxed;
+        // Bandwidth history is per tick; average the last second to smooth it out
+        let recent = |h: &VecDeque<f64>| {
+            let n = h.len().min(20);
+            if n == 0 { 0.0 } else { h.iter().rev().take(n).sum::<f64>() / n as f64 }
+        };
+
+        MetricsSnapshot {
+            wan_rx_mbps: recent(&self.wan_rx_history),
+            wan_tx_mbps: recent(&self.wan_tx_history),
+            lan_rx_mbps: recent(&self.lan_rx_history),
+            lan_tx_mbps: recent(&self.lan_tx_history),
+
+            pings: self.dash_probes.iter().map(|p| PingMetrics {
+                target: p.target.clone(),
+                latency_ms: p.last_latency() as f64,
+                jitter_ms: p.last_jitter() as f64,
+                sent: p.sent,
+                lost: p.lost,
+                loss_ratio: p.loss_ratio(),
+            }).collect(),
+
+            connections: self.active_connections.len() as u64,
+
+            capture_interface: interface,
+            capture_packets: self.sniffer.packet_count.load(relaxed),
+            wan_rx_bytes: self.sniffer.wan_in_bytes.load(relaxed),
+            wan_tx_bytes: self.sniffer.wan_out_bytes.load(relaxed),
+            lan_rx_bytes: self.sniffer.lan_in_bytes.load(relaxed),
+            lan_tx_bytes: self.sniffer.lan_out_bytes.load(relaxed),
+        }
+    }
+
+    pub fn start_background_tasks(&mut self) {
+        // 0. Note what the current privileges disable, once, rather than only on each screen
+        for screen in CurrentScreen::ALL {
+            if let Some(text) = self.privileges.banner(screen) {
+                tracing::warn!("{}: {}", screen.title(), text);
+            }
+        }
+
+        // 0b. Pick up where the last run left off, before the sniffer picks its interfaces
+        if self.config.ui.restore_session {
+            self.restore_session(session::load());
+        }
+
+        // 1. Start Sniffer (Auto-select first interface or loopback); pointless without capture rights
+        if !self.interfaces.is_empty() && self.privileges.capture {
+            self.start_sniffer();
+        }
+
+        // 2. Start the dashboard's background pings, and find the gateway to ping separately
+        self.start_probes();
+        let (tx, rx) = crossbeam::channel::unbounded();
+        self.gateway_rx = Some(rx);
+        gateway::spawn(self.config.dashboard.public_ip_source.clone(), tx);
+        
+        // 2b. Sample OS interface counters
+        let (tx, rx) = crossbeam::channel::unbounded();
+        self.iface_stats_rx = Some(rx);
+        let task = interfaces::InterfaceStatsTask::new(tx);
+        std::thread::spawn(move || {
+            task.run();
+        });
+
+        // 3. Start Connections Monitor (and the GeoIP databases it annotates peers with)
+        self.start_geoip();
+        self.start_connections_monitor();
+
+        // 3b. Load the MAC vendor list
+        let (tx, rx) = crossbeam::channel::unbounded();
+        self.oui_rx = Some(rx);
+        oui::spawn(self.config.oui.clone(), tx);
+
+        // 4. Open result history database, and the device inventory kept in it
+        self.open_history();
+        self.refresh_history();
+        self.open_inventory();
+        self.start_neighbors();
+
+        // 5. Start Prometheus exporter (opt-in via config)
+        if self.config.metrics.enabled {
+            self.start_metrics_server();
+        }
+
+        // 6. Start the API server (opt-in via config)
+        if self.config.api.enabled {
+            self.start_api_server();
+        }
+    }
+
+    pub fn start_api_server(&mut self) {
+        let listen = self.config.api.listen.clone();
+        let listener = match std::net::TcpListener::bind(&listen) {
+            Ok(l) => l,
+            Err(e) => {
+                self.api_error = Some(format!("API: cannot bind {}: {}", listen, e));
+                return;
+            }
+        };
+
+        // The API can start tools with any flags, so it never runs open; without a configured token it gets one
+        let token = match self.config.api.token.clone().filter(|t| !t.trim().is_empty()) {
+            Some(token) => token,
+            None => {
+                let token: String = rand::random::<[u8; 16]>().iter().map(|b| format!("{:02x}", b)).collect();
+                self.config.api.token = Some(token.clone());
+                match self.config.save() {
+                    Ok(()) => tracing::info!("API: generated a token and saved it as [api] token in the config file"),
+                    Err(e) => tracing::warn!("API: generated token {} for this session, but couldn't save it: {}", token, e),
+                }
+                token
+            }
+        };
+
+        let (tx, rx) = crossbeam::channel::unbounded();
+        let (events, _) = tokio::sync::broadcast::channel(1024);
+        match api::spawn_server(listener, token, tx, events.clone()) {
+            Ok(()) => {
+                self.api_rx = Some(rx);
+                self.api_events = Some(events);
+            }
+            Err(e) => self.api_error = Some(format!("API: {}", e)),
+        }
+    }
+
+    fn handle_api(&mut self, command: ApiCommand) -> Result<Value, String> {
+        let screen_for = |tool: &str| {
+            CurrentScreen::all().into_iter()
+                .find(|s| s.slug() == tool)
+                .ok_or_else(|| format!("Unknown tool '{}'", tool))
+        };
+        match command {
+            ApiCommand::Status => {
+                let tools: Vec<Value> = CurrentScreen::all().iter()
+                    .filter_map(|s| self.tool_active(*s).map(|active| json!({ "tool": s.slug(), "active": active })))
+                    .collect();
+                let alerts: Vec<&str> = self.alert_engine.active().map(|a| a.message.as_str()).collect();
+                Ok(json!({ "tools": tools, "alerts": alerts }))
+            }
+            ApiCommand::Start { tool, args } => {
+                let screen = screen_for(&tool)?;
+                if self.tool_active(screen).is_none() {
+                    return Err(format!("'{}' is not a tool", tool));
+                }
+                if let Some(args) = args {
+                    if screen == CurrentScreen::Dns {
+                        // `example.com MX`; the record type is a separate control in the UI
+                        let mut parts = args.split_whitespace();
+                        self.dns_input = Input::new(parts.next().unwrap_or("").to_string());
+                        if let Some(kind) = parts.next() {
+                            self.dns_record_type = kind.to_uppercase().parse().map_err(|_| format!("Unknown record type '{}'", kind))?;
+                        }
+                    } else {
+                        *self.screen_input(screen).ok_or_else(|| format!("'{}' takes no args", tool))? = Input::new(args);
+                    }
+                }
+                self.start_tool(screen);
+                Ok(json!({ "tool": tool, "active": self.tool_active(screen) }))
+            }
+            ApiCommand::Stop { tool } => {
+                let screen = screen_for(&tool)?;
+                self.stop_tool(screen);
+                Ok(json!({ "tool": tool, "active": self.tool_active(screen) }))
+            }
+        }
+    }
+
+    /// Whether the screen's tool is running; `None` for screens that aren't started and stopped.
+    pub fn tool_active(&self, screen: CurrentScreen) -> Option<bool> {
+        if let Some(tool) = self.tools.get(screen) {
+            return Some(tool.active());
+        }
+        Some(match screen {
+            CurrentScreen::Ping => self.is_pinging,
+            CurrentScreen::Dns => self.dns_rx.is_some() || self.dns_watch.is_some() || self.dns_mail_rx.is_some() || self.dns_blocklist_rx.is_some() || self.dns_subdomains_rx.is_some(),
+            CurrentScreen::Sniffer => self.sniffer_active,
+            CurrentScreen::Mtr => self.mtr_active,
+            CurrentScreen::Nmap => self.nmap_active,
+            CurrentScreen::ArpScan => self.arpscan_active,
+            CurrentScreen::Mdns => self.mdns_active,
+            CurrentScreen::Ssdp => self.ssdp_active,
+            CurrentScreen::Flows => self.flows_active,
+            CurrentScreen::Wifi => self.wifi_active,
+            CurrentScreen::Dhcp => self.dhcp_active,
+            CurrentScreen::Traceroute => self.trace_active,
+            CurrentScreen::Sweep => self.sweep_active,
+            CurrentScreen::Syslog => self.syslog_active,
+            CurrentScreen::Diagnose => self.diagnose_active,
+            CurrentScreen::Asn => self.asn_rx.is_some(),
+            CurrentScreen::Dashboard | CurrentScreen::Connections | CurrentScreen::History | CurrentScreen::Devices | CurrentScreen::Ntp | CurrentScreen::Runner | CurrentScreen::HttpLoad | CurrentScreen::Plugin(_) => return None,
+        })
+    }
+
+    /// Adds the screen's input to its recall history, and to the database's.
+    fn remember_input(&mut self, screen: CurrentScreen) {
+        let input = match screen {
+            CurrentScreen::Sniffer => self.sniffer_filter_input.value().trim().to_string(),
+            _ => match self.screen_input(screen) {
+                Some(input) => input.value().trim().to_string(),
+                None => return,
+            },
+        };
+        self.input_history.entry(screen).or_default().push(&input);
+        if let (Some(store), Some(kind)) = (&self.history, input_kind(screen))
+            && let Err(e) = store.record_input(kind, &input, completion::HISTORY_LEN)
+        {
+            self.history_error = Some(format!("Failed to save input: {}", e));
+        }
+    }
+
+    /// Up (`older`) / Down on an idle tool's input: steps through what was run before.
+    pub fn recall_input(&mut self, older: bool) {
+        let screen = self.current_screen;
+        let Some(current) = self.screen_input(screen).map(|i| i.value().to_string()) else { return };
+        let Some(history) = self.input_history.get_mut(&screen) else { return };
+        let recalled = if older { history.older(&current) } else { history.newer() };
+        if let Some(value) = recalled.map(str::to_string) {
+            *self.screen_input(screen).expect("checked above") = Input::new(value);
+        }
+    }
+
+    /// Moves the highlight through the scan output, from the top when nothing is highlighted.
+    pub fn step_nmap_line(&mut self, forward: bool) {
+        let Some(last) = self.nmap_output.len().checked_sub(1) else { return };
+        let next = match self.nmap_list_state.selected() {
+            Some(line) if forward => (line + 1).min(last),
+            Some(line) => line.saturating_sub(1),
+            None => 0,
+        };
+        self.nmap_list_state.select(Some(next));
+    }
+
+    /// What the screen's input could be completed to: an earlier input, or for a lone host
+    /// a device from the inventory or a name from the hosts file. Only while typing at the end.
+    pub fn input_suggestion(&self, screen: CurrentScreen) -> Option<String> {
+        let input = match screen {
+            CurrentScreen::Ping if !self.is_pinging => &self.ping_input,
+            CurrentScreen::Dns if self.focused(screen) == Some(Field::DnsDomain) => &self.dns_input,
+            CurrentScreen::Mtr if !self.mtr_active => &self.mtr_input,
+            CurrentScreen::Nmap if !self.nmap_active => &self.nmap_input,
+            _ => return None,
+        };
+        let value = input.value();
+        if input.cursor() != value.chars().count() {
+            return None;
+        }
+        let history = self.input_history.get(&screen).into_iter().flat_map(|h| h.iter());
+        if let Some(found) = completion::suggest(value, history) {
+            return Some(found.to_string());
+        }
+        if value.contains(char::is_whitespace) {
+            return None;
+        }
+        let devices: Vec<String> = self.inventory.iter().flat_map(|inv| &inv.devices)
+            .flat_map(|d| d.hostname.clone().into_iter().chain(d.ip().map(|ip| ip.to_string())))
+            .collect();
+        completion::suggest(value, devices.iter().chain(&self.hosts_file).map(String::as_str)).map(str::to_string)
+    }
+
+    /// Takes the suggestion (Right at the end of the input).
+    pub fn accept_suggestion(&mut self) {
+        let screen = self.current_screen;
+        if let Some(suggestion) = self.input_suggestion(screen)
+            && let Some(input) = self.screen_input(screen)
+        {
+            *input = Input::new(suggestion);
+        }
+    }
+
+    /// The input a tool starts from, for tools that take a target.
+    fn screen_input(&mut self, screen: CurrentScreen) -> Option<&mut Input> {
+        if self.tools.get(screen).is_some() {
+            return self.tools.get_mut(screen).and_then(|tool| tool.input());
+        }
+        match screen {
+            CurrentScreen::Ping => Some(&mut self.ping_input),
+            CurrentScreen::Dns => Some(&mut self.dns_input),
+            CurrentScreen::Mtr => Some(&mut self.mtr_input),
+            CurrentScreen::Nmap => Some(&mut self.nmap_input),
+            CurrentScreen::ArpScan => Some(&mut self.arpscan_input),
+            CurrentScreen::Traceroute => Some(&mut self.trace_input),
+            CurrentScreen::Sweep => Some(&mut self.sweep_input),
+            CurrentScreen::Syslog => Some(&mut self.syslog_input),
+            CurrentScreen::Asn => Some(&mut self.asn_input),
+            _ => None,
+        }
+    }
+
+    pub fn start_metrics_server(&mut self) {
+        let listen = self.config.metrics.listen.clone();
+        let listener = match std::net::TcpListener::bind(&listen) {
+            Ok(l) => l,
+            Err(e) => {
+                self.metrics_error = Some(format!("Metrics: cannot bind {}: {}", listen, e));
+                return;
+            }
+        };
+
+        let shared: SharedMetrics = Default::default();
+        match metrics::spawn_server(listener, shared.clone()) {
+            Ok(()) => self.metrics = Some(shared),
+            Err(e) => self.metrics_error = Some(format!("Metrics: {}", e)),
+        }
+    }
+
+    /// Flattens the active screen's results for export.
+    pub fn export_table(&self) -> Option<ExportTable> {
+        if let Some(tool) = self.tools.get(self.current_screen) {
+            return tool.export();
+        }
+        let table = match self.current_screen {
+            // Registered tools answered above
+            CurrentScreen::Dashboard | CurrentScreen::Ntp | CurrentScreen::Runner | CurrentScreen::HttpLoad | CurrentScreen::Plugin(_) => return None,
+            CurrentScreen::Ping => {
+                let mut t = ExportTable::new("ping", &["seq", "target", "probe", "ttl", "time_ms", "error"]);
+                for res in &self.ping_history {
+                    match res {
+                        Ok(r) => t.push([r.seq.to_string(), r.target.clone(), r.probe.label(), r.ttl.to_string(), format!("{:.3}", r.time.as_secs_f64() * 1000.0), String::new()]),
+                        Err(e) => t.push(["", "", "", "", "", e.as_str()]),
+                    }
+                }
+                t
+            }
+            CurrentScreen::Dns => {
+                let mut t = ExportTable::new("dns", &["query", "type", "value", "ttl"]);
+                if let Some(Ok(res)) = &self.dns_result {
+                    let (label, records) = match res {
+                        DnsResult::A(r) => ("A", r),
+                        DnsResult::AAAA(r) => ("AAAA", r),
+                        DnsResult::MX(r) => ("MX", r),
+                        DnsResult::TXT(r) => ("TXT", r),
+                        DnsResult::NS(r) => ("NS", r),
+                    };
+                    for r in records {
+                        t.push([self.dns_query.clone(), label.to_string(), r.value.clone(), r.ttl.to_string()]);
+                    }
+                }
+                t
+            }
+            CurrentScreen::Sniffer => {
+                let mut t = ExportTable::new("sniffer", &["time", "interface", "source", "source_mac", "source_vendor", "destination", "protocol", "length", "info"]);
+                for p in &self.sniffer_packets {
+                    t.push([p.time.clone(), p.interface.clone(), p.source.clone(), p.source_mac.clone(), self.mac_vendor(&p.source_mac), p.destination.clone(), p.protocol.clone(), p.length.clone(), p.info.clone()]);
+                }
+                t
+            }
+            CurrentScreen::Mtr => {
+                let mut t = ExportTable::new("mtr", &["ttl", "host", "loss_pct", "sent", "recv", "last_ms", "avg_ms", "best_ms", "worst_ms", "jitter_ms", "p50_ms", "p95_ms", "p99_ms", "path_changes"]);
+                for h in &self.mtr_hops {
+                    let p = |q: f64| h.rtt.percentile(q).map(|v| format!("{:.2}", v)).unwrap_or_default();
+                    t.push([h.ttl.to_string(), h.host.clone(), format!("{:.1}", h.loss), h.sent.to_string(), h.recv.to_string(), h.last.to_string(), h.avg.to_string(), h.best.to_string(), h.worst.to_string(), h.jitter.to_string(), p(50.0), p(95.0), p(99.0), h.changes.to_string()]);
+                }
+                t
+            }
+            CurrentScreen::Nmap => {
+                // "22/tcp   open  ssh  OpenSSH 9.6"
+                let mut t = ExportTable::new("nmap", &["port", "protocol", "state", "service", "version"]);
+                for line in &self.nmap_output {
+                    let mut cols = line.split_whitespace();
+                    let (Some(port_proto), Some(state)) = (cols.next(), cols.next()) else { continue };
+                    let Some((port, proto)) = port_proto.split_once('/') else { continue };
+                    if port.parse::<u16>().is_err() {
+                        continue;
+                    }
+                    let service = cols.next().unwrap_or_default().to_string();
+                    let version = cols.collect::<Vec<_>>().join(" ");
+                    t.push([port.to_string(), proto.to_string(), state.to_string(), service, version]);
+                }
+                t
+            }
+            CurrentScreen::ArpScan if self.arp_view == ArpView::Neighbors => {
+                let mut t = ExportTable::new("neighbors", &["ip", "mac", "vendor", "state", "interface", "previous_mac"]);
+                for e in &self.neighbors.entries {
+                    let previous = match &e.binding {
+                        neighbors::Binding::Changed { previous } => previous.clone(),
+                        _ => String::new(),
+                    };
+                    let mac = e.neighbor.mac.clone().unwrap_or_default();
+                    t.push([e.neighbor.ip.to_string(), mac.clone(), self.mac_vendor(&mac), e.neighbor.state.clone(), e.neighbor.interface.clone(), previous]);
+                }
+                t
+            }
+            CurrentScreen::ArpScan if self.arpwatch_every.is_some() => {
+                let mut t = ExportTable::new("arp", &["ip", "hostname", "mac", "vendor", "online", "last_seen_secs"]);
+                for e in &self.arpscan_results {
+                    let online = self.arpwatch_every.and_then(|every| e.online(every)).unwrap_or(false);
+                    let secs = e.last_seen.map(|t| t.elapsed().as_secs().to_string()).unwrap_or_default();
+                    t.push([e.ip.clone(), self.arp_name(e), e.mac.clone(), e.vendor.clone(), online.to_string(), secs]);
+                }
+                t
+            }
+            CurrentScreen::ArpScan => {
+                let mut t = ExportTable::new("arp", &["ip", "hostname", "mac", "vendor"]);
+                for e in &self.arpscan_results {
+                    t.push([e.ip.clone(), self.arp_name(e), e.mac.clone(), e.vendor.clone()]);
+                }
+                t
+            }
+            CurrentScreen::Connections if self.connections_view == ConnectionsView::Listening => {
+                let mut t = ExportTable::new("listening", &["protocol", "address", "port", "pid", "process"]);
+                for l in &self.listeners {
+                    t.push([l.protocol.clone(), l.address.clone(), l.port.to_string(), l.pid.map(|p| p.to_string()).unwrap_or_default(), l.process.clone()]);
+                }
+                t
+            }
+            CurrentScreen::Connections if self.connections_view == ConnectionsView::Talkers => {
+                let mut t = ExportTable::new("talkers", &["remote_ip", "country", "organization", "bytes", "rate_bps", "window_secs"]);
+                let span = self.talkers.span(self.talkers_window);
+                for h in self.top_talkers() {
+                    t.push([h.ip.to_string(), h.country.unwrap_or_default(), h.org, h.bytes.to_string(), format!("{:.0}", h.bytes as f64 / span as f64), self.talkers_window.secs().to_string()]);
+                }
+                t
+            }
+            CurrentScreen::Connections => {
+                let mut t = ExportTable::new("connections", &["remote_ip", "hostname", "remote_port", "local_port", "state", "sockets", "country", "asn", "organization", "protocol", "packets", "bytes", "rate_bps", "last_seen_secs", "latitude", "longitude", "risk"]);
+                for c in self.sorted_connections() {
+                    let (lat, lon) = c.location.map(|(a, b)| (a.to_string(), b.to_string())).unwrap_or_default();
+                    let port = |p: Option<u16>| p.map(|p| p.to_string()).unwrap_or_default();
+                    let risk = self.peer_reputation.get(&c.remote_ip).cloned().flatten().map(|r| r.describe()).unwrap_or_default();
+                    t.push([c.remote_ip.to_string(), self.hostnames.get(&c.remote_ip).cloned().flatten().unwrap_or_default(), port(c.remote_port), port(c.local_port), c.state.clone(), c.sockets.to_string(), c.country.clone().unwrap_or_default(), c.asn_num.to_string(), c.asn_org.clone(), c.protocol.clone(), c.packet_count.to_string(), c.bytes.to_string(), format!("{:.0}", c.rate_bps), c.last_seen.elapsed().as_secs().to_string(), lat, lon, risk]);
+                }
+                t
+            }
+            CurrentScreen::Mdns => {
+                let mut t = ExportTable::new("mdns", &["service_type", "instance", "host", "ips", "port", "txt"]);
+                for svc in &self.mdns_services {
+                    let ips = svc.ips.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(" ");
+                    t.push([svc.service_type.clone(), svc.instance.clone(), svc.host.clone(), ips, svc.port.to_string(), svc.txt.join(" ")]);
+                }
+                t
+            }
+            CurrentScreen::Ssdp => {
+                let mut t = ExportTable::new("ssdp", &["ip", "friendly_name", "manufacturer", "model", "server", "location", "presentation_url", "service_types"]);
+                for d in &self.ssdp_devices {
+                    let ip = d.ip.map(|ip| ip.to_string()).unwrap_or_default();
+                    t.push([ip, d.friendly_name.clone(), d.manufacturer.clone(), d.model.clone(), d.server.clone(), d.location.clone(), d.presentation_url.clone(), d.service_types.join(" ")]);
+                }
+                t
+            }
+            CurrentScreen::Flows => {
+                let mut t = ExportTable::new("flows", &["kind", "key", "protocol", "bytes", "count"]);
+                for (ip, bytes, pkts) in &self.flows_snapshot.top_talkers {
+                    t.push(["talker".to_string(), ip.to_string(), String::new(), bytes.to_string(), pkts.to_string()]);
+                }
+                for (proto, port, bytes, flows) in &self.flows_snapshot.top_ports {
+                    t.push(["port".to_string(), port.to_string(), flows::protocol_name(*proto).to_string(), bytes.to_string(), flows.to_string()]);
+                }
+                t
+            }
+            CurrentScreen::Wifi => {
+                let mut t = ExportTable::new("wifi", &["ssid", "bssid", "channel", "band", "signal_dbm", "security", "connected"]);
+                for n in &self.wifi_networks {
+                    let channel = n.channel.map(|c| c.to_string()).unwrap_or_default();
+                    let signal = n.signal_dbm.map(|s| s.to_string()).unwrap_or_default();
+                    t.push([n.ssid.clone(), n.bssid.clone(), channel, n.band.to_string(), signal, n.security.clone(), n.connected.to_string()]);
+                }
+                t
+            }
+            CurrentScreen::Dhcp => {
+                let mut t = ExportTable::new("dhcp", &["server", "from", "type", "offered", "lease_secs", "subnet_mask", "routers", "dns", "domain", "ntp"]);
+                let ip = |ip: Option<std::net::Ipv4Addr>| ip.map(|ip| ip.to_string()).unwrap_or_default();
+                let list = |ips: &[std::net::Ipv4Addr]| ips.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(" ");
+                for r in &self.dhcp_replies {
+                    t.push([
+                        ip(r.server()), ip(r.from), r.message_type.to_string(), ip(r.offered),
+                        r.lease_secs.map(|l| l.to_string()).unwrap_or_default(), ip(r.subnet_mask),
+                        list(&r.routers), list(&r.dns), r.domain.clone(), list(&r.ntp),
+                    ]);
+                }
+                t
+            }
+            CurrentScreen::Traceroute => {
+                let mut t = ExportTable::new("traceroute", &["ttl", "host", "rtt1_ms", "rtt2_ms", "rtt3_ms"]);
+                let rtt = |r: Option<std::time::Duration>| r.map(|d| format!("{:.3}", d.as_secs_f64() * 1000.0)).unwrap_or_default();
+                for hop in self.trace_result.iter().flat_map(|r| &r.hops) {
+                    t.push([hop.ttl.to_string(), hop.host.map(|h| h.to_string()).unwrap_or_default(), rtt(hop.rtts[0]), rtt(hop.rtts[1]), rtt(hop.rtts[2])]);
+                }
+                t
+            }
+            CurrentScreen::Sweep => {
+                let mut t = ExportTable::new("sweep", &["ip", "rtt_ms", "mac"]);
+                for h in &self.sweep_hosts {
+                    t.push([h.ip.to_string(), format!("{:.3}", h.rtt.as_secs_f64() * 1000.0), h.mac.clone().unwrap_or_default()]);
+                }
+                t
+            }
+            CurrentScreen::Syslog => {
+                let mut t = ExportTable::new("syslog", &["received_utc", "from", "facility", "severity", "hostname", "app", "message"]);
+                for m in self.syslog_visible() {
+                    t.push([m.received.clone(), m.from.to_string(), m.facility_name().to_string(), m.severity_name().to_string(), m.hostname.clone(), m.app.clone(), m.text.clone()]);
+                }
+                t
+            }
+            CurrentScreen::Diagnose => {
+                let mut t = ExportTable::new("diagnose", &["check", "status", "detail"]);
+                for r in &self.diagnose_results {
+                    t.push([r.check.name(), r.status.label(), r.detail.as_str()]);
+                }
+                t
+            }
+            CurrentScreen::Devices => {
+                let mut t = ExportTable::new("devices", &["mac", "vendor", "hostname", "ip", "all_ips", "first_seen_utc", "last_seen_utc"]);
+                for d in self.devices() {
+                    let all: Vec<String> = d.ips.iter().map(|i| i.ip.to_string()).collect();
+                    t.push([d.mac.clone(), d.vendor.clone(), d.hostname.clone().unwrap_or_default(), d.ip().map(|ip| ip.to_string()).unwrap_or_default(), all.join(" "), inventory::date_label(d.first_seen), inventory::date_label(d.last_seen)]);
+                }
+                t
+            }
+            CurrentScreen::Asn => match self.asn_result.as_ref()? {
+                asn::AsnResult::As(info) => {
+                    let mut t = ExportTable::new("asn", &["asn", "holder", "prefix"]);
+                    for prefix in &info.prefixes {
+                        t.push([info.asn.to_string(), info.holder.clone(), prefix.clone()]);
+                    }
+                    t
+                }
+                asn::AsnResult::Search(_, matches) => {
+                    let mut t = ExportTable::new("asn", &["asn", "holder"]);
+                    for (number, holder) in matches {
+                        t.push([number.to_string(), holder.clone()]);
+                    }
+                    t
+                }
+                asn::AsnResult::Route(ctx) => {
+                    let mut t = ExportTable::new("asn", &["resource", "prefix", "origin", "as_path", "peers", "collectors"]);
+                    let origin = ctx.origins.iter().map(|(n, _)| format!("AS{}", n)).collect::<Vec<_>>().join(" ");
+                    for path in &ctx.paths {
+                        t.push([ctx.resource.clone(), ctx.prefix.clone().unwrap_or_default(), origin.clone(), asn::format_path(&path.path), path.peers.to_string(), path.collectors.join("; ")]);
+                    }
+                    t
+                }
+            },
+            CurrentScreen::History => {
+                let mut t = ExportTable::new("history", &["time_utc", "tool", "target", "value", "summary"]);
+                for e in &self.history_entries {
+                    t.push([e.time_label(), e.kind.as_str().to_string(), e.target.clone(), e.metric.map(|m| m.to_string()).unwrap_or_default(), e.summary.clone()]);
+                }
+                t
+            }
+        };
+        Some(table)
+    }
+
+    pub fn open_export(&mut self) {
+        self.export_status = None;
+        match self.export_table() {
+            Some(table) => self.export_input = Input::new(export::default_filename(&table)),
+            None => {
+                self.export_input.reset();
+                self.export_status = Some(Err("Nothing to export on this screen".to_string()));
+            }
+        }
+        self.show_export = true;
+    }
+
+    pub fn toggle_export_format(&mut self) {
+        self.export_input = Input::new(export::toggle_extension(self.export_input.value()));
+    }
+
+    pub fn run_export(&mut self) {
+        let Some(table) = self.export_table() else { return };
+        self.export_status = Some(export::write(self.export_input.value(), &table));
+    }
+
+    /// Connections as shown on screen, in the chosen sort order (largest / most recent first).
+    pub fn sorted_connections(&self) -> Vec<&ConnectionInfo> {
+        let mut connections: Vec<&ConnectionInfo> = self.active_connections.values().collect();
+        match self.connections_sort {
+            ConnectionSort::LastSeen => connections.sort_by_key(|c| std::cmp::Reverse(c.last_seen)),
+            ConnectionSort::Bandwidth => connections.sort_by(|a, b| b.rate_bps.total_cmp(&a.rate_bps)),
+            ConnectionSort::Packets => connections.sort_by_key(|c| std::cmp::Reverse(c.packet_count)),
+        }
+        connections
+    }
+
+    /// The highlighted peer, while it's still connected.
+    pub fn selected_connection(&self) -> Option<&ConnectionInfo> {
+        self.connections_selected.and_then(|ip| self.active_connections.get(&ip))
+    }
+
+    /// Where the highlighted peer is in the current sort order.
+    pub fn selected_connection_row(&self) -> Option<usize> {
+        let ip = self.connections_selected?;
+        self.sorted_connections().iter().position(|c| c.remote_ip == ip)
+    }
+
+    /// Highlights the peer on row `idx` as the table is sorted right now.
+    pub fn select_connection(&mut self, idx: usize) {
+        self.connections_selected = self.sorted_connections().get(idx).map(|c| c.remote_ip);
+        self.connections_table_state.select(self.selected_connection_row());
+    }
+
+    /// Cycles the Connections table through established peers, local listening sockets and top talkers.
+    pub fn toggle_connections_view(&mut self) {
+        self.connections_view = match self.connections_view {
+            ConnectionsView::Established => ConnectionsView::Listening,
+            ConnectionsView::Listening => ConnectionsView::Talkers,
+            ConnectionsView::Talkers => ConnectionsView::Established,
+        };
+        // Poll only once the view has been opened
+        if self.connections_view == ConnectionsView::Listening && self.listeners_rx.is_none() {
+            let (tx, rx) = crossbeam::channel::unbounded();
+            self.listeners_rx = Some(rx);
+            std::thread::spawn(move || {
+                listeners::ListenersTask::new(tx).run();
+            });
+        }
+    }
+
+    /// Switches the ARP screen between arp-scan results and the OS neighbor table.
+    pub fn toggle_arp_view(&mut self) {
+        self.arp_view = match self.arp_view {
+            ArpView::Scan => ArpView::Neighbors,
+            ArpView::Neighbors => ArpView::Scan,
+        };
+        if self.arp_view == ArpView::Neighbors {
+            self.start_neighbors();
+        }
+    }
+
+    /// Polls the OS neighbor table; it also feeds the device inventory.
+    pub fn start_neighbors(&mut self) {
+        if self.neighbors_rx.is_some() {
+            return;
+        }
+        let (tx, rx) = crossbeam::channel::unbounded();
+        self.neighbors_rx = Some(rx);
+        std::thread::spawn(move || {
+            neighbors::NeighborsTask::new(tx).run();
+        });
+    }
+
+    pub fn cycle_connections_sort(&mut self) {
+        self.connections_sort = self.connections_sort.next();
+    }
+
+    /// Switches peers between IPs and reverse-DNS names; lookups only start once names are shown.
+    pub fn toggle_hostnames(&mut self) {
+        self.connections_hostnames = !self.connections_hostnames;
+        self.resolve_peer_names();
+    }
+
+    /// Queues reverse lookups for peers not looked up yet.
+    fn resolve_peer_names(&mut self) {
+        if !self.connections_hostnames {
+            return;
+        }
+        // Keep the cache to recent peers
+        if self.hostnames.len() > 4096 {
+            let peers = &self.active_connections;
+            self.hostnames.retain(|ip, _| peers.contains_key(ip));
+        }
+        let resolver = self.rdns.get_or_insert_with(|| {
+            let (tx, rx) = crossbeam::channel::unbounded();
+            self.rdns_rx = Some(rx);
+            rdns::ReverseResolver::start(tx)
+        });
+        for ip in self.active_connections.keys() {
+            if !self.hostnames.contains_key(ip) {
+                self.hostnames.insert(*ip, None);
+                resolver.lookup(*ip);
+            }
+        }
+    }
+
+    /// Queues new public peers for the threat feeds in `[reputation]`, when any are set.
+    fn check_peer_reputation(&mut self) {
+        if !self.config.reputation.enabled() {
+            return;
+        }
+        if self.peer_reputation.len() > 4096 {
+            let peers = &self.active_connections;
+            self.peer_reputation.retain(|ip, _| peers.contains_key(ip));
+        }
+        if self.reputation.is_none() {
+            let (tx, rx) = crossbeam::channel::unbounded();
+            let (checker, errors) = reputation::ReputationChecker::start(&self.config.reputation, tx);
+            if !errors.is_empty() {
+                self.reputation_error = Some(format!("Blocklists not loaded: {}", errors.join("; ")));
+            }
+            self.reputation = Some(checker);
+            self.reputation_rx = Some(rx);
+        }
+        let Some(checker) = &self.reputation else { return };
+        // Private addresses aren't in any feed, and shouldn't be sent to one
+        for ip in self.active_connections.keys().filter(|ip| asn::is_routed(**ip)) {
+            if !self.peer_reputation.contains_key(ip) {
+                self.peer_reputation.insert(*ip, None);
+                checker.lookup(*ip);
+            }
+        }
+    }
+
+    fn apply_reputation(&mut self, ip: IpAddr, verdict: reputation::Reputation) {
+        if let Some(e) = &verdict.error {
+            self.reputation_error = Some(e.clone());
+        }
+        if verdict.flagged(self.config.reputation.min_score) {
+            let process = self.active_connections.get(&ip)
+                .and_then(|c| c.process.as_ref().map(|p| format!(" by {}", p)))
+                .unwrap_or_default();
+            self.raise_event_alert(Alert {
+                rule: "threat intel".to_string(),
+                message: format!("Connection{} to flagged IP {}: {}", process, ip, verdict.describe()),
+                since: alerts::timestamp(),
+            });
+        }
+        self.peer_reputation.insert(ip, Some(verdict));
+    }
+
+    /// The peer's verdict when it's in and flagged.
+    pub fn peer_flagged(&self, ip: IpAddr) -> bool {
+        matches!(self.peer_reputation.get(&ip), Some(Some(r)) if r.flagged(self.config.reputation.min_score))
+    }
+
+    /// The peer's hostname when names are shown and known, otherwise its IP.
+    pub fn peer_label(&self, ip: IpAddr) -> String {
+        match self.hostnames.get(&ip) {
+            Some(Some(name)) if self.connections_hostnames => name.clone(),
+            _ => ip.to_string(),
+        }
+    }
+
+    /// Top talkers over the selected window.
+    pub fn top_talkers(&self) -> Vec<TopTalker> {
+        self.talkers.top(self.talkers_window)
+    }
+
+    pub fn cycle_talkers_window(&mut self) {
+        self.talkers_window = self.talkers_window.next();
+    }
+
+    pub fn open_conn_actions(&mut self) {
+        if self.connections_view == ConnectionsView::Established
+            && self.selected_connection().is_some() {
+            self.conn_action_selected = 0;
+            self.show_conn_actions = true;
+        }
+    }
+
+    pub fn run_conn_action(&mut self) {
+        self.show_conn_actions = false;
+        let Some(ip) = self.selected_connection().map(|c| c.remote_ip.to_string()) else { return };
+        match CONNECTION_ACTIONS[self.conn_action_selected] {
+            "Ping" => {
+                self.stop_ping();
+                self.ping_input = Input::new(ip);
+                self.goto_screen(CurrentScreen::Ping);
+                self.start_ping();
+            }
+            "MTR" => {
+                self.stop_mtr();
+                self.mtr_input = Input::new(ip);
+                self.goto_screen(CurrentScreen::Mtr);
+                self.start_mtr();
+            }
+            "WHOIS" => self.start_whois(ip),
+            "ASN" => {
+                self.asn_input = Input::new(ip);
+                self.goto_screen(CurrentScreen::Asn);
+                self.start_asn();
+            }
+            "Copy IP" => self.yank(),
+            "Kill process" => {
+                let owner = self.selected_connection().and_then(|c| Some((c.pid?, c.process.clone()?)));
+                match owner {
+                    Some((pid, name)) => self.confirm_kill = Some(KillTarget::Process { pid, name }),
+                    None => self.flash = Some((Err(format!("No owning process known for {} (try running as root)", ip)), std::time::Instant::now())),
+                }
+            }
+            _ => {
+                if let Ok(ip) = ip.parse() {
+                    self.confirm_kill = Some(KillTarget::Connection { ip });
+                }
+            }
+        }
+    }
+
+    /// Enter on the listening view: offer to kill the selected socket's process.
+    pub fn kill_selected_listener(&mut self) {
+        if self.listeners_table_state.selected().is_none() {
+            return;
+        }
+        let Some(l) = self.listeners.get(self.listeners_selected) else { return };
+        self.confirm_kill = match l.pid {
+            Some(pid) => Some(KillTarget::Process { pid, name: l.process.clone() }),
+            None => {
+                self.flash = Some((Err(format!("No owning process known for port {} (try running as root)", l.port)), std::time::Instant::now()));
+                None
+            }
+        };
+    }
+
+    pub fn run_confirmed_kill(&mut self) {
+        if let Some(target) = self.confirm_kill.take() {
+            self.flash = Some((target.execute(), std::time::Instant::now()));
+        }
+    }
+
+    pub fn start_whois(&mut self, target: String) {
+        let (tx, rx) = crossbeam::channel::bounded(1);
+        self.whois_rx = Some(rx);
+        self.whois_result = None;
+        self.whois_scroll = 0;
+        self.whois_target = target.clone();
+        self.show_whois = true;
+        std::thread::spawn(move || {
+            whois::WhoisTask::new(target, tx).run();
+        });
+    }
+
+    /// Text for the selected row (or the screen's result) on the current screen.
+    fn yank_text(&self) -> Option<String> {
+        if let Some(tool) = self.tools.get(self.current_screen) {
+            return tool.selection();
+        }
+        match self.current_screen {
+            CurrentScreen::Dashboard | CurrentScreen::Flows | CurrentScreen::Ntp | CurrentScreen::Runner | CurrentScreen::HttpLoad | CurrentScreen::Plugin(_) => None,
+            CurrentScreen::Ping => {
+                let s = &self.ping_stats;
+                if s.sent == 0 {
+                    return None;
+                }
+                let rtt = s.avg_ms().map(|avg| format!(", rtt min/avg/max {:.1}/{:.1}/{:.1} ms", s.min_ms, avg, s.max_ms)).unwrap_or_default();
+                Some(format!("{}: {} sent, {} received, {:.1}% loss{}", self.ping_target, s.sent, s.recv, s.loss_pct(), rtt))
+            }
+            CurrentScreen::Dns if self.dns_subdomains.is_some() => {
+                let scan = self.dns_subdomains.as_ref()?;
+                let sub = scan.found.get(scan.selected)?;
+                Some(sub.name.clone())
+            }
+            CurrentScreen::Dns if self.dns_blocklist.is_some() => {
+                let check = self.dns_blocklist.as_ref()?;
+                Some(check.results.iter().map(|(list, result)| {
+                    let verdict = match result.as_ref().map(|r| &r.listing) {
+                        None => "pending".to_string(),
+                        Some(dnsbl::Listing::Clean) => "clean".to_string(),
+                        Some(dnsbl::Listing::Listed { codes, .. }) => format!("LISTED {}", codes.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(",")),
+                        Some(dnsbl::Listing::Refused(why)) => format!("refused: {}", why),
+                        Some(dnsbl::Listing::Error(e)) => format!("error: {}", e),
+                    };
+                    format!("{} {}", list, verdict)
+                }).collect::<Vec<_>>().join("\n"))
+            }
+            CurrentScreen::Dns if self.dns_mail.is_some() => match self.dns_mail.as_ref()? {
+                Ok(check) => Some(check.findings.iter().map(|f| format!("{:<5} {:?}: {}", f.record, f.severity, f.message)).collect::<Vec<_>>().join("\n")),
+                Err(e) => Some(e.clone()),
+            },
+            CurrentScreen::Dns => match &self.dns_result {
+                Some(Ok(res)) => {
+                    let records = match res {
+                        DnsResult::A(r) | DnsResult::AAAA(r) | DnsResult::MX(r) | DnsResult::TXT(r) | DnsResult::NS(r) => r,
+                    };
+                    Some(records.iter().map(|r| r.value.clone()).collect::<Vec<_>>().join("\n"))
+                }
+                _ => None,
+            },
+            CurrentScreen::Sniffer => {
+                self.sniffer_table_state.selected()?;
+                let p = self.sniffer_view().nth(self.sniffer_selected)?;
+                Some(format!("{} [{}] {} {} -> {} len={} {}", p.time, p.interface, p.protocol, p.source, p.destination, p.length, p.info))
+            }
+            CurrentScreen::Mtr => self.mtr_hops.get(self.mtr_selected_hop).map(|h| h.host.clone()),
+            CurrentScreen::Nmap => {
+                if self.nmap_output.is_empty() {
+                    return None;
+                }
+                Some(self.nmap_output.iter().cloned().collect::<Vec<_>>().join("\n"))
+            }
+            CurrentScreen::ArpScan if self.arp_view == ArpView::Neighbors => self.neighbors.entries.get(self.neighbors_selected)
+                .map(|e| format!("{} {}", e.neighbor.ip, e.neighbor.mac.as_deref().unwrap_or_default()).trim_end().to_string()),
+            CurrentScreen::ArpScan => self.arpscan_results.get(self.arpscan_selected).map(|e| e.ip.clone()),
+            CurrentScreen::Connections if self.connections_view == ConnectionsView::Listening => {
+                self.listeners.get(self.listeners_selected).map(|l| format!("{}:{}", l.address, l.port))
+            }
+            CurrentScreen::Connections if self.connections_view == ConnectionsView::Talkers => {
+                self.top_talkers().get(self.talkers_selected).map(|h| h.ip.to_string())
+            }
+            CurrentScreen::Connections => self.selected_connection().map(|c| c.remote_ip.to_string()),
+            CurrentScreen::Mdns => {
+                let svc = self.mdns_services.get(self.mdns_selected)?;
+                Some(match svc.ips.first() {
+                    Some(ip) => std::net::SocketAddr::new(*ip, svc.port).to_string(),
+                    None => svc.host.clone(),
+                })
+            }
+            CurrentScreen::Ssdp => {
+                let d = self.ssdp_devices.get(self.ssdp_selected)?;
+                Some(d.ip.map(|ip| ip.to_string()).unwrap_or_else(|| d.location.clone()))
+            }
+            CurrentScreen::History => self.history_entries.get(self.history_selected).map(|e| e.target.clone()),
+            CurrentScreen::Devices => {
+                self.devices_table_state.selected()?;
+                self.devices().get(self.devices_selected).map(|d| d.mac.clone())
+            }
+            CurrentScreen::Sweep => {
+                self.sweep_table_state.selected()?;
+                self.sweep_hosts.get(self.sweep_selected).map(|h| h.ip.to_string())
+            }
+            CurrentScreen::Syslog => {
+                self.syslog_table_state.selected()?;
+                let m = self.syslog_visible().into_iter().nth(self.syslog_selected)?;
+                Some(format!("{} {} {}.{} {}: {}", m.received, m.hostname, m.facility_name(), m.severity_name(), m.app, m.text))
+            }
+            // The whole report, ready to paste into a support ticket
+            CurrentScreen::Diagnose => {
+                let conclusion = self.diagnose_conclusion.as_ref()?;
+                let mut lines: Vec<String> = self.diagnose_results.iter()
+                    .map(|r| format!("{:<9} {:<8} {}", r.check.name(), r.status.label(), r.detail))
+                    .collect();
+                lines.push(conclusion.clone());
+                Some(lines.join("\n"))
+            }
+            CurrentScreen::Asn => {
+                self.asn_table_state.selected()?;
+                self.asn_result.as_ref()?.rows().into_iter().nth(self.asn_selected)
+            }
+            CurrentScreen::Traceroute => {
+                self.trace_table_state.selected()?;
+                self.trace_result.as_ref()?.hops.get(self.trace_selected)?.host.map(|ip| ip.to_string())
+            }
+            CurrentScreen::Dhcp => {
+                self.dhcp_table_state.selected()?;
+                self.dhcp_replies.get(self.dhcp_selected)?.server().map(|ip| ip.to_string())
+            }
+            CurrentScreen::Wifi => {
+                self.wifi_table_state.selected()?;
+                let n = self.wifi_networks.get(self.wifi_selected)?;
+                Some(format!("{} {}", n.ssid, n.bssid))
+            }
+        }
+    }
+
+    pub fn yank(&mut self) {
+        let status = match self.yank_text().filter(|t| !t.is_empty()) {
+            Some(text) => self.clipboard.copy(&text).map(|via| {
+                let first = text.lines().next().unwrap_or_default();
+                let more = if text.lines().count() > 1 { " …" } else { "" };
+                format!("Copied {}{} ({})", first, more, via)
+            }),
+            None => Err("Nothing selected to copy".to_string()),
+        };
+        self.flash = Some((status, std::time::Instant::now()));
+    }
+
+    /// Searchable text of each row on `screen`, in the order the table shows them.
+    fn search_rows(&self, screen: CurrentScreen) -> Vec<String> {
+        if let Some(tool) = self.tools.get(screen) {
+            return tool.search_rows().unwrap_or_default();
+        }
+        match screen {
+            CurrentScreen::Sniffer => self.sniffer_view()
+                .map(|p| format!("{} {} {} {} {} {} {}", p.time, p.interface, p.protocol, p.source, p.destination, p.length, p.info))
+                .collect(),
+            CurrentScreen::Connections if self.connections_view == ConnectionsView::Listening => self.listeners.iter()
+                .map(|l| format!("{} {}:{} {} {}", l.protocol, l.address, l.port, l.pid.map(|p| p.to_string()).unwrap_or_default(), l.process))
+                .collect(),
+            CurrentScreen::Connections if self.connections_view == ConnectionsView::Talkers => self.top_talkers().iter()
+                .map(|h| format!("{} {} {}", h.ip, h.country.as_deref().unwrap_or_default(), h.org))
+                .collect(),
+            CurrentScreen::Connections => self.sorted_connections().iter()
+                .map(|c| format!("{} {} {} {} AS{} {} {} {}", c.remote_ip, self.hostnames.get(&c.remote_ip).cloned().flatten().unwrap_or_default(), c.remote_port.map(|p| p.to_string()).unwrap_or_default(), c.country.as_deref().unwrap_or_default(), c.asn_num, c.asn_org, c.protocol, c.state))
+                .collect(),
+            CurrentScreen::Nmap => self.nmap_output.iter().cloned().collect(),
+            CurrentScreen::Mtr => self.mtr_hops.iter().map(|h| format!("{} {}", h.ttl, h.host)).collect(),
+            CurrentScreen::ArpScan if self.arp_view == ArpView::Neighbors => self.neighbors.entries.iter()
+                .map(|e| format!("{} {} {} {}", e.neighbor.ip, e.neighbor.mac.as_deref().unwrap_or_default(), e.neighbor.state, e.neighbor.interface))
+                .collect(),
+            CurrentScreen::ArpScan => self.arpscan_results.iter().map(|e| format!("{} {} {} {}", e.ip, self.arp_name(e), e.mac, e.vendor)).collect(),
+            CurrentScreen::Devices => self.devices().iter()
+                .map(|d| format!("{} {} {} {}", d.mac, d.vendor, d.hostname.as_deref().unwrap_or_default(), d.ips.iter().map(|i| i.ip.to_string()).collect::<Vec<_>>().join(" ")))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    fn selected_row(&self, screen: CurrentScreen) -> Option<usize> {
+        if let Some(tool) = self.tools.get(screen) {
+            return tool.selected_row();
+        }
+        match screen {
+            CurrentScreen::Sniffer => self.sniffer_table_state.selected(),
+            CurrentScreen::Connections if self.connections_view == ConnectionsView::Listening => self.listeners_table_state.selected(),
+            CurrentScreen::Connections if self.connections_view == ConnectionsView::Talkers => self.talkers_table_state.selected(),
+            CurrentScreen::Connections => self.selected_connection_row(),
+            CurrentScreen::Nmap => self.nmap_list_state.selected(),
+            CurrentScreen::Mtr => self.mtr_table_state.selected(),
+            CurrentScreen::ArpScan if self.arp_view == ArpView::Neighbors => self.neighbors_table_state.selected(),
+            CurrentScreen::ArpScan => self.arpscan_table_state.selected(),
+            CurrentScreen::Devices => self.devices_table_state.selected(),
+            _ => None,
+        }
+    }
+
+    fn select_row(&mut self, screen: CurrentScreen, idx: usize) {
+        if let Some(tool) = self.tools.get_mut(screen) {
+            tool.select_row(idx);
+            return;
+        }
+        match screen {
+            CurrentScreen::Sniffer => {
+                self.sniffer_selected = idx;
+                self.sniffer_table_state.select(Some(idx));
+            }
+            CurrentScreen::Connections if self.connections_view == ConnectionsView::Listening => {
+                self.listeners_selected = idx;
+                self.listeners_table_state.select(Some(idx));
+            }
+            CurrentScreen::Connections if self.connections_view == ConnectionsView::Talkers => {
+                self.talkers_selected = idx;
+                self.talkers_table_state.select(Some(idx));
+            }
+            CurrentScreen::Connections => self.select_connection(idx),
+            CurrentScreen::Nmap => self.nmap_list_state.select(Some(idx)),
+            CurrentScreen::Mtr => {
+                self.mtr_selected_hop = idx;
+                self.mtr_table_state.select(Some(idx));
+            }
+            CurrentScreen::ArpScan if self.arp_view == ArpView::Neighbors => {
+                self.neighbors_selected = idx;
+                self.neighbors_table_state.select(Some(idx));
+            }
+            CurrentScreen::ArpScan => {
+                self.arpscan_selected = idx;
+                self.arpscan_table_state.select(Some(idx));
+            }
+            CurrentScreen::Devices => {
+                self.devices_selected = idx;
+                self.devices_table_state.select(Some(idx));
+            }
+            _ => {}
+        }
+    }
+
+    /// The search, if it belongs to the current screen.
+    pub fn active_search(&self) -> Option<&TableSearch> {
+        self.search.as_ref().filter(|s| s.screen == self.current_screen)
+    }
+
+    /// Indices of matching rows on the search's screen.
+    pub fn search_matches(&self) -> Vec<usize> {
+        let Some(search) = &self.search else { return vec![] };
+        self.search_rows(search.screen).iter()
+            .enumerate()
+            .filter(|(_, row)| search.is_match(row))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn open_search(&mut self) {
+        let tool_searchable = self.tools.get(self.current_screen).is_some_and(|t| t.search_rows().is_some());
+        if search::SEARCHABLE.contains(&self.current_screen) || tool_searchable {
+            self.search = Some(TableSearch::new(self.current_screen));
+        }
+    }
+
+    /// Called as the query changes: keep the selection on a match, starting from the current row.
+    pub fn update_search(&mut self) {
+        let Some(screen) = self.search.as_ref().map(|s| s.screen) else { return };
+        let matches = self.search_matches();
+        let current = self.selected_row(screen);
+        if current.is_some_and(|c| matches.contains(&c)) {
+            return;
+        }
+        if let Some(idx) = search::step(&matches, current, true) {
+            self.select_row(screen, idx);
+        }
+    }
+
+    pub fn search_jump(&mut self, forward: bool) {
+        let Some(screen) = self.search.as_ref().map(|s| s.screen) else { return };
+        let matches = self.search_matches();
+        if let Some(idx) = search::step(&matches, self.selected_row(screen), forward) {
+            self.select_row(screen, idx);
+        }
+    }
+
+    pub fn commit_search(&mut self) {
+        if let Some(search) = self.search.as_mut() {
+            search.editing = false;
+            if search.query().trim().is_empty() {
+                self.search = None;
+            }
+        }
+    }
+
+    pub fn clear_search(&mut self) {
+        self.search = None;
+    }
+
+    pub fn goto_screen(&mut self, screen: CurrentScreen) {
+        self.current_screen = screen;
+        if screen == CurrentScreen::History {
+            self.refresh_history();
+        }
+    }
+
+    /// The field of `screen` that gets its keys, on screens with more than one.
+    pub fn focused(&self, screen: CurrentScreen) -> Option<Field> {
+        focus::fields(screen).get(self.focus.get(&screen).copied().unwrap_or(0)).copied()
+    }
+
+    /// Whether a plain character typed now lands in the current screen's input, in which
+    /// case the single-letter global keys (tabs, help, quit) leave it alone.
+    pub fn typing(&self) -> bool {
+        if let Some(tool) = self.tools.get(self.current_screen) {
+            return tool.typing();
+        }
+        let screen = self.current_screen;
+        match screen {
+            CurrentScreen::Ping => !self.is_pinging,
+            CurrentScreen::Dns => self.focused(screen) == Some(Field::DnsDomain),
+            CurrentScreen::Sniffer => !self.sniffer_active && self.focused(screen) == Some(Field::SnifferFilter),
+            CurrentScreen::Mtr => !self.mtr_active,
+            CurrentScreen::Nmap => !self.nmap_active,
+            CurrentScreen::ArpScan => !self.arpscan_active,
+            CurrentScreen::Sweep => !self.sweep_active,
+            CurrentScreen::Traceroute => !self.trace_active,
+            CurrentScreen::Syslog => self.focused(screen) == Some(Field::SyslogFilter) || !self.syslog_active,
+            CurrentScreen::Flows => !self.flows_active,
+            CurrentScreen::Asn | CurrentScreen::History => true,
+            _ => false,
+        }
+    }
+
+    /// Tab / Shift+Tab: the next (or previous) field of the screen, then on past the last
+    /// one to the next screen, arriving on its first field (or last, going backwards).
+    pub fn step_focus(&mut self, forward: bool) {
+        let screen = self.current_screen;
+        let current = self.focus.get(&screen).copied().unwrap_or(0);
+        let next = if forward { current + 1 } else { current.wrapping_sub(1) };
+        if next < focus::fields(screen).len() {
+            self.focus.insert(screen, next);
+            return;
+        }
+        self.cycle_screen(forward);
+        let fields = focus::fields(self.current_screen).len();
+        if fields > 0 {
+            self.focus.insert(self.current_screen, if forward { 0 } else { fields - 1 });
+        }
+    }
+
+    /// Moves to the next (or previous) tab, wrapping around.
+    pub fn cycle_screen(&mut self, forward: bool) {
+        let all = CurrentScreen::all();
+        let idx = all.iter().position(|s| *s == self.current_screen).unwrap_or(0);
+        let next = if forward { (idx + 1) % all.len() } else { (idx + all.len() - 1) % all.len() };
+        self.goto_screen(all[next]);
+    }
+
+    /// Opens a second pane showing the current screen (focus moves to the new right pane), or closes the unfocused one.
+    pub fn toggle_split(&mut self) {
+        if self.split_screen.take().is_none() {
+            self.split_screen = Some(self.current_screen);
+            self.split_focus_left = false;
+        } else {
+            self.split_focus_left = true;
+        }
+    }
+
+    pub fn focus_other_pane(&mut self) {
+        if let Some(other) = self.split_screen {
+            self.split_screen = Some(self.current_screen);
+            self.split_focus_left = !self.split_focus_left;
+            self.goto_screen(other);
+        }
+    }
+
+    pub fn open_palette(&mut self) {
+        self.show_palette = true;
+        self.palette_input.reset();
+        self.update_palette();
+    }
+
+    pub fn start_tool(&mut self, screen: CurrentScreen) {
+        if let Some(tool) = self.tools.get_mut(screen) {
+            tool.start();
+            return;
+        }
+        match screen {
+            CurrentScreen::Ping => self.start_ping(),
+            CurrentScreen::Dns => self.start_dns_lookup(),
+            CurrentScreen::Sniffer => self.start_sniffer(),
+            CurrentScreen::Mtr => self.start_mtr(),
+            CurrentScreen::Nmap => self.start_nmap(),
+            CurrentScreen::ArpScan => self.start_arpscan(),
+            CurrentScreen::Mdns => self.start_mdns(),
+            CurrentScreen::Ssdp => self.start_ssdp(),
+            CurrentScreen::Flows => self.start_flows(),
+            CurrentScreen::Wifi => self.start_wifi(),
+            CurrentScreen::Dhcp => self.start_dhcp(),
+            CurrentScreen::Traceroute => self.start_traceroute(),
+            CurrentScreen::Sweep => self.start_sweep(),
+            CurrentScreen::Syslog => self.start_syslog(),
+            CurrentScreen::Diagnose => self.start_diagnose(),
+            CurrentScreen::Asn => self.start_asn(),
+            _ => {}
+        }
+    }
+
+    pub fn stop_tool(&mut self, screen: CurrentScreen) {
+        if let Some(tool) = self.tools.get_mut(screen) {
+            tool.stop();
+            return;
+        }
+        match screen {
+            CurrentScreen::Ping => self.stop_ping(),
+            CurrentScreen::Dns => self.stop_dns_watch(),
+            CurrentScreen::Sniffer => self.stop_sniffer(),
+            CurrentScreen::Mtr => self.stop_mtr(),
+            CurrentScreen::Nmap if self.nmap_active => self.stop_nmap(),
+            CurrentScreen::ArpScan if self.arpscan_active => self.stop_arpscan(),
+            CurrentScreen::Mdns => self.stop_mdns(),
+            CurrentScreen::Ssdp => self.stop_ssdp(),
+            CurrentScreen::Flows => self.stop_flows(),
+            CurrentScreen::Wifi => self.stop_wifi(),
+            CurrentScreen::Sweep => self.stop_sweep(),
+            CurrentScreen::Syslog => self.stop_syslog(),
+            CurrentScreen::Diagnose => self.stop_diagnose(),
+            // The lookup finishes unheard
+            CurrentScreen::Asn => self.asn_rx = None,
+            _ => {}
+        }
+    }
+
+    pub fn update_palette(&mut self) {
+        self.palette_matches = palette::filter(self.palette_input.value(), &self.keymap);
+        self.palette_selected = 0;
+    }
+
+    pub fn run_palette(&mut self) {
+        let Some(entry) = self.palette_matches.get(self.palette_selected).cloned() else { return };
+        self.show_palette = false;
+
+        match entry.action {
+            PaletteAction::Goto(screen) => self.goto_screen(screen),
+            PaletteAction::Start(screen) => {
+                self.current_screen = screen;
+                self.start_tool(screen);
+            }
+            PaletteAction::Stop(screen) => self.stop_tool(screen),
+            PaletteAction::ToolOptions => self.open_tool_options(),
+            PaletteAction::Export => self.open_export(),
+            PaletteAction::AlertRules => self.open_alerts(),
+            PaletteAction::Profiles => self.open_profiles(),
+            PaletteAction::WakeOnLan => {
+                self.current_screen = CurrentScreen::ArpScan;
+                self.open_wol();
+            }
+            PaletteAction::MacVendor => self.open_oui_lookup(),
+            PaletteAction::Hostnames => self.toggle_hostnames(),
+            PaletteAction::MtrPaths => {
+                self.goto_screen(CurrentScreen::Mtr);
+                self.open_mtr_paths();
+            }
+            PaletteAction::Help => self.help = Some(HelpBrowser::default()),
+            PaletteAction::Yank => self.yank(),
+            PaletteAction::EventLog => self.show_log = !self.show_log,
+            PaletteAction::Split => self.toggle_split(),
+            PaletteAction::FocusPane => self.focus_other_pane(),
+            PaletteAction::ZoomIn => {
+                self.current_screen = CurrentScreen::Dashboard;
+                self.dash_range = self.dash_range.zoom_in();
+            }
+            PaletteAction::ZoomOut => {
+                self.current_screen = CurrentScreen::Dashboard;
+                self.dash_range = self.dash_range.zoom_out();
+            }
+            PaletteAction::EditLayout => {
+                self.current_screen = CurrentScreen::Dashboard;
+                self.open_layout();
+            }
+            PaletteAction::PingTargets => {
+                self.current_screen = CurrentScreen::Dashboard;
+                self.open_probe_targets();
+            }
+            PaletteAction::SnifferPause => {
+                self.current_screen = CurrentScreen::Sniffer;
+                self.toggle_sniffer_pause();
+            }
+            PaletteAction::SnifferClear => {
+                self.current_screen = CurrentScreen::Sniffer;
+                self.clear_sniffer();
+            }
+            PaletteAction::Theme(name) => {
+                crate::theme::set_theme(name);
+                self.config.ui.theme = name.to_string();
+                self.theme_picked = true;
+            }
+            PaletteAction::Quit => self.quit(),
+        }
+    }
+
+    fn record_interface_counters(&mut self, sample: HashMap<String, interfaces::InterfaceCounters>) {
+        let now = std::time::Instant::now();
+        for (name, counters) in sample {
+            let Some(h) = self.iface_history.get_mut(&name) else {
+                self.iface_history.insert(name, InterfaceHistory {
+                    counters,
+                    sampled: now,
+                    rx_mbps: Ring::filled(HISTORY, 0.0),
+                    tx_mbps: Ring::filled(HISTORY, 0.0),
+                    rx_pps: Ring::filled(HISTORY, 0.0),
+                    tx_pps: Ring::filled(HISTORY, 0.0),
+                });
+                continue;
+            };
+            let secs = now.duration_since(h.sampled).as_secs_f64().max(0.001);
+            let rate = |new: u64, old: u64| new.saturating_sub(old) as f64 / secs;
+            for (series, value) in [
+                (&mut h.rx_mbps, rate(counters.rx_bytes, h.counters.rx_bytes) * 8.0 / 1_000_000.0),
+                (&mut h.tx_mbps, rate(counters.tx_bytes, h.counters.tx_bytes) * 8.0 / 1_000_000.0),
+                (&mut h.rx_pps, rate(counters.rx_packets, h.counters.rx_packets)),
+                (&mut h.tx_pps, rate(counters.tx_packets, h.counters.tx_packets)),
+            ] {
+                series.push(value);
+            }
+            h.counters = counters;
+            h.sampled = now;
+        }
+    }
+
+    /// Latest (rx, tx) Mbps over all up, non-loopback interfaces, from OS counters.
+    fn os_bandwidth(&self) -> (f64, f64) {
+        self.interfaces
+            .iter()
+            .filter(|i| i.is_up() && !i.is_loopback())
+            .filter_map(|i| self.iface_history.get(&i.name))
+            .fold((0.0, 0.0), |(rx, tx), h| (rx + h.rx_mbps.back().unwrap_or(&0.0), tx + h.tx_mbps.back().unwrap_or(&0.0)))
+    }
+
+    /// Latest captured (rx, tx) Mbps, WAN and LAN together.
+    pub fn sniffer_rate(&self) -> (f64, f64) {
+        let last = |h: &VecDeque<f64>| *h.back().unwrap_or(&0.0);
+        (last(&self.wan_rx_history) + last(&self.lan_rx_history), last(&self.wan_tx_history) + last(&self.lan_tx_history))
+    }
+
+    /// Up/Down on the dashboard move through the Interfaces panel.
+    pub fn select_dash_interface(&mut self, down: bool) {
+        let last = self.interfaces.len().saturating_sub(1);
+        self.dash_iface_selected = if down { (self.dash_iface_selected + 1).min(last) } else { self.dash_iface_selected.saturating_sub(1) };
+    }
+
+    pub fn open_interface_detail(&mut self) {
+        if let Some(iface) = self.interfaces.get(self.dash_iface_selected) {
+            self.iface_detail = Some((iface.name.clone(), interfaces::link_info(&iface.name)));
+        }
+    }
+
+    pub fn open_layout(&mut self) {
+        self.show_layout = true;
+        self.layout_status = None;
+        self.layout_cursor.clamp(&self.config.dashboard.rows);
+    }
+
+    pub fn layout_move(&mut self, rows: isize, cols: isize) {
+        let c = &mut self.layout_cursor;
+        c.row = c.row.saturating_add_signed(rows);
+        c.col = c.col.saturating_add_signed(cols);
+        c.clamp(&self.config.dashboard.rows);
+    }
+
+    /// Replaces the panel under the cursor with the next kind.
+    pub fn layout_cycle(&mut self) {
+        let LayoutCursor { row, col } = self.layout_cursor;
+        if let Some(panel) = self.config.dashboard.rows.get_mut(row).and_then(|r| r.get_mut(col)) {
+            *panel = panel.next();
+            self.save_layout();
+        }
+    }
+
+    /// Adds a panel after the cursor, or on a new row below it; picks one not shown yet.
+    pub fn layout_add(&mut self, new_row: bool) {
+        let rows = &mut self.config.dashboard.rows;
+        let panel = Panel::ALL.into_iter().find(|p| !rows.iter().flatten().any(|q| q == p)).unwrap_or(Panel::Bandwidth);
+        let c = &mut self.layout_cursor;
+        if new_row || rows.is_empty() {
+            c.row = if rows.is_empty() { 0 } else { c.row + 1 };
+            c.col = 0;
+            rows.insert(c.row, vec![panel]);
+        } else {
+            c.col += 1;
+            rows[c.row].insert(c.col, panel);
+        }
+        self.save_layout();
+    }
+
+    pub fn layout_delete(&mut self) {
+        let LayoutCursor { row, col } = self.layout_cursor;
+        let rows = &mut self.config.dashboard.rows;
+        if row >= rows.len() || col >= rows[row].len() {
+            return;
+        }
+        rows[row].remove(col);
+        if rows[row].is_empty() {
+            rows.remove(row);
+        }
+        self.layout_cursor.clamp(rows);
+        self.save_layout();
+    }
+
+    /// Moves the panel under the cursor one slot left or right within its row.
+    pub fn layout_shift(&mut self, right: bool) {
+        let LayoutCursor { row, col } = self.layout_cursor;
+        let Some(r) = self.config.dashboard.rows.get_mut(row) else { return };
+        let target = if right { col + 1 } else { col.wrapping_sub(1) };
+        if target < r.len() && col < r.len() {
+            r.swap(col, target);
+            self.layout_cursor.col = target;
+            self.save_layout();
+        }
+    }
+
+    fn save_layout(&mut self) {
+        self.layout_status = Some(match self.config.save() {
+            Ok(()) => "Saved to config".to_string(),
+            Err(e) => format!("Layout active but not saved: {}", e),
+        });
+    }
+
+    /// (Re)starts one background ping per configured target; dropping the old probes ends their tasks.
+    fn start_probes(&mut self) {
+        self.dash_probes = self.config.dashboard.ping_targets.iter().map(|t| LatencyProbe::start(t)).collect();
+    }
+
+    /// Restarts the gateway ping when the default route moves, e.g. when a VPN comes up.
+    fn update_gateway(&mut self, info: gateway::GatewayInfo) {
+        let old = self.gateway_info.as_ref().and_then(|i| i.gateway.as_ref().ok().copied());
+        let new = info.gateway.as_ref().ok().copied();
+        if new != old || self.gateway_info.is_none() {
+            self.gateway_probe = new.map(|ip| LatencyProbe::start(&ip.to_string()));
+            match (&info.gateway, old) {
+                (Ok(ip), Some(_)) => tracing::info!("Default gateway changed to {}", ip),
+                (Ok(_), None) => {}
+                (Err(e), _) => tracing::warn!("Gateway: {}", e),
+            }
+        }
+        let old_public = self.gateway_info.as_ref().and_then(|i| i.public_ip.clone());
+        if let Some(Err(e)) = &info.public_ip
+            && old_public.as_ref().is_none_or(|p| p.as_ref().err() != Some(e))
+        {
+            tracing::warn!("Public IP lookup: {}", e);
+        }
+        self.gateway_info = Some(info);
+    }
+
+    pub fn open_probe_targets(&mut self) {
+        self.show_probe_targets = true;
+        self.probe_targets_status = None;
+        self.probe_targets_input = Input::new(self.config.dashboard.ping_targets.join(", "));
+    }
+
+    pub fn save_probe_targets(&mut self) {
+        let targets = match dashboard::parse_ping_targets(self.probe_targets_input.value()) {
+            Ok(targets) => targets,
+            Err(e) => {
+                self.probe_targets_status = Some(e);
+                return;
+            }
+        };
+        self.config.dashboard.ping_targets = targets;
+        self.start_probes();
+        tracing::info!("Dashboard now pings {}", self.config.dashboard.ping_targets.join(", "));
+        match self.config.save() {
+            Ok(()) => self.show_probe_targets = false,
+            Err(e) => self.probe_targets_status = Some(format!("Targets active but not saved: {}", e)),
+        }
+    }
+
+    pub fn open_alerts(&mut self) {
+        self.show_alerts = true;
+        self.alerts_input.reset();
+        self.alerts_status = None;
+        self.alerts_selected = self.alerts_selected.min(self.config.alerts.rules.len().saturating_sub(1));
+    }
+
+    pub fn add_alert_rule(&mut self) {
+        let text = self.alerts_input.value().trim().to_string();
+        if text.is_empty() {
+            return;
+        }
+        match text.parse::<AlertRule>() {
+            Ok(rule) => {
+                self.config.alerts.rules.push(rule);
+                self.alerts_selected = self.config.alerts.rules.len() - 1;
+                self.alerts_input.reset();
+                self.save_alert_rules();
+            }
+            Err(e) => self.alerts_status = Some(e),
+        }
+    }
+
+    pub fn delete_alert_rule(&mut self) {
+        if self.alerts_selected < self.config.alerts.rules.len() {
+            self.config.alerts.rules.remove(self.alerts_selected);
+            self.alerts_selected = self.alerts_selected.min(self.config.alerts.rules.len().saturating_sub(1));
+            self.save_alert_rules();
+        }
+    }
+
+    fn save_alert_rules(&mut self) {
+        self.alert_engine.reset();
+        self.alerts_status = Some(match self.config.save() {
+            Ok(()) => "Saved to config".to_string(),
+            Err(e) => format!("Rules active but not saved: {}", e),
+        });
+    }
+
+    pub fn open_profiles(&mut self) {
+        self.show_profiles = true;
+        self.profiles_input.reset();
+        self.profiles_status = None;
+        // Start on the first profile for the tool on screen
+        let tool = ProfileTool::for_screen(self.current_screen);
+        if let Some(i) = self.config.profiles.iter().position(|p| Some(p.tool) == tool) {
+            self.profiles_selected = i;
+        }
+        self.profiles_selected = self.profiles_selected.min(self.config.profiles.len().saturating_sub(1));
+    }
+
+    fn tool_input(&mut self, tool: ProfileTool) -> &mut Input {
+        match tool {
+            ProfileTool::Nmap => &mut self.nmap_input,
+            ProfileTool::Ping => &mut self.ping_input,
+            ProfileTool::Mtr => &mut self.mtr_input,
+        }
+    }
+
+    /// Saves the current tool's input under the typed name, replacing a profile of the same name.
+    pub fn save_profile(&mut self) {
+        let name = self.profiles_input.value().trim().to_string();
+        let Some(tool) = ProfileTool::for_screen(self.current_screen) else {
+            self.profiles_status = Some("Open Ping, Nmap or MTR to save its target and flags".to_string());
+            return;
+        };
+        let args = self.tool_input(tool).value().trim().to_string();
+        if args.is_empty() {
+            self.profiles_status = Some(format!("The {} input is empty", tool.label()));
+            return;
+        }
+        let profile = ScanProfile { name, tool, args };
+        match self.config.profiles.iter().position(|p| p.name == profile.name) {
+            Some(i) => {
+                self.config.profiles[i] = profile;
+                self.profiles_selected = i;
+            }
+            None => {
+                self.config.profiles.push(profile);
+                self.profiles_selected = self.config.profiles.len() - 1;
+            }
+        }
+        self.profiles_input.reset();
+        self.save_profiles();
+    }
+
+    pub fn delete_profile(&mut self) {
+        if self.profiles_selected < self.config.profiles.len() {
+            self.config.profiles.remove(self.profiles_selected);
+            self.profiles_selected = self.profiles_selected.min(self.config.profiles.len().saturating_sub(1));
+            self.save_profiles();
+        }
+    }
+
+    fn save_profiles(&mut self) {
+        self.profiles_status = Some(match self.config.save() {
+            Ok(()) => "Saved to config".to_string(),
+            Err(e) => format!("Not saved: {}", e),
         });
+    }
+
+    /// Loads the selected profile into its tool and (re)starts it.
+    pub fn run_profile(&mut self) {
+        let Some(profile) = self.config.profiles.get(self.profiles_selected).cloned() else { return };
+        match profile.tool {
+            ProfileTool::Nmap if self.nmap_active => self.stop_nmap(),
+            ProfileTool::Ping if self.is_pinging => self.stop_ping(),
+            ProfileTool::Mtr => self.stop_mtr(),
+            _ => {}
+        }
+        *self.tool_input(profile.tool) = Input::new(profile.args);
+        self.goto_screen(profile.tool.screen());
+        match profile.tool {
+            ProfileTool::Nmap => self.start_nmap(),
+            ProfileTool::Ping => self.start_ping(),
+            ProfileTool::Mtr => self.start_mtr(),
+        }
+        self.show_profiles = false;
+    }
+
+    pub fn start_sniffer(&mut self) {
+        if self.sniffer_active {
+            return;
+        }
         
-        // 3. Start Connections Monitor
-        self.start_connections_monitor();
+        let targets = self.sniffer_targets();
+        if !targets.is_empty() {
+             let filter = match CaptureFilter::parse(self.sniffer_filter_input.value()) {
+                 Ok(filter) => filter,
+                 Err(e) => {
+                     self.sniffer_error = Some(e);
+                     return;
+                 }
+             };
+             self.remember_input(CurrentScreen::Sniffer);
+             let (tx, rx) = crossbeam::channel::unbounded();
+             self.sniffer_ingest = Some(SnifferIngest::start(rx, self.config.retention.packets));
+             self.sniffer_error = None;
+
+             self.sniffer_seen = 0;
+             self.sniffer_evicted = 0;
+             self.sniffer_icmp.clear();
+             self.sniffer_paused_at = None;
+             // The new session's counters start at zero
+             self.last_packet_count = 0;
+             self.last_rx_count = 0;
+             self.last_tx_count = 0;
+             self.last_protocol_counts = [0; 4];
+             self.last_wan_rx_bytes = 0;
+             self.last_wan_tx_bytes = 0;
+             self.last_lan_rx_bytes = 0;
+             self.last_lan_tx_bytes = 0;
+             if self.config.capture.ring && !self.recording() {
+                 self.toggle_recording();
+             }
+             let triggers = self.config.capture.triggers.iter()
+                 .filter_map(|t| t.packet_filter().map(|f| (t.to_string(), f.clone())))
+                 .collect();
+             self.sniffer.start(targets, tx, filter, triggers);
+             self.sniffer_active = true;
+        }
+    }
+
+    /// Whether matching packets are being written to the pcap ring.
+    pub fn recording(&self) -> bool {
+        self.sniffer.ring.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+    }
+
+    /// Starts or stops writing matching packets to rotating pcap files; works mid-capture.
+    pub fn toggle_recording(&mut self) {
+        // Taken over by hand: no longer stops on its own
+        self.trigger_until = None;
+        self.trigger_started_sniffer = false;
+        let mut ring = self.sniffer.ring.lock().unwrap_or_else(|e| e.into_inner());
+        let message = match ring.take() {
+            Some(writer) => Ok(format!("Recording stopped: {} packets in {}", writer.packets, writer.dir().display())),
+            None => match pcap::PcapRing::new(&self.config.capture) {
+                Ok(writer) => {
+                    let message = format!("Recording to {} ({} files of {} MB kept)", writer.dir().display(), self.config.capture.ring_files, self.config.capture.ring_file_mb);
+                    *ring = Some(writer);
+                    Ok(message)
+                }
+                Err(e) => Err(e),
+            },
+        };
+        drop(ring);
+        if let Ok(message) = &message {
+            tracing::info!("{}", message);
+        }
+        self.flash = Some((message, std::time::Instant::now()));
+    }
+
+    /// A capture trigger fired: starts the capture if it isn't running and records for
+    /// `[capture] trigger_secs` from now. A recording started by hand is left alone.
+    fn fire_capture_trigger(&mut self, trigger: &str, detail: &str) {
+        let manual = self.recording() && self.trigger_until.is_none();
+        if !manual {
+            if !self.sniffer_active {
+                self.start_sniffer();
+                self.trigger_started_sniffer = self.sniffer_active;
+            }
+            if !self.recording() {
+                self.toggle_recording();
+            }
+            if self.recording() {
+                self.trigger_until = Some(std::time::Instant::now() + std::time::Duration::from_secs(self.config.capture.trigger_secs));
+            }
+        }
+        let file = self.sniffer.ring.lock().unwrap_or_else(|e| e.into_inner()).as_ref()
+            .and_then(|r| r.current().map(|p| p.display().to_string()))
+            .unwrap_or_default();
+        self.raise_event_alert(Alert {
+            rule: format!("capture trigger {}", trigger),
+            message: format!("Capture trigger '{}' fired ({}){}", trigger, detail, if file.is_empty() { String::new() } else { format!("; recording to {}", file) }),
+            since: alerts::timestamp(),
+        });
+    }
+
+    fn end_triggered_recording(&mut self) {
+        self.trigger_until = None;
+        if self.recording() {
+            self.toggle_recording();
+        }
+        if std::mem::take(&mut self.trigger_started_sniffer) && self.sniffer_active {
+            self.stop_sniffer();
+        }
+        tracing::info!("Triggered recording ended");
+    }
+
+    /// Packets captured over the last second (20 ticks).
+    pub fn sniffer_pps(&self) -> u64 {
+        self.traffic_history.iter().rev().take(20).sum()
+    }
+
+    /// Interfaces the sniffer will capture on: every up, non-loopback interface for
+    /// "All", the Tab-ticked set if there is one, otherwise the one under the cursor.
+    pub fn sniffer_targets(&self) -> Vec<String> {
+        if self.selected_interface_index >= self.interfaces.len() {
+            return self.interfaces.iter().filter(|i| i.is_up() && !i.is_loopback()).map(|i| i.name.clone()).collect();
+        }
+        if !self.sniffer_capture.is_empty() {
+            return self.sniffer_capture.clone();
+        }
+        self.interfaces.get(self.selected_interface_index).map(|i| vec![i.name.clone()]).unwrap_or_default()
+    }
+
+    pub fn toggle_sniffer_interface(&mut self) {
+        let Some(name) = self.interfaces.get(self.selected_interface_index).map(|i| i.name.clone()) else { return };
+        if let Some(pos) = self.sniffer_capture.iter().position(|n| *n == name) {
+            self.sniffer_capture.remove(pos);
+        } else {
+            self.sniffer_capture.push(name);
+        }
+    }
+
+    /// Packets the table shows, newest first. While paused, packets captured since
+    /// the pause are held back (they still go into the ring buffer).
+    pub fn sniffer_view(&self) -> impl ExactSizeIterator<Item = &sniffer::PacketSummary> {
+        let hidden = self.sniffer_paused_at.map(|at| self.sniffer_seen - at).unwrap_or(0);
+        self.sniffer_packets.iter().rev().skip(hidden as usize)
+    }
+
+    pub fn toggle_sniffer_pause(&mut self) {
+        self.sniffer_paused_at = match self.sniffer_paused_at {
+            Some(_) => None,
+            None => Some(self.sniffer_seen),
+        };
+    }
+
+    pub fn clear_sniffer(&mut self) {
+        self.sniffer_packets.clear();
+        self.sniffer_icmp.clear();
+        self.sniffer_selected = 0;
+        self.sniffer_table_state = TableState::default();
+        if self.sniffer_paused_at.is_some() {
+            self.sniffer_paused_at = Some(self.sniffer_seen);
+        }
+    }
+
+    pub fn stop_sniffer(&mut self) {
+        if self.sniffer_active {
+            self.sniffer.stop();
+            self.sniffer_active = false;
+        }
+    }
+
+    pub fn start_mtr(&mut self) {
+        self.start_mtr_seeded(Vec::new());
+    }
+
+    /// `seed` results are counted before the first live probe.
+    fn start_mtr_seeded(&mut self, seed: Vec<mtr::MtrResult>) {
+        if self.mtr_active { return; }
+        
+        let target = self.mtr_input.value().to_string();
+        if target.is_empty() { return; }
+        self.remember_input(CurrentScreen::Mtr);
+        self.mtr_editing = false;
+
+        // Ensure we don't start MTR with invalid state even if UI allowed it
+        debug_assert!(!target.trim().is_empty(), "MTR target must not be empty/whitespace");
+
+        self.mtr_hops.clear();
+        self.mtr_paths = mtr::PathTracker::default();
+        self.mtr_flows = mtr::FlowPaths::default();
+        self.mtr_error = None;
+        self.mtr_target = target.clone();
+        let (tx, rx) = crossbeam::channel::unbounded();
+        for result in seed {
+            let _ = tx.send(mtr::MtrEvent::Hop(result));
+        }
+        self.mtr_rx = Some(rx);
+        self.mtr_task.start(target, tx);
+        self.mtr_active = true;
+    }
+
+    /// Starts MTR on the finished trace's target with its probes already counted.
+    pub fn send_trace_to_mtr(&mut self) {
+        let Some(trace) = &self.trace_result else { return };
+        let target = trace.target;
+        let seed: Vec<_> = trace.hops.iter().flat_map(|h| h.as_mtr_results(target)).collect();
+        self.stop_mtr();
+        self.mtr_input = Input::new(target.to_string());
+        self.current_screen = CurrentScreen::Mtr;
+        self.start_mtr_seeded(seed);
+    }
+
+    pub fn open_mtr_paths(&mut self) {
+        self.mtr_paths_scroll = 0;
+        self.show_mtr_paths = true;
+    }
+
+    pub fn stop_mtr(&mut self) {
+        if self.mtr_active {
+            self.mtr_task.stop();
+            self.mtr_active = false;
+            self.record_mtr_run();
+        }
+    }
+
+    /// Swaps flags that need raw sockets for connect()-based equivalents, noting each change.
+    fn unprivileged_nmap_args(&mut self, target: Vec<String>) -> Vec<String> {
+        let mut args = vec![];
+        for arg in target {
+            match arg.as_str() {
+                "-sS" => {
+                    self.nmap_output.push("No raw socket access: using a TCP connect scan (-sT) instead of -sS".to_string());
+                    args.push("-sT".to_string());
+                }
+                "-O" => {
+                    self.nmap_output.push("No raw socket access: skipping OS detection (-O)".to_string());
+                }
+                _ => args.push(arg),
+            }
+        }
+        args
+    }
+
+    pub fn start_nmap(&mut self) {
+        if self.nmap_active { return; }
+        
+        let target = self.nmap_input.value().to_string();
+        if target.is_empty() { return; }
+        self.remember_input(CurrentScreen::Nmap);
+
+        self.nmap_ports.clear();
+        self.nmap_banners.clear();
+        self.nmap_banner_rx = None;
+        self.nmap_output.clear();
+        self.nmap_list_state = ListState::default();
+        self.nmap_editing = false;
+        self.nmap_error = None;
+        let mut args = match args::split(&target) {
+            Ok(args) => args,
+            Err(e) => {
+                self.nmap_error = Some(e);
+                return;
+            }
+        };
+        // `--banners` is ours, not nmap's
+        self.nmap_grab_banners = args.iter().any(|a| a == "--banners");
+        args.retain(|a| a != "--banners");
+        let targets = match nmap::Targets::parse(&args) {
+            Ok(targets) => targets,
+            Err(e) => {
+                self.nmap_error = Some(e);
+                return;
+            }
+        };
+        let args = if self.privileges.raw_sockets { args } else { self.unprivileged_nmap_args(args) };
+        let target = nmap::with_limits(&args::join(&args), &self.config.scan.nmap);
+        self.nmap_output.push(format!("Starting nmap scan on: {} ({})", target, targets.describe()));
+        self.nmap_target = target.clone();
+        
+        // Use a channel for async output
+        let (tx, rx) = crossbeam::channel::unbounded();
+        self.nmap_rx = Some(rx);
+        self.nmap_active = true;
+        let process = ProcessHandle::new();
+        self.nmap_process = Some(process.clone());
+        
+        // Spawn thread for nmap execution
+        std::thread::spawn(move || {
+            let task = nmap::NmapTask::new(target, tx, process);
+            task.run();
+        });
+    }
+
+    /// Lists the finished scan's open TCP ports and, with `--banners`, starts grabbing their banners.
+    fn finish_nmap_ports(&mut self) {
+        self.nmap_ports = nmap::parse_ports(self.nmap_output.iter().map(String::as_str)).into_iter()
+            .filter(|p| p.state == "open" && p.proto == "tcp")
+            .collect();
+        if !self.nmap_grab_banners || self.nmap_ports.is_empty() {
+            return;
+        }
+        let targets = self.nmap_ports.iter().map(|p| (p.address().to_string(), p.port)).collect();
+        let (tx, rx) = crossbeam::channel::unbounded();
+        self.nmap_banner_rx = Some(rx);
+        banner::grab_all(targets, tx);
+    }
+
+    /// Opens the flag editor on the current screen's input, or closes it if open.
+    pub fn open_tool_options(&mut self) {
+        if self.flag_editor.take().is_some() {
+            return;
+        }
+        let screen = self.current_screen;
+        if screen == CurrentScreen::Sniffer {
+            self.filter_presets = (!self.sniffer_active && self.filter_presets.is_none()).then_some(0);
+            return;
+        }
+        let specs = match self.tools.get(screen) {
+            Some(tool) => tool.options(),
+            None => flags::options(screen),
+        };
+        let Some(input) = self.screen_input(screen) else { return };
+        let value = input.value().to_string();
+        self.flag_editor = FlagEditor::new(screen, specs, &value);
+    }
+
+    /// Writes the flag editor's command back into the tool's input and closes it.
+    pub fn apply_tool_options(&mut self) {
+        let Some(editor) = self.flag_editor.take() else { return };
+        if let Some(input) = self.screen_input(editor.screen) {
+            *input = Input::new(editor.command());
+        }
+    }
+
+    /// The sniffer's Ctrl+F list as (label, filter): the presets, then recently used filters.
+    /// "Only this host" is left out when the capturing interfaces have no address.
+    pub fn filter_choices(&self) -> Vec<(String, String)> {
+        let own: Vec<String> = self.sniffer_targets().iter()
+            .filter_map(|name| self.interfaces.iter().find(|i| &i.name == name))
+            .flat_map(|i| i.ips.iter().map(|ip| format!("host {}", ip.ip())))
+            .collect();
+        let mut choices: Vec<(String, String)> = capture_filter::PRESETS.iter().filter_map(|(label, filter)| {
+            let filter = if *filter == "{host}" {
+                (!own.is_empty()).then(|| own.join(" or "))?
+            } else {
+                filter.to_string()
+            };
+            Some((label.to_string(), filter))
+        }).collect();
+        let recent = self.input_history.get(&CurrentScreen::Sniffer).into_iter().flat_map(|h| h.iter());
+        choices.extend(recent.take(10).map(|filter| ("Recent".to_string(), filter.to_string())));
+        choices
+    }
+
+    /// Puts the chosen filter into the sniffer's filter input and closes the list.
+    pub fn apply_filter_preset(&mut self) {
+        let Some(selected) = self.filter_presets.take() else { return };
+        if let Some((_, filter)) = self.filter_choices().into_iter().nth(selected) {
+            self.sniffer_filter_input = Input::new(filter);
+        }
+    }
+
+    /// Terminates the running nmap; its exit status still comes through `nmap_rx`.
+    pub fn stop_nmap(&mut self) {
+        if let Some(process) = &self.nmap_process {
+            process.stop();
+        }
+        self.nmap_active = false;
+        self.nmap_output.push("Stopping scan...".to_string());
+    }
+
+    pub fn start_arpscan(&mut self) {
+        if self.arpscan_active { return; }
+        
+        let target = self.arpscan_input.value().to_string();
+        if target.is_empty() { return; }
+
+        if arpscan::DiscoveryRequest::wanted(&target) {
+            self.start_arpwatch(&target);
+            return;
+        }
+
+        let target = arpscan::with_limits(&target, &self.config.scan.arp);
+        self.arpwatch_every = None;
+        self.arpscan_output.clear();
+        self.arpscan_error = None;
+        self.arpscan_results.clear();
+        self.arpscan_selected = 0;
+        self.arpscan_table_state.select(None);
+        self.arpscan_output.push(format!("Starting arp-scan with args: {}", target));
+        
+        // Use a channel for async output
+        let (tx, rx) = crossbeam::channel::unbounded();
+        self.arpscan_rx = Some(rx);
+        self.arpscan_active = true;
+        let process = ProcessHandle::new();
+        self.arpscan_process = Some(process.clone());
+        
+        // Spawn thread for arpscan execution
+        std::thread::spawn(move || {
+            let task = arpscan::ArpScanTask::new(target, tx, process);
+            task.run();
+        });
+    }
+
+    fn start_arpwatch(&mut self, input: &str) {
+        self.arpscan_error = None;
+        let request = match arpscan::DiscoveryRequest::parse(input, &self.interfaces, &self.config.scan.arp) {
+            Ok(request) => request,
+            Err(e) => {
+                self.arpscan_error = Some(e);
+                return;
+            }
+        };
+        self.arpscan_output.clear();
+        self.arpscan_results.clear();
+        self.arpscan_selected = 0;
+        self.arpscan_table_state.select(None);
+        self.arpwatch_sweeps = 0;
+        self.arpwatch_every = Some(request.every);
+        self.arpscan_output.push(format!("Watching {} on {}: ARP sweep every {}s, {}ms between packets, listening in between",
+            request.network, request.interface.name, request.every.as_secs(), request.gap.as_millis()));
+
+        let (tx, rx) = crossbeam::channel::unbounded();
+        let task = arpscan::DiscoveryTask::new();
+        task.start(request, tx);
+        self.arpwatch_rx = Some(rx);
+        self.arpwatch_task = Some(task);
+        self.arpscan_active = true;
+    }
+
+    /// Refreshes a host heard during continuous discovery, adding it the first time.
+    fn arpwatch_seen(&mut self, ip: std::net::Ipv4Addr, mac: String, passive: bool) {
+        let now = std::time::Instant::now();
+        let ip_text = ip.to_string();
+        match self.arpscan_results.iter_mut().find(|e| e.ip == ip_text) {
+            Some(entry) => {
+                let back = self.arpwatch_every.and_then(|every| entry.online(every)) == Some(false);
+                if back || entry.mac != mac {
+                    api::emit(&self.api_events, "arp", json!({ "ip": ip_text, "mac": mac, "vendor": entry.vendor, "online": true, "passive": passive }));
+                }
+                if entry.mac != mac {
+                    entry.vendor = oui::describe(self.oui.as_ref(), &mac);
+                    entry.mac = mac.clone();
+                }
+                entry.last_seen = Some(now);
+            }
+            None => {
+                let vendor = Some(self.mac_vendor(&mac)).filter(|v| !v.is_empty()).unwrap_or_else(|| "Unknown".to_string());
+                api::emit(&self.api_events, "arp", json!({ "ip": ip_text, "mac": mac, "vendor": vendor, "online": true, "passive": passive }));
+                self.name_arp_host(IpAddr::V4(ip));
+                self.arpscan_results.push(arpscan::ArpEntry { ip: ip_text, mac: mac.clone(), vendor, last_seen: Some(now) });
+                // Keep the table in address order as hosts trickle in
+                self.arpscan_results.sort_by_key(|e| e.ip.parse::<std::net::Ipv4Addr>().ok());
+            }
+        }
+        self.see_device(&mac, Some(IpAddr::V4(ip)), None);
+    }
+
+    /// Asks for a name for an ARP Scan host, once per address.
+    fn name_arp_host(&mut self, ip: IpAddr) {
+        if self.arp_names.contains_key(&ip) {
+            return;
+        }
+        let namer = self.arp_namer.get_or_insert_with(|| {
+            let (tx, rx) = crossbeam::channel::unbounded();
+            self.arp_names_rx = Some(rx);
+            hostnames::HostNamer::start(tx)
+        });
+        self.arp_names.insert(ip, None);
+        namer.lookup(ip);
+    }
+
+    /// The ARP Scan Hostname column for an entry, empty until a name is known.
+    pub fn arp_name(&self, entry: &arpscan::ArpEntry) -> String {
+        entry.ip.parse().ok().and_then(|ip| self.arp_names.get(&ip).cloned().flatten()).map(|n| n.name).unwrap_or_default()
+    }
+
+    pub fn stop_arpscan(&mut self) {
+        if let Some(process) = &self.arpscan_process {
+            process.stop();
+        }
+        if let Some(task) = &self.arpwatch_task {
+            task.stop();
+        }
+        self.arpscan_active = false;
+        self.arpscan_output.push("Stopping scan...".to_string());
+    }
+
+    pub fn open_wol(&mut self) {
+        // Prefill with the selected ArpScan host's MAC, if any
+        let mac = self.arpscan_results.get(self.arpscan_selected).map(|e| e.mac.clone()).unwrap_or_default();
+        self.wol_input = Input::new(mac);
+        self.wol_status = None;
+        self.show_wol = true;
+    }
+
+    /// Vendor registered for `mac`'s prefix, `(randomized)` for private MACs, or empty.
+    pub fn mac_vendor(&self, mac: &str) -> String {
+        oui::describe(self.oui.as_ref(), mac)
+    }
+
+    pub fn open_oui_lookup(&mut self) {
+        // Prefill with the selected host's MAC on the ARP screen
+        let mac = match self.arp_view {
+            ArpView::Scan => self.arpscan_results.get(self.arpscan_selected).map(|e| e.mac.clone()),
+            ArpView::Neighbors => self.neighbors.entries.get(self.neighbors_selected).and_then(|e| e.neighbor.mac.clone()),
+        };
+        self.oui_input = Input::new(mac.filter(|_| self.current_screen == CurrentScreen::ArpScan).unwrap_or_default());
+        self.show_oui_lookup = true;
+    }
+
+    pub fn next_wol_interface(&mut self) {
+        self.wol_interface_index = (self.wol_interface_index + 1) % (self.interfaces.len() + 1);
+    }
+
+    pub fn prev_wol_interface(&mut self) {
+        let count = self.interfaces.len() + 1;
+        self.wol_interface_index = (self.wol_interface_index + count - 1) % count;
+    }
+
+    pub fn wol_target_label(&self) -> String {
+        match self.wol_interface_index {
+            0 => "All (255.255.255.255)".to_string(),
+            n => match self.interfaces.get(n - 1) {
+                Some(iface) => match wol::interface_broadcast(iface) {
+                    Some((_, bcast)) => format!("{} ({})", iface.name, bcast),
+                    None => format!("{} (no IPv4)", iface.name),
+                },
+                None => "-".to_string(),
+            },
+        }
+    }
+
+    pub fn send_wol(&mut self) {
+        let iface = match self.wol_interface_index {
+            0 => None,
+            n => self.interfaces.get(n - 1),
+        };
+        let result = wol::send(self.wol_input.value(), iface);
+        let line = match &result {
+            Ok(msg) => msg.clone(),
+            Err(e) => format!("WoL failed: {}", e),
+        };
+        self.arpscan_output.push(line);
+        self.wol_status = Some(result);
     }
 
-    pub fn start_sniffer(&mut self) {
-        if self.sniffer_active {
-            return;
+    pub fn start_mdns(&mut self) {
+        if self.mdns_active { return; }
+
+        self.mdns_services.clear();
+        self.mdns_error = None;
+        self.mdns_selected = 0;
+        self.mdns_table_state.select(None);
+
+        let (tx, rx) = crossbeam::channel::unbounded();
+        self.mdns_rx = Some(rx);
+        self.mdns_task.start(tx);
+        self.mdns_active = true;
+    }
+
+    pub fn stop_mdns(&mut self) {
+        if self.mdns_active {
+            self.mdns_task.stop();
+            self.mdns_active = false;
         }
-        
-        if let Some(interface) = self.interfaces.get(self.selected_interface_index) {
-             let (tx, rx) = crossbeam::channel::unbounded();
-             self.sniffer_rx = Some(rx);
-             
-             assert!(self.selected_interface_index < self.interfaces.len(), "Selected interface index out of bounds");
-             
-             let filter = self.sniffer_filter_input.value().to_string();
-             self.sniffer.start(interface.name.clone(), tx, filter);
-             self.sniffer_active = true;
+    }
+
+    pub fn start_traceroute(&mut self) {
+        if self.trace_active { return; }
+        self.trace_result = None;
+        self.trace_error = None;
+        self.trace_selected = 0;
+        self.trace_table_state.select(None);
+
+        let (tx, rx) = crossbeam::channel::unbounded();
+        self.trace_rx = Some(rx);
+        traceroute::trace(self.trace_input.value().to_string(), tx);
+        self.trace_active = true;
+    }
+
+    pub fn start_sweep(&mut self) {
+        if self.sweep_active { return; }
+        let request = match sweep::SweepRequest::parse(self.sweep_input.value(), &self.config.scan.sweep) {
+            Ok(r) => r,
+            Err(e) => {
+                self.sweep_error = Some(e);
+                return;
+            }
+        };
+        self.sweep_error = None;
+        self.sweep_hosts.clear();
+        self.sweep_total = request.hosts().len();
+        self.sweep_probed = 0;
+        self.sweep_selected = 0;
+        self.sweep_table_state.select(None);
+
+        let (tx, rx) = crossbeam::channel::unbounded();
+        self.sweep_rx = Some(rx);
+        self.sweep_task.start(request, tx);
+        self.sweep_active = true;
+    }
+
+    pub fn stop_sweep(&mut self) {
+        if self.sweep_active {
+            self.sweep_task.stop();
+            self.sweep_active = false;
+            self.sweep_rx = None;
         }
     }
 
-    pub fn stop_sniffer(&mut self) {
-        if self.sniffer_active {
-            self.sniffer.stop();
-            self.sniffer_active = false;
+    pub fn start_syslog(&mut self) {
+        if self.syslog_active { return; }
+        let port = match self.syslog_input.value().trim().parse::<u16>() {
+            Ok(p) if p > 0 => p,
+            _ => {
+                self.syslog_error = Some(format!("Invalid port: {}", self.syslog_input.value()));
+                return;
+            }
+        };
+        self.syslog_error = None;
+
+        let (tx, rx) = crossbeam::channel::unbounded();
+        self.syslog_rx = Some(rx);
+        self.syslog_listener.start(port, tx);
+        self.syslog_active = true;
+    }
+
+    pub fn stop_syslog(&mut self) {
+        if self.syslog_active {
+            self.syslog_listener.stop();
+            self.syslog_active = false;
+            self.syslog_rx = None;
         }
     }
 
-    pub fn start_mtr(&mut self) {
-        if self.mtr_active { return; }
-        
-        let target = self.mtr_input.value().to_string();
-        if target.is_empty() { return; }
-        
-        // Ensure we don't start MTR with invalid state even if UI allowed it
-        debug_assert!(!target.trim().is_empty(), "MTR target must not be empty/whitespace");
+    pub fn start_diagnose(&mut self) {
+        if self.diagnose_active { return; }
+        self.diagnose_results.clear();
+        self.diagnose_conclusion = None;
+        let (tx, rx) = crossbeam::channel::unbounded();
+        self.diagnose_rx = Some(rx);
+        diagnose::start(tx);
+        self.diagnose_active = true;
+    }
+
+    /// Dropping the receiver ends the checks after the one in progress.
+    pub fn stop_diagnose(&mut self) {
+        self.diagnose_active = false;
+        self.diagnose_running = None;
+        self.diagnose_rx = None;
+    }
+
+    /// Messages passing the severity and text filters, newest first.
+    pub fn syslog_visible(&self) -> Vec<&syslog::SyslogMessage> {
+        let filter = self.syslog_filter_input.value();
+        self.syslog_messages.iter().rev()
+            .filter(|m| m.severity <= self.syslog_min_severity && m.matches(filter))
+            .collect()
+    }
+
+    /// Left shows only more severe messages, Right shows more.
+    pub fn shift_syslog_severity(&mut self, more: bool) {
+        self.syslog_min_severity = if more { (self.syslog_min_severity + 1).min(7) } else { self.syslog_min_severity.saturating_sub(1) };
+        self.syslog_selected = 0;
+        self.syslog_table_state.select(None);
+    }
+
+    /// Adds swept hosts with a known MAC to the ARP Scan table, keeping arp-scan's own rows.
+    fn merge_sweep_into_arp(&mut self) {
+        let hosts: Vec<_> = self.sweep_hosts.iter().filter_map(|h| Some((h.ip, h.mac.clone()?))).collect();
+        for (ip, mac) in &hosts {
+            self.see_device(mac, Some(IpAddr::V4(*ip)), None);
+            self.name_arp_host(IpAddr::V4(*ip));
+        }
+        for host in &self.sweep_hosts {
+            let (ip, Some(mac)) = (host.ip.to_string(), &host.mac) else { continue };
+            if !self.arpscan_results.iter().any(|e| e.ip == ip) {
+                let vendor = Some(self.mac_vendor(mac)).filter(|v| !v.is_empty()).unwrap_or_else(|| "(ping sweep)".to_string());
+                self.arpscan_results.push(arpscan::ArpEntry { ip, mac: mac.clone(), vendor, last_seen: None });
+            }
+        }
+    }
+
+    /// Prefills the Nmap target with the picked subdomains, or all that resolve.
+    pub fn send_subdomains_to_nmap(&mut self) {
+        let Some(scan) = &self.dns_subdomains else { return };
+        let targets = scan.targets();
+        if targets.is_empty() { return; }
+        self.nmap_input = Input::new(targets.join(" "));
+        self.current_screen = CurrentScreen::Nmap;
+    }
+
+    /// Prefills the Nmap target with every live host from the sweep.
+    pub fn send_sweep_to_nmap(&mut self) {
+        if self.sweep_hosts.is_empty() { return; }
+        let targets: Vec<String> = self.sweep_hosts.iter().map(|h| h.ip.to_string()).collect();
+        self.nmap_input = Input::new(targets.join(" "));
+        self.current_screen = CurrentScreen::Nmap;
+    }
+
+    pub fn start_dhcp(&mut self) {
+        if self.dhcp_active { return; }
+        let Some(iface) = self.interfaces.get(self.dhcp_interface_index) else { return };
+
+        self.dhcp_replies.clear();
+        self.dhcp_selected = 0;
+        self.dhcp_table_state.select(None);
+        self.dhcp_error = None;
+
+        let Some(mac) = iface.mac.map(|m| m.octets()) else {
+            self.dhcp_error = Some(format!("{} has no MAC address", iface.name));
+            return;
+        };
+        let client_ip = iface.ips.iter().find_map(|ip| match ip.ip() {
+            IpAddr::V4(v4) => Some(v4),
+            _ => None,
+        });
+        if self.dhcp_inform && client_ip.is_none() {
+            self.dhcp_error = Some(format!("INFORM needs an IPv4 address on {}", iface.name));
+            return;
+        }
 
-        self.mtr_hops.clear();
         let (tx, rx) = crossbeam::channel::unbounded();
-        self.mtr_rx = Some(rx);
-        self.mtr_task.start(target, tx);
-        self.mtr_active = true;
+        self.dhcp_rx = Some(rx);
+        dhcp::probe(iface.name.clone(), mac, client_ip, self.dhcp_inform, tx);
+        self.dhcp_active = true;
     }
 
-    pub fn stop_mtr(&mut self) {
-        if self.mtr_active {
-            self.mtr_task.stop();
-            self.mtr_active = false;
+    /// Distinct servers that answered the last probe; more than one usually means a rogue.
+    pub fn dhcp_servers(&self) -> Vec<std::net::Ipv4Addr> {
+        let mut servers: Vec<_> = self.dhcp_replies.iter().filter_map(|r| r.server()).collect();
+        servers.sort();
+        servers.dedup();
+        servers
+    }
+
+    pub fn start_wifi(&mut self) {
+        if self.wifi_active { return; }
+
+        self.wifi_error = None;
+        self.wifi_rssi_history.clear();
+        self.wifi_table_state.select(None);
+        self.wifi_selected = 0;
+
+        let (tx, rx) = crossbeam::channel::unbounded();
+        self.wifi_rx = Some(rx);
+        self.wifi_task.start(tx);
+        self.wifi_active = true;
+    }
+
+    pub fn stop_wifi(&mut self) {
+        if self.wifi_active {
+            self.wifi_task.stop();
+            self.wifi_active = false;
         }
     }
 
-    pub fn start_nmap(&mut self) {
-        if self.nmap_active { return; }
-        
-        let target = self.nmap_input.value().to_string();
-        if target.is_empty() { return; }
+    pub fn start_ssdp(&mut self) {
+        if self.ssdp_active { return; }
+
+        self.ssdp_devices.clear();
+        self.ssdp_error = None;
+        self.ssdp_selected = 0;
+        self.ssdp_table_state.select(None);
 
-        self.nmap_output.clear();
-        self.nmap_output.push_back(format!("Starting nmap scan on: {}", target));
-        
-        // Use a channel for async output
         let (tx, rx) = crossbeam::channel::unbounded();
-        self.nmap_rx = Some(rx);
-        self.nmap_active = true;
-        
-        // Spawn thread for nmap execution
-        std::thread::spawn(move || {
-            let task = nmap::NmapTask::new(target, tx);
-            task.run();
-        });
+        self.ssdp_rx = Some(rx);
+        self.ssdp_task.start(tx);
+        self.ssdp_active = true;
     }
 
-    pub fn get_tool_options(&self) -> Vec<(&'static str, &'static str, &'static str)> {
-        // Returns (Flag, Description, Template to Insert)
-        match self.current_screen {
-            CurrentScreen::Ping => vec![
-                ("-i", "Interval (seconds)", " -i 0.5"),
-                ("-s", "Payload Size (bytes)", " -s 128"),
-                ("-c", "Count (limit)", " -c 5"),
-            ],
-            CurrentScreen::Mtr => vec![
-                ("-i", "Interval (seconds)", " -i 1.0"),
-                ("-m", "Max Hops", " -m 30"),
-                ("-c", "Cycles", " -c 10"),
-            ],
-            CurrentScreen::Nmap => vec![
-                ("-p", "Ports (e.g. 80,443)", " -p 80,443"),
-                ("-F", "Fast Scan", " -F"),
-                ("-sV", "Service Version", " -sV"),
-                ("-Pn", "No Ping", " -Pn"),
-                ("-O", "OS Detection", " -O"),
-            ],
-            CurrentScreen::ArpScan => vec![
-                ("-l", "Localnet", " -l"),
-                ("-I <iface>", "Interface", " -I en0"),
-                ("-q", "Quiet", " -q"),
-                ("-r", "Retry", " -r 3"),
-            ],
-            _ => vec![]
+    pub fn stop_ssdp(&mut self) {
+        if self.ssdp_active {
+            self.ssdp_task.stop();
+            self.ssdp_active = false;
         }
     }
 
-    pub fn stop_nmap(&mut self) {
-        self.nmap_active = false;
-        self.nmap_rx = None;
-        self.nmap_output.push_back("Scan stopped/detached.".to_string());
+    pub fn start_flows(&mut self) {
+        if self.flows_active { return; }
+
+        let port = match self.flows_input.value().trim().parse::<u16>() {
+            Ok(p) => p,
+            Err(_) => {
+                self.flows_error = Some(format!("Invalid port: {}", self.flows_input.value()));
+                return;
+            }
+        };
+
+        self.flows_error = None;
+        self.flows_snapshot = flows::FlowSnapshot::default();
+        let (tx, rx) = crossbeam::channel::unbounded();
+        self.flows_rx = Some(rx);
+        self.flow_collector.start(port, tx);
+        self.flows_active = true;
     }
 
-    pub fn start_arpscan(&mut self) {
-        if self.arpscan_active { return; }
-        
-        let target = self.arpscan_input.value().to_string();
-        if target.is_empty() { return; }
+    pub fn stop_flows(&mut self) {
+        if self.flows_active {
+            self.flow_collector.stop();
+            self.flows_active = false;
+        }
+    }
 
-        self.arpscan_output.clear();
-        self.arpscan_results.clear();
-        self.arpscan_output.push_back(format!("Starting arp-scan with args: {}", target));
-        
-        // Use a channel for async output
+    pub fn start_geoip(&mut self) {
         let (tx, rx) = crossbeam::channel::unbounded();
-        self.arpscan_rx = Some(rx);
-        self.arpscan_active = true;
-        
-        // Spawn thread for arpscan execution
+        self.geoip_rx = Some(rx);
+        let manager = geoip::GeoIpManager::new(self.config.geoip.clone(), tx);
         std::thread::spawn(move || {
-            let task = arpscan::ArpScanTask::new(target, tx);
-            task.run();
+            manager.run();
         });
     }
 
-    pub fn stop_arpscan(&mut self) {
-        self.arpscan_active = false;
-        self.arpscan_rx = None;
-        self.arpscan_output.push_back("Scan stopped/detached.".to_string());
-    }
-
     pub fn start_connections_monitor(&mut self) {
+        let (raw_tx, raw_rx) = crossbeam::channel::unbounded();
         let (tx, rx) = crossbeam::channel::unbounded();
         self.connections_rx = Some(rx);
-        let task = connections::ConnectionsTask::new(tx);
+        let task = connections::ConnectionsTask::new(raw_tx);
         std::thread::spawn(move || {
             task.run();
         });
+        ingest::annotate_connections(raw_rx, self.geoip_lookups.clone(), tx);
     }
 
     // ... ping methods ...
@@ -659,21 +4448,125 @@ impl App {
         };
     }
 
+    pub fn prev_dns_record_type(&mut self) {
+        self.dns_record_type = match self.dns_record_type {
+            RecordType::AAAA => RecordType::A,
+            RecordType::MX => RecordType::AAAA,
+            RecordType::TXT => RecordType::MX,
+            RecordType::NS => RecordType::TXT,
+            _ => RecordType::NS,
+        };
+    }
+
     pub fn start_dns_lookup(&mut self) {
-        let domain = self.dns_input.value().to_string();
-        if domain.is_empty() { return; }
-        
+        if self.dns_input.value().trim().is_empty() { return; }
+        self.remember_input(CurrentScreen::Dns);
+        self.dns_watch = None;
+        self.dns_result = None; // Clear previous
+        self.dns_mail = None;
+        self.dns_blocklist = None;
+        self.dns_subdomains = None;
+        let request = match dns::DnsRequest::parse(self.dns_input.value()) {
+            Ok(request) => request,
+            Err(e) => {
+                self.dns_result = Some(Err(e));
+                return;
+            }
+        };
+
+        if request.subdomains() {
+            let (tx, rx) = crossbeam::channel::unbounded();
+            self.dns_subdomains = Some(subdomains::SubdomainScan::new(request.domain.clone()));
+            self.dns_subdomains_rx = Some(rx);
+            subdomains::enumerate(subdomains::SubdomainRequest { domain: request.domain, crt_sh: request.crt_sh, wordlist: request.wordlist }, tx);
+            return;
+        }
+        if request.blocklist {
+            let lists = self.config.dnsbl.lists.clone();
+            let (tx, rx) = mpsc::channel(lists.len() + 1);
+            self.dns_blocklist = Some(dnsbl::BlocklistCheck::new(request.domain.clone(), &lists));
+            self.dns_blocklist_rx = Some(rx);
+            dnsbl::check(request.domain, lists, tx);
+            return;
+        }
+        if request.mail {
+            let (tx, rx) = mpsc::channel(1);
+            self.dns_mail_rx = Some(rx);
+            tokio::spawn(async move {
+                let _ = tx.send(mailcheck::check(&request.domain, request.dkim.as_deref()).await).await;
+            });
+            return;
+        }
         let record_type = self.dns_record_type;
+        self.dns_query = format!("{} {}", request.domain, record_type);
+        self.dns_watch = request.watch.map(|interval| dns::DnsWatch::new(request.domain.clone(), record_type, interval));
+        self.query_dns(request.domain, record_type);
+    }
+
+    fn query_dns(&mut self, domain: String, record_type: RecordType) {
         let (tx, rx) = mpsc::channel(1);
         self.dns_rx = Some(rx);
-        self.dns_result = None; // Clear previous
-
         tokio::spawn(async move {
+            let started = std::time::Instant::now();
             let res = dns::resolve(&domain, record_type).await;
-            let _ = tx.send(res).await;
+            let _ = tx.send((res, started.elapsed())).await;
+        });
+    }
+
+    pub fn start_asn(&mut self) {
+        if self.asn_rx.is_some() { return; }
+        let query = match asn::AsnQuery::parse(self.asn_input.value()) {
+            Ok(query) => query,
+            Err(e) => {
+                self.asn_error = Some(e);
+                return;
+            }
+        };
+        self.remember_input(CurrentScreen::Asn);
+        self.asn_error = None;
+        self.asn_result = None;
+        self.asn_selected = 0;
+        self.asn_table_state.select(None);
+        let (tx, rx) = mpsc::channel(1);
+        self.asn_rx = Some(rx);
+        tokio::spawn(async move {
+            let _ = tx.send(asn::lookup(query).await).await;
         });
     }
 
+    /// Ctrl+N on an MTR hop: how the world routes to that hop, on the ASN screen.
+    pub fn check_hop_routing(&mut self) {
+        let Some(hop) = self.mtr_hops.get(self.mtr_selected_hop) else { return };
+        let ip = match hop.host.parse::<IpAddr>() {
+            Ok(ip) if !asn::is_routed(ip) => Err(format!("{} is a private address; it isn't in the global routing table", ip)),
+            Ok(ip) => Ok(ip),
+            Err(_) => Err(format!("Hop {} has no address to look up", hop.ttl)),
+        };
+        match ip {
+            Ok(ip) => {
+                self.asn_input = Input::new(ip.to_string());
+                self.asn_rx = None;
+                self.goto_screen(CurrentScreen::Asn);
+                self.start_asn();
+            }
+            Err(e) => self.flash = Some((Err(e), std::time::Instant::now())),
+        }
+    }
+
+    /// Enter on a selected search match: shows that network's prefixes.
+    pub fn open_asn_match(&mut self) {
+        let Some(asn::AsnResult::Search(_, matches)) = &self.asn_result else { return };
+        if self.asn_table_state.selected().is_none() { return; }
+        let Some((number, _)) = matches.get(self.asn_selected) else { return };
+        self.asn_input = Input::new(format!("AS{}", number));
+        self.start_asn();
+    }
+
+    /// Ends watch mode; a query in flight still lands.
+    pub fn stop_dns_watch(&mut self) {
+        self.dns_watch = None;
+    }
+
     pub fn start_ping(&mut self) {
         if self.is_pinging {
             return; // Already pinging, maybe stop? 
@@ -683,9 +4576,13 @@ impl App {
         if target.is_empty() {
             return;
         }
+        self.remember_input(CurrentScreen::Ping);
 
         self.ping_history.clear();
         self.ping_rtt_history.clear();
+        self.ping_stats = PingStats::default();
+        self.ping_heatmap = LatencyHeatmap::default();
+        self.ping_target = target.clone();
         let (tx, rx) = mpsc::channel(100);
         self.ping_rx = Some(rx);
         self.is_pinging = true;
@@ -699,9 +4596,340 @@ impl App {
     pub fn stop_ping(&mut self) {
         self.is_pinging = false;
         self.ping_rx = None; // Drop receiver, sender will error and stop loop
+        self.finish_ping_session();
+    }
+
+    fn finish_ping_session(&mut self) {
+        let stats = std::mem::take(&mut self.ping_stats);
+        let target = self.ping_target.clone();
+        self.record_ping_stats(&target, &stats);
+    }
+
+    fn record_ping_stats(&mut self, target: &str, stats: &PingStats) {
+        if stats.sent == 0 {
+            return;
+        }
+        let summary = match stats.avg_ms() {
+            Some(avg) => format!("{}/{} replies, {:.1}% loss, avg {:.1} ms", stats.recv, stats.sent, stats.loss_pct(), avg),
+            None => format!("0/{} replies, 100% loss", stats.sent),
+        };
+        let details = format!(
+            "Sent: {}\nReceived: {}\nLoss: {:.1}%\nMin: {:.2} ms\nAvg: {:.2} ms\nMax: {:.2} ms",
+            stats.sent, stats.recv, stats.loss_pct(), stats.min_ms, stats.avg_ms().unwrap_or(0.0), stats.max_ms
+        );
+        self.record_history(HistoryKind::Ping, target, &summary, &details, stats.avg_ms());
+    }
+
+    fn record_dns_result(&mut self, target: &str, result: &Result<DnsResult, String>) {
+        let (summary, details) = match result {
+            Ok(res) => {
+                let (label, records) = res.records();
+                let lines: Vec<String> = records.iter().map(|r| format!("{}  (TTL {})", r.value, r.ttl)).collect();
+                let first = records.first().map(|r| format!(": {}", r.value)).unwrap_or_default();
+                (format!("{} {} record(s){}", records.len(), label, first), lines.join("\n"))
+            }
+            Err(e) => ("Lookup failed".to_string(), e.clone()),
+        };
+        self.record_history(HistoryKind::Dns, target, &summary, &details, None);
+    }
+
+    fn record_nmap_scan(&mut self) {
+        let lines: Vec<String> = self.nmap_output.iter().cloned().collect();
+        let target = self.nmap_target.clone();
+        self.record_scan(&target, &lines);
+    }
+
+    fn record_scan(&mut self, target: &str, lines: &[String]) {
+        let ports = nmap::parse_ports(lines.iter().map(String::as_str));
+        let open: Vec<String> = ports.iter().filter(|p| p.state == "open").map(|p| format!("{}/{}", p.port, p.proto)).collect();
+        let summary = match open.len() {
+            0 => "No open ports found".to_string(),
+            n => format!("{} open: {}", n, open.join(", ")),
+        };
+        let details = lines.join("\n");
+        let count = open.len() as f64;
+        if let Some(id) = self.record_history(HistoryKind::Scan, target, &summary, &details, Some(count))
+            && let Some(Err(e)) = self.history.as_mut().map(|store| store.record_scan_ports(id, &ports)) {
+            self.history_error = Some(format!("Failed to save scanned ports: {}", e));
+        }
+    }
+
+    fn record_mtr_run(&mut self) {
+        let Some(dest) = self.mtr_hops.last() else { return };
+        let summary = format!("{} hops, {} avg {} ms, {:.1}% loss", self.mtr_hops.len(), dest.host, dest.avg, dest.loss);
+        let metric = Some(dest.avg as f64);
+        let mut lines = vec![format!("{:>3}  {:<40} {:>6} {:>5} {:>6} {:>6} {:>6}", "TTL", "Host", "Loss%", "Sent", "Avg", "Best", "Worst")];
+        for hop in &self.mtr_hops {
+            lines.push(format!("{:>3}  {:<40} {:>5.1}% {:>5} {:>6} {:>6} {:>6}", hop.ttl, hop.host, hop.loss, hop.sent, hop.avg, hop.best, hop.worst));
+        }
+        let target = self.mtr_target.clone();
+        self.record_history(HistoryKind::Mtr, &target, &summary, &lines.join("\n"), metric);
+    }
+
+    /// Records a background run to history and raises an alert when it differs from the previous run.
+    fn finish_scheduled_run(&mut self, run: FinishedRun) {
+        let name = self.config.schedules.get(run.index).map(|s| s.name.clone()).unwrap_or_default();
+        match &run.outcome {
+            Ok(RunOutcome::Scan { target, lines }) => self.record_scan(target, lines),
+            Ok(RunOutcome::Dns { target, result }) => self.record_dns_result(target, result),
+            Ok(RunOutcome::Ping { target, stats }) => self.record_ping_stats(target, stats),
+            Err(e) => {
+                tracing::error!("Schedule '{}': {}", name, e);
+                self.flash = Some((Err(format!("Schedule '{}': {}", name, e)), std::time::Instant::now()));
+            }
+        }
+        let Some(change) = run.change else { return };
+        self.raise_event_alert(Alert {
+            rule: format!("schedule {}", name),
+            message: format!("{}: {}", name, change),
+            since: alerts::timestamp(),
+        });
+    }
+
+    /// One-off alerts for things that happened rather than thresholds crossed; they are
+    /// notified and logged like rule alerts but never stay active.
+    fn raise_event_alert(&mut self, alert: Alert) {
+        tracing::warn!("Alert raised: {}", alert.message);
+        alerts::notify(&self.config.alerts, &alert);
+        api::emit(&self.api_events, "alerts", json!({ "raised": alert.message, "rule": alert.rule }));
+        self.flash = Some((Err(alert.message.clone()), std::time::Instant::now()));
+        self.alert_log.push(format!("{} RAISED  {}", alert.since, alert.message));
+    }
+
+    pub fn open_inventory(&mut self) {
+        let config = self.config.history.enabled.then_some(&self.config.history);
+        match Inventory::open(config).or_else(|e| {
+            self.inventory_error = Some(format!("Inventory not saved: {:#}", e));
+            Inventory::open(None)
+        }) {
+            Ok(inventory) => self.inventory = Some(inventory),
+            Err(e) => self.inventory_error = Some(format!("Inventory disabled: {:#}", e)),
+        }
+    }
+
+    /// Devices in the inventory, in table order.
+    pub fn devices(&self) -> &[inventory::Device] {
+        self.inventory.as_ref().map(|i| i.devices.as_slice()).unwrap_or_default()
+    }
+
+    /// Adds a MAC seen by any tool to the inventory, alerting on devices never seen before.
+    fn see_device(&mut self, mac: &str, ip: Option<IpAddr>, hostname: Option<String>) {
+        let vendor = self.mac_vendor(mac);
+        let Some(inventory) = &mut self.inventory else { return };
+        let sighting = inventory::Sighting { mac: mac.to_string(), ip, hostname, vendor };
+        match inventory.record(sighting) {
+            Ok(Some(device)) => {
+                let ip = device.ip().map(|ip| ip.to_string()).unwrap_or_default();
+                let what = [device.mac.as_str(), device.hostname.as_deref().unwrap_or_default(), device.vendor.as_str(), ip.as_str()]
+                    .into_iter().filter(|s| !s.is_empty()).collect::<Vec<_>>().join(", ");
+                api::emit(&self.api_events, "devices", json!({ "new": true, "mac": device.mac, "ip": ip, "vendor": device.vendor, "hostname": device.hostname }));
+                if self.config.inventory.alert_new {
+                    self.raise_event_alert(Alert {
+                        rule: "new device".to_string(),
+                        message: format!("New device on the network: {}", what),
+                        since: alerts::timestamp(),
+                    });
+                } else {
+                    tracing::info!("New device on the network: {}", what);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => self.inventory_error = Some(format!("Inventory: {:#}", e)),
+        }
+    }
+
+    pub fn acknowledge_devices(&mut self) {
+        if let Some(inventory) = &mut self.inventory {
+            inventory.acknowledge();
+        }
+    }
+
+    pub fn open_history(&mut self) {
+        if self.history.is_none() && self.history_error.is_none() && self.config.history.enabled {
+            match HistoryStore::open(&self.config.history) {
+                Ok(store) => {
+                    for screen in CurrentScreen::ALL {
+                        if let Some(inputs) = input_kind(screen).and_then(|kind| store.inputs(kind).ok()) {
+                            self.input_history.insert(screen, InputHistory::new(inputs));
+                        }
+                    }
+                    self.history = Some(store);
+                }
+                Err(e) => self.history_error = Some(format!("History disabled: {:#}", e)),
+            }
+        }
+    }
+
+    fn record_history(&mut self, kind: HistoryKind, target: &str, summary: &str, details: &str, metric: Option<f64>) -> Option<i64> {
+        self.open_history();
+        let store = self.history.as_ref()?;
+        let id = match store.record(kind, target, summary, details, metric) {
+            Ok(id) => Some(id),
+            Err(e) => {
+                self.history_error = Some(format!("Failed to save result: {}", e));
+                None
+            }
+        };
+        self.refresh_history();
+        id
+    }
+
+    pub fn refresh_history(&mut self) {
+        let Some(store) = &self.history else { return };
+        match store.list(self.history_input.value(), 500) {
+            Ok(entries) => self.history_entries = entries,
+            Err(e) => self.history_error = Some(e.to_string()),
+        }
+        self.history_selected = self.history_selected.min(self.history_entries.len().saturating_sub(1));
+        self.select_history(self.history_selected);
+    }
+
+    pub fn select_history(&mut self, index: usize) {
+        self.history_selected = index;
+        self.history_table_state.select(if self.history_entries.is_empty() { None } else { Some(index) });
+        self.history_trend = match (&self.history, self.history_entries.get(index)) {
+            (Some(store), Some(entry)) => store.trend(entry.kind, &entry.target).unwrap_or_default(),
+            _ => vec![],
+        };
+        self.update_scan_diff();
+    }
+
+    pub fn toggle_history_compare(&mut self) {
+        let selected = self.history_entries.get(self.history_selected).cloned();
+        self.history_compare = match (&self.history_compare, selected) {
+            (Some(cur), Some(sel)) if cur.id == sel.id => None,
+            (_, sel) => sel,
+        };
+        self.update_scan_diff();
+    }
+
+    /// Diffs the marked and selected entries when both are port scans, older one as the baseline.
+    fn update_scan_diff(&mut self) {
+        self.history_scan_diff = None;
+        let (Some(store), Some(cmp), Some(sel)) = (&self.history, &self.history_compare, self.history_entries.get(self.history_selected)) else { return };
+        if cmp.id == sel.id || cmp.kind != HistoryKind::Scan || sel.kind != HistoryKind::Scan {
+            return;
+        }
+        // Scans saved before ports were stored only have nmap's text output
+        let ports = |e: &HistoryEntry| match store.scan_ports(e.id) {
+            Ok(ports) if !ports.is_empty() => ports,
+            _ => nmap::parse_ports(e.details.lines()),
+        };
+        let (old, new) = if (cmp.timestamp, cmp.id) < (sel.timestamp, sel.id) { (cmp, sel) } else { (sel, cmp) };
+        self.history_scan_diff = Some(nmap::diff_ports(&ports(old), &ports(new)));
+    }
+
+    /// Loads the selected entry back into its tool and starts it.
+    pub fn rerun_history(&mut self) {
+        let Some(entry) = self.history_entries.get(self.history_selected).cloned() else { return };
+        match entry.kind {
+            HistoryKind::Ping => {
+                self.stop_ping();
+                self.ping_input = Input::new(entry.target);
+                self.current_screen = CurrentScreen::Ping;
+                self.start_ping();
+            }
+            HistoryKind::Dns => {
+                // Target is stored as "<domain> <record type>"
+                let (domain, rtype) = entry.target.rsplit_once(' ').unwrap_or((&entry.target, "A"));
+                self.dns_record_type = rtype.parse().unwrap_or(RecordType::A);
+                self.dns_input = Input::new(domain.to_string());
+                self.current_screen = CurrentScreen::Dns;
+                self.start_dns_lookup();
+            }
+            HistoryKind::Scan => {
+                if self.nmap_active {
+                    self.stop_nmap();
+                }
+                self.nmap_input = Input::new(entry.target);
+                self.current_screen = CurrentScreen::Nmap;
+                self.start_nmap();
+            }
+            HistoryKind::Mtr => {
+                self.stop_mtr();
+                self.mtr_input = Input::new(entry.target);
+                self.current_screen = CurrentScreen::Mtr;
+                self.start_mtr();
+            }
+            HistoryKind::Capture => {
+                self.stop_sniffer();
+                self.sniffer_filter_input = Input::new(entry.target);
+                self.current_screen = CurrentScreen::Sniffer;
+                self.start_sniffer();
+            }
+        }
+    }
+
+    /// Tabs, theme, sniffer interfaces and inputs, for the next launch.
+    fn session(&mut self) -> Session {
+        let mut inputs = BTreeMap::new();
+        for screen in CurrentScreen::all() {
+            if let Some(input) = self.screen_input(screen).map(|i| i.value().to_string()) {
+                inputs.insert(screen.slug().to_string(), input);
+            }
+        }
+        Session {
+            screen: Some(self.current_screen.slug().to_string()),
+            split: self.split_screen.map(|s| s.slug().to_string()),
+            theme: (self.theme_picked && !theme::no_color()).then(|| self.config.ui.theme.clone()),
+            interface: self.interfaces.get(self.selected_interface_index).map(|i| i.name.clone()),
+            capture: self.sniffer_capture.clone(),
+            inputs,
+        }
+    }
+
+    fn restore_session(&mut self, session: Session) {
+        let screen = |slug: &Option<String>| CurrentScreen::all().into_iter().find(|s| Some(s.slug()) == slug.as_deref());
+        for (slug, value) in &session.inputs {
+            if let Some(input) = screen(&Some(slug.clone())).and_then(|s| self.screen_input(s)) {
+                *input = Input::new(value.clone());
+            }
+        }
+        // Interfaces that have gone since are dropped; a missing cursor one means "All"
+        self.selected_interface_index = match &session.interface {
+            Some(name) => self.interfaces.iter().position(|i| i.name == *name).unwrap_or(self.selected_interface_index),
+            None if session.screen.is_some() => self.interfaces.len(),
+            None => self.selected_interface_index,
+        };
+        self.sniffer_capture = session.capture.into_iter().filter(|n| self.interfaces.iter().any(|i| i.name == *n)).collect();
+        if let Some(name) = session.theme.filter(|_| !theme::no_color())
+            && theme::set_theme(&name)
+        {
+            self.config.ui.theme = name;
+            self.theme_picked = true;
+        }
+        if let Some(screen) = screen(&session.screen) {
+            self.goto_screen(screen);
+        }
+        self.split_screen = screen(&session.split).filter(|s| *s != self.current_screen);
     }
 
     pub fn quit(&mut self) {
+        if self.config.ui.restore_session {
+            let session = self.session();
+            if let Err(e) = session::save(&session) {
+                tracing::warn!("Session not saved: {}", e);
+            }
+        }
+        // Scans would otherwise outlive us
+        for process in self.nmap_process.iter().chain(&self.arpscan_process) {
+            process.stop();
+        }
+        self.scheduler.stop_all();
+        self.tools.stop_all();
+        shutdown::request();
         self.should_quit = true;
     }
 }
+
+/// Where a screen's inputs are kept in the history database.
+fn input_kind(screen: CurrentScreen) -> Option<HistoryKind> {
+    match screen {
+        CurrentScreen::Ping => Some(HistoryKind::Ping),
+        CurrentScreen::Dns => Some(HistoryKind::Dns),
+        CurrentScreen::Mtr => Some(HistoryKind::Mtr),
+        CurrentScreen::Nmap => Some(HistoryKind::Scan),
+        CurrentScreen::Sniffer => Some(HistoryKind::Capture),
+        _ => None,
+    }
+}
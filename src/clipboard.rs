@@ -0,0 +1,38 @@
+use std::io::Write;
+
+use base64::Engine;
+
+/// System clipboard via arboard, falling back to an OSC 52 escape sequence
+/// (handled by the terminal itself) over SSH or when no display is available.
+#[derive(Default)]
+pub struct Clipboard {
+    // Kept alive: on X11/Wayland the contents are served by this handle and vanish when it drops
+    system: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    /// Copies `text`, returning which mechanism was used.
+    pub fn copy(&mut self, text: &str) -> Result<&'static str, String> {
+        let remote = std::env::var_os("SSH_TTY").is_some() || std::env::var_os("SSH_CONNECTION").is_some();
+        if !remote {
+            if self.system.is_none() {
+                self.system = arboard::Clipboard::new().ok();
+            }
+            if let Some(cb) = self.system.as_mut()
+                && cb.set_text(text).is_ok()
+            {
+                return Ok("clipboard");
+            }
+        }
+
+        osc52(text).map_err(|e| format!("Clipboard unavailable: {}", e))?;
+        Ok("OSC 52")
+    }
+}
+
+fn osc52(text: &str) -> std::io::Result<()> {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    let mut out = std::io::stdout();
+    write!(out, "\x1b]52;c;{}\x07", encoded)?;
+    out.flush()
+}
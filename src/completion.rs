@@ -0,0 +1,91 @@
+use std::collections::VecDeque;
+
+// Inputs remembered per tool, in memory and in the history database
+pub const HISTORY_LEN: usize = 100;
+
+/// A tool's earlier inputs, recalled shell-style: Up goes back in time, Down forward and
+/// finally to whatever was being typed before Up.
+#[derive(Debug, Default)]
+pub struct InputHistory {
+    /// Newest first
+    entries: VecDeque<String>,
+    /// Index into `entries` while recalling
+    cursor: Option<usize>,
+    draft: String,
+}
+
+impl InputHistory {
+    /// From stored inputs, newest first.
+    pub fn new(entries: Vec<String>) -> Self {
+        Self { entries: entries.into_iter().take(HISTORY_LEN).collect(), cursor: None, draft: String::new() }
+    }
+
+    /// Records a run's input as the newest, moving it up if it was already there.
+    pub fn push(&mut self, input: &str) {
+        let input = input.trim();
+        self.cursor = None;
+        if input.is_empty() {
+            return;
+        }
+        self.entries.retain(|e| e != input);
+        self.entries.push_front(input.to_string());
+        self.entries.truncate(HISTORY_LEN);
+    }
+
+    /// The input before the one shown; `current` is kept to come back to.
+    pub fn older(&mut self, current: &str) -> Option<&str> {
+        let next = match self.cursor {
+            None => {
+                self.draft = current.to_string();
+                0
+            }
+            Some(i) => i + 1,
+        };
+        let entry = self.entries.get(next)?;
+        self.cursor = Some(next);
+        Some(entry)
+    }
+
+    /// The input after the one shown, then the draft; `None` when not recalling.
+    pub fn newer(&mut self) -> Option<&str> {
+        match self.cursor? {
+            0 => {
+                self.cursor = None;
+                Some(&self.draft)
+            }
+            i => {
+                self.cursor = Some(i - 1);
+                self.entries.get(i - 1).map(String::as_str)
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(String::as_str)
+    }
+}
+
+/// Host names from the hosts file, without the localhost entries every system has.
+pub fn hosts_file() -> Vec<String> {
+    let path = if cfg!(windows) { r"C:\Windows\System32\drivers\etc\hosts" } else { "/etc/hosts" };
+    let Ok(text) = std::fs::read_to_string(path) else { return vec![] };
+    let mut names = vec![];
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        for name in line.split_whitespace().skip(1) {
+            if !name.starts_with("localhost") && !name.starts_with("ip6-") && !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names
+}
+
+/// The first candidate that `input` is the start of (ignoring case) and is longer than it.
+pub fn suggest<'a>(input: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    if input.trim().is_empty() {
+        return None;
+    }
+    let input = input.to_lowercase();
+    candidates.into_iter().find(|c| c.len() > input.len() && c.to_lowercase().starts_with(&input))
+}
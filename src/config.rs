@@ -0,0 +1,420 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::alerts::AlertRule;
+use crate::dashboard::{self, Panel};
+use crate::profiles::ScanProfile;
+use crate::scheduler::Schedule;
+use crate::screens::command::CommandToolConfig;
+use crate::screens::runner::CommandTemplate;
+use crate::tools::{dnsbl, gateway};
+use crate::triggers::CaptureTrigger;
+use crate::units::RateUnit;
+
+// Loaded from $NETOPS_CONFIG, or $XDG_CONFIG_HOME/netops/config.toml (~/.config/netops/config.toml).
+// Every section is optional; missing keys fall back to defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub metrics: MetricsConfig,
+    pub api: ApiConfig,
+    pub alerts: AlertsConfig,
+    pub history: HistoryConfig,
+    pub log: LogConfig,
+    pub ui: UiConfig,
+    pub slo: SloConfig,
+    pub retention: RetentionConfig,
+    pub geoip: GeoIpConfig,
+    pub oui: OuiConfig,
+    pub inventory: InventoryConfig,
+    pub dashboard: DashboardConfig,
+    pub scan: ScanConfig,
+    pub dnsbl: DnsblConfig,
+    pub reputation: ReputationConfig,
+    pub capture: CaptureConfig,
+    /// Saved targets + flags, run from the profiles popup
+    pub profiles: Vec<ScanProfile>,
+    /// Tools run in the background on an interval, recorded to history
+    pub schedules: Vec<Schedule>,
+    /// External commands shown as their own tabs, with their output parsed into a table
+    pub plugins: Vec<CommandToolConfig>,
+    /// Command lines with placeholders, run from the Runner screen
+    pub commands: Vec<CommandTemplate>,
+    /// Action name -> keys, e.g. `up = ["up", "k"]`. Replaces that action's defaults.
+    pub keys: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    pub enabled: bool,
+    pub listen: String,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: "127.0.0.1:9898".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    pub enabled: bool,
+    pub listen: String,
+    /// Required as `Authorization: Bearer <token>` or `?token=`; generated and saved when unset
+    pub token: Option<String>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: "127.0.0.1:9899".to_string(),
+            token: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertsConfig {
+    pub bell: bool,
+    pub desktop: bool,
+    pub webhook: Option<String>,
+    pub rules: Vec<AlertRule>,
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            bell: true,
+            desktop: false,
+            webhook: None,
+            rules: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    pub enabled: bool,
+    /// Defaults to $XDG_DATA_HOME/netops/history.db
+    pub path: Option<PathBuf>,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self { enabled: true, path: None }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    /// Also append to a log file; the in-app event log is always on
+    pub file: bool,
+    /// Defaults to $XDG_DATA_HOME/netops/netops.log
+    pub path: Option<PathBuf>,
+    /// Lowest level written to the file: error, warn, info, debug or trace
+    pub level: String,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self { file: true, path: None, level: "info".to_string() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiConfig {
+    pub theme: String,
+    /// Emoji country flags; false shows the bare country code
+    pub flags: bool,
+    pub rate_unit: RateUnit,
+    /// KiB/MiB (steps of 1024) instead of KB/MB (steps of 1000)
+    pub binary_prefixes: bool,
+    /// Capture mouse events; the terminal's own text selection then needs Shift held
+    pub mouse: bool,
+    /// The kitty keyboard protocol where the terminal supports it, so Alt and Ctrl
+    /// combinations arrive unambiguously instead of as escape sequences
+    pub enhanced_keyboard: bool,
+    /// Reopen the last session's tabs, inputs, interfaces and palette-picked theme
+    pub restore_session: bool,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            theme: "neon".to_string(),
+            flags: true,
+            rate_unit: RateUnit::default(),
+            binary_prefixes: false,
+            mouse: false,
+            enhanced_keyboard: true,
+            restore_session: true,
+        }
+    }
+}
+
+/// Targets the ping, MTR and dashboard charts mark violations of; 0 turns one off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SloConfig {
+    pub latency_ms: f64,
+    pub jitter_ms: f64,
+    pub loss_percent: f64,
+}
+
+impl Default for SloConfig {
+    fn default() -> Self {
+        Self { latency_ms: 100.0, jitter_ms: 30.0, loss_percent: 1.0 }
+    }
+}
+
+/// How many items each tool keeps in memory; the oldest are dropped past these.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// Sniffed packets
+    pub packets: usize,
+    /// Ping replies, listed and charted
+    pub ping: usize,
+    /// Latency samples charted per MTR hop
+    pub hop_history: usize,
+    /// Lines of nmap and arp-scan output
+    pub scan_output: usize,
+    pub syslog: usize,
+    /// Raised and cleared alerts in the alert log
+    pub alerts: usize,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self { packets: 1000, ping: 100, hop_history: 100, scan_output: 1000, syslog: 2000, alerts: 100 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeoIpConfig {
+    /// Defaults to $XDG_DATA_HOME/netops/GeoLite2-ASN.mmdb, then /usr/share/GeoIP and /var/lib/GeoIP
+    pub asn_db: Option<PathBuf>,
+    /// GeoLite2-Country (or City) database, searched the same way
+    pub country_db: Option<PathBuf>,
+    /// `{edition}` and `{license_key}` are substituted; downloads are off when unset
+    pub download_url: Option<String>,
+    pub license_key: Option<String>,
+    pub account_id: Option<String>,
+    pub update_days: u64,
+    /// (lat, lon) the globe draws connection arcs from
+    pub home: Option<(f64, f64)>,
+}
+
+impl Default for GeoIpConfig {
+    fn default() -> Self {
+        Self {
+            asn_db: None,
+            country_db: None,
+            download_url: None,
+            license_key: None,
+            account_id: None,
+            update_days: 7,
+            home: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InventoryConfig {
+    /// Raise an alert (notify, log, API) when a device is seen for the first time
+    pub alert_new: bool,
+}
+
+impl Default for InventoryConfig {
+    fn default() -> Self {
+        Self { alert_new: true }
+    }
+}
+
+/// IEEE MAC prefix list used to name the vendor behind a MAC address.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OuiConfig {
+    /// Defaults to $XDG_DATA_HOME/netops/oui.txt, then the files arp-scan, ieee-data, Wireshark or nmap install
+    pub path: Option<PathBuf>,
+    /// Downloads are off when unset
+    pub download_url: Option<String>,
+    pub update_days: u64,
+}
+
+impl Default for OuiConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            download_url: None,
+            update_days: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DashboardConfig {
+    /// Panels per row, top to bottom
+    pub rows: Vec<Vec<Panel>>,
+    /// Hosts the latency and jitter panels ping, one line each
+    pub ping_targets: Vec<String>,
+    /// STUN server (`host:port`) or `https://` URL the gateway panel asks for our public
+    /// address; empty to skip the lookup
+    pub public_ip_source: String,
+}
+
+impl Default for DashboardConfig {
+    fn default() -> Self {
+        Self {
+            rows: dashboard::default_rows(),
+            ping_targets: dashboard::default_ping_targets(),
+            public_ip_source: gateway::DEFAULT_PUBLIC_IP_SOURCE.to_string(),
+        }
+    }
+}
+
+/// DNS blocklists the DNS screen's `--bl` checks an address against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DnsblConfig {
+    /// Zones such as `zen.spamhaus.org`, asked all at once
+    pub lists: Vec<String>,
+}
+
+impl Default for DnsblConfig {
+    fn default() -> Self {
+        Self { lists: dnsbl::default_lists() }
+    }
+}
+
+/// Threat feeds Connections checks peers against; off while neither a key nor a file is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReputationConfig {
+    /// AbuseIPDB API key; each new peer costs one lookup from the daily quota
+    pub abuseipdb_key: Option<String>,
+    /// Files of addresses or CIDR prefixes, one per line (Spamhaus DROP, FireHOL...)
+    pub blocklists: Vec<PathBuf>,
+    /// AbuseIPDB confidence from which a peer is flagged
+    pub min_score: u8,
+    /// Only reports this recent count towards the score
+    pub max_age_days: u32,
+}
+
+impl ReputationConfig {
+    pub fn enabled(&self) -> bool {
+        self.abuseipdb_key.as_ref().is_some_and(|k| !k.is_empty()) || !self.blocklists.is_empty()
+    }
+}
+
+impl Default for ReputationConfig {
+    fn default() -> Self {
+        Self { abuseipdb_key: None, blocklists: vec![], min_score: 50, max_age_days: 90 }
+    }
+}
+
+/// Continuous capture to rotating pcap files while the sniffer runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptureConfig {
+    /// Write files with every capture, without turning it on from the Sniffer screen
+    pub ring: bool,
+    /// Defaults to $XDG_DATA_HOME/netops/captures
+    pub ring_dir: Option<PathBuf>,
+    pub ring_file_mb: u64,
+    /// A file older than this is closed too; 0 rotates on size only
+    pub ring_file_secs: u64,
+    /// Files kept; the oldest are deleted
+    pub ring_files: usize,
+    /// Conditions that start recording (and the capture, if it isn't running)
+    pub triggers: Vec<CaptureTrigger>,
+    /// How long a triggered recording runs after the trigger last fired
+    pub trigger_secs: u64,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self { ring: false, ring_dir: None, ring_file_mb: 100, ring_file_secs: 3600, ring_files: 10, triggers: vec![], trigger_secs: 300 }
+    }
+}
+
+/// Default pacing for the scanners, so large scans don't saturate links or trip an IDS.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanConfig {
+    pub nmap: ScanLimits,
+    pub sweep: ScanLimits,
+    pub arp: ScanLimits,
+}
+
+/// Unset values leave the tool's own default; flags typed into the input always win.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanLimits {
+    /// Probes in flight at once (arp-scan sends one at a time and ignores this)
+    pub concurrency: Option<usize>,
+    /// Packets per second
+    pub rate: Option<u32>,
+    /// Per-probe timeout in milliseconds
+    pub timeout_ms: Option<u64>,
+}
+
+pub fn data_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/share")))?;
+    Some(base.join("netops"))
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+    Some(base.join("netops"))
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    std::env::var_os("NETOPS_CONFIG")
+        .map(PathBuf::from)
+        .or_else(|| config_dir().map(|d| d.join("config.toml")))
+}
+
+impl Config {
+    pub fn load() -> Result<Config> {
+        let Some(path) = config_path() else {
+            return Ok(Config::default());
+        };
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("Invalid config {}", path.display()))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = config_path().context("No config directory available")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string_pretty(self)?;
+        std::fs::write(&path, text).with_context(|| format!("Failed to write config {}", path.display()))
+    }
+}
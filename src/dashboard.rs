@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::ring::Ring;
+use crate::series::TieredSeries;
+use crate::tools::ping::{PingResult, PingTask};
+
+// Samples kept for the live charts and the recent loss ratio
+pub const HISTORY: usize = 100;
+
+/// A dashboard tile. Config names are the snake_case variant names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Panel {
+    Bandwidth,
+    LanTraffic,
+    Connections,
+    Latency,
+    Jitter,
+    PacketRate,
+    ProtocolMix,
+    Interfaces,
+    TopAsns,
+    Countries,
+    Gateway,
+    TopTalkers,
+}
+
+impl Panel {
+    pub const ALL: [Panel; 12] = [
+        Self::Bandwidth,
+        Self::LanTraffic,
+        Self::Connections,
+        Self::Latency,
+        Self::Jitter,
+        Self::PacketRate,
+        Self::ProtocolMix,
+        Self::Interfaces,
+        Self::TopAsns,
+        Self::Countries,
+        Self::Gateway,
+        Self::TopTalkers,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Bandwidth => "Internet Traffic",
+            Self::LanTraffic => "LAN Traffic",
+            Self::Connections => "Total Connections",
+            Self::Latency => "Ping Latency",
+            Self::Jitter => "Jitter",
+            Self::PacketRate => "Packet Rate",
+            Self::ProtocolMix => "Protocol Mix",
+            Self::Interfaces => "Interfaces",
+            Self::TopAsns => "Top ASNs",
+            Self::Countries => "Countries",
+            Self::Gateway => "Gateway & ISP",
+            Self::TopTalkers => "Top Talkers",
+        }
+    }
+
+    /// Charts stretch to fill their row; lists only need a few lines.
+    pub fn is_chart(&self) -> bool {
+        !matches!(self, Self::Interfaces | Self::TopAsns | Self::Countries | Self::Gateway | Self::TopTalkers)
+    }
+
+    pub fn next(&self) -> Panel {
+        let i = Self::ALL.iter().position(|p| p == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+/// The original fixed layout, plus the protocol mix and gateway.
+pub fn default_rows() -> Vec<Vec<Panel>> {
+    vec![
+        vec![Panel::Bandwidth, Panel::Connections],
+        vec![Panel::Latency, Panel::Jitter, Panel::ProtocolMix],
+        vec![Panel::Interfaces, Panel::Gateway, Panel::TopAsns, Panel::Countries],
+    ]
+}
+
+/// What the latency and jitter panels probe until `[dashboard] ping_targets` says otherwise.
+pub fn default_ping_targets() -> Vec<String> {
+    vec!["1.1.1.1".to_string()]
+}
+
+/// Splits the target editor's text on commas and spaces; at least one target is required.
+pub fn parse_ping_targets(text: &str) -> Result<Vec<String>, String> {
+    let mut targets: Vec<String> = vec![];
+    for target in text.split([',', ' ']).map(str::trim).filter(|t| !t.is_empty()) {
+        if target.starts_with('-') {
+            return Err(format!("'{}' is not a host", target));
+        }
+        if !targets.iter().any(|t| t == target) {
+            targets.push(target.to_string());
+        }
+    }
+    if targets.is_empty() {
+        return Err("Enter at least one target, e.g. 192.168.1.1, 8.8.8.8".to_string());
+    }
+    Ok(targets)
+}
+
+/// One background ping charted on the dashboard. The ping task ends when the probe is dropped.
+pub struct LatencyProbe {
+    pub target: String,
+    rx: mpsc::Receiver<Result<PingResult, String>>,
+    pub latency: Ring<u64>,
+    pub jitter: Ring<u64>,
+    pub latency_series: TieredSeries,
+    pub jitter_series: TieredSeries,
+    pub sent: u64,
+    pub lost: u64,
+    outcomes: Ring<bool>,
+}
+
+impl LatencyProbe {
+    pub fn start(target: &str) -> Self {
+        let (tx, rx) = mpsc::channel(100);
+        let task = PingTask { target: target.to_string(), tx };
+        tokio::spawn(async move {
+            task.run().await;
+        });
+        Self {
+            target: target.to_string(),
+            rx,
+            latency: Ring::filled(HISTORY, 0),
+            jitter: Ring::filled(HISTORY, 0),
+            latency_series: TieredSeries::new(),
+            jitter_series: TieredSeries::new(),
+            sent: 0,
+            lost: 0,
+            outcomes: Ring::new(HISTORY),
+        }
+    }
+
+    pub fn poll(&mut self) {
+        while let Ok(result) = self.rx.try_recv() {
+            self.sent += 1;
+            if result.is_err() {
+                self.lost += 1;
+            }
+            self.outcomes.push(result.is_ok());
+
+            let Ok(res) = result else { continue };
+            let time = res.time.as_millis() as u64;
+            let jitter = time.abs_diff(self.last_latency());
+            self.jitter.push(jitter);
+            self.latency.push(time);
+            self.latency_series.push(time as f64);
+            self.jitter_series.push(jitter as f64);
+        }
+    }
+
+    pub fn last_latency(&self) -> u64 {
+        self.latency.back().copied().unwrap_or(0)
+    }
+
+    pub fn last_jitter(&self) -> u64 {
+        self.jitter.back().copied().unwrap_or(0)
+    }
+
+    /// Share of the last `n` probes that got no reply; `None` before the first one.
+    pub fn recent_loss(&self, n: usize) -> Option<f64> {
+        let recent: Vec<_> = self.outcomes.iter().rev().take(n).collect();
+        (!recent.is_empty()).then(|| recent.iter().filter(|ok| !***ok).count() as f64 / recent.len() as f64)
+    }
+
+    /// Share of the last 100 probes that got no reply.
+    pub fn loss_ratio(&self) -> f64 {
+        if self.outcomes.is_empty() {
+            return 0.0;
+        }
+        self.outcomes.iter().filter(|ok| !**ok).count() as f64 / self.outcomes.len() as f64
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Health {
+    Ok,
+    Degraded,
+    Down,
+    Unknown,
+}
+
+/// Where along LAN -> router -> ISP things break, from the gateway ping and the WAN targets'
+/// last 10 probes.
+pub fn diagnose(gateway: Option<&LatencyProbe>, wan: &[LatencyProbe]) -> (Health, &'static str) {
+    let Some(gateway) = gateway else { return (Health::Down, "No default gateway: LAN or Wi-Fi is down") };
+    let Some(gw_loss) = gateway.recent_loss(10) else { return (Health::Unknown, "Checking...") };
+    if gw_loss >= 0.5 {
+        return (Health::Down, "Router not answering: check LAN / Wi-Fi");
+    }
+    let wan_loss: Vec<f64> = wan.iter().filter_map(|p| p.recent_loss(10)).collect();
+    if wan_loss.is_empty() {
+        return (Health::Unknown, "Router OK, no WAN targets");
+    }
+    if wan_loss.iter().all(|l| *l >= 0.5) {
+        (Health::Down, "Router OK, internet down: ISP or modem")
+    } else if gw_loss > 0.0 {
+        (Health::Degraded, "Loss to the router: LAN / Wi-Fi problem")
+    } else if wan_loss.iter().any(|l| *l > 0.0) {
+        (Health::Degraded, "Loss past the router: likely ISP")
+    } else {
+        (Health::Ok, "LAN, router and ISP OK")
+    }
+}
+
+/// Cursor position in the layout editor.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LayoutCursor {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl LayoutCursor {
+    /// Keeps the cursor on an existing panel after rows change.
+    pub fn clamp(&mut self, rows: &[Vec<Panel>]) {
+        self.row = self.row.min(rows.len().saturating_sub(1));
+        let len = rows.get(self.row).map(|r| r.len()).unwrap_or(0);
+        self.col = self.col.min(len.saturating_sub(1));
+    }
+}
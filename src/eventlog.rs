@@ -0,0 +1,134 @@
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crossbeam::channel::{Receiver, Sender};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+use crate::config::{self, LogConfig};
+
+// Oldest entries drop off the pane; the file keeps everything
+const MAX_ENTRIES: usize = 1000;
+
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub time: String,
+    pub level: Level,
+    /// Module that logged it, e.g. `mtr` or `app`
+    pub source: String,
+    pub message: String,
+}
+
+/// What the app has logged since startup, newest last. Filled by a tracing layer, drained on tick.
+pub struct EventLog {
+    rx: Receiver<LogEntry>,
+    pub entries: VecDeque<LogEntry>,
+    /// Warnings and errors logged while the pane was hidden
+    pub unseen: usize,
+    pub file: Option<PathBuf>,
+}
+
+impl EventLog {
+    pub fn drain(&mut self, visible: bool) {
+        while let Ok(entry) = self.rx.try_recv() {
+            if !visible && entry.level <= Level::WARN {
+                self.unseen += 1;
+            }
+            if self.entries.len() >= MAX_ENTRIES {
+                self.entries.pop_front();
+            }
+            self.entries.push_back(entry);
+        }
+        if visible {
+            self.unseen = 0;
+        }
+    }
+}
+
+/// Installs the global tracing subscriber: our own events go to the log pane, and to
+/// `netops.log` in the data directory unless `[log] file = false`. Only a bad `level` is fatal;
+/// a log file that can't be opened is reported in the pane instead.
+pub fn init(config: &LogConfig) -> Result<EventLog, String> {
+    let level: Level = config.level.parse().map_err(|_| format!("Invalid [log] level '{}' (error, warn, info, debug or trace)", config.level))?;
+    let (tx, rx) = crossbeam::channel::unbounded();
+
+    let pane = PaneLayer { tx }.with_filter(Targets::new().with_target("netops", Level::INFO));
+
+    let mut file_error = None;
+    let path = config.path.clone().or_else(|| config::data_dir().map(|d| d.join("netops.log"))).filter(|_| config.file);
+    let file = path.as_ref().and_then(|path| {
+        let opened = path.parent().map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::OpenOptions::new().create(true).append(true).open(path));
+        match opened {
+            Ok(file) => Some(file),
+            Err(e) => {
+                file_error = Some(format!("Cannot open log file {}: {}", path.display(), e));
+                None
+            }
+        }
+    });
+    let opened_path = file.as_ref().and(path);
+    let file_layer = file.map(|file| {
+        tracing_subscriber::fmt::layer()
+            .with_writer(Mutex::new(file))
+            .with_ansi(false)
+            // Dependencies (resolver, HTTP client) only when something goes wrong
+            .with_filter(Targets::new().with_target("netops", level).with_default(Level::WARN))
+    });
+
+    tracing_subscriber::registry().with(pane).with(file_layer).try_init().map_err(|e| e.to_string())?;
+
+    if let Some(e) = file_error {
+        tracing::warn!("{}", e);
+    }
+    Ok(EventLog { rx, entries: VecDeque::new(), unseen: 0, file: opened_path })
+}
+
+struct PaneLayer {
+    tx: Sender<LogEntry>,
+}
+
+impl<S: Subscriber> Layer<S> for PaneLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let target = event.metadata().target();
+        let source = target.rsplit("::").next().filter(|s| *s != "netops").unwrap_or("app");
+        let _ = self.tx.send(LogEntry {
+            time: crate::alerts::timestamp(),
+            level: *event.metadata().level(),
+            source: source.to_string(),
+            message: visitor.message,
+        });
+    }
+}
+
+/// The event's message, followed by any other fields as `key=value`.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.insert_str(0, value);
+        } else {
+            let _ = write!(self.message, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message.insert_str(0, &format!("{:?}", value));
+        } else {
+            let _ = write!(self.message, " {}={:?}", field.name(), value);
+        }
+    }
+}
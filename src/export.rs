@@ -0,0 +1,105 @@
+use std::path::{Path, PathBuf};
+
+/// A screen's data flattened to rows of strings, ready to serialize.
+#[derive(Debug, Clone, Default)]
+pub struct ExportTable {
+    pub name: &'static str,
+    pub headers: Vec<&'static str>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl ExportTable {
+    pub fn new(name: &'static str, headers: &[&'static str]) -> Self {
+        Self { name, headers: headers.to_vec(), rows: vec![] }
+    }
+
+    pub fn push<I, S>(&mut self, row: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: ToString,
+    {
+        self.rows.push(row.into_iter().map(|c| c.to_string()).collect());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Csv,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// `netops-<screen>-<HHMMSS>.csv` in the working directory.
+pub fn default_filename(table: &ExportTable) -> String {
+    let stamp = time::OffsetDateTime::now_utc().time().format(&time::format_description::parse("[hour][minute][second]").unwrap()).unwrap_or_default();
+    format!("netops-{}-{}.csv", table.name, stamp)
+}
+
+/// Swaps the filename's extension between .csv and .json.
+pub fn toggle_extension(filename: &str) -> String {
+    let path = PathBuf::from(filename);
+    let next = match ExportFormat::from_path(&path) {
+        ExportFormat::Csv => ExportFormat::Json,
+        ExportFormat::Json => ExportFormat::Csv,
+    };
+    path.with_extension(next.extension()).to_string_lossy().to_string()
+}
+
+pub fn write(filename: &str, table: &ExportTable) -> Result<String, String> {
+    let path = PathBuf::from(filename.trim());
+    if path.as_os_str().is_empty() {
+        return Err("Enter a filename".to_string());
+    }
+
+    let body = match ExportFormat::from_path(&path) {
+        ExportFormat::Csv => to_csv(table),
+        ExportFormat::Json => to_json(table)?,
+    };
+    std::fs::write(&path, body).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(format!("Exported {} rows to {}", table.rows.len(), path.display()))
+}
+
+fn to_csv(table: &ExportTable) -> String {
+    let mut out = String::new();
+    let line = |cells: &mut dyn Iterator<Item = &str>| cells.map(csv_escape).collect::<Vec<_>>().join(",");
+    out.push_str(&line(&mut table.headers.iter().copied()));
+    out.push('\n');
+    for row in &table.rows {
+        out.push_str(&line(&mut row.iter().map(|s| s.as_str())));
+        out.push('\n');
+    }
+    out
+}
+
+fn csv_escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+fn to_json(table: &ExportTable) -> Result<String, String> {
+    let rows: Vec<serde_json::Map<String, serde_json::Value>> = table.rows.iter().map(|row| {
+        table.headers.iter()
+            .zip(row.iter())
+            .map(|(h, v)| (h.to_string(), serde_json::Value::String(v.clone())))
+            .collect()
+    }).collect();
+    serde_json::to_string_pretty(&rows).map_err(|e| e.to_string())
+}
@@ -0,0 +1,188 @@
+use tui_input::Input;
+
+use crate::app::CurrentScreen;
+use crate::tools::args;
+
+/// An option a tool's input understands. `value` is what a newly checked option starts
+/// with; `None` for plain switches. A flag ending in `=` takes its value joined to it.
+#[derive(Debug, Clone, Copy)]
+pub struct FlagSpec {
+    pub flag: &'static str,
+    pub description: &'static str,
+    pub value: Option<&'static str>,
+}
+
+pub const fn switch(flag: &'static str, description: &'static str) -> FlagSpec {
+    FlagSpec { flag, description, value: None }
+}
+
+pub const fn valued(flag: &'static str, description: &'static str, value: &'static str) -> FlagSpec {
+    FlagSpec { flag, description, value: Some(value) }
+}
+
+const PING: &[FlagSpec] = &[
+    valued("-i", "Interval (seconds)", "0.5"),
+    valued("-s", "Payload Size (bytes)", "128"),
+    valued("-c", "Count (limit)", "5"),
+    valued("-p", "Port to probe (with --tcp or --udp)", "443"),
+    switch("--tcp", "TCP connect to the port"),
+    switch("--udp", "UDP probe to the port"),
+    valued("-Q", "ToS byte or DSCP class (ef, af41, cs1)", "ef"),
+    valued("-t", "TTL of the probes", "64"),
+];
+
+const DNS: &[FlagSpec] = &[
+    valued("-w", "Watch: re-query every N seconds", "30"),
+    switch("--mail", "Check MX, SPF and DMARC instead"),
+    valued("--dkim", "DKIM selector to check too (implies --mail)", "default"),
+    switch("--bl", "Check the address against DNS blocklists instead"),
+    switch("--subs", "Subdomains from certificate logs (crt.sh)"),
+    valued("--wordlist", "Subdomains guessed from a wordlist file", "subdomains.txt"),
+];
+
+const MTR: &[FlagSpec] = &[
+    valued("-i", "Interval (seconds)", "1.0"),
+    valued("-m", "Max Hops", "30"),
+    valued("-c", "Cycles", "10"),
+    valued("--paths", "ECMP flows to map (2-16)", "8"),
+    valued("-f", "First TTL", "1"),
+    valued("-Q", "ToS byte or DSCP class (ef, af41, cs1)", "ef"),
+];
+
+const NMAP: &[FlagSpec] = &[
+    valued("-p", "Ports (e.g. 80,443)", "80,443"),
+    switch("-F", "Fast Scan"),
+    switch("-sV", "Service Version"),
+    switch("-Pn", "No Ping"),
+    switch("-O", "OS Detection"),
+    switch("--banners", "Grab banners of open TCP ports afterwards"),
+    valued("--max-parallelism", "Max probes in flight", "32"),
+    valued("--max-rate", "Max packets per second", "100"),
+    valued("--max-rtt-timeout", "Probe timeout", "500ms"),
+];
+
+const SWEEP: &[FlagSpec] = &[
+    valued("-c", "Parallel probes", "64"),
+    valued("-r", "Max probes per second", "50"),
+    valued("-t", "Timeout (seconds)", "1"),
+];
+
+const TRACEROUTE: &[FlagSpec] = &[
+    valued("-m", "Max Hops", "30"),
+    valued("-w", "Wait for replies (seconds)", "2"),
+];
+
+const ARPSCAN: &[FlagSpec] = &[
+    switch("-l", "Localnet"),
+    valued("-I", "Interface", "en0"),
+    switch("-q", "Quiet"),
+    valued("-r", "Retry", "3"),
+    valued("--interval=", "Gap between packets (ms)", "10"),
+    valued("-B", "Bandwidth limit", "64K"),
+    valued("--timeout=", "Per-host timeout (ms)", "500"),
+    valued("--watch=", "Continuous discovery, sweep every (s)", "60"),
+];
+
+/// The options offered in the Ctrl+F editor for a screen's input; empty for screens without one.
+/// Tools in the registry bring their own.
+pub fn options(screen: CurrentScreen) -> &'static [FlagSpec] {
+    match screen {
+        CurrentScreen::Ping => PING,
+        CurrentScreen::Dns => DNS,
+        CurrentScreen::Mtr => MTR,
+        CurrentScreen::Nmap => NMAP,
+        CurrentScreen::Sweep => SWEEP,
+        CurrentScreen::Traceroute => TRACEROUTE,
+        CurrentScreen::ArpScan => ARPSCAN,
+        _ => &[],
+    }
+}
+
+pub struct FlagRow {
+    pub spec: FlagSpec,
+    pub checked: bool,
+    pub value: Input,
+}
+
+/// The Ctrl+F popup: a tool input taken apart into its known options, each checked or not
+/// with its value, and everything else (targets, unknown flags) kept as typed.
+pub struct FlagEditor {
+    pub screen: CurrentScreen,
+    pub rows: Vec<FlagRow>,
+    pub selected: usize,
+    rest: Vec<String>,
+}
+
+impl FlagEditor {
+    /// Parses `input` against the screen's options; `None` if it has none.
+    pub fn new(screen: CurrentScreen, specs: &'static [FlagSpec], input: &str) -> Option<Self> {
+        if specs.is_empty() {
+            return None;
+        }
+        let mut rows: Vec<FlagRow> = specs.iter().map(|spec| FlagRow {
+            spec: *spec,
+            checked: false,
+            value: Input::new(spec.value.unwrap_or_default().to_string()),
+        }).collect();
+        let mut rest = vec![];
+        // An unclosed quote is the tool's error to report; until then it's just another word
+        let tokens = args::split(input).unwrap_or_else(|_| input.split_whitespace().map(String::from).collect());
+        let mut tokens = tokens.into_iter();
+        while let Some(token) = tokens.next() {
+            let joined = rows.iter().position(|r| r.spec.flag.ends_with('=') && token.starts_with(r.spec.flag));
+            let spaced = rows.iter().position(|r| !r.spec.flag.ends_with('=') && token == r.spec.flag);
+            if let Some(i) = joined {
+                rows[i].checked = true;
+                rows[i].value = Input::new(token[rows[i].spec.flag.len()..].to_string());
+            } else if let Some(i) = spaced {
+                rows[i].checked = true;
+                if rows[i].spec.value.is_some() {
+                    rows[i].value = Input::new(tokens.next().unwrap_or_default());
+                }
+            } else {
+                rest.push(token);
+            }
+        }
+        Some(Self { screen, rows, selected: 0, rest })
+    }
+
+    pub fn select(&mut self, forward: bool) {
+        self.selected = if forward {
+            (self.selected + 1).min(self.rows.len() - 1)
+        } else {
+            self.selected.saturating_sub(1)
+        };
+    }
+
+    pub fn toggle(&mut self) {
+        let row = &mut self.rows[self.selected];
+        row.checked = !row.checked;
+    }
+
+    /// The selected option's value, for typing into; editing a value checks its option.
+    pub fn value_mut(&mut self) -> Option<&mut Input> {
+        let row = &mut self.rows[self.selected];
+        row.spec.value?;
+        row.checked = true;
+        Some(&mut row.value)
+    }
+
+    /// The input as it will be written back: what was kept as typed, then the checked
+    /// options in table order. Options checked with an empty value are left out.
+    pub fn command(&self) -> String {
+        let mut parts = self.rest.clone();
+        for row in self.rows.iter().filter(|r| r.checked) {
+            let value = row.value.value().trim();
+            match row.spec.value {
+                None => parts.push(row.spec.flag.to_string()),
+                Some(_) if value.is_empty() => {}
+                Some(_) if row.spec.flag.ends_with('=') => parts.push(format!("{}{}", row.spec.flag, value)),
+                Some(_) => {
+                    parts.push(row.spec.flag.to_string());
+                    parts.push(value.to_string());
+                }
+            }
+        }
+        args::join(&parts)
+    }
+}
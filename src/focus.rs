@@ -0,0 +1,26 @@
+use crate::app::CurrentScreen;
+
+/// An input or selector on a screen that has more than one. The focused one gets the
+/// screen's typing and arrow keys, and a highlighted border.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    SnifferInterface,
+    SnifferFilter,
+    DnsDomain,
+    DnsType,
+    /// The subdomain list, for picking scan targets
+    DnsResults,
+    SyslogPort,
+    SyslogFilter,
+}
+
+/// A screen's fields in Tab order, the first focused by default; empty for screens with a
+/// single input, where Tab goes straight to the next screen.
+pub fn fields(screen: CurrentScreen) -> &'static [Field] {
+    match screen {
+        CurrentScreen::Sniffer => &[Field::SnifferInterface, Field::SnifferFilter],
+        CurrentScreen::Dns => &[Field::DnsDomain, Field::DnsType, Field::DnsResults],
+        CurrentScreen::Syslog => &[Field::SyslogPort, Field::SyslogFilter],
+        _ => &[],
+    }
+}
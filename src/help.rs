@@ -0,0 +1,382 @@
+use tui_input::Input;
+
+use crate::app::CurrentScreen;
+use crate::keymap::Keymap;
+use crate::screens;
+
+// Order of the keymap's headings; the screen's own notes come first
+const GROUPS: [&str; 4] = ["Global", "Tabs", "On screens", "Search"];
+
+/// One row of the help browser: a heading, or keys with what they do. Notes without
+/// keys have an empty `keys`.
+pub struct HelpLine {
+    pub heading: bool,
+    pub keys: String,
+    pub text: String,
+}
+
+impl HelpLine {
+    fn heading(text: &str) -> Self {
+        Self { heading: true, keys: String::new(), text: text.trim().to_string() }
+    }
+
+    fn entry(keys: &str, text: &str) -> Self {
+        Self { heading: false, keys: keys.to_string(), text: text.to_string() }
+    }
+
+    fn is_match(&self, query: &str) -> bool {
+        self.keys.to_lowercase().contains(query) || self.text.to_lowercase().contains(query)
+    }
+}
+
+/// The help popup: the current screen's notes and every keymap binding, filtered by
+/// what is typed and scrolled when it doesn't fit.
+#[derive(Default)]
+pub struct HelpBrowser {
+    pub input: Input,
+    /// First line shown
+    pub scroll: usize,
+}
+
+impl HelpBrowser {
+    /// The lines matching the query, with the headings of sections that still have any.
+    pub fn lines(&self, keymap: &Keymap, screen: CurrentScreen) -> Vec<HelpLine> {
+        let notes = screen_notes(screen);
+        let mut sections: Vec<(HelpLine, Vec<HelpLine>)> = vec![(HelpLine::heading(notes[0]), notes[1..].iter().map(|line| note(line)).collect())];
+        let bindings = keymap.help();
+        for group in GROUPS {
+            let entries = bindings.iter().filter(|(g, _, _)| *g == group).map(|(_, keys, text)| HelpLine::entry(keys, text)).collect();
+            sections.push((HelpLine::heading(group), entries));
+        }
+
+        let query = self.input.value().trim().to_lowercase();
+        let mut lines = vec![];
+        for (heading, entries) in sections {
+            let entries: Vec<HelpLine> = if query.is_empty() {
+                entries
+            } else {
+                entries.into_iter().filter(|l| l.is_match(&query)).collect()
+            };
+            if !entries.is_empty() {
+                if !lines.is_empty() {
+                    lines.push(HelpLine::entry("", ""));
+                }
+                lines.push(heading);
+                lines.extend(entries);
+            }
+        }
+        lines
+    }
+}
+
+/// A note line, with a leading `[keys]` moved into the key column.
+fn note(line: &str) -> HelpLine {
+    let trimmed = line.trim();
+    if let Some(rest) = trimmed.strip_prefix('[')
+        && let Some((keys, text)) = rest.split_once(']')
+    {
+        return HelpLine::entry(keys, text.trim());
+    }
+    HelpLine::entry("", line.strip_prefix(' ').unwrap_or(line))
+}
+
+/// What the screen does and the keys only it uses; the first line is its title.
+fn screen_notes(screen: CurrentScreen) -> Vec<&'static str> {
+    match screen {
+        CurrentScreen::Dashboard => vec![
+            " Dashboard ",
+            " Overview of network traffic and connectivity.",
+            " - Default: WAN bandwidth, connections, latency, jitter,",
+            "   protocol mix, interfaces, top ASNs and countries.",
+            " - Panels and their order are configurable ([dashboard]).",
+            " ",
+            " [-]  Zoom out charts (live -> 1m -> 15m -> 1h -> 24h)",
+            " [+]  Zoom in",
+            " [e]  Edit layout (add, remove, reorder panels)",
+            " [t]  Ping targets for the latency and jitter charts",
+            " [Up/Down] Select interface, [Enter] details, [Esc] back",
+        ],
+        CurrentScreen::Ping => vec![
+            " Ping Tool ",
+            " [Enter]  Start Ping to target",
+            " [Esc]    Stop Ping",
+            " [Up/Down] Earlier targets, [Right] take the suggestion",
+            " ",
+            " Features:",
+            " - Real-time Latency Graph (Bottom)",
+            " - [Ctrl+N] Heatmap instead: a row per time bucket,",
+            "   a column per latency range, darker = more probes",
+            " - Live Statistics (Min/Avg/Max/Loss)",
+            " - Flags: -i <sec> -s <bytes> -c <count>",
+            " - Port ping: -p <port> [--tcp|--udp] times the TCP",
+            "   handshake or a UDP reply instead of ICMP",
+            " - Marking: -Q <tos|ef|af41|cs1...> sets the DSCP/ToS,",
+            "   -t <ttl> the TTL (a low one shows where it expires)",
+        ],
+        CurrentScreen::Dns => vec![
+            " DNS Resolver ",
+            " [Enter]  Resolve Domain",
+            " [Up/Down] Earlier domains, [Right] take the suggestion",
+            " [Ctrl+N] Cycle Record Type (A -> AAAA -> MX...)",
+            " [Tab]    Focus domain / record type (arrows pick the type)",
+            " [Esc]    Stop watching",
+            " ",
+            " Returns detailed records including TTL.",
+            " '-w 30 example.com' re-queries every 30 seconds, charts the",
+            " resolution time and alerts when the answers change.",
+            " '--mail example.com' checks MX, SPF and DMARC (add",
+            " '--dkim <selector>' for a DKIM key) and flags mistakes.",
+            " '--bl 192.0.2.1' checks an address against DNS blocklists.",
+            " '--subs example.com' lists subdomains from certificate logs",
+            " ('--wordlist <file>' guesses them); [Tab] to the list,",
+            " [Space] picks, [Ctrl+N] sends them to the port scanner.",
+        ],
+        CurrentScreen::Sniffer => vec![
+            " Packet Sniffer ",
+            " [Enter]      Start/Stop Capture",
+            " [Tab]        Focus interface / filter",
+            " [Left/Right] Select Interface (past the last one: All)",
+            " [Ctrl+N]     Add/remove interface for multi-capture",
+            " [Up/Down]    Select Packet (Ctrl+Y copies it)",
+            " [p]          Pause/resume display (capture continues)",
+            " [c]          Clear packet list (Ctrl+L while idle)",
+            " [a]          Toggle auto-scroll (off keeps rows in place)",
+            " [Ctrl+S]     Search rows (n/N next/prev match)",
+            " [Filter]     tcpdump-style (e.g. 'tcp port 80 and not host 10.0.0.1') or text",
+            " [Ctrl+F]     Filter presets and recently used filters",
+            " [Ctrl+B]     Record matching packets to rotating pcap files",
+            " ",
+            " Displays: Time, Interface, Protocol, Source, Dest, Length, Info",
+            " An ICMP panel counts messages by type (unreachable with",
+            " its code, time exceeded, redirect...), errors in red.",
+            " Recording keeps [capture] ring_files files of ring_file_mb",
+            " each, deleting the oldest, until turned off.",
+            " [capture] triggers start it (and the capture) on an alert",
+            " condition or a matching packet, for trigger_secs.",
+        ],
+        CurrentScreen::Mtr => vec![
+            " My Traceroute (MTR) ",
+            " [Enter]    Start Trace",
+            " [Esc]      Stop",
+            " [Up/Down]  Select Hop to view Latency Graph (no hops yet,",
+            "            or typing a target: earlier targets)",
+            " [Right]    Take the suggestion while typing",
+            " [Ctrl+S]   Search hops (n/N next/prev match)",
+            " [Ctrl+N]   Check how the world routes to the selected hop",
+            " [v]        Path timeline: every route change (while running)",
+            " ",
+            " Shows path to target with loss & jitter per hop.",
+            " Chg counts the times another router answered a TTL",
+            " (a route flap, or ECMP spreading probes over links).",
+            " Probes keep one flow id (Paris traceroute), so ECMP",
+            " routers send them all down the same link.",
+            " --paths <n> probes n flow ids and draws their paths",
+            " as a tree in place of the latency chart.",
+            " -Q <tos|ef|af41...> marks probes with a DSCP/ToS;",
+            " -f <ttl> starts probing at that hop.",
+        ],
+        CurrentScreen::Nmap => vec![
+            " Port Scanner ",
+            " [Enter]  Start Scan",
+            " [Esc]    Stop",
+            " [Up/Down] Select an output line (no output yet, or typing",
+            "           a target: earlier targets)",
+            " [Right]  Take the suggestion while typing",
+            " [Ctrl+S] Search output (n/N next/prev match)",
+            " ",
+            " Targets: 10.0.0.5, 10.0.0.0/24, 10.0.0.1-50, 192.168.1,3.*, host names.",
+            " The input's corner shows how many hosts they cover, or what's wrong.",
+            " ",
+            " Useful Flags (Ctrl+F):",
+            " -p 80,443   Specific ports",
+            " -F          Fast scan (top 100 ports)",
+            " -sV         Service Version detection",
+            " --banners   Grab banners of open TCP ports",
+        ],
+        CurrentScreen::ArpScan => vec![
+            " Arp Scanner ",
+            " [Enter]  Start Scan",
+            " [Esc]    Stop",
+            " ",
+            " automatically scans local network if no args given.",
+            " -l: Localnet (default)",
+            " -I: Interface (e.g. -I en0)",
+            " --watch[=60]: Keep discovering: ARP sweep every 60s,",
+            "   listening to ARP traffic in between (-l or a CIDR).",
+            "   Hosts unheard for two sweeps show offline.",
+            " ",
+            " View switches to Table composed of IP, Hostname, MAC to Vendor.",
+            " Hostnames come from reverse DNS, mDNS or NetBIOS (shown dimmed).",
+            " [Ctrl+N]   Scan results / OS neighbor table (ARP & NDP cache)",
+            "            New bindings show green, changed MACs red",
+            " [Up/Down]  Select Host",
+            " [Ctrl+S]   Search hosts (n/N next/prev match)",
+            " [Ctrl+W]   Wake-on-LAN (selected host or manual MAC)",
+        ],
+        CurrentScreen::Connections => vec![
+            " Active Connections ",
+            " Monitors live socket connections.",
+            " [Up/Down]  Select Peer (Ctrl+Y copies the IP)",
+            " [Enter]    Actions: Ping / MTR / WHOIS / ASN / Copy / Kill / Reset",
+            " [Ctrl+N]   Sort by last seen / bandwidth / packets",
+            " [v]        Cycle views: established / listening sockets / top talkers",
+            " [Ctrl+N]   (Top talkers) Window: last 1 / 5 / 15 minutes",
+            " [g]        Toggle flat map / rotating globe",
+            " [r]        Show peers by reverse-DNS hostname / IP",
+            " [Enter]    (Listening) Kill the owning process",
+            " [/]        Search rows (n/N next/prev match)",
+            " ",
+            " - [Table] Real-time list of remote peers.",
+            " - [Map]   World map showing peer locations.",
+            " - Shows ASN (ISP/Org) for each IP.",
+            " - With [reputation] feeds set, Risk shows the AbuseIPDB",
+            "   score or a blocklist hit; flagged peers are red and alert.",
+            " - Packets and rate come from the running sniffer.",
+        ],
+        CurrentScreen::Mdns => vec![
+            " mDNS / Bonjour Discovery ",
+            " [Enter]    Start/Stop Browsing",
+            " [Esc]      Stop",
+            " [Up/Down]  Select Service to view TXT records",
+            " ",
+            " Browses _services._dns-sd._udp.local continuously",
+            " (AirPlay, printers, Chromecast, SSH, HomeKit...).",
+        ],
+        CurrentScreen::Ssdp => vec![
+            " SSDP / UPnP Discovery ",
+            " [Enter]    Start/Stop Discovery",
+            " [Esc]      Stop",
+            " [Up/Down]  Select Device to view details",
+            " ",
+            " Sends M-SEARCH (ssdp:all) periodically and reads each",
+            " device description for name, model and presentation URL.",
+        ],
+        CurrentScreen::Flows => vec![
+            " Flow Collector ",
+            " [Enter]  Start/Stop listening on UDP port",
+            " [Esc]    Stop",
+            " ",
+            " Accepts NetFlow v5/v9, IPFIX and sFlow v5 exports.",
+            " Point your router's exporter at this host (default 2055,",
+            " sFlow usually 6343). Stats cover the last 60 seconds.",
+        ],
+        CurrentScreen::History => vec![
+            " Result History ",
+            " [Type]     Filter by tool, target or summary",
+            " [Up/Down]  Select Result",
+            " [Enter]    Re-run in its tool",
+            " [Ctrl+N]   Mark/unmark for side-by-side compare",
+            "            (two port scans show a diff of open ports)",
+            " [Esc]      Clear filter and compare",
+            " ",
+            " Ping sessions, DNS lookups, scans and MTR runs are",
+            " saved to SQLite when they finish.",
+        ],
+        CurrentScreen::Wifi => vec![
+            " Wi-Fi ",
+            " [Enter]    Start/Stop Scanning",
+            " [Esc]      Stop",
+            " [Up/Down]  Select Network (Ctrl+Y copies SSID and BSSID)",
+            " ",
+            " Scans every 15s (iw or nmcli on Linux, airport on macOS)",
+            " and graphs the connected network's RSSI every second.",
+        ],
+        CurrentScreen::Dhcp => vec![
+            " DHCP Diagnostics ",
+            " [Enter]      Send probe and listen for 5 seconds",
+            " [Left/Right] Select Interface",
+            " [Ctrl+N]     Toggle DISCOVER / INFORM",
+            " [Up/Down]    Select Reply (Ctrl+Y copies the server)",
+            " ",
+            " DISCOVER asks every server for an offer without taking a",
+            " lease; more than one server answering usually means a",
+            " rogue DHCP server. Needs root to bind UDP port 68.",
+        ],
+        CurrentScreen::Syslog => vec![
+            " Syslog ",
+            " [Enter]       Start/Stop listening (UDP and TCP)",
+            " [Esc]         Stop",
+            " [Tab]         Switch between port and filter input",
+            " [Left/Right]  Minimum severity shown (port focused)",
+            " [Up/Down]     Select Message (Ctrl+Y copies it)",
+            " ",
+            " Accepts RFC 3164 and RFC 5424 messages. Point a device's",
+            " logging at this host; ports below 1024 (514) need root,",
+            " so many devices can use 5514 instead. The filter matches",
+            " host, program, message, sender address or facility.",
+        ],
+        CurrentScreen::Diagnose => vec![
+            " Diagnose ",
+            " [Enter]  Run / Stop the checks",
+            " [Esc]    Stop",
+            " ",
+            " Checks in order: an interface with an address, the",
+            " router answering pings, DNS resolving, a TCP connection",
+            " to the internet by address, and the largest packet that",
+            " gets through. The verdict names the first thing broken.",
+            " Ctrl+Y copies the report.",
+        ],
+        CurrentScreen::Asn => vec![
+            " ASN ",
+            " [Enter]    Look up / open the selected network",
+            " [Up/Down]  Select Prefix, Network or Path",
+            " ",
+            " Takes an AS number (AS15169 or 15169) for its holder and",
+            " announced prefixes, an organization name to search for",
+            " networks, or an IP address or prefix for its origin AS,",
+            " how many RIPE RIS peers see it, and the AS paths they",
+            " use, with the collectors' locations. Enter on a",
+            " Connections row offers ASN for the remote address;",
+            " Ctrl+N on an MTR hop looks up that hop.",
+            " Data comes from RIPEstat, so it needs internet access.",
+        ],
+        CurrentScreen::Devices => vec![
+            " Devices ",
+            " [Up/Down]  Select Device (Ctrl+Y copies the MAC)",
+            " [Enter]    Scan the local network now (arp-scan)",
+            " [c]        Mark new devices as seen",
+            " [/]        Search by MAC, vendor, hostname or IP",
+            " ",
+            " Every device ARP Scan, Ping Sweep, the neighbor table,",
+            " mDNS or sniffed DHCP requests have shown, kept across runs",
+            " in the history database. Devices never seen before are",
+            " highlighted and raise a \"new device\" alert.",
+        ],
+        CurrentScreen::Sweep => vec![
+            " Ping Sweep ",
+            " [Enter]    Start/Stop Sweep",
+            " [Esc]      Stop",
+            " [Ctrl+N]   Send live hosts to Nmap",
+            " [Up/Down]  Select Host (Ctrl+Y copies the address)",
+            " [Ctrl+F]   Options: -c <n> parallel probes, -t <secs> timeout",
+            " ",
+            " Pings every address in a CIDR range (up to a /16). Hosts",
+            " with a MAC in the neighbour cache are added to ARP Scan.",
+        ],
+        CurrentScreen::Traceroute => vec![
+            " Traceroute ",
+            " [Enter]    Trace",
+            " [Ctrl+N]   Continue in MTR with these results",
+            " [Up/Down]  Select Hop (Ctrl+Y copies the address)",
+            " [Ctrl+F]   Options: -m <hops> max hops, -w <secs> wait",
+            " ",
+            " Sends three probes for every TTL at once, so a trace takes",
+            " one wait period instead of one per hop.",
+        ],
+        CurrentScreen::Plugin(_) => vec![
+            " Command Tool ",
+            " [Enter]    Run / Stop",
+            " [Esc]      Stop",
+            " [Up/Down]  Select Row (Down on the last row follows output)",
+            " [Ctrl+S]   Search rows (n/N next/prev match)",
+            " ",
+            " An external command from [[plugins]] in the config. The",
+            " title shows the command as it will run; {input} in it is",
+            " replaced by the input, which is otherwise appended.",
+            " Output lines become rows as they arrive, split by the",
+            " parser set for the tool: lines, columns or json.",
+        ],
+        // Registered tools keep their notes in their `ToolInfo`
+        _ => screens::info(screen).map(|t| t.notes.to_vec()).unwrap_or_default(),
+    }
+}
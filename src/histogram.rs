@@ -0,0 +1,83 @@
+// Buckets are log-linear: exact below 64µs, then 32 per power of two, so any value is
+// placed within about 3% however long the session runs.
+const SUB_BITS: u32 = 5;
+const SUB: usize = 1 << SUB_BITS;
+
+fn index(us: u64) -> usize {
+    if us < 2 * SUB as u64 {
+        return us as usize;
+    }
+    let shift = 63 - us.leading_zeros() - SUB_BITS;
+    shift as usize * SUB + (us >> shift) as usize
+}
+
+/// (lowest value, width) of bucket `i`, in µs.
+fn bucket(i: usize) -> (u64, u64) {
+    if i < 2 * SUB {
+        return (i as u64, 1);
+    }
+    let shift = i / SUB - 1;
+    (((i % SUB + SUB) as u64) << shift, 1 << shift)
+}
+
+/// Streaming latency histogram: a fixed set of counters rather than every sample.
+#[derive(Debug, Clone, Default)]
+pub struct Histogram {
+    counts: Vec<u64>,
+    total: u64,
+    min_us: u64,
+    max_us: u64,
+}
+
+impl Histogram {
+    pub fn record(&mut self, ms: f64) {
+        let us = (ms * 1000.0).max(0.0) as u64;
+        let i = index(us);
+        if i >= self.counts.len() {
+            self.counts.resize(i + 1, 0);
+        }
+        self.counts[i] += 1;
+        self.min_us = if self.total == 0 { us } else { self.min_us.min(us) };
+        self.max_us = self.max_us.max(us);
+        self.total += 1;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.total
+    }
+
+    /// The value `p` percent of samples are at or below, in ms; `None` before the first sample.
+    pub fn percentile(&self, p: f64) -> Option<f64> {
+        if self.total == 0 {
+            return None;
+        }
+        let rank = ((p / 100.0 * self.total as f64).ceil() as u64).clamp(1, self.total);
+        let mut seen = 0;
+        for (i, &n) in self.counts.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                let (low, width) = bucket(i);
+                let mid = (low + width / 2).clamp(self.min_us, self.max_us);
+                return Some(mid as f64 / 1000.0);
+            }
+        }
+        Some(self.max_us as f64 / 1000.0)
+    }
+
+    /// Samples in `bins` equal slices from the fastest sample to the 99th percentile (the
+    /// slowest 1% goes in the last one), as (slice start in ms, count).
+    pub fn bins(&self, bins: usize) -> Vec<(f64, u64)> {
+        let Some(top) = self.percentile(99.0) else { return vec![] };
+        let bins = bins.max(1);
+        let low = self.min_us as f64 / 1000.0;
+        let width = ((top - low) / bins as f64).max(0.001);
+        let mut out: Vec<(f64, u64)> = (0..bins).map(|b| (low + b as f64 * width, 0)).collect();
+        for (i, &n) in self.counts.iter().enumerate().filter(|(_, n)| **n > 0) {
+            let (start, w) = bucket(i);
+            let ms = (start + w / 2) as f64 / 1000.0;
+            let b = (((ms - low) / width) as usize).min(bins - 1);
+            out[b].1 += n;
+        }
+        out
+    }
+}
@@ -0,0 +1,214 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+
+use crate::config::{self, HistoryConfig};
+use crate::tools::nmap::ScanPort;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryKind {
+    Ping,
+    Dns,
+    Scan,
+    Mtr,
+    /// Only ever an input: a sniffer filter
+    Capture,
+}
+
+impl HistoryKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ping => "ping",
+            Self::Dns => "dns",
+            Self::Scan => "scan",
+            Self::Mtr => "mtr",
+            Self::Capture => "capture",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "ping" => Some(Self::Ping),
+            "dns" => Some(Self::Dns),
+            "scan" => Some(Self::Scan),
+            "mtr" => Some(Self::Mtr),
+            "capture" => Some(Self::Capture),
+            _ => None,
+        }
+    }
+
+    /// Label for `HistoryEntry::metric`, if the kind records one.
+    pub fn metric_label(&self) -> Option<&'static str> {
+        match self {
+            Self::Ping => Some("avg latency (ms)"),
+            Self::Mtr => Some("destination avg (ms)"),
+            Self::Scan => Some("open ports"),
+            Self::Dns | Self::Capture => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub timestamp: i64,
+    pub kind: HistoryKind,
+    pub target: String,
+    pub summary: String,
+    pub details: String,
+    pub metric: Option<f64>,
+}
+
+impl HistoryEntry {
+    pub fn time_label(&self) -> String {
+        let format = time::format_description::parse("[weekday repr:short] [year]-[month]-[day] [hour]:[minute]").unwrap();
+        time::OffsetDateTime::from_unix_timestamp(self.timestamp)
+            .ok()
+            .and_then(|t| t.format(&format).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// The configured database, or `history.db` in the data directory. Also holds the device inventory.
+pub fn db_path(config: &HistoryConfig) -> Result<PathBuf> {
+    match &config.path {
+        Some(p) => Ok(p.clone()),
+        None => Ok(config::data_dir().context("No data directory available")?.join("history.db")),
+    }
+}
+
+pub struct HistoryStore {
+    conn: Connection,
+}
+
+impl HistoryStore {
+    pub fn open(config: &HistoryConfig) -> Result<HistoryStore> {
+        Self::open_path(db_path(config)?)
+    }
+
+    fn open_path(path: PathBuf) -> Result<HistoryStore> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let conn = Connection::open(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS results (
+                id        INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                kind      TEXT NOT NULL,
+                target    TEXT NOT NULL,
+                summary   TEXT NOT NULL,
+                details   TEXT NOT NULL,
+                metric    REAL
+            );
+            CREATE INDEX IF NOT EXISTS results_target ON results (kind, target, timestamp);
+            CREATE TABLE IF NOT EXISTS scan_ports (
+                result_id INTEGER NOT NULL REFERENCES results (id),
+                host      TEXT NOT NULL,
+                port      INTEGER NOT NULL,
+                proto     TEXT NOT NULL,
+                state     TEXT NOT NULL,
+                service   TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS scan_ports_result ON scan_ports (result_id);
+            CREATE TABLE IF NOT EXISTS inputs (
+                kind      TEXT NOT NULL,
+                input     TEXT NOT NULL,
+                last_used INTEGER NOT NULL,
+                PRIMARY KEY (kind, input)
+            );",
+        )?;
+        Ok(HistoryStore { conn })
+    }
+
+    /// Returns the new result's id.
+    pub fn record(&self, kind: HistoryKind, target: &str, summary: &str, details: &str, metric: Option<f64>) -> Result<i64> {
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        self.conn.execute(
+            "INSERT INTO results (timestamp, kind, target, summary, details, metric) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![now, kind.as_str(), target, summary, details, metric],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn record_scan_ports(&mut self, result_id: i64, ports: &[ScanPort]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        for p in ports {
+            tx.execute(
+                "INSERT INTO scan_ports (result_id, host, port, proto, state, service) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![result_id, p.host, p.port, p.proto, p.state, p.service],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Ports stored with a scan result; empty for scans recorded before ports were stored.
+    pub fn scan_ports(&self, result_id: i64) -> Result<Vec<ScanPort>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT host, port, proto, state, service FROM scan_ports WHERE result_id = ?1 ORDER BY host, port",
+        )?;
+        let rows = stmt.query_map(params![result_id], |row| {
+            Ok(ScanPort { host: row.get(0)?, port: row.get(1)?, proto: row.get(2)?, state: row.get(3)?, service: row.get(4)? })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Remembers `input` as the latest typed into the `kind` tool, keeping the newest `keep`.
+    pub fn record_input(&self, kind: HistoryKind, input: &str, keep: usize) -> Result<()> {
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        self.conn.execute(
+            "INSERT INTO inputs (kind, input, last_used) VALUES (?1, ?2, ?3)
+             ON CONFLICT (kind, input) DO UPDATE SET last_used = excluded.last_used",
+            params![kind.as_str(), input, now],
+        )?;
+        self.conn.execute(
+            "DELETE FROM inputs WHERE kind = ?1 AND input NOT IN
+             (SELECT input FROM inputs WHERE kind = ?1 ORDER BY last_used DESC LIMIT ?2)",
+            params![kind.as_str(), keep as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Inputs typed into the `kind` tool, newest first.
+    pub fn inputs(&self, kind: HistoryKind) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare("SELECT input FROM inputs WHERE kind = ?1 ORDER BY last_used DESC, rowid DESC")?;
+        let rows = stmt.query_map(params![kind.as_str()], |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Newest first. `filter` matches the kind, target or summary (case-insensitive substring).
+    pub fn list(&self, filter: &str, limit: usize) -> Result<Vec<HistoryEntry>> {
+        let pattern = format!("%{}%", filter.trim());
+        let mut stmt = self.conn.prepare(
+            "SELECT id, timestamp, kind, target, summary, details, metric FROM results
+             WHERE kind LIKE ?1 OR target LIKE ?1 OR summary LIKE ?1
+             ORDER BY timestamp DESC, id DESC LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![pattern, limit as i64], |row| {
+            let Some(kind) = HistoryKind::from_str(&row.get::<_, String>(2)?) else { return Ok(None) };
+            Ok(Some(HistoryEntry {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                kind,
+                target: row.get(3)?,
+                summary: row.get(4)?,
+                details: row.get(5)?,
+                metric: row.get(6)?,
+            }))
+        })?;
+        Ok(rows.filter_map(|r| r.transpose()).collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// (timestamp, metric) for every run of `kind` against `target`, oldest first.
+    pub fn trend(&self, kind: HistoryKind, target: &str) -> Result<Vec<(i64, f64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT timestamp, metric FROM results
+             WHERE kind = ?1 AND target = ?2 AND metric IS NOT NULL
+             ORDER BY timestamp ASC",
+        )?;
+        let rows = stmt.query_map(params![kind.as_str(), target], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+}
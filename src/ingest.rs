@@ -0,0 +1,210 @@
+use std::collections::{HashMap, VecDeque};
+use std::mem;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, RwLock};
+
+use crossbeam::channel::{Receiver, Sender};
+
+use crate::lru::LruCache;
+use crate::tools::connections::RawConnection;
+use crate::tools::dhcp::ClientHello;
+use crate::tools::geoip::GeoIpReader;
+use crate::tools::sniffer::{IcmpKind, PacketSummary, SnifferEvent};
+
+/// Everything the sniffer reported since the last `take`, summed up off the UI thread.
+#[derive(Default)]
+pub struct SnifferBatch {
+    /// The newest packets, at most the packet retention; older ones are only counted in `dropped`
+    pub packets: VecDeque<PacketSummary>,
+    pub seen: u64,
+    pub dropped: u64,
+    /// (packets, bytes) per source or destination IP
+    pub traffic: HashMap<IpAddr, (u64, u64)>,
+    pub dhcp: Vec<Box<ClientHello>>,
+    /// ICMP and ICMPv6 messages per type
+    pub icmp: HashMap<IcmpKind, u64>,
+    pub arp_alerts: Vec<String>,
+    /// Capture triggers that fired: the trigger, and the packet that matched
+    pub triggers: Vec<(String, String)>,
+    pub error: Option<String>,
+}
+
+/// Drains a capture's events on its own thread, so a packet storm costs the render loop one
+/// batch per tick rather than a pass over every packet. The thread ends with the capture.
+pub struct SnifferIngest {
+    batch: Arc<Mutex<SnifferBatch>>,
+}
+
+impl SnifferIngest {
+    pub fn start(rx: Receiver<SnifferEvent>, capacity: usize) -> Self {
+        let batch = Arc::new(Mutex::new(SnifferBatch::default()));
+        let shared = batch.clone();
+        std::thread::spawn(move || {
+            while let Ok(event) = rx.recv() {
+                let mut batch = shared.lock().unwrap_or_else(|e| e.into_inner());
+                match event {
+                    SnifferEvent::Packet(mut packet) => {
+                        batch.seen += 1;
+                        let len: u64 = packet.length.parse().unwrap_or(0);
+                        for addr in [&packet.source, &packet.destination] {
+                            if let Ok(ip) = addr.parse::<IpAddr>() {
+                                let entry = batch.traffic.entry(ip).or_default();
+                                entry.0 += 1;
+                                entry.1 += len;
+                            }
+                        }
+                        if let Some(kind) = packet.icmp {
+                            *batch.icmp.entry(kind).or_default() += 1;
+                        }
+                        if let Some(hello) = packet.dhcp.take() {
+                            batch.dhcp.push(hello);
+                        }
+                        batch.packets.push_back(packet);
+                        if batch.packets.len() > capacity {
+                            batch.packets.pop_front();
+                            batch.dropped += 1;
+                        }
+                    }
+                    SnifferEvent::Error(e) => batch.error = Some(e),
+                    SnifferEvent::ArpAlert(message) => batch.arp_alerts.push(message),
+                    SnifferEvent::Trigger(name, packet) => batch.triggers.push((name, packet)),
+                }
+            }
+        });
+        Self { batch }
+    }
+
+    pub fn take(&self) -> SnifferBatch {
+        mem::take(&mut *self.batch.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+/// GeoIP facts about a peer, looked up once per peer and database load.
+#[derive(Debug, Clone)]
+pub struct PeerInfo {
+    pub asn_num: u32,
+    pub asn_org: String,
+    pub location: Option<(f64, f64)>,
+    pub country: Option<String>,
+}
+
+// Peers remembered across netstat refreshes; well past what a busy desktop has open
+const CACHE_SIZE: usize = 4096;
+
+/// The loaded databases. `generation` changes on every (re)load so cached answers from the
+/// old ones are dropped.
+#[derive(Default)]
+struct GeoIpReaders {
+    asn: Option<Arc<GeoIpReader>>,
+    country: Option<Arc<GeoIpReader>>,
+    generation: u64,
+}
+
+/// GeoIP lookups off the UI thread, with the answers for recently seen peers cached. The
+/// connections thread looks up each snapshot's new peers in one batch; `get` serves the UI
+/// from the cache and queues misses for a worker thread.
+#[derive(Clone)]
+pub struct GeoIpLookups {
+    readers: Arc<RwLock<GeoIpReaders>>,
+    /// The generation the entries were looked up with
+    cache: Arc<Mutex<(u64, LruCache<IpAddr, PeerInfo>)>>,
+    requests: Sender<IpAddr>,
+}
+
+impl GeoIpLookups {
+    pub fn start() -> Self {
+        let (requests, rx) = crossbeam::channel::unbounded::<IpAddr>();
+        let lookups = Self {
+            readers: Arc::default(),
+            cache: Arc::new(Mutex::new((0, LruCache::new(CACHE_SIZE)))),
+            requests,
+        };
+        let worker = lookups.clone();
+        std::thread::spawn(move || {
+            while let Ok(ip) = rx.recv() {
+                // Whatever else queued up meanwhile goes in the same batch
+                let mut batch: Vec<IpAddr> = std::iter::once(ip).chain(rx.try_iter()).collect();
+                batch.sort();
+                batch.dedup();
+                worker.resolve(&batch);
+            }
+        });
+        lookups
+    }
+
+    /// Swaps in a (re)loaded database; `country` is the country edition, otherwise ASN.
+    pub fn set_reader(&self, reader: Arc<GeoIpReader>, country: bool) {
+        let mut readers = self.readers.write().unwrap_or_else(|e| e.into_inner());
+        if country { readers.country = Some(reader) } else { readers.asn = Some(reader) }
+        readers.generation += 1;
+    }
+
+    /// The cached answer for `ip`, or `None` while the worker looks it up.
+    pub fn get(&self, ip: IpAddr) -> Option<PeerInfo> {
+        let generation = self.readers.read().unwrap_or_else(|e| e.into_inner()).generation;
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        let hit = if cache.0 == generation { cache.1.get(&ip).cloned() } else { None };
+        if hit.is_none() {
+            let _ = self.requests.send(ip);
+        }
+        hit
+    }
+
+    /// Answers for all of `ips`: cached ones, plus the rest looked up on the calling thread.
+    pub fn resolve(&self, ips: &[IpAddr]) -> HashMap<IpAddr, PeerInfo> {
+        let readers = self.readers.read().unwrap_or_else(|e| e.into_inner());
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        if cache.0 != readers.generation {
+            cache.0 = readers.generation;
+            cache.1.clear();
+        }
+        let mut found = HashMap::new();
+        let mut missing = vec![];
+        for &ip in ips {
+            match cache.1.get(&ip) {
+                Some(info) => { found.insert(ip, info.clone()); }
+                None => missing.push(ip),
+            }
+        }
+        if missing.is_empty() {
+            return found;
+        }
+        // The databases stay read-locked, so nothing looked up here is stale
+        drop(cache);
+        let looked_up: Vec<_> = missing.into_iter().map(|ip| (ip, lookup(&readers, ip))).collect();
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        for (ip, info) in looked_up {
+            cache.1.insert(ip, info.clone());
+            found.insert(ip, info);
+        }
+        found
+    }
+}
+
+/// Annotates each netstat snapshot with GeoIP data before it reaches the app, so lookups for
+/// new peers happen here rather than in the render loop.
+pub fn annotate_connections(raw: Receiver<Vec<RawConnection>>, geoip: GeoIpLookups, tx: Sender<Vec<(RawConnection, PeerInfo)>>) {
+    std::thread::spawn(move || {
+        while let Ok(conns) = raw.recv() {
+            let ips: Vec<IpAddr> = conns.iter().filter_map(|c| c.remote_ip).collect();
+            let peers = geoip.resolve(&ips);
+            let annotated = conns.into_iter().map(|c| {
+                let info = c.remote_ip.and_then(|ip| peers.get(&ip).cloned())
+                    .unwrap_or(PeerInfo { asn_num: 0, asn_org: "-".to_string(), location: None, country: None });
+                (c, info)
+            }).collect();
+            if tx.send(annotated).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+fn lookup(readers: &GeoIpReaders, ip: IpAddr) -> PeerInfo {
+    let country = readers.country.as_ref().and_then(|r| r.lookup_country(ip));
+    let (asn_num, asn_org, location) = match &readers.asn {
+        Some(reader) => reader.lookup_info(ip).unwrap_or((0, "Unknown".to_string(), None)),
+        None => (0, "-".to_string(), None),
+    };
+    PeerInfo { asn_num, asn_org, location, country }
+}
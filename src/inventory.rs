@@ -0,0 +1,266 @@
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+
+use crate::config::HistoryConfig;
+use crate::history;
+use crate::tools::oui;
+
+// Sightings refresh last_seen in memory every time, on disk at most this often
+const WRITE_EVERY: i64 = 60;
+// Devices not seen for this long are shown as offline
+const ONLINE_FOR: i64 = 10 * 60;
+// With an empty inventory, what turns up this soon after the first sighting is the baseline, not news
+const LEARNING: Duration = Duration::from_secs(120);
+
+/// A device seen by some tool: a MAC, and whatever else that tool knows about it.
+#[derive(Debug, Clone)]
+pub struct Sighting {
+    pub mac: String,
+    pub ip: Option<IpAddr>,
+    pub hostname: Option<String>,
+    pub vendor: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeviceIp {
+    pub ip: IpAddr,
+    pub first_seen: i64,
+    pub last_seen: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Device {
+    /// Lowercase, colon-separated
+    pub mac: String,
+    pub vendor: String,
+    pub hostname: Option<String>,
+    /// Most recently seen first
+    pub ips: Vec<DeviceIp>,
+    pub first_seen: i64,
+    pub last_seen: i64,
+    /// First seen since startup and not yet acknowledged
+    pub new: bool,
+    written: i64,
+}
+
+impl Device {
+    /// The current IPv4 address if it has one; neighbors list IPv4 and IPv6 side by side.
+    pub fn ip(&self) -> Option<IpAddr> {
+        self.ips.iter().find(|i| i.ip.is_ipv4()).or(self.ips.first()).map(|i| i.ip)
+    }
+
+    pub fn online(&self) -> bool {
+        now() - self.last_seen < ONLINE_FOR
+    }
+}
+
+/// Every device seen on the local network, kept in the history database across runs.
+pub struct Inventory {
+    conn: Option<Connection>,
+    /// Ordered by current IP
+    pub devices: Vec<Device>,
+    learning_until: Option<Instant>,
+    learning: bool,
+}
+
+impl Inventory {
+    /// In the history database; `None` with history disabled keeps the inventory in memory only.
+    pub fn open(config: Option<&HistoryConfig>) -> Result<Self> {
+        let conn = match config {
+            Some(config) => {
+                let path = history::db_path(config)?;
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir)?;
+                }
+                let conn = Connection::open(&path)?;
+                conn.execute_batch(
+                    "CREATE TABLE IF NOT EXISTS devices (
+                        mac        TEXT PRIMARY KEY,
+                        vendor     TEXT NOT NULL,
+                        hostname   TEXT,
+                        first_seen INTEGER NOT NULL,
+                        last_seen  INTEGER NOT NULL
+                    );
+                    CREATE TABLE IF NOT EXISTS device_ips (
+                        mac        TEXT NOT NULL,
+                        ip         TEXT NOT NULL,
+                        first_seen INTEGER NOT NULL,
+                        last_seen  INTEGER NOT NULL,
+                        PRIMARY KEY (mac, ip)
+                    );",
+                )?;
+                Some(conn)
+            }
+            None => None,
+        };
+        let mut inventory = Self { conn, devices: vec![], learning_until: None, learning: false };
+        inventory.load()?;
+        inventory.learning = inventory.devices.is_empty();
+        Ok(inventory)
+    }
+
+    fn load(&mut self) -> Result<()> {
+        let Some(conn) = self.conn.take() else { return Ok(()) };
+        let result = self.load_from(&conn);
+        self.conn = Some(conn);
+        result?;
+        self.sort();
+        Ok(())
+    }
+
+    fn load_from(&mut self, conn: &Connection) -> Result<()> {
+        let mut stmt = conn.prepare("SELECT mac, vendor, hostname, first_seen, last_seen FROM devices")?;
+        let rows = stmt.query_map([], |row| {
+            let last_seen = row.get(4)?;
+            Ok(Device {
+                mac: row.get(0)?,
+                vendor: row.get(1)?,
+                hostname: row.get(2)?,
+                ips: vec![],
+                first_seen: row.get(3)?,
+                last_seen,
+                new: false,
+                written: last_seen,
+            })
+        })?;
+        self.devices = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut stmt = conn.prepare("SELECT mac, ip, first_seen, last_seen FROM device_ips ORDER BY last_seen DESC")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get(2)?, row.get(3)?)))?;
+        for row in rows {
+            let (mac, ip, first_seen, last_seen) = row?;
+            let (Some(device), Ok(ip)) = (self.devices.iter_mut().find(|d| d.mac == mac), ip.parse()) else { continue };
+            device.ips.push(DeviceIp { ip, first_seen, last_seen });
+        }
+        Ok(())
+    }
+
+    /// Records a sighting; returns the device if it has never been seen before and isn't part of
+    /// the first inventory being learned.
+    pub fn record(&mut self, sighting: Sighting) -> Result<Option<Device>> {
+        let Some(bytes) = oui::parse_mac(&sighting.mac) else { return Ok(None) };
+        // Broadcast, multicast and the all-zero placeholder of incomplete entries
+        if bytes[0] & 0x01 != 0 || bytes == [0; 6] {
+            return Ok(None);
+        }
+        let mac = bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":");
+        let now = now();
+        if self.learning && self.learning_until.is_none() {
+            self.learning_until = Some(Instant::now() + LEARNING);
+        }
+        let announce = self.learning_until.is_none_or(|t| Instant::now() >= t);
+
+        let (idx, is_new) = match self.devices.iter().position(|d| d.mac == mac) {
+            Some(idx) => (idx, false),
+            None => {
+                self.devices.push(Device {
+                    mac: mac.clone(),
+                    vendor: String::new(),
+                    hostname: None,
+                    ips: vec![],
+                    first_seen: now,
+                    last_seen: now,
+                    new: announce,
+                    written: 0,
+                });
+                (self.devices.len() - 1, true)
+            }
+        };
+        let device = &mut self.devices[idx];
+        device.last_seen = now;
+        let mut changed = is_new;
+        if device.vendor.is_empty() && !sighting.vendor.is_empty() {
+            device.vendor = sighting.vendor;
+            changed = true;
+        }
+        if let Some(hostname) = sighting.hostname.filter(|h| !h.is_empty() && device.hostname.as_ref() != Some(h)) {
+            device.hostname = Some(hostname);
+            changed = true;
+        }
+        let mut new_ip = false;
+        if let Some(ip) = sighting.ip {
+            match device.ips.iter().position(|i| i.ip == ip) {
+                Some(i) => {
+                    let mut entry = device.ips.remove(i);
+                    entry.last_seen = now;
+                    device.ips.insert(0, entry);
+                }
+                None => {
+                    device.ips.insert(0, DeviceIp { ip, first_seen: now, last_seen: now });
+                    new_ip = true;
+                }
+            }
+        }
+
+        if changed || new_ip || now - device.written >= WRITE_EVERY {
+            let since = std::mem::replace(&mut device.written, now);
+            if let Some(conn) = &self.conn {
+                conn.execute(
+                    "INSERT INTO devices (mac, vendor, hostname, first_seen, last_seen) VALUES (?1, ?2, ?3, ?4, ?5)
+                     ON CONFLICT (mac) DO UPDATE SET vendor = ?2, hostname = ?3, last_seen = ?5",
+                    params![device.mac, device.vendor, device.hostname, device.first_seen, device.last_seen],
+                )?;
+                // Every address seen since the last write, not just this sighting's
+                for ip in device.ips.iter().filter(|i| i.last_seen > since || i.first_seen == now) {
+                    conn.execute(
+                        "INSERT INTO device_ips (mac, ip, first_seen, last_seen) VALUES (?1, ?2, ?3, ?4)
+                         ON CONFLICT (mac, ip) DO UPDATE SET last_seen = ?4",
+                        params![device.mac, ip.ip.to_string(), ip.first_seen, ip.last_seen],
+                    )?;
+                }
+            }
+        }
+
+        let result = (is_new && announce).then(|| device.clone());
+        if is_new || new_ip {
+            self.sort();
+        }
+        Ok(result)
+    }
+
+    /// Names the device currently at `ip`, e.g. from an mDNS announcement that carries no MAC.
+    pub fn name_ip(&mut self, ip: IpAddr, hostname: &str) -> Result<()> {
+        let Some(device) = self.devices.iter().find(|d| d.ip() == Some(ip) && d.online()) else { return Ok(()) };
+        if device.hostname.as_deref() == Some(hostname) || device.hostname.is_some() && hostname.is_empty() {
+            return Ok(());
+        }
+        let sighting = Sighting { mac: device.mac.clone(), ip: None, hostname: Some(hostname.to_string()), vendor: String::new() };
+        self.record(sighting).map(|_| ())
+    }
+
+    /// Clears the "new" highlight once the user has looked.
+    pub fn acknowledge(&mut self) {
+        for d in &mut self.devices {
+            d.new = false;
+        }
+    }
+
+    fn sort(&mut self) {
+        self.devices.sort_by_key(|d| (d.ip().is_none(), d.ip().map(|ip| (ip.is_ipv6(), ip))));
+    }
+}
+
+fn now() -> i64 {
+    time::OffsetDateTime::now_utc().unix_timestamp()
+}
+
+/// `5m ago`-style age of a unix timestamp.
+pub fn ago(timestamp: i64) -> String {
+    match now() - timestamp {
+        s if s < 60 => "just now".to_string(),
+        s if s < 3600 => format!("{}m ago", s / 60),
+        s if s < 86400 => format!("{}h ago", s / 3600),
+        s => format!("{}d ago", s / 86400),
+    }
+}
+
+pub fn date_label(timestamp: i64) -> String {
+    let format = time::format_description::parse("[year]-[month]-[day] [hour]:[minute]").unwrap();
+    time::OffsetDateTime::from_unix_timestamp(timestamp)
+        .ok()
+        .and_then(|t| t.format(&format).ok())
+        .unwrap_or_default()
+}
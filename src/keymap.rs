@@ -0,0 +1,324 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::app::CurrentScreen;
+use crate::screens;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    Help,
+    Palette,
+    ToolOptions,
+    Export,
+    AlertRules,
+    Profiles,
+    WakeOnLan,
+    MacVendor,
+    Yank,
+    EventLog,
+    Search,
+    SearchNext,
+    SearchPrev,
+    NextTab,
+    PrevTab,
+    Split,
+    FocusPane,
+    Goto(CurrentScreen),
+    Start,
+    Stop,
+    Up,
+    Down,
+    Left,
+    Right,
+    Cycle,
+    ToggleView,
+    ToggleMap,
+    Hostnames,
+    EditLayout,
+    PingTargets,
+    Pause,
+    Clear,
+    AutoScroll,
+    Record,
+    ZoomIn,
+    ZoomOut,
+}
+
+type ActionTable = [(&'static str, Action, &'static [&'static str], &'static str)];
+
+/// Config name, action, default keys and what it does (for help). Listing an action under
+/// `[keys]` replaces its defaults. The registered tools' `goto_` actions come from their
+/// `ToolInfo`; see `actions()`.
+const ACTIONS: &ActionTable = &[
+    ("quit", Action::Quit, &["Q", "ctrl+c"], "Quit"),
+    ("help", Action::Help, &["H", "?", "f1"], "Help (this browser)"),
+    ("palette", Action::Palette, &["ctrl+p"], "Command palette"),
+    ("tool_options", Action::ToolOptions, &["ctrl+f"], "Tool flags editor"),
+    ("export", Action::Export, &["ctrl+e"], "Export the screen's table to CSV/JSON"),
+    ("alert_rules", Action::AlertRules, &["ctrl+r"], "Alert rules"),
+    ("profiles", Action::Profiles, &["ctrl+g"], "Scan profiles"),
+    ("wake_on_lan", Action::WakeOnLan, &["ctrl+w"], "Wake-on-LAN"),
+    ("mac_vendor", Action::MacVendor, &["ctrl+v"], "MAC vendor lookup"),
+    ("yank", Action::Yank, &["ctrl+y"], "Copy the selected row"),
+    ("event_log", Action::EventLog, &["ctrl+k"], "Event log (show/hide)"),
+    ("search", Action::Search, &["/", "ctrl+s"], "Search the screen's table"),
+    ("next_tab", Action::NextTab, &["tab"], "Next field, then next tab"),
+    ("prev_tab", Action::PrevTab, &["backtab"], "Previous field, then previous tab"),
+    ("split", Action::Split, &["ctrl+t"], "Split view (open/close pane)"),
+    ("focus_pane", Action::FocusPane, &["ctrl+o"], "Focus the other pane"),
+    ("goto_dashboard", Action::Goto(CurrentScreen::Dashboard), &["D", "alt+1"], "Dashboard"),
+    ("goto_ping", Action::Goto(CurrentScreen::Ping), &["P", "alt+2"], "Ping"),
+    ("goto_dns", Action::Goto(CurrentScreen::Dns), &["N", "alt+3"], "DNS"),
+    ("goto_sniffer", Action::Goto(CurrentScreen::Sniffer), &["S", "alt+4"], "Sniffer"),
+    ("goto_mtr", Action::Goto(CurrentScreen::Mtr), &["M", "alt+5"], "MTR"),
+    ("goto_scan", Action::Goto(CurrentScreen::Nmap), &["R", "alt+6"], "Port scan (Nmap)"),
+    ("goto_arp", Action::Goto(CurrentScreen::ArpScan), &["A", "alt+7"], "ARP scan"),
+    ("goto_connections", Action::Goto(CurrentScreen::Connections), &["C", "alt+8"], "Connections"),
+    ("goto_mdns", Action::Goto(CurrentScreen::Mdns), &["B", "alt+9"], "mDNS / Bonjour"),
+    ("goto_upnp", Action::Goto(CurrentScreen::Ssdp), &["U"], "SSDP / UPnP"),
+    ("goto_flows", Action::Goto(CurrentScreen::Flows), &["F"], "Flow collector"),
+    ("goto_history", Action::Goto(CurrentScreen::History), &["Y"], "History"),
+    ("goto_wifi", Action::Goto(CurrentScreen::Wifi), &["W"], "Wi-Fi"),
+    ("goto_dhcp", Action::Goto(CurrentScreen::Dhcp), &["O"], "DHCP"),
+    ("goto_traceroute", Action::Goto(CurrentScreen::Traceroute), &["T"], "Traceroute"),
+    ("goto_sweep", Action::Goto(CurrentScreen::Sweep), &["E"], "Ping sweep"),
+    ("goto_syslog", Action::Goto(CurrentScreen::Syslog), &["L"], "Syslog"),
+    ("goto_diagnose", Action::Goto(CurrentScreen::Diagnose), &["I"], "Diagnose"),
+    ("goto_devices", Action::Goto(CurrentScreen::Devices), &["V"], "Devices"),
+    ("goto_asn", Action::Goto(CurrentScreen::Asn), &["J"], "ASN / BGP prefixes"),
+    ("start", Action::Start, &["enter"], "Start / run the screen's tool"),
+    ("stop", Action::Stop, &["esc"], "Stop the screen's tool"),
+    ("up", Action::Up, &["up"], "Select previous row"),
+    ("down", Action::Down, &["down"], "Select next row"),
+    ("left", Action::Left, &["left"], "Previous choice (interface, severity...)"),
+    ("right", Action::Right, &["right"], "Next choice, or take a suggestion"),
+    // Tab moves between screens, so per-screen cycling (DNS record type, table sort...) has its own key
+    ("cycle", Action::Cycle, &["ctrl+n"], "Cycle the screen's option (record type, sort, view...)"),
+    ("toggle_view", Action::ToggleView, &["v"], "Switch view (Connections), path timeline (MTR)"),
+    ("toggle_map", Action::ToggleMap, &["g"], "Flat map / globe (Connections)"),
+    ("hostnames", Action::Hostnames, &["r"], "Hostnames / IPs (Connections)"),
+    ("edit_layout", Action::EditLayout, &["e"], "Edit layout (Dashboard)"),
+    ("ping_targets", Action::PingTargets, &["t"], "Latency ping targets (Dashboard)"),
+    ("pause", Action::Pause, &["p"], "Pause display (Sniffer)"),
+    ("clear", Action::Clear, &["c", "ctrl+l"], "Clear the list"),
+    ("auto_scroll", Action::AutoScroll, &["a"], "Auto-scroll on/off (Sniffer)"),
+    ("record", Action::Record, &["ctrl+b"], "Write packets to rotating pcap files (Sniffer)"),
+    ("zoom_in", Action::ZoomIn, &["+", "="], "Zoom charts in"),
+    ("zoom_out", Action::ZoomOut, &["-"], "Zoom charts out"),
+];
+
+/// Only active while a table search is showing, so they may reuse keys from `ACTIONS`.
+const SEARCH_ACTIONS: &ActionTable = &[
+    ("search_next", Action::SearchNext, &["n"], "Next search match"),
+    ("search_prev", Action::SearchPrev, &["N"], "Previous search match"),
+];
+
+type Entry = (Cow<'static, str>, Action, &'static [&'static str], &'static str);
+
+fn entries(table: &ActionTable) -> Vec<Entry> {
+    table.iter().map(|(name, action, keys, description)| (Cow::Borrowed(*name), *action, *keys, *description)).collect()
+}
+
+/// `ACTIONS` with a `goto_` action for each registered tool, the `goto_`s in tab order.
+fn actions() -> Vec<Entry> {
+    let mut actions = entries(ACTIONS);
+    let is_goto = |(_, action, _, _): &Entry| matches!(action, Action::Goto(_));
+    let start = actions.iter().position(is_goto).unwrap_or(0);
+    let mut end = actions.iter().rposition(is_goto).map_or(start, |i| i + 1);
+    for tool in screens::TOOLS {
+        actions.insert(end, (Cow::Owned(format!("goto_{}", tool.slug)), Action::Goto(tool.screen), tool.keys, tool.title));
+        end += 1;
+    }
+    actions[start..end].sort_by_key(|(_, action, _, _)| match action {
+        Action::Goto(screen) => CurrentScreen::ALL.iter().position(|s| s == screen),
+        _ => None,
+    });
+    actions
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct KeyBinding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    fn from_event(key: &KeyEvent) -> Self {
+        Self::normalized(key.code, key.modifiers)
+    }
+
+    // Shift is already part of an uppercase char / BackTab, and terminals disagree on reporting it
+    fn normalized(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers = modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if matches!(code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self { code, modifiers }
+    }
+
+    fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let (mods, key) = if s == "+" {
+            ("", "+")
+        } else if let Some(mods) = s.strip_suffix("++") {
+            (mods, "+")
+        } else {
+            s.rsplit_once('+').unwrap_or(("", s))
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for m in mods.split('+').filter(|m| !m.is_empty()) {
+            modifiers |= match m.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" | "option" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("Unknown modifier '{}' in '{}'", m, s)),
+            };
+        }
+
+        let code = match key.to_lowercase().as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "space" => KeyCode::Char(' '),
+            k if k.len() > 1 && k.starts_with('f') && k[1..].parse::<u8>().is_ok() => KeyCode::F(k[1..].parse().unwrap()),
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::Char(c.to_ascii_uppercase()),
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(format!("Unknown key '{}'", s)),
+                }
+            }
+        };
+        Ok(Self::normalized(code, modifiers))
+    }
+
+    fn label(&self) -> String {
+        let mut out = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) { out.push_str("Ctrl+"); }
+        if self.modifiers.contains(KeyModifiers::ALT) { out.push_str("Alt+"); }
+        if self.modifiers.contains(KeyModifiers::SHIFT) { out.push_str("Shift+"); }
+        match self.code {
+            KeyCode::Char(' ') => out.push_str("Space"),
+            KeyCode::Char(c) if self.modifiers.contains(KeyModifiers::CONTROL) => out.push(c.to_ascii_uppercase()),
+            KeyCode::Char(c) => out.push(c),
+            KeyCode::F(n) => out.push_str(&format!("F{}", n)),
+            KeyCode::BackTab => out.push_str("Shift+Tab"),
+            other => out.push_str(&format!("{:?}", other)),
+        }
+        out
+    }
+}
+
+pub struct Keymap {
+    bindings: HashMap<KeyBinding, Action>,
+    search: HashMap<KeyBinding, Action>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_config(&HashMap::new()).expect("default keymap is valid")
+    }
+}
+
+impl Keymap {
+    /// Builds the keymap from the `[keys]` config table (action name -> list of keys).
+    pub fn from_config(overrides: &HashMap<String, Vec<String>>) -> Result<Keymap, String> {
+        let (actions, search) = (actions(), entries(SEARCH_ACTIONS));
+        for name in overrides.keys() {
+            if !actions.iter().chain(&search).any(|(n, _, _, _)| n == name) {
+                return Err(format!("Unknown key action '{}'", name));
+            }
+        }
+
+        Ok(Keymap {
+            bindings: build(&actions, overrides)?,
+            search: build(&search, overrides)?,
+        })
+    }
+
+    pub fn action(&self, key: &KeyEvent) -> Option<Action> {
+        self.bindings.get(&KeyBinding::from_event(key)).copied()
+    }
+
+    pub fn search_action(&self, key: &KeyEvent) -> Option<Action> {
+        self.search.get(&KeyBinding::from_event(key)).copied()
+    }
+
+    /// First key bound to `action` for help text, e.g. "Ctrl+P".
+    pub fn label(&self, action: Action) -> String {
+        self.labels(action).into_iter().next().unwrap_or_else(|| "unbound".to_string())
+    }
+
+    /// Every key bound to `action`, shortest first.
+    pub fn labels(&self, action: Action) -> Vec<String> {
+        let mut labels: Vec<String> = self.bindings.iter().chain(&self.search)
+            .filter(|(_, a)| **a == action)
+            .map(|(k, _)| k.label())
+            .collect();
+        labels.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+        labels
+    }
+
+    /// Every action's heading, keys and description for the help browser, in table order.
+    pub fn help(&self) -> Vec<(&'static str, String, &'static str)> {
+        actions().iter().chain(&entries(SEARCH_ACTIONS)).map(|(_, action, _, description)| {
+            let keys = self.labels(*action);
+            let keys = if keys.is_empty() { "unbound".to_string() } else { keys.join(", ") };
+            (group(*action), keys, *description)
+        }).collect()
+    }
+}
+
+/// The help browser heading an action is listed under.
+fn group(action: Action) -> &'static str {
+    use Action::*;
+    match action {
+        Goto(_) => "Tabs",
+        Search | SearchNext | SearchPrev => "Search",
+        Start | Stop | Up | Down | Left | Right | Cycle | ToggleView | ToggleMap | Hostnames | EditLayout
+        | PingTargets | Pause | Clear | AutoScroll | ZoomIn | ZoomOut => "On screens",
+        _ => "Global",
+    }
+}
+
+fn build(actions: &[Entry], overrides: &HashMap<String, Vec<String>>) -> Result<HashMap<KeyBinding, Action>, String> {
+    let mut bindings = HashMap::new();
+    for (name, action, defaults, _) in actions {
+        if overrides.contains_key(name.as_ref()) {
+            continue;
+        }
+        for key in defaults.iter() {
+            bindings.insert(KeyBinding::parse(key)?, *action);
+        }
+    }
+
+    // User bindings win over any default that used the same key
+    let mut user: HashMap<KeyBinding, &Cow<str>> = HashMap::new();
+    for (name, action, _, _) in actions {
+        let Some(keys) = overrides.get(name.as_ref()) else { continue };
+        for key in keys {
+            let binding = KeyBinding::parse(key)?;
+            if let Some(other) = user.insert(binding, name) && other != name {
+                return Err(format!("Key '{}' is bound to both '{}' and '{}'", key, other, name));
+            }
+            bindings.insert(binding, *action);
+        }
+    }
+    Ok(bindings)
+}
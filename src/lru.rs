@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A map that forgets its least recently used entries past `capacity`. Eviction drops the
+/// oldest eighth at once, so a full cache pays for the scan once every `capacity / 8` inserts.
+pub struct LruCache<K, V> {
+    entries: HashMap<K, (V, u64)>,
+    capacity: usize,
+    clock: u64,
+}
+
+impl<K: Eq + Hash + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self { entries: HashMap::new(), capacity: capacity.max(8), clock: 0 }
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.clock += 1;
+        let clock = self.clock;
+        self.entries.get_mut(key).map(|(value, used)| {
+            *used = clock;
+            &*value
+        })
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            let mut used: Vec<u64> = self.entries.values().map(|(_, u)| *u).collect();
+            let cut = self.capacity / 8;
+            used.select_nth_unstable(cut);
+            let oldest = used[cut];
+            self.entries.retain(|_, (_, u)| *u > oldest);
+        }
+        self.clock += 1;
+        self.entries.insert(key, (value, self.clock));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -2,36 +2,112 @@ use std::{io, time::Duration};
 
 use anyhow::Result;
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+        KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 
+mod alerts;
+mod api;
 mod app;
+mod clipboard;
+mod completion;
+mod config;
+mod dashboard;
+mod eventlog;
+mod export;
+mod flags;
+mod focus;
+mod help;
+mod histogram;
+mod history;
+mod ingest;
+mod inventory;
+mod keymap;
+mod lru;
+mod metrics;
+mod palette;
+mod privileges;
+mod profiles;
+mod ring;
+mod scheduler;
+mod screens;
+mod search;
+mod series;
+mod shutdown;
+mod talkers;
+mod session;
 mod ui;
+mod units;
 mod tools;
 mod theme;
+mod triggers;
 
 use app::{App, CurrentScreen};
+use keymap::{Action, Keymap};
+use focus::Field;
+use help::HelpBrowser;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Load config before touching the terminal so errors print normally
+    let config = config::Config::load()?;
+    if !theme::set_theme(&config.ui.theme) {
+        anyhow::bail!("Unknown theme '{}' (available: {})", config.ui.theme, theme::theme_names().collect::<Vec<_>>().join(", "));
+    }
+    // https://no-color.org: the environment wins over the configured theme
+    if theme::no_color() {
+        theme::set_theme("mono");
+    }
+    units::configure(config.ui.rate_unit, config.ui.binary_prefixes);
+    let keymap = Keymap::from_config(&config.keys).map_err(|e| anyhow::anyhow!("Invalid [keys] config: {}", e))?;
+    let event_log = eventlog::init(&config.log).map_err(|e| anyhow::anyhow!(e))?;
+    tracing::info!("netops {} starting", env!("CARGO_PKG_VERSION"));
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
+    let mouse = config.ui.mouse;
+    if mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
+    // Terminals without the protocol would print the escape sequence as text
+    let enhanced_keyboard = config.ui.enhanced_keyboard && supports_keyboard_enhancement().unwrap_or(false);
+    if enhanced_keyboard {
+        execute!(stdout, PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES))?;
+    }
+
+    // A panic anywhere, a background thread included, puts the terminal back before the
+    // message prints, and stops the app rather than leaving it drawing over the message
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_terminal(mouse, enhanced_keyboard);
+        shutdown::request();
+        default_hook(info);
+    }));
+
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     // Create app
-    let mut app = App::new();
+    let mut app = App::new(config, keymap, event_log);
     app.start_background_tasks();
 
     // Run app
     let res = run_app(&mut terminal, &mut app).await;
 
     // Restore terminal
+    if enhanced_keyboard {
+        execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags)?;
+    }
+    if mouse {
+        execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    }
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
@@ -39,6 +115,13 @@ async fn main() -> Result<()> {
     )?;
     terminal.show_cursor()?;
 
+    // Stop the background tasks, and give capture threads a read timeout to close their channels
+    shutdown::request();
+    let deadline = std::time::Instant::now() + Duration::from_millis(500);
+    while app.sniffer.running() && std::time::Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+
     if let Err(err) = res {
         println!("{:?}", err);
     }
@@ -46,8 +129,25 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Best effort: the terminal may be already restored.
+fn restore_terminal(mouse: bool, enhanced_keyboard: bool) {
+    if enhanced_keyboard {
+        let _ = execute!(io::stdout(), PopKeyboardEnhancementFlags);
+    }
+    if mouse {
+        let _ = execute!(io::stdout(), DisableMouseCapture);
+    }
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), LeaveAlternateScreen, crossterm::cursor::Show);
+}
+
 use tui_input::backend::crossterm::EventHandler;
 
+/// Plain characters belong to a screen's text input while it's editable; only modified keys act as commands there.
+fn is_text_key(key: &KeyEvent) -> bool {
+    matches!(key.code, KeyCode::Char(_)) && !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+}
+
 async fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
@@ -56,6 +156,10 @@ async fn run_app<B: ratatui::backend::Backend>(
     let mut last_tick = std::time::Instant::now();
 
     loop {
+        // Only a panic requests shutdown before we quit; the terminal is no longer ours to draw on
+        if shutdown::requested() {
+            anyhow::bail!("A background task panicked");
+        }
         terminal.draw(|f| ui::ui(f, app))?;
 
         let timeout = tick_rate
@@ -66,160 +170,374 @@ async fn run_app<B: ratatui::backend::Backend>(
             let evt = event::read()?;
             match evt {
                 Event::Key(key) => {
-                    // Quick Tab Switching (Alt + 1-8)
-                    if key.modifiers.contains(event::KeyModifiers::ALT) {
-                        match key.code {
-                            KeyCode::Char('1') => { app.current_screen = CurrentScreen::Dashboard; continue; }
-                            KeyCode::Char('2') => { app.current_screen = CurrentScreen::Ping; continue; }
-                            KeyCode::Char('3') => { app.current_screen = CurrentScreen::Dns; continue; }
-                            KeyCode::Char('4') => { app.current_screen = CurrentScreen::Sniffer; continue; }
-                            KeyCode::Char('5') => { app.current_screen = CurrentScreen::Mtr; continue; }
-                            KeyCode::Char('6') => { app.current_screen = CurrentScreen::Nmap; continue; }
-                            KeyCode::Char('7') => { app.current_screen = CurrentScreen::ArpScan; continue; }
-                            KeyCode::Char('8') => { app.current_screen = CurrentScreen::Connections; continue; }
-                            _ => {}
+                    if let Some(editor) = app.flag_editor.as_mut() {
+                        if key.kind == KeyEventKind::Press {
+                            match key.code {
+                                KeyCode::Esc => app.flag_editor = None,
+                                KeyCode::Enter => app.apply_tool_options(),
+                                KeyCode::Up => editor.select(false),
+                                KeyCode::Down => editor.select(true),
+                                KeyCode::Char(' ') => editor.toggle(),
+                                KeyCode::Char(_) | KeyCode::Backspace | KeyCode::Delete
+                                | KeyCode::Left | KeyCode::Right | KeyCode::Home | KeyCode::End => {
+                                    if let Some(value) = editor.value_mut() {
+                                        value.handle_event(&Event::Key(key));
+                                    }
+                                }
+                                _ => {}
+                            }
                         }
+                        continue;
                     }
 
-                    if app.show_options {
-                         if key.kind == KeyEventKind::Press {
-                             match key.code {
-                                 KeyCode::Esc => app.show_options = false,
-                                 KeyCode::Up => {
-                                     if app.options_scroll > 0 {
-                                         app.options_scroll -= 1;
-                                     }
-                                 }
-                                 KeyCode::Down => {
-                                     let count = app.get_tool_options().len();
-                                     if count > 0 && app.options_scroll < count - 1 {
-                                         app.options_scroll += 1;
-                                     }
-                                 }
-                                 KeyCode::Enter => {
-                                     // Insert and close
-                                     let opts = app.get_tool_options();
-                                     if let Some((_, _, template)) = opts.get(app.options_scroll) {
-                                         let val = template.to_string();
-                                         // Append to active input
-                                         match app.current_screen {
-                                             CurrentScreen::Ping => {
-                                                 // TuiInput doesn't have direct append string method easily exposed as "type chars", 
-                                                 // but it handles KeyEvents. 
-                                                 // Or we can assume we can convert string to events?
-                                                 // Actually tui-input `handle_event` only takes events.
-                                                 // But we can just create events? 
-                                                 // Or better: accessing the value and replacing it is hard with just `Input`.
-                                                 // `tui-input` 0.8+ allows `with_value`.
-                                                 // Let's check `Cargo.toml`. `tui-input` = "0.8.0".
-                                                 // Actually checking view lines: `use tui_input::Input;`
-                                                 // Wait, modifying the input value directly?
-                                                 // `app.ping_input` is `Input`.
-                                                 // `Input` struct usually has `value()` accessor and maybe builder?
-                                                 // If not, simulating keys is safest.
-                                                 for c in val.chars() {
-                                                     app.ping_input.handle_event(&Event::Key(crossterm::event::KeyEvent::new(KeyCode::Char(c), crossterm::event::KeyModifiers::NONE)));
-                                                 }
-                                             }
-                                             CurrentScreen::Mtr => {
-                                                  for c in val.chars() {
-                                                     app.mtr_input.handle_event(&Event::Key(crossterm::event::KeyEvent::new(KeyCode::Char(c), crossterm::event::KeyModifiers::NONE)));
-                                                 }
-                                             }
-                                             CurrentScreen::Nmap => {
-                                                  for c in val.chars() {
-                                                     app.nmap_input.handle_event(&Event::Key(crossterm::event::KeyEvent::new(KeyCode::Char(c), crossterm::event::KeyModifiers::NONE)));
-                                                 }
-                                              }
-                                              CurrentScreen::ArpScan => {
-                                                  for c in val.chars() {
-                                                     app.arpscan_input.handle_event(&Event::Key(crossterm::event::KeyEvent::new(KeyCode::Char(c), crossterm::event::KeyModifiers::NONE)));
-                                                  }
-                                              }
-                                              _ => {}
-                                         }
-                                     }
-                                     app.show_options = false;
-                                 }
-                                 _ => {}
-                             }
-                         }
-                         continue;
+                    if let Some(selected) = app.filter_presets {
+                        if key.kind == KeyEventKind::Press {
+                            match key.code {
+                                KeyCode::Esc => app.filter_presets = None,
+                                KeyCode::Enter => app.apply_filter_preset(),
+                                KeyCode::Up => app.filter_presets = Some(selected.saturating_sub(1)),
+                                KeyCode::Down => {
+                                    let last = app.filter_choices().len().saturating_sub(1);
+                                    app.filter_presets = Some((selected + 1).min(last));
+                                }
+                                _ => {}
+                            }
+                        }
+                        continue;
                     }
 
-                     // Help Overlay Logic
-                    if app.show_help {
+                    if app.show_wol {
                         if key.kind == KeyEventKind::Press {
-                            app.show_help = false;
+                            match key.code {
+                                KeyCode::Esc => app.show_wol = false,
+                                KeyCode::Enter => app.send_wol(),
+                                KeyCode::Left => app.prev_wol_interface(),
+                                KeyCode::Right => app.next_wol_interface(),
+                                _ => {
+                                    app.wol_input.handle_event(&Event::Key(key));
+                                }
+                            }
                         }
-                        // Consume all events when help is showing
                         continue;
                     }
 
-                     if key.kind == KeyEventKind::Press {
-                        let mut handled = false;
-                        match key.code {
-                            KeyCode::Char('Q') => {
-                                app.quit();
-                                handled = true;
+                    if app.show_oui_lookup {
+                        if key.kind == KeyEventKind::Press {
+                            match key.code {
+                                KeyCode::Esc | KeyCode::Enter => app.show_oui_lookup = false,
+                                _ => {
+                                    app.oui_input.handle_event(&Event::Key(key));
+                                }
                             }
-                            KeyCode::Char('D') if key.modifiers.contains(event::KeyModifiers::SHIFT) => {
-                                app.current_screen = CurrentScreen::Dashboard;
-                                handled = true;
+                        }
+                        continue;
+                    }
+
+                    if app.show_conn_actions {
+                        if key.kind == KeyEventKind::Press {
+                            match key.code {
+                                KeyCode::Esc => app.show_conn_actions = false,
+                                KeyCode::Enter => app.run_conn_action(),
+                                KeyCode::Up => app.conn_action_selected = app.conn_action_selected.saturating_sub(1),
+                                KeyCode::Down if app.conn_action_selected + 1 < app::CONNECTION_ACTIONS.len() => app.conn_action_selected += 1,
+                                _ => {}
                             }
-                            KeyCode::Char('P') if key.modifiers.contains(event::KeyModifiers::SHIFT) => {
-                                app.current_screen = CurrentScreen::Ping;
-                                handled = true;
+                        }
+                        continue;
+                    }
+
+                    if app.confirm_kill.is_some() {
+                        if key.kind == KeyEventKind::Press {
+                            match key.code {
+                                KeyCode::Char('y') | KeyCode::Char('Y') => app.run_confirmed_kill(),
+                                _ => app.confirm_kill = None,
                             }
-                            KeyCode::Char('N') if key.modifiers.contains(event::KeyModifiers::SHIFT) => {
-                                app.current_screen = CurrentScreen::Dns;
-                                handled = true;
+                        }
+                        continue;
+                    }
+
+                    if app.show_whois {
+                        if key.kind == KeyEventKind::Press {
+                            match key.code {
+                                KeyCode::Esc | KeyCode::Enter => app.show_whois = false,
+                                KeyCode::Up => app.whois_scroll = app.whois_scroll.saturating_sub(1),
+                                KeyCode::Down => app.whois_scroll = app.whois_scroll.saturating_add(1),
+                                KeyCode::PageUp => app.whois_scroll = app.whois_scroll.saturating_sub(10),
+                                KeyCode::PageDown => app.whois_scroll = app.whois_scroll.saturating_add(10),
+                                _ => {}
                             }
-                            KeyCode::Char('S') if key.modifiers.contains(event::KeyModifiers::SHIFT) => {
-                                app.current_screen = CurrentScreen::Sniffer;
-                                handled = true;
+                        }
+                        continue;
+                    }
+
+                    if app.show_mtr_paths {
+                        if key.kind == KeyEventKind::Press {
+                            match key.code {
+                                KeyCode::Esc | KeyCode::Enter => app.show_mtr_paths = false,
+                                KeyCode::Up => app.mtr_paths_scroll = app.mtr_paths_scroll.saturating_sub(1),
+                                KeyCode::Down => app.mtr_paths_scroll = app.mtr_paths_scroll.saturating_add(1),
+                                KeyCode::PageUp => app.mtr_paths_scroll = app.mtr_paths_scroll.saturating_sub(10),
+                                KeyCode::PageDown => app.mtr_paths_scroll = app.mtr_paths_scroll.saturating_add(10),
+                                _ => {}
                             }
-                            KeyCode::Char('M') if key.modifiers.contains(event::KeyModifiers::SHIFT) => {
-                                app.current_screen = CurrentScreen::Mtr;
-                                handled = true;
+                        }
+                        continue;
+                    }
+
+                    if app.show_palette {
+                        if key.kind == KeyEventKind::Press {
+                            match key.code {
+                                KeyCode::Esc => app.show_palette = false,
+                                KeyCode::Enter => app.run_palette(),
+                                KeyCode::Up => app.palette_selected = app.palette_selected.saturating_sub(1),
+                                KeyCode::Down => {
+                                    if app.palette_selected + 1 < app.palette_matches.len() {
+                                        app.palette_selected += 1;
+                                    }
+                                }
+                                _ => {
+                                    app.palette_input.handle_event(&Event::Key(key));
+                                    app.update_palette();
+                                }
                             }
-                            KeyCode::Char('R') if key.modifiers.contains(event::KeyModifiers::SHIFT) => {
-                                app.current_screen = CurrentScreen::Nmap;
-                                handled = true;
+                        }
+                        continue;
+                    }
+
+                    if app.show_export {
+                        if key.kind == KeyEventKind::Press {
+                            match key.code {
+                                KeyCode::Esc => app.show_export = false,
+                                KeyCode::Enter => app.run_export(),
+                                KeyCode::Tab => app.toggle_export_format(),
+                                _ => {
+                                    app.export_input.handle_event(&Event::Key(key));
+                                }
                             }
-                             KeyCode::Char('A') if key.modifiers.contains(event::KeyModifiers::SHIFT) => {
-                                app.current_screen = CurrentScreen::ArpScan;
-                                handled = true;
+                        }
+                        continue;
+                    }
+
+                    if app.show_layout {
+                        if key.kind == KeyEventKind::Press {
+                            match key.code {
+                                KeyCode::Esc => app.show_layout = false,
+                                KeyCode::Up => app.layout_move(-1, 0),
+                                KeyCode::Down => app.layout_move(1, 0),
+                                KeyCode::Left => app.layout_move(0, -1),
+                                KeyCode::Right => app.layout_move(0, 1),
+                                KeyCode::Char('<') => app.layout_shift(false),
+                                KeyCode::Char('>') => app.layout_shift(true),
+                                KeyCode::Tab | KeyCode::Enter => app.layout_cycle(),
+                                KeyCode::Char('a') => app.layout_add(false),
+                                KeyCode::Char('r') => app.layout_add(true),
+                                KeyCode::Char('d') | KeyCode::Delete => app.layout_delete(),
+                                _ => {}
                             }
-                            KeyCode::Char('C') if key.modifiers.contains(event::KeyModifiers::SHIFT) => {
-                                app.current_screen = CurrentScreen::Connections;
-                                handled = true;
+                        }
+                        continue;
+                    }
+
+                    if app.show_probe_targets {
+                        if key.kind == KeyEventKind::Press {
+                            match key.code {
+                                KeyCode::Esc => app.show_probe_targets = false,
+                                KeyCode::Enter => app.save_probe_targets(),
+                                _ => {
+                                    app.probe_targets_input.handle_event(&Event::Key(key));
+                                }
                             }
-                            KeyCode::Char('?') | KeyCode::Char('H') => {
-                                app.show_help = true;
-                                handled = true;
+                        }
+                        continue;
+                    }
+
+                    if app.show_profiles {
+                        if key.kind == KeyEventKind::Press {
+                            match key.code {
+                                KeyCode::Esc => app.show_profiles = false,
+                                KeyCode::Enter if app.profiles_input.value().trim().is_empty() => app.run_profile(),
+                                KeyCode::Enter => app.save_profile(),
+                                KeyCode::Delete => app.delete_profile(),
+                                KeyCode::Up => app.profiles_selected = app.profiles_selected.saturating_sub(1),
+                                KeyCode::Down => {
+                                    if app.profiles_selected + 1 < app.config.profiles.len() {
+                                        app.profiles_selected += 1;
+                                    }
+                                }
+                                _ => {
+                                    app.profiles_input.handle_event(&Event::Key(key));
+                                }
                             }
-                            KeyCode::Char('f') => {
-                                if key.modifiers.contains(event::KeyModifiers::CONTROL) {
-                                    app.show_options = !app.show_options;
-                                    app.options_scroll = 0;
-                                    handled = true;
+                        }
+                        continue;
+                    }
+
+                    if app.show_alerts {
+                        if key.kind == KeyEventKind::Press {
+                            match key.code {
+                                KeyCode::Esc => app.show_alerts = false,
+                                KeyCode::Enter => app.add_alert_rule(),
+                                KeyCode::Delete => app.delete_alert_rule(),
+                                KeyCode::Up => app.alerts_selected = app.alerts_selected.saturating_sub(1),
+                                KeyCode::Down => {
+                                    if app.alerts_selected + 1 < app.config.alerts.rules.len() {
+                                        app.alerts_selected += 1;
+                                    }
+                                }
+                                _ => {
+                                    app.alerts_input.handle_event(&Event::Key(key));
                                 }
                             }
-                            _ => {}
                         }
+                        continue;
+                    }
+
+                    if let Some(search) = app.search.as_mut()
+                        && search.editing
+                    {
+                        if key.kind == KeyEventKind::Press {
+                            match key.code {
+                                KeyCode::Esc => app.clear_search(),
+                                KeyCode::Enter => app.commit_search(),
+                                _ => {
+                                    search.input.handle_event(&Event::Key(key));
+                                    app.update_search();
+                                }
+                            }
+                        }
+                        continue;
+                    }
+
+                    // The help browser takes every key: typing searches it
+                    if app.help.is_some() {
+                        if key.kind == KeyEventKind::Press {
+                            let count = app.help.as_ref().map_or(0, |h| h.lines(&app.keymap, app.current_screen).len());
+                            let help = app.help.as_mut().expect("checked above");
+                            match key.code {
+                                KeyCode::Esc if help.input.value().is_empty() => app.help = None,
+                                KeyCode::Esc => *help = HelpBrowser::default(),
+                                KeyCode::Up => help.scroll = help.scroll.saturating_sub(1),
+                                KeyCode::Down => help.scroll = (help.scroll + 1).min(count.saturating_sub(1)),
+                                KeyCode::PageUp => help.scroll = help.scroll.saturating_sub(10),
+                                KeyCode::PageDown => help.scroll = (help.scroll + 10).min(count.saturating_sub(1)),
+                                KeyCode::Home => help.scroll = 0,
+                                KeyCode::End => help.scroll = count.saturating_sub(1),
+                                _ => {
+                                    if help.input.handle_event(&Event::Key(key)).is_some_and(|c| c.value) {
+                                        help.scroll = 0;
+                                    }
+                                }
+                            }
+                        }
+                        continue;
+                    }
+
+                    if key.kind == KeyEventKind::Press {
+                        let action = app.keymap.action(&key);
+                        // Letters bound to tabs, help or quit are text while an input is taking it
+                        let action = action.filter(|a| {
+                            !(matches!(a, Action::Goto(_) | Action::Help | Action::Quit) && is_text_key(&key) && app.typing())
+                        });
+
+                        // A committed search on this screen claims n/N and Esc before the regular bindings
+                        if app.active_search().is_some() {
+                            match app.keymap.search_action(&key).or(action) {
+                                Some(Action::SearchNext) => { app.search_jump(true); continue; }
+                                Some(Action::SearchPrev) => { app.search_jump(false); continue; }
+                                Some(Action::Stop) => { app.clear_search(); continue; }
+                                _ => {}
+                            }
+                        }
+
+                        let handled = match action {
+                            Some(Action::Quit) => {
+                                app.quit();
+                                true
+                            }
+                            Some(Action::Help) => {
+                                app.help = Some(HelpBrowser::default());
+                                true
+                            }
+                            Some(Action::ToolOptions) => {
+                                app.open_tool_options();
+                                true
+                            }
+                            Some(Action::Palette) => {
+                                app.open_palette();
+                                true
+                            }
+                            Some(Action::Export) => {
+                                app.open_export();
+                                true
+                            }
+                            Some(Action::AlertRules) => {
+                                app.open_alerts();
+                                true
+                            }
+                            Some(Action::Profiles) => {
+                                app.open_profiles();
+                                true
+                            }
+                            Some(Action::WakeOnLan) if app.current_screen == CurrentScreen::ArpScan => {
+                                app.open_wol();
+                                true
+                            }
+                            Some(Action::MacVendor) => {
+                                app.open_oui_lookup();
+                                true
+                            }
+                            Some(Action::Goto(screen)) => {
+                                app.goto_screen(screen);
+                                true
+                            }
+                            Some(Action::Yank) => {
+                                app.yank();
+                                true
+                            }
+                            Some(Action::EventLog) => {
+                                app.show_log = !app.show_log;
+                                true
+                            }
+                            Some(Action::Split) => {
+                                app.toggle_split();
+                                true
+                            }
+                            Some(Action::FocusPane) => {
+                                app.focus_other_pane();
+                                true
+                            }
+                            Some(Action::NextTab) => {
+                                app.step_focus(true);
+                                true
+                            }
+                            Some(Action::PrevTab) => {
+                                app.step_focus(false);
+                                true
+                            }
+                            _ => false,
+                        };
 
-                        // Screen specific keys
-                        if !handled {
+                        // Screen specific keys; anything unbound falls through to the screen's input
+                        let tool_search = action == Some(Action::Search)
+                            && app.tools.get(app.current_screen).is_some_and(|t| t.search_rows().is_some() && (t.active() || !is_text_key(&key)));
+                        if !handled && tool_search {
+                            app.open_search();
+                        } else if !handled && let Some(tool) = app.tools.get_mut(app.current_screen) {
+                            tool.handle_key(action, key);
+                        } else if !handled {
                             match app.current_screen {
                                 CurrentScreen::Ping => {
-                                    match key.code {
-                                        KeyCode::Enter => {
+                                    match action {
+                                        Some(Action::Start) => {
                                             app.start_ping();
                                         }
-                                        KeyCode::Esc => {
+                                        Some(Action::Stop) => {
                                             app.stop_ping();
                                         }
+                                        Some(Action::Cycle) => app.ping_heatmap_view = !app.ping_heatmap_view,
+                                        Some(Action::Up) if !app.is_pinging => app.recall_input(true),
+                                        Some(Action::Down) if !app.is_pinging => app.recall_input(false),
+                                        Some(Action::Right) if app.input_suggestion(CurrentScreen::Ping).is_some() => app.accept_suggestion(),
                                         _ => {
                                             if !app.is_pinging {
                                                 app.ping_input.handle_event(&Event::Key(key));
@@ -228,100 +546,184 @@ async fn run_app<B: ratatui::backend::Backend>(
                                     }
                                 }
                                 CurrentScreen::Dns => {
-                                    match key.code {
-                                        KeyCode::Enter => {
+                                    match action {
+                                        Some(Action::Start) => {
                                             app.start_dns_lookup();
                                         }
-                                        KeyCode::Tab => {
+                                        Some(Action::Stop) if app.dns_watch.is_some() => app.stop_dns_watch(),
+                                        Some(Action::Cycle) if app.focused(CurrentScreen::Dns) == Some(Field::DnsResults) => app.send_subdomains_to_nmap(),
+                                        Some(Action::Up) if app.focused(CurrentScreen::Dns) == Some(Field::DnsResults) => {
+                                            if let Some(scan) = app.dns_subdomains.as_mut() {
+                                                scan.select(false);
+                                            }
+                                        }
+                                        Some(Action::Down) if app.focused(CurrentScreen::Dns) == Some(Field::DnsResults) => {
+                                            if let Some(scan) = app.dns_subdomains.as_mut() {
+                                                scan.select(true);
+                                            }
+                                        }
+                                        _ if key.code == KeyCode::Char(' ') && app.focused(CurrentScreen::Dns) == Some(Field::DnsResults) => {
+                                            if let Some(scan) = app.dns_subdomains.as_mut() {
+                                                scan.toggle_pick();
+                                            }
+                                        }
+                                        Some(Action::Cycle) => {
                                             app.next_dns_record_type();
                                         }
+                                        Some(Action::Right | Action::Down) if app.focused(CurrentScreen::Dns) == Some(Field::DnsType) => app.next_dns_record_type(),
+                                        Some(Action::Left | Action::Up) if app.focused(CurrentScreen::Dns) == Some(Field::DnsType) => app.prev_dns_record_type(),
+                                        Some(Action::Up) => app.recall_input(true),
+                                        Some(Action::Down) => app.recall_input(false),
+                                        Some(Action::Right) if app.input_suggestion(CurrentScreen::Dns).is_some() => app.accept_suggestion(),
                                         _ => {
-                                            app.dns_input.handle_event(&Event::Key(key));
+                                            if app.focused(CurrentScreen::Dns) == Some(Field::DnsDomain) {
+                                                app.dns_input.handle_event(&Event::Key(key));
+                                            }
                                         }
                                     }
                                 }
                                 CurrentScreen::Sniffer => {
-                                    match key.code {
-                                        KeyCode::Enter => {
+                                    match action {
+                                        Some(Action::Search) if app.sniffer_active || !is_text_key(&key) => app.open_search(),
+                                        Some(Action::Start) => {
                                             if app.sniffer_active {
                                                 app.stop_sniffer();
                                             } else {
                                                 app.start_sniffer();
                                             }
                                         }
-                                        KeyCode::Esc => {
+                                        Some(Action::Stop) => {
                                             if app.sniffer_active {
                                                  app.stop_sniffer();
                                             }
                                         }
-                                        KeyCode::Left => {
+                                        Some(Action::Left) if app.focused(CurrentScreen::Sniffer) == Some(Field::SnifferInterface) => {
                                             if app.selected_interface_index > 0 {
                                                 app.selected_interface_index -= 1;
                                             }
                                         }
-                                        KeyCode::Right => {
-                                            if app.selected_interface_index < app.interfaces.len().saturating_sub(1) {
+                                        Some(Action::Right) if app.focused(CurrentScreen::Sniffer) == Some(Field::SnifferInterface) => {
+                                            // One past the last interface is "All"
+                                            if app.selected_interface_index < app.interfaces.len() {
                                                 app.selected_interface_index += 1;
                                             }
                                         }
+                                        Some(Action::Cycle) if !app.sniffer_active => app.toggle_sniffer_interface(),
+                                        Some(Action::Up) if app.sniffer_selected > 0 => {
+                                            app.sniffer_selected -= 1;
+                                            app.sniffer_table_state.select(Some(app.sniffer_selected));
+                                        }
+                                        Some(Action::Pause) if app.sniffer_active => app.toggle_sniffer_pause(),
+                                        Some(Action::Clear) if app.sniffer_active || !is_text_key(&key) => app.clear_sniffer(),
+                                        Some(Action::AutoScroll) if app.sniffer_active => app.sniffer_autoscroll = !app.sniffer_autoscroll,
+                                        Some(Action::Record) => app.toggle_recording(),
+                                        Some(Action::Down) if app.sniffer_view().next().is_some() => {
+                                            if app.sniffer_table_state.selected().is_some() && app.sniffer_selected + 1 < app.sniffer_view().count() {
+                                                app.sniffer_selected += 1;
+                                            }
+                                            app.sniffer_table_state.select(Some(app.sniffer_selected));
+                                        }
                                         _ => {
-                                            if !app.sniffer_active {
+                                            if !app.sniffer_active && app.focused(CurrentScreen::Sniffer) == Some(Field::SnifferFilter) {
                                                 app.sniffer_filter_input.handle_event(&Event::Key(key));
                                             }
                                         }
                                     }
                                 }
                                 CurrentScreen::Mtr => {
-                                    match key.code {
-                                        KeyCode::Enter => {
+                                    match action {
+                                        Some(Action::Search) if app.mtr_active || !is_text_key(&key) => app.open_search(),
+                                        Some(Action::Start) => {
                                             app.start_mtr();
                                         }
-                                        KeyCode::Esc => {
+                                        Some(Action::Stop) => {
                                             app.stop_mtr();
                                         }
-                                        KeyCode::Up => {
+                                        Some(Action::Cycle) if app.mtr_table_state.selected().is_some() => app.check_hop_routing(),
+                                        Some(Action::ToggleView) if app.mtr_active || !is_text_key(&key) => app.open_mtr_paths(),
+                                        // History while there are no hops to move through, or a new target is being typed
+                                        Some(Action::Up) if !app.mtr_active && (app.mtr_hops.is_empty() || app.mtr_editing) => app.recall_input(true),
+                                        Some(Action::Down) if !app.mtr_active && (app.mtr_hops.is_empty() || app.mtr_editing) => app.recall_input(false),
+                                        Some(Action::Right) if app.input_suggestion(CurrentScreen::Mtr).is_some() => app.accept_suggestion(),
+                                        Some(Action::Up) => {
                                             if app.mtr_selected_hop > 0 {
                                                 app.mtr_selected_hop -= 1;
                                                 app.mtr_table_state.select(Some(app.mtr_selected_hop));
                                             }
                                         }
-                                        KeyCode::Down => {
+                                        Some(Action::Down) => {
                                             if app.mtr_selected_hop < app.mtr_hops.len().saturating_sub(1) {
                                                 app.mtr_selected_hop += 1;
                                                 app.mtr_table_state.select(Some(app.mtr_selected_hop));
                                             }
                                         }
                                         _ => {
-                                            if !app.mtr_active {
-                                                app.mtr_input.handle_event(&Event::Key(key));
+                                            if !app.mtr_active && app.mtr_input.handle_event(&Event::Key(key)).is_some() {
+                                                app.mtr_editing = true;
                                             }
                                         }
                                     }
 
                                 }
                                 CurrentScreen::Nmap => {
-                                    match key.code {
-                                        KeyCode::Enter => {
+                                    match action {
+                                        Some(Action::Search) if app.nmap_active || !is_text_key(&key) => app.open_search(),
+                                        Some(Action::Start) => {
                                             app.start_nmap();
                                         }
-                                        KeyCode::Esc => {
+                                        Some(Action::Stop) => {
                                             app.stop_nmap();
                                         }
+                                        Some(Action::Up) if !app.nmap_active && (app.nmap_output.is_empty() || app.nmap_editing) => app.recall_input(true),
+                                        Some(Action::Down) if !app.nmap_active && (app.nmap_output.is_empty() || app.nmap_editing) => app.recall_input(false),
+                                        Some(Action::Right) if app.input_suggestion(CurrentScreen::Nmap).is_some() => app.accept_suggestion(),
+                                        Some(Action::Up) => app.step_nmap_line(false),
+                                        Some(Action::Down) => app.step_nmap_line(true),
                                         _ => {
-                                            if !app.nmap_active {
-                                                app.nmap_input.handle_event(&Event::Key(key));
+                                            if !app.nmap_active && app.nmap_input.handle_event(&Event::Key(key)).is_some() {
+                                                app.nmap_editing = true;
                                             }
                                         }
                                     }
                                 }
                                 CurrentScreen::ArpScan => {
-                                    match key.code {
-                                        KeyCode::Enter => {
+                                    match action {
+                                        Some(Action::Search) if app.arpscan_active || !is_text_key(&key) => app.open_search(),
+                                        Some(Action::Start) => {
                                             app.start_arpscan();
                                         }
-                                        KeyCode::Esc => {
+                                        Some(Action::Stop) => {
                                             app.stop_arpscan();
                                         }
+                                        Some(Action::Cycle) => app.toggle_arp_view(),
+                                        Some(Action::Up) if app.arp_view == app::ArpView::Neighbors => {
+                                            if app.neighbors_selected > 0 {
+                                                app.neighbors_selected -= 1;
+                                            }
+                                            app.neighbors_table_state.select(Some(app.neighbors_selected));
+                                        }
+                                        Some(Action::Down) if app.arp_view == app::ArpView::Neighbors => {
+                                            if app.neighbors_table_state.selected().is_some() && app.neighbors_selected + 1 < app.neighbors.entries.len() {
+                                                app.neighbors_selected += 1;
+                                            }
+                                            if !app.neighbors.entries.is_empty() {
+                                                app.neighbors_table_state.select(Some(app.neighbors_selected));
+                                            }
+                                        }
+                                        Some(Action::Up) => {
+                                            if app.arpscan_selected > 0 {
+                                                app.arpscan_selected -= 1;
+                                                app.arpscan_table_state.select(Some(app.arpscan_selected));
+                                            }
+                                        }
+                                        Some(Action::Down) => {
+                                            if !app.arpscan_results.is_empty() {
+                                                if app.arpscan_table_state.selected().is_some() && app.arpscan_selected < app.arpscan_results.len() - 1 {
+                                                    app.arpscan_selected += 1;
+                                                }
+                                                app.arpscan_table_state.select(Some(app.arpscan_selected));
+                                            }
+                                        }
                                         _ => {
                                             if !app.arpscan_active {
                                                 app.arpscan_input.handle_event(&Event::Key(key));
@@ -329,7 +731,363 @@ async fn run_app<B: ratatui::backend::Backend>(
                                         }
                                     }
                                 }
-                                _ => {}
+                                CurrentScreen::Mdns => {
+                                    match action {
+                                        Some(Action::Start) => {
+                                            if app.mdns_active {
+                                                app.stop_mdns();
+                                            } else {
+                                                app.start_mdns();
+                                            }
+                                        }
+                                        Some(Action::Stop) => {
+                                            app.stop_mdns();
+                                        }
+                                        Some(Action::Up) if app.mdns_selected > 0 => {
+                                            app.mdns_selected -= 1;
+                                            app.mdns_table_state.select(Some(app.mdns_selected));
+                                        }
+                                        Some(Action::Down) if !app.mdns_services.is_empty() => {
+                                            if app.mdns_table_state.selected().is_some() && app.mdns_selected < app.mdns_services.len() - 1 {
+                                                app.mdns_selected += 1;
+                                            }
+                                            app.mdns_table_state.select(Some(app.mdns_selected));
+                                        }
+                                        _ => {}
+                                    }
+                                }
+                                CurrentScreen::Ssdp => {
+                                    match action {
+                                        Some(Action::Start) => {
+                                            if app.ssdp_active {
+                                                app.stop_ssdp();
+                                            } else {
+                                                app.start_ssdp();
+                                            }
+                                        }
+                                        Some(Action::Stop) => {
+                                            app.stop_ssdp();
+                                        }
+                                        Some(Action::Up) if app.ssdp_selected > 0 => {
+                                            app.ssdp_selected -= 1;
+                                            app.ssdp_table_state.select(Some(app.ssdp_selected));
+                                        }
+                                        Some(Action::Down) if !app.ssdp_devices.is_empty() => {
+                                            if app.ssdp_table_state.selected().is_some() && app.ssdp_selected < app.ssdp_devices.len() - 1 {
+                                                app.ssdp_selected += 1;
+                                            }
+                                            app.ssdp_table_state.select(Some(app.ssdp_selected));
+                                        }
+                                        _ => {}
+                                    }
+                                }
+                                CurrentScreen::Sweep => {
+                                    match action {
+                                        Some(Action::Start) => {
+                                            if app.sweep_active {
+                                                app.stop_sweep();
+                                            } else {
+                                                app.start_sweep();
+                                            }
+                                        }
+                                        Some(Action::Stop) => {
+                                            app.stop_sweep();
+                                        }
+                                        Some(Action::Cycle) => app.send_sweep_to_nmap(),
+                                        Some(Action::Up) if app.sweep_selected > 0 => {
+                                            app.sweep_selected -= 1;
+                                            app.sweep_table_state.select(Some(app.sweep_selected));
+                                        }
+                                        Some(Action::Down) if !app.sweep_hosts.is_empty() => {
+                                            if app.sweep_table_state.selected().is_some() && app.sweep_selected < app.sweep_hosts.len() - 1 {
+                                                app.sweep_selected += 1;
+                                            }
+                                            app.sweep_table_state.select(Some(app.sweep_selected));
+                                        }
+                                        _ => {
+                                            if !app.sweep_active {
+                                                app.sweep_input.handle_event(&Event::Key(key));
+                                            }
+                                        }
+                                    }
+                                }
+                                CurrentScreen::Traceroute => {
+                                    let hops = app.trace_result.as_ref().map_or(0, |r| r.hops.len());
+                                    match action {
+                                        Some(Action::Start) => app.start_traceroute(),
+                                        Some(Action::Cycle) if !app.trace_active => app.send_trace_to_mtr(),
+                                        Some(Action::Up) if app.trace_selected > 0 => {
+                                            app.trace_selected -= 1;
+                                            app.trace_table_state.select(Some(app.trace_selected));
+                                        }
+                                        Some(Action::Down) if hops > 0 => {
+                                            if app.trace_table_state.selected().is_some() && app.trace_selected < hops - 1 {
+                                                app.trace_selected += 1;
+                                            }
+                                            app.trace_table_state.select(Some(app.trace_selected));
+                                        }
+                                        _ => {
+                                            if !app.trace_active {
+                                                app.trace_input.handle_event(&Event::Key(key));
+                                            }
+                                        }
+                                    }
+                                }
+                                CurrentScreen::Dhcp => {
+                                    match action {
+                                        Some(Action::Start) => app.start_dhcp(),
+                                        Some(Action::Cycle) if !app.dhcp_active => app.dhcp_inform = !app.dhcp_inform,
+                                        Some(Action::Left) if app.dhcp_interface_index > 0 => {
+                                            app.dhcp_interface_index -= 1;
+                                        }
+                                        Some(Action::Right) if app.dhcp_interface_index + 1 < app.interfaces.len() => {
+                                            app.dhcp_interface_index += 1;
+                                        }
+                                        Some(Action::Up) if app.dhcp_selected > 0 => {
+                                            app.dhcp_selected -= 1;
+                                            app.dhcp_table_state.select(Some(app.dhcp_selected));
+                                        }
+                                        Some(Action::Down) if !app.dhcp_replies.is_empty() => {
+                                            if app.dhcp_table_state.selected().is_some() && app.dhcp_selected < app.dhcp_replies.len() - 1 {
+                                                app.dhcp_selected += 1;
+                                            }
+                                            app.dhcp_table_state.select(Some(app.dhcp_selected));
+                                        }
+                                        _ => {}
+                                    }
+                                }
+                                CurrentScreen::Wifi => {
+                                    match action {
+                                        Some(Action::Start) => {
+                                            if app.wifi_active {
+                                                app.stop_wifi();
+                                            } else {
+                                                app.start_wifi();
+                                            }
+                                        }
+                                        Some(Action::Stop) => {
+                                            app.stop_wifi();
+                                        }
+                                        Some(Action::Up) if app.wifi_selected > 0 => {
+                                            app.wifi_selected -= 1;
+                                            app.wifi_table_state.select(Some(app.wifi_selected));
+                                        }
+                                        Some(Action::Down) if !app.wifi_networks.is_empty() => {
+                                            if app.wifi_table_state.selected().is_some() && app.wifi_selected < app.wifi_networks.len() - 1 {
+                                                app.wifi_selected += 1;
+                                            }
+                                            app.wifi_table_state.select(Some(app.wifi_selected));
+                                        }
+                                        _ => {}
+                                    }
+                                }
+                                CurrentScreen::Syslog => {
+                                    let shown = app.syslog_visible().len();
+                                    match action {
+                                        Some(Action::Start) => {
+                                            if app.syslog_active {
+                                                app.stop_syslog();
+                                            } else {
+                                                app.start_syslog();
+                                            }
+                                        }
+                                        Some(Action::Stop) => app.stop_syslog(),
+                                        Some(Action::Left) if app.focused(CurrentScreen::Syslog) == Some(Field::SyslogPort) => app.shift_syslog_severity(false),
+                                        Some(Action::Right) if app.focused(CurrentScreen::Syslog) == Some(Field::SyslogPort) => app.shift_syslog_severity(true),
+                                        Some(Action::Up) if app.syslog_selected > 0 => {
+                                            app.syslog_selected -= 1;
+                                            app.syslog_table_state.select(Some(app.syslog_selected));
+                                        }
+                                        Some(Action::Down) if shown > 0 => {
+                                            if app.syslog_table_state.selected().is_some() && app.syslog_selected < shown - 1 {
+                                                app.syslog_selected += 1;
+                                            }
+                                            app.syslog_table_state.select(Some(app.syslog_selected));
+                                        }
+                                        _ => {
+                                            if app.focused(CurrentScreen::Syslog) == Some(Field::SyslogFilter) {
+                                                app.syslog_filter_input.handle_event(&Event::Key(key));
+                                                app.syslog_selected = 0;
+                                                app.syslog_table_state.select(None);
+                                            } else if !app.syslog_active {
+                                                app.syslog_input.handle_event(&Event::Key(key));
+                                            }
+                                        }
+                                    }
+                                }
+                                CurrentScreen::Diagnose => {
+                                    match action {
+                                        Some(Action::Start) => {
+                                            if app.diagnose_active {
+                                                app.stop_diagnose();
+                                            } else {
+                                                app.start_diagnose();
+                                            }
+                                        }
+                                        Some(Action::Stop) => app.stop_diagnose(),
+                                        _ => {}
+                                    }
+                                }
+                                CurrentScreen::Flows => {
+                                    match action {
+                                        Some(Action::Start) => {
+                                            if app.flows_active {
+                                                app.stop_flows();
+                                            } else {
+                                                app.start_flows();
+                                            }
+                                        }
+                                        Some(Action::Stop) => {
+                                            app.stop_flows();
+                                        }
+                                        _ => {
+                                            if !app.flows_active {
+                                                app.flows_input.handle_event(&Event::Key(key));
+                                            }
+                                        }
+                                    }
+                                }
+                                CurrentScreen::Connections => {
+                                    match action {
+                                        Some(Action::Search) => app.open_search(),
+                                        Some(Action::Start) if app.connections_view == app::ConnectionsView::Listening => app.kill_selected_listener(),
+                                        Some(Action::Start) => app.open_conn_actions(),
+                                        Some(Action::Cycle) if app.connections_view == app::ConnectionsView::Talkers => app.cycle_talkers_window(),
+                                        Some(Action::Cycle) => app.cycle_connections_sort(),
+                                        Some(Action::ToggleView) => app.toggle_connections_view(),
+                                        Some(Action::ToggleMap) => app.connections_globe = !app.connections_globe,
+                                        Some(Action::Hostnames) => app.toggle_hostnames(),
+                                        Some(Action::Up) if app.connections_view == app::ConnectionsView::Listening => {
+                                            if app.listeners_selected > 0 {
+                                                app.listeners_selected -= 1;
+                                            }
+                                            app.listeners_table_state.select(Some(app.listeners_selected));
+                                        }
+                                        Some(Action::Down) if app.connections_view == app::ConnectionsView::Listening => {
+                                            if app.listeners_table_state.selected().is_some() && app.listeners_selected + 1 < app.listeners.len() {
+                                                app.listeners_selected += 1;
+                                            }
+                                            if !app.listeners.is_empty() {
+                                                app.listeners_table_state.select(Some(app.listeners_selected));
+                                            }
+                                        }
+                                        Some(Action::Up) if app.connections_view == app::ConnectionsView::Talkers => {
+                                            if app.talkers_selected > 0 {
+                                                app.talkers_selected -= 1;
+                                            }
+                                            app.talkers_table_state.select(Some(app.talkers_selected));
+                                        }
+                                        Some(Action::Down) if app.connections_view == app::ConnectionsView::Talkers => {
+                                            let count = app.top_talkers().len();
+                                            if app.talkers_table_state.selected().is_some() && app.talkers_selected + 1 < count {
+                                                app.talkers_selected += 1;
+                                            }
+                                            if count > 0 {
+                                                app.talkers_table_state.select(Some(app.talkers_selected));
+                                            }
+                                        }
+                                        Some(Action::Up) => {
+                                            if let Some(row) = app.selected_connection_row() {
+                                                app.select_connection(row.saturating_sub(1));
+                                            }
+                                        }
+                                        // Nothing highlighted (or the peer has gone): start from the top
+                                        Some(Action::Down) => {
+                                            let count = app.active_connections.len();
+                                            let next = app.selected_connection_row().map_or(0, |row| (row + 1).min(count.saturating_sub(1)));
+                                            app.select_connection(next);
+                                        }
+                                        _ => {}
+                                    }
+                                }
+                                CurrentScreen::Dashboard => {
+                                    match action {
+                                        Some(Action::ZoomIn) => app.dash_range = app.dash_range.zoom_in(),
+                                        Some(Action::EditLayout) => app.open_layout(),
+                                        Some(Action::PingTargets) => app.open_probe_targets(),
+                                        Some(Action::Up) => app.select_dash_interface(false),
+                                        Some(Action::Down) => app.select_dash_interface(true),
+                                        Some(Action::Start) => app.open_interface_detail(),
+                                        Some(Action::Stop) => app.iface_detail = None,
+                                        Some(Action::ZoomOut) => app.dash_range = app.dash_range.zoom_out(),
+                                        _ => {}
+                                    }
+                                }
+                                CurrentScreen::Asn => {
+                                    match action {
+                                        Some(Action::Start) if matches!(app.asn_result, Some(tools::asn::AsnResult::Search(..))) && app.asn_table_state.selected().is_some() => app.open_asn_match(),
+                                        Some(Action::Start) => app.start_asn(),
+                                        Some(Action::Up) if app.asn_result.is_some() => {
+                                            app.asn_selected = app.asn_selected.saturating_sub(1);
+                                            app.asn_table_state.select(Some(app.asn_selected));
+                                        }
+                                        Some(Action::Down) if app.asn_result.is_some() => {
+                                            let count = app.asn_result.as_ref().map(|r| r.rows().len()).unwrap_or(0);
+                                            if app.asn_table_state.selected().is_some() && app.asn_selected + 1 < count {
+                                                app.asn_selected += 1;
+                                            }
+                                            if count > 0 {
+                                                app.asn_table_state.select(Some(app.asn_selected));
+                                            }
+                                        }
+                                        Some(Action::Up) => app.recall_input(true),
+                                        Some(Action::Down) => app.recall_input(false),
+                                        Some(Action::Right) if app.input_suggestion(CurrentScreen::Asn).is_some() => app.accept_suggestion(),
+                                        _ => {
+                                            // Typing starts a new query, so Enter no longer opens a match
+                                            app.asn_table_state.select(None);
+                                            app.asn_input.handle_event(&Event::Key(key));
+                                        }
+                                    }
+                                }
+                                CurrentScreen::Devices => {
+                                    match action {
+                                        Some(Action::Search) => app.open_search(),
+                                        Some(Action::Start) => app.start_arpscan(),
+                                        Some(Action::Clear) => app.acknowledge_devices(),
+                                        Some(Action::Up) => {
+                                            if app.devices_selected > 0 {
+                                                app.devices_selected -= 1;
+                                            }
+                                            if !app.devices().is_empty() {
+                                                app.devices_table_state.select(Some(app.devices_selected));
+                                            }
+                                        }
+                                        Some(Action::Down) => {
+                                            if app.devices_table_state.selected().is_some() && app.devices_selected + 1 < app.devices().len() {
+                                                app.devices_selected += 1;
+                                            }
+                                            if !app.devices().is_empty() {
+                                                app.devices_table_state.select(Some(app.devices_selected));
+                                            }
+                                        }
+                                        _ => {}
+                                    }
+                                }
+                                CurrentScreen::History => {
+                                    match action {
+                                        Some(Action::Start) => app.rerun_history(),
+                                        Some(Action::Cycle) => app.toggle_history_compare(),
+                                        Some(Action::Stop) => {
+                                            app.history_compare = None;
+                                            app.history_input.reset();
+                                            app.refresh_history();
+                                        }
+                                        Some(Action::Up) if app.history_selected > 0 => {
+                                            app.select_history(app.history_selected - 1);
+                                        }
+                                        Some(Action::Down) if app.history_selected + 1 < app.history_entries.len() => {
+                                            app.select_history(app.history_selected + 1);
+                                        }
+                                        Some(Action::Up | Action::Down) => {}
+                                        _ => {
+                                            app.history_input.handle_event(&Event::Key(key));
+                                            app.refresh_history();
+                                        }
+                                    }
+                                }
+                                // Registered tools took the key above
+                                CurrentScreen::Ntp | CurrentScreen::Runner | CurrentScreen::HttpLoad | CurrentScreen::Plugin(_) => {}
                             }
                         }
                     }
@@ -0,0 +1,138 @@
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use crate::shutdown;
+
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+// Dashboard numbers mirrored for Prometheus. App::tick() overwrites the snapshot;
+// the HTTP task only ever reads it.
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSnapshot {
+    pub wan_rx_mbps: f64,
+    pub wan_tx_mbps: f64,
+    pub lan_rx_mbps: f64,
+    pub lan_tx_mbps: f64,
+
+    pub pings: Vec<PingMetrics>,
+
+    pub connections: u64,
+
+    pub capture_interface: String,
+    pub capture_packets: u64,
+    pub wan_rx_bytes: u64,
+    pub wan_tx_bytes: u64,
+    pub lan_rx_bytes: u64,
+    pub lan_tx_bytes: u64,
+}
+
+/// One dashboard background ping.
+#[derive(Debug, Clone, Default)]
+pub struct PingMetrics {
+    pub target: String,
+    pub latency_ms: f64,
+    pub jitter_ms: f64,
+    pub sent: u64,
+    pub lost: u64,
+    /// Over the last 100 probes
+    pub loss_ratio: f64,
+}
+
+pub type SharedMetrics = Arc<Mutex<MetricsSnapshot>>;
+
+pub fn render(m: &MetricsSnapshot) -> String {
+    let mut out = String::new();
+
+    let mut gauge = |name: &str, help: &str, samples: &[(String, f64)]| {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} gauge", name);
+        for (labels, v) in samples {
+            let _ = writeln!(out, "{}{} {}", name, labels, v);
+        }
+    };
+
+    gauge("netops_bandwidth_mbps", "Current bandwidth in Mbps as shown on the dashboard.", &[
+        ("{scope=\"wan\",direction=\"rx\"}".to_string(), m.wan_rx_mbps),
+        ("{scope=\"wan\",direction=\"tx\"}".to_string(), m.wan_tx_mbps),
+        ("{scope=\"lan\",direction=\"rx\"}".to_string(), m.lan_rx_mbps),
+        ("{scope=\"lan\",direction=\"tx\"}".to_string(), m.lan_tx_mbps),
+    ]);
+
+    let per_target = |value: fn(&PingMetrics) -> f64| -> Vec<(String, f64)> {
+        m.pings.iter().map(|p| (format!("{{target=\"{}\"}}", escape(&p.target)), value(p))).collect()
+    };
+    gauge("netops_ping_latency_ms", "Latest background ping round-trip time.", &per_target(|p| p.latency_ms));
+    gauge("netops_ping_jitter_ms", "Latest background ping jitter.", &per_target(|p| p.jitter_ms));
+    gauge("netops_ping_loss_ratio", "Background ping loss over the last 100 probes (0-1).", &per_target(|p| p.loss_ratio));
+    gauge("netops_connections", "Active remote connections.", &[(String::new(), m.connections as f64)]);
+
+    let iface = escape(&m.capture_interface);
+    let _ = writeln!(out, "# HELP netops_ping_probes_total Background ping probes by outcome.");
+    let _ = writeln!(out, "# TYPE netops_ping_probes_total counter");
+    for p in &m.pings {
+        let _ = writeln!(out, "netops_ping_probes_total{{target=\"{}\",result=\"sent\"}} {}", escape(&p.target), p.sent);
+        let _ = writeln!(out, "netops_ping_probes_total{{target=\"{}\",result=\"lost\"}} {}", escape(&p.target), p.lost);
+    }
+
+    let _ = writeln!(out, "# HELP netops_interface_bytes_total Bytes seen by the capture on an interface.");
+    let _ = writeln!(out, "# TYPE netops_interface_bytes_total counter");
+    for (scope, dir, v) in [("wan", "rx", m.wan_rx_bytes), ("wan", "tx", m.wan_tx_bytes), ("lan", "rx", m.lan_rx_bytes), ("lan", "tx", m.lan_tx_bytes)] {
+        let _ = writeln!(out, "netops_interface_bytes_total{{interface=\"{}\",scope=\"{}\",direction=\"{}\"}} {}", iface, scope, dir, v);
+    }
+
+    let _ = writeln!(out, "# HELP netops_interface_packets_total Packets seen by the capture on an interface.");
+    let _ = writeln!(out, "# TYPE netops_interface_packets_total counter");
+    let _ = writeln!(out, "netops_interface_packets_total{{interface=\"{}\"}} {}", iface, m.capture_packets);
+
+    out
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+pub fn spawn_server(listener: std::net::TcpListener, metrics: SharedMetrics) -> std::io::Result<()> {
+    listener.set_nonblocking(true)?;
+    let listener = TcpListener::from_std(listener)?;
+
+    tokio::spawn(async move {
+        loop {
+            let mut stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    // Out of file descriptors and the like; retrying at once would spin a core
+                    tracing::warn!("Metrics: accept failed: {}", e);
+                    if !shutdown::sleep_async(ACCEPT_BACKOFF).await {
+                        return;
+                    }
+                    continue;
+                }
+            };
+            let metrics = metrics.clone();
+            tokio::spawn(async move {
+                let mut buf = [0u8; 1024];
+                let Ok(n) = stream.read(&mut buf).await else { return };
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+                let response = if path == "/metrics" {
+                    let body = match metrics.lock() {
+                        Ok(m) => render(&m),
+                        Err(_) => String::new(),
+                    };
+                    format!("HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body)
+                } else {
+                    let body = "Not Found. Metrics are served at /metrics\n";
+                    format!("HTTP/1.1 404 Not Found\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body)
+                };
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+    Ok(())
+}
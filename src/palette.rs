@@ -0,0 +1,144 @@
+use crate::app::CurrentScreen;
+use crate::keymap::{Action, Keymap};
+use crate::screens;
+use crate::theme;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaletteAction {
+    Goto(CurrentScreen),
+    Start(CurrentScreen),
+    Stop(CurrentScreen),
+    ToolOptions,
+    Export,
+    AlertRules,
+    Profiles,
+    WakeOnLan,
+    MacVendor,
+    Help,
+    Yank,
+    EventLog,
+    Split,
+    FocusPane,
+    ZoomIn,
+    ZoomOut,
+    EditLayout,
+    PingTargets,
+    SnifferPause,
+    SnifferClear,
+    Hostnames,
+    MtrPaths,
+    Theme(&'static str),
+    Quit,
+}
+
+#[derive(Debug, Clone)]
+pub struct PaletteEntry {
+    pub label: String,
+    pub hint: String,
+    pub action: PaletteAction,
+}
+
+impl PaletteEntry {
+    fn new(label: impl Into<String>, hint: impl Into<String>, action: PaletteAction) -> Self {
+        Self { label: label.into(), hint: hint.into(), action }
+    }
+}
+
+/// Screens on App whose tool can be started/stopped from the palette; registered tools
+/// and plugins always can.
+const TOOLS: [CurrentScreen; 16] = [
+    CurrentScreen::Ping,
+    CurrentScreen::Dns,
+    CurrentScreen::Sniffer,
+    CurrentScreen::Mtr,
+    CurrentScreen::Nmap,
+    CurrentScreen::ArpScan,
+    CurrentScreen::Mdns,
+    CurrentScreen::Ssdp,
+    CurrentScreen::Flows,
+    CurrentScreen::Wifi,
+    CurrentScreen::Dhcp,
+    CurrentScreen::Traceroute,
+    CurrentScreen::Sweep,
+    CurrentScreen::Syslog,
+    CurrentScreen::Diagnose,
+    CurrentScreen::Asn,
+];
+
+/// Hints show the currently bound key, so they follow `[keys]` remaps.
+pub fn entries(keymap: &Keymap) -> Vec<PaletteEntry> {
+    let mut entries = vec![];
+
+    for screen in CurrentScreen::all() {
+        entries.push(PaletteEntry::new(format!("Go to {}", screen.title()), keymap.label(Action::Goto(screen)), PaletteAction::Goto(screen)));
+    }
+    let tools = CurrentScreen::all().into_iter()
+        .filter(|s| TOOLS.contains(s) || screens::info(*s).is_some() || matches!(s, CurrentScreen::Plugin(_)));
+    for screen in tools {
+        entries.push(PaletteEntry::new(format!("Start {}", screen.title()), keymap.label(Action::Start), PaletteAction::Start(screen)));
+        // One-shot tools finish on their own
+        if !matches!(screen, CurrentScreen::Dns | CurrentScreen::Dhcp | CurrentScreen::Traceroute) {
+            entries.push(PaletteEntry::new(format!("Stop {}", screen.title()), keymap.label(Action::Stop), PaletteAction::Stop(screen)));
+        }
+    }
+
+    entries.push(PaletteEntry::new("Tool options / flags", keymap.label(Action::ToolOptions), PaletteAction::ToolOptions));
+    entries.push(PaletteEntry::new("Export current screen (CSV/JSON)", keymap.label(Action::Export), PaletteAction::Export));
+    entries.push(PaletteEntry::new("Edit alert rules", keymap.label(Action::AlertRules), PaletteAction::AlertRules));
+    entries.push(PaletteEntry::new("Scan profiles: run / save", keymap.label(Action::Profiles), PaletteAction::Profiles));
+    entries.push(PaletteEntry::new("Wake-on-LAN", keymap.label(Action::WakeOnLan), PaletteAction::WakeOnLan));
+    entries.push(PaletteEntry::new("MAC vendor lookup", keymap.label(Action::MacVendor), PaletteAction::MacVendor));
+    entries.push(PaletteEntry::new("Copy selected row to clipboard", keymap.label(Action::Yank), PaletteAction::Yank));
+    entries.push(PaletteEntry::new("Event log: show / hide", keymap.label(Action::EventLog), PaletteAction::EventLog));
+    entries.push(PaletteEntry::new("Split view: open / close second pane", keymap.label(Action::Split), PaletteAction::Split));
+    entries.push(PaletteEntry::new("Split view: focus other pane", keymap.label(Action::FocusPane), PaletteAction::FocusPane));
+    entries.push(PaletteEntry::new("Dashboard: zoom in", keymap.label(Action::ZoomIn), PaletteAction::ZoomIn));
+    entries.push(PaletteEntry::new("Dashboard: zoom out", keymap.label(Action::ZoomOut), PaletteAction::ZoomOut));
+    entries.push(PaletteEntry::new("Dashboard: edit layout", keymap.label(Action::EditLayout), PaletteAction::EditLayout));
+    entries.push(PaletteEntry::new("Dashboard: ping targets", keymap.label(Action::PingTargets), PaletteAction::PingTargets));
+    entries.push(PaletteEntry::new("Sniffer: pause / resume display", keymap.label(Action::Pause), PaletteAction::SnifferPause));
+    entries.push(PaletteEntry::new("Sniffer: clear packet list", keymap.label(Action::Clear), PaletteAction::SnifferClear));
+    entries.push(PaletteEntry::new("Connections: show hostnames / IPs", keymap.label(Action::Hostnames), PaletteAction::Hostnames));
+    entries.push(PaletteEntry::new("MTR: path changes timeline", keymap.label(Action::ToggleView), PaletteAction::MtrPaths));
+    for name in theme::theme_names() {
+        let hint = if name == theme::current_theme_name() { "active" } else { "" };
+        entries.push(PaletteEntry::new(format!("Theme: {}", name), hint, PaletteAction::Theme(name)));
+    }
+    entries.push(PaletteEntry::new("Help", keymap.label(Action::Help), PaletteAction::Help));
+    entries.push(PaletteEntry::new("Quit", keymap.label(Action::Quit), PaletteAction::Quit));
+
+    entries
+}
+
+/// Entries matching `query`, best match first. An empty query keeps the default order.
+pub fn filter(query: &str, keymap: &Keymap) -> Vec<PaletteEntry> {
+    let mut scored: Vec<(i32, PaletteEntry)> = entries(keymap)
+        .into_iter()
+        .filter_map(|e| fuzzy_score(query, &e.label).map(|s| (s, e)))
+        .collect();
+    scored.sort_by_key(|(s, _)| std::cmp::Reverse(*s));
+    scored.into_iter().map(|(_, e)| e).collect()
+}
+
+/// Subsequence match, rewarding consecutive characters and word starts.
+fn fuzzy_score(query: &str, label: &str) -> Option<i32> {
+    let label: Vec<char> = label.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut prev: Option<usize> = None;
+
+    for qc in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let idx = (pos..label.len()).find(|&i| label[i] == qc)?;
+        score += 1;
+        if prev.is_some_and(|p| p + 1 == idx) {
+            score += 5;
+        }
+        if idx == 0 || !label[idx - 1].is_alphanumeric() {
+            score += 8;
+        }
+        score -= (idx - pos) as i32;
+        prev = Some(idx);
+        pos = idx + 1;
+    }
+    Some(score)
+}
@@ -0,0 +1,63 @@
+use socket2::{Domain, Protocol, Socket, Type};
+
+use crate::app::CurrentScreen;
+
+/// What the process is allowed to do, probed once at startup by opening (and
+/// immediately dropping) the sockets the tools need.
+#[derive(Debug, Clone, Copy)]
+pub struct Privileges {
+    /// Raw ICMP sockets (MTR, SYN scans, arp-scan)
+    pub raw_sockets: bool,
+    /// Unprivileged ICMP "ping sockets" (Linux ping_group_range, macOS)
+    pub icmp_dgram: bool,
+    /// Link-layer capture (AF_PACKET on Linux, /dev/bpf elsewhere)
+    pub capture: bool,
+}
+
+impl Privileges {
+    pub fn detect() -> Self {
+        let raw_sockets = Socket::new(Domain::IPV4, Type::RAW, Some(Protocol::ICMPV4)).is_ok();
+        let icmp_dgram = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::ICMPV4)).is_ok();
+        // AF_PACKET needs the same CAP_NET_RAW as raw sockets; BPF devices have their own permissions
+        let capture = if cfg!(target_os = "linux") {
+            raw_sockets
+        } else {
+            raw_sockets || (0..16).any(|i| std::fs::OpenOptions::new().read(true).open(format!("/dev/bpf{}", i)).is_ok())
+        };
+        Self { raw_sockets, icmp_dgram, capture }
+    }
+
+    /// Explains what is disabled or degraded on `screen`, if anything.
+    pub fn banner(&self, screen: CurrentScreen) -> Option<&'static str> {
+        match screen {
+            CurrentScreen::Dashboard if !self.capture => {
+                Some("No capture permission: bandwidth comes from OS counters; WAN/LAN split and protocol mix are unavailable.")
+            }
+            CurrentScreen::Sniffer if !self.capture => {
+                Some("No capture permission: run as root or grant CAP_NET_RAW (setcap cap_net_raw,cap_net_admin=eip netops).")
+            }
+            CurrentScreen::Ping if !self.raw_sockets && !self.icmp_dgram => {
+                Some("ICMP not permitted: run as root or allow ping sockets (sysctl net.ipv4.ping_group_range=\"0 2147483647\"), or ping a port with -p <port> --tcp.")
+            }
+            CurrentScreen::Sweep if !self.raw_sockets && !self.icmp_dgram => Some("ICMP not permitted: a ping sweep needs root or ping sockets."),
+            CurrentScreen::Mtr if !self.raw_sockets && self.icmp_dgram => {
+                Some("Unprivileged ICMP: some intermediate hops may not be reported. Run as root for full traces.")
+            }
+            CurrentScreen::Mtr if !self.raw_sockets => Some("ICMP not permitted: MTR needs root or CAP_NET_RAW."),
+            CurrentScreen::Traceroute if !self.raw_sockets => {
+                Some("Without raw sockets only the destination answers; run as root to see intermediate hops.")
+            }
+            CurrentScreen::Nmap if !self.raw_sockets => {
+                Some("Unprivileged: SYN scans (-sS) run as TCP connect scans (-sT); OS detection (-O) is unavailable.")
+            }
+            CurrentScreen::ArpScan if !self.raw_sockets => Some("arp-scan needs root or CAP_NET_RAW; scans will fail."),
+            // Without raw sockets we're almost certainly not root either
+            CurrentScreen::Dhcp if !self.raw_sockets => Some("DHCP probes bind UDP port 68 on a device, which needs root."),
+            CurrentScreen::Syslog if !self.raw_sockets => Some("Listening on port 514 needs root; have devices send to a port above 1023, such as 5514."),
+            CurrentScreen::Diagnose if !self.raw_sockets && !self.icmp_dgram => {
+                Some("ICMP not permitted: the gateway and MTU checks can't ping; the rest still run.")
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+use crate::app::CurrentScreen;
+
+/// A named target + flags for one tool, kept under `[[profiles]]` in the config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanProfile {
+    pub name: String,
+    pub tool: ProfileTool,
+    /// Exactly what would be typed into the tool's input, e.g. `10.0.0.0/24 -sV`
+    pub args: String,
+}
+
+/// Tools a profile can drive. Config names are lowercase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProfileTool {
+    Nmap,
+    Ping,
+    Mtr,
+}
+
+impl ProfileTool {
+    pub fn screen(self) -> CurrentScreen {
+        match self {
+            Self::Nmap => CurrentScreen::Nmap,
+            Self::Ping => CurrentScreen::Ping,
+            Self::Mtr => CurrentScreen::Mtr,
+        }
+    }
+
+    pub fn for_screen(screen: CurrentScreen) -> Option<Self> {
+        match screen {
+            CurrentScreen::Nmap => Some(Self::Nmap),
+            CurrentScreen::Ping => Some(Self::Ping),
+            CurrentScreen::Mtr => Some(Self::Mtr),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Nmap => "nmap",
+            Self::Ping => "ping",
+            Self::Mtr => "mtr",
+        }
+    }
+}
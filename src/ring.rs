@@ -0,0 +1,57 @@
+use std::collections::VecDeque;
+use std::ops::Deref;
+
+/// A history that keeps its newest `capacity` items, dropping the oldest as new ones come in.
+/// Reads go through the inner `VecDeque`; the only ways in are `push` and `clear`.
+#[derive(Debug, Clone)]
+pub struct Ring<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> Ring<T> {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self { items: VecDeque::with_capacity(capacity.min(4096)), capacity }
+    }
+
+    /// Appends `item`, returning the oldest one when it had to make room.
+    pub fn push(&mut self, item: T) -> Option<T> {
+        let evicted = if self.items.len() >= self.capacity { self.items.pop_front() } else { None };
+        self.items.push_back(item);
+        evicted
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl<T: Clone> Ring<T> {
+    /// Full of `value`, for charts that should start as a flat line rather than empty.
+    pub fn filled(capacity: usize, value: T) -> Self {
+        let capacity = capacity.max(1);
+        Self { items: VecDeque::from(vec![value; capacity]), capacity }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Ring<T> {
+    type Item = &'a T;
+    type IntoIter = std::collections::vec_deque::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+impl<T> Deref for Ring<T> {
+    type Target = VecDeque<T>;
+
+    fn deref(&self) -> &VecDeque<T> {
+        &self.items
+    }
+}
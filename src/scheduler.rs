@@ -0,0 +1,241 @@
+use std::time::{Duration, Instant};
+
+use crossbeam::channel::{Receiver, Sender};
+use hickory_resolver::proto::rr::RecordType;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::tools::args;
+use crate::tools::dns::{self, DnsResult};
+use crate::tools::nmap::{self, PortChange};
+use crate::tools::ping::{PingStats, PingTask};
+use crate::tools::process::{self, ProcessHandle};
+
+// Pings per scheduled check when the args don't give `-c`
+const PING_COUNT: u64 = 5;
+
+/// A tool run in the background on a fixed interval, kept under `[[schedules]]` in the config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schedule {
+    pub name: String,
+    pub tool: ScheduleTool,
+    /// Exactly what would be typed into the tool, e.g. `10.0.0.0/24 -F` or `example.com MX`
+    pub args: String,
+    /// `30s`, `5m`, `1h` or `1d`
+    pub every: String,
+}
+
+impl Schedule {
+    pub fn interval(&self) -> Result<Duration, String> {
+        let every = self.every.trim();
+        let split = every.find(|c: char| !c.is_ascii_digit()).unwrap_or(every.len());
+        let (num, unit) = every.split_at(split);
+        let mult = match unit {
+            "s" | "" => 1,
+            "m" => 60,
+            "h" => 3600,
+            "d" => 86400,
+            _ => return Err(format!("Invalid interval '{}' for schedule '{}'", self.every, self.name)),
+        };
+        num.parse::<u64>().ok()
+            .filter(|n| *n > 0)
+            .and_then(|n| n.checked_mul(mult))
+            .map(Duration::from_secs)
+            .ok_or_else(|| format!("Invalid interval '{}' for schedule '{}'", self.every, self.name))
+    }
+}
+
+/// Tools a schedule can run. Config names are lowercase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleTool {
+    Nmap,
+    Dns,
+    Ping,
+}
+
+/// What one run produced, in the shape the history database records it.
+#[derive(Debug, Clone)]
+pub enum RunOutcome {
+    Scan { target: String, lines: Vec<String> },
+    Dns { target: String, result: Result<DnsResult, String> },
+    Ping { target: String, stats: PingStats },
+}
+
+impl RunOutcome {
+    /// A one-line description of what differs from the previous run, if anything worth alerting on.
+    fn change_from(&self, previous: &RunOutcome) -> Option<String> {
+        match (previous, self) {
+            (RunOutcome::Scan { lines: old, .. }, RunOutcome::Scan { lines: new, .. }) => {
+                let old = nmap::parse_ports(old.iter().map(String::as_str));
+                let new = nmap::parse_ports(new.iter().map(String::as_str));
+                let changed: Vec<String> = nmap::diff_ports(&old, &new).into_iter()
+                    .filter_map(|(change, p)| match change {
+                        PortChange::Opened => Some(format!("+{} {}/{}", p.host, p.port, p.proto)),
+                        PortChange::Closed => Some(format!("-{} {}/{}", p.host, p.port, p.proto)),
+                        PortChange::Unchanged => None,
+                    })
+                    .collect();
+                (!changed.is_empty()).then(|| format!("open ports changed: {}", changed.join(", ")))
+            }
+            (RunOutcome::Dns { result: old, .. }, RunOutcome::Dns { result: new, .. }) => {
+                let (old, new) = (answers(old), answers(new));
+                (old != new).then(|| format!("answers changed: {} -> {}", old.join(", "), new.join(", ")))
+            }
+            (RunOutcome::Ping { stats: old, .. }, RunOutcome::Ping { stats: new, .. }) => {
+                match (old.recv > 0, new.recv > 0) {
+                    (true, false) => Some(format!("unreachable ({:.0}% loss)", new.loss_pct())),
+                    (false, true) => Some("reachable again".to_string()),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+fn answers(result: &Result<DnsResult, String>) -> Vec<String> {
+    match result {
+        Ok(result) => result.values(),
+        Err(_) => vec!["(failed)".to_string()],
+    }
+}
+
+/// A finished run of `config.schedules[index]`, with the change since its previous run.
+#[derive(Debug)]
+pub struct FinishedRun {
+    pub index: usize,
+    pub outcome: Result<RunOutcome, String>,
+    pub change: Option<String>,
+}
+
+#[derive(Default)]
+struct Slot {
+    next: Option<Instant>,
+    running: bool,
+    last: Option<RunOutcome>,
+    /// The running scan's nmap, stopped on quit
+    process: Option<ProcessHandle>,
+}
+
+/// Runs `config.schedules` in the background; the first run of each starts right away and sets
+/// the baseline later runs are compared against.
+pub struct Scheduler {
+    slots: Vec<Slot>,
+    tx: Sender<(usize, Result<RunOutcome, String>)>,
+    rx: Receiver<(usize, Result<RunOutcome, String>)>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        let (tx, rx) = crossbeam::channel::unbounded();
+        Self { slots: vec![], tx, rx }
+    }
+
+    /// Starts every schedule that is due. Invalid schedules report their error once and never run.
+    pub fn run_due(&mut self, config: &Config) -> Vec<FinishedRun> {
+        self.slots.resize_with(config.schedules.len(), Slot::default);
+        let mut errors = vec![];
+        let now = Instant::now();
+        for (index, (schedule, slot)) in config.schedules.iter().zip(self.slots.iter_mut()).enumerate() {
+            if slot.running || slot.next.is_some_and(|next| next > now) {
+                continue;
+            }
+            // An interval too long to add to the clock is as invalid as a typo
+            let next = schedule.interval().and_then(|interval| {
+                now.checked_add(interval).ok_or_else(|| format!("Interval '{}' for schedule '{}' is too long", schedule.every, schedule.name))
+            });
+            let next = match next {
+                Ok(next) => next,
+                Err(e) => {
+                    if slot.next.is_none() {
+                        errors.push(FinishedRun { index, outcome: Err(e), change: None });
+                    }
+                    // Far enough out that it never comes round again
+                    slot.next = Some(now + Duration::from_secs(u32::MAX as u64));
+                    continue;
+                }
+            };
+            slot.next = Some(next);
+            slot.running = true;
+            slot.process = start(index, schedule, config, self.tx.clone());
+        }
+        errors
+    }
+
+    pub fn finished(&mut self) -> Vec<FinishedRun> {
+        let mut runs = vec![];
+        while let Ok((index, outcome)) = self.rx.try_recv() {
+            let Some(slot) = self.slots.get_mut(index) else { continue };
+            slot.running = false;
+            slot.process = None;
+            let change = match (&outcome, &slot.last) {
+                (Ok(new), Some(old)) => new.change_from(old),
+                _ => None,
+            };
+            if let Ok(new) = &outcome {
+                slot.last = Some(new.clone());
+            }
+            runs.push(FinishedRun { index, outcome, change });
+        }
+        runs
+    }
+
+    /// Stops the scheduled scans still running, which would otherwise outlive the app.
+    pub fn stop_all(&self) {
+        for process in self.slots.iter().filter_map(|s| s.process.as_ref()) {
+            process.stop();
+        }
+    }
+}
+
+/// Runs one schedule in the background; for nmap, returns the process so it can be stopped.
+fn start(index: usize, schedule: &Schedule, config: &Config, tx: Sender<(usize, Result<RunOutcome, String>)>) -> Option<ProcessHandle> {
+    let args = schedule.args.trim().to_string();
+    match schedule.tool {
+        ScheduleTool::Nmap => {
+            let args = nmap::with_limits(&args, &config.scan.nmap);
+            let process = ProcessHandle::new();
+            let handle = process.clone();
+            std::thread::spawn(move || {
+                // Split and run the way the Nmap screen does, so quoting means the same in both
+                let (lines_tx, lines_rx) = crossbeam::channel::unbounded();
+                let outcome = args::split(&args)
+                    .and_then(|argv| process::stream("nmap", &argv, &lines_tx, &process))
+                    .map(|_| RunOutcome::Scan {
+                        target: args.clone(),
+                        lines: lines_rx.try_iter().filter_map(Result::ok).filter(|l| !l.starts_with(process::STDERR)).collect(),
+                    });
+                let _ = tx.send((index, outcome));
+            });
+            Some(handle)
+        }
+        ScheduleTool::Dns => {
+            tokio::spawn(async move {
+                let mut parts = args.split_whitespace();
+                let domain = parts.next().unwrap_or("").to_string();
+                let outcome = match parts.next().unwrap_or("A").to_uppercase().parse::<RecordType>() {
+                    _ if domain.is_empty() => Err("No domain given".to_string()),
+                    Ok(record_type) => Ok(RunOutcome::Dns { target: domain.clone(), result: dns::resolve(&domain, record_type).await }),
+                    Err(_) => Err(format!("Unknown record type in '{}'", args)),
+                };
+                let _ = tx.send((index, outcome));
+            });
+            None
+        }
+        ScheduleTool::Ping => {
+            tokio::spawn(async move {
+                let target = args.split_whitespace().find(|a| !a.starts_with('-')).unwrap_or("").to_string();
+                let counted = if args.split_whitespace().any(|a| a == "-c") { args } else { format!("{} -c {}", args, PING_COUNT) };
+                let (ping_tx, mut ping_rx) = tokio::sync::mpsc::channel(16);
+                tokio::spawn(PingTask { target: counted, tx: ping_tx }.run());
+                let mut stats = PingStats::default();
+                while let Some(result) = ping_rx.recv().await {
+                    stats.record(&result);
+                }
+                let _ = tx.send((index, Ok(RunOutcome::Ping { target, stats })));
+            });
+            None
+        }
+    }
+}
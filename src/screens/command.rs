@@ -0,0 +1,376 @@
+use std::sync::OnceLock;
+
+use crossbeam::channel::Receiver;
+use crossterm::event::{Event, KeyEvent};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, Borders, Cell, Paragraph, Row, Table, TableState};
+use serde::{Deserialize, Serialize};
+use tui_input::Input;
+use tui_input::backend::crossterm::EventHandler;
+
+use crate::app::CurrentScreen;
+use crate::export::ExportTable;
+use crate::keymap::Action;
+use crate::ring::Ring;
+use crate::screens::Tool;
+use crate::theme::THEME;
+use crate::tools::args;
+use crate::tools::process::{self, ProcessHandle};
+use crate::ui::row_style;
+
+// Rows kept per tab; older output scrolls away
+const KEEP: usize = 5000;
+const INPUT: &str = "{input}";
+
+/// How a command tool's output lines become table rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputParser {
+    /// Each line as it is
+    #[default]
+    Lines,
+    /// Split on `separator` (whitespace by default); the last column keeps the rest of the line
+    Columns,
+    /// One JSON object per line; `columns` name the keys shown
+    Json,
+}
+
+/// An external command shown as its own tab, kept under `[[plugins]]` in the config, e.g.
+/// `dig +trace {input}`. The command is split like a tool input and run without a shell;
+/// `{input}` is replaced by what's typed in the tab, or the input is appended when the
+/// command doesn't mention it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandToolConfig {
+    pub name: String,
+    pub command: String,
+    /// What the input starts with
+    #[serde(default)]
+    pub input: String,
+    #[serde(default)]
+    pub parser: OutputParser,
+    /// Column headings; for `json`, the keys to show
+    #[serde(default)]
+    pub columns: Vec<String>,
+    #[serde(default)]
+    pub separator: Option<String>,
+    /// Lines to drop at the start of each run, such as a header the columns replace
+    #[serde(default)]
+    pub skip: usize,
+}
+
+static INSTALLED: OnceLock<Vec<(CommandToolConfig, String)>> = OnceLock::new();
+
+/// Makes the configured command tools the plugin tabs, once at startup.
+pub fn install(configs: &[CommandToolConfig]) {
+    let _ = INSTALLED.set(configs.iter().map(|c| (c.clone(), slug(&c.name))).collect());
+}
+
+fn installed() -> &'static [(CommandToolConfig, String)] {
+    INSTALLED.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+pub fn count() -> usize {
+    installed().len()
+}
+
+/// The tab title of plugin `index`.
+pub fn title(index: usize) -> &'static str {
+    installed().get(index).map(|(c, _)| c.name.as_str()).unwrap_or("Plugin")
+}
+
+/// The plugin's name in API paths: lowercase, dashes for anything but letters and digits.
+pub fn slug_of(index: usize) -> &'static str {
+    installed().get(index).map(|(_, s)| s.as_str()).unwrap_or("plugin")
+}
+
+fn slug(name: &str) -> String {
+    let slug: String = name.to_lowercase().chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '-' }).collect();
+    slug.split('-').filter(|s| !s.is_empty()).collect::<Vec<_>>().join("-")
+}
+
+/// The command line a run would start, with the input put in.
+fn command_line(config: &CommandToolConfig, input: &str) -> Result<Vec<String>, String> {
+    let template = args::split(&config.command).map_err(|e| format!("{}: command: {}", config.name, e))?;
+    let typed = args::split(input)?;
+    let mut line = vec![];
+    for word in template {
+        if word == INPUT {
+            line.extend(typed.iter().cloned());
+        } else {
+            line.push(word.replace(INPUT, input.trim()));
+        }
+    }
+    if !config.command.contains(INPUT) {
+        line.extend(typed);
+    }
+    if line.is_empty() {
+        return Err(format!("{}: the command is empty", config.name));
+    }
+    Ok(line)
+}
+
+/// A configured external command: its output streams into a table as it runs.
+pub struct CommandTool {
+    index: usize,
+    config: &'static CommandToolConfig,
+    input: Input,
+    rx: Option<Receiver<Result<String, String>>>,
+    process: Option<ProcessHandle>,
+    active: bool,
+    error: Option<String>,
+    rows: Ring<Vec<String>>,
+    skipped: usize,
+    table_state: TableState,
+    /// Newest rows stay in view until a row is picked
+    follow: bool,
+}
+
+impl CommandTool {
+    /// A tab for each installed command tool.
+    pub fn all() -> Vec<Self> {
+        installed().iter().enumerate().map(|(index, (config, _))| Self {
+            index,
+            config,
+            input: Input::new(config.input.clone()),
+            rx: None,
+            process: None,
+            active: false,
+            error: None,
+            rows: Ring::new(KEEP),
+            skipped: 0,
+            table_state: TableState::default(),
+            follow: true,
+        }).collect()
+    }
+
+    fn headers(&self) -> Vec<&'static str> {
+        match (self.config.parser, self.config.columns.is_empty()) {
+            (OutputParser::Lines, _) | (OutputParser::Columns, true) => vec!["output"],
+            _ => self.config.columns.iter().map(String::as_str).collect(),
+        }
+    }
+
+    fn parse(&self, line: &str) -> Option<Vec<String>> {
+        // Only a plain listing has room for what went to stderr
+        if line.starts_with(process::STDERR) && self.config.parser != OutputParser::Lines {
+            return None;
+        }
+        match self.config.parser {
+            OutputParser::Lines => Some(vec![line.to_string()]),
+            OutputParser::Columns if self.config.columns.is_empty() => Some(vec![line.to_string()]),
+            OutputParser::Columns => {
+                let n = self.config.columns.len();
+                let mut cells: Vec<String> = match self.config.separator.as_deref() {
+                    Some(sep) => line.splitn(n, sep).map(|c| c.trim().to_string()).collect(),
+                    None => {
+                        let mut words = line.split_whitespace();
+                        let mut cells: Vec<String> = words.by_ref().take(n.saturating_sub(1)).map(str::to_string).collect();
+                        cells.push(words.collect::<Vec<_>>().join(" "));
+                        cells
+                    }
+                };
+                if cells.iter().all(|c| c.is_empty()) {
+                    return None;
+                }
+                cells.resize(n, String::new());
+                Some(cells)
+            }
+            OutputParser::Json => {
+                let value: serde_json::Value = serde_json::from_str(line).ok()?;
+                Some(self.config.columns.iter().map(|key| match &value[key.as_str()] {
+                    serde_json::Value::Null => String::new(),
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                }).collect())
+            }
+        }
+    }
+
+    fn select(&mut self, forward: bool) {
+        let len = self.rows.len();
+        if len == 0 {
+            return;
+        }
+        let current = self.table_state.selected().unwrap_or(len - 1);
+        let next = if forward { (current + 1).min(len - 1) } else { current.saturating_sub(1) };
+        self.follow = forward && next == len - 1;
+        self.table_state.select(Some(next));
+    }
+}
+
+impl Tool for CommandTool {
+    fn screen(&self) -> CurrentScreen {
+        CurrentScreen::Plugin(self.index)
+    }
+
+    fn input(&mut self) -> Option<&mut Input> {
+        Some(&mut self.input)
+    }
+
+    fn active(&self) -> bool {
+        self.active
+    }
+
+    fn typing(&self) -> bool {
+        !self.active
+    }
+
+    fn error(&self) -> Option<&String> {
+        self.error.as_ref()
+    }
+
+    fn start(&mut self) {
+        if self.active { return; }
+        if self.config.parser == OutputParser::Json && self.config.columns.is_empty() {
+            self.error = Some(format!("{}: the json parser needs columns = [\"key\", ...]", self.config.name));
+            return;
+        }
+        let line = match command_line(self.config, self.input.value()) {
+            Ok(line) => line,
+            Err(e) => {
+                self.error = Some(e);
+                return;
+            }
+        };
+        self.error = None;
+        self.rows.clear();
+        self.skipped = 0;
+        self.follow = true;
+        self.table_state.select(None);
+
+        let (tx, rx) = crossbeam::channel::unbounded();
+        let process = ProcessHandle::new();
+        self.rx = Some(rx);
+        self.process = Some(process.clone());
+        self.active = true;
+        std::thread::spawn(move || {
+            if let Err(e) = process::stream(&line[0], &line[1..], &tx, &process) {
+                let _ = tx.send(Err(e));
+            }
+        });
+    }
+
+    fn stop(&mut self) {
+        if let Some(process) = &self.process {
+            process.stop();
+        }
+        self.active = false;
+    }
+
+    fn tick(&mut self) {
+        let Some(rx) = self.rx.clone() else { return };
+        loop {
+            match rx.try_recv() {
+                Ok(Ok(line)) => {
+                    if self.skipped < self.config.skip {
+                        self.skipped += 1;
+                        continue;
+                    }
+                    if let Some(row) = self.parse(&line) {
+                        self.rows.push(row);
+                    }
+                }
+                Ok(Err(e)) => self.error = Some(e),
+                Err(crossbeam::channel::TryRecvError::Empty) => break,
+                Err(crossbeam::channel::TryRecvError::Disconnected) => {
+                    self.active = false;
+                    self.rx = None;
+                    self.process = None;
+                    break;
+                }
+            }
+        }
+        if self.follow && !self.rows.is_empty() && self.table_state.selected().is_some() {
+            self.table_state.select(Some(self.rows.len() - 1));
+        }
+    }
+
+    fn handle_key(&mut self, action: Option<Action>, key: KeyEvent) {
+        match action {
+            Some(Action::Start) => {
+                if self.active {
+                    self.stop();
+                } else {
+                    self.start();
+                }
+            }
+            Some(Action::Stop) => self.stop(),
+            Some(Action::Up) => self.select(false),
+            Some(Action::Down) => self.select(true),
+            _ => {
+                if !self.active {
+                    self.input.handle_event(&Event::Key(key));
+                }
+            }
+        }
+    }
+
+    fn export(&self) -> Option<ExportTable> {
+        let mut t = ExportTable::new(slug_of(self.index), &self.headers());
+        for row in self.rows.iter() {
+            t.push(row.iter());
+        }
+        Some(t)
+    }
+
+    fn selection(&self) -> Option<String> {
+        let row = self.rows.iter().nth(self.table_state.selected()?)?;
+        Some(row.join(" "))
+    }
+
+    fn search_rows(&self) -> Option<Vec<String>> {
+        Some(self.rows.iter().map(|row| row.join(" ")).collect())
+    }
+
+    fn selected_row(&self) -> Option<usize> {
+        self.table_state.selected()
+    }
+
+    fn select_row(&mut self, idx: usize) {
+        self.follow = false;
+        self.table_state.select(Some(idx));
+    }
+
+    fn render(&mut self, f: &mut Frame, area: Rect, hits: &[usize]) {
+        let [input_area, output_area] = Layout::vertical([Constraint::Length(3), Constraint::Min(1)]).areas(area);
+
+        // The command as it will run, so a template mistake shows before Enter
+        let preview = match command_line(self.config, self.input.value()) {
+            Ok(line) => Line::from(Span::styled(format!(" {} ", args::join(&line)), Style::default().fg(THEME.muted))),
+            Err(e) => Line::from(Span::styled(format!(" {} ", e), Style::default().fg(THEME.error))),
+        };
+        let input_block = Block::default()
+            .title(format!(" {} ", self.config.name))
+            .title(preview.right_aligned())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(if self.active { THEME.success } else { THEME.border }));
+        f.render_widget(Paragraph::new(self.input.value()).block(input_block).style(Style::default().fg(THEME.fg)), input_area);
+        if !self.active {
+            f.set_cursor_position((input_area.x + self.input.visual_cursor() as u16 + 1, input_area.y + 1));
+        }
+
+        let headers = self.headers();
+        let widths: Vec<Constraint> = (0..headers.len()).map(|i| if i + 1 == headers.len() { Constraint::Min(10) } else { Constraint::Length(18) }).collect();
+        let header = Row::new(headers.iter().map(|h| Cell::from(*h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))))
+            .style(Style::default().bg(THEME.surface)).height(1);
+        let rows = self.rows.iter().enumerate().map(|(i, row)| Row::new(row.iter().map(|c| Cell::from(c.as_str()))).style(row_style(hits.contains(&i))));
+        let status = if self.active { "running" } else { "idle" };
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded)
+                .title(format!(" Output ({} rows, {}) ", self.rows.len(), status)).border_style(Style::default().fg(THEME.border)))
+            .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD))
+            .highlight_symbol(">");
+
+        // Without a picked row the newest output stays at the bottom of the view
+        if self.table_state.selected().is_none() && !self.rows.is_empty() {
+            let visible = output_area.height.saturating_sub(3) as usize;
+            *self.table_state.offset_mut() = self.rows.len().saturating_sub(visible);
+        }
+        f.render_stateful_widget(table, output_area, &mut self.table_state);
+    }
+}
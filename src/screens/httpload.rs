@@ -0,0 +1,298 @@
+use crossbeam::channel::Receiver;
+use crossterm::event::{Event, KeyEvent};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::symbols;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Axis, Block, BorderType, Borders, Chart, Dataset, GraphType, Paragraph};
+use tui_input::Input;
+use tui_input::backend::crossterm::EventHandler;
+
+use crate::app::CurrentScreen;
+use crate::export::ExportTable;
+use crate::flags::{FlagSpec, switch, valued};
+use crate::keymap::Action;
+use crate::screens::{Tool, ToolInfo};
+use crate::theme::THEME;
+use crate::tools::httpload::{LoadEvent, LoadRequest, LoadStats, LoadTask};
+
+const OPTIONS: &[FlagSpec] = &[
+    valued("-c", "Concurrent connections", "10"),
+    valued("-d", "Duration (seconds)", "10"),
+    valued("-t", "Request timeout (seconds)", "5"),
+    switch("--no-keepalive", "New connection for every request"),
+    switch("--head", "HEAD instead of GET"),
+];
+
+pub const INFO: ToolInfo = ToolInfo {
+    screen: CurrentScreen::HttpLoad,
+    title: "HTTP Load Test",
+    tab: "HTTP",
+    slug: "http",
+    keys: &["G"],
+    notes: &[
+        " HTTP Load Test ",
+        " [Enter]    Start / Stop",
+        " [Esc]      Stop",
+        " [Ctrl+F]   Options: -c <conns>, -d <secs>, -t <secs> timeout,",
+        "            --no-keepalive, --head",
+        " ",
+        " Keeps -c requests in flight for -d seconds (10 and 10 by",
+        " default), each connection sending its next request once",
+        " the last response is read, as wrk does. Latency includes",
+        " reading the body. Only test servers you may load.",
+    ],
+    new: |_config| Box::new(HttpLoadTool::new()),
+};
+
+/// A short HTTP load test: requests per second, latency percentiles and errors, charted live.
+pub struct HttpLoadTool {
+    input: Input,
+    task: LoadTask,
+    rx: Option<Receiver<LoadEvent>>,
+    request: Option<LoadRequest>,
+    stats: Option<LoadStats>,
+    active: bool,
+    error: Option<String>,
+}
+
+impl HttpLoadTool {
+    pub fn new() -> Self {
+        Self {
+            input: Input::new("http://localhost:8080/ -c 10 -d 10".to_string()),
+            task: LoadTask::new(),
+            rx: None,
+            request: None,
+            stats: None,
+            active: false,
+            error: None,
+        }
+    }
+
+    fn finish(&mut self) {
+        if let Some(stats) = self.stats.as_mut()
+            && stats.finished.is_none()
+        {
+            stats.finished = Some(std::time::Instant::now());
+        }
+        self.active = false;
+    }
+}
+
+fn ms(value: Option<f64>) -> String {
+    value.map(|v| format!("{:.1} ms", v)).unwrap_or_else(|| "-".to_string())
+}
+
+impl Tool for HttpLoadTool {
+    fn screen(&self) -> CurrentScreen {
+        CurrentScreen::HttpLoad
+    }
+
+    fn input(&mut self) -> Option<&mut Input> {
+        Some(&mut self.input)
+    }
+
+    fn options(&self) -> &'static [FlagSpec] {
+        OPTIONS
+    }
+
+    fn active(&self) -> bool {
+        self.active
+    }
+
+    fn typing(&self) -> bool {
+        !self.active
+    }
+
+    fn error(&self) -> Option<&String> {
+        self.error.as_ref()
+    }
+
+    fn start(&mut self) {
+        if self.active { return; }
+
+        let request = match LoadRequest::parse(self.input.value()) {
+            Ok(r) => r,
+            Err(e) => {
+                self.error = Some(e);
+                return;
+            }
+        };
+        let (tx, rx) = crossbeam::channel::unbounded();
+        if let Err(e) = self.task.start(request.clone(), tx) {
+            self.error = Some(e);
+            return;
+        }
+        self.error = None;
+        self.rx = Some(rx);
+        self.request = Some(request);
+        self.stats = Some(LoadStats::new());
+        self.active = true;
+    }
+
+    fn stop(&mut self) {
+        if self.active {
+            self.task.stop();
+            self.finish();
+        }
+    }
+
+    fn tick(&mut self) {
+        let events: Vec<_> = self.rx.as_ref().map(|rx| rx.try_iter().collect()).unwrap_or_default();
+        for event in events {
+            match event {
+                LoadEvent::Response { latency_ms, status } => {
+                    // Requests still in flight when stopped don't count
+                    if let Some(stats) = self.stats.as_mut().filter(|s| s.finished.is_none()) {
+                        stats.record(latency_ms, status);
+                    }
+                }
+                LoadEvent::Done => {
+                    self.finish();
+                    self.rx = None;
+                }
+            }
+        }
+    }
+
+    fn handle_key(&mut self, action: Option<Action>, key: KeyEvent) {
+        match action {
+            Some(Action::Start) => {
+                if self.active {
+                    self.stop();
+                } else {
+                    self.start();
+                }
+            }
+            Some(Action::Stop) => {
+                self.stop();
+            }
+            _ => {
+                if !self.active {
+                    self.input.handle_event(&Event::Key(key));
+                }
+            }
+        }
+    }
+
+    fn export(&self) -> Option<ExportTable> {
+        let mut t = ExportTable::new("http_load", &["second", "responses", "errors", "p50_ms", "p90_ms", "p99_ms"]);
+        for (i, second) in self.stats.as_ref()?.seconds.iter().enumerate() {
+            let p = |q: f64| second.latency.percentile(q).map(|v| format!("{:.3}", v)).unwrap_or_default();
+            t.push([(i + 1).to_string(), second.responses.to_string(), second.errors.to_string(), p(50.0), p(90.0), p(99.0)]);
+        }
+        Some(t)
+    }
+
+    fn selection(&self) -> Option<String> {
+        let (request, stats) = (self.request.as_ref()?, self.stats.as_ref()?);
+        Some(format!("{} x{} for {:.1}s: {} responses, {:.1} req/s, p50 {} p99 {}, {} errors",
+            request.url, request.connections, stats.elapsed().as_secs_f64(), stats.responses, stats.rate(),
+            ms(stats.latency.percentile(50.0)), ms(stats.latency.percentile(99.0)), stats.error_count()))
+    }
+
+    fn render(&mut self, f: &mut Frame, area: Rect, _hits: &[usize]) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(8), Constraint::Length(12)].as_ref())
+            .split(area);
+
+        // Controls
+        let top = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Min(1)].as_ref())
+            .split(chunks[0]);
+
+        let input_block = Block::default()
+            .title(" URL [-c conns] [-d secs] [--no-keepalive] ")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(if self.active { THEME.success } else { THEME.border }));
+        f.render_widget(Paragraph::new(self.input.value()).block(input_block).style(Style::default().fg(THEME.fg)), top[0]);
+        if !self.active {
+            f.set_cursor_position((top[0].x + self.input.visual_cursor() as u16 + 1, top[0].y + 1));
+        }
+
+        let (status_text, status_col) = if self.active { ("LOADING", THEME.success) } else { ("IDLE", THEME.muted) };
+        let mut info = vec![
+            Span::raw(" "),
+            Span::styled(status_text, Style::default().fg(status_col).add_modifier(Modifier::BOLD)),
+        ];
+        if let (Some(request), Some(stats)) = (&self.request, &self.stats) {
+            info.push(Span::styled(format!("  {:.0}/{}s  x{}  keep-alive {}", stats.elapsed().as_secs_f64().min(request.duration.as_secs_f64()),
+                request.duration.as_secs_f64(), request.connections, if request.keep_alive { "on" } else { "off" }), Style::default().fg(THEME.muted)));
+        }
+        f.render_widget(Paragraph::new(Line::from(info)).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)).title(" Status ")), top[1]);
+
+        let middle = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(34), Constraint::Min(20)].as_ref())
+            .split(chunks[1]);
+        let summary_block = Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(" Summary ").border_style(Style::default().fg(THEME.border));
+
+        let Some(stats) = &self.stats else {
+            let hint = vec![
+                Line::from(" Enter a URL and press Enter."),
+                Line::from(Span::styled(" Only load test servers you run", Style::default().fg(THEME.muted))),
+                Line::from(Span::styled(" or have permission to test.", Style::default().fg(THEME.muted))),
+            ];
+            f.render_widget(Paragraph::new(hint).block(summary_block), middle[0]);
+            return;
+        };
+
+        // Totals
+        let label = |text: &str| Span::styled(format!(" {:<11}", text), Style::default().fg(THEME.muted));
+        let errors = stats.error_count();
+        let mut lines = vec![
+            Line::from(vec![label("Responses"), Span::raw(stats.responses.to_string())]),
+            Line::from(vec![label("Req/s"), Span::styled(format!("{:.1}", stats.rate()), Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))]),
+            Line::from(vec![label("Latency p50"), Span::raw(ms(stats.latency.percentile(50.0)))]),
+            Line::from(vec![label("        p90"), Span::raw(ms(stats.latency.percentile(90.0)))]),
+            Line::from(vec![label("        p99"), Span::styled(ms(stats.latency.percentile(99.0)), Style::default().fg(THEME.accent))]),
+            Line::from(vec![label("        max"), Span::raw(ms(stats.latency.percentile(100.0)))]),
+            Line::from(""),
+        ];
+        let classes: Vec<Span> = stats.classes.iter().enumerate().filter(|(_, n)| **n > 0).map(|(i, n)| {
+            let col = match i { 1 => THEME.success, 2 => THEME.secondary, 3 => THEME.accent, 4 => THEME.error, _ => THEME.fg };
+            Span::styled(format!("{}xx {}  ", i + 1, n), Style::default().fg(col))
+        }).collect();
+        lines.push(Line::from([vec![label("Status")], classes].concat()));
+        lines.push(Line::from(vec![label("Errors"), Span::styled(errors.to_string(), Style::default().fg(if errors > 0 { THEME.error } else { THEME.success }))]));
+        for (kind, n) in &stats.errors {
+            lines.push(Line::from(vec![label(&format!("  {}", kind)), Span::styled(n.to_string(), Style::default().fg(THEME.error))]));
+        }
+        f.render_widget(Paragraph::new(lines).block(summary_block), middle[0]);
+
+        // Responses per second; the second in progress is left out until it's over
+        let complete = if stats.finished.is_some() { stats.seconds.len() } else { stats.seconds.len().saturating_sub(1) };
+        let seconds = &stats.seconds[..complete];
+        let rate: Vec<(f64, f64)> = seconds.iter().enumerate().map(|(i, s)| (i as f64 + 1.0, s.responses as f64)).collect();
+        let failed: Vec<(f64, f64)> = seconds.iter().enumerate().map(|(i, s)| (i as f64 + 1.0, s.errors as f64)).collect();
+        let x_max = (complete as f64).max(2.0);
+        let rate_max = seconds.iter().map(|s| s.responses.max(s.errors) as f64).fold(1.0, f64::max) * 1.2;
+        let datasets = vec![
+            Dataset::default().name("req/s").marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(THEME.primary)).data(&rate),
+            Dataset::default().name("errors/s").marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(THEME.error)).data(&failed),
+        ];
+        let chart = Chart::new(datasets)
+            .block(Block::default().title(" Requests per second ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)))
+            .x_axis(Axis::default().bounds([1.0, x_max]).labels(["1s".to_string(), format!("{}s", x_max as u64)]).style(Style::default().fg(THEME.muted)))
+            .y_axis(Axis::default().bounds([0.0, rate_max]).labels(["0".to_string(), format!("{:.0}", rate_max)]).style(Style::default().fg(THEME.muted)));
+        f.render_widget(chart, middle[1]);
+
+        // Latency percentiles per second
+        let p50: Vec<(f64, f64)> = seconds.iter().enumerate().filter_map(|(i, s)| Some((i as f64 + 1.0, s.latency.percentile(50.0)?))).collect();
+        let p99: Vec<(f64, f64)> = seconds.iter().enumerate().filter_map(|(i, s)| Some((i as f64 + 1.0, s.latency.percentile(99.0)?))).collect();
+        let latency_max = p99.iter().map(|(_, v)| *v).fold(1.0, f64::max) * 1.2;
+        let datasets = vec![
+            Dataset::default().name("p50").marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(THEME.secondary)).data(&p50),
+            Dataset::default().name("p99").marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(THEME.accent)).data(&p99),
+        ];
+        let chart = Chart::new(datasets)
+            .block(Block::default().title(" Latency (ms) per second ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)))
+            .x_axis(Axis::default().bounds([1.0, x_max]).style(Style::default().fg(THEME.muted)))
+            .y_axis(Axis::default().bounds([0.0, latency_max]).labels(["0".to_string(), format!("{:.1}", latency_max)]).style(Style::default().fg(THEME.muted)));
+        f.render_widget(chart, chunks[2]);
+    }
+}
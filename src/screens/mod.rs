@@ -0,0 +1,156 @@
+use crossterm::event::KeyEvent;
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use tui_input::Input;
+
+use crate::app::CurrentScreen;
+use crate::config::Config;
+use crate::export::ExportTable;
+use crate::flags::FlagSpec;
+use crate::keymap::Action;
+
+pub mod command;
+pub mod httpload;
+pub mod ntp;
+pub mod runner;
+
+/// What the rest of the app needs to know about a built-in tool before there is one: its
+/// tab, its `goto_` action and API path, its help notes, and how to make it.
+pub struct ToolInfo {
+    pub screen: CurrentScreen,
+    /// Window title, palette entries and `goto_` action help
+    pub title: &'static str,
+    /// Name on the tab bar
+    pub tab: &'static str,
+    /// Name in the `goto_<slug>` key action, API paths and the saved session
+    pub slug: &'static str,
+    /// Default keys of the `goto_` action; the first is also shown on the tab
+    pub keys: &'static [&'static str],
+    /// The help browser's notes, the first line being the heading
+    pub notes: &'static [&'static str],
+    pub new: fn(&Config) -> Box<dyn Tool>,
+}
+
+/// The built-in tools that own their screens. A tool listed here gets its tab, key, palette
+/// entries and help from its `ToolInfo`; it still needs a `CurrentScreen` variant (whose
+/// place in `CurrentScreen::ALL` is its place on the tab bar).
+pub const TOOLS: [&ToolInfo; 3] = [&ntp::INFO, &runner::INFO, &httpload::INFO];
+
+/// The `ToolInfo` of a built-in registered tool; `None` for plugins and screens still on App.
+pub fn info(screen: CurrentScreen) -> Option<&'static ToolInfo> {
+    TOOLS.into_iter().find(|t| t.screen == screen)
+}
+
+/// A tool that owns its state and its screen. The App routes the generic work to a registered
+/// tool (starting and stopping, the tick, drawing, keys, the Ctrl+F options, search, export and copy)
+/// instead of matching on the screen in each place. So far that is NTP, the Runner, the HTTP
+/// load test and the `[[plugins]]` command tools; the other screens keep their fields on App
+/// and their arms in those matches.
+pub trait Tool {
+    /// The tab the tool lives on.
+    fn screen(&self) -> CurrentScreen;
+
+    /// The input the tool starts from, for history, suggestions and the flag editor.
+    fn input(&mut self) -> Option<&mut Input> {
+        None
+    }
+
+    /// The flags offered in the Ctrl+F editor.
+    fn options(&self) -> &'static [FlagSpec] {
+        &[]
+    }
+
+    fn active(&self) -> bool;
+
+    /// Whether plain characters go to the input right now, so the single-letter global
+    /// keys should leave them alone.
+    fn typing(&self) -> bool {
+        false
+    }
+
+    /// Why the tool last failed, shown as a banner above the screen until the next start.
+    fn error(&self) -> Option<&String> {
+        None
+    }
+
+    fn start(&mut self);
+
+    fn stop(&mut self);
+
+    /// Called every tick whichever screen is shown, to take in results.
+    fn tick(&mut self) {}
+
+    /// Draws the screen; `hits` are the rows matching the `/` search, to highlight.
+    fn render(&mut self, f: &mut Frame, area: Rect, hits: &[usize]);
+
+    /// Keys the global bindings didn't take, with the action they're bound to, if any.
+    fn handle_key(&mut self, action: Option<Action>, key: KeyEvent);
+
+    /// The results as a table, for Ctrl+E.
+    fn export(&self) -> Option<ExportTable> {
+        None
+    }
+
+    /// The selected row as text, for Ctrl+Y.
+    fn selection(&self) -> Option<String> {
+        None
+    }
+
+    /// Each row's text for `/` search, in table order; `None` when the screen can't be searched.
+    fn search_rows(&self) -> Option<Vec<String>> {
+        None
+    }
+
+    fn selected_row(&self) -> Option<usize> {
+        None
+    }
+
+    /// Moves the selection to a search match.
+    fn select_row(&mut self, _idx: usize) {}
+}
+
+/// The tools that own their screens, looked up by the screen they're on.
+pub struct ToolRegistry {
+    tools: Vec<Box<dyn Tool>>,
+}
+
+impl ToolRegistry {
+    pub fn new(config: &Config) -> Self {
+        let mut registry = Self { tools: vec![] };
+        for info in TOOLS {
+            registry.register((info.new)(config));
+        }
+        for tool in command::CommandTool::all() {
+            registry.register(Box::new(tool));
+        }
+        registry
+    }
+
+    /// Adds a tool, replacing one already on the same screen.
+    pub fn register(&mut self, tool: Box<dyn Tool>) {
+        self.tools.retain(|t| t.screen() != tool.screen());
+        self.tools.push(tool);
+    }
+
+    pub fn get(&self, screen: CurrentScreen) -> Option<&dyn Tool> {
+        self.tools.iter().find(|t| t.screen() == screen).map(|t| t.as_ref())
+    }
+
+    pub fn get_mut(&mut self, screen: CurrentScreen) -> Option<&mut (dyn Tool + 'static)> {
+        self.tools.iter_mut().find(|t| t.screen() == screen).map(|t| t.as_mut())
+    }
+
+    pub fn tick(&mut self) {
+        for tool in &mut self.tools {
+            tool.tick();
+        }
+    }
+
+    pub fn stop_all(&mut self) {
+        for tool in &mut self.tools {
+            if tool.active() {
+                tool.stop();
+            }
+        }
+    }
+}
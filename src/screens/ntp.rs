@@ -0,0 +1,281 @@
+use crossbeam::channel::Receiver;
+use crossterm::event::{Event, KeyEvent};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Axis, Block, BorderType, Borders, Cell, Chart, Dataset, GraphType, Paragraph, Row, Table, TableState};
+use tui_input::Input;
+use tui_input::backend::crossterm::EventHandler;
+
+use crate::app::CurrentScreen;
+use crate::export::ExportTable;
+use crate::flags::{FlagSpec, valued};
+use crate::keymap::Action;
+use crate::screens::{Tool, ToolInfo};
+use crate::theme::THEME;
+use crate::tools::ntp::{NtpEvent, NtpRequest, NtpServerState, NtpTask};
+
+const OPTIONS: &[FlagSpec] = &[
+    valued("-i", "Repeat every N seconds", "10"),
+    valued("-t", "Timeout (seconds)", "2"),
+];
+
+pub const INFO: ToolInfo = ToolInfo {
+    screen: CurrentScreen::Ntp,
+    title: "NTP",
+    tab: "NTP",
+    slug: "ntp",
+    keys: &["K"],
+    notes: &[
+        " NTP ",
+        " [Enter]    Query / Stop",
+        " [Esc]      Stop",
+        " [Up/Down]  Select Server",
+        " [Ctrl+F]   Options: -i <secs> repeat, -t <secs> timeout",
+        " ",
+        " Servers are separated by spaces or commas. Offset is how",
+        " far the server's clock is ahead of this machine's.",
+    ],
+    new: |_config| Box::new(NtpTool::new()),
+};
+
+/// Clock offset against one or more NTP servers, once or every `-i` seconds.
+pub struct NtpTool {
+    input: Input,
+    task: NtpTask,
+    rx: Option<Receiver<NtpEvent>>,
+    servers: Vec<NtpServerState>,
+    active: bool,
+    error: Option<String>,
+    table_state: TableState,
+    selected: usize,
+}
+
+impl NtpTool {
+    pub fn new() -> Self {
+        Self {
+            input: Input::new("pool.ntp.org time.cloudflare.com".to_string()),
+            task: NtpTask::new(),
+            rx: None,
+            servers: Vec::new(),
+            active: false,
+            error: None,
+            table_state: TableState::default(),
+            selected: 0,
+        }
+    }
+}
+
+impl Tool for NtpTool {
+    fn screen(&self) -> CurrentScreen {
+        CurrentScreen::Ntp
+    }
+
+    fn input(&mut self) -> Option<&mut Input> {
+        Some(&mut self.input)
+    }
+
+    fn options(&self) -> &'static [FlagSpec] {
+        OPTIONS
+    }
+
+    fn active(&self) -> bool {
+        self.active
+    }
+
+    fn typing(&self) -> bool {
+        !self.active
+    }
+
+    fn error(&self) -> Option<&String> {
+        self.error.as_ref()
+    }
+
+    fn start(&mut self) {
+        if self.active { return; }
+
+        let request = match NtpRequest::parse(self.input.value()) {
+            Ok(r) => r,
+            Err(e) => {
+                self.error = Some(e);
+                return;
+            }
+        };
+        self.error = None;
+        self.servers = request.servers.iter().cloned().map(NtpServerState::new).collect();
+        self.selected = 0;
+        self.table_state.select(None);
+
+        let (tx, rx) = crossbeam::channel::unbounded();
+        self.rx = Some(rx);
+        self.task.start(request, tx);
+        self.active = true;
+    }
+
+    fn stop(&mut self) {
+        if self.active {
+            self.task.stop();
+            self.active = false;
+        }
+    }
+
+    fn tick(&mut self) {
+        let events: Vec<_> = self.rx.as_ref().map(|rx| rx.try_iter().collect()).unwrap_or_default();
+        for event in events {
+            let (server, result) = match event {
+                NtpEvent::Sample(sample) => (sample.server.clone(), Ok(sample)),
+                NtpEvent::Error { server, error } => (server, Err(error)),
+                NtpEvent::Done => {
+                    self.active = false;
+                    continue;
+                }
+            };
+            if let Some(state) = self.servers.iter_mut().find(|s| s.server == server) {
+                state.record(result);
+            }
+        }
+    }
+
+    fn handle_key(&mut self, action: Option<Action>, key: KeyEvent) {
+        match action {
+            Some(Action::Start) => {
+                if self.active {
+                    self.stop();
+                } else {
+                    self.start();
+                }
+            }
+            Some(Action::Stop) => {
+                self.stop();
+            }
+            Some(Action::Up) if self.selected > 0 => {
+                self.selected -= 1;
+                self.table_state.select(Some(self.selected));
+            }
+            Some(Action::Down) if !self.servers.is_empty() => {
+                if self.table_state.selected().is_some() && self.selected < self.servers.len() - 1 {
+                    self.selected += 1;
+                }
+                self.table_state.select(Some(self.selected));
+            }
+            _ => {
+                if !self.active {
+                    self.input.handle_event(&Event::Key(key));
+                }
+            }
+        }
+    }
+
+    fn export(&self) -> Option<ExportTable> {
+        let mut t = ExportTable::new("ntp", &["server", "address", "offset_ms", "delay_ms", "stratum", "reference", "error"]);
+        for state in &self.servers {
+            match &state.last {
+                Some(Ok(s)) => t.push([s.server.clone(), s.addr.ip().to_string(), format!("{:.3}", s.offset_ms), format!("{:.3}", s.delay_ms), s.stratum.to_string(), s.reference.clone(), String::new()]),
+                Some(Err(e)) => t.push([state.server.clone(), String::new(), String::new(), String::new(), String::new(), String::new(), e.clone()]),
+                None => {}
+            }
+        }
+        Some(t)
+    }
+
+    fn selection(&self) -> Option<String> {
+        self.table_state.selected()?;
+        let state = self.servers.get(self.selected)?;
+        Some(match &state.last {
+            Some(Ok(s)) => format!("{} offset {:+.3} ms delay {:.3} ms stratum {}", s.server, s.offset_ms, s.delay_ms, s.stratum),
+            _ => state.server.clone(),
+        })
+    }
+
+    fn render(&mut self, f: &mut Frame, area: Rect, _hits: &[usize]) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(5), Constraint::Length(12)].as_ref())
+            .split(area);
+
+        // Controls
+        let top = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Min(1)].as_ref())
+            .split(chunks[0]);
+
+        let input_block = Block::default()
+            .title(" NTP Servers [-i secs] ")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(if self.active { THEME.success } else { THEME.border }));
+        f.render_widget(Paragraph::new(self.input.value()).block(input_block).style(Style::default().fg(THEME.fg)), top[0]);
+        if !self.active {
+            f.set_cursor_position((top[0].x + self.input.visual_cursor() as u16 + 1, top[0].y + 1));
+        }
+
+        let (status_text, status_col) = if self.active { ("QUERYING", THEME.success) } else { ("IDLE", THEME.muted) };
+        let info = vec![
+            Span::raw(" "),
+            Span::styled(status_text, Style::default().fg(status_col).add_modifier(Modifier::BOLD)),
+        ];
+        f.render_widget(Paragraph::new(Line::from(info)).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)).title(" Status ")), top[1]);
+
+        // Results Table
+        let header = Row::new(["Server", "Address", "Offset", "Delay", "Stratum", "Reference"].iter().map(|h| Cell::from(*h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))))
+            .style(Style::default().bg(THEME.surface)).height(1);
+
+        let rows = self.servers.iter().map(|state| {
+            let cells = match &state.last {
+                Some(Ok(s)) => {
+                    // Beyond 100 ms, TLS, Kerberos and log correlation start to suffer
+                    let offset_col = if s.offset_ms.abs() > 100.0 { THEME.error } else if s.offset_ms.abs() > 10.0 { THEME.accent } else { THEME.success };
+                    vec![
+                        Cell::from(state.server.clone()),
+                        Cell::from(s.addr.ip().to_string()).style(Style::default().fg(THEME.muted)),
+                        Cell::from(format!("{:+.3} ms", s.offset_ms)).style(Style::default().fg(offset_col)),
+                        Cell::from(format!("{:.3} ms", s.delay_ms)),
+                        Cell::from(s.stratum.to_string()),
+                        Cell::from(s.reference.clone()).style(Style::default().fg(THEME.secondary)),
+                    ]
+                }
+                Some(Err(e)) => vec![
+                    Cell::from(state.server.clone()),
+                    Cell::from(e.clone()).style(Style::default().fg(THEME.error)),
+                ],
+                None => vec![
+                    Cell::from(state.server.clone()),
+                    Cell::from("waiting...").style(Style::default().fg(THEME.muted)),
+                ],
+            };
+            Row::new(cells).style(Style::default().fg(THEME.fg))
+        });
+
+        let table = Table::new(rows, [
+            Constraint::Min(20),
+            Constraint::Length(24),
+            Constraint::Length(14),
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Length(16),
+        ].as_ref())
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(" Servers ").border_style(Style::default().fg(THEME.border)))
+        .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">");
+
+        f.render_stateful_widget(table, chunks[1], &mut self.table_state);
+
+        // Offset over time, one line per server
+        let colors = [THEME.primary, THEME.secondary, THEME.accent, THEME.success, Color::Magenta, Color::Cyan];
+        let max_round = self.servers.iter().flat_map(|s| s.offsets.last()).map(|(r, _)| *r).fold(1.0f64, f64::max);
+        let min_round = self.servers.iter().flat_map(|s| s.offsets.first()).map(|(r, _)| *r).fold(max_round, f64::min);
+        let bound = self.servers.iter().flat_map(|s| s.offsets.iter()).map(|(_, o)| o.abs()).fold(1.0f64, f64::max) * 1.2;
+        let datasets: Vec<Dataset> = self.servers.iter().enumerate().map(|(i, s)| {
+            Dataset::default().name(s.server.clone()).marker(symbols::Marker::Braille).graph_type(GraphType::Line)
+                .style(Style::default().fg(colors[i % colors.len()])).data(&s.offsets)
+        }).collect();
+        let chart = Chart::new(datasets)
+            .block(Block::default().title(" Clock Offset (ms) per round ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)))
+            .x_axis(Axis::default().bounds([min_round, max_round]).style(Style::default().fg(THEME.muted)))
+            .y_axis(Axis::default().bounds([-bound, bound]).labels([format!("{:.1}", -bound), "0".to_string(), format!("{:.1}", bound)]).style(Style::default().fg(THEME.muted)));
+        f.render_widget(chart, chunks[2]);
+    }
+}
@@ -0,0 +1,423 @@
+use std::collections::HashMap;
+
+use crossbeam::channel::Receiver;
+use crossterm::event::{Event, KeyEvent};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, Borders, List, ListItem, ListState, Paragraph};
+use serde::{Deserialize, Serialize};
+use tui_input::Input;
+use tui_input::backend::crossterm::EventHandler;
+
+use crate::app::CurrentScreen;
+use crate::export::ExportTable;
+use crate::is_text_key;
+use crate::keymap::Action;
+use crate::ring::Ring;
+use crate::screens::{Tool, ToolInfo};
+use crate::theme::THEME;
+use crate::tools::args;
+use crate::tools::process::{self, ProcessHandle};
+use crate::ui::{row_style, visible_window};
+
+/// A named command line with `{name}` or `{name=default}` placeholders, filled from the
+/// Runner's input, e.g. `openssl s_client -connect {host}:{port=443}`. Kept under
+/// `[[commands]]` in the config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandTemplate {
+    pub name: String,
+    pub command: String,
+}
+
+/// One `{name}` or `{name=default}` in a template, by byte range.
+struct Placeholder<'a> {
+    range: std::ops::Range<usize>,
+    name: &'a str,
+    default: Option<&'a str>,
+}
+
+fn placeholders(text: &str) -> Vec<Placeholder<'_>> {
+    let mut found = vec![];
+    let mut from = 0;
+    while let Some(open) = text[from..].find('{').map(|i| from + i) {
+        let Some(close) = text[open..].find('}').map(|i| open + i) else { break };
+        let inner = &text[open + 1..close];
+        let (name, default) = match inner.split_once('=') {
+            Some((name, default)) => (name, Some(default)),
+            None => (inner, None),
+        };
+        // Anything else in braces (`{}`, JSON, awk) is left as written
+        if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            found.push(Placeholder { range: open..close + 1, name, default });
+        }
+        from = close + 1;
+    }
+    found
+}
+
+impl CommandTemplate {
+    /// The placeholder names in order of first use, with their defaults.
+    pub fn fields(&self) -> Vec<(&str, Option<&str>)> {
+        let mut fields: Vec<(&str, Option<&str>)> = vec![];
+        for p in placeholders(&self.command) {
+            match fields.iter_mut().find(|(name, _)| *name == p.name) {
+                Some(field) => field.1 = field.1.or(p.default),
+                None => fields.push((p.name, p.default)),
+            }
+        }
+        fields
+    }
+
+    /// The command line for `input`: values in placeholder order, or as `name=value`, split
+    /// like any tool input. Values left over are appended as extra arguments, and a word that
+    /// is only an empty placeholder is dropped.
+    pub fn fill(&self, input: &str) -> Result<Vec<String>, String> {
+        let words = args::split(&self.command).map_err(|e| format!("{}: command: {}", self.name, e))?;
+        let fields = self.fields();
+        let mut values: HashMap<&str, String> = HashMap::new();
+        let mut positional = vec![];
+        for given in args::split(input)? {
+            match given.split_once('=').and_then(|(name, value)| fields.iter().find(|(f, _)| *f == name).map(|(f, _)| (*f, value))) {
+                Some((name, value)) if !values.contains_key(name) => {
+                    values.insert(name, value.to_string());
+                }
+                _ => positional.push(given),
+            }
+        }
+        let mut positional = positional.into_iter();
+        for (name, default) in &fields {
+            if values.contains_key(name) {
+                continue;
+            }
+            let value = positional.next().or(default.map(str::to_string))
+                .ok_or_else(|| format!("{} needs {{{}}}", self.name, name))?;
+            values.insert(name, value);
+        }
+
+        let mut line = vec![];
+        for word in words {
+            let found = placeholders(&word);
+            if let [only] = found.as_slice()
+                && only.range == (0..word.len())
+                && values[only.name].is_empty()
+            {
+                continue;
+            }
+            let mut filled = String::new();
+            let mut at = 0;
+            for p in &found {
+                filled.push_str(&word[at..p.range.start]);
+                filled.push_str(&values[p.name]);
+                at = p.range.end;
+            }
+            filled.push_str(&word[at..]);
+            line.push(filled);
+        }
+        line.extend(positional);
+        if line.is_empty() {
+            return Err(format!("{}: the command is empty", self.name));
+        }
+        Ok(line)
+    }
+}
+
+/// Used when the config has no `[[commands]]`.
+fn defaults() -> Vec<CommandTemplate> {
+    [
+        ("DNS trace", "dig +trace {name} {type=A}"),
+        ("Whois", "whois {query}"),
+        ("HTTP headers", "curl -sSI --max-time 10 {url}"),
+        ("TLS certificate", "openssl s_client -connect {host}:{port=443} -servername {host} -showcerts"),
+        ("Path MTU", "tracepath -n {host}"),
+    ].into_iter().map(|(name, command)| CommandTemplate { name: name.to_string(), command: command.to_string() }).collect()
+}
+
+pub const INFO: ToolInfo = ToolInfo {
+    screen: CurrentScreen::Runner,
+    title: "Runner",
+    tab: "Runner",
+    slug: "runner",
+    keys: &["X"],
+    notes: &[
+        " Runner ",
+        " [Enter]    Run / Stop",
+        " [Esc]      Stop",
+        " [Ctrl+N]   Next command",
+        " [Up/Down]  Select Line (Ctrl+Y copies it)",
+        " [Ctrl+S]   Search output (n/N next/prev match)",
+        " [Ctrl+L]   Clear the output",
+        " ",
+        " Runs the commands under [[commands]] in the config, or a",
+        " few defaults (dig +trace, whois, curl, openssl) without.",
+        " The input fills the {name} placeholders in order, or as",
+        " name=value; {name=default} may be left out, and extra",
+        " values are appended. Nothing goes through a shell.",
+    ],
+    new: |config| Box::new(RunnerTool::new(config.commands.clone(), config.retention.scan_output)),
+};
+
+/// Runs command templates and streams their output into a scrollable, searchable list, as
+/// the Nmap and ARP Scan screens do for their own tools.
+pub struct RunnerTool {
+    templates: Vec<CommandTemplate>,
+    template: usize,
+    input: Input,
+    rx: Option<Receiver<Result<String, String>>>,
+    process: Option<ProcessHandle>,
+    active: bool,
+    error: Option<String>,
+    output: Ring<String>,
+    /// The command line of the last run, for the output title
+    ran: Option<String>,
+    list_state: ListState,
+}
+
+impl RunnerTool {
+    pub fn new(templates: Vec<CommandTemplate>, keep: usize) -> Self {
+        Self {
+            templates: if templates.is_empty() { defaults() } else { templates },
+            template: 0,
+            input: Input::default(),
+            rx: None,
+            process: None,
+            active: false,
+            error: None,
+            output: Ring::new(keep),
+            ran: None,
+            list_state: ListState::default(),
+        }
+    }
+
+    fn current(&self) -> Option<&CommandTemplate> {
+        self.templates.get(self.template)
+    }
+
+    fn next_template(&mut self) {
+        if self.active || self.templates.is_empty() {
+            return;
+        }
+        self.template = (self.template + 1) % self.templates.len();
+        self.input = Input::default();
+        self.error = None;
+    }
+
+    // Down past the last line goes back to following the output
+    fn select(&mut self, forward: bool) {
+        let len = self.output.len();
+        if len == 0 {
+            return;
+        }
+        let next = match (self.list_state.selected(), forward) {
+            (None, true) => return,
+            (None, false) => len - 1,
+            (Some(i), true) if i + 1 >= len => {
+                self.list_state.select(None);
+                return;
+            }
+            (Some(i), true) => i + 1,
+            (Some(i), false) => i.saturating_sub(1),
+        };
+        self.list_state.select(Some(next));
+    }
+}
+
+impl Tool for RunnerTool {
+    fn screen(&self) -> CurrentScreen {
+        CurrentScreen::Runner
+    }
+
+    fn input(&mut self) -> Option<&mut Input> {
+        Some(&mut self.input)
+    }
+
+    fn active(&self) -> bool {
+        self.active
+    }
+
+    // Template arguments are typed here, capitals included
+    fn typing(&self) -> bool {
+        !self.active
+    }
+
+    fn error(&self) -> Option<&String> {
+        self.error.as_ref()
+    }
+
+    fn start(&mut self) {
+        if self.active { return; }
+        let Some(template) = self.current() else { return };
+        let line = match template.fill(self.input.value()) {
+            Ok(line) => line,
+            Err(e) => {
+                self.error = Some(e);
+                return;
+            }
+        };
+        self.error = None;
+        self.output.clear();
+        self.list_state.select(None);
+        self.ran = Some(args::join(&line));
+
+        let (tx, rx) = crossbeam::channel::unbounded();
+        let process = ProcessHandle::new();
+        self.rx = Some(rx);
+        self.process = Some(process.clone());
+        self.active = true;
+        std::thread::spawn(move || {
+            let result = process::stream(&line[0], &line[1..], &tx, &process);
+            let _ = tx.send(result);
+        });
+    }
+
+    fn stop(&mut self) {
+        if let Some(process) = &self.process {
+            process.stop();
+        }
+        self.active = false;
+    }
+
+    fn tick(&mut self) {
+        let Some(rx) = self.rx.clone() else { return };
+        loop {
+            match rx.try_recv() {
+                Ok(Ok(line)) => {
+                    // Keep a selected line in place as old ones scroll away
+                    if self.output.push(line).is_some()
+                        && let Some(i) = self.list_state.selected()
+                    {
+                        self.list_state.select(Some(i.saturating_sub(1)));
+                    }
+                }
+                Ok(Err(e)) => self.error = Some(e),
+                Err(crossbeam::channel::TryRecvError::Empty) => break,
+                Err(crossbeam::channel::TryRecvError::Disconnected) => {
+                    self.active = false;
+                    self.rx = None;
+                    self.process = None;
+                    break;
+                }
+            }
+        }
+    }
+
+    fn handle_key(&mut self, action: Option<Action>, key: KeyEvent) {
+        match action {
+            Some(Action::Start) => {
+                if self.active {
+                    self.stop();
+                } else {
+                    self.start();
+                }
+            }
+            Some(Action::Stop) => self.stop(),
+            Some(Action::Cycle) => self.next_template(),
+            Some(Action::Up) => self.select(false),
+            Some(Action::Down) => self.select(true),
+            Some(Action::Clear) if self.active || !is_text_key(&key) => {
+                self.output.clear();
+                self.list_state.select(None);
+            }
+            _ => {
+                if !self.active {
+                    self.input.handle_event(&Event::Key(key));
+                }
+            }
+        }
+    }
+
+    fn export(&self) -> Option<ExportTable> {
+        let mut t = ExportTable::new("runner", &["line"]);
+        for line in self.output.iter() {
+            t.push([line]);
+        }
+        Some(t)
+    }
+
+    fn selection(&self) -> Option<String> {
+        self.output.get(self.list_state.selected()?).cloned()
+    }
+
+    fn search_rows(&self) -> Option<Vec<String>> {
+        Some(self.output.iter().cloned().collect())
+    }
+
+    fn selected_row(&self) -> Option<usize> {
+        self.list_state.selected()
+    }
+
+    fn select_row(&mut self, idx: usize) {
+        self.list_state.select(Some(idx));
+    }
+
+    fn render(&mut self, f: &mut Frame, area: Rect, hits: &[usize]) {
+        let [templates_area, main] = Layout::horizontal([Constraint::Length(28), Constraint::Min(20)]).areas(area);
+        let [input_area, output_area] = Layout::vertical([Constraint::Length(3), Constraint::Min(3)]).areas(main);
+
+        // Templates
+        let items: Vec<ListItem> = self.templates.iter().enumerate().map(|(i, t)| {
+            let style = if i == self.template {
+                Style::default().fg(THEME.bg).bg(THEME.primary).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(THEME.fg)
+            };
+            ListItem::new(Line::from(format!(" {}", t.name))).style(style)
+        }).collect();
+        let templates_block = Block::default()
+            .title(" Commands [Ctrl+N] ")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(THEME.border));
+        f.render_widget(List::new(items).block(templates_block), templates_area);
+
+        // Input: the template as the title, the command it would run on the right
+        let (title, preview) = match self.current() {
+            Some(t) => {
+                let preview = match t.fill(self.input.value()) {
+                    Ok(line) => Span::styled(format!(" {} ", args::join(&line)), Style::default().fg(THEME.muted)),
+                    Err(e) => Span::styled(format!(" {} ", e), Style::default().fg(THEME.error)),
+                };
+                (format!(" {} ", t.command), preview)
+            }
+            None => (" Command ".to_string(), Span::raw("")),
+        };
+        let input_block = Block::default()
+            .title(title)
+            .title(Line::from(preview).right_aligned())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(if self.active { THEME.success } else { THEME.border }));
+        f.render_widget(Paragraph::new(self.input.value()).block(input_block).style(Style::default().fg(THEME.fg)), input_area);
+        if !self.active {
+            f.set_cursor_position((input_area.x + self.input.visual_cursor() as u16 + 1, input_area.y + 1));
+        }
+
+        // Output; without a selected line the newest stays in view
+        let height = output_area.height.saturating_sub(2) as usize;
+        if self.list_state.selected().is_none() {
+            *self.list_state.offset_mut() = self.output.len().saturating_sub(height);
+        }
+        let (start, end) = visible_window(self.list_state.offset(), self.list_state.selected(), self.output.len(), height);
+        let items: Vec<ListItem> = self.output.range(start..end).enumerate().map(|(i, line)| {
+            let style = if line.starts_with(process::STDERR) && !hits.contains(&(start + i)) {
+                Style::default().fg(THEME.error)
+            } else {
+                row_style(hits.contains(&(start + i)))
+            };
+            ListItem::new(Line::from(line.as_str())).style(style)
+        }).collect();
+        let status = if self.active { "running" } else { "idle" };
+        let output_title = match &self.ran {
+            Some(ran) => format!(" {} ({} lines, {}) ", ran, self.output.len(), status),
+            None => " Output ".to_string(),
+        };
+        let list = List::new(items)
+            .block(Block::default().title(output_title).borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)))
+            .style(Style::default().fg(THEME.fg))
+            .highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD));
+        let selected = self.list_state.selected().filter(|s| (start..end).contains(s)).map(|s| s - start);
+        f.render_stateful_widget(list, output_area, &mut ListState::default().with_selected(selected));
+        *self.list_state.offset_mut() = start;
+    }
+}
@@ -0,0 +1,51 @@
+use tui_input::Input;
+
+use crate::app::CurrentScreen;
+
+/// Screens whose tables support `/` search.
+pub const SEARCHABLE: [CurrentScreen; 5] = [
+    CurrentScreen::Sniffer,
+    CurrentScreen::Connections,
+    CurrentScreen::Nmap,
+    CurrentScreen::Mtr,
+    CurrentScreen::ArpScan,
+];
+
+/// Incremental search over one screen's table. Matching rows are highlighted, not hidden,
+/// so row indices (and the table selection) stay stable while the query changes.
+pub struct TableSearch {
+    pub screen: CurrentScreen,
+    pub input: Input,
+    /// True while the query is being typed; false once committed with Enter
+    pub editing: bool,
+}
+
+impl TableSearch {
+    pub fn new(screen: CurrentScreen) -> Self {
+        Self { screen, input: Input::default(), editing: true }
+    }
+
+    pub fn query(&self) -> &str {
+        self.input.value()
+    }
+
+    /// Case-insensitive substring match; an empty query matches nothing.
+    pub fn is_match(&self, text: &str) -> bool {
+        let query = self.query().trim();
+        !query.is_empty() && text.to_lowercase().contains(&query.to_lowercase())
+    }
+}
+
+/// The next (or previous) match after `current`, wrapping around. With no current
+/// row, the first (or last) match.
+pub fn step(matches: &[usize], current: Option<usize>, forward: bool) -> Option<usize> {
+    let (first, last) = (*matches.first()?, *matches.last()?);
+    let Some(current) = current else {
+        return Some(if forward { first } else { last });
+    };
+    if forward {
+        Some(matches.iter().copied().find(|&i| i > current).unwrap_or(first))
+    } else {
+        Some(matches.iter().rev().copied().find(|&i| i < current).unwrap_or(last))
+    }
+}
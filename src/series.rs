@@ -0,0 +1,115 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Points per tier; matches the 100-sample live histories so charts share x bounds.
+pub const SERIES_POINTS: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeRange {
+    #[default]
+    Live,
+    Minute,
+    FifteenMinutes,
+    Hour,
+    Day,
+}
+
+impl TimeRange {
+    const ALL: [TimeRange; 5] = [Self::Live, Self::Minute, Self::FifteenMinutes, Self::Hour, Self::Day];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Live => "live",
+            Self::Minute => "1m",
+            Self::FifteenMinutes => "15m",
+            Self::Hour => "1h",
+            Self::Day => "24h",
+        }
+    }
+
+    fn span(&self) -> Duration {
+        match self {
+            Self::Live => Duration::ZERO,
+            Self::Minute => Duration::from_secs(60),
+            Self::FifteenMinutes => Duration::from_secs(15 * 60),
+            Self::Hour => Duration::from_secs(60 * 60),
+            Self::Day => Duration::from_secs(24 * 60 * 60),
+        }
+    }
+
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|r| r == self).unwrap_or(0)
+    }
+
+    pub fn zoom_out(&self) -> TimeRange {
+        Self::ALL[(self.index() + 1).min(Self::ALL.len() - 1)]
+    }
+
+    pub fn zoom_in(&self) -> TimeRange {
+        Self::ALL[self.index().saturating_sub(1)]
+    }
+}
+
+struct Tier {
+    bucket: Duration,
+    points: VecDeque<f64>,
+    sum: f64,
+    count: u32,
+    started: Instant,
+}
+
+impl Tier {
+    fn new(range: TimeRange) -> Self {
+        Self {
+            bucket: range.span() / SERIES_POINTS as u32,
+            points: VecDeque::with_capacity(SERIES_POINTS),
+            sum: 0.0,
+            count: 0,
+            started: Instant::now(),
+        }
+    }
+
+    fn push(&mut self, value: f64, now: Instant) {
+        self.sum += value;
+        self.count += 1;
+        if now.duration_since(self.started) >= self.bucket {
+            self.points.push_back(self.sum / self.count as f64);
+            if self.points.len() > SERIES_POINTS {
+                self.points.pop_front();
+            }
+            self.sum = 0.0;
+            self.count = 0;
+            self.started = now;
+        }
+    }
+}
+
+/// Downsampled history at 1m/15m/1h/24h, each tier averaging samples into 100 buckets.
+pub struct TieredSeries {
+    tiers: Vec<(TimeRange, Tier)>,
+}
+
+impl TieredSeries {
+    pub fn new() -> Self {
+        Self {
+            tiers: TimeRange::ALL.iter()
+                .filter(|r| **r != TimeRange::Live)
+                .map(|r| (*r, Tier::new(*r)))
+                .collect(),
+        }
+    }
+
+    pub fn push(&mut self, value: f64) {
+        let now = Instant::now();
+        for (_, tier) in self.tiers.iter_mut() {
+            tier.push(value, now);
+        }
+    }
+
+    /// Chart points for a tier, right-aligned so the newest sample sits at x = 100.
+    pub fn points(&self, range: TimeRange) -> Vec<(f64, f64)> {
+        let Some((_, tier)) = self.tiers.iter().find(|(r, _)| *r == range) else { return vec![] };
+        let offset = SERIES_POINTS - tier.points.len();
+        tier.points.iter().enumerate().map(|(i, &v)| ((offset + i) as f64, v)).collect()
+    }
+}
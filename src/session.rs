@@ -0,0 +1,49 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+
+/// What the next launch picks up where this one left off: tabs, a theme picked from the
+/// palette, the sniffer's interfaces and every tool's input. Screens are kept by slug, so
+/// a renamed or removed plugin is simply skipped.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub screen: Option<String>,
+    pub split: Option<String>,
+    pub theme: Option<String>,
+    /// The interface under the sniffer's cursor; `None` for "All"
+    pub interface: Option<String>,
+    /// Interfaces ticked with Tab on the Sniffer screen
+    pub capture: Vec<String>,
+    /// Screen slug -> input text
+    pub inputs: BTreeMap<String, String>,
+}
+
+/// $XDG_DATA_HOME/netops/state.json
+pub fn path() -> Option<PathBuf> {
+    config::data_dir().map(|d| d.join("state.json"))
+}
+
+/// The saved session, or an empty one on the first run or when the file can't be read.
+pub fn load() -> Session {
+    let Some(path) = path() else { return Session::default() };
+    let Ok(text) = std::fs::read_to_string(&path) else { return Session::default() };
+    serde_json::from_str(&text).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring {}: {}", path.display(), e);
+        Session::default()
+    })
+}
+
+pub fn save(session: &Session) -> Result<(), String> {
+    let path = path().ok_or("No data directory ($XDG_DATA_HOME or $HOME) to save the session in")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    let text = serde_json::to_string_pretty(session).map_err(|e| e.to_string())?;
+    // Written aside and renamed, so a crash mid-write can't leave half a file
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, text).and_then(|_| std::fs::rename(&tmp, &path)).map_err(|e| format!("{}: {}", path.display(), e))
+}
@@ -0,0 +1,51 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+
+// Set once on quit (or a panic). Threads sleeping in `sleep` wake on the condvar, async tasks
+// on the notify; the lock only orders a request against a sleeper about to wait.
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+static LOCK: Mutex<()> = Mutex::new(());
+static WAKE: Condvar = Condvar::new();
+static WAKE_ASYNC: Notify = Notify::const_new();
+
+/// Tells every background task to wind down.
+pub fn request() {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    REQUESTED.store(true, Ordering::Relaxed);
+    WAKE.notify_all();
+    WAKE_ASYNC.notify_waiters();
+}
+
+pub fn requested() -> bool {
+    REQUESTED.load(Ordering::Relaxed)
+}
+
+/// A task's own stop flag, or shutdown.
+pub fn stopping(should_stop: &AtomicBool) -> bool {
+    should_stop.load(Ordering::Relaxed) || requested()
+}
+
+/// Sleeps for `duration` or until shutdown; `false` means stop rather than go round again.
+pub fn sleep(duration: Duration) -> bool {
+    let guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let _guard = WAKE.wait_timeout_while(guard, duration, |_| !requested()).unwrap_or_else(|e| e.into_inner());
+    !requested()
+}
+
+/// `sleep` for async tasks.
+pub async fn sleep_async(duration: Duration) -> bool {
+    let notified = WAKE_ASYNC.notified();
+    tokio::pin!(notified);
+    // Registered before the check, so a request in between still wakes us
+    notified.as_mut().enable();
+    if requested() {
+        return false;
+    }
+    tokio::select! {
+        _ = tokio::time::sleep(duration) => !requested(),
+        _ = notified => false,
+    }
+}
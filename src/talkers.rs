@@ -0,0 +1,119 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+// Byte counts are kept in 10s buckets, back to the longest window
+const BUCKET: Duration = Duration::from_secs(10);
+const KEEP: u64 = 15 * 60 / BUCKET.as_secs();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TalkerWindow {
+    Minute,
+    #[default]
+    FiveMinutes,
+    FifteenMinutes,
+}
+
+impl TalkerWindow {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Minute => "1m",
+            Self::FiveMinutes => "5m",
+            Self::FifteenMinutes => "15m",
+        }
+    }
+
+    pub fn secs(&self) -> u64 {
+        match self {
+            Self::Minute => 60,
+            Self::FiveMinutes => 300,
+            Self::FifteenMinutes => 900,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Minute => Self::FiveMinutes,
+            Self::FiveMinutes => Self::FifteenMinutes,
+            Self::FifteenMinutes => Self::Minute,
+        }
+    }
+}
+
+struct Talker {
+    org: String,
+    country: Option<String>,
+    /// (bucket number, bytes), oldest first
+    buckets: VecDeque<(u64, u64)>,
+}
+
+/// A remote host's share of the sniffed traffic over a window.
+#[derive(Debug, Clone)]
+pub struct TopTalker {
+    pub ip: IpAddr,
+    pub org: String,
+    pub country: Option<String>,
+    pub bytes: u64,
+}
+
+/// Sniffed bytes per remote peer over the last 15 minutes. Peers stay listed after their
+/// connections close, until their traffic ages out.
+pub struct TalkerStats {
+    started: Instant,
+    hosts: HashMap<IpAddr, Talker>,
+}
+
+impl Default for TalkerStats {
+    fn default() -> Self {
+        Self { started: Instant::now(), hosts: HashMap::new() }
+    }
+}
+
+impl TalkerStats {
+    fn bucket(&self) -> u64 {
+        self.started.elapsed().as_secs() / BUCKET.as_secs()
+    }
+
+    /// Adds `bytes` to `ip`; `org` and `country` label it once the connection is gone.
+    pub fn record(&mut self, ip: IpAddr, bytes: u64, org: &str, country: Option<&str>) {
+        let bucket = self.bucket();
+        let talker = self.hosts.entry(ip).or_insert_with(|| Talker { org: String::new(), country: None, buckets: VecDeque::new() });
+        if talker.org != org {
+            talker.org = org.to_string();
+        }
+        if country.is_some() && talker.country.as_deref() != country {
+            talker.country = country.map(str::to_string);
+        }
+        match talker.buckets.back_mut() {
+            Some((b, total)) if *b == bucket => *total += bytes,
+            _ => talker.buckets.push_back((bucket, bytes)),
+        }
+    }
+
+    /// Drops traffic older than the longest window, and hosts with none left.
+    pub fn prune(&mut self) {
+        let oldest = self.bucket().saturating_sub(KEEP);
+        self.hosts.retain(|_, t| {
+            while t.buckets.front().is_some_and(|(b, _)| *b < oldest) {
+                t.buckets.pop_front();
+            }
+            !t.buckets.is_empty()
+        });
+    }
+
+    /// Hosts by bytes over `window`, most first.
+    pub fn top(&self, window: TalkerWindow) -> Vec<TopTalker> {
+        let oldest = self.bucket().saturating_sub(window.secs() / BUCKET.as_secs() - 1);
+        let mut top: Vec<TopTalker> = self.hosts.iter().filter_map(|(ip, t)| {
+            let bytes: u64 = t.buckets.iter().filter(|(b, _)| *b >= oldest).map(|(_, n)| n).sum();
+            (bytes > 0).then(|| TopTalker { ip: *ip, org: t.org.clone(), country: t.country.clone(), bytes })
+        }).collect();
+        top.sort_by_key(|t| std::cmp::Reverse(t.bytes));
+        top
+    }
+
+    /// Seconds of traffic `window` covers so far; less than the window right after startup.
+    pub fn span(&self, window: TalkerWindow) -> u64 {
+        (self.started.elapsed().as_secs() + 1).min(window.secs())
+    }
+}
@@ -1,4 +1,8 @@
-use ratatui::style::Color;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
 
 pub struct Theme {
     pub bg: Color,
@@ -11,6 +15,10 @@ pub struct Theme {
     pub error: Color,
     pub border: Color,
     pub muted: Color,
+    /// Good and bad results also get a mark, for when green and red can't be told apart
+    pub symbols: bool,
+    /// Colors are stripped after drawing; see `strip_colors`
+    pub monochrome: bool,
 }
 
 impl Default for Theme {
@@ -27,11 +35,13 @@ impl Default for Theme {
             error: Color::Rgb(255, 50, 80),     // Bright Red
             border: Color::Rgb(60, 60, 80),     // Muted Blue-Grey
             muted: Color::Rgb(100, 100, 120),   // Grey text
+            symbols: false,
+            monochrome: false,
         }
     }
 }
 
-pub const THEME: Theme = Theme {
+const NEON: Theme = Theme {
     bg: Color::Rgb(10, 10, 15),
     surface: Color::Rgb(20, 20, 30),
     fg: Color::Rgb(240, 240, 255),
@@ -42,4 +52,137 @@ pub const THEME: Theme = Theme {
     error: Color::Rgb(255, 50, 80),
     border: Color::Rgb(60, 60, 80),
     muted: Color::Rgb(100, 100, 120),
+    symbols: false,
+    monochrome: false,
+};
+
+// Nord palette
+const NORD: Theme = Theme {
+    bg: Color::Rgb(46, 52, 64),
+    surface: Color::Rgb(59, 66, 82),
+    fg: Color::Rgb(236, 239, 244),
+    primary: Color::Rgb(136, 192, 208),
+    secondary: Color::Rgb(180, 142, 173),
+    accent: Color::Rgb(129, 161, 193),
+    success: Color::Rgb(163, 190, 140),
+    error: Color::Rgb(191, 97, 106),
+    border: Color::Rgb(76, 86, 106),
+    muted: Color::Rgb(118, 128, 148),
+    symbols: false,
+    monochrome: false,
+};
+
+const LIGHT: Theme = Theme {
+    bg: Color::Rgb(250, 250, 250),
+    surface: Color::Rgb(230, 230, 235),
+    fg: Color::Rgb(30, 30, 40),
+    primary: Color::Rgb(0, 110, 190),
+    secondary: Color::Rgb(170, 30, 140),
+    accent: Color::Rgb(100, 70, 200),
+    success: Color::Rgb(20, 140, 60),
+    error: Color::Rgb(200, 30, 50),
+    border: Color::Rgb(180, 180, 190),
+    muted: Color::Rgb(110, 110, 120),
+    symbols: false,
+    monochrome: false,
+};
+
+// Okabe-Ito palette: blue for good and vermillion for bad stay apart with any color vision
+const COLORBLIND: Theme = Theme {
+    bg: Color::Rgb(10, 10, 15),
+    surface: Color::Rgb(25, 25, 32),
+    fg: Color::Rgb(240, 240, 240),
+    primary: Color::Rgb(240, 228, 66),
+    secondary: Color::Rgb(204, 121, 167),
+    accent: Color::Rgb(230, 159, 0),
+    success: Color::Rgb(86, 180, 233),
+    error: Color::Rgb(213, 94, 0),
+    border: Color::Rgb(70, 70, 80),
+    muted: Color::Rgb(120, 120, 130),
+    symbols: true,
+    monochrome: false,
+};
+
+// The terminal's own colors only. The placeholders never reach the screen: `strip_colors`
+// turns highlighted backgrounds into reverse video, errors into bold and muted text into dim.
+const MONO: Theme = Theme {
+    bg: Color::Reset,
+    surface: Color::Reset,
+    fg: Color::Reset,
+    primary: Color::Indexed(1),
+    secondary: Color::Indexed(2),
+    accent: Color::Indexed(3),
+    success: Color::Indexed(4),
+    error: Color::Indexed(5),
+    border: Color::Reset,
+    muted: Color::Indexed(6),
+    symbols: true,
+    monochrome: true,
 };
+
+const THEMES: [(&str, &Theme); 5] = [("neon", &NEON), ("nord", &NORD), ("light", &LIGHT), ("colorblind", &COLORBLIND), ("mono", &MONO)];
+
+static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+
+/// Resolves to whichever preset is active, so `THEME.bg` follows theme changes at runtime.
+pub struct ActiveTheme;
+
+impl Deref for ActiveTheme {
+    type Target = Theme;
+
+    fn deref(&self) -> &Theme {
+        THEMES[ACTIVE.load(Ordering::Relaxed)].1
+    }
+}
+
+pub static THEME: ActiveTheme = ActiveTheme;
+
+pub fn theme_names() -> impl Iterator<Item = &'static str> {
+    THEMES.iter().map(|(name, _)| *name)
+}
+
+pub fn current_theme_name() -> &'static str {
+    THEMES[ACTIVE.load(Ordering::Relaxed)].0
+}
+
+/// Switches the active preset; returns false for an unknown name.
+pub fn set_theme(name: &str) -> bool {
+    match THEMES.iter().position(|(n, _)| n.eq_ignore_ascii_case(name)) {
+        Some(i) => {
+            ACTIVE.store(i, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Whether `NO_COLOR` is set (to anything non-empty), which forces the mono theme.
+pub fn no_color() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
+/// A mark before a good (`ok`) or bad result in themes with `symbols`; empty otherwise.
+pub fn mark(ok: bool) -> &'static str {
+    match (THEME.symbols, ok) {
+        (false, _) => "",
+        (true, true) => "✓ ",
+        (true, false) => "✗ ",
+    }
+}
+
+/// For the monochrome theme: replaces every color in a drawn frame with the terminal's
+/// defaults, keeping what the colors meant as reverse video, bold and dim.
+pub fn strip_colors(buf: &mut Buffer) {
+    for cell in buf.content.iter_mut() {
+        let mut modifier = cell.modifier;
+        if cell.bg != Color::Reset {
+            modifier |= Modifier::REVERSED;
+        } else if cell.fg == MONO.error {
+            modifier |= Modifier::BOLD;
+        } else if cell.fg == MONO.muted {
+            modifier |= Modifier::DIM;
+        }
+        cell.set_fg(Color::Reset).set_bg(Color::Reset);
+        cell.modifier = modifier;
+    }
+}
@@ -0,0 +1,198 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Splits a tool input into arguments the way a shell would: on whitespace, except inside
+/// single or double quotes, with `\` escaping the next character outside single quotes.
+pub fn split(input: &str) -> Result<Vec<String>, String> {
+    let mut args = vec![];
+    let mut current = String::new();
+    // Whether `current` is an argument yet; `""` is an empty one
+    let mut started = false;
+    let mut quote: Option<char> = None;
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('\''), c) => current.push(c),
+            (_, '\\') => {
+                current.push(chars.next().ok_or("Trailing \\ in arguments")?);
+                started = true;
+            }
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                started = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if started {
+                    args.push(std::mem::take(&mut current));
+                    started = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                started = true;
+            }
+        }
+    }
+    if let Some(q) = quote {
+        return Err(format!("Unclosed {} in arguments", q));
+    }
+    if started {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+/// Joins arguments back into an input `split` gives them back from, quoting where needed.
+pub fn join<S: AsRef<str>>(args: &[S]) -> String {
+    args.iter().map(|arg| {
+        let arg = arg.as_ref();
+        if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '\\')) {
+            arg.to_string()
+        } else if !arg.contains('\'') {
+            format!("'{}'", arg)
+        } else {
+            format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+        }
+    }).collect::<Vec<_>>().join(" ")
+}
+
+/// A tool input's arguments, walked front to back. Flags take their value from the
+/// argument after them; a missing or unparseable one is an error naming the flag.
+pub struct Args {
+    args: std::vec::IntoIter<String>,
+}
+
+impl Args {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        Ok(Self { args: split(input)?.into_iter() })
+    }
+
+    /// The value after `flag`, described as `what` in the error ("-c needs a count").
+    pub fn value<T: FromStr>(&mut self, flag: &str, what: &str) -> Result<T, String> {
+        let value = self.args.next().ok_or_else(|| format!("{} needs {}", flag, what))?;
+        value.parse().map_err(|_| format!("{} needs {}, not '{}'", flag, what, value))
+    }
+
+    /// `value`, which must also be more than zero.
+    pub fn positive<T: FromStr + PartialOrd + Default>(&mut self, flag: &str, what: &str) -> Result<T, String> {
+        let value = self.args.next().ok_or_else(|| format!("{} needs {}", flag, what))?;
+        value.parse().ok().filter(|v| *v > T::default()).ok_or_else(|| format!("{} needs {}, not '{}'", flag, what, value))
+    }
+
+    /// A positive number of seconds, fractions allowed; `inf` and the like are refused
+    /// rather than overflowing a `Duration`.
+    pub fn seconds(&mut self, flag: &str) -> Result<Duration, String> {
+        let value = self.args.next().ok_or_else(|| format!("{} needs seconds", flag))?;
+        value.parse::<f64>().ok()
+            .filter(|secs| *secs > 0.0)
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+            .ok_or_else(|| format!("{} needs seconds, not '{}'", flag, value))
+    }
+
+    /// A ToS byte, as ping's `-Q` takes it (`184`, `0xb8`), or a DSCP class name (`ef`,
+    /// `af41`, `cs1`) shifted into place.
+    pub fn tos(&mut self, flag: &str) -> Result<u8, String> {
+        let value = self.args.next().ok_or_else(|| format!("{} needs a ToS byte or DSCP class", flag))?;
+        let bad = || format!("{} needs a ToS byte (0-255) or DSCP class (ef, af11-af43, cs0-cs7), not '{}'", flag, value);
+        let lower = value.to_lowercase();
+        let dscp = match lower.as_str() {
+            "ef" => Some(46),
+            "be" | "default" => Some(0),
+            class if class.starts_with("cs") => class[2..].parse::<u8>().ok().filter(|n| *n <= 7).map(|n| n << 3),
+            class if class.starts_with("af") => match &class.as_bytes()[2..] {
+                [c @ b'1'..=b'4', d @ b'1'..=b'3'] => Some((c - b'0') * 8 + (d - b'0') * 2),
+                _ => return Err(bad()),
+            },
+            _ => None,
+        };
+        if let Some(dscp) = dscp {
+            return Ok(dscp << 2);
+        }
+        match lower.strip_prefix("0x") {
+            Some(hex) => u8::from_str_radix(hex, 16).map_err(|_| bad()),
+            None => lower.parse().map_err(|_| bad()),
+        }
+    }
+}
+
+impl Iterator for Args {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        self.args.next()
+    }
+}
+
+/// The error for a flag a tool doesn't know.
+pub fn unknown(flag: &str) -> String {
+    format!("Unknown flag: {}", flag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(input: &str) -> Args {
+        Args::parse(input).unwrap()
+    }
+
+    #[test]
+    fn split_quotes_and_escapes() {
+        assert_eq!(split("  ping  -c 3 host ").unwrap(), ["ping", "-c", "3", "host"]);
+        assert_eq!(split(r#"grep "a b" 'c "d"' e\ f"#).unwrap(), ["grep", "a b", r#"c "d""#, "e f"]);
+        assert_eq!(split(r#"'a\b' "a\"b""#).unwrap(), [r"a\b", r#"a"b"#]);
+        assert_eq!(split(r#"x "" '' y"#).unwrap(), ["x", "", "", "y"]);
+        assert!(split("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn split_errors() {
+        assert_eq!(split("say \"hi").unwrap_err(), "Unclosed \" in arguments");
+        assert_eq!(split("it's").unwrap_err(), "Unclosed ' in arguments");
+        assert_eq!(split("trailing \\").unwrap_err(), "Trailing \\ in arguments");
+    }
+
+    #[test]
+    fn join_round_trips() {
+        let cases: [&[&str]; 5] = [
+            &["plain", "-c", "3"],
+            &["a b", ""],
+            &["it's", r#"say "hi""#],
+            &[r"back\slash", r"it's \ both"],
+            &["tab\there", "new\nline"],
+        ];
+        for case in cases {
+            assert_eq!(split(&join(case)).unwrap(), case, "{}", join(case));
+        }
+    }
+
+    #[test]
+    fn positive_rejects_zero_and_negative() {
+        assert_eq!(args("5").positive::<u32>("-c", "a count"), Ok(5));
+        assert_eq!(args("0").positive::<u32>("-c", "a count").unwrap_err(), "-c needs a count, not '0'");
+        assert_eq!(args("-2").positive::<i64>("-c", "a count").unwrap_err(), "-c needs a count, not '-2'");
+        assert_eq!(args("").positive::<u32>("-c", "a count").unwrap_err(), "-c needs a count");
+    }
+
+    #[test]
+    fn seconds() {
+        assert_eq!(args("0.25").seconds("-i"), Ok(Duration::from_millis(250)));
+        for bad in ["0", "-1", "inf", "NaN", "1e30", "soon"] {
+            assert_eq!(args(bad).seconds("-i").unwrap_err(), format!("-i needs seconds, not '{}'", bad));
+        }
+    }
+
+    #[test]
+    fn tos() {
+        assert_eq!(args("ef").tos("-Q"), Ok(184));
+        assert_eq!(args("AF41").tos("-Q"), Ok(136));
+        assert_eq!(args("cs1").tos("-Q"), Ok(32));
+        assert_eq!(args("0xb8").tos("-Q"), Ok(184));
+        assert_eq!(args("184").tos("-Q"), Ok(184));
+        for bad in ["cs8", "af5", "af44", "0x1ff", "256", "fast"] {
+            assert!(args(bad).tos("-Q").is_err(), "{}", bad);
+        }
+    }
+}
@@ -1,70 +1,288 @@
-use std::process::{Command, Stdio};
-use std::io::{BufRead, BufReader};
+use std::net::Ipv4Addr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use crossbeam::channel::Sender;
+use pnet::ipnetwork::{IpNetwork, Ipv4Network};
+use pnet::packet::arp::{ArpHardwareTypes, ArpOperations, ArpPacket, MutableArpPacket};
+use pnet::packet::ethernet::{EtherTypes, EthernetPacket, MutableEthernetPacket};
+use pnet::packet::{MutablePacket, Packet};
+use pnet::util::MacAddr;
+use pnet_datalink::{self as datalink, Channel, NetworkInterface};
+
+use crate::config::ScanLimits;
+use crate::shutdown;
+use crate::tools::args::{self, Args};
+use crate::tools::process::{self, ProcessHandle};
+
+// As for the ping sweep: anything wider takes too long and looks like an attack
+const MAX_PREFIX: u8 = 16;
+const DEFAULT_WATCH_EVERY: Duration = Duration::from_secs(60);
+// ARP requests per second when neither -r nor [scan.arp] rate says
+const DEFAULT_RATE: u32 = 200;
+// Sweeps a host can miss before it shows offline
+const MISSED_SWEEPS: u32 = 2;
 
 #[derive(Clone, Debug)]
 pub struct ArpEntry {
     pub ip: String,
     pub mac: String,
     pub vendor: String,
+    /// When continuous discovery last heard from the host; `None` for one-shot results
+    pub last_seen: Option<Instant>,
 }
 
+impl ArpEntry {
+    /// Heard from within the last couple of sweeps; `None` outside continuous discovery.
+    pub fn online(&self, every: Duration) -> Option<bool> {
+        self.last_seen.map(|t| t.elapsed() <= every * MISSED_SWEEPS + every / 2)
+    }
+}
+
+/// Output lines as they come; an `Err` when arp-scan couldn't run or exited with an error.
 pub struct ArpScanTask {
     pub target: String,
-    pub tx: Sender<String>,
+    pub tx: Sender<Result<String, String>>,
+    pub process: ProcessHandle,
 }
 
 impl ArpScanTask {
-    pub fn new(target: String, tx: Sender<String>) -> Self {
-        Self { target, tx }
+    pub fn new(target: String, tx: Sender<Result<String, String>>, process: ProcessHandle) -> Self {
+        Self { target, tx, process }
     }
 
     pub fn run(&self) {
-        let args: Vec<&str> = self.target.split_whitespace().collect();
-        
-        // sudo is often needed for arp-scan due to raw socket usage
-        // But the main app is run with sudo, so child process inherits permissions.
-        let mut cmd = Command::new("arp-scan");
-        cmd.args(&args);
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::piped());
-
-        match cmd.spawn() {
-            Ok(mut child) => {
-                let stdout = child.stdout.take().expect("Failed to capture stdout");
-                let stderr = child.stderr.take().expect("Failed to capture stderr");
-
-                let tx_out = self.tx.clone();
-                let tx_err = self.tx.clone();
-
-                // Stream stdout
-                std::thread::spawn(move || {
-                    let reader = BufReader::new(stdout);
-                    for line in reader.lines() {
-                        if let Ok(l) = line {
-                            let _ = tx_out.send(l);
-                        }
-                    }
-                });
-
-                // Stream stderr
-                std::thread::spawn(move || {
-                    let reader = BufReader::new(stderr);
-                    for line in reader.lines() {
-                        if let Ok(l) = line {
-                            let _ = tx_err.send(format!("ERR: {}", l));
-                        }
-                    }
-                });
-                
-                // Wait for process to finish
-                let _ = child.wait();
-                let _ = self.tx.send("Done.".to_string());
+        // arp-scan needs raw sockets; it inherits whatever privileges the app runs with
+        let result = args::split(&self.target).and_then(|args| process::stream("arp-scan", &args, &self.tx, &self.process));
+        let _ = self.tx.send(result);
+    }
+}
+
+/// Appends arp-scan's packet interval and per-host timeout for configured limits the args don't already set.
+pub fn with_limits(args: &str, limits: &ScanLimits) -> String {
+    let has = |short: &str, long: &str| args.split_whitespace().any(|a| a == short || a.starts_with(long));
+    let mut out = args.to_string();
+    // arp-scan paces by the gap between packets; a `u` suffix means microseconds
+    if let Some(rate) = limits.rate.filter(|r| *r > 0 && !has("-i", "--interval") && !has("-B", "--bandwidth")) {
+        out.push_str(&format!(" --interval={}u", 1_000_000 / rate));
+    }
+    if let Some(ms) = limits.timeout_ms.filter(|_| !has("-t", "--timeout")) {
+        out.push_str(&format!(" --timeout={}", ms));
+    }
+    out
+}
+
+/// Continuous discovery: `--watch[=<secs>]` with `-I <interface>` and a CIDR or `-l` for the
+/// interface's own network. Sweeps are paced by `--interval=<ms>` between packets, as for
+/// arp-scan, or by `[scan.arp] rate`.
+#[derive(Debug, Clone)]
+pub struct DiscoveryRequest {
+    pub interface: NetworkInterface,
+    pub network: Ipv4Network,
+    /// Time between the starts of two active sweeps
+    pub every: Duration,
+    /// Time between two ARP requests of a sweep
+    pub gap: Duration,
+}
+
+impl DiscoveryRequest {
+    /// Whether the ArpScan input asks for continuous discovery rather than an arp-scan run.
+    pub fn wanted(input: &str) -> bool {
+        input.split_whitespace().any(|a| a == "--watch" || a.starts_with("--watch="))
+    }
+
+    pub fn parse(input: &str, interfaces: &[NetworkInterface], limits: &ScanLimits) -> Result<Self, String> {
+        let mut every = DEFAULT_WATCH_EVERY;
+        let mut interface_name: Option<String> = None;
+        let mut network = None;
+        let mut localnet = false;
+        let mut gap = Duration::from_secs_f64(1.0 / limits.rate.filter(|r| *r > 0).unwrap_or(DEFAULT_RATE) as f64);
+        let mut args = Args::parse(input)?;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--watch" => {}
+                "-I" | "--interface" => interface_name = Some(args.value("-I", "an interface name")?),
+                "-l" | "--localnet" => localnet = true,
+                flag if flag.starts_with("--watch=") => {
+                    let secs = &flag["--watch=".len()..];
+                    every = secs.parse::<f64>().ok().filter(|s| *s >= 1.0).and_then(|s| Duration::try_from_secs_f64(s).ok())
+                        .ok_or_else(|| format!("--watch needs a number of seconds (1 or more), not '{}'", secs))?;
+                }
+                flag if flag.starts_with("--interval=") => {
+                    let ms = &flag["--interval=".len()..];
+                    gap = ms.parse::<f64>().ok().filter(|ms| *ms > 0.0).and_then(|ms| Duration::try_from_secs_f64(ms / 1000.0).ok())
+                        .ok_or_else(|| format!("--interval needs milliseconds between packets, not '{}'", ms))?;
+                }
+                flag if flag.starts_with('-') => return Err(args::unknown(flag)),
+                cidr => network = Some(cidr.parse::<Ipv4Network>().map_err(|_| format!("Not an IPv4 address or CIDR: {}", cidr))?),
+            }
+        }
+
+        let usable = |i: &&NetworkInterface| i.is_up() && !i.is_loopback() && i.mac.is_some() && i.ips.iter().any(|ip| ip.is_ipv4());
+        let interface = match &interface_name {
+            Some(name) => interfaces.iter().find(|i| &i.name == name).ok_or_else(|| format!("{}: interface not found", name))?,
+            // The interface whose network holds the range, or the first that could send ARP at all
+            None => interfaces.iter().filter(usable)
+                .find(|i| network.is_none_or(|n: Ipv4Network| own_network(i).is_some_and(|own| own.contains(n.network()))))
+                .or_else(|| interfaces.iter().find(usable))
+                .ok_or("No up Ethernet interface with an IPv4 address; pick one with -I")?,
+        };
+        if interface.mac.is_none() {
+            return Err(format!("{} has no MAC address to send ARP from", interface.name));
+        }
+        let network = match network {
+            Some(network) => network,
+            None if localnet || interface_name.is_some() => own_network(interface).ok_or_else(|| format!("{} has no IPv4 network", interface.name))?,
+            None => return Err("Enter a range like 192.168.1.0/24, or -l for the interface's network".to_string()),
+        };
+        if network.prefix() < MAX_PREFIX {
+            return Err(format!("/{} is too large to watch; use /{} or smaller", network.prefix(), MAX_PREFIX));
+        }
+        Ok(Self { interface: interface.clone(), network, every, gap })
+    }
+
+    fn hosts(&self) -> Vec<Ipv4Addr> {
+        let net = self.network;
+        net.iter().filter(|ip| net.prefix() >= 31 || (*ip != net.network() && *ip != net.broadcast())).collect()
+    }
+}
+
+fn own_network(interface: &NetworkInterface) -> Option<Ipv4Network> {
+    interface.ips.iter().find_map(|ip| match ip {
+        IpNetwork::V4(net) if net.prefix() < 32 => Ipv4Network::new(net.network(), net.prefix()).ok(),
+        _ => None,
+    })
+}
+
+#[derive(Debug, Clone)]
+pub enum DiscoveryEvent {
+    /// A host's ARP packet: an answer to our sweep, or (`passive`) anything else it sent
+    Seen { ip: Ipv4Addr, mac: String, passive: bool },
+    /// An active sweep of this many addresses has begun
+    SweepStarted(usize),
+    SweepDone,
+    Error(String),
+}
+
+/// Sweeps the range with ARP requests every `every`, listening to all ARP traffic on the
+/// interface in between so hosts that talk are kept fresh without being asked.
+pub struct DiscoveryTask {
+    pub should_stop: Arc<AtomicBool>,
+}
+
+impl DiscoveryTask {
+    pub fn new() -> Self {
+        Self {
+            should_stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn start(&self, request: DiscoveryRequest, tx: Sender<DiscoveryEvent>) {
+        let should_stop = self.should_stop.clone();
+        should_stop.store(false, Ordering::Relaxed);
+
+        // Short reads so both threads notice a stop on a quiet network
+        let config = datalink::Config { read_timeout: Some(Duration::from_millis(200)), ..Default::default() };
+        let (mut sender, mut receiver) = match datalink::channel(&request.interface, config) {
+            Ok(Channel::Ethernet(sender, receiver)) => (sender, receiver),
+            Ok(_) => {
+                let _ = tx.send(DiscoveryEvent::Error(format!("{}: unsupported channel type", request.interface.name)));
+                return;
             }
             Err(e) => {
-                let _ = self.tx.send(format!("Failed to start arp-scan: {}", e));
-                let _ = self.tx.send("Ensure 'arp-scan' is installed and in your PATH.".to_string());
+                let _ = tx.send(DiscoveryEvent::Error(format!("{}: {} (needs root or CAP_NET_RAW)", request.interface.name, e)));
+                return;
+            }
+        };
+        let Some(own_mac) = request.interface.mac else { return };
+
+        let listen_stop = should_stop.clone();
+        let listen_tx = tx.clone();
+        std::thread::spawn(move || {
+            while !shutdown::stopping(&listen_stop) {
+                let frame = match receiver.next() {
+                    Ok(frame) => frame,
+                    Err(e) if e.kind() == std::io::ErrorKind::TimedOut => continue,
+                    Err(e) => {
+                        let _ = listen_tx.send(DiscoveryEvent::Error(format!("Listening for ARP: {}", e)));
+                        break;
+                    }
+                };
+                let Some(ethernet) = EthernetPacket::new(frame) else { continue };
+                if ethernet.get_ethertype() != EtherTypes::Arp {
+                    continue;
+                }
+                let Some(arp) = ArpPacket::new(ethernet.payload()) else { continue };
+                let (ip, mac) = (arp.get_sender_proto_addr(), arp.get_sender_hw_addr());
+                // Our own requests, and RFC 5227 probes from hosts still checking their address
+                if mac == own_mac || ip.is_unspecified() {
+                    continue;
+                }
+                let answer = arp.get_operation() == ArpOperations::Reply && arp.get_target_hw_addr() == own_mac;
+                if listen_tx.send(DiscoveryEvent::Seen { ip, mac: mac.to_string(), passive: !answer }).is_err() {
+                    break;
+                }
             }
+        });
+
+        std::thread::spawn(move || {
+            let source = request.interface.ips.iter().find_map(|ip| match ip {
+                IpNetwork::V4(net) => Some(net.ip()),
+                _ => None,
+            }).unwrap_or(Ipv4Addr::UNSPECIFIED);
+            let hosts = request.hosts();
+            while !shutdown::stopping(&should_stop) {
+                let started = Instant::now();
+                let _ = tx.send(DiscoveryEvent::SweepStarted(hosts.len()));
+                for target in &hosts {
+                    if shutdown::stopping(&should_stop) {
+                        return;
+                    }
+                    if *target == source {
+                        continue;
+                    }
+                    let frame = arp_request(own_mac, source, *target);
+                    if let Some(Err(e)) = sender.send_to(&frame, None) {
+                        let _ = tx.send(DiscoveryEvent::Error(format!("Sending ARP to {}: {}", target, e)));
+                        return;
+                    }
+                    std::thread::sleep(request.gap);
+                }
+                let _ = tx.send(DiscoveryEvent::SweepDone);
+                // Passive listening carries on while we wait for the next sweep
+                while started.elapsed() < request.every {
+                    if shutdown::stopping(&should_stop) {
+                        return;
+                    }
+                    std::thread::sleep(Duration::from_millis(200));
+                }
+            }
+        });
+    }
+
+    pub fn stop(&self) {
+        self.should_stop.store(true, Ordering::Relaxed);
+    }
+}
+
+// Who-has `target`, tell `source`, broadcast on the wire
+fn arp_request(own_mac: MacAddr, source: Ipv4Addr, target: Ipv4Addr) -> Vec<u8> {
+    let mut frame = vec![0u8; 42];
+    if let Some(mut ethernet) = MutableEthernetPacket::new(&mut frame) {
+        ethernet.set_destination(MacAddr::broadcast());
+        ethernet.set_source(own_mac);
+        ethernet.set_ethertype(EtherTypes::Arp);
+        if let Some(mut arp) = MutableArpPacket::new(ethernet.payload_mut()) {
+            arp.set_hardware_type(ArpHardwareTypes::Ethernet);
+            arp.set_protocol_type(EtherTypes::Ipv4);
+            arp.set_hw_addr_len(6);
+            arp.set_proto_addr_len(4);
+            arp.set_operation(ArpOperations::Request);
+            arp.set_sender_hw_addr(own_mac);
+            arp.set_sender_proto_addr(source);
+            arp.set_target_hw_addr(MacAddr::zero());
+            arp.set_target_proto_addr(target);
         }
     }
+    frame
 }
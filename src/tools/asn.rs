@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use serde_json::Value;
+
+// RIPEstat's public data API; answers for every RIR, not just RIPE's region
+const RIPESTAT: &str = "https://stat.ripe.net/data";
+const TIMEOUT: Duration = Duration::from_secs(30);
+// Organization searches list at most this many networks
+const SEARCH_LIMIT: usize = 50;
+
+/// What the ASN screen was asked about: `AS15169` or `15169`, an address or prefix, or
+/// anything else as an organization name to search for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AsnQuery {
+    Asn(u32),
+    /// An address or a CIDR prefix, looked up in the global routing table
+    Route(String),
+    Org(String),
+}
+
+impl AsnQuery {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err("Enter an AS number, an IP address or an organization".to_string());
+        }
+        let digits = input.strip_prefix("AS").or_else(|| input.strip_prefix("as")).unwrap_or(input);
+        if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+            return digits.parse().map(AsnQuery::Asn).map_err(|_| format!("{} is not an AS number", input));
+        }
+        if let Ok(ip) = input.parse::<IpAddr>() {
+            return Ok(AsnQuery::Route(ip.to_string()));
+        }
+        if let Some((addr, len)) = input.split_once('/')
+            && let Ok(addr) = addr.parse::<IpAddr>()
+        {
+            let max = if addr.is_ipv4() { 32 } else { 128 };
+            return match len.parse::<u8>() {
+                Ok(len) if len <= max => Ok(AsnQuery::Route(format!("{}/{}", addr, len))),
+                _ => Err(format!("{} is not a valid prefix", input)),
+            };
+        }
+        Ok(AsnQuery::Org(input.to_string()))
+    }
+}
+
+/// An autonomous system and the prefixes it announces, IPv4 first.
+#[derive(Debug, Clone)]
+pub struct AsInfo {
+    pub asn: u32,
+    pub holder: String,
+    pub prefixes: Vec<String>,
+}
+
+impl AsInfo {
+    pub fn v4_count(&self) -> usize {
+        self.prefixes.iter().filter(|p| !p.contains(':')).count()
+    }
+}
+
+/// One AS path to the prefix, how many route collector peers see it, and where those
+/// collectors are.
+#[derive(Debug, Clone)]
+pub struct AsPath {
+    pub path: Vec<u32>,
+    pub peers: usize,
+    pub collectors: Vec<String>,
+}
+
+/// How RIS route collector peers see a prefix: how many of them have a route at all.
+#[derive(Debug, Clone, Copy)]
+pub struct Visibility {
+    pub seeing: u64,
+    pub total: u64,
+}
+
+impl Visibility {
+    pub fn pct(&self) -> f64 {
+        if self.total == 0 { 0.0 } else { self.seeing as f64 / self.total as f64 * 100.0 }
+    }
+}
+
+/// Where an address or prefix sits in BGP, as the RIPE RIS looking glass sees it: the
+/// covering prefix, the networks originating it, how widely it's visible and the paths
+/// collectors see towards it, most common first.
+#[derive(Debug, Clone)]
+pub struct RouteContext {
+    pub resource: String,
+    pub prefix: Option<String>,
+    pub origins: Vec<(u32, String)>,
+    pub visibility: Option<Visibility>,
+    /// When RIS first saw the prefix, as RIPEstat reports it (UTC)
+    pub first_seen: Option<String>,
+    pub paths: Vec<AsPath>,
+}
+
+impl RouteContext {
+    /// More than one origin AS: an anycast setup, a migration, or a hijack.
+    pub fn moas(&self) -> bool {
+        self.origins.len() > 1
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum AsnResult {
+    As(AsInfo),
+    /// Networks whose name matches the search, with their holders
+    Search(String, Vec<(u32, String)>),
+    Route(RouteContext),
+}
+
+impl AsnResult {
+    /// The lines a table on the ASN screen shows, for selection and copying.
+    pub fn rows(&self) -> Vec<String> {
+        match self {
+            AsnResult::As(info) => info.prefixes.clone(),
+            AsnResult::Search(_, matches) => matches.iter().map(|(asn, holder)| format!("AS{} {}", asn, holder)).collect(),
+            AsnResult::Route(ctx) => ctx.paths.iter().map(|p| format_path(&p.path)).collect(),
+        }
+    }
+}
+
+/// Whether an address can appear in the global routing table: not private, CGNAT,
+/// loopback, link-local or multicast.
+pub fn is_routed(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_private() || v4.is_loopback() || v4.is_link_local() || v4.is_unspecified() || v4.is_broadcast()
+                || v4.is_multicast() || v4.is_documentation() || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            !(v6.is_loopback() || v6.is_unspecified() || v6.is_multicast() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+pub fn format_path(path: &[u32]) -> String {
+    path.iter().map(|asn| format!("AS{}", asn)).collect::<Vec<_>>().join(" → ")
+}
+
+pub async fn lookup(query: AsnQuery) -> Result<AsnResult, String> {
+    let client = reqwest::Client::builder().timeout(TIMEOUT).build().map_err(|e| e.to_string())?;
+    match query {
+        AsnQuery::Asn(asn) => as_info(&client, asn).await.map(AsnResult::As),
+        AsnQuery::Org(name) => search(&client, &name).await.map(|matches| AsnResult::Search(name, matches)),
+        AsnQuery::Route(resource) => route(&client, &resource).await.map(AsnResult::Route),
+    }
+}
+
+async fn get(client: &reqwest::Client, call: &str, resource: &str) -> Result<Value, String> {
+    let url = format!("{}/{}/data.json", RIPESTAT, call);
+    let response = client.get(&url).query(&[("resource", resource), ("sourceapp", "netops")]).send().await
+        .map_err(|e| format!("RIPEstat {}: {}", call, e))?;
+    if !response.status().is_success() {
+        return Err(format!("RIPEstat {}: HTTP {}", call, response.status()));
+    }
+    let body: Value = response.json().await.map_err(|e| format!("RIPEstat {}: bad answer: {}", call, e))?;
+    Ok(body["data"].clone())
+}
+
+async fn as_info(client: &reqwest::Client, asn: u32) -> Result<AsInfo, String> {
+    let resource = format!("AS{}", asn);
+    let (overview, announced) = tokio::try_join!(get(client, "as-overview", &resource), get(client, "announced-prefixes", &resource))?;
+    let holder = overview["holder"].as_str().unwrap_or("").to_string();
+    let mut prefixes: Vec<String> = announced["prefixes"].as_array().into_iter().flatten()
+        .filter_map(|p| p["prefix"].as_str().map(str::to_string))
+        .collect();
+    prefixes.sort_by_key(|p| prefix_key(p));
+    prefixes.dedup();
+    Ok(AsInfo { asn, holder, prefixes })
+}
+
+// Numeric order, IPv4 before IPv6
+fn prefix_key(prefix: &str) -> (bool, Option<IpAddr>, u8) {
+    let (addr, len) = prefix.split_once('/').unwrap_or((prefix, ""));
+    let addr: Option<IpAddr> = addr.parse().ok();
+    (addr.is_some_and(|a| a.is_ipv6()), addr, len.parse().unwrap_or(0))
+}
+
+async fn search(client: &reqwest::Client, name: &str) -> Result<Vec<(u32, String)>, String> {
+    let data = get(client, "searchcomplete", name).await?;
+    let matches: Vec<(u32, String)> = data["categories"].as_array().into_iter().flatten()
+        .filter(|c| c["category"].as_str() == Some("ASNs"))
+        .flat_map(|c| c["suggestions"].as_array().cloned().unwrap_or_default())
+        .filter_map(|s| {
+            let asn = s["value"].as_str()?.trim_start_matches("AS").parse().ok()?;
+            Some((asn, s["description"].as_str().unwrap_or("").to_string()))
+        })
+        .take(SEARCH_LIMIT)
+        .collect();
+    if matches.is_empty() {
+        return Err(format!("No networks named like {:?}", name));
+    }
+    Ok(matches)
+}
+
+async fn route(client: &reqwest::Client, resource: &str) -> Result<RouteContext, String> {
+    let (overview, glass) = tokio::try_join!(get(client, "prefix-overview", resource), get(client, "looking-glass", resource))?;
+    let prefix = overview["resource"].as_str().filter(|r| r.contains('/')).map(str::to_string);
+    let origins = overview["asns"].as_array().into_iter().flatten()
+        .filter_map(|a| Some((a["asn"].as_u64()? as u32, a["holder"].as_str().unwrap_or("").to_string())))
+        .collect();
+
+    // The same path is usually seen by many collector peers; count them
+    let mut seen: HashMap<Vec<u32>, (usize, Vec<String>)> = HashMap::new();
+    for rrc in glass["rrcs"].as_array().into_iter().flatten() {
+        let location = rrc["location"].as_str().or(rrc["rrc"].as_str()).unwrap_or("").to_string();
+        for peer in rrc["peers"].as_array().into_iter().flatten() {
+            let Some(path) = peer["as_path"].as_str() else { continue };
+            let mut path: Vec<u32> = path.split_whitespace().filter_map(|asn| asn.parse().ok()).collect();
+            // Prepending repeats an AS to make the path look longer; it's still one hop
+            path.dedup();
+            if path.is_empty() {
+                continue;
+            }
+            let (peers, collectors) = seen.entry(path).or_default();
+            *peers += 1;
+            if !location.is_empty() && !collectors.contains(&location) {
+                collectors.push(location.clone());
+            }
+        }
+    }
+    let mut paths: Vec<AsPath> = seen.into_iter().map(|(path, (peers, collectors))| AsPath { path, peers, collectors }).collect();
+    paths.sort_by(|a, b| b.peers.cmp(&a.peers).then(a.path.len().cmp(&b.path.len())).then(a.path.cmp(&b.path)));
+
+    if prefix.is_none() && paths.is_empty() {
+        return Err(format!("{} is not announced", resource));
+    }
+
+    // Visibility is per prefix; without one there's nothing to ask about
+    let (visibility, first_seen) = match &prefix {
+        Some(prefix) => match get(client, "routing-status", prefix).await {
+            Ok(status) => {
+                let family = if prefix.contains(':') { "v6" } else { "v4" };
+                let v = &status["visibility"][family];
+                let visibility = v["ris_peers_seeing"].as_u64().zip(v["total_ris_peers"].as_u64()).map(|(seeing, total)| Visibility { seeing, total });
+                (visibility, status["first_seen"]["time"].as_str().map(str::to_string))
+            }
+            Err(_) => (None, None),
+        },
+        None => (None, None),
+    };
+    Ok(RouteContext { resource: resource.to_string(), prefix, origins, visibility, first_seen, paths })
+}
@@ -0,0 +1,115 @@
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use crossbeam::channel::Sender;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+// Server-first protocols (SSH, SMTP, FTP) greet well within this
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+const WORKERS: usize = 16;
+
+// Ports where the client speaks first, so waiting for a greeting only wastes time
+const HTTP_PORTS: [u16; 9] = [80, 81, 3000, 5000, 8000, 8008, 8080, 8081, 8888];
+
+#[derive(Debug, Clone)]
+pub struct BannerResult {
+    pub host: String,
+    pub port: u16,
+    /// Protocol recognised from the banner itself
+    pub service: Option<&'static str>,
+    pub banner: Result<String, String>,
+}
+
+/// Connects to each `(host, port)` with a few workers and reports the first thing each
+/// service says, probing with HTTP `HEAD` when it says nothing.
+pub fn grab_all(targets: Vec<(String, u16)>, tx: Sender<BannerResult>) {
+    let (work_tx, work_rx) = crossbeam::channel::unbounded();
+    for target in targets {
+        let _ = work_tx.send(target);
+    }
+    drop(work_tx);
+
+    for _ in 0..WORKERS {
+        let (work_rx, tx) = (work_rx.clone(), tx.clone());
+        std::thread::spawn(move || {
+            for (host, port) in work_rx.iter() {
+                let (service, banner) = match grab(&host, port) {
+                    Ok((service, banner)) => (service, Ok(banner)),
+                    Err(e) => (None, Err(e)),
+                };
+                if tx.send(BannerResult { host, port, service, banner }).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+}
+
+fn grab(host: &str, port: u16) -> Result<(Option<&'static str>, String), String> {
+    let addr = (host, port).to_socket_addrs().map_err(|e| e.to_string())?.next().ok_or("no address")?;
+    let mut stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).map_err(|e| e.to_string())?;
+    stream.set_read_timeout(Some(READ_TIMEOUT)).map_err(|e| e.to_string())?;
+    let _ = stream.set_write_timeout(Some(READ_TIMEOUT));
+
+    let mut text = if HTTP_PORTS.contains(&port) { String::new() } else { read_some(&mut stream) };
+    if text.trim().is_empty() {
+        stream.write_all(format!("HEAD / HTTP/1.0\r\nHost: {}\r\nUser-Agent: netops\r\n\r\n", host).as_bytes()).map_err(|e| e.to_string())?;
+        text = read_some(&mut stream);
+    }
+    if text.trim().is_empty() {
+        return Err("no banner".to_string());
+    }
+
+    let service = identify(&text);
+    let mut banner = match service {
+        Some("http") => {
+            let status = text.lines().next().unwrap_or("").trim();
+            let server = text.lines().find_map(|l| l.split_once(':').filter(|(k, _)| k.eq_ignore_ascii_case("server")).map(|(_, v)| v.trim()));
+            match server {
+                Some(server) => format!("{} · {}", status, server),
+                None => status.to_string(),
+            }
+        }
+        _ => text.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or("").to_string(),
+    };
+    // SMTP lists STARTTLS only in reply to EHLO
+    if service == Some("smtp") && stream.write_all(b"EHLO netops.local\r\n").is_ok() {
+        if read_some(&mut stream).to_ascii_uppercase().contains("STARTTLS") {
+            banner.push_str(" · STARTTLS");
+        }
+        let _ = stream.write_all(b"QUIT\r\n");
+    }
+    Ok((service, printable(&banner)))
+}
+
+fn read_some(stream: &mut TcpStream) -> String {
+    let mut buf = [0u8; 2048];
+    match stream.read(&mut buf) {
+        Ok(n) => String::from_utf8_lossy(&buf[..n]).into_owned(),
+        Err(_) => String::new(),
+    }
+}
+
+fn identify(text: &str) -> Option<&'static str> {
+    let upper = text.to_ascii_uppercase();
+    if text.starts_with("SSH-") {
+        Some("ssh")
+    } else if text.starts_with("HTTP/") {
+        Some("http")
+    } else if text.starts_with("220") && upper.contains("SMTP") {
+        Some("smtp")
+    } else if text.starts_with("220") && upper.contains("FTP") {
+        Some("ftp")
+    } else if text.starts_with("+OK") {
+        Some("pop3")
+    } else if text.starts_with("* OK") {
+        Some("imap")
+    } else {
+        None
+    }
+}
+
+/// Control bytes replaced and capped, so binary greetings don't wreck the table.
+fn printable(s: &str) -> String {
+    s.chars().take(120).map(|c| if c.is_control() { '.' } else { c }).collect()
+}
@@ -0,0 +1,372 @@
+use pnet::packet::arp::ArpPacket;
+use pnet::packet::ethernet::{EtherTypes, EthernetPacket};
+use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::Packet;
+use pnet::util::MacAddr;
+use std::net::IpAddr;
+
+use crate::tools::sniffer::PacketSummary;
+
+/// Filters offered in the sniffer's Ctrl+F list, before the recently used ones.
+/// `{host}` is replaced by the capturing interfaces' own addresses.
+pub const PRESETS: &[(&str, &str)] = &[
+    ("DNS only", "port 53"),
+    ("No SSH", "not port 22"),
+    ("Only this host", "{host}"),
+    ("Broadcast / multicast", "broadcast or multicast"),
+    ("Web (HTTP/HTTPS)", "tcp port 80 or tcp port 443"),
+    ("ARP only", "arp"),
+];
+
+// Words an expression can start with; any other filter is plain text
+const KEYWORDS: &[&str] = &[
+    "not", "!", "(", "tcp", "udp", "icmp", "icmp6", "arp", "ip", "ip6", "src", "dst",
+    "host", "net", "port", "portrange", "broadcast", "multicast",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Proto {
+    Tcp,
+    Udp,
+    Icmp,
+    Icmp6,
+    Arp,
+    Ip,
+    Ip6,
+}
+
+/// Which end of the packet a primitive looks at; `Either` when neither `src` nor `dst` is given.
+#[derive(Debug, Clone, Copy)]
+enum Dir {
+    Src,
+    Dst,
+    Either,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Proto(Proto),
+    Host(Dir, IpAddr),
+    Net(Dir, IpAddr, u8),
+    /// Ports `from..=to`, optionally only for TCP or UDP
+    Port(Option<Proto>, Dir, u16, u16),
+    Broadcast,
+    Multicast,
+}
+
+/// The sniffer's filter: a tcpdump-style expression (`tcp port 443 and not host 10.0.0.1`),
+/// or any other text, matched case-insensitively against the packet list's columns.
+#[derive(Debug, Clone)]
+pub struct CaptureFilter(Filter);
+
+#[derive(Debug, Clone)]
+enum Filter {
+    All,
+    Text(String),
+    Expr(Expr),
+}
+
+impl CaptureFilter {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Ok(Self(Filter::All));
+        }
+        let tokens = tokenize(input);
+        if !KEYWORDS.contains(&tokens[0].to_lowercase().as_str()) {
+            return Ok(Self(Filter::Text(input.to_lowercase())));
+        }
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        if let Some(extra) = parser.peek() {
+            return Err(format!("Filter: unexpected '{}'", extra));
+        }
+        Ok(Self(Filter::Expr(expr)))
+    }
+
+    pub fn matches(&self, ethernet: &EthernetPacket, summary: &PacketSummary) -> bool {
+        match &self.0 {
+            Filter::All => true,
+            Filter::Text(text) => {
+                summary.source.to_lowercase().contains(text)
+                    || summary.destination.to_lowercase().contains(text)
+                    || summary.protocol.to_lowercase().contains(text)
+                    || summary.info.to_lowercase().contains(text)
+            }
+            Filter::Expr(expr) => expr.matches(&Fields::of(ethernet)),
+        }
+    }
+}
+
+// `(`, `)` and `!` are tokens even without spaces around them; `&&` and `||` stand for `and` and `or`
+fn tokenize(input: &str) -> Vec<String> {
+    let spaced = input.replace("&&", " and ").replace("||", " or ").replace('(', " ( ").replace(')', " ) ").replace('!', " ! ");
+    spaced.split_whitespace().map(String::from).collect()
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.pos)?.to_lowercase();
+        self.pos += 1;
+        Some(token)
+    }
+
+    fn eat(&mut self, word: &str) -> bool {
+        let found = self.peek().is_some_and(|t| t.eq_ignore_ascii_case(word));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.eat("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while self.eat("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat("not") || self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("(") {
+            let expr = self.or()?;
+            if !self.eat(")") {
+                return Err("Filter: unclosed (".to_string());
+            }
+            return Ok(expr);
+        }
+        self.primitive()
+    }
+
+    fn primitive(&mut self) -> Result<Expr, String> {
+        let token = self.next().ok_or("Filter: expression ends too early")?;
+        let proto = match token.as_str() {
+            "tcp" => Some(Proto::Tcp),
+            "udp" => Some(Proto::Udp),
+            "icmp" => Some(Proto::Icmp),
+            "icmp6" => Some(Proto::Icmp6),
+            "arp" => Some(Proto::Arp),
+            "ip" => Some(Proto::Ip),
+            "ip6" => Some(Proto::Ip6),
+            _ => None,
+        };
+        if let Some(proto) = proto {
+            // `tcp port 80`, `udp dst port 53`
+            let qualified = matches!(proto, Proto::Tcp | Proto::Udp)
+                && matches!(self.peek().map(str::to_lowercase).as_deref(), Some("src" | "dst" | "port" | "portrange"));
+            if !qualified {
+                return Ok(Expr::Proto(proto));
+            }
+            let dir = self.dir();
+            let token = self.next().unwrap_or_default();
+            return self.port(Some(proto), dir, &token);
+        }
+
+        let (dir, token) = match token.as_str() {
+            "src" => (Dir::Src, self.next().unwrap_or_default()),
+            "dst" => (Dir::Dst, self.next().unwrap_or_default()),
+            _ => (Dir::Either, token),
+        };
+        match token.as_str() {
+            "host" => {
+                let value = self.value("host", "an IP address")?;
+                let ip = value.parse().map_err(|_| format!("Filter: host needs an IP address, not '{}'", value))?;
+                Ok(Expr::Host(dir, ip))
+            }
+            "net" => {
+                let value = self.value("net", "a network like 10.0.0.0/8")?;
+                let (ip, len) = parse_net(&value).ok_or_else(|| format!("Filter: net needs a network like 10.0.0.0/8, not '{}'", value))?;
+                Ok(Expr::Net(dir, ip, len))
+            }
+            "port" | "portrange" => self.port(None, dir, &token),
+            "broadcast" if matches!(dir, Dir::Either) => Ok(Expr::Broadcast),
+            "multicast" if matches!(dir, Dir::Either) => Ok(Expr::Multicast),
+            "" => Err("Filter: expression ends too early".to_string()),
+            other => Err(format!("Filter: unknown primitive '{}'", other)),
+        }
+    }
+
+    fn dir(&mut self) -> Dir {
+        if self.eat("src") {
+            Dir::Src
+        } else if self.eat("dst") {
+            Dir::Dst
+        } else {
+            Dir::Either
+        }
+    }
+
+    fn value(&mut self, keyword: &str, what: &str) -> Result<String, String> {
+        self.next().ok_or_else(|| format!("Filter: {} needs {}", keyword, what))
+    }
+
+    // `token` is `port` or `portrange`, already taken
+    fn port(&mut self, proto: Option<Proto>, dir: Dir, token: &str) -> Result<Expr, String> {
+        match token {
+            "port" => {
+                let value = self.value("port", "a number")?;
+                let port = value.parse().map_err(|_| format!("Filter: port needs a number, not '{}'", value))?;
+                Ok(Expr::Port(proto, dir, port, port))
+            }
+            "portrange" => {
+                let value = self.value("portrange", "a range like 8000-8080")?;
+                value.split_once('-')
+                    .and_then(|(from, to)| Some((from.parse().ok()?, to.parse().ok()?)))
+                    .filter(|(from, to)| from <= to)
+                    .map(|(from, to)| Expr::Port(proto, dir, from, to))
+                    .ok_or_else(|| format!("Filter: portrange needs a range like 8000-8080, not '{}'", value))
+            }
+            "" => Err("Filter: expression ends too early".to_string()),
+            other => Err(format!("Filter: expected port after the protocol, not '{}'", other)),
+        }
+    }
+}
+
+// `10.0.0.0/8`, or a bare address for just that host
+fn parse_net(value: &str) -> Option<(IpAddr, u8)> {
+    let (ip, len) = match value.split_once('/') {
+        Some((ip, len)) => (ip.parse::<IpAddr>().ok()?, len.parse::<u8>().ok()?),
+        None => {
+            let ip = value.parse::<IpAddr>().ok()?;
+            (ip, if ip.is_ipv4() { 32 } else { 128 })
+        }
+    };
+    let max = if ip.is_ipv4() { 32 } else { 128 };
+    (len <= max).then_some((ip, len))
+}
+
+fn in_net(ip: IpAddr, net: IpAddr, len: u8) -> bool {
+    match (ip, net) {
+        (IpAddr::V4(ip), IpAddr::V4(net)) => {
+            let mask = u32::MAX.checked_shl(32 - len as u32).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(net) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(net)) => {
+            let mask = u128::MAX.checked_shl(128 - len as u32).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(net) & mask
+        }
+        _ => false,
+    }
+}
+
+/// What an expression looks at, taken from the frame once.
+struct Fields {
+    proto: Option<Proto>,
+    ether_dst: MacAddr,
+    src: Option<IpAddr>,
+    dst: Option<IpAddr>,
+    ports: Option<(u16, u16)>,
+}
+
+impl Fields {
+    fn of(ethernet: &EthernetPacket) -> Self {
+        let mut fields = Self { proto: None, ether_dst: ethernet.get_destination(), src: None, dst: None, ports: None };
+        let payload = ethernet.payload();
+        match ethernet.get_ethertype() {
+            EtherTypes::Ipv4 => {
+                if let Some(header) = Ipv4Packet::new(payload) {
+                    fields.src = Some(IpAddr::V4(header.get_source()));
+                    fields.dst = Some(IpAddr::V4(header.get_destination()));
+                    fields.transport(header.get_next_level_protocol(), header.payload(), Proto::Ip);
+                }
+            }
+            EtherTypes::Ipv6 => {
+                if let Some(header) = Ipv6Packet::new(payload) {
+                    fields.src = Some(IpAddr::V6(header.get_source()));
+                    fields.dst = Some(IpAddr::V6(header.get_destination()));
+                    fields.transport(header.get_next_header(), header.payload(), Proto::Ip6);
+                }
+            }
+            EtherTypes::Arp => {
+                fields.proto = Some(Proto::Arp);
+                if let Some(arp) = ArpPacket::new(payload) {
+                    fields.src = Some(IpAddr::V4(arp.get_sender_proto_addr()));
+                    fields.dst = Some(IpAddr::V4(arp.get_target_proto_addr()));
+                }
+            }
+            _ => {}
+        }
+        fields
+    }
+
+    // Both TCP and UDP start with the source and destination ports
+    fn transport(&mut self, next: IpNextHeaderProtocol, payload: &[u8], ip: Proto) {
+        self.proto = Some(match next {
+            IpNextHeaderProtocols::Tcp => Proto::Tcp,
+            IpNextHeaderProtocols::Udp => Proto::Udp,
+            IpNextHeaderProtocols::Icmp => Proto::Icmp,
+            IpNextHeaderProtocols::Icmpv6 => Proto::Icmp6,
+            _ => ip,
+        });
+        if matches!(self.proto, Some(Proto::Tcp | Proto::Udp)) && payload.len() >= 4 {
+            self.ports = Some((u16::from_be_bytes([payload[0], payload[1]]), u16::from_be_bytes([payload[2], payload[3]])));
+        }
+    }
+
+    fn is_ipv4(&self) -> bool {
+        matches!(self.src, Some(IpAddr::V4(_))) && self.proto != Some(Proto::Arp)
+    }
+
+    fn is_ipv6(&self) -> bool {
+        matches!(self.src, Some(IpAddr::V6(_)))
+    }
+}
+
+fn either<T: Copy>(dir: Dir, src: Option<T>, dst: Option<T>, test: impl Fn(T) -> bool) -> bool {
+    match dir {
+        Dir::Src => src.is_some_and(&test),
+        Dir::Dst => dst.is_some_and(&test),
+        Dir::Either => src.is_some_and(&test) || dst.is_some_and(&test),
+    }
+}
+
+impl Expr {
+    fn matches(&self, p: &Fields) -> bool {
+        match self {
+            Self::And(a, b) => a.matches(p) && b.matches(p),
+            Self::Or(a, b) => a.matches(p) || b.matches(p),
+            Self::Not(a) => !a.matches(p),
+            Self::Proto(Proto::Ip) => p.is_ipv4(),
+            Self::Proto(Proto::Ip6) => p.is_ipv6(),
+            Self::Proto(proto) => p.proto == Some(*proto),
+            Self::Host(dir, ip) => either(*dir, p.src, p.dst, |a| a == *ip),
+            Self::Net(dir, net, len) => either(*dir, p.src, p.dst, |a| in_net(a, *net, *len)),
+            Self::Port(proto, dir, from, to) => {
+                proto.is_none_or(|proto| p.proto == Some(proto))
+                    && either(*dir, p.ports.map(|(s, _)| s), p.ports.map(|(_, d)| d), |port| (*from..=*to).contains(&port))
+            }
+            Self::Broadcast => {
+                p.ether_dst == MacAddr::broadcast() || p.dst == Some(IpAddr::V4(std::net::Ipv4Addr::BROADCAST))
+            }
+            Self::Multicast => {
+                // The group bit of the first octet; broadcast has it too
+                (p.ether_dst.0 & 1 == 1 && p.ether_dst != MacAddr::broadcast()) || p.dst.is_some_and(|ip| ip.is_multicast())
+            }
+        }
+    }
+}
@@ -1,14 +1,41 @@
+use std::net::IpAddr;
 use std::process::Command;
-use std::thread;
 use std::time::Duration;
 use crossbeam::channel::Sender;
 
+use super::listeners;
+use crate::shutdown;
+
 #[derive(Debug, Clone)]
 pub struct RawConnection {
     pub protocol: String,
-    pub local_addr: String,
-    pub remote_addr: String,
+    pub local_port: Option<u16>,
+    pub remote_ip: Option<IpAddr>,
+    pub remote_port: Option<u16>,
+    /// TCP state as netstat prints it (`ESTABLISHED`, `TIME_WAIT`...); empty for UDP
     pub state: String,
+    pub pid: Option<u32>,
+    pub process: Option<String>,
+}
+
+/// IP and port of a netstat address. BSD separates the port with a dot ("1.2.3.4.443",
+/// "2001:db8::1.443"), Linux with a colon ("1.2.3.4:443", "2001:db8::1:443"); `*` is no port.
+/// IPv4 peers of dual-stack sockets ("::ffff:1.2.3.4") come back as plain IPv4.
+pub fn split_addr(addr: &str) -> Option<(IpAddr, Option<u16>)> {
+    if let Some(rest) = addr.strip_prefix('[') {
+        let (ip, port) = rest.split_once("]:")?;
+        return Some((ip.split('%').next()?.parse::<IpAddr>().ok()?.to_canonical(), port.parse().ok()));
+    }
+    // Whichever separator leaves a valid IP on its left; netstat always prints a port
+    ['.', ':'].iter().find_map(|sep| {
+        let (ip, port) = addr.rsplit_once(*sep)?;
+        let port = match port {
+            "*" => None,
+            p => Some(p.parse().ok()?),
+        };
+        // Link-local IPv6 carries a zone: fe80::1%en0
+        Some((ip.split('%').next()?.parse::<IpAddr>().ok()?.to_canonical(), port))
+    })
 }
 
 pub struct ConnectionsTask {
@@ -22,32 +49,39 @@ impl ConnectionsTask {
 
     pub fn run(self) {
         loop {
-            let output = Command::new("netstat")
-                .args(&["-f", "inet", "-n"])
-                .output();
+            // TCP and UDP over IPv4 and IPv6; BSD netstat has no such filter, so other protocols are skipped below.
+            // Linux netstat cuts addresses to the column width unless --wide, which mangles most IPv6 peers
+            let args: &[&str] = if cfg!(target_os = "linux") { &["-tunW"] } else { &["-n"] };
+            let output = Command::new("netstat").args(args).output();
 
             if let Ok(output) = output {
                 let stdout = String::from_utf8_lossy(&output.stdout);
                 let mut connections = Vec::new();
+                let owners = listeners::connection_owners();
 
-                for line in stdout.lines().skip(2) { // Skip headers
+                for line in stdout.lines() {
                     let parts: Vec<&str> = line.split_whitespace().collect();
-                    if parts.len() >= 5 {
-                        let proto = parts[0].to_string();
-                        // netstat columns vary slightly by OS but usually: Proto Recv Send Local Foreign State
-                        // macos: Proto Recv-Q Send-Q  Local Address          Foreign Address        (state)
-                        
-                        let local = parts[3].to_string();
-                        let remote = parts[4].to_string();
-                        let state = if parts.len() > 5 { parts[5].to_string() } else { "UNKNOWN".to_string() };
-
-                        connections.push(RawConnection {
-                            protocol: proto,
-                            local_addr: local,
-                            remote_addr: remote,
-                            state,
-                        });
+                    // Proto Recv-Q Send-Q Local Foreign [State]; headers and unix sockets don't start with tcp/udp
+                    if parts.len() < 5 || !(parts[0].starts_with("tcp") || parts[0].starts_with("udp")) {
+                        continue;
                     }
+                    let local = split_addr(parts[3]);
+                    let remote = split_addr(parts[4]);
+                    // macOS spells FIN_WAIT_1 where Linux has FIN_WAIT1
+                    let state = parts.get(5).filter(|s| s.chars().all(|c| c.is_ascii_uppercase() || c == '_' || c.is_ascii_digit()))
+                        .map(|s| s.replace("WAIT_", "WAIT"))
+                        .unwrap_or_default();
+
+                    let owner = remote.and_then(|(ip, _)| owners.get(&ip));
+                    connections.push(RawConnection {
+                        protocol: parts[0].to_string(),
+                        local_port: local.and_then(|(_, port)| port),
+                        remote_ip: remote.map(|(ip, _)| ip),
+                        remote_port: remote.and_then(|(_, port)| port),
+                        state,
+                        pid: owner.map(|(pid, _)| *pid),
+                        process: owner.map(|(_, name)| name.clone()),
+                    });
                 }
 
                 if self.tx.send(connections).is_err() {
@@ -55,7 +89,9 @@ impl ConnectionsTask {
                 }
             }
 
-            thread::sleep(Duration::from_secs(2));
+            if !shutdown::sleep(Duration::from_secs(2)) {
+                break;
+            }
         }
     }
 }
@@ -0,0 +1,194 @@
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant};
+use crossbeam::channel::Sender;
+use socket2::{Domain, Protocol, Socket, Type};
+
+const SERVER_PORT: u16 = 67;
+const CLIENT_PORT: u16 = 68;
+const MAGIC_COOKIE: [u8; 4] = [99, 130, 83, 99];
+// Offers to one DISCOVER normally arrive within a second; slow relays get the rest
+const LISTEN_FOR: Duration = Duration::from_secs(5);
+
+const DHCPDISCOVER: u8 = 1;
+const DHCPINFORM: u8 = 8;
+
+/// One server's answer to our DISCOVER (an OFFER) or INFORM (an ACK).
+#[derive(Debug, Clone, Default)]
+pub struct DhcpReply {
+    pub from: Option<Ipv4Addr>,
+    pub server_id: Option<Ipv4Addr>,
+    pub message_type: &'static str,
+    pub offered: Option<Ipv4Addr>,
+    pub lease_secs: Option<u32>,
+    pub subnet_mask: Option<Ipv4Addr>,
+    pub routers: Vec<Ipv4Addr>,
+    pub dns: Vec<Ipv4Addr>,
+    pub domain: String,
+    pub ntp: Vec<Ipv4Addr>,
+}
+
+impl DhcpReply {
+    /// Which server sent this: the Server Identifier option, else the packet source.
+    pub fn server(&self) -> Option<Ipv4Addr> {
+        self.server_id.or(self.from)
+    }
+}
+
+/// What a client says about itself when it asks for a lease (DISCOVER, REQUEST, INFORM).
+#[derive(Debug, Clone)]
+pub struct ClientHello {
+    pub mac: [u8; 6],
+    pub hostname: Option<String>,
+    /// The address it has or is asking for
+    pub ip: Option<Ipv4Addr>,
+}
+
+#[derive(Debug, Clone)]
+pub enum DhcpEvent {
+    Reply(Box<DhcpReply>),
+    Done,
+    Error(String),
+}
+
+/// Broadcasts a DISCOVER (or, with `inform`, an INFORM from `client_ip`) out of
+/// `interface` and reports every reply for a few seconds. Never sends a REQUEST,
+/// so no lease is actually taken.
+pub fn probe(interface: String, mac: [u8; 6], client_ip: Option<Ipv4Addr>, inform: bool, tx: Sender<DhcpEvent>) {
+    std::thread::spawn(move || {
+        if let Err(e) = run_probe(&interface, mac, client_ip, inform, &tx) {
+            let _ = tx.send(DhcpEvent::Error(e));
+        }
+        let _ = tx.send(DhcpEvent::Done);
+    });
+}
+
+fn run_probe(interface: &str, mac: [u8; 6], client_ip: Option<Ipv4Addr>, inform: bool, tx: &Sender<DhcpEvent>) -> Result<(), String> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP)).map_err(|e| format!("Failed to open socket: {}", e))?;
+    let _ = socket.set_reuse_address(true);
+    socket.set_broadcast(true).map_err(|e| format!("Failed to enable broadcast: {}", e))?;
+    #[cfg(target_os = "linux")]
+    socket.bind_device(Some(interface.as_bytes())).map_err(|e| format!("Failed to bind to {}: {} (needs root)", interface, e))?;
+    #[cfg(not(target_os = "linux"))]
+    let _ = interface;
+    socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, CLIENT_PORT)).into()).map_err(|e| {
+        let hint = if e.kind() == std::io::ErrorKind::PermissionDenied { " (port 68 needs root)" } else { " (is a DHCP client holding it?)" };
+        format!("Failed to bind UDP {}: {}{}", CLIENT_PORT, e, hint)
+    })?;
+    let socket: std::net::UdpSocket = socket.into();
+    let _ = socket.set_read_timeout(Some(Duration::from_millis(250)));
+
+    let xid: u32 = rand::random();
+    let request = build_request(xid, mac, client_ip.filter(|_| inform), if inform { DHCPINFORM } else { DHCPDISCOVER });
+    socket.send_to(&request, SocketAddr::from((Ipv4Addr::BROADCAST, SERVER_PORT))).map_err(|e| format!("Failed to send: {}", e))?;
+
+    let started = Instant::now();
+    let mut buf = [0u8; 1500];
+    while started.elapsed() < LISTEN_FOR {
+        let Ok((len, from)) = socket.recv_from(&mut buf) else { continue };
+        let Some(mut reply) = parse_reply(&buf[..len], xid) else { continue };
+        reply.from = match from.ip() {
+            std::net::IpAddr::V4(ip) if !ip.is_unspecified() => Some(ip),
+            _ => None,
+        };
+        if tx.send(DhcpEvent::Reply(Box::new(reply))).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn build_request(xid: u32, mac: [u8; 6], ciaddr: Option<Ipv4Addr>, message_type: u8) -> Vec<u8> {
+    let mut p = vec![0u8; 236];
+    p[0] = 1; // BOOTREQUEST
+    p[1] = 1; // Ethernet
+    p[2] = 6;
+    p[4..8].copy_from_slice(&xid.to_be_bytes());
+    // Ask for broadcast replies; we have no address to receive unicast on yet
+    p[10] = 0x80;
+    if let Some(ip) = ciaddr {
+        p[12..16].copy_from_slice(&ip.octets());
+    }
+    p[28..34].copy_from_slice(&mac);
+    p.extend_from_slice(&MAGIC_COOKIE);
+    p.extend_from_slice(&[53, 1, message_type]);
+    // Parameter request list: subnet, router, DNS, domain, NTP, lease time, server id
+    p.extend_from_slice(&[55, 7, 1, 3, 6, 15, 42, 51, 54]);
+    p.push(255);
+    // Some servers ignore BOOTP-sized packets under 300 bytes
+    p.resize(p.len().max(300), 0);
+    p
+}
+
+fn parse_reply(data: &[u8], xid: u32) -> Option<DhcpReply> {
+    if data.len() < 240 || data[0] != 2 || data[4..8] != xid.to_be_bytes() || data[236..240] != MAGIC_COOKIE {
+        return None;
+    }
+    let ip_at = |b: &[u8]| Ipv4Addr::new(b[0], b[1], b[2], b[3]);
+    let ips = |b: &[u8]| b.chunks_exact(4).map(ip_at).collect::<Vec<_>>();
+
+    let yiaddr = ip_at(&data[16..20]);
+    let mut reply = DhcpReply {
+        offered: (!yiaddr.is_unspecified()).then_some(yiaddr),
+        ..Default::default()
+    };
+
+    for_each_option(data, |code, value| match (code, value.len()) {
+        (1, 4) => reply.subnet_mask = Some(ip_at(value)),
+        (3, _) => reply.routers = ips(value),
+        (6, _) => reply.dns = ips(value),
+        (15, _) => reply.domain = String::from_utf8_lossy(value).trim_end_matches('\0').to_string(),
+        (42, _) => reply.ntp = ips(value),
+        (51, 4) => reply.lease_secs = Some(u32::from_be_bytes([value[0], value[1], value[2], value[3]])),
+        (53, 1) => reply.message_type = message_type_name(value[0]),
+        (54, 4) => reply.server_id = Some(ip_at(value)),
+        _ => {}
+    });
+    Some(reply)
+}
+
+/// A client's request as seen on the wire (UDP payload to port 67); used by the sniffer.
+pub fn parse_client(data: &[u8]) -> Option<ClientHello> {
+    if data.len() < 240 || data[0] != 1 || data[1] != 1 || data[236..240] != MAGIC_COOKIE {
+        return None;
+    }
+    let ciaddr = Ipv4Addr::new(data[12], data[13], data[14], data[15]);
+    let mut hello = ClientHello {
+        mac: data[28..34].try_into().ok()?,
+        hostname: None,
+        ip: (!ciaddr.is_unspecified()).then_some(ciaddr),
+    };
+    for_each_option(data, |code, value| match (code, value.len()) {
+        (12, 1..) => hello.hostname = Some(String::from_utf8_lossy(value).trim_end_matches('\0').to_string()),
+        (50, 4) => hello.ip = hello.ip.or(Some(Ipv4Addr::new(value[0], value[1], value[2], value[3]))),
+        _ => {}
+    });
+    Some(hello)
+}
+
+/// Options after the magic cookie, skipping pads and stopping at the end marker.
+fn for_each_option(data: &[u8], mut f: impl FnMut(u8, &[u8])) {
+    let mut i = 240;
+    while i < data.len() {
+        let code = data[i];
+        if code == 0 {
+            i += 1;
+            continue;
+        }
+        if code == 255 || i + 1 >= data.len() {
+            break;
+        }
+        let len = data[i + 1] as usize;
+        let Some(value) = data.get(i + 2..i + 2 + len) else { break };
+        f(code, value);
+        i += 2 + len;
+    }
+}
+
+fn message_type_name(t: u8) -> &'static str {
+    match t {
+        2 => "OFFER",
+        5 => "ACK",
+        6 => "NAK",
+        _ => "OTHER",
+    }
+}
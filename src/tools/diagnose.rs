@@ -0,0 +1,240 @@
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+use crossbeam::channel::Sender;
+
+use crate::tools::{gateway, interfaces};
+use crate::tools::ping::{PingStats, PingTask};
+
+// Resolved through the OS resolver, like every other program on the machine
+const DNS_NAME: &str = "example.com";
+// Reached by address so a DNS failure doesn't look like an internet outage
+const INTERNET_HOSTS: [&str; 2] = ["1.1.1.1:443", "8.8.8.8:443"];
+const MTU_TARGET: &str = "1.1.1.1";
+// ICMP payloads for 1500, 1492 (PPPoE), 1480, 1460, 1400 and 1300 byte packets
+const MTU_PAYLOADS: [usize; 6] = [1472, 1464, 1452, 1432, 1372, 1272];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    Link,
+    Gateway,
+    Dns,
+    Internet,
+    Mtu,
+}
+
+impl Check {
+    pub const ALL: [Check; 5] = [Check::Link, Check::Gateway, Check::Dns, Check::Internet, Check::Mtu];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Check::Link => "Link",
+            Check::Gateway => "Gateway",
+            Check::Dns => "DNS",
+            Check::Internet => "Internet",
+            Check::Mtu => "MTU",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+    /// Couldn't be run, e.g. after an earlier failure or without privileges
+    Skipped,
+}
+
+impl Status {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Status::Pass => "pass",
+            Status::Warn => "warn",
+            Status::Fail => "fail",
+            Status::Skipped => "skipped",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub check: Check,
+    pub status: Status,
+    pub detail: String,
+}
+
+impl CheckResult {
+    fn new(check: Check, status: Status, detail: impl Into<String>) -> Self {
+        Self { check, status, detail: detail.into() }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum DiagnoseEvent {
+    Running(Check),
+    Done(CheckResult),
+    /// All checks ran; the plain-language verdict
+    Finished(String),
+}
+
+/// Runs every check in order. Stops early if the receiver goes away.
+pub fn start(tx: Sender<DiagnoseEvent>) {
+    tokio::spawn(async move {
+        let mut results: Vec<CheckResult> = vec![];
+        for check in Check::ALL {
+            if tx.send(DiagnoseEvent::Running(check)).is_err() {
+                return;
+            }
+            let result = run(check, &results).await;
+            results.push(result.clone());
+            if tx.send(DiagnoseEvent::Done(result)).is_err() {
+                return;
+            }
+        }
+        let _ = tx.send(DiagnoseEvent::Finished(conclusion(&results)));
+    });
+}
+
+async fn run(check: Check, earlier: &[CheckResult]) -> CheckResult {
+    let failed = |c: Check| earlier.iter().any(|r| r.check == c && r.status == Status::Fail);
+    match check {
+        Check::Link => link(),
+        _ if failed(Check::Link) => CheckResult::new(check, Status::Skipped, "no network link"),
+        Check::Gateway => gateway_check().await,
+        Check::Dns => dns().await,
+        Check::Internet => internet().await,
+        Check::Mtu if failed(Check::Internet) => CheckResult::new(check, Status::Skipped, "internet unreachable"),
+        Check::Mtu => mtu().await,
+    }
+}
+
+fn link() -> CheckResult {
+    let up: Vec<_> = interfaces::get_interfaces().into_iter().filter(|i| i.is_up() && !i.is_loopback()).collect();
+    let routable = up.iter().find_map(|i| {
+        i.ips.iter().map(|n| n.ip()).find(|ip| match ip {
+            IpAddr::V4(v4) => !v4.is_link_local(),
+            IpAddr::V6(_) => false,
+        }).map(|ip| format!("{} has {}", i.name, ip))
+    });
+    match routable {
+        Some(detail) => CheckResult::new(Check::Link, Status::Pass, detail),
+        None if up.is_empty() => CheckResult::new(Check::Link, Status::Fail, "no interface is up"),
+        // 169.254.x.x only: the link is up but DHCP never answered
+        None => CheckResult::new(Check::Link, Status::Fail, format!("{} is up but has no IPv4 address (DHCP?)", up[0].name)),
+    }
+}
+
+async fn gateway_check() -> CheckResult {
+    let ip = match tokio::task::spawn_blocking(gateway::default_gateway).await {
+        Ok(Ok(ip)) => ip,
+        Ok(Err(e)) => return CheckResult::new(Check::Gateway, Status::Fail, e),
+        Err(e) => return CheckResult::new(Check::Gateway, Status::Fail, e.to_string()),
+    };
+    let (stats, error) = ping(&format!("{} -c 3 -i 0.3", ip)).await;
+    match stats.avg_ms() {
+        Some(avg) => CheckResult::new(Check::Gateway, Status::Pass, format!("{} answers in {:.1} ms", ip, avg)),
+        // Can't tell either way without ICMP; the internet check will show whether it routes
+        None if error.as_deref().is_some_and(is_permission_error) => {
+            CheckResult::new(Check::Gateway, Status::Warn, format!("{}: can't ping without privileges", ip))
+        }
+        None => CheckResult::new(Check::Gateway, Status::Fail, format!("{} does not answer pings", ip)),
+    }
+}
+
+async fn dns() -> CheckResult {
+    let started = Instant::now();
+    let lookup = tokio::time::timeout(Duration::from_secs(5), tokio::net::lookup_host((DNS_NAME, 443))).await;
+    let ms = started.elapsed().as_secs_f64() * 1000.0;
+    match lookup {
+        Ok(Ok(mut addrs)) => match addrs.next() {
+            Some(addr) if ms > 1000.0 => CheckResult::new(Check::Dns, Status::Warn, format!("{} -> {} but took {:.0} ms", DNS_NAME, addr.ip(), ms)),
+            Some(addr) => CheckResult::new(Check::Dns, Status::Pass, format!("{} -> {} in {:.0} ms", DNS_NAME, addr.ip(), ms)),
+            None => CheckResult::new(Check::Dns, Status::Fail, format!("{} resolved to nothing", DNS_NAME)),
+        },
+        Ok(Err(e)) => CheckResult::new(Check::Dns, Status::Fail, format!("{}: {}", DNS_NAME, e)),
+        Err(_) => CheckResult::new(Check::Dns, Status::Fail, format!("{}: no answer in 5 s", DNS_NAME)),
+    }
+}
+
+/// A TCP connect needs no privileges and gets through firewalls that drop ICMP.
+async fn internet() -> CheckResult {
+    let mut errors = vec![];
+    for host in INTERNET_HOSTS {
+        let addr: SocketAddr = host.parse().expect("valid address");
+        let started = Instant::now();
+        match tokio::time::timeout(Duration::from_secs(3), tokio::net::TcpStream::connect(addr)).await {
+            Ok(Ok(_)) => {
+                let ms = started.elapsed().as_secs_f64() * 1000.0;
+                return CheckResult::new(Check::Internet, Status::Pass, format!("connected to {} in {:.0} ms", host, ms));
+            }
+            Ok(Err(e)) => errors.push(format!("{}: {}", host, e)),
+            Err(_) => errors.push(format!("{}: timed out", host)),
+        }
+    }
+    CheckResult::new(Check::Internet, Status::Fail, errors.join(", "))
+}
+
+/// Largest ICMP payload that gets an answer. Linux sets Don't Fragment on ICMP sockets (path
+/// MTU discovery), so a packet too big for some hop is dropped rather than fragmented.
+async fn mtu() -> CheckResult {
+    for payload in MTU_PAYLOADS {
+        let (stats, error) = ping(&format!("{} -c 2 -i 0.2 -s {}", MTU_TARGET, payload)).await;
+        if stats.recv > 0 {
+            let mtu = payload + 28;
+            return if payload == MTU_PAYLOADS[0] {
+                CheckResult::new(Check::Mtu, Status::Pass, format!("{} bytes get through", mtu))
+            } else {
+                CheckResult::new(Check::Mtu, Status::Warn, format!("path MTU is about {} bytes; larger packets are dropped", mtu))
+            };
+        }
+        if error.as_deref().is_some_and(is_permission_error) {
+            return CheckResult::new(Check::Mtu, Status::Skipped, "can't ping without privileges");
+        }
+    }
+    CheckResult::new(Check::Mtu, Status::Skipped, format!("{} does not answer pings; could not measure", MTU_TARGET))
+}
+
+/// Runs a counted ping to completion; the error is the last one seen, if any.
+async fn ping(args: &str) -> (PingStats, Option<String>) {
+    let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+    tokio::spawn(PingTask { target: args.to_string(), tx }.run());
+    let mut stats = PingStats::default();
+    let mut error = None;
+    while let Some(result) = rx.recv().await {
+        stats.record(&result);
+        if let Err(e) = result {
+            error = Some(e);
+        }
+    }
+    (stats, error)
+}
+
+fn is_permission_error(error: &str) -> bool {
+    let error = error.to_lowercase();
+    error.contains("permission") || error.contains("not permitted")
+}
+
+/// The first thing that broke, in the order traffic depends on it, said plainly.
+pub fn conclusion(results: &[CheckResult]) -> String {
+    let status = |check: Check| results.iter().find(|r| r.check == check).map(|r| r.status);
+    let failed = |check: Check| status(check) == Some(Status::Fail);
+
+    let verdict = if failed(Check::Link) {
+        "No network connection: plug in the cable or join a Wi-Fi network."
+    } else if failed(Check::Gateway) {
+        "The router is not answering: check the cable or Wi-Fi, or restart the router."
+    } else if failed(Check::Internet) && failed(Check::Dns) {
+        "Router fine, but nothing past it answers: the ISP or modem is down."
+    } else if failed(Check::Internet) {
+        "Router fine and names resolve, but the internet is unreachable: likely a firewall, proxy or the ISP."
+    } else if failed(Check::Dns) {
+        "DNS is failing; gateway and internet fine. Try another DNS server, e.g. 1.1.1.1."
+    } else if status(Check::Mtu) == Some(Status::Warn) {
+        "Connected, but large packets are dropped: lower the MTU (common with VPNs and PPPoE)."
+    } else if results.iter().any(|r| r.status == Status::Warn) {
+        "Connected; see the warnings above."
+    } else {
+        "Everything looks fine."
+    };
+    verdict.to_string()
+}
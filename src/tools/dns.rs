@@ -1,7 +1,14 @@
 use hickory_resolver::TokioAsyncResolver;
 use hickory_resolver::config::*;
 use hickory_resolver::proto::rr::RecordType;
-// Removed unused import: std::net::IpAddr
+use std::time::{Duration, Instant};
+
+use crate::ring::Ring;
+use crate::tools::args::{self, Args};
+
+// Resolution times and answer changes kept while watching
+const WATCH_HISTORY: usize = 120;
+const WATCH_CHANGES: usize = 50;
 
 #[derive(Debug, Clone)]
 pub struct DnsRecord {
@@ -18,6 +25,27 @@ pub enum DnsResult {
     NS(Vec<DnsRecord>),
 }
 
+impl DnsResult {
+    /// Record type label and the records, whichever the type.
+    pub fn records(&self) -> (&'static str, &[DnsRecord]) {
+        match self {
+            DnsResult::A(r) => ("A", r),
+            DnsResult::AAAA(r) => ("AAAA", r),
+            DnsResult::MX(r) => ("MX", r),
+            DnsResult::TXT(r) => ("TXT", r),
+            DnsResult::NS(r) => ("NS", r),
+        }
+    }
+
+    /// The answer set: record values sorted, TTLs aside, for telling whether answers changed.
+    pub fn values(&self) -> Vec<String> {
+        let mut values: Vec<String> = self.records().1.iter().map(|r| r.value.clone()).collect();
+        values.sort();
+        values.dedup();
+        values
+    }
+}
+
 pub async fn resolve(domain: &str, record_type: RecordType) -> Result<DnsResult, String> {
     let resolver = TokioAsyncResolver::tokio(
         ResolverConfig::default(),
@@ -59,3 +87,110 @@ pub async fn resolve(domain: &str, record_type: RecordType) -> Result<DnsResult,
         Err(e) => Err(format!("DNS Lookup failed: {}", e)),
     }
 }
+
+/// The DNS input: a domain, optionally with `-w <secs>` to re-query it that often, or
+/// `--mail` (and `--dkim <selector>`) to check its mail records instead, or `--bl` to check
+/// the address (or the host's) against the configured DNS blocklists, or `--subs` and/or
+/// `--wordlist <file>` to enumerate subdomains from certificate logs and/or guesses.
+#[derive(Debug, Clone)]
+pub struct DnsRequest {
+    pub domain: String,
+    pub watch: Option<Duration>,
+    pub mail: bool,
+    pub dkim: Option<String>,
+    pub blocklist: bool,
+    pub crt_sh: bool,
+    pub wordlist: Option<std::path::PathBuf>,
+}
+
+impl DnsRequest {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut domain = None;
+        let mut request = DnsRequest { domain: String::new(), watch: None, mail: false, dkim: None, blocklist: false, crt_sh: false, wordlist: None };
+        let mut args = Args::parse(input)?;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-w" => request.watch = Some(args.seconds("-w")?),
+                "--mail" => request.mail = true,
+                "--bl" => request.blocklist = true,
+                "--subs" => request.crt_sh = true,
+                "--wordlist" => request.wordlist = Some(args.value::<String>("--wordlist", "a file")?.into()),
+                "--dkim" => {
+                    request.mail = true;
+                    request.dkim = Some(args.value("--dkim", "a selector")?);
+                }
+                flag if flag.starts_with('-') => return Err(args::unknown(flag)),
+                _ if domain.is_some() => return Err(format!("Only one domain at a time, not '{}'", arg)),
+                _ => domain = Some(arg),
+            }
+        }
+        if [request.mail, request.watch.is_some(), request.blocklist, request.subdomains()].iter().filter(|m| **m).count() > 1 {
+            return Err("Pick one of -w, --mail, --bl and --subs/--wordlist".to_string());
+        }
+        request.domain = domain.ok_or("No domain to look up")?;
+        Ok(request)
+    }
+
+    pub fn subdomains(&self) -> bool {
+        self.crt_sh || self.wordlist.is_some()
+    }
+}
+
+/// Watch mode: one query repeated every `interval`, with how long each took and every
+/// change of the answer set (TTLs aside) since the first answer.
+#[derive(Debug)]
+pub struct DnsWatch {
+    pub domain: String,
+    pub record_type: RecordType,
+    pub interval: Duration,
+    pub next_at: Instant,
+    /// Milliseconds per query, failed ones included
+    pub times_ms: Ring<f64>,
+    /// "<time> <what changed>", newest last
+    pub changes: Ring<String>,
+    pub queries: u64,
+    pub failures: u64,
+    answer: Option<Vec<String>>,
+}
+
+impl DnsWatch {
+    pub fn new(domain: String, record_type: RecordType, interval: Duration) -> Self {
+        Self {
+            domain,
+            record_type,
+            interval,
+            next_at: Instant::now() + interval,
+            times_ms: Ring::new(WATCH_HISTORY),
+            changes: Ring::new(WATCH_CHANGES),
+            queries: 0,
+            failures: 0,
+            answer: None,
+        }
+    }
+
+    /// Takes in a query's result and schedules the next one. Returns what changed when the
+    /// answer differs from the previous one; a failed query keeps the previous answer.
+    pub fn record(&mut self, result: &Result<DnsResult, String>, elapsed: Duration) -> Option<String> {
+        self.queries += 1;
+        self.times_ms.push(elapsed.as_secs_f64() * 1000.0);
+        self.next_at = Instant::now() + self.interval;
+        let Ok(result) = result else {
+            self.failures += 1;
+            return None;
+        };
+        let answer = result.values();
+        let previous = self.answer.replace(answer.clone())?;
+        if previous == answer {
+            return None;
+        }
+        let added = answer.iter().filter(|v| !previous.contains(v)).map(|v| format!("+{}", v));
+        let removed = previous.iter().filter(|v| !answer.contains(v)).map(|v| format!("-{}", v));
+        let change = added.chain(removed).collect::<Vec<_>>().join(" ");
+        self.changes.push(format!("{} {}", crate::alerts::timestamp(), change));
+        Some(change)
+    }
+
+    pub fn due(&self) -> bool {
+        Instant::now() >= self.next_at
+    }
+}
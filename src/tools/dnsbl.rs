@@ -0,0 +1,148 @@
+use hickory_resolver::TokioAsyncResolver;
+use hickory_resolver::config::*;
+use hickory_resolver::error::ResolveErrorKind;
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Instant;
+use tokio::sync::mpsc::Sender;
+
+/// Blocklists checked when the config names none.
+pub fn default_lists() -> Vec<String> {
+    [
+        "zen.spamhaus.org",
+        "b.barracudacentral.org",
+        "bl.spamcop.net",
+        "dnsbl.sorbs.net",
+        "psbl.surriel.com",
+        "dnsbl-1.uceprotect.net",
+        "bl.mailspike.net",
+        "ix.dnsbl.manitu.net",
+    ].map(String::from).to_vec()
+}
+
+#[derive(Debug, Clone)]
+pub enum Listing {
+    Clean,
+    /// The 127.0.0.x return codes, and the list's TXT explanation if it gives one
+    Listed { codes: Vec<Ipv4Addr>, reason: Option<String> },
+    /// The list answered, but not about the address: Spamhaus' 127.255.255.x for queries
+    /// through public resolvers, or a resolver that answers every name
+    Refused(String),
+    Error(String),
+}
+
+/// One list's verdict on the address, with how long it took to answer.
+#[derive(Debug, Clone)]
+pub struct DnsblResult {
+    pub list: String,
+    pub listing: Listing,
+    pub ms: f64,
+}
+
+/// The name a blocklist is asked for: the address reversed (octets, or nibbles for IPv6)
+/// under the list's zone, e.g. `2.0.0.127.zen.spamhaus.org`.
+pub fn query_name(ip: IpAddr, list: &str) -> String {
+    let reversed = match ip {
+        IpAddr::V4(v4) => v4.octets().iter().rev().map(u8::to_string).collect::<Vec<_>>().join("."),
+        IpAddr::V6(v6) => {
+            let nibbles: Vec<String> = v6.octets().iter().flat_map(|b| [b >> 4, b & 0xf]).map(|n| format!("{:x}", n)).collect();
+            nibbles.into_iter().rev().collect::<Vec<_>>().join(".")
+        }
+    };
+    format!("{}.{}", reversed, list.trim_end_matches('.'))
+}
+
+#[derive(Debug, Clone)]
+pub enum DnsblEvent {
+    /// What `target` resolved to, sent before any verdict
+    Address(Result<IpAddr, String>),
+    Verdict(DnsblResult),
+}
+
+/// A check on the DNS screen: every list, with its verdict once it's in.
+#[derive(Debug, Clone)]
+pub struct BlocklistCheck {
+    pub target: String,
+    pub address: Option<Result<IpAddr, String>>,
+    pub results: Vec<(String, Option<DnsblResult>)>,
+}
+
+impl BlocklistCheck {
+    pub fn new(target: String, lists: &[String]) -> Self {
+        Self { target, address: None, results: lists.iter().map(|l| (l.clone(), None)).collect() }
+    }
+
+    pub fn apply(&mut self, event: DnsblEvent) {
+        match event {
+            DnsblEvent::Address(address) => self.address = Some(address),
+            DnsblEvent::Verdict(result) => {
+                if let Some((_, slot)) = self.results.iter_mut().find(|(list, _)| *list == result.list) {
+                    *slot = Some(result);
+                }
+            }
+        }
+    }
+
+    /// Every list has answered, or there was no address to ask about.
+    pub fn done(&self) -> bool {
+        matches!(self.address, Some(Err(_))) || self.results.iter().all(|(_, r)| r.is_some())
+    }
+
+    pub fn listed(&self) -> usize {
+        self.results.iter().filter(|(_, r)| matches!(r, Some(DnsblResult { listing: Listing::Listed { .. }, .. }))).count()
+    }
+}
+
+/// Resolves `target` if it's a host name (blocklists list addresses), then asks every list
+/// about the address at once, sending each verdict as it comes in.
+pub fn check(target: String, lists: Vec<String>, tx: Sender<DnsblEvent>) {
+    tokio::spawn(async move {
+        let resolver = TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default());
+        let address = address(&resolver, &target).await;
+        let _ = tx.send(DnsblEvent::Address(address.clone())).await;
+        let Ok(ip) = address else { return };
+        for list in lists {
+            let resolver = resolver.clone();
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let started = Instant::now();
+                let listing = lookup(&resolver, ip, &list).await;
+                let _ = tx.send(DnsblEvent::Verdict(DnsblResult { list, listing, ms: started.elapsed().as_secs_f64() * 1000.0 })).await;
+            });
+        }
+    });
+}
+
+async fn address(resolver: &TokioAsyncResolver, target: &str) -> Result<IpAddr, String> {
+    if let Ok(ip) = target.parse() {
+        return Ok(ip);
+    }
+    let ips = resolver.lookup_ip(target).await.map_err(|e| format!("Cannot resolve {}: {}", target, e))?;
+    // Most lists only carry IPv4; prefer it
+    let ips: Vec<IpAddr> = ips.iter().collect();
+    ips.iter().find(|ip| ip.is_ipv4()).or(ips.first()).copied().ok_or_else(|| format!("{} has no address", target))
+}
+
+async fn lookup(resolver: &TokioAsyncResolver, ip: IpAddr, list: &str) -> Listing {
+    // Fully qualified, so no search domain gets appended
+    let name = format!("{}.", query_name(ip, list));
+    let codes: Vec<Ipv4Addr> = match resolver.ipv4_lookup(name.as_str()).await {
+        Ok(lookup) => lookup.iter().map(|a| a.0).collect(),
+        Err(e) if matches!(e.kind(), ResolveErrorKind::NoRecordsFound { .. }) => return Listing::Clean,
+        Err(e) => return Listing::Error(e.to_string()),
+    };
+    if let Some(code) = codes.iter().find(|c| c.octets()[..3] == [127, 255, 255]) {
+        let why = match code.octets()[3] {
+            252 => "typo in the query",
+            254 => "queries through public resolvers are refused",
+            255 => "too many queries",
+            _ => "query refused",
+        };
+        return Listing::Refused(format!("{} ({})", why, code));
+    }
+    if let Some(code) = codes.iter().find(|c| c.octets()[0] != 127) {
+        return Listing::Refused(format!("answered {}, not a 127.x code; the resolver may answer every name", code));
+    }
+    let reason = resolver.txt_lookup(name.as_str()).await.ok()
+        .and_then(|txt| txt.iter().next().map(|t| t.txt_data().iter().map(|part| String::from_utf8_lossy(part)).collect::<String>()));
+    Listing::Listed { codes, reason }
+}
@@ -0,0 +1,417 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crossbeam::channel::Sender;
+use pnet::packet::ethernet::{EtherTypes, EthernetPacket};
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::tcp::TcpPacket;
+use pnet::packet::udp::UdpPacket;
+use pnet::packet::Packet;
+
+use crate::shutdown;
+
+pub const DEFAULT_FLOW_PORT: u16 = 2055;
+const WINDOW: Duration = Duration::from_secs(60);
+const TOP_N: usize = 15;
+
+#[derive(Debug, Clone)]
+pub struct FlowRecord {
+    pub src: IpAddr,
+    pub dst: IpAddr,
+    pub src_port: u16,
+    pub dst_port: u16,
+    pub protocol: u8,
+    pub bytes: u64,
+    pub packets: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FlowSnapshot {
+    pub top_talkers: Vec<(IpAddr, u64, u64)>,   // (ip, bytes, packets)
+    pub top_ports: Vec<(u8, u16, u64, u64)>,    // (protocol, port, bytes, flows)
+    pub flows_per_sec: f64,
+    pub bytes_per_sec: f64,
+    pub total_flows: u64,
+    pub total_datagrams: u64,
+    pub exporters: Vec<(IpAddr, &'static str)>,
+}
+
+#[derive(Debug, Clone)]
+pub enum FlowEvent {
+    Snapshot(FlowSnapshot),
+    Error(String),
+}
+
+pub struct FlowCollector {
+    pub should_stop: Arc<AtomicBool>,
+}
+
+impl FlowCollector {
+    pub fn new() -> Self {
+        Self {
+            should_stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn start(&mut self, port: u16, tx: Sender<FlowEvent>) {
+        // Not a reset of the old flag: a listener still in recv_from would carry on
+        *self = Self::new();
+        let should_stop = self.should_stop.clone();
+
+        std::thread::spawn(move || {
+            let socket = match UdpSocket::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, port))) {
+                Ok(s) => s,
+                Err(e) => {
+                    let _ = tx.send(FlowEvent::Error(format!("Failed to bind UDP {}: {}", port, e)));
+                    return;
+                }
+            };
+            let _ = socket.set_read_timeout(Some(Duration::from_millis(200)));
+
+            let mut decoder = FlowDecoder::default();
+            let mut window: VecDeque<(Instant, FlowRecord)> = VecDeque::new();
+            let mut exporters: HashMap<IpAddr, &'static str> = HashMap::new();
+            let mut total_flows = 0u64;
+            let mut total_datagrams = 0u64;
+            let mut last_snapshot = Instant::now();
+            let mut buf = [0u8; 65535];
+
+            loop {
+                if shutdown::stopping(&should_stop) {
+                    break;
+                }
+
+                if let Ok((len, from)) = socket.recv_from(&mut buf) {
+                    total_datagrams += 1;
+                    let (kind, records) = decoder.decode(from.ip(), &buf[..len]);
+                    exporters.insert(from.ip(), kind);
+                    let now = Instant::now();
+                    total_flows += records.len() as u64;
+                    window.extend(records.into_iter().map(|r| (now, r)));
+                }
+
+                if last_snapshot.elapsed() >= Duration::from_secs(1) {
+                    while window.front().is_some_and(|(t, _)| t.elapsed() > WINDOW) {
+                        window.pop_front();
+                    }
+                    let mut snapshot = summarize(&window);
+                    snapshot.total_flows = total_flows;
+                    snapshot.total_datagrams = total_datagrams;
+                    snapshot.exporters = exporters.iter().map(|(ip, k)| (*ip, *k)).collect();
+                    if tx.send(FlowEvent::Snapshot(snapshot)).is_err() {
+                        break;
+                    }
+                    last_snapshot = Instant::now();
+                }
+            }
+        });
+    }
+
+    pub fn stop(&self) {
+        self.should_stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn summarize(window: &VecDeque<(Instant, FlowRecord)>) -> FlowSnapshot {
+    let mut talkers: HashMap<IpAddr, (u64, u64)> = HashMap::new();
+    let mut ports: HashMap<(u8, u16), (u64, u64)> = HashMap::new();
+    let mut recent_flows = 0u64;
+    let mut recent_bytes = 0u64;
+
+    // Counters come straight from exporters' datagrams, so a crafted one can be near u64::MAX
+    for (t, r) in window {
+        let talker = talkers.entry(r.src).or_default();
+        talker.0 = talker.0.saturating_add(r.bytes);
+        talker.1 = talker.1.saturating_add(r.packets);
+
+        // Attribute to the lower (service) port
+        let port = if r.src_port != 0 && r.src_port < r.dst_port { r.src_port } else { r.dst_port };
+        let p = ports.entry((r.protocol, port)).or_default();
+        p.0 = p.0.saturating_add(r.bytes);
+        p.1 += 1;
+
+        if t.elapsed() <= Duration::from_secs(10) {
+            recent_flows += 1;
+            recent_bytes = recent_bytes.saturating_add(r.bytes);
+        }
+    }
+
+    let mut top_talkers: Vec<(IpAddr, u64, u64)> = talkers.into_iter().map(|(ip, (b, p))| (ip, b, p)).collect();
+    top_talkers.sort_by_key(|t| std::cmp::Reverse(t.1));
+    top_talkers.truncate(TOP_N);
+
+    let mut top_ports: Vec<(u8, u16, u64, u64)> = ports.into_iter().map(|((proto, port), (b, f))| (proto, port, b, f)).collect();
+    top_ports.sort_by_key(|p| std::cmp::Reverse(p.2));
+    top_ports.truncate(TOP_N);
+
+    FlowSnapshot {
+        top_talkers,
+        top_ports,
+        flows_per_sec: recent_flows as f64 / 10.0,
+        bytes_per_sec: recent_bytes as f64 / 10.0,
+        ..Default::default()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TemplateField {
+    id: u16,
+    len: u16,
+}
+
+/// Decodes NetFlow v5/v9, IPFIX and sFlow v5 datagrams. Keeps v9/IPFIX templates per exporter.
+#[derive(Default)]
+struct FlowDecoder {
+    templates: HashMap<(IpAddr, u32, u16), Vec<TemplateField>>,
+}
+
+impl FlowDecoder {
+    fn decode(&mut self, exporter: IpAddr, data: &[u8]) -> (&'static str, Vec<FlowRecord>) {
+        if data.len() < 4 {
+            return ("?", vec![]);
+        }
+        match (be16(data, 0), be32(data, 0)) {
+            (_, 5) => ("sFlow v5", decode_sflow(data).unwrap_or_default()),
+            (5, _) => ("NetFlow v5", decode_v5(data)),
+            (9, _) => ("NetFlow v9", self.decode_v9(exporter, data)),
+            (10, _) => ("IPFIX", self.decode_ipfix(exporter, data)),
+            _ => ("?", vec![]),
+        }
+    }
+
+    fn decode_v9(&mut self, exporter: IpAddr, data: &[u8]) -> Vec<FlowRecord> {
+        if data.len() < 20 {
+            return vec![];
+        }
+        let source_id = be32(data, 16);
+        self.decode_sets(exporter, source_id, &data[20..], 0, 1)
+    }
+
+    fn decode_ipfix(&mut self, exporter: IpAddr, data: &[u8]) -> Vec<FlowRecord> {
+        if data.len() < 16 {
+            return vec![];
+        }
+        let msg_len = (be16(data, 2) as usize).clamp(16, data.len());
+        let domain_id = be32(data, 12);
+        self.decode_sets(exporter, domain_id, &data[16..msg_len], 2, 3)
+    }
+
+    /// Walks (Flow)Sets shared by v9 and IPFIX; they only differ in template set ids and enterprise fields.
+    fn decode_sets(&mut self, exporter: IpAddr, domain: u32, mut sets: &[u8], template_id: u16, options_id: u16) -> Vec<FlowRecord> {
+        let ipfix = template_id == 2;
+        let mut records = vec![];
+
+        while sets.len() >= 4 {
+            let set_id = be16(sets, 0);
+            let set_len = be16(sets, 2) as usize;
+            if set_len < 4 || set_len > sets.len() {
+                break;
+            }
+            let body = &sets[4..set_len];
+
+            if set_id == template_id {
+                let mut off = 0;
+                while off + 4 <= body.len() {
+                    let tid = be16(body, off);
+                    let count = be16(body, off + 2) as usize;
+                    off += 4;
+                    let mut fields = Vec::with_capacity(count);
+                    for _ in 0..count {
+                        if off + 4 > body.len() {
+                            break;
+                        }
+                        let raw_id = be16(body, off);
+                        let len = be16(body, off + 2);
+                        off += 4;
+                        if ipfix && raw_id & 0x8000 != 0 {
+                            off += 4; // enterprise number
+                        }
+                        fields.push(TemplateField { id: raw_id & 0x7FFF, len });
+                    }
+                    self.templates.insert((exporter, domain, tid), fields);
+                }
+            } else if set_id == options_id {
+                // Options templates describe exporter metadata, not flows
+            } else if set_id >= 256 && let Some(fields) = self.templates.get(&(exporter, domain, set_id)) {
+                let mut off = 0;
+                while let Some((rec, used)) = decode_data_record(fields, &body[off..]) {
+                    if let Some(r) = rec {
+                        records.push(r);
+                    }
+                    off += used;
+                }
+            }
+
+            sets = &sets[set_len..];
+        }
+        records
+    }
+}
+
+fn decode_data_record(fields: &[TemplateField], data: &[u8]) -> Option<(Option<FlowRecord>, usize)> {
+    let mut off = 0;
+    let mut src = None;
+    let mut dst = None;
+    let mut rec = FlowRecord {
+        src: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        dst: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        src_port: 0,
+        dst_port: 0,
+        protocol: 0,
+        bytes: 0,
+        packets: 0,
+    };
+
+    for field in fields {
+        let mut len = field.len as usize;
+        if len == 65535 {
+            // IPFIX variable-length encoding
+            let first = *data.get(off)? as usize;
+            off += 1;
+            len = if first == 255 { let l = be16(data.get(off..off + 2)?, 0) as usize; off += 2; l } else { first };
+        }
+        let value = data.get(off..off + len)?;
+        off += len;
+
+        match field.id {
+            1 | 85 => rec.bytes = be_uint(value),
+            2 | 86 => rec.packets = be_uint(value),
+            4 => rec.protocol = be_uint(value) as u8,
+            7 => rec.src_port = be_uint(value) as u16,
+            11 => rec.dst_port = be_uint(value) as u16,
+            8 if len == 4 => src = Some(IpAddr::V4(Ipv4Addr::new(value[0], value[1], value[2], value[3]))),
+            12 if len == 4 => dst = Some(IpAddr::V4(Ipv4Addr::new(value[0], value[1], value[2], value[3]))),
+            27 if len == 16 => src = Some(IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(value).ok()?))),
+            28 if len == 16 => dst = Some(IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(value).ok()?))),
+            _ => {}
+        }
+    }
+
+    if off == 0 {
+        return None;
+    }
+    let record = match (src, dst) {
+        (Some(s), Some(d)) => {
+            rec.src = s;
+            rec.dst = d;
+            Some(rec)
+        }
+        _ => None,
+    };
+    Some((record, off))
+}
+
+fn decode_v5(data: &[u8]) -> Vec<FlowRecord> {
+    let count = be16(data, 2) as usize;
+    (0..count).filter_map(|i| {
+        let r = data.get(24 + i * 48..24 + (i + 1) * 48)?;
+        Some(FlowRecord {
+            src: IpAddr::V4(Ipv4Addr::new(r[0], r[1], r[2], r[3])),
+            dst: IpAddr::V4(Ipv4Addr::new(r[4], r[5], r[6], r[7])),
+            packets: be32(r, 16) as u64,
+            bytes: be32(r, 20) as u64,
+            src_port: be16(r, 32),
+            dst_port: be16(r, 34),
+            protocol: r[38],
+        })
+    }).collect()
+}
+
+fn decode_sflow(data: &[u8]) -> Option<Vec<FlowRecord>> {
+    let mut off = 4;
+    let addr_len = match be32(data.get(off..off + 4)?, 0) {
+        1 => 4,
+        2 => 16,
+        _ => return None,
+    };
+    off += 4 + addr_len + 12; // agent addr, sub agent id, sequence, uptime
+    let num_samples = be32(data.get(off..off + 4)?, 0);
+    off += 4;
+
+    let mut records = vec![];
+    for _ in 0..num_samples {
+        let format = be32(data.get(off..off + 4)?, 0) & 0xFFF;
+        let len = be32(data.get(off + 4..off + 8)?, 0) as usize;
+        let sample = data.get(off + 8..off + 8 + len)?;
+        off += 8 + len;
+
+        // 1 = flow sample, 3 = expanded flow sample
+        let (rate_off, records_off) = match format {
+            1 => (8, 32),
+            3 => (12, 44),
+            _ => continue,
+        };
+        let Some(rate) = sample.get(rate_off..rate_off + 4).map(|b| be32(b, 0) as u64) else { continue };
+        let Some(num_records) = sample.get(records_off - 4..records_off).map(|b| be32(b, 0)) else { continue };
+
+        let mut roff = records_off;
+        for _ in 0..num_records {
+            let Some(hdr) = sample.get(roff..roff + 8) else { break };
+            let rformat = be32(hdr, 0) & 0xFFF;
+            let rlen = be32(hdr, 4) as usize;
+            let Some(body) = sample.get(roff + 8..roff + 8 + rlen) else { break };
+            roff += 8 + rlen;
+
+            // Raw packet header record (ethernet)
+            if rformat == 1 && body.len() >= 16 && be32(body, 0) == 1 {
+                let frame_len = be32(body, 4) as u64;
+                let header_len = be32(body, 12) as usize;
+                if let Some(header) = body.get(16..16 + header_len) && let Some(mut r) = parse_sampled_header(header) {
+                    r.bytes = frame_len * rate.max(1);
+                    r.packets = rate.max(1);
+                    records.push(r);
+                }
+            }
+        }
+    }
+    Some(records)
+}
+
+fn parse_sampled_header(header: &[u8]) -> Option<FlowRecord> {
+    let eth = EthernetPacket::new(header)?;
+    let (src, dst, proto, payload) = match eth.get_ethertype() {
+        EtherTypes::Ipv4 => {
+            let ip = Ipv4Packet::new(eth.payload())?;
+            (IpAddr::V4(ip.get_source()), IpAddr::V4(ip.get_destination()), ip.get_next_level_protocol(), ip.payload().to_vec())
+        }
+        EtherTypes::Ipv6 => {
+            let ip = Ipv6Packet::new(eth.payload())?;
+            (IpAddr::V6(ip.get_source()), IpAddr::V6(ip.get_destination()), ip.get_next_header(), ip.payload().to_vec())
+        }
+        _ => return None,
+    };
+    let (src_port, dst_port) = match proto {
+        IpNextHeaderProtocols::Tcp => TcpPacket::new(&payload).map(|t| (t.get_source(), t.get_destination())).unwrap_or((0, 0)),
+        IpNextHeaderProtocols::Udp => UdpPacket::new(&payload).map(|u| (u.get_source(), u.get_destination())).unwrap_or((0, 0)),
+        _ => (0, 0),
+    };
+    Some(FlowRecord { src, dst, src_port, dst_port, protocol: proto.0, bytes: 0, packets: 0 })
+}
+
+pub fn protocol_name(proto: u8) -> String {
+    match proto {
+        1 => "ICMP".to_string(),
+        6 => "TCP".to_string(),
+        17 => "UDP".to_string(),
+        47 => "GRE".to_string(),
+        50 => "ESP".to_string(),
+        58 => "ICMPv6".to_string(),
+        n => format!("{}", n),
+    }
+}
+
+fn be16(data: &[u8], off: usize) -> u16 {
+    u16::from_be_bytes([data[off], data[off + 1]])
+}
+
+fn be32(data: &[u8], off: usize) -> u32 {
+    u32::from_be_bytes([data[off], data[off + 1], data[off + 2], data[off + 3]])
+}
+
+fn be_uint(bytes: &[u8]) -> u64 {
+    bytes.iter().take(8).fold(0u64, |acc, b| (acc << 8) | *b as u64)
+}
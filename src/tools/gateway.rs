@@ -0,0 +1,139 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, UdpSocket};
+use std::time::Duration;
+use crossbeam::channel::Sender;
+
+use crate::shutdown;
+
+pub const DEFAULT_PUBLIC_IP_SOURCE: &str = "stun.l.google.com:19302";
+// The gateway or public address rarely changes; a VPN coming up is the usual reason
+const REFRESH: Duration = Duration::from_secs(300);
+const STUN_MAGIC: u32 = 0x2112_A442;
+
+/// The default gateway and the address the internet sees us as, refreshed every few minutes.
+#[derive(Debug, Clone)]
+pub struct GatewayInfo {
+    pub gateway: Result<IpAddr, String>,
+    /// `None` with `public_ip_source = ""`
+    pub public_ip: Option<Result<IpAddr, String>>,
+}
+
+/// Looks both up now and every `REFRESH` until the receiver goes away.
+pub fn spawn(public_ip_source: String, tx: Sender<GatewayInfo>) {
+    std::thread::spawn(move || loop {
+        let source = public_ip_source.trim();
+        let info = GatewayInfo {
+            gateway: default_gateway(),
+            public_ip: (!source.is_empty()).then(|| public_ip(source)),
+        };
+        if tx.send(info).is_err() {
+            return;
+        }
+        if !shutdown::sleep(REFRESH) {
+            return;
+        }
+    });
+}
+
+pub fn default_gateway() -> Result<IpAddr, String> {
+    if cfg!(target_os = "linux") {
+        // Iface Destination Gateway Flags ...; addresses are hex in host byte order
+        let routes = std::fs::read_to_string("/proc/net/route").map_err(|e| format!("Cannot read routes: {}", e))?;
+        routes.lines().skip(1)
+            .map(|l| l.split_whitespace().collect::<Vec<_>>())
+            .filter(|f| f.len() > 3 && f[1] == "00000000")
+            .filter(|f| u16::from_str_radix(f[3], 16).is_ok_and(|flags| flags & 0x2 != 0))
+            .find_map(|f| u32::from_str_radix(f[2], 16).ok())
+            .map(|gw| IpAddr::V4(Ipv4Addr::from(gw.to_le_bytes())))
+            .ok_or_else(|| "No default route".to_string())
+    } else {
+        let output = std::process::Command::new("route").args(["-n", "get", "default"]).output()
+            .map_err(|e| format!("Failed to run route: {}", e))?;
+        String::from_utf8_lossy(&output.stdout).lines()
+            .find_map(|l| l.trim().strip_prefix("gateway:").and_then(|g| g.trim().parse().ok()))
+            .ok_or_else(|| "No default route".to_string())
+    }
+}
+
+/// `source` is an `http(s)://` URL answering with the bare address (e.g. `https://api.ipify.org`)
+/// or a STUN server as `host:port`.
+pub fn public_ip(source: &str) -> Result<IpAddr, String> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        let client = reqwest::blocking::Client::builder().timeout(Duration::from_secs(5)).build().map_err(|e| e.to_string())?;
+        let body = client.get(source).send().and_then(|r| r.error_for_status()).and_then(|r| r.text())
+            .map_err(|e| format!("{}: {}", source, e))?;
+        return body.trim().parse().map_err(|_| format!("{} did not answer with an address", source));
+    }
+    stun(source)
+}
+
+/// One RFC 5389 Binding request; the server reports the address and port it saw us from.
+fn stun(server: &str) -> Result<IpAddr, String> {
+    let socket = UdpSocket::bind("0.0.0.0:0").map_err(|e| e.to_string())?;
+    socket.set_read_timeout(Some(Duration::from_secs(3))).map_err(|e| e.to_string())?;
+    socket.connect(server).map_err(|e| format!("STUN {}: {}", server, e))?;
+
+    let transaction: [u8; 12] = rand::random();
+    let mut request = vec![0x00, 0x01, 0x00, 0x00];
+    request.extend_from_slice(&STUN_MAGIC.to_be_bytes());
+    request.extend_from_slice(&transaction);
+
+    let mut buf = [0u8; 512];
+    let mut last_err = String::new();
+    // UDP: a lost request is retried a couple of times before giving up
+    for _ in 0..3 {
+        socket.send(&request).map_err(|e| format!("STUN {}: {}", server, e))?;
+        match socket.recv(&mut buf) {
+            Ok(n) if n >= 20 && buf[8..20] == transaction => {
+                return parse_binding_response(&buf[..n]).ok_or_else(|| format!("STUN {}: no mapped address in reply", server));
+            }
+            Ok(_) => last_err = "unexpected reply".to_string(),
+            Err(e) => last_err = e.to_string(),
+        }
+    }
+    Err(format!("STUN {}: {}", server, last_err))
+}
+
+/// XOR-MAPPED-ADDRESS, or the older MAPPED-ADDRESS from pre-5389 servers.
+fn parse_binding_response(msg: &[u8]) -> Option<IpAddr> {
+    if u16::from_be_bytes([msg[0], msg[1]]) != 0x0101 {
+        return None;
+    }
+    let len = (u16::from_be_bytes([msg[2], msg[3]]) as usize).min(msg.len() - 20);
+    let attrs = &msg[20..20 + len];
+    let mut mapped = None;
+    let mut i = 0;
+    while i + 4 <= attrs.len() {
+        let kind = u16::from_be_bytes([attrs[i], attrs[i + 1]]);
+        let size = u16::from_be_bytes([attrs[i + 2], attrs[i + 3]]) as usize;
+        let Some(value) = attrs.get(i + 4..i + 4 + size) else { break };
+        match kind {
+            0x0020 => return decode_address(value, Some(&msg[4..20])),
+            0x0001 => mapped = decode_address(value, None),
+            _ => {}
+        }
+        // Attributes are padded to 4 bytes
+        i += 4 + size.div_ceil(4) * 4;
+    }
+    mapped
+}
+
+/// Family, port and address; XOR-ed with the magic cookie and transaction ID when `xor` is given.
+fn decode_address(value: &[u8], xor: Option<&[u8]>) -> Option<IpAddr> {
+    let unmask = |addr: &[u8]| -> Vec<u8> {
+        match xor {
+            Some(key) => addr.iter().zip(key).map(|(a, k)| a ^ k).collect(),
+            None => addr.to_vec(),
+        }
+    };
+    match value.get(1)? {
+        0x01 => {
+            let a: [u8; 4] = unmask(value.get(4..8)?).try_into().ok()?;
+            Some(IpAddr::V4(Ipv4Addr::from(a)))
+        }
+        0x02 => {
+            let a: [u8; 16] = unmask(value.get(4..20)?).try_into().ok()?;
+            Some(IpAddr::V6(Ipv6Addr::from(a)))
+        }
+        _ => None,
+    }
+}
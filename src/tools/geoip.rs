@@ -1,20 +1,43 @@
+use crossbeam::channel::Sender;
 use maxminddb::geoip2;
+use std::io::Read;
 use std::net::IpAddr;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::config::{self, GeoIpConfig};
+use crate::shutdown;
+
+pub const ASN_EDITION: &str = "GeoLite2-ASN";
+pub const COUNTRY_EDITION: &str = "GeoLite2-Country";
+
+// Where distro packages (geoipupdate, geoip-database) install MaxMind databases
+const SYSTEM_DIRS: [&str; 3] = ["/usr/share/GeoIP", "/var/lib/GeoIP", "/usr/local/share/GeoIP"];
 
 pub struct GeoIpReader {
-    reader: Arc<maxminddb::Reader<&'static [u8]>>,
+    reader: maxminddb::Reader<Vec<u8>>,
 }
 
 impl GeoIpReader {
-    pub fn new(db_bytes: &'static [u8]) -> Result<Self, maxminddb::MaxMindDBError> {
-        let reader = maxminddb::Reader::from_source(db_bytes)?;
-        Ok(Self {
-            reader: Arc::new(reader),
-        })
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let reader = maxminddb::Reader::open_readfile(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(Self { reader })
+    }
+
+    /// IPv4-only databases answer no IPv6 lookups.
+    pub fn has_ipv6(&self) -> bool {
+        self.reader.metadata.ip_version == 6
+    }
+
+    /// ISO 3166 country code; works with Country and City databases.
+    pub fn lookup_country(&self, ip: IpAddr) -> Option<String> {
+        let country = self.reader.lookup::<geoip2::Country>(ip.to_canonical()).ok()?;
+        country.country?.iso_code.map(|c| c.to_string())
     }
 
     pub fn lookup_info(&self, ip: IpAddr) -> Option<(u32, String, Option<(f64, f64)>)> {
+        // ::ffff:1.2.3.4 is filed under 1.2.3.4
+        let ip = ip.to_canonical();
         match self.reader.lookup::<geoip2::Asn>(ip) {
             Ok(asn) => {
                 let number = asn.autonomous_system_number;
@@ -46,6 +69,147 @@ impl GeoIpReader {
     }
 }
 
+/// A (re)loaded database, or why it could not be loaded/updated.
+pub struct GeoIpEvent {
+    pub edition: &'static str,
+    pub result: Result<GeoIpReader, String>,
+}
+
+/// Finds MaxMind databases on disk, reloads them when the file changes and,
+/// with a download URL configured, keeps them up to date.
+pub struct GeoIpManager {
+    config: GeoIpConfig,
+    editions: Vec<(&'static str, Option<PathBuf>)>,
+    tx: Sender<GeoIpEvent>,
+}
+
+impl GeoIpManager {
+    pub fn new(config: GeoIpConfig, tx: Sender<GeoIpEvent>) -> Self {
+        let editions = vec![(ASN_EDITION, config.asn_db.clone()), (COUNTRY_EDITION, config.country_db.clone())];
+        Self { config, editions, tx }
+    }
+
+    pub fn run(self) {
+        let mut loaded: Vec<Option<SystemTime>> = vec![None; self.editions.len()];
+        let mut last_update_check: Option<SystemTime> = None;
+        loop {
+            if self.config.download_url.is_some() && last_update_check.is_none_or(|t| age(t) > Duration::from_secs(3600)) {
+                last_update_check = Some(SystemTime::now());
+                for (edition, configured) in &self.editions {
+                    let Some(target) = configured.clone().or_else(|| default_path(edition)) else { continue };
+                    if let Err(e) = self.update_if_stale(edition, &target) {
+                        let result = Err(format!("GeoIP update of {} failed: {}", edition, e));
+                        if self.tx.send(GeoIpEvent { edition, result }).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+
+            for ((edition, configured), seen) in self.editions.iter().zip(loaded.iter_mut()) {
+                let Some(path) = locate(edition, configured.as_deref()) else { continue };
+                let Ok(modified) = std::fs::metadata(&path).and_then(|m| m.modified()) else { continue };
+                if *seen == Some(modified) {
+                    continue;
+                }
+                *seen = Some(modified);
+                if self.tx.send(GeoIpEvent { edition, result: GeoIpReader::open(&path) }).is_err() {
+                    return;
+                }
+            }
+            if !shutdown::sleep(Duration::from_secs(10)) {
+                return;
+            }
+        }
+    }
+
+    fn update_if_stale(&self, edition: &str, target: &Path) -> Result<(), String> {
+        let max_age = Duration::from_secs(self.config.update_days.max(1) * 86400);
+        if let Ok(modified) = std::fs::metadata(target).and_then(|m| m.modified())
+            && age(modified) < max_age
+        {
+            return Ok(());
+        }
+        let Some(url) = &self.config.download_url else { return Ok(()) };
+        let license_key = self.config.license_key.as_deref().unwrap_or_default();
+        let url = url.replace("{edition}", edition).replace("{license_key}", license_key);
+
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(120))
+            .build()
+            .map_err(|e| e.to_string())?;
+        let mut request = client.get(&url);
+        // MaxMind's current download endpoints authenticate with account ID + license key
+        if let Some(account) = &self.config.account_id {
+            request = request.basic_auth(account, Some(license_key));
+        }
+        let response = request.send().and_then(|r| r.error_for_status()).map_err(|e| e.to_string())?;
+        let body = response.bytes().map_err(|e| e.to_string())?;
+        let db = extract_mmdb(&body)?;
+        maxminddb::Reader::from_source(db.as_slice()).map_err(|e| format!("Downloaded file is not a MaxMind database: {}", e))?;
+
+        // Write next to the target and rename, so the reload never sees a partial file
+        if let Some(dir) = target.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let tmp = target.with_extension("mmdb.part");
+        std::fs::write(&tmp, &db).map_err(|e| format!("{}: {}", tmp.display(), e))?;
+        std::fs::rename(&tmp, target).map_err(|e| format!("{}: {}", target.display(), e))
+    }
+}
+
+/// "DE" -> 🇩🇪 (regional indicator symbols; terminals without flag glyphs show the two letters)
+pub fn flag(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .filter_map(|c| char::from_u32(0x1F1E6 + (c.to_ascii_uppercase() as u32 - 'A' as u32)))
+        .collect()
+}
+
+fn age(t: SystemTime) -> Duration {
+    SystemTime::now().duration_since(t).unwrap_or_default()
+}
+
+/// $XDG_DATA_HOME/netops/<edition>.mmdb, also where downloads are saved.
+fn default_path(edition: &str) -> Option<PathBuf> {
+    config::data_dir().map(|d| d.join(format!("{}.mmdb", edition)))
+}
+
+/// The configured path if set, otherwise the first existing default location.
+fn locate(edition: &str, configured: Option<&Path>) -> Option<PathBuf> {
+    if let Some(path) = configured {
+        return Some(path.to_path_buf());
+    }
+    default_path(edition)
+        .into_iter()
+        .chain(SYSTEM_DIRS.iter().map(|d| Path::new(d).join(format!("{}.mmdb", edition))))
+        .find(|p| p.exists())
+}
+
+/// Accepts a raw .mmdb, a gzipped one, or MaxMind's .tar.gz release archive.
+fn extract_mmdb(body: &[u8]) -> Result<Vec<u8>, String> {
+    if !body.starts_with(&[0x1f, 0x8b]) {
+        return Ok(body.to_vec());
+    }
+    let mut data = vec![];
+    flate2::read::GzDecoder::new(body).read_to_end(&mut data).map_err(|e| format!("Bad gzip data: {}", e))?;
+    if data.get(257..262) != Some(b"ustar".as_slice()) {
+        return Ok(data);
+    }
+
+    let mut archive = tar::Archive::new(data.as_slice());
+    for entry in archive.entries().map_err(|e| e.to_string())? {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        let is_mmdb = entry.path().map(|p| p.extension().is_some_and(|ext| ext == "mmdb")).unwrap_or(false);
+        if is_mmdb {
+            let mut db = vec![];
+            entry.read_to_end(&mut db).map_err(|e| e.to_string())?;
+            return Ok(db);
+        }
+    }
+    Err("No .mmdb file in archive".to_string())
+}
+
 fn get_location_from_org(org: &str) -> Option<(f64, f64)> {
     let lower = org.to_lowercase();
     
@@ -0,0 +1,135 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crossbeam::channel::Sender;
+use hickory_proto::op::{Message, MessageType, Query};
+use hickory_proto::rr::{Name, RData, RecordType};
+use hickory_resolver::TokioAsyncResolver;
+use hickory_resolver::config::{ResolverConfig, ResolverOpts};
+use tokio::net::UdpSocket;
+use tokio::sync::{mpsc, Semaphore};
+
+// Hosts named at once; each asks three ways in parallel
+const WORKERS: usize = 8;
+// Local hosts answer quickly or not at all
+const TIMEOUT: Duration = Duration::from_millis(1500);
+const MDNS_PORT: u16 = 5353;
+const NETBIOS_PORT: u16 = 137;
+
+/// A local host's name and where it came from: "dns", "mdns" or "netbios".
+#[derive(Debug, Clone)]
+pub struct HostName {
+    pub name: String,
+    pub source: &'static str,
+}
+
+/// Names hosts on the local network. Reverse DNS knows what the router's DHCP server was
+/// told; devices that never registered often still answer an mDNS reverse query (Apple,
+/// Linux with Avahi, printers) or a NetBIOS status query (Windows, Samba). The first of
+/// those three that answers wins, and `None` comes back when none does.
+pub struct HostNamer {
+    queue: mpsc::UnboundedSender<IpAddr>,
+}
+
+impl HostNamer {
+    pub fn start(tx: Sender<(IpAddr, Option<HostName>)>) -> Self {
+        let (queue, mut requests) = mpsc::unbounded_channel::<IpAddr>();
+        tokio::spawn(async move {
+            let resolver = TokioAsyncResolver::tokio_from_system_conf()
+                .unwrap_or_else(|_| TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default()));
+            let workers = Arc::new(Semaphore::new(WORKERS));
+            while let Some(ip) = requests.recv().await {
+                let Ok(permit) = workers.clone().acquire_owned().await else { break };
+                let (resolver, tx) = (resolver.clone(), tx.clone());
+                tokio::spawn(async move {
+                    let (dns, mdns, netbios) = tokio::join!(reverse_dns(&resolver, ip), mdns(ip), netbios(ip));
+                    let name = [(dns, "dns"), (mdns, "mdns"), (netbios, "netbios")].into_iter()
+                        .find_map(|(name, source)| name.map(|name| HostName { name, source }));
+                    let _ = tx.send((ip, name));
+                    drop(permit);
+                });
+            }
+        });
+        Self { queue }
+    }
+
+    pub fn lookup(&self, ip: IpAddr) {
+        let _ = self.queue.send(ip);
+    }
+}
+
+async fn reverse_dns(resolver: &TokioAsyncResolver, ip: IpAddr) -> Option<String> {
+    let names = tokio::time::timeout(TIMEOUT, resolver.reverse_lookup(ip)).await.ok()?.ok()?;
+    names.iter().next().map(|n| n.to_utf8().trim_end_matches('.').to_string())
+}
+
+// Sends one query to the host and waits for the first answer from it
+async fn ask(ip: IpAddr, port: u16, query: &[u8]) -> Option<Vec<u8>> {
+    let bind: SocketAddr = if ip.is_ipv4() { "0.0.0.0:0".parse().ok()? } else { "[::]:0".parse().ok()? };
+    let socket = UdpSocket::bind(bind).await.ok()?;
+    socket.send_to(query, (ip, port)).await.ok()?;
+    let mut buf = [0u8; 1500];
+    let answer = async {
+        loop {
+            let (len, from) = socket.recv_from(&mut buf).await.ok()?;
+            if from.ip() == ip {
+                return Some(buf[..len].to_vec());
+            }
+        }
+    };
+    tokio::time::timeout(TIMEOUT, answer).await.ok()?
+}
+
+// A PTR question for the host's reverse name, sent straight to its mDNS responder. Coming
+// from a port other than 5353 makes it a legacy unicast query, answered to us alone.
+async fn mdns(ip: IpAddr) -> Option<String> {
+    let mut msg = Message::new();
+    msg.set_id(rand::random()).set_message_type(MessageType::Query).set_recursion_desired(false);
+    msg.add_query(Query::query(Name::from(ip), RecordType::PTR));
+    let answer = Message::from_vec(&ask(ip, MDNS_PORT, &msg.to_vec().ok()?).await?).ok()?;
+    answer.answers().iter().find_map(|record| match record.data() {
+        Some(RData::PTR(ptr)) => Some(ptr.0.to_utf8().trim_end_matches('.').to_string()),
+        _ => None,
+    })
+}
+
+// A node status (NBSTAT) request for `*`; the answer lists the names the host has
+// registered, and its own unique workstation name is the one we want.
+async fn netbios(ip: IpAddr) -> Option<String> {
+    if !ip.is_ipv4() {
+        return None;
+    }
+    let id: u16 = rand::random();
+    let mut query = Vec::with_capacity(50);
+    query.extend(id.to_be_bytes());
+    query.extend([0, 0, 0, 1, 0, 0, 0, 0, 0, 0]);
+    // "*" padded with NULs to 16 bytes, each nibble written as a letter from 'A'
+    query.push(32);
+    for byte in std::iter::once(b'*').chain(std::iter::repeat_n(0, 15)) {
+        query.extend([b'A' + (byte >> 4), b'A' + (byte & 0x0f)]);
+    }
+    query.push(0);
+    query.extend([0x00, 0x21, 0x00, 0x01]);
+    let answer = ask(ip, NETBIOS_PORT, &query).await?;
+    parse_nbstat(&answer, id)
+}
+
+fn parse_nbstat(answer: &[u8], id: u16) -> Option<String> {
+    if answer.get(..2)? != id.to_be_bytes() || answer.get(6..8)? == [0, 0] {
+        return None;
+    }
+    // Past the header and the name echoed back, then type, class, TTL and length
+    let mut at = 12;
+    while *answer.get(at)? != 0 {
+        at += 1 + *answer.get(at)? as usize;
+    }
+    at += 1 + 10;
+    let count = *answer.get(at)? as usize;
+    (0..count).find_map(|i| {
+        let entry = answer.get(at + 1 + i * 18..at + 1 + (i + 1) * 18)?;
+        let (suffix, flags) = (entry[15], u16::from_be_bytes([entry[16], entry[17]]));
+        // Suffix 0x00 is the workstation service; the top flag bit marks group names
+        (suffix == 0 && flags & 0x8000 == 0).then(|| String::from_utf8_lossy(&entry[..15]).trim_end().to_string())
+    }).filter(|name| !name.is_empty())
+}
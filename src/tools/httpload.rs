@@ -0,0 +1,212 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crossbeam::channel::Sender;
+use reqwest::Url;
+
+use crate::histogram::Histogram;
+use crate::shutdown;
+use crate::tools::args::{self, Args};
+
+// Enough to find where a service starts to struggle without turning into a DoS tool
+const MAX_CONNECTIONS: usize = 1000;
+const MAX_DURATION: Duration = Duration::from_secs(600);
+
+/// `<url>` plus `-c <connections>`, `-d <secs>`, `-t <secs>` (per request timeout),
+/// `--no-keepalive` (a new connection for every request) and `--head`.
+#[derive(Debug, Clone)]
+pub struct LoadRequest {
+    pub url: Url,
+    pub connections: usize,
+    pub duration: Duration,
+    pub timeout: Duration,
+    pub keep_alive: bool,
+    pub head: bool,
+}
+
+impl LoadRequest {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let (mut url, mut connections, mut duration, mut timeout) = (None, 10, Duration::from_secs(10), Duration::from_secs(5));
+        let (mut keep_alive, mut head) = (true, false);
+        let mut args = Args::parse(input)?;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-c" => connections = args.positive("-c", "a number of connections")?,
+                "-d" => duration = args.seconds("-d")?,
+                "-t" => timeout = args.seconds("-t")?,
+                "--no-keepalive" => keep_alive = false,
+                "--head" => head = true,
+                flag if flag.starts_with('-') => return Err(args::unknown(flag)),
+                target => url = Some(target.to_string()),
+            }
+        }
+        let url = url.ok_or("Enter a URL to load test, e.g. https://example.com/ -c 20 -d 10")?;
+        // A bare host is taken as http://, as curl does
+        let url = if url.contains("://") { url } else { format!("http://{}", url) };
+        let url = Url::parse(&url).map_err(|e| format!("Bad URL '{}': {}", url, e))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!("Only http:// and https:// URLs can be load tested, not {}://", url.scheme()));
+        }
+        if connections > MAX_CONNECTIONS {
+            return Err(format!("-c is at most {} connections", MAX_CONNECTIONS));
+        }
+        if duration > MAX_DURATION {
+            return Err(format!("-d is at most {} seconds", MAX_DURATION.as_secs()));
+        }
+        Ok(Self { url, connections, duration, timeout, keep_alive, head })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum LoadEvent {
+    /// A finished request: how long until the whole body was read, and the status or why it failed
+    Response { latency_ms: f64, status: Result<u16, &'static str> },
+    Done,
+}
+
+/// One second of a run.
+#[derive(Debug, Clone, Default)]
+pub struct LoadSecond {
+    pub responses: u64,
+    pub errors: u64,
+    pub latency: Histogram,
+}
+
+/// Totals for a run, and the same per second for the charts.
+#[derive(Debug, Clone)]
+pub struct LoadStats {
+    pub started: Instant,
+    pub finished: Option<Instant>,
+    pub responses: u64,
+    pub latency: Histogram,
+    /// Responses by status class, 1xx to 5xx
+    pub classes: [u64; 5],
+    /// Failed requests by what went wrong
+    pub errors: BTreeMap<&'static str, u64>,
+    pub seconds: Vec<LoadSecond>,
+}
+
+impl LoadStats {
+    pub fn new() -> Self {
+        Self { started: Instant::now(), finished: None, responses: 0, latency: Histogram::default(), classes: [0; 5], errors: BTreeMap::new(), seconds: vec![] }
+    }
+
+    pub fn record(&mut self, latency_ms: f64, status: Result<u16, &'static str>) {
+        let second = self.started.elapsed().as_secs() as usize;
+        if self.seconds.len() <= second {
+            self.seconds.resize_with(second + 1, LoadSecond::default);
+        }
+        let slot = &mut self.seconds[second];
+        match status {
+            Ok(code) => {
+                self.responses += 1;
+                self.latency.record(latency_ms);
+                slot.responses += 1;
+                slot.latency.record(latency_ms);
+                if let Some(class) = self.classes.get_mut((code / 100).saturating_sub(1) as usize) {
+                    *class += 1;
+                }
+            }
+            Err(kind) => {
+                *self.errors.entry(kind).or_default() += 1;
+                slot.errors += 1;
+            }
+        }
+    }
+
+    pub fn error_count(&self) -> u64 {
+        self.errors.values().sum()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.finished.unwrap_or_else(Instant::now).duration_since(self.started)
+    }
+
+    /// Responses per second over the whole run.
+    pub fn rate(&self) -> f64 {
+        let secs = self.elapsed().as_secs_f64();
+        if secs > 0.0 { self.responses as f64 / secs } else { 0.0 }
+    }
+}
+
+pub struct LoadTask {
+    pub should_stop: Arc<AtomicBool>,
+}
+
+impl LoadTask {
+    pub fn new() -> Self {
+        Self {
+            should_stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Keeps `connections` requests in flight for the duration, like wrk: each worker sends
+    /// its next request as soon as the last one is read.
+    /// Workers of a stopped run keep their own flag, so restarting doesn't revive them.
+    pub fn start(&mut self, request: LoadRequest, tx: Sender<LoadEvent>) -> Result<(), String> {
+        *self = Self::new();
+        let should_stop = self.should_stop.clone();
+
+        let mut builder = reqwest::Client::builder()
+            .timeout(request.timeout)
+            .user_agent(concat!("netops/", env!("CARGO_PKG_VERSION")));
+        if request.keep_alive {
+            builder = builder.pool_max_idle_per_host(request.connections);
+        } else {
+            builder = builder.pool_max_idle_per_host(0);
+        }
+        let client = builder.build().map_err(|e| format!("HTTP client: {}", e))?;
+
+        let request = Arc::new(request);
+        let deadline = tokio::time::Instant::now() + request.duration;
+        tokio::spawn(async move {
+            let mut workers = tokio::task::JoinSet::new();
+            for _ in 0..request.connections {
+                let (client, request, tx, should_stop) = (client.clone(), request.clone(), tx.clone(), should_stop.clone());
+                workers.spawn(async move {
+                    while tokio::time::Instant::now() < deadline && !shutdown::stopping(&should_stop) {
+                        let sent = Instant::now();
+                        // An answer arriving after the deadline still counts; no new request starts
+                        let status = send(&client, &request).await;
+                        let latency_ms = sent.elapsed().as_secs_f64() * 1000.0;
+                        if tx.send(LoadEvent::Response { latency_ms, status }).is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+            while workers.join_next().await.is_some() {}
+            let _ = tx.send(LoadEvent::Done);
+        });
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        self.should_stop.store(true, Ordering::Relaxed);
+    }
+}
+
+async fn send(client: &reqwest::Client, request: &LoadRequest) -> Result<u16, &'static str> {
+    let builder = if request.head { client.head(request.url.clone()) } else { client.get(request.url.clone()) };
+    let builder = if request.keep_alive { builder } else { builder.header(reqwest::header::CONNECTION, "close") };
+    let response = builder.send().await.map_err(error_kind)?;
+    let status = response.status().as_u16();
+    // The body is part of the answer; wrk reads it too
+    response.bytes().await.map_err(error_kind)?;
+    Ok(status)
+}
+
+fn error_kind(e: reqwest::Error) -> &'static str {
+    if e.is_timeout() {
+        "timeout"
+    } else if e.is_connect() {
+        "connect"
+    } else if e.is_body() || e.is_decode() {
+        "read"
+    } else if e.is_redirect() {
+        "redirect"
+    } else {
+        "request"
+    }
+}
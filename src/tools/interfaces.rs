@@ -1,5 +1,125 @@
+use std::collections::HashMap;
+use std::process::Command;
+use std::time::Duration;
+
+use crossbeam::channel::Sender;
 use pnet_datalink::{self, NetworkInterface};
 
+use crate::shutdown;
+
 pub fn get_interfaces() -> Vec<NetworkInterface> {
     pnet_datalink::interfaces()
 }
+
+/// Kernel counters for one interface; cover all traffic, not just what the sniffer saw.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InterfaceCounters {
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    pub rx_dropped: u64,
+    pub tx_dropped: u64,
+}
+
+/// Link properties pnet doesn't expose.
+#[derive(Debug, Clone, Default)]
+pub struct LinkInfo {
+    pub mtu: Option<u32>,
+    pub speed_mbps: Option<u32>,
+}
+
+/// Samples every interface's counters once a second.
+pub struct InterfaceStatsTask {
+    tx: Sender<HashMap<String, InterfaceCounters>>,
+}
+
+impl InterfaceStatsTask {
+    pub fn new(tx: Sender<HashMap<String, InterfaceCounters>>) -> Self {
+        Self { tx }
+    }
+
+    pub fn run(self) {
+        loop {
+            if self.tx.send(read_counters()).is_err() {
+                break;
+            }
+            if !shutdown::sleep(Duration::from_secs(1)) {
+                break;
+            }
+        }
+    }
+}
+
+fn read_counters() -> HashMap<String, InterfaceCounters> {
+    if cfg!(target_os = "linux") {
+        get_interfaces().into_iter().filter_map(|i| Some((i.name.clone(), read_sysfs_counters(&i.name)?))).collect()
+    } else {
+        read_netstat_counters()
+    }
+}
+
+fn sysfs(name: &str, file: &str) -> Option<u64> {
+    std::fs::read_to_string(format!("/sys/class/net/{}/{}", name, file)).ok()?.trim().parse().ok()
+}
+
+fn read_sysfs_counters(name: &str) -> Option<InterfaceCounters> {
+    let stat = |counter: &str| sysfs(name, &format!("statistics/{}", counter)).unwrap_or(0);
+    sysfs(name, "statistics/rx_bytes")?;
+    Some(InterfaceCounters {
+        rx_bytes: stat("rx_bytes"),
+        tx_bytes: stat("tx_bytes"),
+        rx_packets: stat("rx_packets"),
+        tx_packets: stat("tx_packets"),
+        rx_errors: stat("rx_errors"),
+        tx_errors: stat("tx_errors"),
+        rx_dropped: stat("rx_dropped"),
+        tx_dropped: stat("tx_dropped"),
+    })
+}
+
+/// `netstat -ibnd`: Name Mtu Network Address Ipkts Ierrs Ibytes Opkts Oerrs Obytes Coll [Drop].
+/// Only the <Link#N> row of each interface carries the totals.
+fn read_netstat_counters() -> HashMap<String, InterfaceCounters> {
+    let mut counters = HashMap::new();
+    let Ok(output) = Command::new("netstat").args(["-ibnd"]).output() else { return counters };
+    for line in String::from_utf8_lossy(&output.stdout).lines().skip(1) {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 10 || !parts[2].starts_with("<Link") {
+            continue;
+        }
+        // Link rows without a MAC (loopback, tunnels) have one column fewer
+        let off = if parts[3].contains(':') { 4 } else { 3 };
+        let num = |i: usize| parts.get(off + i).and_then(|v| v.parse().ok()).unwrap_or(0);
+        counters.insert(parts[0].trim_end_matches('*').to_string(), InterfaceCounters {
+            rx_packets: num(0),
+            rx_errors: num(1),
+            rx_bytes: num(2),
+            tx_packets: num(3),
+            tx_errors: num(4),
+            tx_bytes: num(5),
+            rx_dropped: 0,
+            tx_dropped: num(7),
+        });
+    }
+    counters
+}
+
+pub fn link_info(name: &str) -> LinkInfo {
+    if cfg!(target_os = "linux") {
+        // `speed` reads -1 or fails for wireless and virtual links
+        let speed = std::fs::read_to_string(format!("/sys/class/net/{}/speed", name)).ok().and_then(|s| s.trim().parse::<i64>().ok());
+        LinkInfo {
+            mtu: sysfs(name, "mtu").map(|m| m as u32),
+            speed_mbps: speed.filter(|s| *s > 0).map(|s| s as u32),
+        }
+    } else {
+        let mtu = Command::new("ifconfig").arg(name).output().ok().and_then(|o| {
+            let text = String::from_utf8_lossy(&o.stdout).to_string();
+            text.split("mtu ").nth(1)?.split_whitespace().next()?.parse().ok()
+        });
+        LinkInfo { mtu, speed_mbps: None }
+    }
+}
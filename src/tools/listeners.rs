@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::process::Command;
+use std::time::Duration;
+use crossbeam::channel::Sender;
+
+use crate::shutdown;
+
+/// A local socket accepting connections (TCP LISTEN) or bound for datagrams (UDP).
+#[derive(Debug, Clone)]
+pub struct ListeningSocket {
+    pub protocol: String,
+    pub address: String,
+    pub port: u16,
+    pub pid: Option<u32>,
+    pub process: String,
+}
+
+pub struct ListenersTask {
+    tx: Sender<Result<Vec<ListeningSocket>, String>>,
+}
+
+impl ListenersTask {
+    pub fn new(tx: Sender<Result<Vec<ListeningSocket>, String>>) -> Self {
+        Self { tx }
+    }
+
+    pub fn run(self) {
+        loop {
+            if self.tx.send(list_listeners()).is_err() {
+                break;
+            }
+            if !shutdown::sleep(Duration::from_secs(5)) {
+                break;
+            }
+        }
+    }
+}
+
+// Process names need root for sockets owned by other users; those rows show "-"
+fn list_listeners() -> Result<Vec<ListeningSocket>, String> {
+    if cfg!(target_os = "linux") {
+        let output = Command::new("ss").args(["-H", "-tulnp"]).output().map_err(|e| format!("Failed to run ss: {}", e))?;
+        Ok(String::from_utf8_lossy(&output.stdout).lines().filter_map(parse_ss_line).collect())
+    } else {
+        let output = Command::new("lsof")
+            .args(["-nP", "-iTCP", "-sTCP:LISTEN", "-iUDP"])
+            .output()
+            .map_err(|e| format!("Failed to run lsof: {}", e))?;
+        let mut sockets: Vec<ListeningSocket> = String::from_utf8_lossy(&output.stdout).lines().skip(1).filter_map(parse_lsof_line).collect();
+        // lsof prints one row per file descriptor
+        sockets.sort_by(|a, b| (&a.protocol, a.port, &a.address).cmp(&(&b.protocol, b.port, &b.address)));
+        sockets.dedup_by(|a, b| a.protocol == b.protocol && a.port == b.port && a.address == b.address && a.pid == b.pid);
+        Ok(sockets)
+    }
+}
+
+/// `ss -H -tulnp`: Netid State Recv-Q Send-Q Local:Port Peer:Port [users:(("sshd",pid=812,fd=3))]
+fn parse_ss_line(line: &str) -> Option<ListeningSocket> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 5 {
+        return None;
+    }
+    let (address, port) = split_host_port(parts[4])?;
+    let users = parts.get(6).copied().unwrap_or_default();
+    let process = users.split('"').nth(1).unwrap_or("-").to_string();
+    let pid = users.split("pid=").nth(1).and_then(|rest| rest.split([',', ')']).next()).and_then(|p| p.parse().ok());
+
+    Some(ListeningSocket {
+        protocol: parts[0].to_uppercase(),
+        address,
+        port,
+        pid,
+        process,
+    })
+}
+
+/// `lsof -nP`: COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME [(LISTEN)]
+fn parse_lsof_line(line: &str) -> Option<ListeningSocket> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 9 {
+        return None;
+    }
+    // Skip connected UDP sockets ("local->remote")
+    if parts[8].contains("->") {
+        return None;
+    }
+    let (address, port) = split_host_port(parts[8])?;
+
+    Some(ListeningSocket {
+        protocol: parts[7].to_string(),
+        address,
+        port,
+        pid: parts[1].parse().ok(),
+        process: parts[0].to_string(),
+    })
+}
+
+/// "0.0.0.0:22", "[::]:22", "*:5353", "127.0.0.53%lo:53"
+fn split_host_port(s: &str) -> Option<(String, u16)> {
+    let (host, port) = s.rsplit_once(':')?;
+    let port = port.parse().ok()?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let host = host.split('%').next().unwrap_or(host);
+    Some((host.to_string(), port))
+}
+
+/// Owning (pid, process) of established TCP connections, keyed by remote IP.
+pub fn connection_owners() -> HashMap<IpAddr, (u32, String)> {
+    let mut owners = HashMap::new();
+    if cfg!(target_os = "linux") {
+        // ESTAB Recv-Q Send-Q Local:Port Peer:Port users:(("firefox",pid=4242,fd=87))
+        let Ok(output) = Command::new("ss").args(["-H", "-tnp"]).output() else { return owners };
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let (Some(peer), Some(users)) = (parts.get(4), parts.get(5)) else { continue };
+            let Some((ip, _)) = split_host_port(peer) else { continue };
+            let process = users.split('"').nth(1).unwrap_or("-").to_string();
+            let pid = users.split("pid=").nth(1).and_then(|rest| rest.split([',', ')']).next()).and_then(|p| p.parse().ok());
+            if let (Ok(ip), Some(pid)) = (ip.parse::<IpAddr>(), pid) {
+                owners.insert(ip.to_canonical(), (pid, process));
+            }
+        }
+    } else {
+        // COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE local->remote (ESTABLISHED)
+        let Ok(output) = Command::new("lsof").args(["-nP", "-iTCP", "-sTCP:ESTABLISHED"]).output() else { return owners };
+        for line in String::from_utf8_lossy(&output.stdout).lines().skip(1) {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let Some(name) = parts.get(8) else { continue };
+            let Some((_, remote)) = name.split_once("->") else { continue };
+            let Some((ip, _)) = split_host_port(remote) else { continue };
+            if let (Ok(ip), Ok(pid)) = (ip.parse::<IpAddr>(), parts[1].parse()) {
+                owners.insert(ip.to_canonical(), (pid, parts[0].to_string()));
+            }
+        }
+    }
+    owners
+}
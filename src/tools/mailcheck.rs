@@ -0,0 +1,337 @@
+use base64::Engine;
+use hickory_resolver::TokioAsyncResolver;
+use hickory_resolver::config::*;
+use hickory_resolver::error::ResolveErrorKind;
+use hickory_resolver::proto::op::ResponseCode;
+use std::net::IpAddr;
+
+// RFC 7208: more DNS-querying terms than this is a permerror
+const SPF_LOOKUP_LIMIT: usize = 10;
+// A 1024-bit RSA key is 162 bytes as SubjectPublicKeyInfo, 2048-bit 294
+const DKIM_RSA_MIN_BYTES: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// One verdict about one of the domain's mail records.
+#[derive(Debug, Clone)]
+pub struct Finding {
+    /// MX, SPF, DMARC or DKIM
+    pub record: &'static str,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// The records a domain's mail depends on, as found, and what's wrong with them.
+#[derive(Debug, Clone, Default)]
+pub struct MailCheck {
+    pub domain: String,
+    pub mx: Vec<String>,
+    pub spf: Vec<String>,
+    pub dmarc: Vec<String>,
+    /// Selector and record, when a selector was given
+    pub dkim: Option<(String, Vec<String>)>,
+    pub findings: Vec<Finding>,
+}
+
+impl MailCheck {
+    fn push(&mut self, record: &'static str, severity: Severity, message: impl Into<String>) {
+        self.findings.push(Finding { record, severity, message: message.into() });
+    }
+
+    pub fn worst(&self) -> Severity {
+        let errors = self.findings.iter().any(|f| f.severity == Severity::Error);
+        let warnings = self.findings.iter().any(|f| f.severity == Severity::Warning);
+        if errors { Severity::Error } else if warnings { Severity::Warning } else { Severity::Ok }
+    }
+}
+
+/// Looks up the domain's MX, SPF and DMARC records (and the DKIM key under `dkim_selector`)
+/// and checks them for the usual mistakes. Fails when the domain doesn't exist or a lookup errs.
+pub async fn check(domain: &str, dkim_selector: Option<&str>) -> Result<MailCheck, String> {
+    let resolver = TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default());
+    let domain = domain.trim_end_matches('.').to_lowercase();
+    let mut check = MailCheck { domain: domain.clone(), ..Default::default() };
+
+    // MX
+    match resolver.mx_lookup(domain.as_str()).await {
+        Ok(lookup) => {
+            let mut mx: Vec<(u16, String)> = lookup.iter().map(|mx| (mx.preference(), mx.exchange().to_utf8())).collect();
+            mx.sort();
+            check.mx = mx.iter().map(|(pref, host)| format!("{} {}", pref, host)).collect();
+            check_mx(&resolver, &mut check, &mx).await;
+        }
+        Err(e) => match e.kind() {
+            ResolveErrorKind::NoRecordsFound { response_code: ResponseCode::NXDomain, .. } => {
+                return Err(format!("{} does not exist", domain));
+            }
+            ResolveErrorKind::NoRecordsFound { .. } => {
+                check.push("MX", Severity::Error, "No MX records: senders fall back to the domain's own address, if any");
+            }
+            _ => return Err(format!("MX lookup failed: {}", e)),
+        },
+    }
+
+    // SPF lives in the domain's TXT records
+    check.spf = txt(&resolver, &domain).await?.into_iter().filter(|t| is_tagged(t, "v=spf1")).collect();
+    check_spf(&mut check);
+
+    let dmarc_name = format!("_dmarc.{}", domain);
+    check.dmarc = txt(&resolver, &dmarc_name).await?.into_iter().filter(|t| is_tagged(t, "v=DMARC1")).collect();
+    check_dmarc(&mut check);
+
+    if let Some(selector) = dkim_selector {
+        let name = format!("{}._domainkey.{}", selector, domain);
+        let records = txt(&resolver, &name).await?;
+        check_dkim(&mut check, selector, &records);
+        check.dkim = Some((selector.to_string(), records));
+    }
+    Ok(check)
+}
+
+// TXT records with their strings joined, as SPF and DMARC read them; none if the name doesn't exist
+async fn txt(resolver: &TokioAsyncResolver, name: &str) -> Result<Vec<String>, String> {
+    match resolver.txt_lookup(name).await {
+        Ok(lookup) => Ok(lookup.iter().map(|txt| {
+            txt.txt_data().iter().map(|part| String::from_utf8_lossy(part)).collect::<String>()
+        }).collect()),
+        Err(e) if matches!(e.kind(), ResolveErrorKind::NoRecordsFound { .. }) => Ok(vec![]),
+        Err(e) => Err(format!("TXT lookup for {} failed: {}", name, e)),
+    }
+}
+
+// `v=spf1` must be the whole first term; `v=spf10` is not SPF
+fn is_tagged(record: &str, version: &str) -> bool {
+    let first = record.split([' ', ';']).next().unwrap_or("").trim();
+    first.eq_ignore_ascii_case(version)
+}
+
+async fn check_mx(resolver: &TokioAsyncResolver, check: &mut MailCheck, mx: &[(u16, String)]) {
+    // RFC 7505: a lone "0 ." says the domain takes no mail
+    if let [(0, host)] = mx
+        && host == "."
+    {
+        check.push("MX", Severity::Ok, "Null MX: the domain accepts no mail");
+        return;
+    }
+    let mut fine = true;
+    for (_, host) in mx {
+        let host = host.trim_end_matches('.');
+        if host.is_empty() {
+            check.push("MX", Severity::Error, "Null MX next to other MX records");
+            fine = false;
+        } else if host.parse::<IpAddr>().is_ok() {
+            check.push("MX", Severity::Error, format!("{} is an IP address; MX must name a host", host));
+            fine = false;
+        } else if resolver.lookup_ip(host).await.map(|ips| ips.iter().next().is_none()).unwrap_or(true) {
+            check.push("MX", Severity::Error, format!("{} has no A or AAAA record", host));
+            fine = false;
+        }
+    }
+    if mx.len() == 1 && fine {
+        check.push("MX", Severity::Warning, "Only one MX host; mail queues at senders while it's down");
+    } else if fine {
+        check.push("MX", Severity::Ok, format!("{} MX host(s), all resolve", mx.len()));
+    }
+}
+
+fn check_spf(check: &mut MailCheck) {
+    let record = match check.spf.as_slice() {
+        [] => {
+            check.push("SPF", Severity::Warning, "No SPF record: receivers can't tell which servers may send for the domain");
+            return;
+        }
+        [record] => record.clone(),
+        _ => {
+            check.push("SPF", Severity::Error, "More than one SPF record; receivers treat this as a permanent error");
+            return;
+        }
+    };
+    let mut lookups = 0;
+    let mut all = None;
+    let mut redirect = false;
+    let mut valid = true;
+    for term in record.split_whitespace().skip(1) {
+        let (qualifier, mechanism) = match term.chars().next() {
+            Some(q @ ('+' | '-' | '~' | '?')) => (q, &term[1..]),
+            _ => ('+', term),
+        };
+        let (name, value) = match mechanism.split_once([':', '=', '/']) {
+            Some((name, _)) => (name.to_lowercase(), Some(&mechanism[name.len() + 1..])),
+            None => (mechanism.to_lowercase(), None),
+        };
+        match name.as_str() {
+            "all" => all = Some(qualifier),
+            "include" | "exists" if value.is_none_or(str::is_empty) => {
+                check.push("SPF", Severity::Error, format!("'{}' needs a domain", term));
+                valid = false;
+            }
+            "include" | "exists" | "a" | "mx" => lookups += 1,
+            "ptr" => {
+                lookups += 1;
+                check.push("SPF", Severity::Warning, "'ptr' is deprecated and slow; list the addresses instead");
+            }
+            "ip4" | "ip6" => {
+                let address = value.unwrap_or("");
+                let (ip, prefix) = address.split_once('/').unwrap_or((address, ""));
+                let ok = match ip.parse::<IpAddr>() {
+                    Ok(IpAddr::V4(_)) => name == "ip4" && (prefix.is_empty() || prefix.parse::<u8>().is_ok_and(|p| p <= 32)),
+                    Ok(IpAddr::V6(_)) => name == "ip6" && (prefix.is_empty() || prefix.parse::<u8>().is_ok_and(|p| p <= 128)),
+                    Err(_) => false,
+                };
+                if !ok {
+                    check.push("SPF", Severity::Error, format!("'{}' is not a valid {} address", term, name));
+                    valid = false;
+                }
+            }
+            "redirect" => {
+                lookups += 1;
+                redirect = true;
+            }
+            "exp" => {}
+            _ => {
+                check.push("SPF", Severity::Error, format!("Unknown term '{}'", term));
+                valid = false;
+            }
+        }
+    }
+    if lookups > SPF_LOOKUP_LIMIT {
+        check.push("SPF", Severity::Error, format!("{} DNS-querying terms; more than {} is a permanent error", lookups, SPF_LOOKUP_LIMIT));
+        valid = false;
+    }
+    match all {
+        Some('+') => {
+            check.push("SPF", Severity::Error, "'+all' lets any server send as the domain");
+            valid = false;
+        }
+        Some('?') => check.push("SPF", Severity::Warning, "'?all' is neutral; unlisted servers aren't rejected"),
+        None if !redirect => check.push("SPF", Severity::Warning, "No 'all' at the end; unlisted servers get a neutral result"),
+        _ => {}
+    }
+    if valid {
+        check.push("SPF", Severity::Ok, format!("Valid, {} of {} DNS lookups at the top level (includes not followed)", lookups, SPF_LOOKUP_LIMIT));
+    }
+}
+
+// `k=v; k=v` with the names lowercased; DMARC and DKIM both use this
+fn tags(record: &str) -> Vec<(String, String)> {
+    record.split(';')
+        .filter_map(|tag| tag.split_once('='))
+        .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_string()))
+        .collect()
+}
+
+fn check_dmarc(check: &mut MailCheck) {
+    let record = match check.dmarc.as_slice() {
+        [] => {
+            check.push("DMARC", Severity::Warning, format!("No DMARC record at _dmarc.{}: spoofed mail isn't reported or rejected", check.domain));
+            return;
+        }
+        [record] => record.clone(),
+        _ => {
+            check.push("DMARC", Severity::Error, "More than one DMARC record; receivers ignore them all");
+            return;
+        }
+    };
+    let tags = tags(&record);
+    let get = |name: &str| tags.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str());
+    let mut valid = true;
+    match get("p") {
+        None => {
+            check.push("DMARC", Severity::Error, "No policy (p=); the record is ignored");
+            valid = false;
+        }
+        Some(p) if p.eq_ignore_ascii_case("none") => check.push("DMARC", Severity::Warning, "p=none only monitors; spoofed mail is still delivered"),
+        Some(p) if p.eq_ignore_ascii_case("quarantine") || p.eq_ignore_ascii_case("reject") => {}
+        Some(p) => {
+            check.push("DMARC", Severity::Error, format!("Unknown policy p={}", p));
+            valid = false;
+        }
+    }
+    if let Some(sp) = get("sp")
+        && !["none", "quarantine", "reject"].iter().any(|p| sp.eq_ignore_ascii_case(p))
+    {
+        check.push("DMARC", Severity::Error, format!("Unknown subdomain policy sp={}", sp));
+        valid = false;
+    }
+    if let Some(pct) = get("pct") {
+        match pct.parse::<u8>() {
+            Ok(100) => {}
+            Ok(pct @ 0..=99) => check.push("DMARC", Severity::Warning, format!("pct={}: the policy covers only part of the mail", pct)),
+            _ => {
+                check.push("DMARC", Severity::Error, format!("pct={} is not 0-100", pct));
+                valid = false;
+            }
+        }
+    }
+    for alignment in ["adkim", "aspf"] {
+        if let Some(mode) = get(alignment)
+            && !mode.eq_ignore_ascii_case("r")
+            && !mode.eq_ignore_ascii_case("s")
+        {
+            check.push("DMARC", Severity::Error, format!("{}={} must be r or s", alignment, mode));
+            valid = false;
+        }
+    }
+    match get("rua") {
+        None => check.push("DMARC", Severity::Warning, "No rua=: you get no aggregate reports"),
+        Some(rua) => {
+            for uri in rua.split(',').map(str::trim).filter(|u| !u.to_lowercase().starts_with("mailto:")) {
+                check.push("DMARC", Severity::Error, format!("Report address '{}' is not a mailto: URI", uri));
+                valid = false;
+            }
+        }
+    }
+    if valid {
+        check.push("DMARC", Severity::Ok, format!("Valid, policy {}", get("p").unwrap_or_default()));
+    }
+}
+
+fn check_dkim(check: &mut MailCheck, selector: &str, records: &[String]) {
+    let record = match records {
+        [] => {
+            check.push("DKIM", Severity::Error, format!("No key at {}._domainkey.{}", selector, check.domain));
+            return;
+        }
+        [record] => record,
+        _ => {
+            check.push("DKIM", Severity::Error, format!("More than one record for selector '{}'", selector));
+            return;
+        }
+    };
+    let tags = tags(record);
+    let get = |name: &str| tags.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str());
+    if let Some(version) = get("v")
+        && (version != "DKIM1" || tags[0].0 != "v")
+    {
+        check.push("DKIM", Severity::Error, "v= must be DKIM1 and come first");
+        return;
+    }
+    let key_type = get("k").unwrap_or("rsa");
+    if key_type != "rsa" && key_type != "ed25519" {
+        check.push("DKIM", Severity::Error, format!("Unknown key type k={}", key_type));
+        return;
+    }
+    let Some(key) = get("p") else {
+        check.push("DKIM", Severity::Error, "No public key (p=)");
+        return;
+    };
+    if key.is_empty() {
+        check.push("DKIM", Severity::Warning, format!("Selector '{}' is revoked (empty p=)", selector));
+        return;
+    }
+    let key: String = key.chars().filter(|c| !c.is_whitespace()).collect();
+    let Ok(bytes) = base64::engine::general_purpose::STANDARD.decode(&key) else {
+        check.push("DKIM", Severity::Error, "The public key is not valid base64");
+        return;
+    };
+    if key_type == "rsa" && bytes.len() < DKIM_RSA_MIN_BYTES {
+        check.push("DKIM", Severity::Warning, "RSA key of 1024 bits or less; 2048 is the norm");
+    } else {
+        check.push("DKIM", Severity::Ok, format!("{} key published for selector '{}'", key_type.to_uppercase(), selector));
+    }
+}
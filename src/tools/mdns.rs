@@ -0,0 +1,285 @@
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crossbeam::channel::Sender;
+use hickory_proto::op::{Message, MessageType, Query};
+use hickory_proto::rr::{Name, RData, Record, RecordType};
+use socket2::{Domain, Protocol, Socket, Type};
+
+use crate::shutdown;
+
+const MDNS_ADDR: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+const MDNS_PORT: u16 = 5353;
+const META_QUERY: &str = "_services._dns-sd._udp.local.";
+const BROWSE_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone)]
+pub struct MdnsService {
+    pub fqdn: String,
+    pub instance: String,
+    pub service_type: String,
+    pub host: String,
+    pub ips: Vec<IpAddr>,
+    pub port: u16,
+    pub txt: Vec<String>,
+    pub last_seen: Instant,
+}
+
+#[derive(Debug, Clone)]
+pub enum MdnsEvent {
+    Update(MdnsService),
+    Remove(String),
+    Error(String),
+}
+
+pub struct MdnsTask {
+    pub should_stop: Arc<AtomicBool>,
+}
+
+impl MdnsTask {
+    pub fn new() -> Self {
+        Self {
+            should_stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Browses in a new thread. Each run gets its own stop flag: clearing a shared one
+    /// could revive a previous run that hasn't noticed its stop yet.
+    pub fn start(&mut self, tx: Sender<MdnsEvent>) {
+        *self = Self::new();
+        let should_stop = self.should_stop.clone();
+
+        std::thread::spawn(move || {
+            let socket = match open_socket() {
+                Ok(s) => s,
+                Err(e) => {
+                    let _ = tx.send(MdnsEvent::Error(format!("Failed to open mDNS socket: {}", e)));
+                    return;
+                }
+            };
+
+            let meta = Name::from_ascii(META_QUERY).expect("valid meta query name");
+            let mut browser = Browser::default();
+            let mut last_browse: Option<Instant> = None;
+            let mut buf = [0u8; 9000];
+
+            loop {
+                if shutdown::stopping(&should_stop) {
+                    break;
+                }
+
+                // Periodically re-browse the meta query and every known service type
+                if last_browse.is_none_or(|t| t.elapsed() >= BROWSE_INTERVAL) {
+                    let mut questions = vec![(meta.clone(), RecordType::PTR)];
+                    questions.extend(browser.types.iter().map(|t| (t.clone(), RecordType::PTR)));
+                    send_query(&socket, &questions);
+                    last_browse = Some(Instant::now());
+                }
+
+                match socket.recv_from(&mut buf) {
+                    Ok((len, _)) => {
+                        let Ok(msg) = Message::from_vec(&buf[..len]) else { continue };
+                        if msg.message_type() != MessageType::Response {
+                            continue;
+                        }
+
+                        let records: Vec<&Record> = msg.answers().iter().chain(msg.additionals().iter()).collect();
+                        let (events, follow_up) = browser.ingest(&meta, &records);
+                        if !follow_up.is_empty() {
+                            send_query(&socket, &follow_up);
+                        }
+                        for ev in events {
+                            if tx.send(ev).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(_) => {
+                        // Read timeout, loop around to check stop flag
+                    }
+                }
+            }
+        });
+    }
+
+    pub fn stop(&self) {
+        self.should_stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[derive(Default)]
+struct Browser {
+    types: HashSet<Name>,
+    instances: HashMap<Name, MdnsService>,
+    hosts: HashMap<Name, Vec<IpAddr>>,
+}
+
+impl Browser {
+    /// Folds a response into the browse state, returning changed services and follow-up questions.
+    fn ingest(&mut self, meta: &Name, records: &[&Record]) -> (Vec<MdnsEvent>, Vec<(Name, RecordType)>) {
+        let mut dirty: HashSet<Name> = HashSet::new();
+        let mut events = vec![];
+        let mut follow_up = vec![];
+
+        for record in records {
+            let name = record.name();
+            match record.data() {
+                Some(RData::PTR(ptr)) if name == meta => {
+                    let is_new = self.types.insert(ptr.0.clone());
+                    if is_new {
+                        follow_up.push((ptr.0.clone(), RecordType::PTR));
+                    }
+                }
+                Some(RData::PTR(ptr)) => {
+                    let instance = ptr.0.clone();
+                    if record.ttl() == 0 {
+                        // Goodbye packet
+                        if self.instances.remove(&instance).is_some() {
+                            events.push(MdnsEvent::Remove(instance.to_utf8()));
+                        }
+                        continue;
+                    }
+                    self.types.insert(name.clone());
+                    if !self.instances.contains_key(&instance) {
+                        self.instances.insert(instance.clone(), MdnsService {
+                            fqdn: instance.to_utf8(),
+                            instance: first_label(&instance),
+                            service_type: service_type_label(name),
+                            host: String::new(),
+                            ips: vec![],
+                            port: 0,
+                            txt: vec![],
+                            last_seen: Instant::now(),
+                        });
+                        follow_up.push((instance.clone(), RecordType::SRV));
+                        follow_up.push((instance.clone(), RecordType::TXT));
+                    }
+                    dirty.insert(instance);
+                }
+                Some(RData::SRV(srv)) => {
+                    if let Some(svc) = self.instances.get_mut(name) {
+                        svc.host = srv.target().to_utf8().trim_end_matches('.').to_string();
+                        svc.port = srv.port();
+                        if !self.hosts.contains_key(srv.target()) {
+                            follow_up.push((srv.target().clone(), RecordType::A));
+                        }
+                        dirty.insert(name.clone());
+                    }
+                }
+                Some(RData::TXT(txt)) => {
+                    if let Some(svc) = self.instances.get_mut(name) {
+                        svc.txt = txt.iter()
+                            .map(|b| String::from_utf8_lossy(b).to_string())
+                            .filter(|s| !s.is_empty())
+                            .collect();
+                        dirty.insert(name.clone());
+                    }
+                }
+                Some(RData::A(a)) => {
+                    self.add_host_ip(name, IpAddr::V4(a.0));
+                }
+                Some(RData::AAAA(aaaa)) => {
+                    self.add_host_ip(name, IpAddr::V6(aaaa.0));
+                }
+                _ => {}
+            }
+        }
+
+        // Resolve host addresses for any service whose host we now know
+        for (instance, svc) in self.instances.iter_mut() {
+            if svc.host.is_empty() {
+                continue;
+            }
+            let host_ips = self.hosts.iter()
+                .find(|(h, _)| h.to_utf8().trim_end_matches('.').eq_ignore_ascii_case(&svc.host))
+                .map(|(_, ips)| ips.clone());
+            if let Some(ips) = host_ips && ips != svc.ips {
+                svc.ips = ips;
+                dirty.insert(instance.clone());
+            }
+        }
+
+        for instance in dirty {
+            if let Some(svc) = self.instances.get_mut(&instance) {
+                svc.last_seen = Instant::now();
+                events.push(MdnsEvent::Update(svc.clone()));
+            }
+        }
+
+        (events, follow_up)
+    }
+
+    fn add_host_ip(&mut self, host: &Name, ip: IpAddr) {
+        let ips = self.hosts.entry(host.clone()).or_default();
+        if !ips.contains(&ip) {
+            // Prefer IPv4 first for display
+            ips.push(ip);
+            ips.sort_by_key(|ip| ip.is_ipv6());
+        }
+    }
+}
+
+fn open_socket() -> std::io::Result<UdpSocket> {
+    // Try to join the multicast group on 5353 so unsolicited announcements are seen too.
+    // If another responder owns the port, fall back to an ephemeral port (legacy unicast replies).
+    let shared = || -> std::io::Result<UdpSocket> {
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+        socket.set_reuse_address(true)?;
+        socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, MDNS_PORT)).into())?;
+        socket.join_multicast_v4(&MDNS_ADDR, &Ipv4Addr::UNSPECIFIED)?;
+        Ok(socket.into())
+    };
+
+    let socket = match shared() {
+        Ok(s) => s,
+        Err(_) => UdpSocket::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)))?,
+    };
+    socket.set_read_timeout(Some(Duration::from_millis(250)))?;
+    Ok(socket)
+}
+
+fn send_query(socket: &UdpSocket, questions: &[(Name, RecordType)]) {
+    let mut msg = Message::new();
+    msg.set_id(0).set_message_type(MessageType::Query).set_recursion_desired(false);
+    for (name, rtype) in questions {
+        msg.add_query(Query::query(name.clone(), *rtype));
+    }
+    if let Ok(bytes) = msg.to_vec() {
+        let _ = socket.send_to(&bytes, SocketAddr::from((MDNS_ADDR, MDNS_PORT)));
+    }
+}
+
+fn first_label(name: &Name) -> String {
+    name.iter().next().map(|l| String::from_utf8_lossy(l).to_string()).unwrap_or_default()
+}
+
+fn service_type_label(service_type: &Name) -> String {
+    // "_airplay._tcp.local." -> "_airplay._tcp"
+    let labels: Vec<String> = service_type.iter().take(2).map(|l| String::from_utf8_lossy(l).to_string()).collect();
+    labels.join(".")
+}
+
+pub fn friendly_service_name(service_type: &str) -> &'static str {
+    match service_type {
+        "_airplay._tcp" => "AirPlay",
+        "_raop._tcp" => "AirPlay Audio",
+        "_googlecast._tcp" => "Chromecast",
+        "_ipp._tcp" | "_ipps._tcp" | "_printer._tcp" | "_pdl-datastream._tcp" => "Printer",
+        "_scanner._tcp" | "_uscan._tcp" => "Scanner",
+        "_ssh._tcp" => "SSH",
+        "_sftp-ssh._tcp" => "SFTP",
+        "_hap._tcp" | "_homekit._tcp" => "HomeKit",
+        "_matter._tcp" | "_matterc._udp" => "Matter",
+        "_smb._tcp" => "SMB",
+        "_afpovertcp._tcp" => "AFP",
+        "_http._tcp" | "_https._tcp" => "Web",
+        "_spotify-connect._tcp" => "Spotify",
+        "_companion-link._tcp" => "Apple Device",
+        "_device-info._tcp" => "Device Info",
+        "_workstation._tcp" => "Workstation",
+        "_sleep-proxy._udp" => "Sleep Proxy",
+        _ => "",
+    }
+}
@@ -1,12 +1,42 @@
+pub mod args;
+pub mod capture_filter;
 pub mod interfaces;
 pub mod ping;
 pub mod dns;
+pub mod dnsbl;
+pub mod mailcheck;
 pub mod sniffer;
 pub mod mtr;
 pub mod nmap;
 pub mod arpscan;
 pub mod geoip;
 pub mod connections;
+pub mod wol;
+pub mod mdns;
+pub mod ssdp;
+pub mod flows;
+pub mod whois;
+pub mod listeners;
+pub mod terminate;
+pub mod process;
+pub mod wifi;
+pub mod dhcp;
+pub mod ntp;
+pub mod traceroute;
+pub mod sweep;
+pub mod subdomains;
+pub mod banner;
+pub mod syslog;
+pub mod gateway;
+pub mod diagnose;
+pub mod neighbors;
+pub mod oui;
+pub mod rdns;
+pub mod hostnames;
 
 
 
+pub mod asn;
+pub mod reputation;
+pub mod pcap;
+pub mod httpload;
@@ -1,4 +1,5 @@
-use std::collections::VecDeque;
+use std::collections::BTreeMap;
+use std::mem::MaybeUninit;
 use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
 use std::time::{Duration, Instant};
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -6,6 +7,13 @@ use std::sync::Arc;
 use crossbeam::channel::Sender;
 use socket2::{Domain, Protocol, Socket, Type};
 
+use super::args::{self, Args};
+use super::ping::set_tos;
+use super::traceroute::{paris_echo_request, parse_icmp};
+use crate::histogram::Histogram;
+use crate::ring::Ring;
+use crate::shutdown;
+
 #[derive(Debug, Clone)]
 pub struct MtrResult {
     pub ttl: u8,
@@ -13,6 +21,22 @@ pub struct MtrResult {
     pub rtt: Duration,
     pub successful: bool,
     pub is_target: bool,
+    /// Which flow id the probe used; 0 unless `--paths` spreads probes over several
+    pub flow: u8,
+}
+
+// `--paths` at most: the flow id takes 4 bits of the sequence number
+pub const MAX_FLOWS: u8 = 16;
+
+#[derive(Debug, Clone)]
+pub enum MtrEvent {
+    Hop(MtrResult),
+    /// The run is over: the target didn't resolve or no probe could be sent
+    Error(String),
+    /// Every reply of a cycle has been sent
+    CycleDone,
+    /// All `-c` cycles are done
+    Finished { cycles: u64 },
 }
 
 #[derive(Debug, Clone)]
@@ -26,8 +50,179 @@ pub struct HopStats {
     pub worst: u64,
     pub avg: u64,
     pub loss: f64,
-    pub history: VecDeque<u64>,
+    pub history: Ring<u64>,
     pub jitter: u64,
+    pub rtt: Histogram,
+    /// Times the router answering at this TTL changed from one cycle to the next
+    pub changes: u64,
+}
+
+/// A route the run has seen: the router answering at each TTL (None where none ever did).
+#[derive(Debug, Clone)]
+pub struct ObservedPath {
+    pub hops: Vec<Option<IpAddr>>,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub cycles: u64,
+}
+
+/// The run moved to another path: which one (an index into `PathTracker::paths`) and the
+/// TTLs whose router changed.
+#[derive(Debug, Clone)]
+pub struct PathChange {
+    pub time: String,
+    pub cycle: u64,
+    pub path: usize,
+    pub ttls: Vec<u8>,
+}
+
+/// Follows the route cycle by cycle. A TTL that didn't answer keeps its last router, so loss
+/// alone isn't a change; a different router is, whether the route flapped or ECMP hashed the
+/// probe onto another link. Going back to a path seen before (as ECMP does) reuses its entry.
+#[derive(Debug, Clone, Default)]
+pub struct PathTracker {
+    pub paths: Vec<ObservedPath>,
+    pub changes: Vec<PathChange>,
+    current: Option<usize>,
+    cycle: Vec<Option<IpAddr>>,
+    cycles: u64,
+}
+
+impl PathTracker {
+    pub fn record(&mut self, result: &MtrResult) {
+        let idx = result.ttl.saturating_sub(1) as usize;
+        if self.cycle.len() <= idx {
+            self.cycle.resize(idx + 1, None);
+        }
+        if result.successful {
+            self.cycle[idx] = result.host;
+        }
+        // Probes past the target just echo it
+        if result.is_target && result.successful {
+            self.cycle.truncate(idx + 1);
+        }
+    }
+
+    /// Closes the cycle; returns the TTLs that changed, if the path did.
+    pub fn end_cycle(&mut self, time: String) -> Option<&PathChange> {
+        let mut hops = std::mem::take(&mut self.cycle);
+        self.cycles += 1;
+        let previous = self.current.map(|i| &self.paths[i].hops);
+        if let Some(previous) = previous {
+            for (i, hop) in hops.iter_mut().enumerate() {
+                if hop.is_none() {
+                    *hop = previous.get(i).copied().flatten();
+                }
+            }
+        }
+        let ttls: Vec<u8> = match previous {
+            Some(previous) => (0..hops.len().max(previous.len()))
+                .filter(|&i| matches!((hops.get(i).copied().flatten(), previous.get(i).copied().flatten()), (Some(a), Some(b)) if a != b))
+                .map(|i| i as u8 + 1)
+                .collect(),
+            None => vec![],
+        };
+        if let Some(current) = self.current
+            && ttls.is_empty()
+        {
+            // Same route; fill in routers that answered for the first time
+            let path = &mut self.paths[current];
+            if hops.len() > path.hops.len() {
+                path.hops.resize(hops.len(), None);
+            }
+            for (slot, hop) in path.hops.iter_mut().zip(hops) {
+                *slot = slot.or(hop);
+            }
+            path.last_seen = time;
+            path.cycles += 1;
+            return None;
+        }
+
+        let index = match self.paths.iter().position(|p| same_route(&p.hops, &hops)) {
+            Some(i) => i,
+            None => {
+                self.paths.push(ObservedPath { hops, first_seen: time.clone(), last_seen: time.clone(), cycles: 0 });
+                self.paths.len() - 1
+            }
+        };
+        let path = &mut self.paths[index];
+        path.last_seen = time.clone();
+        path.cycles += 1;
+        self.current = Some(index);
+        self.changes.push(PathChange { time, cycle: self.cycles, path: index, ttls });
+        self.changes.last()
+    }
+}
+
+/// The router answering each TTL for every flow id of a `--paths` run.
+#[derive(Debug, Clone, Default)]
+pub struct FlowPaths {
+    pub flows: Vec<Vec<Option<IpAddr>>>,
+}
+
+/// One line of the ECMP tree: a router, drawn after `prefix` (the branches above it), and
+/// how many flows went through it.
+#[derive(Debug, Clone)]
+pub struct FlowTreeLine {
+    pub ttl: u8,
+    pub prefix: String,
+    pub host: Option<IpAddr>,
+    pub flows: usize,
+}
+
+impl FlowPaths {
+    pub fn record(&mut self, result: &MtrResult) {
+        let flow = result.flow as usize;
+        if self.flows.len() <= flow {
+            self.flows.resize(flow + 1, vec![]);
+        }
+        let hops = &mut self.flows[flow];
+        let idx = result.ttl.saturating_sub(1) as usize;
+        if hops.len() <= idx {
+            hops.resize(idx + 1, None);
+        }
+        if result.successful {
+            hops[idx] = result.host;
+            if result.is_target {
+                hops.truncate(idx + 1);
+            }
+        }
+    }
+
+    /// The flows' paths merged from the first hop: a line per router, branching where the
+    /// flows split up. Paths that join again further on are drawn once per branch.
+    pub fn tree(&self) -> Vec<FlowTreeLine> {
+        let mut lines = vec![];
+        self.walk((0..self.flows.len()).collect(), 0, String::new(), &mut lines);
+        lines
+    }
+
+    fn walk(&self, flows: Vec<usize>, at: usize, prefix: String, lines: &mut Vec<FlowTreeLine>) {
+        let mut groups: BTreeMap<Option<IpAddr>, Vec<usize>> = BTreeMap::new();
+        for flow in flows {
+            if let Some(hop) = self.flows[flow].get(at) {
+                groups.entry(*hop).or_default().push(flow);
+            }
+        }
+        // Straight runs stay at the same indent; only a split draws branches
+        let branching = groups.len() > 1;
+        let count = groups.len();
+        for (i, (host, members)) in groups.into_iter().enumerate() {
+            let last = i + 1 == count;
+            let (head, below) = match (branching, last) {
+                (false, _) => (prefix.clone(), prefix.clone()),
+                (true, false) => (format!("{}├ ", prefix), format!("{}│ ", prefix)),
+                (true, true) => (format!("{}└ ", prefix), format!("{}  ", prefix)),
+            };
+            lines.push(FlowTreeLine { ttl: at as u8 + 1, prefix: head, host, flows: members.len() });
+            self.walk(members, at + 1, below, lines);
+        }
+    }
+}
+
+// Routers that both answered agree; unanswered TTLs match anything
+fn same_route(a: &[Option<IpAddr>], b: &[Option<IpAddr>]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x.is_none() || y.is_none() || x == y)
 }
 
 pub struct MtrTask {
@@ -41,107 +236,90 @@ impl MtrTask {
         }
     }
 
-    pub fn start(&self, target_str: String, tx: Sender<MtrResult>) {
+    /// Traces in a new thread until stopped. Each run has its own stop flag: a previous run
+    /// still sleeping out its interval wakes to find it set and exits instead of probing on.
+    pub fn start(&mut self, target_str: String, tx: Sender<MtrEvent>) {
+        *self = Self::new();
         let should_stop = self.should_stop.clone();
-        should_stop.store(false, Ordering::Relaxed);
-        
+
         std::thread::spawn(move || {
-             // Parse args
-            let args: Vec<&str> = target_str.split_whitespace().collect();
-            let mut host_str = "";
-            let mut interval_ms = 1000;
-            let mut max_hops = 30;
-            let mut count: Option<u64> = None;
-            
-            let mut i = 0;
-            while i < args.len() {
-                match args[i] {
-                    "-i" => {
-                        if i + 1 < args.len() {
-                             if let Ok(v) = args[i+1].parse::<f64>() {
-                                 interval_ms = (v * 1000.0) as u64;
-                             }
-                             i += 2;
-                        } else { i += 1; }
-                    }
-                    "-m" => {
-                        if i + 1 < args.len() {
-                             if let Ok(v) = args[i+1].parse::<u8>() {
-                                 max_hops = v;
-                             }
-                             i += 2;
-                        } else { i += 1; }
-                    }
-                    "-c" => {
-                        if i + 1 < args.len() {
-                             if let Ok(v) = args[i+1].parse::<u64>() {
-                                 count = Some(v);
-                             }
-                             i += 2;
-                        } else { i += 1; }
-                    }
-                    arg => {
-                        if !arg.starts_with("-") {
-                            host_str = arg;
-                        }
-                        i += 1;
-                    }
+            let MtrArgs { host: host_str, interval_ms, first_ttl, max_hops, count, flows, tos } = match parse_args(&target_str) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    let _ = tx.send(MtrEvent::Error(e));
+                    return;
                 }
-            }
-            
-            if host_str.is_empty() { return; }
+            };
+            let host_str = host_str.as_str();
 
             // Resolve
             let target_ip: IpAddr = match format!("{}:0", host_str).to_socket_addrs() {
                 Ok(mut addrs) => {
                     if let Some(a) = addrs.next() {
                          a.ip()
-                    } else { return; }
+                    } else {
+                        let _ = tx.send(MtrEvent::Error(format!("{} has no addresses", host_str)));
+                        return;
+                    }
                 }
-                Err(_) => {
+                Err(e) => {
                     // Try parsing as IP directly if resolution failed or it was just an IP
                     if let Ok(ip) = host_str.parse() {
                         ip
                     } else {
+                        let _ = tx.send(MtrEvent::Error(format!("Cannot resolve {}: {}", host_str, e)));
                         return;
                     }
                 }
             };
+            tracing::debug!("Tracing {} ({})", host_str, target_ip);
             
             // let sock_addr = SocketAddr::new(target_ip, 0);
 
-            let mut cycles_done = 0;
+            let mut session = match ProbeSession::open(target_ip, first_ttl, tos) {
+                Ok(session) => session,
+                Err(e) => {
+                    let _ = tx.send(MtrEvent::Error(format!("Cannot probe {}: {}", target_ip, e)));
+                    return;
+                }
+            };
+
+            let mut cycles_done: u64 = 0;
             loop {
-                if should_stop.load(Ordering::Relaxed) {
+                if shutdown::stopping(&should_stop) {
                     break;
                 }
                 
-                if let Some(c) = count {
-                    if cycles_done >= c {
-                        break;
-                    }
+                if let Some(c) = count
+                    && cycles_done >= c
+                {
+                    let _ = tx.send(MtrEvent::Finished { cycles: c });
+                    break;
                 }
-                cycles_done += 1;
 
-                // Run one pass of traceroute (TTL 1..max_hops)
-                for ttl in 1..=max_hops {
-                    if should_stop.load(Ordering::Relaxed) {
-                        break;
-                    }
-
-                    let res = probe(target_ip, ttl);
-                    if let Ok(r) = res {
-                        let is_target = r.host == Some(target_ip);
-                        let _ = tx.send(r.clone());
-                        if is_target {
+                // Every TTL of the cycle at once, then wait for the answers
+                let started = Instant::now();
+                match session.cycle(cycles_done as u8, max_hops, flows, &should_stop) {
+                    Ok(results) => {
+                        // Nobody listening any more (the screen moved on to a new trace)
+                        if results.into_iter().any(|r| tx.send(MtrEvent::Hop(r)).is_err()) || tx.send(MtrEvent::CycleDone).is_err() {
                             break;
                         }
                     }
-                    std::thread::sleep(Duration::from_millis(100)); // Pace packets slightly
+                    // Failing from the very first cycle is a permissions or routing problem that
+                    // won't go away; later failures (a link flapping) just cost that cycle
+                    Err(e) if cycles_done == 0 => {
+                        let _ = tx.send(MtrEvent::Error(format!("Cannot probe {}: {}", target_ip, e)));
+                        return;
+                    }
+                    Err(_) => {}
                 }
+                cycles_done += 1;
                 
-                // Wait before next cycle
-                std::thread::sleep(Duration::from_millis(interval_ms));
+                // Wait before next cycle; the loop's check then catches a stop that came meanwhile
+                if !shutdown::sleep(Duration::from_millis(interval_ms).saturating_sub(started.elapsed())) {
+                    break;
+                }
             }
         });
     }
@@ -151,103 +329,162 @@ impl MtrTask {
     }
 }
 
-fn probe(target: IpAddr, ttl: u8) -> std::io::Result<MtrResult> {
-    // This is a very simplified raw socket implementation.
-    // In Rust, for ICMP, we need a raw socket.
-    
-    // NOTE: This uses functionality that requires sudo.
-    // We will use surge-ping approach or pnet? 
-    // Socket2 gives us raw socket control.
-    
-    let domain = match target {
-        IpAddr::V4(_) => Domain::IPV4,
-        IpAddr::V6(_) => Domain::IPV6,
-    };
-    
-    // Create raw socket for ICMP
-    let proto = match target {
-         IpAddr::V4(_) => Protocol::ICMPV4,
-         IpAddr::V6(_) => Protocol::ICMPV6,
-    };
-    
-    let socket = Socket::new(domain, Type::RAW, Some(proto))?;
-
-    // #[cfg(unix)]
-    // socket.set_ttl(ttl as u32)?;
-    // On Windows it might be different, but socket2 handles some differences. 
-    // Wait, error said `set_ttl` not found. 
-    // socket2 0.6 has `set_ttl` for specific protocol layers?
-    // Let's check if it exists or use `set_ip_ttl`?
-    // The error suggestion was `set_ttl_v4`.
-    
-    // Logic for socket2 v0.5+:
-    if target.is_ipv4() {
-        socket.set_ttl_v4(ttl as u32)?;
-    } else {
-        socket.set_unicast_hops_v6(ttl as u32)?;
+/// An MTR input, parsed.
+struct MtrArgs {
+    host: String,
+    interval_ms: u64,
+    first_ttl: u8,
+    max_hops: u8,
+    count: Option<u64>,
+    flows: u8,
+    tos: Option<u8>,
+}
+
+fn parse_args(input: &str) -> Result<MtrArgs, String> {
+    let mut host = None;
+    let mut interval = Duration::from_secs(1);
+    let mut first_ttl = 1;
+    let mut max_hops = 30;
+    let mut count = None;
+    let mut flows = 1;
+    let mut tos = None;
+
+    let mut args = Args::parse(input)?;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-i" => interval = args.seconds("-i")?,
+            "-m" => max_hops = args.positive("-m", "a hop count")?,
+            "-f" => first_ttl = args.positive("-f", "a TTL (1-255)")?,
+            "-Q" => tos = Some(args.tos("-Q")?),
+            "-c" => count = Some(args.positive("-c", "a number of cycles")?),
+            "--paths" => {
+                flows = args.positive("--paths", "a number of flows")?;
+                if flows > MAX_FLOWS {
+                    return Err(format!("--paths takes at most {} flows", MAX_FLOWS));
+                }
+            }
+            flag if flag.starts_with('-') => return Err(args::unknown(flag)),
+            _ => host = Some(arg),
+        }
     }
-    socket.set_read_timeout(Some(Duration::from_secs(1)))?;
-    
-    // Construct ICMP Echo Request
-    let mut packet = vec![0u8; 64]; 
-    if target.is_ipv4() {
-        packet[0] = 8; // Echo Request
-        packet[1] = 0; // Code
-    } else {
-        packet[0] = 128; // Echo Request V6
-        packet[1] = 0;
+    let host = host.ok_or_else(|| format!("No target given in '{}'", input))?;
+    if first_ttl > max_hops {
+        return Err(format!("-f {} is past the last hop probed (-m {})", first_ttl, max_hops));
     }
-    
-    // Basic checksum (needed for IPv4 ICMP)
-    if target.is_ipv4() {
-        let checksum_bytes = internet_checksum::checksum(&packet);
-        // checksum returns [u8; 2] aka byte array
-        packet[2] = checksum_bytes[0];
-        packet[3] = checksum_bytes[1];
+    Ok(MtrArgs { host, interval_ms: interval.as_millis() as u64, first_ttl, max_hops, count, flows, tos })
+}
+
+// How long a cycle waits for its replies
+const REPLY_WAIT: Duration = Duration::from_secs(1);
+
+/// One ICMP socket for a whole MTR run. Each cycle sends an echo request per TTL and flow back
+/// to back; the sequence number (cycle << 12 | flow << 8 | ttl) says which probe a reply
+/// answers, so late replies from an earlier cycle are ignored. Every probe of a flow hashes
+/// the same on ECMP routers (see `paris_echo_request`), so a flow keeps to one path.
+struct ProbeSession {
+    socket: Socket,
+    raw: bool,
+    target: IpAddr,
+    id: u16,
+    /// `-f`: hops before it aren't probed
+    first_ttl: u8,
+    /// TTL the target answered at last cycle; later cycles stop probing just past it
+    target_ttl: Option<u8>,
+}
+
+impl ProbeSession {
+    fn open(target: IpAddr, first_ttl: u8, tos: Option<u8>) -> std::io::Result<Self> {
+        let (domain, proto) = match target {
+            IpAddr::V4(_) => (Domain::IPV4, Protocol::ICMPV4),
+            IpAddr::V6(_) => (Domain::IPV6, Protocol::ICMPV6),
+        };
+        // Unprivileged ICMP sockets work too, but on Linux the kernel only hands them the
+        // final echo reply, so intermediate hops time out
+        let (socket, raw) = match Socket::new(domain, Type::RAW, Some(proto)) {
+            Ok(s) => (s, true),
+            Err(_) => (Socket::new(domain, Type::DGRAM, Some(proto))?, false),
+        };
+        socket.set_read_timeout(Some(Duration::from_millis(50)))?;
+        if let Some(tos) = tos {
+            set_tos(&socket, target, tos)?;
+        }
+        Ok(Self { socket, raw, target, id: std::process::id() as u16 ^ 0x4d54, first_ttl, target_ttl: None })
     }
-    // Note: Kernel handles ICMPv6 checksum usually?
-
-    let start = Instant::now();
-    let sock_addr = SocketAddr::new(target, 0);
-    
-    socket.send_to(&packet, &sock_addr.into())?;
-    
-    // Listen for reply
-    // let mut buf = [0u8; 1024]; // SAFETY: buf is initialized
-    // let mut buf = unsafe { std::mem::MaybeUninit::new(buf).assume_init() }; 
-    // Actually Socket2 recv_from takes MaybeUninit? No, &mut [MaybeUninit<u8>].
-    // Let's us regular buffer for simpler API if possible, or use `recv_from` with initialized buf.
-    // socket2 0.4 vs 0.5 diffs. latest socket2 `recv_from` takes `&mut [MaybeUninit<u8>]`.
-
-    let mut recv_buf = [std::mem::MaybeUninit::new(0u8); 1024];
-
-    match socket.recv_from(&mut recv_buf) {
-        Ok((_size, addr)) => {
-            let rtt = start.elapsed();
-            let addr = addr.as_socket().map(|s| s.ip());
-            
-            // Check packet type to see if TimeExceeded or EchoReply
-            // But for MTR, getting *any* response from an IP usually means that's the hop.
-            // (Unless it's some stray packet).
-            // We assume if we receive something, it's related.
-            
-            Ok(MtrResult {
-                ttl,
-                host: addr,
-                rtt,
-                successful: true,
-                is_target: addr == Some(target),
-            })
-        }
-        Err(_) => {
-            // Timeout or error
-             Ok(MtrResult {
-                ttl,
-                host: None,
-                rtt: Duration::MAX,
-                successful: false,
-                is_target: false,
-            })
+
+    fn cycle(&mut self, tag: u8, max_hops: u8, flows: u8, should_stop: &AtomicBool) -> std::io::Result<Vec<MtrResult>> {
+        let tag = tag & 0x0f;
+        let first = self.first_ttl;
+        let last_ttl = self.target_ttl.map_or(max_hops, |t| (t + 1).min(max_hops));
+        let per_flow = (last_ttl - first) as usize + 1;
+        let dest: socket2::SockAddr = SocketAddr::new(self.target, 0).into();
+        let mut sent: Vec<Option<Instant>> = vec![None; per_flow * flows as usize];
+        for ttl in first..=last_ttl {
+            match self.target {
+                IpAddr::V4(_) => self.socket.set_ttl_v4(ttl as u32),
+                IpAddr::V6(_) => self.socket.set_unicast_hops_v6(ttl as u32),
+            }?;
+            for flow in 0..flows {
+                let seq = (tag as u16) << 12 | (flow as u16) << 8 | ttl as u16;
+                self.socket.send_to(&paris_echo_request(self.target, self.id, seq, flow as u16), &dest)?;
+                sent[flow as usize * per_flow + (ttl - first) as usize] = Some(Instant::now());
+            }
+        }
+
+        let mut results: Vec<MtrResult> = (0..flows).flat_map(|flow| (first..=last_ttl).map(move |ttl| MtrResult {
+            ttl,
+            host: None,
+            rtt: Duration::MAX,
+            successful: false,
+            is_target: false,
+            flow,
+        })).collect();
+        // Per flow: ECMP paths can differ in length
+        let mut target_ttl: Vec<Option<u8>> = vec![None; flows as usize];
+        let mut buf = [MaybeUninit::new(0u8); 1500];
+        let deadline = Instant::now() + REPLY_WAIT;
+        while Instant::now() < deadline && !shutdown::stopping(should_stop) {
+            let Ok((len, from)) = self.socket.recv_from(&mut buf) else { continue };
+            let received = Instant::now();
+            let data: Vec<u8> = buf[..len].iter().map(|b| unsafe { b.assume_init() }).collect();
+            let Some(from) = from.as_socket().map(|s| s.ip()) else { continue };
+            // Raw IPv4 sockets deliver the IP header; everything else starts at ICMP
+            let icmp = if self.raw && self.target.is_ipv4() { data.get(((data.first().copied().unwrap_or(0) & 0x0f) as usize * 4)..) } else { Some(&data[..]) };
+            let Some((reply_id, seq, is_echo_reply)) = icmp.and_then(|icmp| parse_icmp(icmp, self.target.is_ipv4())) else { continue };
+            // The kernel picks the id for ping sockets
+            if (self.raw && reply_id != self.id) || (seq >> 12) as u8 != tag {
+                continue;
+            }
+            let (flow, ttl) = ((seq >> 8) as u8 & 0x0f, seq as u8);
+            if flow >= flows || ttl < first || ttl > last_ttl {
+                continue;
+            }
+            let i = flow as usize * per_flow + (ttl - first) as usize;
+            let (Some(Some(sent_at)), Some(result)) = (sent.get(i), results.get_mut(i)) else { continue };
+            result.host = Some(from);
+            result.rtt = received - *sent_at;
+            result.successful = true;
+            if is_echo_reply || from == self.target {
+                result.is_target = true;
+                let t = &mut target_ttl[flow as usize];
+                *t = Some(t.map_or(ttl, |t| t.min(ttl)));
+            }
+            // Done once every flow's probes up to the target have answered
+            let answered = |f: usize| target_ttl[f].is_some_and(|t| results[f * per_flow..f * per_flow + (t - first) as usize + 1].iter().all(|r| r.successful));
+            if (0..flows as usize).all(answered) {
+                break;
+            }
+        }
+
+        let mut kept = Vec::with_capacity(results.len());
+        for (flow, hops) in results.chunks(per_flow).enumerate() {
+            let keep = match target_ttl[flow] {
+                Some(t) => (t - first) as usize + 1,
+                // A couple of silent hops past the last answer show where the path went dark
+                None => hops.iter().rposition(|r| r.successful).map_or(hops.len(), |last| (last + 3).min(hops.len())),
+            };
+            kept.extend_from_slice(&hops[..keep]);
         }
+        self.target_ttl = target_ttl.into_iter().flatten().max().or(self.target_ttl);
+        Ok(kept)
     }
 }
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::process::Command;
+use std::time::{Duration, Instant};
+use crossbeam::channel::Sender;
+
+use crate::shutdown;
+
+// New neighbors stay highlighted this long; changed MACs stay flagged for the session
+const NEW_HIGHLIGHT: Duration = Duration::from_secs(60);
+
+/// One entry of the OS ARP (IPv4) or NDP (IPv6) cache.
+#[derive(Debug, Clone)]
+pub struct Neighbor {
+    pub ip: IpAddr,
+    /// Missing while resolution is incomplete or has failed
+    pub mac: Option<String>,
+    /// `REACHABLE`, `STALE`, `FAILED`...; `PERMANENT` for static entries
+    pub state: String,
+    pub interface: String,
+    pub router: bool,
+}
+
+pub struct NeighborsTask {
+    tx: Sender<Result<Vec<Neighbor>, String>>,
+}
+
+impl NeighborsTask {
+    pub fn new(tx: Sender<Result<Vec<Neighbor>, String>>) -> Self {
+        Self { tx }
+    }
+
+    pub fn run(self) {
+        loop {
+            if self.tx.send(list_neighbors()).is_err() {
+                break;
+            }
+            if !shutdown::sleep(Duration::from_secs(2)) {
+                break;
+            }
+        }
+    }
+}
+
+fn list_neighbors() -> Result<Vec<Neighbor>, String> {
+    let mut neighbors: Vec<Neighbor> = if cfg!(target_os = "linux") {
+        let output = Command::new("ip").arg("neigh").output().map_err(|e| format!("Failed to run ip neigh: {}", e))?;
+        String::from_utf8_lossy(&output.stdout).lines().filter_map(parse_ip_neigh).collect()
+    } else {
+        let output = Command::new("arp").arg("-an").output().map_err(|e| format!("Failed to run arp: {}", e))?;
+        let mut list: Vec<Neighbor> = String::from_utf8_lossy(&output.stdout).lines().filter_map(parse_arp_line).collect();
+        // IPv6 is optional; not every BSD ships ndp
+        if let Ok(output) = Command::new("ndp").arg("-an").output() {
+            list.extend(String::from_utf8_lossy(&output.stdout).lines().skip(1).filter_map(parse_ndp_line));
+        }
+        list
+    };
+    neighbors.sort_by_key(|n| (n.ip.is_ipv6(), n.ip));
+    Ok(neighbors)
+}
+
+/// `ip neigh`: `192.168.1.1 dev eth0 lladdr aa:bb:cc:dd:ee:ff [router] REACHABLE`
+fn parse_ip_neigh(line: &str) -> Option<Neighbor> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    let ip = parts.first()?.parse().ok()?;
+    let after = |key: &str| parts.iter().position(|p| *p == key).and_then(|i| parts.get(i + 1)).map(|s| s.to_string());
+    Some(Neighbor {
+        ip,
+        mac: after("lladdr"),
+        state: parts.last().filter(|s| s.chars().all(|c| c.is_ascii_uppercase())).map(|s| s.to_string()).unwrap_or_default(),
+        interface: after("dev").unwrap_or_default(),
+        router: parts.contains(&"router"),
+    })
+}
+
+/// `arp -an`: `? (192.168.1.1) at aa:bb:cc:dd:ee:ff on en0 ifscope [ethernet]`
+fn parse_arp_line(line: &str) -> Option<Neighbor> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    let ip = parts.get(1)?.trim_matches(|c| c == '(' || c == ')').parse().ok()?;
+    let mac = parts.get(3).filter(|m| m.contains(':')).map(|m| m.to_string());
+    let state = if line.contains("permanent") {
+        "PERMANENT"
+    } else if mac.is_none() {
+        "INCOMPLETE"
+    } else {
+        ""
+    };
+    Some(Neighbor {
+        ip,
+        mac,
+        state: state.to_string(),
+        interface: parts.iter().position(|p| *p == "on").and_then(|i| parts.get(i + 1)).map(|s| s.to_string()).unwrap_or_default(),
+        router: false,
+    })
+}
+
+/// `ndp -an`: `Neighbor  Linklayer Address  Netif  Expire  St  Flgs  Prbs`
+fn parse_ndp_line(line: &str) -> Option<Neighbor> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    let ip = parts.first()?.split('%').next()?.parse().ok()?;
+    let mac = parts.get(1).filter(|m| m.contains(':')).map(|m| m.to_string());
+    let state = match parts.get(4).copied() {
+        Some("R") => "REACHABLE",
+        Some("S") => "STALE",
+        Some("D") => "DELAY",
+        Some("P") => "PROBE",
+        Some("I") => "INCOMPLETE",
+        _ => "",
+    };
+    Some(Neighbor {
+        ip,
+        mac,
+        state: state.to_string(),
+        interface: parts.get(2).map(|s| s.to_string()).unwrap_or_default(),
+        router: parts.get(5).is_some_and(|f| f.contains('R')),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Binding {
+    /// In the first snapshot, or seen before with the same MAC
+    Known,
+    New(Instant),
+    /// The IP answered from a different MAC than before, as with ARP spoofing or a replaced device
+    Changed { previous: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct NeighborEntry {
+    pub neighbor: Neighbor,
+    pub binding: Binding,
+    /// Another IPv4 neighbor has the same MAC
+    pub shared_mac: bool,
+}
+
+impl NeighborEntry {
+    pub fn is_new(&self) -> bool {
+        matches!(self.binding, Binding::New(at) if at.elapsed() < NEW_HIGHLIGHT)
+    }
+}
+
+/// The latest neighbor table, with each IP compared to the MAC it last had.
+#[derive(Default)]
+pub struct NeighborTable {
+    pub entries: Vec<NeighborEntry>,
+    /// Last MAC per IP; outlives the OS cache entry, so a binding that returns changed is caught
+    known: HashMap<IpAddr, String>,
+    changed: HashMap<IpAddr, String>,
+    new: HashMap<IpAddr, Instant>,
+    baseline: bool,
+}
+
+impl NeighborTable {
+    /// Replaces the table; returns the bindings that changed since the last update.
+    pub fn update(&mut self, neighbors: Vec<Neighbor>) -> Vec<(Neighbor, String)> {
+        let mut changes = vec![];
+        for n in &neighbors {
+            let Some(mac) = &n.mac else { continue };
+            match self.known.insert(n.ip, mac.clone()) {
+                Some(old) if !old.eq_ignore_ascii_case(mac) => {
+                    self.changed.insert(n.ip, old.clone());
+                    changes.push((n.clone(), old));
+                }
+                None if self.baseline => {
+                    self.new.insert(n.ip, Instant::now());
+                }
+                _ => {}
+            }
+        }
+        self.baseline = true;
+
+        let mut per_mac: HashMap<String, usize> = HashMap::new();
+        for mac in neighbors.iter().filter(|n| n.ip.is_ipv4()).filter_map(|n| n.mac.as_ref()) {
+            *per_mac.entry(mac.to_lowercase()).or_default() += 1;
+        }
+        self.entries = neighbors.into_iter().map(|n| {
+            let binding = match (self.changed.get(&n.ip), self.new.get(&n.ip)) {
+                (Some(previous), _) => Binding::Changed { previous: previous.clone() },
+                (None, Some(at)) => Binding::New(*at),
+                (None, None) => Binding::Known,
+            };
+            let shared_mac = n.ip.is_ipv4() && n.mac.as_ref().is_some_and(|m| per_mac.get(&m.to_lowercase()).is_some_and(|c| *c > 1));
+            NeighborEntry { neighbor: n, binding, shared_mac }
+        }).collect();
+        changes
+    }
+}
@@ -1,60 +1,249 @@
-use std::process::{Command, Stdio};
-use std::io::{BufRead, BufReader};
+use std::net::IpAddr;
 use crossbeam::channel::Sender;
 
+use crate::config::ScanLimits;
+use crate::tools::args;
+use crate::tools::process::{self, ProcessHandle};
+
+/// Output lines as they come; an `Err` when nmap couldn't run or exited with an error.
 pub struct NmapTask {
     pub target: String,
-    pub tx: Sender<String>,
+    pub tx: Sender<Result<String, String>>,
+    pub process: ProcessHandle,
 }
 
 impl NmapTask {
-    pub fn new(target: String, tx: Sender<String>) -> Self {
-        Self { target, tx }
+    pub fn new(target: String, tx: Sender<Result<String, String>>, process: ProcessHandle) -> Self {
+        Self { target, tx, process }
     }
 
     pub fn run(&self) {
-        let args: Vec<&str> = self.target.split_whitespace().collect();
-        
-        let mut cmd = Command::new("nmap");
-        cmd.args(&args);
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::piped());
-
-        match cmd.spawn() {
-            Ok(mut child) => {
-                let stdout = child.stdout.take().expect("Failed to capture stdout");
-                let stderr = child.stderr.take().expect("Failed to capture stderr");
-
-                let tx_out = self.tx.clone();
-                let tx_err = self.tx.clone();
-
-                // Stream stdout
-                std::thread::spawn(move || {
-                    let reader = BufReader::new(stdout);
-                    for line in reader.lines() {
-                        if let Ok(l) = line {
-                            let _ = tx_out.send(l);
-                        }
-                    }
-                });
-
-                // Stream stderr
-                std::thread::spawn(move || {
-                    let reader = BufReader::new(stderr);
-                    for line in reader.lines() {
-                        if let Ok(l) = line {
-                            let _ = tx_err.send(format!("ERR: {}", l));
-                        }
-                    }
-                });
-                
-                // Wait for process to finish
-                let _ = child.wait();
-                let _ = self.tx.send("Done.".to_string());
+        let result = args::split(&self.target).and_then(|args| process::stream("nmap", &args, &self.tx, &self.process));
+        let _ = self.tx.send(result);
+    }
+}
+
+/// One `PORT STATE SERVICE` line of nmap's normal output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanPort {
+    pub host: String,
+    pub port: u16,
+    pub proto: String,
+    pub state: String,
+    pub service: String,
+}
+
+impl ScanPort {
+    /// The address to connect to: the IP from `name (ip)` report lines, else the host as given.
+    pub fn address(&self) -> &str {
+        self.host.rsplit_once('(').and_then(|(_, ip)| ip.strip_suffix(')')).unwrap_or(&self.host)
+    }
+
+    fn key(&self) -> (&str, u16, &str) {
+        (&self.host, self.port, &self.proto)
+    }
+}
+
+/// Ports from nmap's normal output, tagged with the `Nmap scan report for` host they belong to.
+pub fn parse_ports<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<ScanPort> {
+    let mut host = String::new();
+    let mut ports = vec![];
+    for line in lines {
+        if let Some(h) = line.strip_prefix("Nmap scan report for ") {
+            host = h.trim().to_string();
+            continue;
+        }
+        let mut cols = line.split_whitespace();
+        let (Some(port_proto), Some(state)) = (cols.next(), cols.next()) else { continue };
+        let Some((port, proto)) = port_proto.split_once('/') else { continue };
+        let Ok(port) = port.parse() else { continue };
+        if !matches!(proto, "tcp" | "udp" | "sctp") {
+            continue;
+        }
+        ports.push(ScanPort {
+            host: host.clone(),
+            port,
+            proto: proto.to_string(),
+            state: state.to_string(),
+            service: cols.next().unwrap_or("").to_string(),
+        });
+    }
+    ports
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortChange {
+    Opened,
+    Closed,
+    Unchanged,
+}
+
+/// Open ports of `new` against `old`: newly open, no longer open, and open in both. Ports
+/// that are filtered or closed in both scans are left out.
+pub fn diff_ports(old: &[ScanPort], new: &[ScanPort]) -> Vec<(PortChange, ScanPort)> {
+    let is_open = |p: &ScanPort| p.state == "open";
+    let open_in = |ports: &[ScanPort], p: &ScanPort| ports.iter().any(|o| o.key() == p.key() && is_open(o));
+
+    let mut changes: Vec<(PortChange, ScanPort)> = new.iter().filter(|p| is_open(p))
+        .map(|p| (if open_in(old, p) { PortChange::Unchanged } else { PortChange::Opened }, p.clone()))
+        .collect();
+    changes.extend(old.iter().filter(|p| is_open(p) && !open_in(new, p)).map(|p| (PortChange::Closed, p.clone())));
+    changes.sort_by(|(_, a), (_, b)| (&a.host, a.port, &a.proto).cmp(&(&b.host, b.port, &b.proto)));
+    changes
+}
+
+/// Appends nmap's parallelism, rate and RTT timeout flags for configured limits the args don't already set.
+pub fn with_limits(args: &str, limits: &ScanLimits) -> String {
+    let has = |flag: &str| args.split_whitespace().any(|a| a == flag);
+    let mut out = args.to_string();
+    if let Some(n) = limits.concurrency.filter(|_| !has("--max-parallelism")) {
+        out.push_str(&format!(" --max-parallelism {}", n));
+    }
+    if let Some(rate) = limits.rate.filter(|_| !has("--max-rate")) {
+        out.push_str(&format!(" --max-rate {}", rate));
+    }
+    if let Some(ms) = limits.timeout_ms.filter(|_| !has("--max-rtt-timeout")) {
+        out.push_str(&format!(" --max-rtt-timeout {}ms", ms));
+    }
+    out
+}
+
+// nmap options that take the next argument as their value, so it isn't mistaken for a target
+const VALUED: &[&str] = &[
+    "-p", "-e", "-S", "-D", "-g", "-iL", "-iR", "-oN", "-oX", "-oG", "-oA", "-oS", "-b",
+    "--source-port", "--exclude", "--excludefile", "--exclude-ports", "--top-ports", "--port-ratio",
+    "--script", "--script-args", "--script-args-file", "--script-help", "--script-timeout",
+    "--max-retries", "--host-timeout", "--min-rate", "--max-rate", "--scan-delay", "--max-scan-delay",
+    "--min-parallelism", "--max-parallelism", "--min-hostgroup", "--max-hostgroup",
+    "--min-rtt-timeout", "--max-rtt-timeout", "--initial-rtt-timeout", "--version-intensity",
+    "--dns-servers", "--proxies", "--data", "--data-string", "--data-length", "--spoof-mac",
+    "--ttl", "--mtu", "--stats-every", "--datadir", "--servicedb", "--versiondb", "--resume",
+    "--stylesheet", "--ip-options", "--scanflags",
+];
+
+/// What an nmap command line will scan: its target specs checked the way nmap reads them
+/// (addresses, CIDR blocks, octet ranges like `10.0.0.1-50` or `192.168.1,3.*`, host
+/// names), and how many addresses they cover.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Targets {
+    pub specs: Vec<String>,
+    pub hosts: u128,
+    /// `-iL` or `-iR`: nmap picks the hosts, so the count is only what was typed
+    pub external: bool,
+}
+
+impl Targets {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut specs = vec![];
+        let mut hosts: u128 = 0;
+        let mut external = false;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "-iL" || arg == "-iR" {
+                external = true;
+            }
+            if VALUED.contains(&arg.as_str()) {
+                args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+                continue;
             }
-            Err(e) => {
-                let _ = self.tx.send(format!("Failed to start nmap: {}", e));
+            if arg.starts_with('-') {
+                continue;
             }
+            hosts = hosts.saturating_add(count(arg)?);
+            specs.push(arg.clone());
+        }
+        if specs.is_empty() && !external {
+            return Err("Enter a target: an address, a CIDR block (10.0.0.0/24), a range (10.0.0.1-50) or a host name".to_string());
+        }
+        Ok(Self { specs, hosts, external })
+    }
+
+    /// The preview next to the input: "254 hosts", "1 host", "hosts from -iL".
+    pub fn describe(&self) -> String {
+        let typed = match self.hosts {
+            1 => "1 host".to_string(),
+            n => format!("{} hosts", n),
+        };
+        match (self.external, self.specs.is_empty()) {
+            (true, true) => "hosts chosen by -iL/-iR".to_string(),
+            (true, false) => format!("{} + -iL/-iR", typed),
+            (false, _) => typed,
+        }
+    }
+}
+
+// Addresses one target spec stands for, or why nmap would refuse it
+fn count(spec: &str) -> Result<u128, String> {
+    let (host, prefix) = match spec.split_once('/') {
+        Some((host, prefix)) => (host, Some(prefix)),
+        None => (spec, None),
+    };
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        let bits = if ip.is_ipv4() { 32 } else { 128 };
+        return match prefix {
+            None => Ok(1),
+            Some(p) => match p.parse::<u32>() {
+                Ok(p) if p <= bits => Ok(1u128.checked_shl(bits - p).unwrap_or(u128::MAX)),
+                _ => Err(format!("'{}': the prefix length must be 0-{}", spec, bits)),
+            },
+        };
+    }
+    let v4_pattern = host.split('.').count() == 4 && host.chars().all(|c| c.is_ascii_digit() || ".,-*".contains(c));
+    if v4_pattern {
+        if prefix.is_some() {
+            return Err(format!("'{}': ranges can't also have a /prefix", spec));
+        }
+        return host.split('.').try_fold(1u128, |total, octet| Ok(total * octets(octet).map_err(|e| format!("'{}': {}", spec, e))?));
+    }
+    // Digits and dots that aren't four octets, e.g. a mistyped address
+    if host.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return Err(format!("'{}' is not a valid IPv4 address", spec));
+    }
+    if host.contains(':') {
+        return Err(format!("'{}' is not a valid IPv6 address", spec));
+    }
+    let name_ok = host.len() <= 253 && host.trim_end_matches('.').split('.').all(|label| {
+        !label.is_empty() && label.len() <= 63 && !label.starts_with('-') && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    });
+    if !name_ok {
+        return Err(format!("'{}' is not an address, range or host name", spec));
+    }
+    match prefix {
+        None => Ok(1),
+        // nmap resolves the name and scans the block around it
+        Some(p) => match p.parse::<u32>() {
+            Ok(p) if p <= 32 => Ok(1u128 << (32 - p)),
+            _ => Err(format!("'{}': the prefix length must be 0-32", spec)),
+        },
+    }
+}
+
+// Values one octet of a range covers: `*`, `7`, `1-50`, `-50`, `200-`, or a comma list of those
+fn octets(octet: &str) -> Result<u128, String> {
+    let value = |s: &str, default: u16| -> Result<u16, String> {
+        if s.is_empty() {
+            return Ok(default);
+        }
+        s.parse::<u16>().ok().filter(|n| *n <= 255).ok_or_else(|| format!("octet {} is out of range", s))
+    };
+    let mut seen = [false; 256];
+    for part in octet.split(',') {
+        let (low, high) = match part {
+            "*" => (0, 255),
+            "" => return Err("an octet is empty".to_string()),
+            _ => match part.split_once('-') {
+                Some((low, high)) => (value(low, 0)?, value(high, 255)?),
+                None => {
+                    let n = value(part, 0)?;
+                    (n, n)
+                }
+            },
+        };
+        if low > high {
+            return Err(format!("range {} runs backwards", part));
         }
+        seen[low as usize..=high as usize].fill(true);
     }
+    Ok(seen.iter().filter(|s| **s).count() as u128)
 }
@@ -0,0 +1,209 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crossbeam::channel::Sender;
+
+use crate::shutdown;
+
+const NTP_PORT: u16 = 123;
+// Seconds between the NTP era (1900) and the Unix epoch
+const NTP_UNIX_OFFSET: f64 = 2_208_988_800.0;
+
+/// One SNTP exchange with a server.
+#[derive(Debug, Clone)]
+pub struct NtpSample {
+    pub server: String,
+    pub addr: SocketAddr,
+    /// How far the server's clock is ahead of ours
+    pub offset_ms: f64,
+    /// Round trip, minus the server's processing time
+    pub delay_ms: f64,
+    pub stratum: u8,
+    /// Upstream source: a refclock code at stratum 1, else the upstream server's address
+    pub reference: String,
+    pub round: u64,
+}
+
+#[derive(Debug, Clone)]
+pub enum NtpEvent {
+    Sample(NtpSample),
+    Error { server: String, error: String },
+    Done,
+}
+
+/// Latest result and offset history (round, ms) for one server on the NTP screen.
+#[derive(Debug, Clone)]
+pub struct NtpServerState {
+    pub server: String,
+    pub last: Option<Result<NtpSample, String>>,
+    pub offsets: Vec<(f64, f64)>,
+}
+
+impl NtpServerState {
+    pub fn new(server: String) -> Self {
+        Self { server, last: None, offsets: Vec::new() }
+    }
+
+    pub fn record(&mut self, result: Result<NtpSample, String>) {
+        if let Ok(sample) = &result {
+            self.offsets.push((sample.round as f64, sample.offset_ms));
+            if self.offsets.len() > 200 {
+                self.offsets.remove(0);
+            }
+        }
+        self.last = Some(result);
+    }
+}
+
+/// `servers...` plus optional `-i <secs>` (repeat) and `-t <secs>` (timeout).
+#[derive(Debug, Clone)]
+pub struct NtpRequest {
+    pub servers: Vec<String>,
+    pub interval: Option<Duration>,
+    pub timeout: Duration,
+}
+
+impl NtpRequest {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut req = NtpRequest { servers: Vec::new(), interval: None, timeout: Duration::from_secs(2) };
+        let mut args = input.split(|c: char| c.is_whitespace() || c == ',').filter(|a| !a.is_empty());
+        while let Some(arg) = args.next() {
+            match arg {
+                "-i" | "-t" => {
+                    let d = args.next().and_then(|v| v.parse::<f64>().ok()).filter(|v| *v > 0.0)
+                        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                        .ok_or_else(|| format!("{} needs a number of seconds", arg))?;
+                    if arg == "-i" { req.interval = Some(d) } else { req.timeout = d }
+                }
+                server => req.servers.push(server.to_string()),
+            }
+        }
+        if req.servers.is_empty() {
+            return Err("Enter one or more NTP servers".to_string());
+        }
+        Ok(req)
+    }
+}
+
+pub struct NtpTask {
+    pub should_stop: Arc<AtomicBool>,
+}
+
+impl NtpTask {
+    pub fn new() -> Self {
+        Self {
+            should_stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Queries every server once, then again each `interval` until stopped. A fresh stop
+    /// flag per run, so a previous run still sleeping out its interval stays stopped.
+    pub fn start(&mut self, request: NtpRequest, tx: Sender<NtpEvent>) {
+        *self = Self::new();
+        let should_stop = self.should_stop.clone();
+
+        std::thread::spawn(move || {
+            let mut round = 0;
+            loop {
+                for server in &request.servers {
+                    let event = match query(server, request.timeout) {
+                        Ok(mut sample) => {
+                            sample.round = round;
+                            NtpEvent::Sample(sample)
+                        }
+                        Err(error) => NtpEvent::Error { server: server.clone(), error },
+                    };
+                    if shutdown::stopping(&should_stop) || tx.send(event).is_err() {
+                        return;
+                    }
+                }
+                round += 1;
+
+                let Some(interval) = request.interval else { break };
+                let mut waited = Duration::ZERO;
+                while waited < interval {
+                    if shutdown::stopping(&should_stop) {
+                        return;
+                    }
+                    let step = Duration::from_millis(100).min(interval - waited);
+                    std::thread::sleep(step);
+                    waited += step;
+                }
+            }
+            let _ = tx.send(NtpEvent::Done);
+        });
+    }
+
+    pub fn stop(&self) {
+        self.should_stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn now() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs_f64()).unwrap_or(0.0) + NTP_UNIX_OFFSET
+}
+
+fn read_timestamp(b: &[u8]) -> f64 {
+    let secs = u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as f64;
+    let frac = u32::from_be_bytes([b[4], b[5], b[6], b[7]]) as f64 / 4_294_967_296.0;
+    secs + frac
+}
+
+fn write_timestamp(b: &mut [u8], t: f64) {
+    b[0..4].copy_from_slice(&(t.trunc() as u32).to_be_bytes());
+    b[4..8].copy_from_slice(&((t.fract() * 4_294_967_296.0) as u32).to_be_bytes());
+}
+
+fn query(server: &str, timeout: Duration) -> Result<NtpSample, String> {
+    let addr = (server, NTP_PORT).to_socket_addrs().map_err(|e| format!("resolve failed: {}", e))?
+        .next().ok_or("no address")?;
+    let bind: SocketAddr = if addr.is_ipv4() { (Ipv4Addr::UNSPECIFIED, 0).into() } else { (IpAddr::from([0u16; 8]), 0).into() };
+    let socket = UdpSocket::bind(bind).map_err(|e| e.to_string())?;
+    socket.set_read_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+
+    let mut request = [0u8; 48];
+    request[0] = 0x23; // LI 0, version 4, mode 3 (client)
+    let t1 = now();
+    write_timestamp(&mut request[40..48], t1);
+    socket.send_to(&request, addr).map_err(|e| format!("send failed: {}", e))?;
+
+    let mut buf = [0u8; 512];
+    let (len, _) = socket.recv_from(&mut buf).map_err(|e| match e.kind() {
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut => "timed out".to_string(),
+        _ => e.to_string(),
+    })?;
+    let t4 = now();
+    if len < 48 || buf[0] & 0x07 != 4 {
+        return Err("not an NTP server reply".to_string());
+    }
+    // Our transmit time must come back as the originate time, or this answers someone else
+    if buf[24..32] != request[40..48] {
+        return Err("reply doesn't match request".to_string());
+    }
+
+    let stratum = buf[1];
+    let ref_id = &buf[12..16];
+    if stratum == 0 {
+        return Err(format!("kiss-o'-death: {}", String::from_utf8_lossy(ref_id).trim_end_matches('\0')));
+    }
+    let reference = if stratum == 1 {
+        String::from_utf8_lossy(ref_id).trim_end_matches('\0').to_string()
+    } else if addr.is_ipv4() {
+        Ipv4Addr::new(ref_id[0], ref_id[1], ref_id[2], ref_id[3]).to_string()
+    } else {
+        // IPv6 upstreams are identified by the first 4 bytes of an MD5 hash
+        format!("{:02x}{:02x}{:02x}{:02x}", ref_id[0], ref_id[1], ref_id[2], ref_id[3])
+    };
+
+    let (t2, t3) = (read_timestamp(&buf[32..40]), read_timestamp(&buf[40..48]));
+    Ok(NtpSample {
+        server: server.to_string(),
+        addr,
+        offset_ms: ((t2 - t1) + (t3 - t4)) / 2.0 * 1000.0,
+        delay_ms: ((t4 - t1) - (t3 - t2)) * 1000.0,
+        stratum,
+        reference,
+        round: 0,
+    })
+}
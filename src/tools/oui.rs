@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use crossbeam::channel::Sender;
+
+use crate::config::{self, OuiConfig};
+
+// What arp-scan, ieee-data, Wireshark and nmap install; all four formats are understood
+const SYSTEM_FILES: [&str; 8] = [
+    "/usr/share/ieee-data/oui.txt",
+    "/usr/share/arp-scan/ieee-oui.txt",
+    "/usr/local/share/arp-scan/ieee-oui.txt",
+    "/opt/homebrew/share/arp-scan/ieee-oui.txt",
+    "/usr/share/wireshark/manuf",
+    "/usr/local/share/wireshark/manuf",
+    "/usr/share/nmap/nmap-mac-prefixes",
+    "/usr/local/share/nmap/nmap-mac-prefixes",
+];
+// Block sizes IEEE assigns: MA-S (36 bits), MA-M (28) and MA-L (24), longest match first
+const PREFIX_BITS: [u32; 3] = [36, 28, 24];
+
+/// MAC prefix -> organization, from an IEEE registry file.
+pub struct OuiDb {
+    prefixes: [HashMap<u64, String>; 3],
+    pub path: PathBuf,
+}
+
+impl OuiDb {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let db = Self::parse(&String::from_utf8_lossy(&text), path.to_path_buf());
+        if db.len() == 0 {
+            return Err(format!("{}: no MAC prefixes found", path.display()));
+        }
+        Ok(db)
+    }
+
+    /// One prefix per line: IEEE `oui.txt` (`00-00-0C   (hex)  Cisco Systems, Inc`), arp-scan and nmap
+    /// (`00000C Cisco Systems`) or Wireshark `manuf` (`00:1B:C5:00:00:00/36  Short  Long name`).
+    fn parse(text: &str, path: PathBuf) -> Self {
+        let mut prefixes: [HashMap<u64, String>; 3] = Default::default();
+        for line in text.lines() {
+            // Indented lines are the addresses under each oui.txt entry
+            if line.starts_with(char::is_whitespace) || line.starts_with('#') {
+                continue;
+            }
+            let Some((prefix, rest)) = line.split_once(char::is_whitespace) else { continue };
+            let (hex, bits) = match prefix.split_once('/') {
+                Some((hex, bits)) => (hex, bits.parse().ok()),
+                None => (prefix, None),
+            };
+            let hex: String = hex.chars().filter(|c| !matches!(c, '-' | ':' | '.')).collect();
+            let bits = bits.unwrap_or(hex.len() as u32 * 4);
+            let Some(slot) = PREFIX_BITS.iter().position(|b| *b == bits) else { continue };
+            let Some(value) = hex.get(..bits as usize / 4).and_then(|h| u64::from_str_radix(h, 16).ok()) else { continue };
+
+            let rest = rest.trim_start();
+            let rest = rest.strip_prefix("(hex)").or_else(|| rest.strip_prefix("(base 16)")).unwrap_or(rest);
+            // manuf has a short and a long name; the long one is last
+            let Some(name) = rest.split('\t').map(str::trim).rfind(|s| !s.is_empty()) else { continue };
+            prefixes[slot].entry(value).or_insert_with(|| name.to_string());
+        }
+        Self { prefixes, path }
+    }
+
+    pub fn len(&self) -> usize {
+        self.prefixes.iter().map(HashMap::len).sum()
+    }
+
+    /// Organization the MAC's prefix is registered to; randomized and other locally
+    /// administered MACs have none.
+    pub fn lookup(&self, mac: &str) -> Option<&str> {
+        let mac = parse_mac(mac)?;
+        if is_local(mac) {
+            return None;
+        }
+        let value = mac.iter().fold(0u64, |acc, b| acc << 8 | *b as u64);
+        PREFIX_BITS.iter().zip(&self.prefixes)
+            .find_map(|(bits, map)| map.get(&(value >> (48 - bits))))
+            .map(String::as_str)
+    }
+}
+
+/// `aa:bb:cc:dd:ee:ff`, `aa-bb-...`, `aabb.ccdd.eeff` or bare hex. Only the first three bytes
+/// are needed for a vendor, so a prefix like `00:1b:63` is accepted too (zero-filled).
+pub fn parse_mac(mac: &str) -> Option<[u8; 6]> {
+    let mac = mac.trim();
+    // arp on macOS drops leading zeros: 0:1b:63:...
+    let hex: String = if mac.contains(':') && mac.split(':').all(|p| p.len() <= 2) {
+        mac.split(':').map(|p| format!("{:0>2}", p)).collect()
+    } else {
+        mac.chars().filter(|c| !matches!(c, '-' | ':' | '.')).collect()
+    };
+    if !(6..=12).contains(&hex.len()) || !hex.len().is_multiple_of(2) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut bytes = [0u8; 6];
+    for (i, b) in bytes.iter_mut().enumerate().take(hex.len() / 2) {
+        *b = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+/// The U/L bit: set by phones and laptops that randomize their MAC per network.
+pub fn is_local(mac: [u8; 6]) -> bool {
+    mac[0] & 0x02 != 0
+}
+
+/// Vendor for display: the registered organization, or why there is none.
+pub fn describe(db: Option<&OuiDb>, mac: &str) -> String {
+    match parse_mac(mac) {
+        None => String::new(),
+        Some(bytes) if is_local(bytes) => "(randomized)".to_string(),
+        Some(_) => db.and_then(|db| db.lookup(mac)).unwrap_or("").to_string(),
+    }
+}
+
+/// Loads the vendor file in the background, downloading it first with `download_url` set when
+/// it's missing or older than `update_days`.
+pub fn spawn(config: OuiConfig, tx: Sender<Result<OuiDb, String>>) {
+    std::thread::spawn(move || {
+        if let Some(url) = &config.download_url {
+            let target = config.path.clone().or_else(default_path);
+            let stale = target.as_ref().is_some_and(|t| {
+                std::fs::metadata(t).and_then(|m| m.modified())
+                    .map_or(true, |m| SystemTime::now().duration_since(m).unwrap_or_default() > Duration::from_secs(config.update_days.max(1) * 86400))
+            });
+            if let (true, Some(target)) = (stale, target)
+                && let Err(e) = download(url, &target)
+            {
+                let _ = tx.send(Err(format!("OUI download failed: {}", e)));
+            }
+        }
+        let result = match locate(config.path.as_deref()) {
+            Some(path) => OuiDb::open(&path),
+            None => Err("No OUI vendor file found (install arp-scan or ieee-data, or set [oui] path)".to_string()),
+        };
+        let _ = tx.send(result);
+    });
+}
+
+fn download(url: &str, target: &Path) -> Result<(), String> {
+    let client = reqwest::blocking::Client::builder().timeout(Duration::from_secs(120)).build().map_err(|e| e.to_string())?;
+    let body = client.get(url).send().and_then(|r| r.error_for_status()).and_then(|r| r.bytes()).map_err(|e| e.to_string())?;
+    if OuiDb::parse(&String::from_utf8_lossy(&body), target.to_path_buf()).len() == 0 {
+        return Err(format!("{} is not an OUI list", url));
+    }
+    if let Some(dir) = target.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    // Write next to the target and rename, so a reader never sees a partial file
+    let tmp = target.with_extension("part");
+    std::fs::write(&tmp, &body).map_err(|e| format!("{}: {}", tmp.display(), e))?;
+    std::fs::rename(&tmp, target).map_err(|e| format!("{}: {}", target.display(), e))
+}
+
+/// $XDG_DATA_HOME/netops/oui.txt, also where downloads are saved.
+fn default_path() -> Option<PathBuf> {
+    config::data_dir().map(|d| d.join("oui.txt"))
+}
+
+fn locate(configured: Option<&Path>) -> Option<PathBuf> {
+    if let Some(path) = configured {
+        return Some(path.to_path_buf());
+    }
+    default_path().into_iter().chain(SYSTEM_FILES.iter().map(PathBuf::from)).find(|p| p.exists())
+}
@@ -0,0 +1,141 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::config::CaptureConfig;
+
+// Classic libpcap format, microsecond timestamps, readable by tcpdump and Wireshark
+const MAGIC: u32 = 0xa1b2_c3d4;
+const SNAPLEN: u32 = 65535;
+const LINKTYPE_ETHERNET: u32 = 1;
+const PREFIX: &str = "netops-";
+
+/// Writes captured frames to `netops-<time>.pcap` files in a directory, starting a new file
+/// when the current one reaches the size or age limit and deleting the oldest beyond the
+/// number kept. Shared by the capture threads of every interface.
+pub struct PcapRing {
+    dir: PathBuf,
+    max_bytes: u64,
+    max_secs: u64,
+    keep: usize,
+    file: Option<(BufWriter<File>, PathBuf, Instant)>,
+    written: u64,
+    flushed: Instant,
+    pub files_started: u64,
+    pub packets: u64,
+}
+
+impl PcapRing {
+    pub fn new(config: &CaptureConfig) -> Result<Self, String> {
+        let dir = config.ring_dir.clone()
+            .or_else(|| crate::config::data_dir().map(|d| d.join("captures")))
+            .ok_or("No directory for capture files; set [capture] ring_dir")?;
+        std::fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        Ok(Self {
+            dir,
+            max_bytes: config.ring_file_mb.max(1) * 1024 * 1024,
+            max_secs: config.ring_file_secs,
+            keep: config.ring_files.max(1),
+            file: None,
+            written: 0,
+            flushed: Instant::now(),
+            files_started: 0,
+            packets: 0,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The file being written, if any.
+    pub fn current(&self) -> Option<&Path> {
+        self.file.as_ref().map(|(_, path, _)| path.as_path())
+    }
+
+    pub fn write(&mut self, frame: &[u8]) -> Result<(), String> {
+        let due = match &self.file {
+            None => true,
+            Some((_, _, opened)) => self.written >= self.max_bytes || (self.max_secs > 0 && opened.elapsed().as_secs() >= self.max_secs),
+        };
+        if due {
+            self.rotate()?;
+        }
+        let Some((out, path, _)) = &mut self.file else { return Ok(()) };
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let len = frame.len().min(SNAPLEN as usize);
+        let mut record = Vec::with_capacity(16 + len);
+        record.extend((now.as_secs() as u32).to_le_bytes());
+        record.extend(now.subsec_micros().to_le_bytes());
+        record.extend((len as u32).to_le_bytes());
+        record.extend((frame.len() as u32).to_le_bytes());
+        record.extend(&frame[..len]);
+        out.write_all(&record).map_err(|e| format!("{}: {}", path.display(), e))?;
+        self.written += record.len() as u64;
+        self.packets += 1;
+        // Packets reach the disk within a second, so a crash loses little
+        if self.flushed.elapsed().as_secs() >= 1 {
+            self.flush();
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) {
+        if let Some((out, _, _)) = &mut self.file {
+            let _ = out.flush();
+        }
+        self.flushed = Instant::now();
+    }
+
+    fn rotate(&mut self) -> Result<(), String> {
+        self.flush();
+        let stamp = time::OffsetDateTime::now_utc()
+            .format(&time::macros::format_description!("[year][month][day]-[hour][minute][second]"))
+            .unwrap_or_default();
+        // Every name gets a counter for rotations within the same second, zero-padded so that
+        // names keep sorting by age; a bare stamp would sort after its own `-1`
+        let mut n = 0;
+        let mut path = self.dir.join(format!("{}{}-{:03}.pcap", PREFIX, stamp, n));
+        while path.exists() {
+            n += 1;
+            path = self.dir.join(format!("{}{}-{:03}.pcap", PREFIX, stamp, n));
+        }
+        let file = File::create(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut out = BufWriter::new(file);
+        let mut header = Vec::with_capacity(24);
+        header.extend(MAGIC.to_le_bytes());
+        header.extend(2u16.to_le_bytes());
+        header.extend(4u16.to_le_bytes());
+        header.extend(0i32.to_le_bytes());
+        header.extend(0u32.to_le_bytes());
+        header.extend(SNAPLEN.to_le_bytes());
+        header.extend(LINKTYPE_ETHERNET.to_le_bytes());
+        out.write_all(&header).map_err(|e| format!("{}: {}", path.display(), e))?;
+        self.file = Some((out, path, Instant::now()));
+        self.written = header.len() as u64;
+        self.files_started += 1;
+        self.prune();
+        Ok(())
+    }
+
+    // Our own files only, oldest first by name (names sort by time)
+    fn prune(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else { return };
+        let mut files: Vec<PathBuf> = entries.flatten()
+            .map(|e| e.path())
+            .filter(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with(PREFIX) && n.ends_with(".pcap")))
+            .collect();
+        files.sort();
+        let excess = files.len().saturating_sub(self.keep);
+        for old in &files[..excess] {
+            let _ = std::fs::remove_file(old);
+        }
+    }
+}
+
+impl Drop for PcapRing {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
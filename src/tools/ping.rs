@@ -1,14 +1,293 @@
-use std::time::Duration;
-use surge_ping::IcmpPacket;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use socket2::{Domain, Protocol, Socket, Type};
+use surge_ping::{Client, Config, IcmpPacket, PingIdentifier, PingSequence, ICMP};
 use tokio::sync::mpsc::Sender;
-use std::net::IpAddr;
+use std::mem::MaybeUninit;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU16, Ordering};
+
+use crate::histogram::Histogram;
+use crate::tools::args::{self, Args};
+use crate::tools::traceroute::parse_icmp;
+use crate::shutdown;
+
+// Connects, echo replies and UDP replies slower than this count as lost
+const PORT_TIMEOUT: Duration = Duration::from_secs(2);
+
+// One ICMP socket per address family (v4, v6), shared by every ping, dashboard probe and sweep
+static CLIENTS: Mutex<[Option<Client>; 2]> = Mutex::new([None, None]);
+static NEXT_IDENTIFIER: AtomicU16 = AtomicU16::new(1);
+
+/// The shared ICMP client for `ip`'s address family, opened on first use.
+pub fn icmp_client(ip: IpAddr) -> Result<Client, String> {
+    let family = ip.is_ipv6() as usize;
+    let mut clients = CLIENTS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(client) = &clients[family] {
+        return Ok(client.clone());
+    }
+    let config = if ip.is_ipv6() { Config::builder().kind(ICMP::V6).build() } else { Config::default() };
+    let client = Client::new(&config).map_err(|e| format!("Failed to open ICMP socket: {}", e))?;
+    clients[family] = Some(client.clone());
+    Ok(client)
+}
+
+/// Replies on a shared client are matched by host, identifier and sequence, so each concurrent
+/// pinger takes its own identifier.
+pub fn icmp_identifier() -> PingIdentifier {
+    PingIdentifier(NEXT_IDENTIFIER.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Sets the ToS byte (IPv4) or traffic class (IPv6) a socket's packets carry.
+pub fn set_tos(socket: &Socket, ip: IpAddr, tos: u8) -> std::io::Result<()> {
+    match ip {
+        IpAddr::V4(_) => socket.set_tos_v4(tos as u32),
+        IpAddr::V6(_) => socket.set_tclass_v6(tos as u32),
+    }
+}
+
+/// `-Q` and `-t`: the ToS byte and TTL a ping's packets go out with, for checking how QoS
+/// classes are treated along a path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Marking {
+    pub tos: Option<u8>,
+    pub ttl: Option<u8>,
+}
+
+impl Marking {
+    pub fn is_set(&self) -> bool {
+        self.tos.is_some() || self.ttl.is_some()
+    }
+
+    fn apply(&self, socket: &Socket, ip: IpAddr) -> std::io::Result<()> {
+        if let Some(tos) = self.tos {
+            set_tos(socket, ip, tos)?;
+        }
+        if let Some(ttl) = self.ttl {
+            match ip {
+                IpAddr::V4(_) => socket.set_ttl_v4(ttl as u32)?,
+                IpAddr::V6(_) => socket.set_unicast_hops_v6(ttl as u32)?,
+            }
+        }
+        Ok(())
+    }
+
+    // A socket of the address's family for `type_`, marked; the port probes' sockets start here
+    fn socket(&self, ip: IpAddr, type_: Type, protocol: Protocol) -> std::io::Result<Socket> {
+        let socket = Socket::new(Domain::for_address(SocketAddr::new(ip, 0)), type_, Some(protocol))?;
+        self.apply(&socket, ip)?;
+        Ok(socket)
+    }
+}
+
+/// An ICMP socket of the ping's own, for marked pings: the shared client's socket can't
+/// carry one ping's ToS or TTL without every other ping inheriting it.
+struct MarkedIcmp {
+    socket: Arc<Socket>,
+    raw: bool,
+    target: IpAddr,
+    id: u16,
+}
+
+impl MarkedIcmp {
+    fn open(target: IpAddr, marking: Marking) -> std::io::Result<Self> {
+        let proto = if target.is_ipv4() { Protocol::ICMPV4 } else { Protocol::ICMPV6 };
+        let (socket, raw) = match marking.socket(target, Type::RAW, proto) {
+            Ok(s) => (s, true),
+            Err(_) => (marking.socket(target, Type::DGRAM, proto)?, false),
+        };
+        socket.set_read_timeout(Some(Duration::from_millis(50)))?;
+        Ok(Self { socket: Arc::new(socket), raw, target, id: icmp_identifier().0 })
+    }
+
+    /// (reply TTL, round trip) of one echo request; a router dropping it for its TTL is an error.
+    async fn ping(&self, seq: u16, payload_size: usize) -> Result<(u8, Duration), String> {
+        let (socket, raw, target, id) = (self.socket.clone(), self.raw, self.target, self.id);
+        tokio::task::spawn_blocking(move || {
+            let mut packet = vec![0u8; 8 + payload_size];
+            packet[0] = if target.is_ipv4() { 8 } else { 128 };
+            packet[4..6].copy_from_slice(&id.to_be_bytes());
+            packet[6..8].copy_from_slice(&seq.to_be_bytes());
+            if target.is_ipv4() {
+                let checksum = internet_checksum::checksum(&packet);
+                packet[2..4].copy_from_slice(&checksum);
+            }
+            let start = Instant::now();
+            socket.send_to(&packet, &SocketAddr::new(target, 0).into()).map_err(|e| format!("Send failed: {}", e))?;
+            let mut buf = [MaybeUninit::new(0u8); 1500];
+            while start.elapsed() < PORT_TIMEOUT {
+                let Ok((len, from)) = socket.recv_from(&mut buf) else { continue };
+                let elapsed = start.elapsed();
+                let data: Vec<u8> = buf[..len].iter().map(|b| unsafe { b.assume_init() }).collect();
+                // Raw IPv4 sockets deliver the IP header, and with it the reply's TTL
+                let (icmp, ttl) = if raw && target.is_ipv4() {
+                    (data.get(((data.first().copied().unwrap_or(0) & 0x0f) as usize * 4)..), data.get(8).copied().unwrap_or(0))
+                } else {
+                    (Some(&data[..]), 0)
+                };
+                let Some(icmp) = icmp else { continue };
+                let Some((reply_id, reply_seq, is_echo_reply)) = parse_icmp(icmp, target.is_ipv4()) else { continue };
+                // The kernel picks the id for ping sockets
+                if (raw && reply_id != id) || reply_seq != seq {
+                    continue;
+                }
+                if is_echo_reply {
+                    return Ok((ttl, elapsed));
+                }
+                let from = from.as_socket().map_or("a router".to_string(), |s| s.ip().to_string());
+                let exceeded = icmp[0] == if target.is_ipv4() { 11 } else { 3 };
+                return Err(if exceeded { format!("Time to live exceeded at {}", from) } else { format!("Unreachable (from {})", from) });
+            }
+            Err("Request timed out".to_string())
+        }).await.map_err(|e| e.to_string())?
+    }
+}
+
+/// What each ping sends: an ICMP echo, or (`-p <port>` with `--tcp`/`--udp`) a probe to a port
+/// for when ICMP is filtered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PingProbe {
+    Icmp,
+    Tcp(u16),
+    Udp(u16),
+}
+
+impl PingProbe {
+    pub fn label(&self) -> String {
+        match self {
+            PingProbe::Icmp => "icmp".to_string(),
+            PingProbe::Tcp(port) => format!("tcp/{}", port),
+            PingProbe::Udp(port) => format!("udp/{}", port),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct PingResult {
     pub seq: u16,
-    pub ttl: u8,
+    pub ttl: u8, // 0 for port probes
     pub time: Duration,
     pub target: String,
+    pub probe: PingProbe,
+}
+
+/// Running totals for one ping session (the visible history is capped).
+#[derive(Debug, Clone, Default)]
+pub struct PingStats {
+    pub sent: u64,
+    pub recv: u64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub sum_ms: f64,
+    pub rtt: Histogram,
+}
+
+impl PingStats {
+    pub fn record(&mut self, result: &Result<PingResult, String>) {
+        self.sent += 1;
+        if let Ok(res) = result {
+            let ms = res.time.as_secs_f64() * 1000.0;
+            self.min_ms = if self.recv == 0 { ms } else { self.min_ms.min(ms) };
+            self.max_ms = self.max_ms.max(ms);
+            self.sum_ms += ms;
+            self.rtt.record(ms);
+            self.recv += 1;
+        }
+    }
+
+    pub fn avg_ms(&self) -> Option<f64> {
+        if self.recv == 0 { None } else { Some(self.sum_ms / self.recv as f64) }
+    }
+
+    pub fn loss_pct(&self) -> f64 {
+        if self.sent == 0 { 0.0 } else { (self.sent - self.recv) as f64 / self.sent as f64 * 100.0 }
+    }
+}
+
+// Upper bounds of the heatmap's latency columns, in ms; one more column takes anything slower
+pub const HEATMAP_BOUNDS: [f64; 10] = [1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0];
+const HEATMAP_ROWS: usize = 720;
+const HEATMAP_START_SECS: u64 = 10;
+
+/// One time bucket of the heatmap: replies per latency column, and probes lost.
+#[derive(Debug, Clone, Default)]
+pub struct HeatmapRow {
+    /// Unix time the bucket starts
+    pub start: u64,
+    pub counts: [u32; HEATMAP_BOUNDS.len() + 1],
+    pub lost: u32,
+}
+
+impl HeatmapRow {
+    pub fn total(&self) -> u32 {
+        self.counts.iter().sum::<u32>() + self.lost
+    }
+
+    /// When the bucket starts (UTC, like alert times).
+    pub fn time_label(&self) -> String {
+        let format = time::format_description::parse("[hour]:[minute]:[second]").unwrap();
+        time::OffsetDateTime::from_unix_timestamp(self.start as i64)
+            .ok()
+            .and_then(|t| t.format(&format).ok())
+            .unwrap_or_default()
+    }
+
+    fn add(&mut self, other: &HeatmapRow) {
+        for (a, b) in self.counts.iter_mut().zip(other.counts) {
+            *a += b;
+        }
+        self.lost += other.lost;
+    }
+}
+
+/// A ping session counted per time bucket and latency column, for the Ping screen's
+/// heatmap. Buckets start 10s wide; past `HEATMAP_ROWS` of them neighbours merge and the
+/// width doubles, so a session of any length fits.
+#[derive(Debug, Clone)]
+pub struct LatencyHeatmap {
+    pub bucket_secs: u64,
+    pub rows: Vec<HeatmapRow>,
+}
+
+impl Default for LatencyHeatmap {
+    fn default() -> Self {
+        Self { bucket_secs: HEATMAP_START_SECS, rows: vec![] }
+    }
+}
+
+impl LatencyHeatmap {
+    pub fn record(&mut self, result: &Result<PingResult, String>) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let start = now - now % self.bucket_secs;
+        if self.rows.last().is_none_or(|r| r.start != start) {
+            self.rows.push(HeatmapRow { start, ..Default::default() });
+        }
+        let Some(row) = self.rows.last_mut() else { return };
+        match result {
+            Ok(res) => {
+                let ms = res.time.as_secs_f64() * 1000.0;
+                let column = HEATMAP_BOUNDS.iter().position(|&b| ms < b).unwrap_or(HEATMAP_BOUNDS.len());
+                row.counts[column] += 1;
+            }
+            Err(_) => row.lost += 1,
+        }
+        while self.rows.len() > HEATMAP_ROWS {
+            self.widen();
+        }
+    }
+
+    fn widen(&mut self) {
+        self.bucket_secs *= 2;
+        let mut merged: Vec<HeatmapRow> = Vec::with_capacity(self.rows.len() / 2 + 1);
+        for row in self.rows.drain(..) {
+            let start = row.start - row.start % self.bucket_secs;
+            match merged.last_mut() {
+                Some(last) if last.start == start => last.add(&row),
+                _ => merged.push(HeatmapRow { start, ..row }),
+            }
+        }
+        self.rows = merged;
+    }
 }
 
 pub struct PingTask {
@@ -18,65 +297,14 @@ pub struct PingTask {
 
 impl PingTask {
     pub async fn run(self) {
-        let args: Vec<&str> = self.target.split_whitespace().collect();
-        let mut host_str = "";
-        let mut interval_ms = 1000;
-        let mut payload_size = 56;
-        
-        // Default sanity checks (though these are mutable based on input)
-        debug_assert!(interval_ms > 0);
-        debug_assert!(payload_size < 65535);
-
-        let mut count: Option<u64> = None;
-
-        let mut i = 0;
-        while i < args.len() {
-            match args[i] {
-                "-i" => {
-                    if i + 1 < args.len() {
-                        // Standard ping takes seconds (e.g., 0.2)
-                         if let Ok(v) = args[i+1].parse::<f64>() {
-                             interval_ms = (v * 1000.0) as u64;
-                         }
-                         i += 2;
-                    } else { i += 1; }
-                }
-                "-s" => {
-                     if i + 1 < args.len() {
-                         if let Ok(v) = args[i+1].parse::<usize>() {
-                             payload_size = v;
-                         }
-                         i += 2;
-                    } else { i += 1; }
-                }
-                "-c" => {
-                     if i + 1 < args.len() {
-                         if let Ok(v) = args[i+1].parse::<u64>() {
-                             count = Some(v);
-                         }
-                         i += 2;
-                    } else { i += 1; }
-                }
-                arg => {
-                    if !arg.starts_with("-") {
-                        host_str = arg;
-                    }
-                    i += 1;
-                }
+        let PingArgs { host: host_str, interval_ms, payload_size, count, probe, marking } = match parse_args(&self.target) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                let _ = self.tx.send(Err(e)).await;
+                return;
             }
-        }
-        
-        if host_str.is_empty() {
-             let _ = self.tx.send(Err("No target provided".to_string())).await;
-             return;
-        }
-
-        // Final sanity checks on parsed arguments
-        // Ensure interval is not zero to prevent busy loop (though sleep(0) yields)
-        if interval_ms == 0 { interval_ms = 100; } // Auto-correct or panic? implementation plan said assert.
-        // Let's assert! heavily here as requested for "definitive and reliable"
-        assert!(interval_ms > 0, "Ping interval must be positive");
-        assert!(payload_size <= 65507, "Ping payload size too large for IPv4"); // 65535 - 20 - 8
+        };
+        let host_str = host_str.as_str();
 
         // Hostname resolution
         let ip: IpAddr = match host_str.parse() {
@@ -89,11 +317,13 @@ impl PingTask {
                          if let Some(socket_addr) = addrs.next() {
                              socket_addr.ip()
                          } else {
+                             tracing::error!("Could not resolve {}", host_str);
                              let _ = self.tx.send(Err(format!("Could not resolve {}", host_str))).await;
                              return;
                          }
                     }
                     Err(e) => {
+                         tracing::error!("Could not resolve {}: {}", host_str, e);
                          let _ = self.tx.send(Err(format!("DNS Error: {}", e))).await;
                          return;
                     }
@@ -101,32 +331,58 @@ impl PingTask {
             }
         };
 
+        let mut pinger = None;
+        let mut marked = None;
+        if probe == PingProbe::Icmp && marking.is_set() {
+            match MarkedIcmp::open(ip, marking) {
+                Ok(socket) => marked = Some(socket),
+                Err(e) => {
+                    let _ = self.tx.send(Err(format!("Failed to open ICMP socket: {}", e))).await;
+                    return;
+                }
+            }
+        } else if probe == PingProbe::Icmp {
+            match icmp_client(ip) {
+                Ok(client) => {
+                    let mut p = client.pinger(ip, icmp_identifier()).await;
+                    p.timeout(PORT_TIMEOUT);
+                    pinger = Some(p);
+                }
+                Err(e) => {
+                    let _ = self.tx.send(Err(e)).await;
+                    return;
+                }
+            }
+        }
+        let payload = vec![0; payload_size];
+
         // Ping loop
         let mut seq = 0;
 
         loop {
-            match surge_ping::ping(ip, &vec![0; payload_size]).await {
-                Ok((icmp_packet, dur)) => {
-                    let ttl = match icmp_packet {
-                        IcmpPacket::V4(p) => p.get_ttl().unwrap_or(0),
-                        IcmpPacket::V6(p) => p.get_max_hop_limit(),
-                    };
-                    
-                    let result = PingResult {
-                        seq,
-                        ttl,
-                        time: dur,
-                        target: host_str.to_string(), // Return the hostname user typed
-                    };
-                    if self.tx.send(Ok(result)).await.is_err() {
-                        break;
-                    }
-                }
-                Err(e) => {
-                     if self.tx.send(Err(format!("Ping failed: {}", e))).await.is_err() {
-                        break;
-                    }
-                }
+            let result = match (probe, pinger.as_mut(), &marked) {
+                (PingProbe::Icmp, _, Some(marked)) => marked.ping(seq, payload_size).await,
+                (PingProbe::Icmp, Some(pinger), None) => pinger.ping(PingSequence(seq), &payload).await
+                    .map(|(icmp_packet, dur)| {
+                        let ttl = match icmp_packet {
+                            IcmpPacket::V4(p) => p.get_ttl().unwrap_or(0),
+                            IcmpPacket::V6(p) => p.get_max_hop_limit(),
+                        };
+                        (ttl, dur)
+                    })
+                    .map_err(|e| format!("Ping failed: {}", e)),
+                (PingProbe::Icmp, None, None) => Err("No ICMP socket".to_string()),
+                (PingProbe::Tcp(port) | PingProbe::Udp(port), _, _) => probe_port(SocketAddr::new(ip, port), probe, payload_size, marking).await.map(|dur| (0, dur)),
+            };
+            let result = result.map(|(ttl, time)| PingResult {
+                seq,
+                ttl,
+                time,
+                target: host_str.to_string(), // Return the hostname user typed
+                probe,
+            });
+            if self.tx.send(result).await.is_err() {
+                break;
             }
             seq = seq.wrapping_add(1);
             
@@ -136,7 +392,98 @@ impl PingTask {
                 }
             }
             
-            tokio::time::sleep(Duration::from_millis(interval_ms)).await;
+            if !shutdown::sleep_async(Duration::from_millis(interval_ms)).await {
+                break;
+            }
         }
     }
 }
+
+/// A ping input, parsed.
+struct PingArgs {
+    host: String,
+    interval_ms: u64,
+    payload_size: usize,
+    count: Option<u64>,
+    probe: PingProbe,
+    marking: Marking,
+}
+
+fn parse_args(input: &str) -> Result<PingArgs, String> {
+    let mut host = None;
+    let mut interval = Duration::from_secs(1);
+    let mut payload_size = 56;
+    let mut count = None;
+    let mut port: Option<u16> = None;
+    let mut udp = false;
+    let mut tcp = false;
+    let mut marking = Marking::default();
+
+    let mut args = Args::parse(input)?;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            // Standard ping takes seconds (e.g., 0.2)
+            "-i" => interval = args.seconds("-i")?,
+            "-s" => payload_size = args.value("-s", "a payload size in bytes")?,
+            "-c" => count = Some(args.positive("-c", "a count")?),
+            "-p" => port = Some(args.positive("-p", "a port")?),
+            "--tcp" => tcp = true,
+            "--udp" => udp = true,
+            "-Q" => marking.tos = Some(args.tos("-Q")?),
+            "-t" => marking.ttl = Some(args.positive("-t", "a TTL (1-255)")?),
+            flag if flag.starts_with('-') => return Err(args::unknown(flag)),
+            _ => host = Some(arg),
+        }
+    }
+    let host = host.ok_or("No target provided")?;
+    // 65535 - 20 - 8
+    if payload_size > 65507 {
+        return Err("-s can be at most 65507 bytes for IPv4".to_string());
+    }
+
+    let probe = match (port, tcp, udp) {
+        (Some(_), true, true) => return Err("Pick one of --tcp or --udp".to_string()),
+        (Some(p), _, true) => PingProbe::Udp(p),
+        (Some(p), _, false) => PingProbe::Tcp(p),
+        (None, false, false) => PingProbe::Icmp,
+        (None, _, _) => return Err("--tcp and --udp need a port (-p <port>)".to_string()),
+    };
+    // Sub-millisecond intervals would be a busy loop
+    let interval_ms = (interval.as_millis() as u64).max(1);
+    Ok(PingArgs { host, interval_ms, payload_size, count, probe, marking })
+}
+
+/// Time to complete a TCP handshake, or for a UDP service to answer a datagram of `payload_size`
+/// bytes. A refused port still proves the host is up, but there's no latency to chart.
+async fn probe_port(addr: SocketAddr, probe: PingProbe, payload_size: usize, marking: Marking) -> Result<Duration, String> {
+    let closed = |e: &std::io::Error| e.kind() == std::io::ErrorKind::ConnectionRefused;
+    if let PingProbe::Tcp(port) = probe {
+        let socket = marking.socket(addr.ip(), Type::STREAM, Protocol::TCP).map_err(|e| e.to_string())?;
+        socket.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let socket = tokio::net::TcpSocket::from_std_stream(socket.into());
+        let start = Instant::now();
+        return match tokio::time::timeout(PORT_TIMEOUT, socket.connect(addr)).await {
+            Ok(Ok(_)) => Ok(start.elapsed()),
+            Ok(Err(e)) if closed(&e) => Err(format!("tcp/{} closed (connection refused)", port)),
+            Ok(Err(e)) => Err(format!("Connect failed: {}", e)),
+            Err(_) => Err(format!("tcp/{} timed out", port)),
+        };
+    }
+
+    let bind: SocketAddr = if addr.is_ipv4() { (Ipv4Addr::UNSPECIFIED, 0).into() } else { (Ipv6Addr::UNSPECIFIED, 0).into() };
+    let socket = marking.socket(addr.ip(), Type::DGRAM, Protocol::UDP).map_err(|e| e.to_string())?;
+    socket.bind(&bind.into()).map_err(|e| e.to_string())?;
+    socket.set_nonblocking(true).map_err(|e| e.to_string())?;
+    let socket = tokio::net::UdpSocket::from_std(socket.into()).map_err(|e| e.to_string())?;
+    // Connected, so an ICMP port unreachable comes back as a refused recv
+    socket.connect(addr).await.map_err(|e| e.to_string())?;
+    let start = Instant::now();
+    socket.send(&vec![0; payload_size]).await.map_err(|e| format!("Send failed: {}", e))?;
+    let mut buf = [0u8; 1500];
+    match tokio::time::timeout(PORT_TIMEOUT, socket.recv(&mut buf)).await {
+        Ok(Ok(_)) => Ok(start.elapsed()),
+        Ok(Err(e)) if closed(&e) => Err(format!("udp/{} closed (port unreachable)", addr.port())),
+        Ok(Err(e)) => Err(format!("Receive failed: {}", e)),
+        Err(_) => Err(format!("udp/{} no reply", addr.port())),
+    }
+}
@@ -0,0 +1,127 @@
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crossbeam::channel::Sender;
+
+// Time a stopped tool gets to exit on SIGTERM before it is killed
+const GRACE: Duration = Duration::from_secs(2);
+/// Marks the lines a tool wrote to stderr among its output.
+pub const STDERR: &str = "ERR: ";
+
+/// An external tool's child process, shared between the thread waiting on it and the app,
+/// which can stop it. Stopping before the process has started kills it as soon as it does.
+#[derive(Clone, Default)]
+pub struct ProcessHandle {
+    child: Arc<Mutex<Option<Child>>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl ProcessHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn attach(&self, child: Child) {
+        *self.child.lock().unwrap_or_else(|e| e.into_inner()) = Some(child);
+        if self.stopped() {
+            self.terminate();
+        }
+    }
+
+    /// Waits for the process to exit, polling so `stop` can get at it meanwhile.
+    pub fn wait(&self) -> std::io::Result<ExitStatus> {
+        loop {
+            if let Some(child) = self.child.lock().unwrap_or_else(|e| e.into_inner()).as_mut()
+                && let Some(status) = child.try_wait()?
+            {
+                return Ok(status);
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    /// SIGTERM, then SIGKILL if it is still running after a grace period.
+    pub fn stop(&self) {
+        if !self.stopped.swap(true, Ordering::Relaxed) {
+            self.terminate();
+        }
+    }
+
+    pub fn stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    fn terminate(&self) {
+        let mut guard = self.child.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(child) = guard.as_mut() else { return };
+        if child.try_wait().ok().flatten().is_some() {
+            return;
+        }
+        if cfg!(unix) && Command::new("kill").args(["-TERM", &child.id().to_string()]).status().is_ok_and(|s| s.success()) {
+            drop(guard);
+            let handle = self.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(GRACE);
+                if let Some(child) = handle.child.lock().unwrap_or_else(|e| e.into_inner()).as_mut()
+                    && child.try_wait().ok().flatten().is_none()
+                {
+                    let _ = child.kill();
+                }
+            });
+        } else {
+            let _ = child.kill();
+        }
+    }
+}
+
+/// Runs `program` with `args` (no shell), sending each line it prints as it comes, stderr
+/// lines marked with `STDERR`. Returns `exit_result` once the process has exited.
+pub fn stream(program: &str, args: &[String], tx: &Sender<Result<String, String>>, process: &ProcessHandle) -> Result<String, String> {
+    let mut cmd = Command::new(program);
+    cmd.args(args).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = cmd.spawn().map_err(|e| format!("Failed to start {}: {}", program, e))?;
+    let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) else {
+        return Err(format!("Failed to read {}'s output", program));
+    };
+    process.attach(child);
+
+    let tx_out = tx.clone();
+    let stdout_reader = std::thread::spawn(move || {
+        for l in BufReader::new(stdout).lines().map_while(Result::ok) {
+            let _ = tx_out.send(Ok(l));
+        }
+    });
+
+    // Keeps the last line to explain a failed exit
+    let tx_err = tx.clone();
+    let stderr_reader = std::thread::spawn(move || {
+        let mut last = None;
+        for l in BufReader::new(stderr).lines().map_while(Result::ok) {
+            let _ = tx_err.send(Ok(format!("{}{}", STDERR, l)));
+            last = Some(l);
+        }
+        last
+    });
+
+    let status = process.wait();
+    let _ = stdout_reader.join();
+    let last_err = stderr_reader.join().ok().flatten();
+    exit_result(program, status, last_err, process.stopped())
+}
+
+/// `Done.` for a clean exit, the exit status for a run that was `stopped`, otherwise why the
+/// tool failed, in its own words when it gave any.
+pub fn exit_result(tool: &str, status: std::io::Result<ExitStatus>, last_err: Option<String>, stopped: bool) -> Result<String, String> {
+    match status {
+        Ok(status) if status.success() => Ok("Done.".to_string()),
+        Ok(status) if stopped => Ok(format!("{} stopped ({}).", tool, status)),
+        Ok(status) => Err(match last_err {
+            Some(line) => format!("{} failed ({}): {}", tool, status, line.trim()),
+            None => format!("{} failed ({})", tool, status),
+        }),
+        Err(e) => Err(format!("{} failed: {}", tool, e)),
+    }
+}
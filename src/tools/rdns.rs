@@ -0,0 +1,42 @@
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use crossbeam::channel::Sender;
+use hickory_resolver::TokioAsyncResolver;
+use hickory_resolver::config::{ResolverConfig, ResolverOpts};
+use tokio::sync::{mpsc, Semaphore};
+
+// Lookups in flight at once; the rest wait in the queue
+const WORKERS: usize = 8;
+
+/// Reverse DNS for peer IPs. Queued addresses are resolved by at most `WORKERS` lookups at a
+/// time; each answer, or `None` when there is no PTR record, comes back on the result channel.
+pub struct ReverseResolver {
+    queue: mpsc::UnboundedSender<IpAddr>,
+}
+
+impl ReverseResolver {
+    pub fn start(tx: Sender<(IpAddr, Option<String>)>) -> Self {
+        let (queue, mut requests) = mpsc::unbounded_channel::<IpAddr>();
+        tokio::spawn(async move {
+            let resolver = TokioAsyncResolver::tokio_from_system_conf()
+                .unwrap_or_else(|_| TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default()));
+            let workers = Arc::new(Semaphore::new(WORKERS));
+            while let Some(ip) = requests.recv().await {
+                let Ok(permit) = workers.clone().acquire_owned().await else { break };
+                let (resolver, tx) = (resolver.clone(), tx.clone());
+                tokio::spawn(async move {
+                    let name = resolver.reverse_lookup(ip).await.ok()
+                        .and_then(|names| names.iter().next().map(|n| n.to_utf8().trim_end_matches('.').to_string()));
+                    let _ = tx.send((ip, name));
+                    drop(permit);
+                });
+            }
+        });
+        Self { queue }
+    }
+
+    pub fn lookup(&self, ip: IpAddr) {
+        let _ = self.queue.send(ip);
+    }
+}
@@ -0,0 +1,176 @@
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crossbeam::channel::Sender;
+use tokio::sync::{mpsc, Semaphore};
+
+use crate::config::ReputationConfig;
+
+// AbuseIPDB lookups in flight at once; the rest wait in the queue
+const WORKERS: usize = 4;
+const TIMEOUT: Duration = Duration::from_secs(15);
+const ABUSEIPDB: &str = "https://api.abuseipdb.com/api/v2/check";
+
+/// A blocklist file's entries, as (network address, prefix length).
+#[derive(Debug, Clone)]
+pub struct Blocklist {
+    pub name: String,
+    nets: Vec<(IpAddr, u8)>,
+}
+
+impl Blocklist {
+    /// One address or CIDR prefix per line; `#` and `;` start comments, as in Spamhaus DROP,
+    /// FireHOL and most feeds. Lines that aren't addresses are skipped.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let nets = text.lines()
+            .filter_map(|line| line.split(['#', ';']).next())
+            .filter_map(|entry| parse_net(entry.trim()))
+            .collect();
+        let name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_else(|| path.display().to_string());
+        Ok(Self { name, nets })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        self.nets.iter().any(|(net, len)| in_net(ip, *net, *len))
+    }
+}
+
+fn parse_net(entry: &str) -> Option<(IpAddr, u8)> {
+    let entry = entry.split_whitespace().next()?;
+    match entry.split_once('/') {
+        Some((addr, len)) => {
+            let addr: IpAddr = addr.parse().ok()?;
+            let len: u8 = len.parse().ok()?;
+            (len <= if addr.is_ipv4() { 32 } else { 128 }).then_some((addr, len))
+        }
+        None => entry.parse().ok().map(|addr: IpAddr| (addr, if addr.is_ipv4() { 32 } else { 128 })),
+    }
+}
+
+fn in_net(ip: IpAddr, net: IpAddr, len: u8) -> bool {
+    match (ip.to_canonical(), net) {
+        (IpAddr::V4(ip), IpAddr::V4(net)) => {
+            let mask = u32::MAX.checked_shl(32 - len as u32).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(net) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(net)) => {
+            let mask = u128::MAX.checked_shl(128 - len as u32).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(net) & mask
+        }
+        _ => false,
+    }
+}
+
+/// What the configured feeds say about a peer.
+#[derive(Debug, Clone, Default)]
+pub struct Reputation {
+    /// AbuseIPDB's abuse confidence, 0-100
+    pub score: Option<u8>,
+    pub reports: u64,
+    /// Blocklist files the address is on
+    pub listed_in: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl Reputation {
+    /// On a blocklist, or scored at least `min_score` by AbuseIPDB.
+    pub fn flagged(&self, min_score: u8) -> bool {
+        !self.listed_in.is_empty() || self.score.is_some_and(|s| s >= min_score)
+    }
+
+    /// The Risk column: the score, or "list" for blocklist-only hits.
+    pub fn label(&self) -> String {
+        match (self.score, self.listed_in.is_empty()) {
+            (Some(score), true) => score.to_string(),
+            (Some(score), false) => format!("{}+list", score),
+            (None, false) => "list".to_string(),
+            (None, true) if self.error.is_some() => "?".to_string(),
+            (None, true) => "-".to_string(),
+        }
+    }
+
+    /// Why the peer is flagged, for alerts and copying.
+    pub fn describe(&self) -> String {
+        let mut parts = vec![];
+        if let Some(score) = self.score {
+            parts.push(format!("AbuseIPDB score {} ({} reports)", score, self.reports));
+        }
+        if !self.listed_in.is_empty() {
+            parts.push(format!("listed in {}", self.listed_in.join(", ")));
+        }
+        if let Some(e) = &self.error {
+            parts.push(e.clone());
+        }
+        parts.join("; ")
+    }
+}
+
+/// Checks peer IPs against the blocklist files, then AbuseIPDB when a key is configured.
+/// Answers come back on the result channel, like reverse DNS.
+pub struct ReputationChecker {
+    queue: mpsc::UnboundedSender<IpAddr>,
+}
+
+impl ReputationChecker {
+    /// Blocklists that fail to load are reported once and left out.
+    pub fn start(config: &ReputationConfig, tx: Sender<(IpAddr, Reputation)>) -> (Self, Vec<String>) {
+        let mut errors = vec![];
+        let lists: Vec<Blocklist> = config.blocklists.iter()
+            .filter_map(|path| Blocklist::load(path).map_err(|e| errors.push(e)).ok())
+            .collect();
+        let lists = Arc::new(lists);
+        let key = config.abuseipdb_key.clone().filter(|k| !k.is_empty());
+        let max_age_days = config.max_age_days;
+
+        let (queue, mut requests) = mpsc::unbounded_channel::<IpAddr>();
+        tokio::spawn(async move {
+            let client = reqwest::Client::builder().timeout(TIMEOUT).build().unwrap_or_default();
+            let workers = Arc::new(Semaphore::new(WORKERS));
+            while let Some(ip) = requests.recv().await {
+                let listed_in: Vec<String> = lists.iter().filter(|l| l.contains(ip)).map(|l| l.name.clone()).collect();
+                let Some(key) = key.clone() else {
+                    let _ = tx.send((ip, Reputation { listed_in, ..Default::default() }));
+                    continue;
+                };
+                let Ok(permit) = workers.clone().acquire_owned().await else { break };
+                let (client, tx) = (client.clone(), tx.clone());
+                tokio::spawn(async move {
+                    let mut reputation = Reputation { listed_in, ..Default::default() };
+                    match abuseipdb(&client, &key, ip, max_age_days).await {
+                        Ok((score, reports)) => (reputation.score, reputation.reports) = (Some(score), reports),
+                        Err(e) => reputation.error = Some(e),
+                    }
+                    let _ = tx.send((ip, reputation));
+                    drop(permit);
+                });
+            }
+        });
+        (Self { queue }, errors)
+    }
+
+    pub fn lookup(&self, ip: IpAddr) {
+        let _ = self.queue.send(ip);
+    }
+}
+
+async fn abuseipdb(client: &reqwest::Client, key: &str, ip: IpAddr, max_age_days: u32) -> Result<(u8, u64), String> {
+    let response = client.get(ABUSEIPDB)
+        .header("Key", key)
+        .header("Accept", "application/json")
+        .query(&[("ipAddress", ip.to_string()), ("maxAgeInDays", max_age_days.to_string())])
+        .send().await
+        .map_err(|e| format!("AbuseIPDB: {}", e))?;
+    match response.status().as_u16() {
+        401 | 403 => return Err("AbuseIPDB: key rejected".to_string()),
+        429 => return Err("AbuseIPDB: daily quota used up".to_string()),
+        s if !(200..300).contains(&s) => return Err(format!("AbuseIPDB: HTTP {}", s)),
+        _ => {}
+    }
+    let body: serde_json::Value = response.json().await.map_err(|e| format!("AbuseIPDB: bad answer: {}", e))?;
+    let data = &body["data"];
+    let score = data["abuseConfidenceScore"].as_u64().ok_or("AbuseIPDB: no score in the answer")?;
+    Ok((score.min(100) as u8, data["totalReports"].as_u64().unwrap_or(0)))
+}
@@ -1,4 +1,5 @@
 use pnet::datalink::{self, Channel};
+use pnet::packet::arp::{ArpOperations, ArpPacket};
 use pnet::packet::ethernet::{EtherTypes, EthernetPacket};
 use pnet::packet::ip::IpNextHeaderProtocols;
 use pnet::packet::ipv4::Ipv4Packet;
@@ -8,20 +9,121 @@ use pnet::packet::udp::UdpPacket;
 use pnet::packet::icmp::IcmpPacket;
 use pnet::packet::Packet;
 use crossbeam::channel::Sender;
+use crate::tools::capture_filter::CaptureFilter;
+use crate::tools::dhcp;
+use crate::tools::pcap::PcapRing;
+use crate::shutdown;
+use pnet::util::MacAddr;
+use std::collections::{HashMap, VecDeque};
+use std::net::Ipv4Addr;
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
+
+// A MAC change this soon after the old MAC was last heard is two devices claiming the IP, not a replaced one
+const BINDING_TTL: Duration = Duration::from_secs(300);
+// More gratuitous ARPs than this within the window is a storm
+const GRATUITOUS_STORM: usize = 20;
+const GRATUITOUS_WINDOW: Duration = Duration::from_secs(10);
+// Per IP (and for storms), an ARP alert is repeated at most this often
+const ARP_ALERT_EVERY: Duration = Duration::from_secs(60);
+// A packet trigger fires at most this often per interface; the recording it starts runs on
+const TRIGGER_EVERY: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Clone)]
 pub struct PacketSummary {
     pub time: String, // Simplified for now, could be SystemTime
     pub source: String,
+    /// Ethernet source, for the vendor lookup
+    pub source_mac: String,
     pub destination: String,
     pub protocol: String,
     pub length: String,
     pub info: String,
+    pub interface: String,
+    /// A DHCP client asking for a lease, for the device inventory
+    pub dhcp: Option<Box<dhcp::ClientHello>>,
+    pub icmp: Option<IcmpKind>,
+}
+
+/// An ICMP or ICMPv6 message as the statistics count it: the type, and the code for the
+/// types whose code says what went wrong.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IcmpKind {
+    pub v6: bool,
+    pub icmp_type: u8,
+    pub code: u8,
+}
+
+impl IcmpKind {
+    pub fn new(v6: bool, icmp_type: u8, code: u8) -> Self {
+        let keeps_code = matches!((v6, icmp_type), (false, 3 | 5 | 11) | (true, 1 | 3));
+        Self { v6, icmp_type, code: if keeps_code { code } else { 0 } }
+    }
+
+    pub fn label(&self) -> String {
+        let code = self.code;
+        match (self.v6, self.icmp_type) {
+            (false, 0) | (true, 129) => "Echo reply".to_string(),
+            (false, 8) | (true, 128) => "Echo request".to_string(),
+            (false, 3) => format!("Unreachable: {}", match code {
+                0 => "net".to_string(),
+                1 => "host".to_string(),
+                2 => "protocol".to_string(),
+                3 => "port".to_string(),
+                4 => "frag needed".to_string(),
+                6 | 7 => "unknown dest".to_string(),
+                9 | 10 | 13 => "prohibited".to_string(),
+                c => format!("code {}", c),
+            }),
+            (true, 1) => format!("Unreachable: {}", match code {
+                0 => "no route".to_string(),
+                1 => "prohibited".to_string(),
+                3 => "address".to_string(),
+                4 => "port".to_string(),
+                5 | 6 => "policy".to_string(),
+                c => format!("code {}", c),
+            }),
+            (false, 5) => format!("Redirect: {}", match code {
+                0 | 2 => "net",
+                _ => "host",
+            }),
+            (true, 137) => "Redirect".to_string(),
+            (false, 11) | (true, 3) => if code == 0 { "Time exceeded: TTL".to_string() } else { "Time exceeded: reassembly".to_string() },
+            (true, 2) => "Packet too big".to_string(),
+            (false, 12) | (true, 4) => "Parameter problem".to_string(),
+            (true, 133) => "Router solicitation".to_string(),
+            (true, 134) => "Router advertisement".to_string(),
+            (true, 135) => "Neighbor solicitation".to_string(),
+            (true, 136) => "Neighbor advertisement".to_string(),
+            (true, 143) => "MLDv2 report".to_string(),
+            (_, t) => format!("Type {}", t),
+        }
+    }
+
+    /// Unreachables, redirects and expired TTLs: a burst of them is worth a look.
+    pub fn is_error(&self) -> bool {
+        matches!((self.v6, self.icmp_type), (false, 3 | 5 | 11 | 12) | (true, 1 | 2 | 3 | 4 | 137))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SnifferEvent {
+    Packet(PacketSummary),
+    /// Capture on one interface couldn't start; the others keep going
+    Error(String),
+    /// An IP claimed by two MACs, or a burst of gratuitous ARPs; checked whatever the filter
+    ArpAlert(String),
+    /// A packet matched a capture trigger's filter (the trigger's text, and the packet)
+    Trigger(String, String),
 }
 
 pub struct Sniffer {
-    pub should_stop: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    /// Bumped by every start and stop; a capture thread exits once it no longer matches its own.
+    generation: Arc<std::sync::atomic::AtomicU64>,
+    capture_threads: Vec<thread::JoinHandle<()>>,
+    /// Matching frames are written here while it's set; kept across sessions
+    pub ring: Arc<Mutex<Option<PcapRing>>>,
     pub packet_count: std::sync::Arc<std::sync::atomic::AtomicU64>,
     pub in_packets: std::sync::Arc<std::sync::atomic::AtomicU64>,
     pub out_packets: std::sync::Arc<std::sync::atomic::AtomicU64>,
@@ -35,12 +137,15 @@ pub struct Sniffer {
     // Protocol Counters
     pub tcp_packets: std::sync::Arc<std::sync::atomic::AtomicU64>,
     pub udp_packets: std::sync::Arc<std::sync::atomic::AtomicU64>,
+    pub icmp_packets: std::sync::Arc<std::sync::atomic::AtomicU64>,
 }
 
 impl Sniffer {
     pub fn new() -> Self {
         Self {
-            should_stop: std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            generation: Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            capture_threads: Vec::new(),
+            ring: Arc::new(Mutex::new(None)),
             packet_count: std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0)),
             in_packets: std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0)),
             out_packets: std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0)),
@@ -50,174 +155,282 @@ impl Sniffer {
             lan_out_bytes: std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0)),
             tcp_packets: std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0)),
             udp_packets: std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            icmp_packets: std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0)),
         }
     }
 
-    pub fn start(&self, interface_name: String, tx: Sender<PacketSummary>, filter: String) {
-        let should_stop = self.should_stop.clone();
-        let packet_count = self.packet_count.clone();
-        let in_packets = self.in_packets.clone();
-        let out_packets = self.out_packets.clone();
-        let wan_in = self.wan_in_bytes.clone();
-        let wan_out = self.wan_out_bytes.clone();
-        let lan_in = self.lan_in_bytes.clone();
-        let lan_out = self.lan_out_bytes.clone();
-        let tcp_count = self.tcp_packets.clone();
-        let udp_count = self.udp_packets.clone();
-        
-        should_stop.store(false, std::sync::atomic::Ordering::Relaxed);
-        
-        // Lowercase filter for case-insensitive match
-        let filter = filter.trim().to_lowercase();
-        
-        thread::spawn(move || {
-            let interfaces = datalink::interfaces();
-            let interface = interfaces
-                .into_iter()
-                .find(|iface| iface.name == interface_name)
-                .expect("Interface not found");
-            
-            // Get local IPs and Network info
-            let local_ips: Vec<std::net::IpAddr> = interface.ips.iter().map(|ip| ip.ip()).collect();
-            let networks: Vec<(std::net::IpAddr, std::net::IpAddr)> = interface.ips.iter().map(|ip| (ip.ip(), ip.mask())).collect();
+    /// Captures on each named interface in its own thread; all feed the same counters and channel.
+    /// Every start is a new session with zeroed counters; its threads bind only after the
+    /// previous session's threads have exited, and those keep writing to the old counters.
+    pub fn start(&mut self, interface_names: Vec<String>, tx: Sender<SnifferEvent>, filter: CaptureFilter, triggers: Vec<(String, CaptureFilter)>) {
+        let previous = std::mem::replace(self, Self::new());
+        self.generation = previous.generation.clone();
+        self.ring = previous.ring.clone();
+        let session = self.generation.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+        // The first new thread to take the lock joins them all; the rest wait on the lock
+        let previous_threads = Arc::new(Mutex::new(previous.capture_threads));
+
+        for interface_name in interface_names {
+            let generation = self.generation.clone();
+            let previous_threads = previous_threads.clone();
+            let packet_count = self.packet_count.clone();
+            let in_packets = self.in_packets.clone();
+            let out_packets = self.out_packets.clone();
+            let wan_in = self.wan_in_bytes.clone();
+            let wan_out = self.wan_out_bytes.clone();
+            let lan_in = self.lan_in_bytes.clone();
+            let lan_out = self.lan_out_bytes.clone();
+            let tcp_count = self.tcp_packets.clone();
+            let udp_count = self.udp_packets.clone();
+            let icmp_count = self.icmp_packets.clone();
+            let tx = tx.clone();
+            let filter = filter.clone();
+            let triggers = triggers.clone();
+            let ring = self.ring.clone();
             
-            let (_, mut rx) = match datalink::channel(&interface, Default::default()) {
-                Ok(Channel::Ethernet(tx, rx)) => (tx, rx),
-                Ok(_) => panic!("Unhandled channel type"),
-                Err(e) => {
-                    let _ = tx.send(PacketSummary {
-                        time: "Error".to_string(),
-                        source: "-".to_string(),
-                        destination: "-".to_string(),
-                        protocol: "ERR".to_string(),
-                        length: "0".to_string(),
-                        info: format!("Failed to create channel: {}", e),
-                    });
+            self.capture_threads.push(thread::spawn(move || {
+                for handle in previous_threads.lock().unwrap_or_else(|e| e.into_inner()).drain(..) {
+                    let _ = handle.join();
+                }
+                if generation.load(std::sync::atomic::Ordering::Relaxed) != session {
                     return;
                 }
-            };
 
-            loop {
-                if should_stop.load(std::sync::atomic::Ordering::Relaxed) {
-                    break;
-                }
+                let interfaces = datalink::interfaces();
+                let Some(interface) = interfaces.into_iter().find(|iface| iface.name == interface_name) else {
+                    let _ = tx.send(SnifferEvent::Error(format!("{}: interface not found", interface_name)));
+                    return;
+                };
+            
+                // Get local IPs and Network info
+                let local_ips: Vec<std::net::IpAddr> = interface.ips.iter().map(|ip| ip.ip()).collect();
+                let networks: Vec<(std::net::IpAddr, std::net::IpAddr)> = interface.ips.iter().map(|ip| (ip.ip(), ip.mask())).collect();
+            
+                // Time out reads so the thread notices a stop on a quiet interface
+                let config = datalink::Config { read_timeout: Some(Duration::from_millis(200)), ..Default::default() };
+                let (_, mut rx) = match datalink::channel(&interface, config) {
+                    Ok(Channel::Ethernet(tx, rx)) => (tx, rx),
+                    Ok(_) => {
+                        let _ = tx.send(SnifferEvent::Error(format!("{}: unsupported capture channel type", interface_name)));
+                        return;
+                    }
+                    Err(e) => {
+                        let _ = tx.send(SnifferEvent::Error(format!("{}: failed to open capture channel: {}", interface_name, e)));
+                        return;
+                    }
+                };
+
+                let mut arp_watch = ArpWatch::default();
+                let mut triggered: Option<Instant> = None;
+                loop {
+                    if generation.load(std::sync::atomic::Ordering::Relaxed) != session || shutdown::requested() {
+                        break;
+                    }
                 
-                match rx.next() {
-                    Ok(packet) => {
-                        packet_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                        let packet_len = packet.len() as u64;
-                        let packet = EthernetPacket::new(packet).unwrap();
+                    match rx.next() {
+                        Ok(packet) => {
+                            packet_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                            let packet_len = packet.len() as u64;
+                            let packet = EthernetPacket::new(packet).unwrap();
                         
-                        // Direction & LAN/WAN Detection
-                        let mut is_inbound = false;
-                        let mut is_lan = false; // Default to WAN if not found in local net
+                            // Direction & LAN/WAN Detection
+                            let mut is_inbound = false;
+                            let mut is_lan = false; // Default to WAN if not found in local net
                         
-                        match packet.get_ethertype() {
-                             EtherTypes::Ipv4 => {
-                                if let Some(header) = Ipv4Packet::new(packet.payload()) {
-                                    match header.get_next_level_protocol() {
-                                        IpNextHeaderProtocols::Tcp => {
-                                            tcp_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                                        },
-                                        IpNextHeaderProtocols::Udp => {
-                                            udp_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                                        },
-                                        _ => {}
+                            match packet.get_ethertype() {
+                                 EtherTypes::Arp => {
+                                    if let Some(arp) = ArpPacket::new(packet.payload()) {
+                                        for alert in arp_watch.observe(&arp) {
+                                            let _ = tx.send(SnifferEvent::ArpAlert(format!("{}: {}", interface_name, alert)));
+                                        }
                                     }
+                                 },
+                                 EtherTypes::Ipv4 => {
+                                    if let Some(header) = Ipv4Packet::new(packet.payload()) {
+                                        match header.get_next_level_protocol() {
+                                            IpNextHeaderProtocols::Tcp => {
+                                                tcp_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                                            },
+                                            IpNextHeaderProtocols::Udp => {
+                                                udp_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                                            },
+                                            IpNextHeaderProtocols::Icmp => {
+                                                icmp_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                                            },
+                                            _ => {}
+                                        }
 
-                                    let source = std::net::IpAddr::V4(header.get_source());
-                                    let dest = std::net::IpAddr::V4(header.get_destination());
+                                        let source = std::net::IpAddr::V4(header.get_source());
+                                        let dest = std::net::IpAddr::V4(header.get_destination());
                                     
-                                    if local_ips.contains(&dest) {
-                                        is_inbound = true;
-                                        // Check if source is in same subnet
-                                        for (ip, mask) in &networks {
-                                            if let (std::net::IpAddr::V4(src_ip), std::net::IpAddr::V4(net_ip), std::net::IpAddr::V4(net_mask)) = (source, ip, mask) {
-                                                 // Simple check: apply mask
-                                                 let src_u32 = u32::from(src_ip);
-                                                 let net_u32 = u32::from(*net_ip);
-                                                 let mask_u32 = u32::from(*net_mask);
-                                                 if (src_u32 & mask_u32) == (net_u32 & mask_u32) {
-                                                     is_lan = true;
-                                                     break;
-                                                 }
+                                        if local_ips.contains(&dest) {
+                                            is_inbound = true;
+                                            // Check if source is in same subnet
+                                            for (ip, mask) in &networks {
+                                                if let (std::net::IpAddr::V4(src_ip), std::net::IpAddr::V4(net_ip), std::net::IpAddr::V4(net_mask)) = (source, ip, mask) {
+                                                     // Simple check: apply mask
+                                                     let src_u32 = u32::from(src_ip);
+                                                     let net_u32 = u32::from(*net_ip);
+                                                     let mask_u32 = u32::from(*net_mask);
+                                                     if (src_u32 & mask_u32) == (net_u32 & mask_u32) {
+                                                         is_lan = true;
+                                                         break;
+                                                     }
+                                                }
                                             }
-                                        }
-                                    } else if local_ips.contains(&source) {
-                                        // Outbound
-                                        // Check if dest is in same subnet
-                                         for (ip, mask) in &networks {
-                                            if let (std::net::IpAddr::V4(dst_ip), std::net::IpAddr::V4(net_ip), std::net::IpAddr::V4(net_mask)) = (dest, ip, mask) {
-                                                 let dst_u32 = u32::from(dst_ip);
-                                                 let net_u32 = u32::from(*net_ip);
-                                                 let mask_u32 = u32::from(*net_mask);
-                                                 if (dst_u32 & mask_u32) == (net_u32 & mask_u32) {
-                                                     is_lan = true;
-                                                     break;
-                                                 }
+                                        } else if local_ips.contains(&source) {
+                                            // Outbound
+                                            // Check if dest is in same subnet
+                                             for (ip, mask) in &networks {
+                                                if let (std::net::IpAddr::V4(dst_ip), std::net::IpAddr::V4(net_ip), std::net::IpAddr::V4(net_mask)) = (dest, ip, mask) {
+                                                     let dst_u32 = u32::from(dst_ip);
+                                                     let net_u32 = u32::from(*net_ip);
+                                                     let mask_u32 = u32::from(*net_mask);
+                                                     if (dst_u32 & mask_u32) == (net_u32 & mask_u32) {
+                                                         is_lan = true;
+                                                         break;
+                                                     }
+                                                }
                                             }
                                         }
                                     }
-                                }
-                             },
-                             // Skipping IPv6 complexity for MVP brevity, defaulting to WAN/Inbound check similar to IPv4 if needed
-                             // But let's just use existing inbound check for v6 and assume WAN for now
-                              EtherTypes::Ipv6 => {
-                                 if let Some(header) = Ipv6Packet::new(packet.payload()) {
-                                    let dest = std::net::IpAddr::V6(header.get_destination());
-                                    if local_ips.contains(&dest) {
-                                        is_inbound = true;
+                                 },
+                                 // Skipping IPv6 complexity for MVP brevity, defaulting to WAN/Inbound check similar to IPv4 if needed
+                                 // But let's just use existing inbound check for v6 and assume WAN for now
+                                  EtherTypes::Ipv6 => {
+                                     if let Some(header) = Ipv6Packet::new(packet.payload()) {
+                                        let counter = match header.get_next_header() {
+                                            IpNextHeaderProtocols::Tcp => Some(&tcp_count),
+                                            IpNextHeaderProtocols::Udp => Some(&udp_count),
+                                            IpNextHeaderProtocols::Icmpv6 => Some(&icmp_count),
+                                            _ => None,
+                                        };
+                                        if let Some(counter) = counter {
+                                            counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                                        }
+                                        let dest = std::net::IpAddr::V6(header.get_destination());
+                                        if local_ips.contains(&dest) {
+                                            is_inbound = true;
+                                        }
                                     }
-                                }
-                             },
-                             _ => {}
-                        }
-                        
-                        if is_inbound {
-                            in_packets.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                            if is_lan {
-                                lan_in.fetch_add(packet_len, std::sync::atomic::Ordering::Relaxed);
-                            } else {
-                                wan_in.fetch_add(packet_len, std::sync::atomic::Ordering::Relaxed);
+                                 },
+                                 _ => {}
                             }
-                        } else {
-                            out_packets.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                             if is_lan {
-                                lan_out.fetch_add(packet_len, std::sync::atomic::Ordering::Relaxed);
+                        
+                            if is_inbound {
+                                in_packets.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                                if is_lan {
+                                    lan_in.fetch_add(packet_len, std::sync::atomic::Ordering::Relaxed);
+                                } else {
+                                    wan_in.fetch_add(packet_len, std::sync::atomic::Ordering::Relaxed);
+                                }
                             } else {
-                                wan_out.fetch_add(packet_len, std::sync::atomic::Ordering::Relaxed);
+                                out_packets.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                                 if is_lan {
+                                    lan_out.fetch_add(packet_len, std::sync::atomic::Ordering::Relaxed);
+                                } else {
+                                    wan_out.fetch_add(packet_len, std::sync::atomic::Ordering::Relaxed);
+                                }
                             }
-                        }
 
-                        let summary = parse_packet(&packet);
-                        if let Some(s) = summary {
-                            // Filter Logic
-                            let mut matches = true;
-                            if !filter.is_empty() {
-                                matches = s.source.to_lowercase().contains(&filter) ||
-                                          s.destination.to_lowercase().contains(&filter) ||
-                                          s.protocol.to_lowercase().contains(&filter) ||
-                                          s.info.to_lowercase().contains(&filter);
-                            }
-                            
-                            if matches {
-                                if tx.send(s).is_err() {
-                                    break;
+                            let summary = parse_packet(&packet);
+                            if let Some(mut s) = summary {
+                                s.interface = interface_name.clone();
+                                if triggered.is_none_or(|at| at.elapsed() >= TRIGGER_EVERY)
+                                    && let Some((name, _)) = triggers.iter().find(|(_, t)| t.matches(&packet, &s))
+                                {
+                                    triggered = Some(Instant::now());
+                                    let what = format!("{} {} > {} {}", s.interface, s.source, s.destination, s.info);
+                                    let _ = tx.send(SnifferEvent::Trigger(name.clone(), what));
+                                }
+                                if filter.matches(&packet, &s) {
+                                    let mut ring = ring.lock().unwrap_or_else(|e| e.into_inner());
+                                    if let Some(writer) = ring.as_mut()
+                                        && let Err(e) = writer.write(packet.packet())
+                                    {
+                                        // A full disk shouldn't stop the capture; recording does
+                                        *ring = None;
+                                        let _ = tx.send(SnifferEvent::Error(format!("Recording stopped: {}", e)));
+                                    }
+                                    drop(ring);
+                                    if tx.send(SnifferEvent::Packet(s)).is_err() {
+                                        break;
+                                    }
                                 }
                             }
                         }
-                    }
-                    Err(_e) => {
-                        // eprintln!("An error occurred while reading: {}", e);
+                        Err(_e) => {
+                            // Read timeouts land here too; eprintln!("An error occurred while reading: {}", e);
+                        }
                     }
                 }
-            }
-        });
+            }));
+        }
     }
 
     pub fn stop(&self) {
-        self.should_stop.store(true, std::sync::atomic::Ordering::Relaxed);
+        self.generation.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
     }
+
+    /// Whether any capture thread of the current session is still alive.
+    pub fn running(&self) -> bool {
+        self.capture_threads.iter().any(|t| !t.is_finished())
+    }
+}
+
+/// IP-to-MAC bindings and gratuitous ARPs seen on one interface.
+#[derive(Default)]
+struct ArpWatch {
+    bindings: HashMap<Ipv4Addr, (MacAddr, Instant)>,
+    gratuitous: VecDeque<Instant>,
+    /// Last alert per IP; `None` for storms
+    alerted: HashMap<Option<Ipv4Addr>, Instant>,
+}
+
+impl ArpWatch {
+    /// Returns the alerts this packet raises.
+    fn observe(&mut self, arp: &ArpPacket) -> Vec<String> {
+        let (ip, mac) = (arp.get_sender_proto_addr(), arp.get_sender_hw_addr());
+        // ARP probes (RFC 5227) come from 0.0.0.0 while an address is being checked
+        if ip.is_unspecified() {
+            return vec![];
+        }
+        let now = Instant::now();
+        let mut alerts = vec![];
+        if is_gratuitous(arp) {
+            self.gratuitous.push_back(now);
+            while self.gratuitous.front().is_some_and(|t| now.duration_since(*t) > GRATUITOUS_WINDOW) {
+                self.gratuitous.pop_front();
+            }
+            if self.gratuitous.len() > GRATUITOUS_STORM && self.may_alert(None, now) {
+                alerts.push(format!("Gratuitous ARP storm: {} in {}s, latest {} is at {}", self.gratuitous.len(), GRATUITOUS_WINDOW.as_secs(), ip, mac));
+            }
+        }
+        if let Some((previous, at)) = self.bindings.insert(ip, (mac, now))
+            && previous != mac
+            && now.duration_since(at) < BINDING_TTL
+            && self.may_alert(Some(ip), now)
+        {
+            alerts.push(format!("ARP conflict: {} claimed by {} and {} (possible spoofing)", ip, previous, mac));
+        }
+        alerts
+    }
+
+    fn may_alert(&mut self, key: Option<Ipv4Addr>, now: Instant) -> bool {
+        match self.alerted.get(&key) {
+            Some(at) if now.duration_since(*at) < ARP_ALERT_EVERY => false,
+            _ => {
+                self.alerted.insert(key, now);
+                true
+            }
+        }
+    }
+}
+
+/// An announcement nobody asked for: a request for the sender's own IP, or a reply to broadcast.
+fn is_gratuitous(arp: &ArpPacket) -> bool {
+    arp.get_sender_proto_addr() == arp.get_target_proto_addr()
+        || arp.get_operation() == ArpOperations::Reply && arp.get_target_hw_addr() == MacAddr::broadcast()
 }
 
 fn parse_packet(ethernet: &EthernetPacket) -> Option<PacketSummary> {
@@ -235,6 +448,8 @@ fn parse_packet(ethernet: &EthernetPacket) -> Option<PacketSummary> {
                     _ => "IPv4",
                 };
                 
+                let mut dhcp = None;
+                let mut icmp_kind = None;
                 let (info, proto_detail) = match header.get_next_level_protocol() {
                     IpNextHeaderProtocols::Tcp => {
                         if let Some(tcp) = TcpPacket::new(header.payload()) {
@@ -245,14 +460,20 @@ fn parse_packet(ethernet: &EthernetPacket) -> Option<PacketSummary> {
                     },
                      IpNextHeaderProtocols::Udp => {
                         if let Some(udp) = UdpPacket::new(header.payload()) {
-                            (format!("{} -> {} [Len={}]", udp.get_source(), udp.get_destination(), udp.get_length()), "UDP")
+                            dhcp = (udp.get_destination() == 67).then(|| dhcp::parse_client(udp.payload())).flatten().map(Box::new);
+                            match &dhcp {
+                                Some(hello) => (format!("DHCP request from {}", hello.hostname.as_deref().unwrap_or("(no hostname)")), "DHCP"),
+                                None => (format!("{} -> {} [Len={}]", udp.get_source(), udp.get_destination(), udp.get_length()), "UDP"),
+                            }
                         } else {
                             ("Malformed UDP".to_string(), "UDP")
                         }
                     },
                     IpNextHeaderProtocols::Icmp => {
                          if let Some(icmp) = IcmpPacket::new(header.payload()) {
-                            (format!("Type={:?} Code={:?}", icmp.get_icmp_type(), icmp.get_icmp_code()), "ICMP")
+                            let kind = IcmpKind::new(false, icmp.get_icmp_type().0, icmp.get_icmp_code().0);
+                            icmp_kind = Some(kind);
+                            (kind.label(), "ICMP")
                         } else {
                             ("Malformed ICMP".to_string(), "ICMP")
                         }
@@ -263,10 +484,14 @@ fn parse_packet(ethernet: &EthernetPacket) -> Option<PacketSummary> {
                 Some(PacketSummary {
                     time,
                     source,
+                    source_mac: ethernet.get_source().to_string(),
                     destination: dest,
                     protocol: proto_detail.to_string(),
                     length: format!("{}", header.get_total_length()),
                     info,
+                    interface: String::new(), // Filled in by the capture thread
+                    dhcp,
+                    icmp: icmp_kind,
                 })
             } else {
                 None
@@ -276,24 +501,56 @@ fn parse_packet(ethernet: &EthernetPacket) -> Option<PacketSummary> {
             if let Some(header) = Ipv6Packet::new(ethernet.payload()) {
                  let source = header.get_source().to_string();
                 let dest = header.get_destination().to_string();
+                // Type and code lead the ICMPv6 header
+                let icmp = match (header.get_next_header(), header.payload()) {
+                    (IpNextHeaderProtocols::Icmpv6, [icmp_type, code, ..]) => Some(IcmpKind::new(true, *icmp_type, *code)),
+                    _ => None,
+                };
                  let (info, proto_detail) = match header.get_next_header() {
                     IpNextHeaderProtocols::Tcp => ("TCP (IPv6)".to_string(), "TCP"),
                     IpNextHeaderProtocols::Udp => ("UDP (IPv6)".to_string(), "UDP"),
-                    IpNextHeaderProtocols::Icmpv6 => ("ICMPv6".to_string(), "ICMPv6"),
+                    IpNextHeaderProtocols::Icmpv6 => (icmp.map_or("ICMPv6".to_string(), |k| k.label()), "ICMPv6"),
                     _ => ("IPv6".to_string(), "IPv6"),
                 };
                  Some(PacketSummary {
                     time,
                     source,
+                    source_mac: ethernet.get_source().to_string(),
                     destination: dest,
                     protocol: proto_detail.to_string(),
                     length: format!("{}", header.get_payload_length()),
                     info,
+                    interface: String::new(),
+                    dhcp: None,
+                    icmp,
                 })
             } else {
                  None
             }
         }
+        EtherTypes::Arp => {
+            let arp = ArpPacket::new(ethernet.payload())?;
+            let (sender, target) = (arp.get_sender_proto_addr(), arp.get_target_proto_addr());
+            let info = if is_gratuitous(&arp) {
+                format!("Gratuitous ARP: {} is at {}", sender, arp.get_sender_hw_addr())
+            } else if arp.get_operation() == ArpOperations::Reply {
+                format!("{} is at {}", sender, arp.get_sender_hw_addr())
+            } else {
+                format!("Who has {}? Tell {}", target, sender)
+            };
+            Some(PacketSummary {
+                time,
+                source: sender.to_string(),
+                source_mac: ethernet.get_source().to_string(),
+                destination: target.to_string(),
+                protocol: "ARP".to_string(),
+                length: format!("{}", ethernet.payload().len()),
+                info,
+                interface: String::new(),
+                dhcp: None,
+                icmp: None,
+            })
+        }
         _ => None // Ignore non-IP for simplicity in MVP
     }
 }
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crossbeam::channel::Sender;
+
+use crate::shutdown;
+
+const SSDP_ADDR: Ipv4Addr = Ipv4Addr::new(239, 255, 255, 250);
+const SSDP_PORT: u16 = 1900;
+const SEARCH_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Default)]
+pub struct SsdpDevice {
+    pub location: String,
+    pub ip: Option<IpAddr>,
+    pub server: String,
+    pub usn: String,
+    pub service_types: Vec<String>,
+    pub friendly_name: String,
+    pub manufacturer: String,
+    pub model: String,
+    pub presentation_url: String,
+    pub last_seen: Option<Instant>,
+}
+
+impl SsdpDevice {
+    /// Merges a newer sighting, keeping description fields already fetched.
+    pub fn merge(&mut self, newer: SsdpDevice) {
+        for st in newer.service_types {
+            if !self.service_types.contains(&st) {
+                self.service_types.push(st);
+            }
+        }
+        if !newer.friendly_name.is_empty() { self.friendly_name = newer.friendly_name; }
+        if !newer.manufacturer.is_empty() { self.manufacturer = newer.manufacturer; }
+        if !newer.model.is_empty() { self.model = newer.model; }
+        if !newer.presentation_url.is_empty() { self.presentation_url = newer.presentation_url; }
+        self.last_seen = newer.last_seen.or(self.last_seen);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SsdpEvent {
+    Device(Box<SsdpDevice>),
+    Error(String),
+}
+
+pub struct SsdpTask {
+    pub should_stop: Arc<AtomicBool>,
+}
+
+impl SsdpTask {
+    pub fn new() -> Self {
+        Self {
+            should_stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn start(&mut self, tx: Sender<SsdpEvent>) {
+        // A fresh flag, so the last search stays stopped even if it's still winding down
+        *self = Self::new();
+        let should_stop = self.should_stop.clone();
+
+        std::thread::spawn(move || {
+            let socket = match UdpSocket::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))) {
+                Ok(s) => s,
+                Err(e) => {
+                    let _ = tx.send(SsdpEvent::Error(format!("Failed to open SSDP socket: {}", e)));
+                    return;
+                }
+            };
+            let _ = socket.set_read_timeout(Some(Duration::from_millis(250)));
+            let _ = socket.set_multicast_ttl_v4(2);
+
+            // Devices keyed by LOCATION (one description per root device)
+            let mut devices: HashMap<String, SsdpDevice> = HashMap::new();
+            let mut last_search: Option<Instant> = None;
+            let mut buf = [0u8; 4096];
+
+            loop {
+                if shutdown::stopping(&should_stop) {
+                    break;
+                }
+
+                if last_search.is_none_or(|t| t.elapsed() >= SEARCH_INTERVAL) {
+                    let msearch = format!(
+                        "M-SEARCH * HTTP/1.1\r\nHOST: {}:{}\r\nMAN: \"ssdp:discover\"\r\nMX: 2\r\nST: ssdp:all\r\n\r\n",
+                        SSDP_ADDR, SSDP_PORT
+                    );
+                    if let Err(e) = socket.send_to(msearch.as_bytes(), SocketAddr::from((SSDP_ADDR, SSDP_PORT))) {
+                        let _ = tx.send(SsdpEvent::Error(format!("M-SEARCH failed: {}", e)));
+                    }
+                    last_search = Some(Instant::now());
+                }
+
+                let Ok((len, from)) = socket.recv_from(&mut buf) else { continue };
+                let headers = parse_headers(&String::from_utf8_lossy(&buf[..len]));
+                let Some(location) = headers.get("location").cloned() else { continue };
+
+                let st = headers.get("st").cloned().unwrap_or_default();
+                let is_new = !devices.contains_key(&location);
+                let device = devices.entry(location.clone()).or_insert_with(|| SsdpDevice {
+                    location: location.clone(),
+                    ip: Some(from.ip()),
+                    server: headers.get("server").cloned().unwrap_or_default(),
+                    usn: headers.get("usn").cloned().unwrap_or_default(),
+                    ..Default::default()
+                });
+                if !st.is_empty() && !device.service_types.contains(&st) {
+                    device.service_types.push(st);
+                }
+                device.last_seen = Some(Instant::now());
+
+                if is_new {
+                    // Fetch the description XML without blocking the receive loop
+                    let mut snapshot = device.clone();
+                    let tx_desc = tx.clone();
+                    std::thread::spawn(move || {
+                        if let Ok(xml) = http_get(&snapshot.location) {
+                            apply_description(&mut snapshot, &xml);
+                        }
+                        let _ = tx_desc.send(SsdpEvent::Device(Box::new(snapshot)));
+                    });
+                } else if tx.send(SsdpEvent::Device(Box::new(device.clone()))).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    pub fn stop(&self) {
+        self.should_stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn parse_headers(response: &str) -> HashMap<String, String> {
+    response.lines().skip(1).filter_map(|line| {
+        let (k, v) = line.split_once(':')?;
+        Some((k.trim().to_lowercase(), v.trim().to_string()))
+    }).collect()
+}
+
+fn apply_description(device: &mut SsdpDevice, xml: &str) {
+    device.friendly_name = extract_tag(xml, "friendlyName").unwrap_or_default();
+    device.manufacturer = extract_tag(xml, "manufacturer").unwrap_or_default();
+    device.model = [extract_tag(xml, "modelName"), extract_tag(xml, "modelNumber")]
+        .into_iter().flatten().collect::<Vec<_>>().join(" ");
+
+    if let Some(url) = extract_tag(xml, "presentationURL") {
+        let base = extract_tag(xml, "URLBase").unwrap_or_else(|| device.location.clone());
+        device.presentation_url = resolve_url(&base, &url);
+    }
+}
+
+fn extract_tag(xml: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = xml.find(&open)? + open.len();
+    let end = xml[start..].find(&close)? + start;
+    let value = xml[start..end].trim();
+    if value.is_empty() { None } else { Some(value.replace("&amp;", "&")) }
+}
+
+fn resolve_url(base: &str, url: &str) -> String {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        return url.to_string();
+    }
+    // Keep scheme://host:port from base
+    let origin = base.find("://")
+        .and_then(|i| base[i + 3..].find('/').map(|j| &base[..i + 3 + j]))
+        .unwrap_or(base.trim_end_matches('/'));
+    if url.starts_with('/') {
+        format!("{}{}", origin, url)
+    } else {
+        format!("{}/{}", origin, url)
+    }
+}
+
+fn http_get(url: &str) -> Result<String, String> {
+    let rest = url.strip_prefix("http://").ok_or("Only http:// descriptions are supported")?;
+    let (host_port, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let addr = if host_port.contains(':') { host_port.to_string() } else { format!("{}:80", host_port) };
+    let sock_addr = addr.to_socket_addrs().map_err(|e| e.to_string())?.next().ok_or("Could not resolve")?;
+
+    let mut stream = TcpStream::connect_timeout(&sock_addr, Duration::from_secs(2)).map_err(|e| e.to_string())?;
+    stream.set_read_timeout(Some(Duration::from_secs(3))).map_err(|e| e.to_string())?;
+    let request = format!("GET {} HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n\r\n", path, host_port);
+    stream.write_all(request.as_bytes()).map_err(|e| e.to_string())?;
+
+    let mut body = Vec::new();
+    let _ = stream.take(256 * 1024).read_to_end(&mut body);
+    let text = String::from_utf8_lossy(&body).to_string();
+    Ok(text.split_once("\r\n\r\n").map(|(_, b)| b.to_string()).unwrap_or(text))
+}
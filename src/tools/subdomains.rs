@@ -0,0 +1,180 @@
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crossbeam::channel::Sender;
+use hickory_resolver::TokioAsyncResolver;
+use hickory_resolver::config::{ResolverConfig, ResolverOpts};
+use tokio::sync::Semaphore;
+
+// Lookups in flight at once
+const WORKERS: usize = 16;
+const CRT_SH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A name found under the domain, with what it resolves to (nothing, for certificates
+/// issued to names that no longer exist).
+#[derive(Debug, Clone)]
+pub struct Subdomain {
+    pub name: String,
+    pub ips: Vec<IpAddr>,
+    /// "crt.sh" or "wordlist"
+    pub source: &'static str,
+}
+
+#[derive(Debug, Clone)]
+pub enum SubdomainEvent {
+    Found(Subdomain),
+    /// A random name resolved too: every guess would, so guesses resolving only to
+    /// these addresses are dropped
+    Wildcard(Vec<IpAddr>),
+    Error(String),
+    Done,
+}
+
+/// Where names come from: certificate transparency logs via crt.sh, and/or a wordlist of
+/// labels tried in front of the domain.
+#[derive(Debug, Clone)]
+pub struct SubdomainRequest {
+    pub domain: String,
+    pub crt_sh: bool,
+    pub wordlist: Option<PathBuf>,
+}
+
+pub fn enumerate(request: SubdomainRequest, tx: Sender<SubdomainEvent>) {
+    tokio::spawn(async move {
+        let resolver = TokioAsyncResolver::tokio_from_system_conf()
+            .unwrap_or_else(|_| TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default()));
+        let domain = request.domain.trim_end_matches('.').to_lowercase();
+
+        if request.crt_sh {
+            match crt_sh(&domain).await {
+                Ok(names) => resolve_all(&resolver, names, "crt.sh", &[], &tx).await,
+                Err(e) => {
+                    let _ = tx.send(SubdomainEvent::Error(format!("crt.sh: {}", e)));
+                }
+            }
+        }
+
+        if let Some(path) = &request.wordlist {
+            match std::fs::read_to_string(path) {
+                Ok(text) => {
+                    let wildcard = wildcard(&resolver, &domain).await;
+                    if !wildcard.is_empty() {
+                        let _ = tx.send(SubdomainEvent::Wildcard(wildcard.clone()));
+                    }
+                    let names: BTreeSet<String> = text.lines()
+                        .map(|l| l.split('#').next().unwrap_or("").trim().to_lowercase())
+                        .filter(|l| !l.is_empty())
+                        .map(|label| format!("{}.{}", label, domain))
+                        .collect();
+                    resolve_all(&resolver, names, "wordlist", &wildcard, &tx).await;
+                }
+                Err(e) => {
+                    let _ = tx.send(SubdomainEvent::Error(format!("{}: {}", path.display(), e)));
+                }
+            }
+        }
+        let _ = tx.send(SubdomainEvent::Done);
+    });
+}
+
+// Every name on a logged certificate for the domain or below it, wildcards made plain
+async fn crt_sh(domain: &str) -> Result<BTreeSet<String>, String> {
+    let url = format!("https://crt.sh/?q=%25.{}&output=json", domain);
+    let response = reqwest::Client::new().get(&url).timeout(CRT_SH_TIMEOUT).send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    let entries: Vec<serde_json::Value> = response.json().await.map_err(|e| format!("bad answer: {}", e))?;
+    let suffix = format!(".{}", domain);
+    Ok(entries.iter()
+        .filter_map(|e| e.get("name_value").and_then(|v| v.as_str()))
+        .flat_map(|names| names.lines())
+        .map(|name| name.trim().trim_start_matches("*.").to_lowercase())
+        .filter(|name| name.ends_with(&suffix) || name == domain)
+        .collect())
+}
+
+async fn wildcard(resolver: &TokioAsyncResolver, domain: &str) -> Vec<IpAddr> {
+    let probe = format!("netops-{:08x}.{}.", rand::random::<u32>(), domain);
+    resolver.lookup_ip(probe).await.map(|ips| ips.iter().collect()).unwrap_or_default()
+}
+
+// Resolves the names `WORKERS` at a time. Wordlist guesses that don't resolve, or resolve
+// only to the wildcard's addresses, aren't worth listing; logged names are listed regardless.
+async fn resolve_all(resolver: &TokioAsyncResolver, names: BTreeSet<String>, source: &'static str, wildcard: &[IpAddr], tx: &Sender<SubdomainEvent>) {
+    let workers = Arc::new(Semaphore::new(WORKERS));
+    let mut tasks = vec![];
+    for name in names {
+        let Ok(permit) = workers.clone().acquire_owned().await else { break };
+        let (resolver, tx, wildcard) = (resolver.clone(), tx.clone(), wildcard.to_vec());
+        tasks.push(tokio::spawn(async move {
+            let ips: Vec<IpAddr> = resolver.lookup_ip(format!("{}.", name)).await.map(|ips| ips.iter().collect()).unwrap_or_default();
+            let guess = source == "wordlist";
+            if !guess || (!ips.is_empty() && !ips.iter().all(|ip| wildcard.contains(ip))) {
+                let _ = tx.send(SubdomainEvent::Found(Subdomain { name, ips, source }));
+            }
+            drop(permit);
+        }));
+    }
+    for task in tasks {
+        let _ = task.await;
+    }
+}
+
+/// An enumeration on the DNS screen, names kept sorted, with the ones picked for a port scan.
+#[derive(Debug, Clone, Default)]
+pub struct SubdomainScan {
+    pub domain: String,
+    pub found: Vec<Subdomain>,
+    pub wildcard: Vec<IpAddr>,
+    pub errors: Vec<String>,
+    pub done: bool,
+    pub selected: usize,
+    pub picked: BTreeSet<String>,
+}
+
+impl SubdomainScan {
+    pub fn new(domain: String) -> Self {
+        Self { domain, ..Default::default() }
+    }
+
+    pub fn apply(&mut self, event: SubdomainEvent) {
+        match event {
+            SubdomainEvent::Found(sub) => {
+                // Names both logged and guessed are listed once, as logged
+                if let Err(i) = self.found.binary_search_by(|s| s.name.cmp(&sub.name)) {
+                    self.found.insert(i, sub);
+                }
+            }
+            SubdomainEvent::Wildcard(ips) => self.wildcard = ips,
+            SubdomainEvent::Error(e) => self.errors.push(e),
+            SubdomainEvent::Done => self.done = true,
+        }
+    }
+
+    pub fn select(&mut self, forward: bool) {
+        self.selected = if forward {
+            (self.selected + 1).min(self.found.len().saturating_sub(1))
+        } else {
+            self.selected.saturating_sub(1)
+        };
+    }
+
+    pub fn toggle_pick(&mut self) {
+        let Some(sub) = self.found.get(self.selected) else { return };
+        if !self.picked.remove(&sub.name) {
+            self.picked.insert(sub.name.clone());
+        }
+    }
+
+    /// What a port scan should take: the picked names, or every name that resolves.
+    pub fn targets(&self) -> Vec<String> {
+        if !self.picked.is_empty() {
+            return self.picked.iter().cloned().collect();
+        }
+        self.found.iter().filter(|s| !s.ips.is_empty()).map(|s| s.name.clone()).collect()
+    }
+}
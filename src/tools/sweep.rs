@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use crossbeam::channel::Sender;
+use pnet::ipnetwork::{IpNetwork, Ipv4Network};
+use pnet_datalink::NetworkInterface;
+use surge_ping::PingSequence;
+
+use crate::tools::args::{self, Args};
+use crate::tools::ping::{icmp_client, icmp_identifier};
+
+use crate::config::ScanLimits;
+use crate::shutdown;
+
+// Sweeping a /8 by accident would take hours and look like an attack
+const MAX_PREFIX: u8 = 16;
+// Past these the pacing interval rounds to zero or the semaphore refuses the permits, both
+// panics; a /16 is swept long before either limit matters
+const MAX_RATE: u32 = 100_000;
+const MAX_CONCURRENCY: usize = 65_536;
+
+#[derive(Debug, Clone)]
+pub struct SweepHost {
+    pub ip: Ipv4Addr,
+    pub rtt: Duration,
+    /// From the neighbour cache once the sweep finishes; only known for on-link hosts
+    pub mac: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SweepEvent {
+    Alive(Ipv4Addr, Duration),
+    Probed,
+    /// IP -> MAC from the neighbour cache, which the sweep has just filled
+    Done(HashMap<Ipv4Addr, String>),
+    Error(String),
+}
+
+/// `cidr` plus optional `-c <parallel probes>`, `-r <probes/sec>` and `-t <timeout secs>`.
+#[derive(Debug, Clone)]
+pub struct SweepRequest {
+    pub network: Ipv4Network,
+    pub concurrency: usize,
+    /// Probes started per second; unlimited when unset
+    pub rate: Option<u32>,
+    pub timeout: Duration,
+}
+
+impl SweepRequest {
+    /// `limits` from the config replace the built-in defaults; flags in `input` override both.
+    pub fn parse(input: &str, limits: &ScanLimits) -> Result<Self, String> {
+        let mut network = None;
+        let mut concurrency = limits.concurrency.filter(|c| *c > 0).unwrap_or(64);
+        let mut rate = limits.rate.filter(|r| *r > 0);
+        let mut timeout = limits.timeout_ms.map(Duration::from_millis).unwrap_or(Duration::from_secs(1));
+        let mut args = Args::parse(input)?;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-c" => concurrency = args.positive("-c", "a number of parallel probes")?,
+                "-r" => rate = Some(args.positive("-r", "probes per second")?),
+                "-t" => timeout = args.seconds("-t")?,
+                flag if flag.starts_with('-') => return Err(args::unknown(flag)),
+                cidr => {
+                    // A bare address parses as a /32
+                    network = Some(cidr.parse::<Ipv4Network>().map_err(|_| format!("Not an IPv4 address or CIDR: {}", cidr))?);
+                }
+            }
+        }
+        let network = network.ok_or("Enter a range like 192.168.1.0/24")?;
+        if concurrency > MAX_CONCURRENCY {
+            return Err(format!("-c {} is too many probes at once; use {} or fewer", concurrency, MAX_CONCURRENCY));
+        }
+        if let Some(rate) = rate.filter(|r| *r > MAX_RATE) {
+            return Err(format!("-r {} is too fast; use {} probes per second or fewer", rate, MAX_RATE));
+        }
+        if network.prefix() < MAX_PREFIX {
+            return Err(format!("/{} is too large to sweep; use /{} or smaller", network.prefix(), MAX_PREFIX));
+        }
+        Ok(Self { network, concurrency, rate, timeout })
+    }
+
+    /// Addresses to probe, leaving out the network and broadcast addresses where they exist.
+    pub fn hosts(&self) -> Vec<Ipv4Addr> {
+        let net = self.network;
+        net.iter().filter(|ip| net.prefix() >= 31 || (*ip != net.network() && *ip != net.broadcast())).collect()
+    }
+}
+
+/// The first up, non-loopback IPv4 network, as a starting point for the input.
+pub fn default_range(interfaces: &[NetworkInterface]) -> Option<String> {
+    interfaces.iter().filter(|i| i.is_up() && !i.is_loopback()).flat_map(|i| &i.ips).find_map(|ip| match ip {
+        IpNetwork::V4(net) if net.prefix() >= MAX_PREFIX && net.prefix() < 32 => {
+            Ipv4Network::new(net.network(), net.prefix()).ok().map(|n| n.to_string())
+        }
+        _ => None,
+    })
+}
+
+pub struct SweepTask {
+    pub should_stop: Arc<AtomicBool>,
+}
+
+impl SweepTask {
+    pub fn new() -> Self {
+        Self {
+            should_stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Pings every host in the range with at most `concurrency` probes in flight. Each sweep
+    /// has its own stop flag; probes of a stopped one still in flight must not carry on.
+    pub fn start(&mut self, request: SweepRequest, tx: Sender<SweepEvent>) {
+        *self = Self::new();
+        let should_stop = self.should_stop.clone();
+
+        tokio::spawn(async move {
+            let client = match icmp_client(IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED)) {
+                Ok(c) => c,
+                Err(e) => {
+                    let _ = tx.send(SweepEvent::Error(e));
+                    return;
+                }
+            };
+            let permits = Arc::new(tokio::sync::Semaphore::new(request.concurrency));
+            let mut probes = tokio::task::JoinSet::new();
+            let mut pace = request.rate.map(|r| {
+                let mut pace = tokio::time::interval(Duration::from_secs_f64(1.0 / r as f64));
+                pace.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                pace
+            });
+
+            for ip in request.hosts() {
+                let Ok(permit) = permits.clone().acquire_owned().await else { break };
+                if let Some(pace) = pace.as_mut() {
+                    pace.tick().await;
+                }
+                if shutdown::stopping(&should_stop) {
+                    return;
+                }
+                let (client, tx, timeout) = (client.clone(), tx.clone(), request.timeout);
+                probes.spawn(async move {
+                    let mut pinger = client.pinger(IpAddr::V4(ip), icmp_identifier()).await;
+                    pinger.timeout(timeout);
+                    if let Ok((_, rtt)) = pinger.ping(PingSequence(0), &[0; 56]).await {
+                        let _ = tx.send(SweepEvent::Alive(ip, rtt));
+                    }
+                    let _ = tx.send(SweepEvent::Probed);
+                    drop(permit);
+                });
+                while probes.try_join_next().is_some() {}
+            }
+            while probes.join_next().await.is_some() {}
+
+            if !shutdown::stopping(&should_stop) {
+                let _ = tx.send(SweepEvent::Done(neighbor_macs()));
+            }
+        });
+    }
+
+    pub fn stop(&self) {
+        self.should_stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Resolved entries of the OS neighbour (ARP) cache.
+fn neighbor_macs() -> HashMap<Ipv4Addr, String> {
+    let mut macs = HashMap::new();
+    if let Ok(table) = std::fs::read_to_string("/proc/net/arp") {
+        // IP address, HW type, Flags, HW address, Mask, Device
+        for line in table.lines().skip(1) {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            if let (Some(ip), Some(&"0x2"), Some(mac)) = (cols.first().and_then(|ip| ip.parse().ok()), cols.get(2), cols.get(3)) {
+                macs.insert(ip, mac.to_string());
+            }
+        }
+    } else if let Ok(out) = std::process::Command::new("arp").arg("-an").output() {
+        // ? (192.168.1.1) at aa:bb:cc:dd:ee:ff on en0 ifscope [ethernet]
+        for line in String::from_utf8_lossy(&out.stdout).lines() {
+            let ip = line.split(['(', ')']).nth(1).and_then(|ip| ip.parse().ok());
+            let mac = line.split_whitespace().skip_while(|w| *w != "at").nth(1).filter(|m| m.contains(':'));
+            if let (Some(ip), Some(mac)) = (ip, mac) {
+                macs.insert(ip, mac.to_string());
+            }
+        }
+    }
+    macs
+}
@@ -0,0 +1,258 @@
+use std::io::{BufRead, BufReader, Read};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use crossbeam::channel::Sender;
+
+use crate::shutdown;
+
+pub const DEFAULT_SYSLOG_PORT: u16 = 514;
+// Longest TCP frame we accept; RFC 5425 only requires 2048
+const MAX_FRAME: usize = 64 * 1024;
+
+const SEVERITIES: [&str; 8] = ["emerg", "alert", "crit", "err", "warning", "notice", "info", "debug"];
+const FACILITIES: [&str; 24] = [
+    "kern", "user", "mail", "daemon", "auth", "syslog", "lpr", "news", "uucp", "cron", "authpriv", "ftp",
+    "ntp", "audit", "alert", "clock", "local0", "local1", "local2", "local3", "local4", "local5", "local6", "local7",
+];
+
+#[derive(Debug, Clone)]
+pub struct SyslogMessage {
+    /// When it arrived here (UTC); sender clocks are often wrong or missing
+    pub received: String,
+    pub from: IpAddr,
+    pub facility: u8,
+    /// 0 (emerg) to 7 (debug)
+    pub severity: u8,
+    pub hostname: String,
+    pub app: String,
+    pub text: String,
+}
+
+impl SyslogMessage {
+    pub fn facility_name(&self) -> &'static str {
+        FACILITIES.get(self.facility as usize).copied().unwrap_or("?")
+    }
+
+    pub fn severity_name(&self) -> &'static str {
+        severity_name(self.severity)
+    }
+
+    pub fn matches(&self, filter: &str) -> bool {
+        let filter = filter.trim().to_lowercase();
+        filter.is_empty()
+            || [&self.hostname, &self.app, &self.text].iter().any(|f| f.to_lowercase().contains(&filter))
+            || self.from.to_string().contains(&filter)
+            || self.facility_name() == filter
+    }
+}
+
+pub fn severity_name(severity: u8) -> &'static str {
+    SEVERITIES.get(severity as usize).copied().unwrap_or("?")
+}
+
+/// Parses RFC 5424 (`<PRI>1 TIMESTAMP HOST APP PROCID MSGID SD MSG`) and the older BSD format
+/// (`<PRI>Mmm dd hh:mm:ss HOST TAG: MSG`), keeping whatever a sloppy sender does manage to send.
+pub fn parse(data: &[u8], from: IpAddr) -> SyslogMessage {
+    let raw = String::from_utf8_lossy(data);
+    let raw = raw.trim_end_matches(['\n', '\r', '\0']);
+
+    // RFC 3164: no PRI means user.notice
+    let (pri, rest) = raw.strip_prefix('<')
+        .and_then(|r| r.split_once('>'))
+        .and_then(|(p, rest)| p.parse::<u8>().ok().filter(|p| *p < 192).map(|p| (p, rest)))
+        .unwrap_or((13, raw));
+
+    let mut msg = SyslogMessage {
+        received: now(),
+        from,
+        facility: pri / 8,
+        severity: pri % 8,
+        hostname: from.to_string(),
+        app: String::new(),
+        text: rest.to_string(),
+    };
+
+    if let Some(rest) = rest.strip_prefix("1 ") {
+        let mut fields = rest.splitn(6, ' ');
+        let (_timestamp, host, app, _procid, _msgid) = (fields.next(), fields.next(), fields.next(), fields.next(), fields.next());
+        if let Some(host) = host.filter(|h| *h != "-") {
+            msg.hostname = host.to_string();
+        }
+        msg.app = app.filter(|a| *a != "-").unwrap_or("").to_string();
+        msg.text = skip_structured_data(fields.next().unwrap_or("")).trim_start_matches('\u{feff}').to_string();
+    } else if has_bsd_timestamp(rest) {
+        let after = rest[15..].trim_start();
+        let (host, after) = after.split_once(' ').unwrap_or((after, ""));
+        msg.hostname = host.to_string();
+        let (tag, text) = split_tag(after);
+        msg.app = tag;
+        msg.text = text.to_string();
+    } else {
+        // No timestamp or host (many embedded devices): maybe still a tag
+        let (tag, text) = split_tag(rest);
+        msg.app = tag;
+        msg.text = text.to_string();
+    }
+    msg
+}
+
+/// `Mmm dd hh:mm:ss `, day padded with a space.
+fn has_bsd_timestamp(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() > 15 && s.is_char_boundary(15) && b[..3].iter().all(u8::is_ascii_alphabetic) && b[3] == b' ' && b[6] == b' ' && b[9] == b':' && b[12] == b':'
+}
+
+/// `sshd[123]: text` or `kernel: text` -> (`sshd`, `text`); anything else is all text.
+fn split_tag(s: &str) -> (String, &str) {
+    let Some((first, text)) = s.split_once(": ") else { return (String::new(), s) };
+    if first.contains(' ') || first.len() > 48 {
+        return (String::new(), s);
+    }
+    // Cisco-style sequence number rather than a tag
+    if first.bytes().all(|b| b.is_ascii_digit()) {
+        return (String::new(), text);
+    }
+    let tag = first.split('[').next().unwrap_or(first);
+    (tag.to_string(), text)
+}
+
+/// Skips the `-` or `[id k="v"]...` element that precedes the RFC 5424 message.
+fn skip_structured_data(s: &str) -> &str {
+    if let Some(rest) = s.strip_prefix('-') {
+        return rest.trim_start();
+    }
+    let (mut depth, mut quoted, mut escaped) = (0, false, false);
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' if depth > 0 => quoted = !quoted,
+            '[' if !quoted => depth += 1,
+            ']' if !quoted => {
+                depth -= 1;
+                if depth == 0 && !s[i + 1..].starts_with('[') {
+                    return s[i + 1..].trim_start();
+                }
+            }
+            _ if depth == 0 => return s,
+            _ => {}
+        }
+    }
+    ""
+}
+
+fn now() -> String {
+    time::OffsetDateTime::now_utc().time().format(&time::format_description::parse("[hour]:[minute]:[second]").unwrap()).unwrap_or_default()
+}
+
+#[derive(Debug, Clone)]
+pub enum SyslogEvent {
+    Message(Box<SyslogMessage>),
+    /// Listening on one transport failed; the other is still up
+    Warning(String),
+    Error(String),
+}
+
+pub struct SyslogListener {
+    pub should_stop: Arc<AtomicBool>,
+}
+
+impl SyslogListener {
+    pub fn new() -> Self {
+        Self {
+            should_stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Listens on UDP and TCP `port`; either one binding is enough to keep going. The old
+    /// run's threads keep their own (set) flag, so a quick restart can't bring them back.
+    pub fn start(&mut self, port: u16, tx: Sender<SyslogEvent>) {
+        *self = Self::new();
+        let should_stop = self.should_stop.clone();
+        let addr = SocketAddr::from((Ipv4Addr::UNSPECIFIED, port));
+        let hint = if port < 1024 { " (ports below 1024 need root; try 5514)" } else { "" };
+
+        let udp = UdpSocket::bind(addr);
+        let tcp = TcpListener::bind(addr);
+        match (udp, tcp) {
+            (Err(udp), Err(_)) => {
+                let _ = tx.send(SyslogEvent::Error(format!("Failed to bind port {}: {}{}", port, udp, hint)));
+            }
+            (udp, tcp) => {
+                match udp {
+                    Ok(socket) => spawn_udp(socket, should_stop.clone(), tx.clone()),
+                    Err(e) => { let _ = tx.send(SyslogEvent::Warning(format!("UDP {}: {}{}", port, e, hint))); }
+                }
+                match tcp {
+                    Ok(listener) => spawn_tcp(listener, should_stop, tx),
+                    Err(e) => { let _ = tx.send(SyslogEvent::Warning(format!("TCP {}: {}{}", port, e, hint))); }
+                }
+            }
+        }
+    }
+
+    pub fn stop(&self) {
+        self.should_stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn spawn_udp(socket: UdpSocket, should_stop: Arc<AtomicBool>, tx: Sender<SyslogEvent>) {
+    let _ = socket.set_read_timeout(Some(Duration::from_millis(200)));
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 65535];
+        while !shutdown::stopping(&should_stop) {
+            let Ok((n, from)) = socket.recv_from(&mut buf) else { continue };
+            if tx.send(SyslogEvent::Message(Box::new(parse(&buf[..n], from.ip())))).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+fn spawn_tcp(listener: TcpListener, should_stop: Arc<AtomicBool>, tx: Sender<SyslogEvent>) {
+    let _ = listener.set_nonblocking(true);
+    std::thread::spawn(move || {
+        while !shutdown::stopping(&should_stop) {
+            match listener.accept() {
+                Ok((stream, peer)) => {
+                    let (should_stop, tx) = (should_stop.clone(), tx.clone());
+                    std::thread::spawn(move || read_tcp(stream, peer.ip(), should_stop, tx));
+                }
+                Err(_) => std::thread::sleep(Duration::from_millis(200)),
+            }
+        }
+    });
+}
+
+/// RFC 6587 framing: octet counting (`<len> <msg>`) or one message per line. Blocks between
+/// messages; after a stop the thread ends with the next message or when the sender disconnects.
+fn read_tcp(stream: TcpStream, from: IpAddr, should_stop: Arc<AtomicBool>, tx: Sender<SyslogEvent>) {
+    let _ = stream.set_nonblocking(false);
+    let mut reader = BufReader::new(stream);
+    let mut frame = Vec::new();
+    while !shutdown::stopping(&should_stop) {
+        let first = match reader.fill_buf() {
+            Ok([]) | Err(_) => return,
+            Ok(buf) => buf[0],
+        };
+        frame.clear();
+        if first.is_ascii_digit() {
+            let mut len = Vec::new();
+            if reader.read_until(b' ', &mut len).is_err() {
+                return;
+            }
+            let Some(len) = std::str::from_utf8(&len).ok().and_then(|l| l.trim().parse::<usize>().ok()).filter(|l| *l <= MAX_FRAME) else { return };
+            frame.resize(len, 0);
+            if reader.read_exact(&mut frame).is_err() {
+                return;
+            }
+        } else if reader.read_until(b'\n', &mut frame).is_err() || frame.is_empty() {
+            return;
+        }
+        if tx.send(SyslogEvent::Message(Box::new(parse(&frame, from)))).is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::net::IpAddr;
+use std::process::Command;
+
+/// Something the Connections screen can shut down, pending user confirmation.
+#[derive(Debug, Clone)]
+pub enum KillTarget {
+    Process { pid: u32, name: String },
+    Connection { ip: IpAddr },
+}
+
+impl KillTarget {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Process { pid, name } => format!("Kill {} (pid {}) with SIGTERM?", name, pid),
+            Self::Connection { ip } => format!("Reset all TCP connections to {}?", ip),
+        }
+    }
+
+    pub fn execute(&self) -> Result<String, String> {
+        match self {
+            Self::Process { pid, name } => {
+                run(Command::new("kill").args(["-TERM", &pid.to_string()]))?;
+                Ok(format!("Sent SIGTERM to {} ({})", name, pid))
+            }
+            // `ss -K` asks the kernel to destroy matching sockets (needs root and CONFIG_INET_DIAG_DESTROY)
+            Self::Connection { ip } => {
+                if !cfg!(target_os = "linux") {
+                    return Err("Resetting connections is only supported on Linux (ss -K)".to_string());
+                }
+                run(Command::new("ss").args(["-K", "dst", &ip.to_string()]))?;
+                Ok(format!("Reset connections to {}", ip))
+            }
+        }
+    }
+}
+
+fn run(cmd: &mut Command) -> Result<(), String> {
+    let output = cmd.output().map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
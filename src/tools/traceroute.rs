@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::mem::MaybeUninit;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use crossbeam::channel::Sender;
+use socket2::{Domain, Protocol, Socket, Type};
+
+use super::args::{self, Args};
+use super::mtr::MtrResult;
+
+pub const PROBES_PER_HOP: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct TraceHop {
+    pub ttl: u8,
+    pub host: Option<IpAddr>,
+    pub rtts: [Option<Duration>; PROBES_PER_HOP],
+}
+
+impl TraceHop {
+    /// The hop as MTR results, one per probe, so a finished trace can seed the MTR screen.
+    pub fn as_mtr_results(&self, target: IpAddr) -> Vec<MtrResult> {
+        self.rtts.iter().map(|rtt| MtrResult {
+            ttl: self.ttl,
+            host: self.host,
+            rtt: rtt.unwrap_or(Duration::MAX),
+            successful: rtt.is_some(),
+            is_target: self.host == Some(target),
+            flow: 0,
+        }).collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TraceResult {
+    pub target: IpAddr,
+    pub hops: Vec<TraceHop>,
+    pub reached: bool,
+    pub elapsed: Duration,
+    /// Set when only an unprivileged ICMP socket was available
+    pub note: Option<String>,
+}
+
+/// `host [-m max_hops] [-w wait_secs]`
+pub fn trace(input: String, tx: Sender<Result<TraceResult, String>>) {
+    std::thread::spawn(move || {
+        let _ = tx.send(run_trace(&input));
+    });
+}
+
+fn run_trace(input: &str) -> Result<TraceResult, String> {
+    let mut host = None;
+    let mut max_hops: u8 = 30;
+    let mut wait = Duration::from_secs(2);
+    let mut args = Args::parse(input)?;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-m" => max_hops = args.positive("-m", "a hop count")?,
+            "-w" => wait = args.seconds("-w")?,
+            flag if flag.starts_with('-') => return Err(args::unknown(flag)),
+            _ => host = Some(arg),
+        }
+    }
+    let host = host.ok_or("Enter a target host")?;
+    let host = host.as_str();
+    let target = (host, 0).to_socket_addrs().map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+        .next().map(|a| a.ip()).ok_or_else(|| format!("No address for {}", host))?;
+
+    let (domain, proto) = match target {
+        IpAddr::V4(_) => (Domain::IPV4, Protocol::ICMPV4),
+        IpAddr::V6(_) => (Domain::IPV6, Protocol::ICMPV6),
+    };
+    // Time Exceeded errors only reach raw sockets; a ping socket just sees the final reply
+    let (socket, raw) = match Socket::new(domain, Type::RAW, Some(proto)) {
+        Ok(s) => (s, true),
+        Err(_) => (Socket::new(domain, Type::DGRAM, Some(proto)).map_err(|e| format!("Failed to open ICMP socket: {} (needs root)", e))?, false),
+    };
+    socket.set_read_timeout(Some(Duration::from_millis(50))).map_err(|e| e.to_string())?;
+
+    // Every TTL at once: sequence = ttl * 4 + probe identifies the probe a reply answers
+    let id = std::process::id() as u16;
+    let mut sent: HashMap<u16, Instant> = HashMap::new();
+    let dest: socket2::SockAddr = SocketAddr::new(target, 0).into();
+    let started = Instant::now();
+    for ttl in 1..=max_hops {
+        match target {
+            IpAddr::V4(_) => socket.set_ttl_v4(ttl as u32),
+            IpAddr::V6(_) => socket.set_unicast_hops_v6(ttl as u32),
+        }.map_err(|e| format!("Failed to set TTL: {}", e))?;
+        for probe in 0..PROBES_PER_HOP as u16 {
+            let seq = ttl as u16 * 4 + probe;
+            socket.send_to(&echo_request(target, id, seq), &dest).map_err(|e| format!("Failed to send: {}", e))?;
+            sent.insert(seq, Instant::now());
+        }
+    }
+
+    let mut hops: Vec<TraceHop> = (1..=max_hops).map(|ttl| TraceHop { ttl, host: None, rtts: [None; PROBES_PER_HOP] }).collect();
+    let mut target_ttl: Option<u8> = None;
+    let mut buf = [MaybeUninit::new(0u8); 1500];
+    let deadline = Instant::now() + wait;
+    while Instant::now() < deadline {
+        let Ok((len, from)) = socket.recv_from(&mut buf) else { continue };
+        let received = Instant::now();
+        let data: Vec<u8> = buf[..len].iter().map(|b| unsafe { b.assume_init() }).collect();
+        let Some(from) = from.as_socket().map(|s| s.ip()) else { continue };
+        // Raw IPv4 sockets deliver the IP header; everything else starts at ICMP
+        let icmp = if raw && target.is_ipv4() { data.get(((data.first().copied().unwrap_or(0) & 0x0f) as usize * 4)..) } else { Some(&data[..]) };
+        let Some((reply_id, seq, is_echo_reply)) = icmp.and_then(|icmp| parse_icmp(icmp, target.is_ipv4())) else { continue };
+        // The kernel picks the id for ping sockets
+        if raw && reply_id != id {
+            continue;
+        }
+        let (ttl, probe) = ((seq / 4) as u8, (seq % 4) as usize);
+        let (Some(sent_at), Some(hop)) = (sent.get(&seq), hops.get_mut(ttl.wrapping_sub(1) as usize)) else { continue };
+        if probe >= PROBES_PER_HOP {
+            continue;
+        }
+        hop.host = Some(from);
+        hop.rtts[probe] = Some(received - *sent_at);
+        if is_echo_reply || from == target {
+            target_ttl = Some(target_ttl.map_or(ttl, |t| t.min(ttl)));
+        }
+        // Done once every probe up to the target has answered
+        if let Some(t) = target_ttl && hops[..t as usize].iter().all(|h| h.rtts.iter().all(Option::is_some)) {
+            break;
+        }
+    }
+
+    if let Some(t) = target_ttl {
+        hops.truncate(t as usize);
+    } else if let Some(last) = hops.iter().rposition(|h| h.host.is_some()) {
+        // Keep a couple of silent hops past the last answer to show where the path went dark
+        hops.truncate((last + 3).min(hops.len()));
+    }
+    let note = (!raw).then(|| "Unprivileged ICMP socket: intermediate hops can't be seen without root".to_string());
+    Ok(TraceResult { target, hops, reached: target_ttl.is_some(), elapsed: started.elapsed(), note })
+}
+
+pub fn echo_request(target: IpAddr, id: u16, seq: u16) -> Vec<u8> {
+    let mut packet = vec![0u8; 64];
+    packet[0] = if target.is_ipv4() { 8 } else { 128 };
+    packet[4..6].copy_from_slice(&id.to_be_bytes());
+    packet[6..8].copy_from_slice(&seq.to_be_bytes());
+    // The kernel fills in the ICMPv6 checksum
+    if target.is_ipv4() {
+        let checksum = internet_checksum::checksum(&packet);
+        packet[2] = checksum[0];
+        packet[3] = checksum[1];
+    }
+    packet
+}
+
+/// An echo request whose checksum depends only on `flow`, Paris traceroute style: routers
+/// balancing over equal-cost links hash the first bytes of the ICMP header like the ports of
+/// a TCP or UDP packet, so a checksum that changed with every sequence number would send each
+/// probe down a different link. A word of payload cancels the sequence number out of the sum.
+pub fn paris_echo_request(target: IpAddr, id: u16, seq: u16, flow: u16) -> Vec<u8> {
+    let mut packet = vec![0u8; 64];
+    packet[0] = if target.is_ipv4() { 8 } else { 128 };
+    packet[4..6].copy_from_slice(&id.to_be_bytes());
+    packet[6..8].copy_from_slice(&seq.to_be_bytes());
+    // seq + (flow - seq) = flow, in the one's complement sum the checksum is made of
+    let sum = flow as u32 + !seq as u32;
+    let compensation = ((sum & 0xffff) + (sum >> 16)) as u16;
+    packet[8..10].copy_from_slice(&compensation.to_be_bytes());
+    // The kernel fills in the ICMPv6 checksum; with the same addresses it's as constant
+    if target.is_ipv4() {
+        let checksum = internet_checksum::checksum(&packet);
+        packet[2] = checksum[0];
+        packet[3] = checksum[1];
+    }
+    packet
+}
+
+/// (id, sequence, is echo reply) of the probe an ICMP message answers. Time Exceeded and
+/// Unreachable quote our original packet: its IP header, then the echo request.
+pub fn parse_icmp(icmp: &[u8], v4: bool) -> Option<(u16, u16, bool)> {
+    let read = |b: &[u8], at: usize| Some(u16::from_be_bytes([*b.get(at)?, *b.get(at + 1)?]));
+    let (echo_reply, errors, inner_header) = if v4 { (0, [11, 3], None) } else { (129, [3, 1], Some(40)) };
+    let t = *icmp.first()?;
+    if t == echo_reply {
+        return Some((read(icmp, 4)?, read(icmp, 6)?, true));
+    }
+    if !errors.contains(&t) {
+        return None;
+    }
+    let inner = icmp.get(8..)?;
+    let header_len = inner_header.unwrap_or((*inner.first()? & 0x0f) as usize * 4);
+    let echo = inner.get(header_len..)?;
+    Some((read(echo, 4)?, read(echo, 6)?, false))
+}
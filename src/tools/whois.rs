@@ -0,0 +1,25 @@
+use std::process::Command;
+use crossbeam::channel::Sender;
+
+pub struct WhoisTask {
+    pub target: String,
+    pub tx: Sender<Result<String, String>>,
+}
+
+impl WhoisTask {
+    pub fn new(target: String, tx: Sender<Result<String, String>>) -> Self {
+        Self { target, tx }
+    }
+
+    /// Runs the system `whois` client once and sends its full output.
+    pub fn run(&self) {
+        let result = match Command::new("whois").arg(&self.target).output() {
+            Ok(output) if output.status.success() || !output.stdout.is_empty() => {
+                Ok(String::from_utf8_lossy(&output.stdout).to_string())
+            }
+            Ok(output) => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+            Err(e) => Err(format!("Failed to start whois: {}", e)),
+        };
+        let _ = self.tx.send(result);
+    }
+}
@@ -0,0 +1,323 @@
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crossbeam::channel::Sender;
+
+use crate::shutdown;
+
+const SCAN_INTERVAL: Duration = Duration::from_secs(15);
+const LINK_INTERVAL: Duration = Duration::from_secs(1);
+const AIRPORT: &str = "/System/Library/PrivateFrameworks/Apple80211.framework/Versions/Current/Resources/airport";
+
+#[derive(Debug, Clone, Default)]
+pub struct WifiNetwork {
+    pub ssid: String,
+    pub bssid: String,
+    pub channel: Option<u32>,
+    pub band: &'static str,
+    pub signal_dbm: Option<i32>,
+    pub security: String,
+    pub connected: bool,
+}
+
+/// The network we're associated with, sampled every second for the RSSI graph.
+#[derive(Debug, Clone, Default)]
+pub struct WifiLink {
+    pub ssid: String,
+    pub bssid: String,
+    pub channel: Option<u32>,
+    pub signal_dbm: Option<i32>,
+    pub tx_rate: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum WifiEvent {
+    Interface(String),
+    Scan(Vec<WifiNetwork>),
+    Link(Option<WifiLink>),
+    Error(String),
+}
+
+pub struct WifiTask {
+    pub should_stop: Arc<AtomicBool>,
+}
+
+impl WifiTask {
+    pub fn new() -> Self {
+        Self {
+            should_stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    // New flag per run: the old scan thread may not have seen its stop yet
+    pub fn start(&mut self, tx: Sender<WifiEvent>) {
+        *self = Self::new();
+        let should_stop = self.should_stop.clone();
+
+        std::thread::spawn(move || {
+            let Some(interface) = wireless_interface() else {
+                let _ = tx.send(WifiEvent::Error("No wireless interface found".to_string()));
+                return;
+            };
+            let _ = tx.send(WifiEvent::Interface(interface.clone()));
+
+            let mut last_scan: Option<Instant> = None;
+            loop {
+                if shutdown::stopping(&should_stop) {
+                    break;
+                }
+
+                if last_scan.is_none_or(|t| t.elapsed() >= SCAN_INTERVAL) {
+                    // Scans take a few seconds; the link sample below is what the graph waits on
+                    let event = match scan(&interface) {
+                        Ok(networks) => WifiEvent::Scan(networks),
+                        Err(e) => WifiEvent::Error(e),
+                    };
+                    if tx.send(event).is_err() {
+                        break;
+                    }
+                    last_scan = Some(Instant::now());
+                }
+
+                if tx.send(WifiEvent::Link(link(&interface))).is_err() {
+                    break;
+                }
+                if !shutdown::sleep(LINK_INTERVAL) {
+                    break;
+                }
+            }
+        });
+    }
+
+    pub fn stop(&self) {
+        self.should_stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program).args(args).output().map_err(|e| format!("{}: {}", program, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(format!("{} failed: {}", program, stderr));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn wireless_interface() -> Option<String> {
+    if cfg!(target_os = "linux") {
+        let entries = std::fs::read_dir("/sys/class/net").ok()?;
+        let mut names: Vec<String> = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().join("wireless").exists())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names.into_iter().next()
+    } else {
+        // The "Wi-Fi" hardware port, usually en0
+        let ports = run("networksetup", &["-listallhardwareports"]).ok()?;
+        let mut lines = ports.lines();
+        while let Some(line) = lines.next() {
+            if line.contains("Wi-Fi") || line.contains("AirPort") {
+                return lines.next()?.strip_prefix("Device: ").map(|d| d.trim().to_string());
+            }
+        }
+        None
+    }
+}
+
+fn scan(interface: &str) -> Result<Vec<WifiNetwork>, String> {
+    let mut networks = if cfg!(target_os = "linux") {
+        // `iw scan` needs CAP_NET_ADMIN; NetworkManager's cached list doesn't
+        match run("iw", &["dev", interface, "scan"]) {
+            Ok(out) => parse_iw_scan(&out),
+            Err(iw_err) => run("nmcli", &["-t", "-f", "IN-USE,BSSID,SSID,CHAN,FREQ,SIGNAL,SECURITY", "dev", "wifi", "list", "ifname", interface])
+                .map(|out| parse_nmcli(&out))
+                .map_err(|_| format!("{} (and nmcli is unavailable)", iw_err))?,
+        }
+    } else {
+        parse_airport_scan(&run(AIRPORT, &["-s"])?)
+    };
+    networks.sort_by_key(|n| std::cmp::Reverse(n.signal_dbm.unwrap_or(i32::MIN)));
+    Ok(networks)
+}
+
+fn link(interface: &str) -> Option<WifiLink> {
+    if cfg!(target_os = "linux") {
+        match run("iw", &["dev", interface, "link"]) {
+            Ok(out) => parse_iw_link(&out),
+            // Without iw, the kernel still reports the signal level
+            Err(_) => proc_wireless_signal(interface).map(|signal| WifiLink { signal_dbm: Some(signal), ..Default::default() }),
+        }
+    } else {
+        parse_airport_info(&run(AIRPORT, &["-I"]).ok()?)
+    }
+}
+
+/// 802.11 channel number for a centre frequency in MHz.
+fn channel_for(freq: u32) -> Option<u32> {
+    match freq {
+        2484 => Some(14),
+        2412..=2472 => Some((freq - 2407) / 5),
+        5160..=5885 => Some((freq - 5000) / 5),
+        5955..=7115 => Some((freq - 5950) / 5),
+        _ => None,
+    }
+}
+
+fn band_for(freq: u32) -> &'static str {
+    match freq {
+        2400..=2500 => "2.4 GHz",
+        5000..=5900 => "5 GHz",
+        5925..=7125 => "6 GHz",
+        _ => "",
+    }
+}
+
+type IwEntry = (WifiNetwork, bool, bool, bool); // network, has RSN, has WPA, privacy bit
+
+fn finish_iw_entry(entry: Option<IwEntry>, networks: &mut Vec<WifiNetwork>) {
+    if let Some((mut net, rsn, wpa, privacy)) = entry {
+        if net.security.is_empty() {
+            net.security = match (rsn, wpa, privacy) {
+                (true, _, _) => "WPA2".to_string(),
+                (_, true, _) => "WPA".to_string(),
+                (_, _, true) => "WEP".to_string(),
+                _ => "Open".to_string(),
+            };
+        }
+        networks.push(net);
+    }
+}
+
+/// `iw dev <if> scan`: one `BSS` block per access point.
+fn parse_iw_scan(output: &str) -> Vec<WifiNetwork> {
+    let mut networks = Vec::new();
+    let mut current: Option<IwEntry> = None;
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if let Some(rest) = line.strip_prefix("BSS ") {
+            finish_iw_entry(current.take(), &mut networks);
+            let bssid = rest.split(['(', ' ']).next().unwrap_or_default().to_string();
+            let connected = rest.contains("associated");
+            current = Some((WifiNetwork { bssid, connected, ..Default::default() }, false, false, false));
+            continue;
+        }
+        let Some((net, rsn, wpa, privacy)) = current.as_mut() else { continue };
+        if let Some(freq) = trimmed.strip_prefix("freq: ") {
+            // Newer iw prints "2437.0"
+            if let Ok(freq) = freq.split('.').next().unwrap_or_default().parse::<u32>() {
+                net.band = band_for(freq);
+                net.channel = net.channel.or(channel_for(freq));
+            }
+        } else if let Some(signal) = trimmed.strip_prefix("signal: ") {
+            net.signal_dbm = signal.split_whitespace().next().and_then(|s| s.parse::<f64>().ok()).map(|s| s.round() as i32);
+        } else if let Some(ssid) = trimmed.strip_prefix("SSID: ") {
+            net.ssid = ssid.to_string();
+        } else if let Some(channel) = trimmed.strip_prefix("DS Parameter set: channel ") {
+            net.channel = channel.trim().parse().ok();
+        } else if trimmed.starts_with("RSN:") {
+            *rsn = true;
+        } else if trimmed.starts_with("WPA:") {
+            *wpa = true;
+        } else if trimmed.starts_with("capability:") && trimmed.contains("Privacy") {
+            *privacy = true;
+        } else if trimmed.contains("Authentication suites:") && trimmed.contains("SAE") {
+            net.security = if trimmed.contains("PSK") { "WPA2/WPA3" } else { "WPA3" }.to_string();
+        }
+    }
+    finish_iw_entry(current.take(), &mut networks);
+    networks
+}
+
+/// `nmcli -t` output; colons inside fields (the BSSID) are escaped as `\:`.
+fn parse_nmcli(output: &str) -> Vec<WifiNetwork> {
+    output.lines().filter_map(|line| {
+        let mut fields = vec![String::new()];
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => fields.last_mut()?.extend(chars.next()),
+                ':' => fields.push(String::new()),
+                _ => fields.last_mut()?.push(c),
+            }
+        }
+        if fields.len() < 7 {
+            return None;
+        }
+        let freq: Option<u32> = fields[4].split_whitespace().next().and_then(|f| f.parse().ok());
+        // SIGNAL is a 0-100 quality; map it back onto the usual -100..-50 dBm range
+        let signal_dbm = fields[5].parse::<i32>().ok().map(|q| q / 2 - 100);
+        Some(WifiNetwork {
+            connected: fields[0] == "*",
+            bssid: fields[1].to_lowercase(),
+            ssid: fields[2].clone(),
+            channel: fields[3].parse().ok(),
+            band: freq.map(band_for).unwrap_or_default(),
+            signal_dbm,
+            security: if fields[6].is_empty() { "Open".to_string() } else { fields[6].clone() },
+        })
+    }).collect()
+}
+
+fn parse_iw_link(output: &str) -> Option<WifiLink> {
+    let first = output.lines().next()?;
+    let bssid = first.strip_prefix("Connected to ")?.split(['(', ' ']).next().unwrap_or_default().to_string();
+    let mut link = WifiLink { bssid, ..Default::default() };
+    for line in output.lines().skip(1).map(str::trim) {
+        if let Some(ssid) = line.strip_prefix("SSID: ") {
+            link.ssid = ssid.to_string();
+        } else if let Some(freq) = line.strip_prefix("freq: ") {
+            link.channel = freq.split('.').next().and_then(|f| f.parse().ok()).and_then(channel_for);
+        } else if let Some(signal) = line.strip_prefix("signal: ") {
+            link.signal_dbm = signal.split_whitespace().next().and_then(|s| s.parse().ok());
+        } else if let Some(rate) = line.strip_prefix("tx bitrate: ") {
+            link.tx_rate = rate.split_whitespace().take(2).collect::<Vec<_>>().join(" ");
+        }
+    }
+    Some(link)
+}
+
+/// `/proc/net/wireless`: "wlan0: 0000   56.  -54.  -256 ..." (status, link, level, noise).
+fn proc_wireless_signal(interface: &str) -> Option<i32> {
+    let text = std::fs::read_to_string("/proc/net/wireless").ok()?;
+    let line = text.lines().find(|l| l.trim_start().starts_with(&format!("{}:", interface)))?;
+    line.split_whitespace().nth(3)?.trim_end_matches('.').parse().ok()
+}
+
+/// `airport -s`: SSIDs are right-aligned and may contain spaces, so columns are
+/// found relative to the BSSID.
+fn parse_airport_scan(output: &str) -> Vec<WifiNetwork> {
+    output.lines().skip(1).filter_map(|line| {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let at = parts.iter().position(|p| p.len() == 17 && p.matches(':').count() == 5)?;
+        let channel: Option<u32> = parts.get(at + 2)?.split(',').next()?.parse().ok();
+        Some(WifiNetwork {
+            ssid: parts[..at].join(" "),
+            bssid: parts[at].to_string(),
+            signal_dbm: parts.get(at + 1)?.parse().ok(),
+            channel,
+            band: match channel {
+                Some(c) if c <= 14 => "2.4 GHz",
+                Some(_) => "5 GHz",
+                None => "",
+            },
+            security: parts.get(at + 5..).map(|s| s.join(" ")).unwrap_or_default(),
+            connected: false,
+        })
+    }).collect()
+}
+
+/// `airport -I`: "key: value" lines.
+fn parse_airport_info(output: &str) -> Option<WifiLink> {
+    let field = |key: &str| output.lines().find_map(|l| l.trim().strip_prefix(key)?.strip_prefix(": ").map(str::to_string));
+    let ssid = field("SSID")?;
+    Some(WifiLink {
+        ssid,
+        bssid: field("BSSID").unwrap_or_default(),
+        channel: field("channel").and_then(|c| c.split(',').next()?.parse().ok()),
+        signal_dbm: field("agrCtlRSSI").and_then(|s| s.parse().ok()),
+        tx_rate: field("lastTxRate").map(|r| format!("{} Mbit/s", r)).unwrap_or_default(),
+    })
+}
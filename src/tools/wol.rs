@@ -0,0 +1,58 @@
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use pnet_datalink::NetworkInterface;
+use pnet::ipnetwork::IpNetwork;
+
+pub const WOL_PORT: u16 = 9;
+
+pub fn parse_mac(input: &str) -> Result<[u8; 6], String> {
+    // Accept aa:bb:cc:dd:ee:ff, aa-bb-cc-dd-ee-ff and aabb.ccdd.eeff / aabbccddeeff
+    let hex: String = input.trim().chars().filter(|c| !matches!(c, ':' | '-' | '.')).collect();
+    // Checked before slicing by byte offsets, which would panic inside a multi-byte char
+    if hex.len() != 12 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid MAC address: {}", input.trim()));
+    }
+
+    let mut mac = [0u8; 6];
+    for (i, byte) in mac.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .map_err(|_| format!("Invalid MAC address: {}", input.trim()))?;
+    }
+    Ok(mac)
+}
+
+pub fn magic_packet(mac: &[u8; 6]) -> Vec<u8> {
+    // 6 x 0xFF followed by the target MAC repeated 16 times
+    let mut packet = vec![0xFFu8; 6];
+    for _ in 0..16 {
+        packet.extend_from_slice(mac);
+    }
+    debug_assert_eq!(packet.len(), 102, "Magic packet must be 102 bytes");
+    packet
+}
+
+/// Returns (bind address, broadcast address) for an interface's first IPv4 network.
+pub fn interface_broadcast(interface: &NetworkInterface) -> Option<(Ipv4Addr, Ipv4Addr)> {
+    interface.ips.iter().find_map(|ip| match ip {
+        IpNetwork::V4(net) => Some((net.ip(), net.broadcast())),
+        _ => None,
+    })
+}
+
+pub fn send(mac_str: &str, interface: Option<&NetworkInterface>) -> Result<String, String> {
+    let mac = parse_mac(mac_str)?;
+    let packet = magic_packet(&mac);
+
+    let (bind_ip, broadcast) = match interface {
+        Some(iface) => interface_broadcast(iface)
+            .ok_or_else(|| format!("Interface {} has no IPv4 address", iface.name))?,
+        None => (Ipv4Addr::UNSPECIFIED, Ipv4Addr::BROADCAST),
+    };
+
+    let socket = UdpSocket::bind(SocketAddr::new(bind_ip.into(), 0))
+        .map_err(|e| format!("Failed to bind socket: {}", e))?;
+    socket.set_broadcast(true).map_err(|e| format!("Failed to enable broadcast: {}", e))?;
+    socket.send_to(&packet, SocketAddr::new(broadcast.into(), WOL_PORT))
+        .map_err(|e| format!("Failed to send magic packet: {}", e))?;
+
+    Ok(format!("Magic packet sent to {} via {}:{}", mac_str.trim(), broadcast, WOL_PORT))
+}
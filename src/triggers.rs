@@ -0,0 +1,73 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::alerts::AlertRule;
+use crate::tools::capture_filter::CaptureFilter;
+
+#[derive(Debug, Clone)]
+pub enum TriggerCondition {
+    /// An alert rule over the dashboard metrics, e.g. `latency to 1.1.1.1 > 200ms for 10s`
+    Metric(AlertRule),
+    /// Any captured packet matching the filter, whatever the display filter
+    Packet(CaptureFilter),
+}
+
+/// A condition that starts recording packets to the pcap ring, written as an alert rule or
+/// as `packet <capture filter>`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CaptureTrigger {
+    text: String,
+    pub condition: TriggerCondition,
+}
+
+impl CaptureTrigger {
+    pub fn packet_filter(&self) -> Option<&CaptureFilter> {
+        match &self.condition {
+            TriggerCondition::Packet(filter) => Some(filter),
+            TriggerCondition::Metric(_) => None,
+        }
+    }
+
+    pub fn metric_rule(&self) -> Option<&AlertRule> {
+        match &self.condition {
+            TriggerCondition::Metric(rule) => Some(rule),
+            TriggerCondition::Packet(_) => None,
+        }
+    }
+}
+
+impl FromStr for CaptureTrigger {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim().to_string();
+        let condition = match text.strip_prefix("packet ") {
+            Some(filter) => TriggerCondition::Packet(CaptureFilter::parse(filter).map_err(|e| format!("Trigger '{}': {}", text, e))?),
+            None => TriggerCondition::Metric(text.parse().map_err(|e| format!("Trigger '{}': {} (or packet <filter>)", text, e))?),
+        };
+        Ok(Self { text, condition })
+    }
+}
+
+impl TryFrom<String> for CaptureTrigger {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<CaptureTrigger> for String {
+    fn from(trigger: CaptureTrigger) -> String {
+        trigger.text
+    }
+}
+
+impl fmt::Display for CaptureTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
@@ -2,16 +2,32 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph, BorderType, Clear, Dataset, Chart, Axis, GraphType},
+    widgets::{Block, Borders, List, ListItem, Paragraph, BorderType, Clear, Dataset, Chart, Axis, GraphType, Wrap, ListState},
     symbols,
     Frame,
 };
 
-
-
-use crate::app::{App, CurrentScreen};
-use crate::theme::THEME;
-use crate::tools::dns::DnsResult;
+mod globe;
+mod sparkline;
+
+use crate::app::{App, ArpView, ConnectionsView, CurrentScreen, CONNECTION_ACTIONS};
+use crate::dashboard::{self, Health, LatencyProbe, Panel};
+use crate::keymap::Action;
+use crate::profiles::ProfileTool;
+use crate::theme::{self, THEME};
+use crate::units;
+use crate::histogram::Histogram;
+use crate::flags::FlagEditor;
+use crate::focus::Field;
+use crate::help::HelpBrowser;
+use crate::screens::{self, command};
+use crate::search;
+use crate::series::TimeRange;
+use crate::tools::dns::{self, DnsResult};
+use crate::tools::{args, asn, dnsbl, mailcheck, nmap, subdomains};
+use crate::tools::ping::{HEATMAP_BOUNDS, LatencyHeatmap, PingProbe};
+use crate::tools::nmap::PortChange;
+use crate::tools::geoip;
 
 // Define zones for hit testing (could be expanded)
 #[derive(Clone, Copy, Debug)]
@@ -23,12 +39,61 @@ pub enum UiZone {
     DnsTypeNext,
 }
 
+// Below this the screens can't be laid out; a notice asks for a larger terminal instead
+const MIN_WIDTH: u16 = 60;
+const MIN_HEIGHT: u16 = 15;
+// Narrower than this, panels that sit side by side are stacked
+const NARROW: u16 = 100;
+// Characters in a table row's sparkline, two samples each
+const TREND_WIDTH: usize = 8;
+
+/// A screen's short code (its default key) and name on the tab bar.
+fn tab_label(screen: CurrentScreen) -> (&'static str, &'static str) {
+    match screen {
+        CurrentScreen::Dashboard => ("D", "Dash"),
+        CurrentScreen::Ping => ("P", "Ping"),
+        CurrentScreen::Dns => ("N", "DNS"),
+        CurrentScreen::Sniffer => ("S", "Sniff"),
+        CurrentScreen::Mtr => ("M", "MTR"),
+        CurrentScreen::Nmap => ("R", "Scan"),
+        CurrentScreen::ArpScan => ("A", "Arp"),
+        CurrentScreen::Connections => ("C", "Conns"),
+        CurrentScreen::Mdns => ("B", "mDNS"),
+        CurrentScreen::Ssdp => ("U", "UPnP"),
+        CurrentScreen::Flows => ("F", "Flows"),
+        CurrentScreen::History => ("Y", "History"),
+        CurrentScreen::Wifi => ("W", "Wi-Fi"),
+        CurrentScreen::Dhcp => ("O", "DHCP"),
+        CurrentScreen::Traceroute => ("T", "Trace"),
+        CurrentScreen::Sweep => ("E", "Sweep"),
+        CurrentScreen::Syslog => ("L", "Syslog"),
+        CurrentScreen::Diagnose => ("I", "Diag"),
+        CurrentScreen::Devices => ("V", "Devices"),
+        CurrentScreen::Asn => ("J", "ASN"),
+        // Plugins have no key of their own; Tab, the palette and the API reach them
+        CurrentScreen::Plugin(i) => ("+", command::title(i)),
+        _ => screens::info(screen).map_or(("", ""), |t| (t.keys.first().copied().unwrap_or(""), t.tab)),
+    }
+}
+
 pub fn ui(f: &mut Frame, app: &mut App) {
+    draw(f, app);
+    if THEME.monochrome {
+        theme::strip_colors(f.buffer_mut());
+    }
+}
+
+fn draw(f: &mut Frame, app: &mut App) {
     let size = f.area();
     // Global Background
     let bg_block = Block::default().style(Style::default().bg(THEME.bg));
     f.render_widget(bg_block, size);
 
+    if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
+        render_too_small(f, size);
+        return;
+    }
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
@@ -54,32 +119,32 @@ pub fn ui(f: &mut Frame, app: &mut App) {
     let logo_style = Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD);
     f.render_widget(Paragraph::new(" NETOPS ").style(logo_style).bg(THEME.surface), header_chunks[0]);
 
-    // Custom Tabs
-    let tabs = ["D", "P", "N", "S", "M", "R", "A", "C"]; // Short codes
-    let tab_names = ["Dash", "Ping", "DNS", "Sniff", "MTR", "Scan", "Arp", "Conns"];
-    
-    let current_idx = match app.current_screen {
-        CurrentScreen::Dashboard => 0,
-        CurrentScreen::Ping => 1,
-        CurrentScreen::Dns => 2,
-        CurrentScreen::Sniffer => 3,
-        CurrentScreen::Mtr => 4,
-        CurrentScreen::Nmap => 5,
-        CurrentScreen::ArpScan => 6,
-        CurrentScreen::Connections => 7,
-    };
+    // Custom Tabs, in the order Tab / Shift+Tab step through
+    let all = CurrentScreen::all();
+    let (tabs, tab_names): (Vec<&str>, Vec<&str>) = all.iter().map(|s| tab_label(*s)).unzip();
+    let current_idx = all.iter().position(|s| *s == app.current_screen).unwrap_or(0);
+
+    // Only show names of unselected tabs when they all fit
+    let full_width: usize = tabs.iter().zip(tab_names.iter()).map(|(c, n)| c.len() + n.len() + 4).sum();
+    let compact = full_width > header_chunks[1].width as usize;
+
+    let split_idx = app.split_screen.and_then(|s| all.iter().position(|c| *c == s));
 
     let mut tab_spans = vec![];
     for (i, (code, name)) in tabs.iter().zip(tab_names.iter()).enumerate() {
         let is_selected = i == current_idx;
         let (bg, fg) = if is_selected {
             (THEME.primary, THEME.bg)
+        } else if split_idx == Some(i) {
+            (THEME.secondary, THEME.bg)
         } else {
             (THEME.surface, THEME.muted)
         };
         
         tab_spans.push(Span::styled(format!(" {} ", code), Style::default().fg(fg).bg(bg).add_modifier(Modifier::BOLD)));
-        tab_spans.push(Span::styled(format!("{} ", name), Style::default().fg(if is_selected { THEME.primary } else { THEME.muted }).bg(THEME.surface)));
+        if is_selected || !compact {
+            tab_spans.push(Span::styled(format!("{} ", name), Style::default().fg(if is_selected { THEME.primary } else { THEME.muted }).bg(THEME.surface)));
+        }
         tab_spans.push(Span::raw(" "));
     }
     
@@ -90,55 +155,459 @@ pub fn ui(f: &mut Frame, app: &mut App) {
     // Add a subtle padding or margin if needed, but full bleed looks modern.
     // Let's verify each render function handles its own blocks.
     
-    match app.current_screen {
-        CurrentScreen::Dashboard => render_dashboard(f, app, content_area),
-        CurrentScreen::Ping => render_ping(f, app, content_area),
-        CurrentScreen::Dns => render_dns(f, app, content_area),
-        CurrentScreen::Sniffer => render_sniffer(f, app, content_area),
-        CurrentScreen::Mtr => render_mtr(f, app, content_area),
-        CurrentScreen::Nmap => render_nmap(f, app, content_area),
-        CurrentScreen::ArpScan => render_arpscan(f, app, content_area),
-        CurrentScreen::Connections => render_connections(f, app, content_area),
-    }
-
-    // --- Footer ---
-    let footer_area = chunks[2];
-    let footer_text = Line::from(vec![
-        Span::styled(" Q ", Style::default().bg(THEME.error).fg(THEME.bg).add_modifier(Modifier::BOLD)),
-        Span::styled(" Quit ", Style::default().fg(THEME.muted).bg(THEME.surface)),
-        Span::raw(" "),
-        Span::styled(" TAB ", Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD)),
-        Span::styled(" Next ", Style::default().fg(THEME.muted).bg(THEME.surface)),
-    ]);
-    f.render_widget(Paragraph::new(footer_text).bg(THEME.surface), footer_area);
+    let content_area = if app.show_log {
+        let [content, log] = Layout::vertical([Constraint::Min(5), Constraint::Length(10)]).areas(content_area);
+        render_event_log(f, app, log);
+        content
+    } else {
+        content_area
+    };
+
+    match app.split_screen {
+        Some(other) => render_split(f, app, other, content_area),
+        None => render_screen(f, app, app.current_screen, content_area),
+    }
+
+    render_status_bar(f, app, chunks[2]);
 
-    if app.show_help {
-        render_help(f, app, size);
+    if let Some(help) = &app.help {
+        render_help(f, app, help, size);
     }
     
-    if app.show_options {
-        render_options(f, app, size);
+    if let Some(editor) = &app.flag_editor {
+        render_options(f, editor, size);
+    }
+
+    if let Some(selected) = app.filter_presets {
+        render_filter_presets(f, app, selected, size);
+    }
+
+    if app.show_wol {
+        render_wol(f, app, size);
+    }
+
+    if app.show_alerts {
+        render_alerts(f, app, size);
+    }
+
+    if app.show_profiles {
+        render_profiles(f, app, size);
+    }
+
+    if app.show_layout {
+        render_layout_editor(f, app, size);
+    }
+
+    if app.show_probe_targets {
+        render_probe_targets(f, app, size);
+    }
+
+    if app.show_oui_lookup {
+        render_oui_lookup(f, app, size);
+    }
+
+    if app.show_export {
+        render_export(f, app, size);
+    }
+
+    if app.show_conn_actions {
+        render_conn_actions(f, app, size);
+    }
+
+    if app.show_whois {
+        render_whois(f, app, size);
+    }
+
+    if app.show_mtr_paths {
+        render_mtr_paths(f, app, size);
+    }
+
+    if app.show_palette {
+        render_palette(f, app, size);
+    }
+
+    if app.confirm_kill.is_some() {
+        render_confirm_kill(f, app, size);
     }
 }
 
-fn render_options(f: &mut Frame, app: &App, area: Rect) {
-    let opts = app.get_tool_options();
-    if opts.is_empty() { return; }
-    
-    let height = (opts.len() as u16) + 4;
-    let width = 60;
-    
+fn render_too_small(f: &mut Frame, area: Rect) {
+    let text = vec![
+        Line::from(Span::styled("Terminal too small", Style::default().fg(THEME.accent).add_modifier(Modifier::BOLD))),
+        Line::from(format!("{}x{}, needs at least {}x{}", area.width, area.height, MIN_WIDTH, MIN_HEIGHT)),
+        Line::from(Span::styled("Enlarge the window or press Q to quit", Style::default().fg(THEME.muted))),
+    ];
+    let y = area.y + area.height.saturating_sub(text.len() as u16) / 2;
+    let notice = Rect { x: area.x, y, width: area.width, height: (text.len() as u16).min(area.height) };
+    f.render_widget(Paragraph::new(text).alignment(ratatui::layout::Alignment::Center).wrap(Wrap { trim: true }), notice);
+}
+
+/// Side by side when there is room, otherwise one above the other.
+fn side_by_side(area: Rect) -> Direction {
+    if area.width >= NARROW { Direction::Horizontal } else { Direction::Vertical }
+}
+
+/// Which of a table's columns to draw: all of them when their widths fit in `width`,
+/// otherwise only those not marked optional. `columns` are (width, optional).
+fn fitting_columns(width: u16, columns: &[(u16, bool)]) -> Vec<bool> {
+    let total: u16 = columns.iter().map(|(w, _)| w + 1).sum();
+    columns.iter().map(|(_, optional)| total <= width || !optional).collect()
+}
+
+/// The items (headers, cells or widths) of the columns `fitting_columns` kept.
+fn keep_columns<T>(items: impl IntoIterator<Item = T>, keep: &[bool]) -> Vec<T> {
+    items.into_iter().zip(keep).filter(|(_, keep)| **keep).map(|(item, _)| item).collect()
+}
+
+fn render_screen(f: &mut Frame, app: &mut App, screen: CurrentScreen, area: Rect) {
+    let area = match app.privileges.banner(screen) {
+        Some(text) => {
+            let [banner, rest] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area);
+            f.render_widget(Paragraph::new(format!(" ! {}", text)).style(Style::default().fg(THEME.bg).bg(THEME.accent)), banner);
+            rest
+        }
+        None => area,
+    };
+    let area = match app.tool_error(screen) {
+        Some(error) => {
+            let mut lines = vec![Line::from(format!(" x {}", error))];
+            if let Some(fix) = suggested_fix(screen, error) {
+                lines.push(Line::from(format!("   {}", fix)));
+            }
+            let [banner, rest] = Layout::vertical([Constraint::Length(lines.len() as u16), Constraint::Min(0)]).areas(area);
+            f.render_widget(Paragraph::new(lines).style(Style::default().fg(THEME.bg).bg(THEME.error)), banner);
+            rest
+        }
+        None => match app.finished.get(&screen) {
+            Some(note) if app.tool_active(screen) == Some(false) => {
+                let [banner, rest] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area);
+                f.render_widget(Paragraph::new(format!(" {} {}", screen.title(), note)).style(Style::default().fg(THEME.bg).bg(THEME.success)), banner);
+                rest
+            }
+            _ => area,
+        },
+    };
+    let hits = search_hits(app, screen);
+    if let Some(tool) = app.tools.get_mut(screen) {
+        tool.render(f, area, &hits);
+        return;
+    }
+    match screen {
+        CurrentScreen::Dashboard => render_dashboard(f, app, area),
+        CurrentScreen::Ping => render_ping(f, app, area),
+        CurrentScreen::Dns => render_dns(f, app, area),
+        CurrentScreen::Sniffer => render_sniffer(f, app, area),
+        CurrentScreen::Mtr => render_mtr(f, app, area),
+        CurrentScreen::Nmap => render_nmap(f, app, area),
+        CurrentScreen::ArpScan => render_arpscan(f, app, area),
+        CurrentScreen::Connections => render_connections(f, app, area),
+        CurrentScreen::Mdns => render_mdns(f, app, area),
+        CurrentScreen::Ssdp => render_ssdp(f, app, area),
+        CurrentScreen::Flows => render_flows(f, app, area),
+        CurrentScreen::History => render_history(f, app, area),
+        CurrentScreen::Wifi => render_wifi(f, app, area),
+        CurrentScreen::Dhcp => render_dhcp(f, app, area),
+        CurrentScreen::Traceroute => render_traceroute(f, app, area),
+        CurrentScreen::Sweep => render_sweep(f, app, area),
+        CurrentScreen::Syslog => render_syslog(f, app, area),
+        CurrentScreen::Diagnose => render_diagnose(f, app, area),
+        CurrentScreen::Devices => render_devices(f, app, area),
+        CurrentScreen::Asn => render_asn(f, app, area),
+        // Drawn by the tool itself, above
+        CurrentScreen::Ntp | CurrentScreen::Runner | CurrentScreen::HttpLoad | CurrentScreen::Plugin(_) => {}
+    }
+}
+
+/// The bottom line: search or the focused screen's keys, running tools, then log, alerts
+/// and flash messages; the metrics and API servers sit on the right.
+fn render_status_bar(f: &mut Frame, app: &App, area: Rect) {
+    let key_style = Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD);
+    let text_style = Style::default().fg(THEME.muted).bg(THEME.surface);
+    let mut spans = vec![];
+
+    if let Some(search) = &app.search
+        && search.screen == app.current_screen
+    {
+        let matches = app.search_matches();
+        let hint = if search.editing { "Enter keep  Esc cancel".to_string() } else { format!("{}/{} next/prev  Esc clear", app.keymap.label(Action::SearchNext), app.keymap.label(Action::SearchPrev)) };
+        if search.editing {
+            f.set_cursor_position((area.x + (2 + search.input.visual_cursor()) as u16, area.y));
+        }
+        spans.push(Span::styled(format!(" /{} ", search.query()), Style::default().bg(THEME.accent).fg(THEME.bg).add_modifier(Modifier::BOLD)));
+        spans.push(Span::styled(format!(" {} matches  {} ", matches.len(), hint), text_style));
+    } else {
+        for (action, desc) in status_hints(app) {
+            spans.push(Span::styled(format!(" {} ", app.keymap.label(action)), key_style));
+            spans.push(Span::styled(format!(" {} ", desc), text_style));
+        }
+    }
+
+    // Running tools, so a capture or scan left going on another screen isn't forgotten
+    let running: Vec<CurrentScreen> = CurrentScreen::all().into_iter().filter(|s| app.tool_active(*s) == Some(true)).collect();
+    if !running.is_empty() || !app.interfaces.is_empty() {
+        spans.push(Span::raw(" "));
+    }
+    for screen in &running {
+        spans.push(Span::styled(" ● ", Style::default().fg(THEME.success).bg(THEME.surface)));
+        spans.push(Span::styled(screen.title(), Style::default().fg(THEME.fg).bg(THEME.surface)));
+        if *screen == CurrentScreen::Sniffer {
+            spans.push(Span::styled(format!(" {} {} pps", app.sniffer_targets().join(","), app.sniffer_pps()), text_style));
+        }
+    }
+    if !running.contains(&CurrentScreen::Sniffer)
+        && let Some(name) = app.sniffer_targets().first()
+    {
+        spans.push(Span::styled(format!(" {} ", name), text_style));
+    }
+
+    if app.event_log.unseen > 0 {
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(
+            format!(" LOG {} ", app.event_log.unseen),
+            Style::default().bg(THEME.accent).fg(THEME.bg).add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::styled(format!(" {} ", app.keymap.label(Action::EventLog)), text_style));
+    }
+    let active_alerts: Vec<_> = app.alert_engine.active().collect();
+    if let Some(latest) = active_alerts.last() {
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(
+            format!(" ALERT {} ", active_alerts.len()),
+            Style::default().bg(THEME.error).fg(THEME.bg).add_modifier(Modifier::BOLD | Modifier::SLOW_BLINK),
+        ));
+        spans.push(Span::styled(format!(" {} ", latest.message), Style::default().fg(THEME.error).bg(THEME.surface)));
+    }
+    if let Some((status, at)) = &app.flash
+        && at.elapsed() < std::time::Duration::from_secs(3)
+    {
+        let (text, color) = match status {
+            Ok(msg) => (msg, THEME.success),
+            Err(e) => (e, THEME.error),
+        };
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(format!(" {} ", text), Style::default().fg(color).bg(THEME.surface)));
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)).bg(THEME.surface), area);
+
+    let mut server_status = vec![];
+    if let Some(err) = &app.metrics_error {
+        server_status.push(Span::styled(format!(" {} ", err), Style::default().fg(THEME.error).bg(THEME.surface)));
+    } else if app.metrics.is_some() {
+        server_status.push(Span::styled(
+            format!(" Metrics http://{}/metrics ", app.config.metrics.listen),
+            Style::default().fg(THEME.success).bg(THEME.surface),
+        ));
+    }
+    if let Some(err) = &app.api_error {
+        server_status.push(Span::styled(format!(" {} ", err), Style::default().fg(THEME.error).bg(THEME.surface)));
+    } else if app.api_events.is_some() {
+        server_status.push(Span::styled(
+            format!(" API http://{}/api ", app.config.api.listen),
+            Style::default().fg(THEME.success).bg(THEME.surface),
+        ));
+    }
+    if !server_status.is_empty() {
+        f.render_widget(Paragraph::new(Line::from(server_status)).alignment(ratatui::layout::Alignment::Right), area);
+    }
+}
+
+/// The few keys worth knowing on the focused screen; the help popup has the rest.
+fn status_hints(app: &App) -> Vec<(Action, &'static str)> {
+    let screen = app.current_screen;
+    let mut hints = match (screen, app.tool_active(screen)) {
+        (CurrentScreen::Dashboard, _) => vec![(Action::ZoomOut, "Zoom out"), (Action::ZoomIn, "Zoom in"), (Action::EditLayout, "Layout")],
+        (CurrentScreen::Sniffer, Some(true)) => vec![(Action::Start, "Stop"), (Action::Pause, "Pause"), (Action::Record, if app.recording() { "Stop recording" } else { "Record" })],
+        (CurrentScreen::Sniffer, _) => vec![(Action::Start, "Capture"), (Action::Cycle, "Add interface"), (Action::ToolOptions, "Filters"), (Action::Record, if app.recording() { "Stop recording" } else { "Record" })],
+        (CurrentScreen::Connections, _) => vec![(Action::ToggleView, "View"), (Action::Hostnames, "Hostnames")],
+        (CurrentScreen::Ping, active) => {
+            let view = (Action::Cycle, if app.ping_heatmap_view { "Chart" } else { "Heatmap" });
+            if active == Some(true) { vec![(Action::Stop, "Stop"), view] } else { vec![(Action::Start, "Start"), (Action::ToolOptions, "Flags"), view] }
+        }
+        (CurrentScreen::Asn, Some(false)) => vec![(Action::Start, "Look up")],
+        (_, Some(true)) => vec![(Action::Stop, "Stop")],
+        (_, Some(false)) => vec![(Action::Start, "Start"), (Action::ToolOptions, "Flags")],
+        (_, None) => vec![],
+    };
+    if search::SEARCHABLE.contains(&screen) {
+        hints.push((Action::Search, "Search"));
+    }
+    hints.extend([(Action::NextTab, "Next"), (Action::Help, "Help"), (Action::Quit, "Quit")]);
+    hints
+}
+
+// Newest entries at the bottom, like a terminal
+fn render_event_log(f: &mut Frame, app: &App, area: Rect) {
+    let title = match &app.event_log.file {
+        Some(path) => format!(" Event Log ({}) ", path.display()),
+        None => " Event Log ".to_string(),
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(THEME.border));
+
+    let height = area.height.saturating_sub(2) as usize;
+    let skip = app.event_log.entries.len().saturating_sub(height);
+    let lines: Vec<Line> = app.event_log.entries.iter().skip(skip).map(|entry| {
+        let color = match entry.level {
+            tracing::Level::ERROR => THEME.error,
+            tracing::Level::WARN => THEME.accent,
+            tracing::Level::INFO => THEME.success,
+            _ => THEME.muted,
+        };
+        Line::from(vec![
+            Span::styled(format!("{} ", entry.time), Style::default().fg(THEME.muted)),
+            Span::styled(format!("{:<5} ", entry.level), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+            Span::styled(format!("{:<10} ", entry.source), Style::default().fg(THEME.secondary)),
+            Span::styled(entry.message.clone(), Style::default().fg(THEME.fg)),
+        ])
+    }).collect();
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// What to try about a tool failure, recognised from the OS or tool's own message.
+fn suggested_fix(screen: CurrentScreen, error: &str) -> Option<&'static str> {
+    let e = error.to_lowercase();
+    let missing = e.contains("no such file") || e.contains("os error 2)") || e.contains("cannot find");
+    if missing && e.starts_with("failed to start") {
+        return Some(match screen {
+            CurrentScreen::Nmap => "Install nmap (apt install nmap, brew install nmap) and make sure it's on PATH.",
+            CurrentScreen::ArpScan => "Install arp-scan (apt install arp-scan, brew install arp-scan) and make sure it's on PATH.",
+            CurrentScreen::Wifi => "Install the platform's Wi-Fi tool (nmcli or iw on Linux).",
+            _ => "Install the missing program and make sure it's on PATH.",
+        });
+    }
+    if e.contains("permission denied") || e.contains("operation not permitted") || e.contains("requires root") || e.contains("root privileges") {
+        return Some("Run as root, or grant raw socket access: sudo setcap cap_net_raw,cap_net_admin=eip $(which netops)");
+    }
+    if e.contains("address already in use") || e.contains("address in use") {
+        return Some("Another program already has this port; stop it or pick a different port.");
+    }
+    if e.contains("resolve") || e.contains("no addresses") || e.contains("failed to lookup") || e.contains("name or service not known") {
+        return Some("Check the host name for typos and that DNS works, or enter an IP address instead.");
+    }
+    if e.contains("interface not found") || e.contains("no such device") {
+        return Some("The interface went away; pick another one with Left/Right.");
+    }
+    if e.contains("network is unreachable") || e.contains("no route to host") {
+        return Some("No route to the target: check the link is up and there's a default route.");
+    }
+    None
+}
+
+// Two screens side by side (stacked on narrow terminals); the focused pane gets the accent border
+fn render_split(f: &mut Frame, app: &mut App, other: CurrentScreen, area: Rect) {
+    let direction = if area.width >= 120 { Direction::Horizontal } else { Direction::Vertical };
+    let panes = Layout::default()
+        .direction(direction)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+
+    let focused = app.current_screen;
+    let (left, right) = if app.split_focus_left { (focused, other) } else { (other, focused) };
+    for (screen, pane, is_focused) in [(left, panes[0], app.split_focus_left), (right, panes[1], !app.split_focus_left)] {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(if is_focused { BorderType::Thick } else { BorderType::Plain })
+            .border_style(Style::default().fg(if is_focused { THEME.primary } else { THEME.border }))
+            .title(Span::styled(format!(" {} ", screen.title()), Style::default().fg(if is_focused { THEME.primary } else { THEME.muted })));
+        let inner = block.inner(pane);
+        f.render_widget(block, pane);
+        render_screen(f, app, screen, inner);
+    }
+}
+
+/// Row indices on `screen` matching the table search.
+fn search_hits(app: &App, screen: CurrentScreen) -> Vec<usize> {
+    match &app.search {
+        Some(search) if search.screen == screen => app.search_matches(),
+        _ => vec![],
+    }
+}
+
+pub fn row_style(hit: bool) -> Style {
+    if hit {
+        Style::default().fg(THEME.accent).add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+    } else {
+        Style::default().fg(THEME.fg)
+    }
+}
+
+/// A target input's text, followed by the rest of its autocomplete suggestion, dimmed.
+fn input_line<'a>(app: &App, screen: CurrentScreen, value: &'a str) -> Line<'a> {
+    let rest = app.input_suggestion(screen).and_then(|s| s.get(value.len()..).map(str::to_string)).unwrap_or_default();
+    Line::from(vec![Span::raw(value), Span::styled(rest, Style::default().fg(THEME.muted))])
+}
+
+/// Border color of an input: accented while it has the keyboard.
+fn focus_color(focused: bool) -> Color {
+    if focused { THEME.accent } else { THEME.border }
+}
+
+/// The rows `start..end` a `height`-line viewport shows, scrolled from `offset` just enough to
+/// keep `selected` in view (as ratatui would), so long lists only build the rows on screen.
+pub fn visible_window(offset: usize, selected: Option<usize>, len: usize, height: usize) -> (usize, usize) {
+    let height = height.max(1);
+    let mut start = offset;
+    if let Some(sel) = selected.map(|s| s.min(len.saturating_sub(1))) {
+        if sel < start {
+            start = sel;
+        } else if sel >= start + height {
+            start = sel + 1 - height;
+        }
+    }
+    let start = start.min(len.saturating_sub(height));
+    (start, (start + height).min(len))
+}
+
+fn render_filter_presets(f: &mut Frame, app: &App, selected: usize, area: Rect) {
+    let choices = app.filter_choices();
+    let width = 72.min(area.width);
+    let height = (choices.len() as u16 + 2).min(area.height);
     let popup_area = Rect {
         x: area.width.saturating_sub(width) / 2,
         y: area.height.saturating_sub(height) / 2,
         width,
         height,
     };
+    f.render_widget(Clear, popup_area);
+
+    let label_width = choices.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+    let items: Vec<ListItem> = choices.iter().map(|(label, filter)| {
+        ListItem::new(Line::from(vec![
+            Span::styled(format!(" {:<w$}  ", label, w = label_width), Style::default().fg(THEME.muted)),
+            Span::styled(filter.clone(), Style::default().fg(THEME.fg)),
+        ]))
+    }).collect();
+    let list = List::new(items)
+        .block(Block::default()
+            .title(" Capture Filters (Enter: Use, Esc: Cancel) ")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(THEME.accent))
+            .bg(THEME.bg))
+        .highlight_style(Style::default().fg(THEME.bg).bg(THEME.accent).add_modifier(Modifier::BOLD));
+    let mut state = ListState::default().with_selected(Some(selected));
+    f.render_stateful_widget(list, popup_area, &mut state);
+}
+
+fn render_options(f: &mut Frame, editor: &FlagEditor, area: Rect) {
+    let height = (editor.rows.len() as u16) + 6;
+    let width = 72.min(area.width);
+    
+    let popup_area = Rect {
+        x: area.width.saturating_sub(width) / 2,
+        y: area.height.saturating_sub(height) / 2,
+        width,
+        height: height.min(area.height),
+    };
     
     f.render_widget(Clear, popup_area);
     
     let block = Block::default()
-        .title(" Select Option (Enter to Insert) ")
+        .title(" Flags (Space: Toggle, Type: Value, Enter: Apply, Esc: Cancel) ")
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(THEME.accent))
@@ -147,131 +616,104 @@ fn render_options(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(block.clone(), popup_area);
     
     let inner = block.inner(popup_area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(3)])
+        .split(inner);
     
-    let items: Vec<ListItem> = opts.iter().enumerate().map(|(i, (flag, desc, _))| {
-        let style = if i == app.options_scroll {
+    let flag_width = editor.rows.iter().map(|r| r.spec.flag.len()).max().unwrap_or(0);
+    let items: Vec<ListItem> = editor.rows.iter().enumerate().map(|(i, row)| {
+        let style = if i == editor.selected {
             Style::default().fg(THEME.bg).bg(THEME.accent).add_modifier(Modifier::BOLD)
         } else {
             Style::default().fg(THEME.fg)
         };
+        let check = if row.checked { "[x]" } else { "[ ]" };
+        let value = if row.spec.value.is_some() { row.value.value() } else { "" };
         
         ListItem::new(Line::from(vec![
-            Span::styled(format!(" {:<5} ", flag), style),
-            Span::styled(format!(" {}", desc), style),
+            Span::styled(format!(" {} {:<w$} ", check, row.spec.flag, w = flag_width), style),
+            Span::styled(format!("{:<10} ", value), style),
+            Span::styled(row.spec.description, style),
         ]))
     }).collect();
     
-    let list = List::new(items);
-    f.render_widget(list, inner);
+    f.render_widget(List::new(items), chunks[0]);
+    
+    let command = Paragraph::new(editor.command())
+        .style(Style::default().fg(THEME.fg))
+        .wrap(Wrap { trim: true })
+        .block(Block::default().title(" Command ").borders(Borders::TOP).border_style(Style::default().fg(THEME.border)));
+    f.render_widget(command, chunks[1]);
+    
+    let row = &editor.rows[editor.selected];
+    if row.spec.value.is_some() && (editor.selected as u16) < chunks[0].height {
+        f.set_cursor_position((
+            chunks[0].x + 6 + flag_width as u16 + row.value.visual_cursor() as u16,
+            chunks[0].y + editor.selected as u16,
+        ));
+    }
 }
 
-fn render_help(f: &mut Frame, app: &App, area: Rect) {
+fn render_help(f: &mut Frame, app: &App, help: &HelpBrowser, area: Rect) {
     let block = Block::default()
-        .title(" Help ")
+        .title(" Help (type to search, Up/Down/PgUp/PgDn scroll, Esc close) ")
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(THEME.primary))
         .bg(THEME.bg); 
         
-    // Calculate centered rect
+    // As large as the terminal allows, up to a comfortable reading size
+    let width = 84.min(area.width);
+    let height = 40.min(area.height);
     let popup_area = Rect {
-        x: area.width.saturating_sub(70) / 2,
-        y: area.height.saturating_sub(26) / 2,
-        width: 70,
-        height: 26,
+        x: area.width.saturating_sub(width) / 2,
+        y: area.height.saturating_sub(height) / 2,
+        width,
+        height,
     };
     
     f.render_widget(Clear, popup_area);
-    
-    let mut text = vec![
-        Line::from(vec![Span::styled(" Global Keys ", Style::default().fg(THEME.accent).add_modifier(Modifier::BOLD))]),
-        Line::from(" [Alt + 1-8]     Switch Tab (Dash/Ping/DNS...)"),
-        Line::from(" [Shift + Key]   Legacy Switch (D,P,N...)"),
-        Line::from(" [H] or [?]      Toggle Help"),
-        Line::from(" [Ctrl+F]        Tool Options/Flags"),
-        Line::from(" [Q]             Quit"),
-        Line::from(""),
-    ];
-    
-    let tool_specific = match app.current_screen {
-        CurrentScreen::Dashboard => vec![
-            " Dashboard ",
-            " Overview of network traffic and connectivity.",
-            " - Top Left:  Real-time WAN I/O bandwidth.",
-            " - Top Right: Active connection count.",
-            " - Bot Left:  Interface status.",
-            " - Bot Right: Top 5 Remote ASNs (Organizations).",
-        ],
-        CurrentScreen::Ping => vec![
-            " Ping Tool ",
-            " [Enter]  Start Ping to target",
-            " [Esc]    Stop Ping",
-            " ",
-            " Features:",
-            " - Real-time Latency Graph (Bottom)",
-            " - Live Statistics (Min/Avg/Max/Loss)",
-            " - Flags: -i <sec> -s <bytes> -c <count>",
-        ],
-        CurrentScreen::Dns => vec![
-            " DNS Resolver ",
-            " [Enter]  Resolve Domain",
-            " [Tab]    Cycle Record Type (A -> AAAA -> MX...)",
-            " ",
-            " Returns detailed records including TTL.",
-        ],
-        CurrentScreen::Sniffer => vec![
-            " Packet Sniffer ",
-            " [Enter]      Start/Stop Capture",
-            " [Left/Right] Select Interface",
-            " [Filter]     BPF Syntax (e.g. 'tcp port 80')",
-            " ",
-            " Displays: Time, Protocol, Source, Dest, Length, Info",
-        ],
-        CurrentScreen::Mtr => vec![
-            " My Traceroute (MTR) ",
-            " [Enter]    Start Trace",
-            " [Esc]      Stop",
-            " [Up/Down]  Select Hop to view Latency Graph",
-            " ",
-            " Shows path to target with loss & jitter per hop.",
-        ],
-        CurrentScreen::Nmap => vec![
-            " Port Scanner ",
-            " [Enter]  Start Scan",
-            " [Esc]    Stop/Detach",
-            " ",
-            " Useful Flags (Ctrl+F):",
-            " -p 80,443   Specific ports",
-            " -F          Fast scan (top 100 ports)",
-            " -sV         Service Version detection",
-        ],
-        CurrentScreen::ArpScan => vec![
-            " Arp Scanner ",
-            " [Enter]  Start Scan",
-            " [Esc]    Stop",
-            " ",
-            " automatically scans local network if no args given.",
-            " -l: Localnet (default)",
-            " -I: Interface (e.g. -I en0)",
-            " ",
-            " View switches to Table composed of IP, MAC to Vendor.",
-        ],
-        CurrentScreen::Connections => vec![
-            " Active Connections ",
-            " Monitors live socket connections.",
-            " ",
-            " - [Table] Real-time list of remote peers.",
-            " - [Map]   World map showing peer locations.",
-            " - Shows ASN (ISP/Org) for each IP.",
-        ],
-    };
-    
-    text.push(Line::from(Span::styled(tool_specific[0], Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))));
-    for line in tool_specific.iter().skip(1) {
-        text.push(Line::from(*line));
+    f.render_widget(block.clone(), popup_area);
+    let inner = block.inner(popup_area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1)])
+        .split(inner);
+
+    f.render_widget(Paragraph::new(Line::from(vec![
+        Span::styled(" / ", Style::default().fg(THEME.accent)),
+        Span::styled(help.input.value(), Style::default().fg(THEME.fg)),
+    ])), chunks[0]);
+    f.set_cursor_position((chunks[0].x + 3 + help.input.visual_cursor() as u16, chunks[0].y));
+
+    let lines = help.lines(&app.keymap, app.current_screen);
+    if lines.is_empty() {
+        f.render_widget(Paragraph::new(" No keys or notes match").style(Style::default().fg(THEME.muted)), chunks[1]);
+        return;
     }
+    let visible = chunks[1].height as usize;
+    let offset = help.scroll.min(lines.len().saturating_sub(visible));
+    let text: Vec<Line> = lines.iter().skip(offset).take(visible).map(|line| {
+        if line.heading {
+            Line::from(Span::styled(format!(" {}", line.text), Style::default().fg(THEME.accent).add_modifier(Modifier::BOLD)))
+        } else if line.keys.is_empty() {
+            Line::from(Span::styled(format!(" {}", line.text), Style::default().fg(THEME.fg)))
+        } else {
+            Line::from(vec![
+                Span::styled(format!(" {:<20}", line.keys), Style::default().fg(THEME.primary)),
+                Span::styled(line.text.clone(), Style::default().fg(THEME.fg)),
+            ])
+        }
+    }).collect();
+    f.render_widget(Paragraph::new(text), chunks[1]);
 
-    f.render_widget(Paragraph::new(text).block(block).alignment(ratatui::layout::Alignment::Center), popup_area);
+    if lines.len() > visible {
+        let position = format!(" {}-{}/{} ", offset + 1, (offset + visible).min(lines.len()), lines.len());
+        let width = (position.len() as u16).min(popup_area.width);
+        let x = popup_area.x + popup_area.width.saturating_sub(width + 2);
+        f.render_widget(Paragraph::new(position).style(Style::default().fg(THEME.muted)), Rect { x, y: popup_area.y + popup_area.height - 1, width, height: 1 });
+    }
 }
 
 // ... render_dashboard, render_ping, render_dns, render_sniffer ...
@@ -289,7 +731,7 @@ fn render_mtr(f: &mut Frame, app: &mut App, area: Rect) {
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(if app.mtr_active { THEME.primary } else { THEME.border }))
         .title(Span::styled(" TARGET ", Style::default().fg(THEME.fg)));
-    f.render_widget(Paragraph::new(app.mtr_input.value()).block(input_block).style(Style::default().fg(THEME.primary)), chunks[0]);
+    f.render_widget(Paragraph::new(input_line(app, CurrentScreen::Mtr, app.mtr_input.value())).block(input_block).style(Style::default().fg(THEME.primary)), chunks[0]);
     if !app.mtr_active {
          f.set_cursor_position((chunks[0].x + app.mtr_input.visual_cursor() as u16 + 1, chunks[0].y + 1));
     }
@@ -302,31 +744,43 @@ fn render_mtr(f: &mut Frame, app: &mut App, area: Rect) {
 
     // Results Table
     use ratatui::widgets::{Table, Row};
-    let header_cells = ["Hop", "Host", "Loss%", "Snt", "Last", "Avg", "Best", "Wrst", "Jit"]
+    let header_cells = ["Hop", "Host", "Chg", "Loss%", "Snt", "Last", "Avg", "Best", "Wrst", "Jit", "p50", "p95", "p99", "History"]
         .iter().map(|h| ratatui::widgets::Cell::from(*h).style(Style::default().fg(THEME.muted).add_modifier(Modifier::BOLD)));
     let header = Row::new(header_cells).style(Style::default().bg(THEME.surface)).height(1);
 
-    let rows = app.mtr_hops.iter().map(|hop| {
-        let loss_color = if hop.loss >= 10.0 { THEME.error } else if hop.loss > 0.0 { THEME.secondary } else { THEME.success };
-        let lat_color = if hop.last > 100 { THEME.error } else if hop.last > 50 { THEME.secondary } else { THEME.primary };
+    let hits = search_hits(app, CurrentScreen::Mtr);
+    let slo = &app.config.slo;
+    let rows = app.mtr_hops.iter().enumerate().map(|(i, hop)| {
+        let loss_color = slo_color(hop.loss, slo.loss_percent, 0.0, THEME.success);
+        let lat_color = slo_color(hop.last as f64, slo.latency_ms, slo.latency_ms / 2.0, THEME.primary);
+        let pct = |p: f64| hop.rtt.percentile(p).map_or(String::new(), |v| format!("{:.0}ms", v));
         
         let cells = vec![
             ratatui::widgets::Cell::from(format!("{:02}", hop.ttl)),
             ratatui::widgets::Cell::from(hop.host.clone()),
-            ratatui::widgets::Cell::from(format!("{:.1}%", hop.loss)).style(Style::default().fg(loss_color)),
+            // Another router answered this TTL at some point: a flap, or ECMP
+            ratatui::widgets::Cell::from(if hop.changes > 0 { format!("~{}", hop.changes) } else { String::new() }).style(Style::default().fg(THEME.accent)),
+            ratatui::widgets::Cell::from(format!("{:.1}%{}", hop.loss, slo_mark(hop.loss, slo.loss_percent))).style(Style::default().fg(loss_color)),
             ratatui::widgets::Cell::from(format!("{}", hop.sent)),
             ratatui::widgets::Cell::from(format!("{}ms", hop.last)).style(Style::default().fg(lat_color)),
             ratatui::widgets::Cell::from(format!("{}ms", hop.avg)),
             ratatui::widgets::Cell::from(format!("{}ms", hop.best)),
+            ratatui::widgets::Cell::from(format!("{}ms", hop.worst)),
             ratatui::widgets::Cell::from(format!("{}ms", hop.jitter)),
+            ratatui::widgets::Cell::from(pct(50.0)),
+            ratatui::widgets::Cell::from(pct(95.0)),
+            ratatui::widgets::Cell::from(pct(99.0)),
+            ratatui::widgets::Cell::from(sparkline::braille(hop.history.iter().map(|&v| v as f64), TREND_WIDTH)).style(Style::default().fg(lat_color)),
         ];
-        Row::new(cells).style(Style::default().fg(THEME.fg))
+        Row::new(cells).style(row_style(hits.contains(&i)))
     });
 
     let table = Table::new(rows, [
-        Constraint::Length(4), Constraint::Length(25), Constraint::Length(8),
+        Constraint::Length(4), Constraint::Length(25), Constraint::Length(4), Constraint::Length(8),
         Constraint::Length(6), Constraint::Length(8), Constraint::Length(8),
-        Constraint::Length(8), Constraint::Length(8), Constraint::Length(8)
+        Constraint::Length(8), Constraint::Length(8), Constraint::Length(8),
+        Constraint::Length(7), Constraint::Length(7), Constraint::Length(7),
+        Constraint::Length(TREND_WIDTH as u16),
     ].as_ref())
     .header(header)
     .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD)) // Assuming selection added to theme or reuse primary
@@ -336,62 +790,177 @@ fn render_mtr(f: &mut Frame, app: &mut App, area: Rect) {
 
     f.render_stateful_widget(table, content_chunks[0], &mut app.mtr_table_state);
 
-    // Graph for Selected Hop
+    // Graph and distribution for Selected Hop
     if let Some(hop) = app.mtr_hops.get(app.mtr_selected_hop) {
+        let graph_split = Layout::default()
+            .direction(side_by_side(content_chunks[1]))
+            .constraints([Constraint::Percentage(65), Constraint::Percentage(35)].as_ref())
+            .split(content_chunks[1]);
         let history: Vec<(f64, f64)> = hop.history.iter().enumerate().map(|(i,&v)| (i as f64, v as f64)).collect();
-        let max_lat = hop.history.iter().max().unwrap_or(&100).max(&50) * 2;
+        let slo = app.config.slo.latency_ms;
+        let max_lat = (*hop.history.iter().max().unwrap_or(&100).max(&50) as f64).max(slo) * 2.0;
+        // The x axis spans the configured retention
+        let x_max = hop.history.capacity() as f64;
+        let overlay = SloOverlay::new(slo, &[&history], x_max);
+        let mut datasets: Vec<Dataset> = overlay.iter().flat_map(|o| o.datasets()).collect();
+        datasets.push(Dataset::default().marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(THEME.primary)).data(&history));
         
-        let chart = Chart::new(vec![
-            Dataset::default().marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(THEME.primary)).data(&history)
-        ])
+        if app.mtr_flows.flows.len() > 1 {
+            render_flow_tree(f, graph_split[0], app);
+            render_histogram(f, graph_split[1], &hop.rtt, slo);
+            return;
+        }
+        let chart = Chart::new(datasets)
         .block(Block::default().title(format!(" Latency: {} ", hop.host)).borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)))
-        .x_axis(Axis::default().bounds([0.0, 100.0]).style(Style::default().fg(THEME.muted)))
-        .y_axis(Axis::default().bounds([0.0, max_lat as f64]).style(Style::default().fg(THEME.muted)));
-        f.render_widget(chart, content_chunks[1]);
+        .x_axis(Axis::default().bounds([0.0, x_max]).style(Style::default().fg(THEME.muted)))
+        .y_axis(Axis::default().bounds([0.0, max_lat]).style(Style::default().fg(THEME.muted)));
+        f.render_widget(chart, graph_split[0]);
+        render_histogram(f, graph_split[1], &hop.rtt, slo);
     } else {
         f.render_widget(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).style(Style::default().fg(THEME.muted)), content_chunks[1]);
     }
 }
 
+/// `--paths` runs: where the flow ids went, one line per router, branching where ECMP split them.
+fn render_flow_tree(f: &mut Frame, area: Rect, app: &App) {
+    let flows = app.mtr_flows.flows.len();
+    let tree = app.mtr_flows.tree();
+    let splits = tree.windows(2).filter(|w| w[1].ttl <= w[0].ttl).count();
+    let title = format!(" ECMP Paths: {} flows, {} ", flows, if splits == 0 { "one path".to_string() } else { format!("{} branches", splits + 1) });
+    let lines: Vec<Line> = tree.iter().map(|line| {
+        let host = line.host.map_or("???".to_string(), |ip| ip.to_string());
+        let share = if line.flows < flows { format!("  {}/{}", line.flows, flows) } else { String::new() };
+        Line::from(vec![
+            Span::styled(format!("{:>2} ", line.ttl), Style::default().fg(THEME.muted)),
+            Span::styled(line.prefix.clone(), Style::default().fg(THEME.border)),
+            Span::styled(host, Style::default().fg(if line.host.is_some() { THEME.fg } else { THEME.muted })),
+            Span::styled(share, Style::default().fg(THEME.accent)),
+        ])
+    }).collect();
+    let block = Block::default().title(title).borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// ICMP messages seen by type, most frequent first; errors (unreachables, redirects, expired
+/// TTLs) in red, since a burst of them usually means something upstream is wrong.
+fn render_icmp_stats(f: &mut Frame, app: &App, area: Rect) {
+    let mut kinds: Vec<_> = app.sniffer_icmp.iter().collect();
+    kinds.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    let total: u64 = kinds.iter().map(|(_, n)| **n).sum();
+    let errors: u64 = kinds.iter().filter(|(k, _)| k.is_error()).map(|(_, n)| **n).sum();
+    let width = area.width.saturating_sub(2) as usize;
+    let lines: Vec<Line> = kinds.iter().map(|(kind, n)| {
+        let label = format!("{}{}", if kind.v6 { "v6 " } else { "" }, kind.label());
+        let count = n.to_string();
+        let pad = width.saturating_sub(label.chars().count() + count.len() + 1);
+        let color = if kind.is_error() { THEME.error } else { THEME.fg };
+        Line::from(vec![
+            Span::styled(format!(" {}{}", label, " ".repeat(pad)), Style::default().fg(color)),
+            Span::styled(count, Style::default().fg(THEME.muted)),
+        ])
+    }).collect();
+    let title = if errors > 0 { format!(" ICMP: {} ({} errors) ", total, errors) } else { format!(" ICMP: {} ", total) };
+    let block = Block::default().title(title).borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
 // ... render_dashboard, render_ping, render_dns ...
 
-fn render_sniffer(f: &mut Frame, app: &App, area: Rect) {
+fn render_sniffer(f: &mut Frame, app: &mut App, area: Rect) {
+    // Narrow terminals get the filter on its own line instead of beside the interface
+    let narrow = area.width < NARROW;
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+        .constraints([Constraint::Length(if narrow { 5 } else { 4 }), Constraint::Min(1)].as_ref())
         .split(area);
 
     // Controls
-    let current = app.interfaces.get(app.selected_interface_index).map(|i| i.name.as_str()).unwrap_or("None");
-    let (status_text, status_col) = if app.sniffer_active { ("CAPTURING", THEME.success) } else { ("IDLE", THEME.muted) };
-    
-    let info_text = Line::from(vec![
-        Span::raw(" Interface: "),
-        Span::styled(current, Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD)),
-        Span::raw("  Status: "),
-        Span::styled(status_text, Style::default().fg(status_col).add_modifier(Modifier::BOLD)),
-    ]);
+    let current = match app.interfaces.get(app.selected_interface_index) {
+        Some(i) if app.sniffer_capture.contains(&i.name) => format!("[x] {}", i.name),
+        Some(i) if !app.sniffer_capture.is_empty() => format!("[ ] {}", i.name),
+        Some(i) => i.name.clone(),
+        None => "All".to_string(),
+    };
+    let capturing = app.sniffer_targets().join("+");
+    let (status_text, status_col) = match (app.sniffer_active, app.sniffer_paused_at.is_some()) {
+        (true, true) => ("PAUSED", THEME.accent),
+        (true, false) => ("CAPTURING", THEME.success),
+        (false, _) => ("IDLE", THEME.muted),
+    };
+    let displayed = app.sniffer_view().len();
+    let captured = app.sniffer.packet_count.load(std::sync::atomic::Ordering::Relaxed);
+    let (rate_rx, rate_tx) = app.sniffer_rate();
+
+    let mut info_text = vec![
+        Line::from(vec![
+            Span::raw(" Interface: "),
+            Span::styled(current, Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD)),
+            Span::raw("  Status: "),
+            Span::styled(status_text, Style::default().fg(status_col).add_modifier(Modifier::BOLD)),
+        ]),
+        Line::from(vec![
+            Span::styled(format!(" Captured: {}  Matched: {}  Displayed: {}  Dropped: {}", captured, app.sniffer_seen, displayed, app.sniffer_evicted), Style::default().fg(THEME.muted)),
+            Span::styled(if app.sniffer_active { format!("  ↓ {}  ↑ {}", units::rate_mbps(rate_rx), units::rate_mbps(rate_tx)) } else { String::new() }, Style::default().fg(THEME.secondary)),
+            Span::styled(if app.sniffer_autoscroll { "" } else { "  [auto-scroll off]" }, Style::default().fg(THEME.accent)),
+        ]),
+    ];
+    if let Some(ring) = app.sniffer.ring.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        let file = ring.current().and_then(|p| p.file_name()).map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| "(first packet opens a file)".to_string());
+        info_text[0].spans.push(Span::styled("  ● REC ", Style::default().fg(THEME.error).add_modifier(Modifier::BOLD)));
+        info_text[0].spans.push(Span::styled(format!("{} packets, {} files  {}", ring.packets, ring.files_started, file), Style::default().fg(THEME.muted)));
+        if let Some(until) = app.trigger_until {
+            let left = until.saturating_duration_since(std::time::Instant::now()).as_secs();
+            info_text[0].spans.push(Span::styled(format!("  (triggered, {}s left)", left), Style::default().fg(THEME.accent)));
+        }
+    }
+    let block_title = if capturing.is_empty() { " Sniffer ".to_string() } else { format!(" Sniffer: {} ", capturing) };
     
-    f.render_widget(Paragraph::new(info_text).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)).title(" Sniffer ")), chunks[0]);
+    let focused = app.focused(CurrentScreen::Sniffer);
+    let border = focus_color(focused == Some(Field::SnifferInterface) && !app.sniffer_active);
+    f.render_widget(Paragraph::new(info_text).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(border)).title(block_title)), chunks[0]);
     
     // Controls 2 (Filter)
-    let filter_area = Rect { x: chunks[0].x + 40, y: chunks[0].y, width: chunks[0].width.saturating_sub(40), height: 3 };
-    let filter_block = Block::default().title(" Filter ").borders(Borders::LEFT);
-    f.render_widget(Paragraph::new(app.sniffer_filter_input.value()).block(filter_block).style(Style::default().fg(THEME.fg)), filter_area);
+    let filter_focused = focused == Some(Field::SnifferFilter) && !app.sniffer_active;
+    // Where the filter text starts, for the cursor
+    let (filter_x, filter_y) = if narrow {
+        let filter_area = Rect { x: chunks[0].x + 1, y: chunks[0].y + 3, width: chunks[0].width.saturating_sub(2), height: 1 };
+        let label = Span::styled(" Filter: ", Style::default().fg(focus_color(filter_focused)));
+        f.render_widget(Paragraph::new(Line::from(vec![label, Span::raw(app.sniffer_filter_input.value())])).style(Style::default().fg(THEME.fg)), filter_area);
+        (filter_area.x + 9, filter_area.y)
+    } else {
+        let filter_area = Rect { x: chunks[0].x + 40, y: chunks[0].y, width: chunks[0].width.saturating_sub(40), height: 2 };
+        let filter_block = Block::default().title(" Filter ").borders(Borders::LEFT).border_style(Style::default().fg(focus_color(filter_focused)));
+        f.render_widget(Paragraph::new(app.sniffer_filter_input.value()).block(filter_block).style(Style::default().fg(THEME.fg)), filter_area);
+        (filter_area.x + 1, filter_area.y + 1)
+    };
     
-    if !app.sniffer_active {
-         f.set_cursor_position((
-            filter_area.x + 1 + app.sniffer_filter_input.visual_cursor() as u16,
-            filter_area.y + 1
-        ));
+    if filter_focused {
+         f.set_cursor_position((filter_x + app.sniffer_filter_input.visual_cursor() as u16, filter_y));
+    }
+
+    // ICMP breakdown beside the table once there's any, if there's room
+    let (table_area, icmp_area) = if !app.sniffer_icmp.is_empty() && chunks[1].width >= 110 {
+        let split = Layout::default().direction(Direction::Horizontal).constraints([Constraint::Min(60), Constraint::Length(34)]).split(chunks[1]);
+        (split[0], Some(split[1]))
+    } else {
+        (chunks[1], None)
+    };
+    if let Some(area) = icmp_area {
+        render_icmp_stats(f, app, area);
     }
 
     // Table
     use ratatui::widgets::{Table, Row};
-    let header = Row::new(["Time", "Proto", "Source", "Dest", "Len", "Info"].iter().map(|h| ratatui::widgets::Cell::from(*h).style(Style::default().fg(THEME.muted).add_modifier(Modifier::BOLD))))
+    // The interface and vendor columns go first when the table is narrow
+    let columns = fitting_columns(table_area.width, &[(10, false), (8, true), (6, false), (20, false), (14, true), (20, false), (6, false), (10, false)]);
+    let header = Row::new(keep_columns(["Time", "Iface", "Proto", "Source", "Src Vendor", "Dest", "Len", "Info"].iter().map(|h| ratatui::widgets::Cell::from(*h).style(Style::default().fg(THEME.muted).add_modifier(Modifier::BOLD))), &columns))
         .style(Style::default().bg(THEME.surface)).height(1);
 
-    let rows = app.sniffer_packets.iter().rev().take(50).map(|p| {
+    // Only the packets in view become rows; the table is drawn as if they were all there is
+    let hits = search_hits(app, CurrentScreen::Sniffer);
+    let state = &app.sniffer_table_state;
+    let (start, end) = visible_window(state.offset(), state.selected(), displayed, table_area.height.saturating_sub(1) as usize);
+    let rows: Vec<Row> = app.sniffer_view().enumerate().skip(start).take(end - start).map(|(i, p)| {
         let proto_color = match p.protocol.as_str() {
             "TCP" => Color::Cyan,
             "UDP" => Color::Yellow,
@@ -399,38 +968,52 @@ fn render_sniffer(f: &mut Frame, app: &App, area: Rect) {
             _ => THEME.fg,
         };
         
-        Row::new(vec![
+        Row::new(keep_columns([
             ratatui::widgets::Cell::from(p.time.clone()).style(Style::default().fg(THEME.muted)),
+            ratatui::widgets::Cell::from(p.interface.clone()).style(Style::default().fg(THEME.muted)),
             ratatui::widgets::Cell::from(p.protocol.clone()).style(Style::default().fg(proto_color)),
             ratatui::widgets::Cell::from(p.source.clone()),
+            ratatui::widgets::Cell::from(app.mac_vendor(&p.source_mac)).style(Style::default().fg(THEME.muted)),
             ratatui::widgets::Cell::from(p.destination.clone()),
             ratatui::widgets::Cell::from(p.length.clone()),
             ratatui::widgets::Cell::from(p.info.clone()),
-        ]).style(Style::default().fg(THEME.fg))
-    });
+        ], &columns)).style(row_style(hits.contains(&i)))
+    }).collect();
 
-    let table = Table::new(rows, [
-        Constraint::Length(10), Constraint::Length(6), Constraint::Length(20),
+    let table = Table::new(rows, keep_columns([
+        Constraint::Length(10), Constraint::Length(8), Constraint::Length(6), Constraint::Length(20), Constraint::Length(14),
         Constraint::Length(20), Constraint::Length(6), Constraint::Min(10)
-    ].as_ref()).header(header);
-    
-    f.render_widget(table, chunks[1]);
+    ], &columns)).header(header)
+    .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD));
+
+    let selected = app.sniffer_table_state.selected().filter(|s| (start..end).contains(s)).map(|s| s - start);
+    f.render_stateful_widget(table, table_area, &mut ratatui::widgets::TableState::default().with_selected(selected));
+    *app.sniffer_table_state.offset_mut() = start;
 }
 
-fn render_nmap(f: &mut Frame, app: &App, area: Rect) {
+fn render_nmap(f: &mut Frame, app: &mut App, area: Rect) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
         .split(area);
 
     let input_border_color = if app.nmap_active { THEME.success } else { THEME.border };
+    // What the typed targets cover, or why nmap would refuse them, updated as you type
+    let preview = match app.nmap_input.value().trim() {
+        "" => Line::from(""),
+        value => match args::split(value).and_then(|a| nmap::Targets::parse(&a)) {
+            Ok(targets) => Line::from(Span::styled(format!(" {} ", targets.describe()), Style::default().fg(THEME.muted))),
+            Err(e) => Line::from(Span::styled(format!(" {}{} ", theme::mark(false), e), Style::default().fg(THEME.error))),
+        },
+    };
     let input_block = Block::default()
         .title(" Nmap Target/Args ")
+        .title(preview.right_aligned())
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(input_border_color));
     
-    let input = Paragraph::new(app.nmap_input.value()).block(input_block).style(Style::default().fg(THEME.fg));
+    let input = Paragraph::new(input_line(app, CurrentScreen::Nmap, app.nmap_input.value())).block(input_block).style(Style::default().fg(THEME.fg));
     f.render_widget(input, chunks[0]);
 
     if !app.nmap_active {
@@ -446,17 +1029,65 @@ fn render_nmap(f: &mut Frame, app: &App, area: Rect) {
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(THEME.border));
         
-    let items: Vec<ListItem> = app.nmap_output.iter().map(|line| {
-        ListItem::new(Line::from(line.clone()))
+    let split = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(chunks[1]);
+    let list_area = if app.nmap_ports.is_empty() { chunks[1] } else { split[0] };
+
+    // Only the lines in view become items. The list state only scrolls when a search match is selected.
+    let hits = search_hits(app, CurrentScreen::Nmap);
+    let state = &app.nmap_list_state;
+    let (start, end) = visible_window(state.offset(), state.selected(), app.nmap_output.len(), list_area.height.saturating_sub(2) as usize);
+    let items: Vec<ListItem> = app.nmap_output.range(start..end).enumerate().map(|(i, line)| {
+        ListItem::new(Line::from(line.clone())).style(row_style(hits.contains(&(start + i))))
     }).collect();
-    
-    // Auto-scroll to bottom if running? implementation for List scrolling usually requires ListState (TODO)
-    // For now simple list.
-    let list = List::new(items).block(output_block).style(Style::default().fg(THEME.fg));
-    f.render_widget(list, chunks[1]);
+    let list = List::new(items).block(output_block).style(Style::default().fg(THEME.fg))
+        .highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD));
+
+    let selected = app.nmap_list_state.selected().filter(|s| (start..end).contains(s)).map(|s| s - start);
+    f.render_stateful_widget(list, list_area, &mut ratatui::widgets::ListState::default().with_selected(selected));
+    *app.nmap_list_state.offset_mut() = start;
+    if app.nmap_ports.is_empty() {
+        return;
+    }
+
+    // Open ports, with banners when the scan ran with --banners
+    use ratatui::widgets::{Table, Row};
+    let header = Row::new(["Host", "Port", "Service", "Banner"].iter().map(|h| ratatui::widgets::Cell::from(*h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))))
+        .style(Style::default().bg(THEME.surface)).height(1);
+
+    let rows = app.nmap_ports.iter().map(|p| {
+        let grabbed = app.nmap_banners.get(&(p.address().to_string(), p.port));
+        // nmap guesses the service from the port number; a recognised banner is better evidence
+        let service = grabbed.and_then(|b| b.service).unwrap_or(&p.service);
+        let banner = match grabbed.map(|b| &b.banner) {
+            Some(Ok(text)) => ratatui::widgets::Cell::from(text.clone()).style(Style::default().fg(THEME.accent)),
+            Some(Err(e)) => ratatui::widgets::Cell::from(e.clone()).style(Style::default().fg(THEME.muted)),
+            None if app.nmap_banner_rx.is_some() => ratatui::widgets::Cell::from("grabbing...").style(Style::default().fg(THEME.muted)),
+            None => ratatui::widgets::Cell::from("-").style(Style::default().fg(THEME.muted)),
+        };
+        Row::new(vec![
+            ratatui::widgets::Cell::from(p.host.clone()),
+            ratatui::widgets::Cell::from(format!("{}/{}", p.port, p.proto)),
+            ratatui::widgets::Cell::from(service.to_string()).style(Style::default().fg(THEME.secondary)),
+            banner,
+        ]).style(Style::default().fg(THEME.fg))
+    });
+
+    let title = if app.nmap_grab_banners { format!(" Open Ports ({}) ", app.nmap_ports.len()) } else { format!(" Open Ports ({}) - add --banners to grab banners ", app.nmap_ports.len()) };
+    let table = Table::new(rows, [
+        Constraint::Length(24),
+        Constraint::Length(10),
+        Constraint::Length(12),
+        Constraint::Min(20),
+    ].as_ref())
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(title).border_style(Style::default().fg(THEME.border)));
+    f.render_stateful_widget(table, split[1], &mut app.nmap_ports_table_state);
 }
 
-fn render_arpscan(f: &mut Frame, app: &App, area: Rect) {
+fn render_arpscan(f: &mut Frame, app: &mut App, area: Rect) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
@@ -482,7 +1113,9 @@ fn render_arpscan(f: &mut Frame, app: &App, area: Rect) {
     // Results Table or Raw Output
     let results_area = chunks[1];
     
-    if app.arpscan_results.is_empty() {
+    if app.arp_view == ArpView::Neighbors {
+        render_neighbors(f, app, results_area);
+    } else if app.arpscan_results.is_empty() {
         // Show raw output if no structured results yet (e.g. startup or error)
         let output_block = Block::default()
             .title(" Log Output ")
@@ -499,49 +1132,1751 @@ fn render_arpscan(f: &mut Frame, app: &App, area: Rect) {
         use ratatui::widgets::{Table, Row};
         
         let count = app.arpscan_results.len();
-        let title = format!(" Scan Results ({}) ", count);
-        
-        let header = Row::new(["IP Address", "MAC Address", "Vendor"].iter().map(|h| ratatui::widgets::Cell::from(*h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))))
+        let title = match app.arpwatch_every {
+            Some(every) => {
+                let online = app.arpscan_results.iter().filter(|e| e.online(every) == Some(true)).count();
+                let state = if app.arpscan_active { format!("sweep {} every {}s", app.arpwatch_sweeps, every.as_secs()) } else { "stopped".to_string() };
+                format!(" Watching ({} online of {}, {}) - [Ctrl+N] neighbor table ", online, count, state)
+            }
+            None => format!(" Scan Results ({}) - [Ctrl+N] neighbor table ", count),
+        };
+
+        let mut headers = vec!["IP Address", "Hostname", "MAC Address", "Vendor"];
+        if app.arpwatch_every.is_some() {
+            headers.extend(["Status", "Last Seen"]);
+        }
+        let header = Row::new(headers.into_iter().map(|h| ratatui::widgets::Cell::from(h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))))
             .style(Style::default().bg(THEME.surface)).height(1);
 
-        let rows = app.arpscan_results.iter().map(|entry| {
-            Row::new(vec![
+        let hits = search_hits(app, CurrentScreen::ArpScan);
+        let rows = app.arpscan_results.iter().enumerate().map(|(i, entry)| {
+            // Where the name came from, dimmed, since a DHCP-registered name and a NetBIOS one can differ
+            let name = match entry.ip.parse().ok().and_then(|ip: std::net::IpAddr| app.arp_names.get(&ip)) {
+                Some(Some(name)) => Line::from(vec![
+                    Span::raw(name.name.clone()),
+                    Span::styled(format!(" {}", name.source), Style::default().fg(THEME.muted)),
+                ]),
+                _ => Line::from(""),
+            };
+            let mut cells = vec![
                 ratatui::widgets::Cell::from(entry.ip.clone()),
+                ratatui::widgets::Cell::from(name),
                 ratatui::widgets::Cell::from(entry.mac.clone()).style(Style::default().fg(THEME.secondary)),
                 ratatui::widgets::Cell::from(entry.vendor.clone()),
-            ]).style(Style::default().fg(THEME.fg))
+            ];
+            // Offline once a host has missed a couple of sweeps and said nothing in between
+            if let (Some(every), Some(seen)) = (app.arpwatch_every, entry.last_seen) {
+                let online = entry.online(every) == Some(true);
+                let (label, color) = if online { ("online", THEME.success) } else { ("offline", THEME.error) };
+                let secs = seen.elapsed().as_secs();
+                let ago = if secs < 60 { format!("{}s ago", secs) } else { format!("{}m ago", secs / 60) };
+                cells.push(ratatui::widgets::Cell::from(format!("{}{}", theme::mark(online), label)).style(Style::default().fg(color)));
+                cells.push(ratatui::widgets::Cell::from(ago).style(Style::default().fg(THEME.muted)));
+            }
+            Row::new(cells).style(row_style(hits.contains(&i)))
         });
 
-        let table = Table::new(rows, [
-            Constraint::Length(16),
-            Constraint::Length(20),
-            Constraint::Min(20)
-        ].as_ref())
+        let mut widths = vec![Constraint::Length(16), Constraint::Min(20), Constraint::Length(18), Constraint::Min(16)];
+        if app.arpwatch_every.is_some() {
+            widths.extend([Constraint::Length(10), Constraint::Length(10)]);
+        }
+        let table = Table::new(rows, widths)
         .header(header)
-        .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(title).border_style(Style::default().fg(THEME.border)));
+        .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(title).border_style(Style::default().fg(THEME.border)))
+        .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">");
         
-        f.render_widget(table, results_area);
+        f.render_stateful_widget(table, results_area, &mut app.arpscan_table_state);
     }
 }
 
-fn render_connections(f: &mut Frame, app: &App, area: Rect) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+/// The OS ARP/NDP cache. New bindings show green for a minute; an IP whose MAC changed stays
+/// red with the old MAC, since that is what ARP spoofing looks like from here.
+fn render_neighbors(f: &mut Frame, app: &mut App, area: Rect) {
+    use ratatui::widgets::{Table, Row};
+    use crate::tools::neighbors::Binding;
+
+    let header = Row::new(["IP Address", "MAC Address", "Vendor", "State", "Interface", "Change"].iter().map(|h| ratatui::widgets::Cell::from(*h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))))
+        .style(Style::default().bg(THEME.surface)).height(1);
+
+    let hits = search_hits(app, CurrentScreen::ArpScan);
+    let rows = app.neighbors.entries.iter().enumerate().map(|(i, e)| {
+        let n = &e.neighbor;
+        let (change, color) = match &e.binding {
+            Binding::Changed { previous } => (format!("MAC changed, was {}", previous), Some(THEME.error)),
+            _ if e.is_new() => ("new".to_string(), Some(THEME.success)),
+            _ if e.shared_mac => ("MAC shared with another IP".to_string(), Some(THEME.accent)),
+            _ => (String::new(), None),
+        };
+        let state = if n.router { format!("{} (router)", n.state) } else { n.state.clone() };
+        let style = match color {
+            Some(c) if !hits.contains(&i) => Style::default().fg(c),
+            _ => row_style(hits.contains(&i)),
+        };
+        Row::new(vec![
+            ratatui::widgets::Cell::from(n.ip.to_string()),
+            ratatui::widgets::Cell::from(n.mac.clone().unwrap_or_else(|| "-".to_string())).style(Style::default().fg(color.unwrap_or(THEME.secondary))),
+            ratatui::widgets::Cell::from(n.mac.as_deref().map(|m| app.mac_vendor(m)).unwrap_or_default()),
+            ratatui::widgets::Cell::from(state).style(Style::default().fg(if n.state == "FAILED" || n.state == "INCOMPLETE" { THEME.muted } else { THEME.fg })),
+            ratatui::widgets::Cell::from(n.interface.clone()),
+            ratatui::widgets::Cell::from(change),
+        ]).style(style)
+    });
+
+    let changed = app.neighbors.entries.iter().filter(|e| matches!(e.binding, Binding::Changed { .. })).count();
+    let title = match &app.neighbors_error {
+        Some(e) => format!(" Neighbors - {} ", e),
+        None if changed > 0 => format!(" Neighbors ({}, {} changed) - [Ctrl+N] scan results ", app.neighbors.entries.len(), changed),
+        None => format!(" Neighbors ({}) - [Ctrl+N] scan results ", app.neighbors.entries.len()),
+    };
+    let table = Table::new(rows, [
+        Constraint::Length(26),
+        Constraint::Length(19),
+        Constraint::Length(20),
+        Constraint::Length(18),
+        Constraint::Length(10),
+        Constraint::Min(20),
+    ].as_ref())
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(title).border_style(Style::default().fg(if changed > 0 { THEME.error } else { THEME.border })))
+    .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD))
+    .highlight_symbol(">");
+
+    f.render_stateful_widget(table, area, &mut app.neighbors_table_state);
+}
+
+fn render_wol(f: &mut Frame, app: &App, area: Rect) {
+    let width = 60.min(area.width);
+    let height = 9;
+
+    let popup_area = Rect {
+        x: area.width.saturating_sub(width) / 2,
+        y: area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Wake-on-LAN (Enter to Send) ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(THEME.accent))
+        .bg(THEME.bg);
+
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(1), Constraint::Length(1), Constraint::Min(0)].as_ref())
+        .split(inner);
+
+    let mac_block = Block::default().title(" MAC Address ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
+    f.render_widget(Paragraph::new(app.wol_input.value()).block(mac_block).style(Style::default().fg(THEME.fg)), chunks[0]);
+    f.set_cursor_position((chunks[0].x + app.wol_input.visual_cursor() as u16 + 1, chunks[0].y + 1));
+
+    f.render_widget(Paragraph::new(Line::from(vec![
+        Span::styled(" Broadcast: ", Style::default().fg(THEME.muted)),
+        Span::styled(format!("< {} >", app.wol_target_label()), Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD)),
+    ])), chunks[1]);
+
+    let status = match &app.wol_status {
+        Some(Ok(msg)) => Span::styled(format!(" {}", msg), Style::default().fg(THEME.success)),
+        Some(Err(e)) => Span::styled(format!(" {}", e), Style::default().fg(THEME.error)),
+        None => Span::styled(" [Left/Right] Interface  [Esc] Close", Style::default().fg(THEME.muted)),
+    };
+    f.render_widget(Paragraph::new(Line::from(status)), chunks[2]);
+}
+
+fn render_probe_targets(f: &mut Frame, app: &App, area: Rect) {
+    let width = 64.min(area.width);
+    let height = 7;
+
+    let popup_area = Rect {
+        x: area.width.saturating_sub(width) / 2,
+        y: area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Dashboard Ping Targets (Enter to Save) ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(THEME.accent))
+        .bg(THEME.bg);
+
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(1), Constraint::Min(0)].as_ref())
+        .split(inner);
+
+    let input_block = Block::default().title(" Hosts (comma separated) ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
+    f.render_widget(Paragraph::new(app.probe_targets_input.value()).block(input_block).style(Style::default().fg(THEME.fg)), chunks[0]);
+    f.set_cursor_position((chunks[0].x + app.probe_targets_input.visual_cursor() as u16 + 1, chunks[0].y + 1));
+
+    let status = match &app.probe_targets_status {
+        Some(e) => Span::styled(format!(" {}", e), Style::default().fg(THEME.error)),
+        None => Span::styled(" e.g. 192.168.1.1, vpn.example.com, 8.8.8.8  [Esc] Close", Style::default().fg(THEME.muted)),
+    };
+    f.render_widget(Paragraph::new(Line::from(status)), chunks[1]);
+}
+
+fn render_oui_lookup(f: &mut Frame, app: &App, area: Rect) {
+    let width = 60.min(area.width);
+    let height = 7;
+
+    let popup_area = Rect {
+        x: area.width.saturating_sub(width) / 2,
+        y: area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" MAC Vendor Lookup ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(THEME.accent))
+        .bg(THEME.bg);
+
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(1), Constraint::Min(0)].as_ref())
+        .split(inner);
+
+    let input_block = Block::default().title(" MAC Address or Prefix ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
+    f.render_widget(Paragraph::new(app.oui_input.value()).block(input_block).style(Style::default().fg(THEME.fg)), chunks[0]);
+    f.set_cursor_position((chunks[0].x + app.oui_input.visual_cursor() as u16 + 1, chunks[0].y + 1));
+
+    // Answers as you type
+    let mac = app.oui_input.value();
+    let vendor = app.mac_vendor(mac);
+    let result = if mac.trim().is_empty() {
+        Span::styled(" e.g. 00:1b:63, b8-27-eb-12-34-56  [Esc] Close", Style::default().fg(THEME.muted))
+    } else if crate::tools::oui::parse_mac(mac).is_none() {
+        Span::styled(" Not a MAC address", Style::default().fg(THEME.muted))
+    } else if !vendor.is_empty() {
+        Span::styled(format!(" {}", vendor), Style::default().fg(THEME.success).add_modifier(Modifier::BOLD))
+    } else if let Some(e) = app.oui_error.as_ref().filter(|_| app.oui.is_none()) {
+        Span::styled(format!(" {}", e), Style::default().fg(THEME.error))
+    } else {
+        Span::styled(" Unknown vendor", Style::default().fg(THEME.muted))
+    };
+    f.render_widget(Paragraph::new(Line::from(result)), chunks[1]);
+}
+
+fn render_palette(f: &mut Frame, app: &App, area: Rect) {
+    let width = 64.min(area.width);
+    let height = 20.min(area.height);
+
+    let popup_area = Rect {
+        x: area.width.saturating_sub(width) / 2,
+        y: area.height.saturating_sub(height) / 4,
+        width,
+        height,
+    };
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Command Palette ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(THEME.accent))
+        .bg(THEME.bg);
+
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .split(inner);
+
+    let input_block = Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
+    f.render_widget(Paragraph::new(format!("> {}", app.palette_input.value())).block(input_block).style(Style::default().fg(THEME.fg)), chunks[0]);
+    f.set_cursor_position((chunks[0].x + app.palette_input.visual_cursor() as u16 + 3, chunks[0].y + 1));
+
+    // Keep the selection visible when the list is longer than the popup
+    let visible = chunks[1].height as usize;
+    let skip = app.palette_selected.saturating_sub(visible.saturating_sub(1));
+    let label_width = (chunks[1].width as usize).saturating_sub(12);
+    let items: Vec<ListItem> = app.palette_matches.iter().enumerate().skip(skip).take(visible).map(|(i, entry)| {
+        let style = if i == app.palette_selected {
+            Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(THEME.fg)
+        };
+        ListItem::new(Line::from(vec![
+            Span::styled(format!(" {:<width$}", entry.label, width = label_width), style),
+            Span::styled(format!("{:>10} ", entry.hint), if i == app.palette_selected { style } else { Style::default().fg(THEME.muted) }),
+        ]))
+    }).collect();
+
+    if items.is_empty() {
+        f.render_widget(Paragraph::new(" No matching commands").style(Style::default().fg(THEME.muted)), chunks[1]);
+    } else {
+        f.render_widget(List::new(items), chunks[1]);
+    }
+}
+
+fn render_conn_actions(f: &mut Frame, app: &App, area: Rect) {
+    let ip = app.selected_connection().map(|c| c.remote_ip.to_string()).unwrap_or_default();
+    let width = 40.min(area.width);
+    let height = CONNECTION_ACTIONS.len() as u16 + 2;
+
+    let popup_area = Rect {
+        x: area.width.saturating_sub(width) / 2,
+        y: area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+
+    f.render_widget(Clear, popup_area);
+
+    let items: Vec<ListItem> = CONNECTION_ACTIONS.iter().enumerate().map(|(i, action)| {
+        let style = if i == app.conn_action_selected {
+            Style::default().bg(THEME.primary).fg(THEME.bg).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(THEME.fg)
+        };
+        ListItem::new(format!(" {} {}", action, ip)).style(style)
+    }).collect();
+
+    let block = Block::default()
+        .title(" Connection (Enter to Run) ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(THEME.accent))
+        .bg(THEME.bg);
+    f.render_widget(List::new(items).block(block), popup_area);
+}
+
+fn render_confirm_kill(f: &mut Frame, app: &App, area: Rect) {
+    let Some(target) = &app.confirm_kill else { return };
+    let question = target.describe();
+    let width = (question.len() as u16 + 6).max(32).min(area.width);
+    let height = 5;
+
+    let popup_area = Rect {
+        x: area.width.saturating_sub(width) / 2,
+        y: area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Confirm ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(THEME.error))
+        .bg(THEME.bg);
+
+    let text = vec![
+        Line::from(Span::styled(format!(" {}", question), Style::default().fg(THEME.fg).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(" [y] ", Style::default().fg(THEME.error).add_modifier(Modifier::BOLD)),
+            Span::styled("Yes  ", Style::default().fg(THEME.fg)),
+            Span::styled("[any key] ", Style::default().fg(THEME.accent).add_modifier(Modifier::BOLD)),
+            Span::styled("Cancel", Style::default().fg(THEME.fg)),
+        ]),
+    ];
+    f.render_widget(Paragraph::new(text).block(block), popup_area);
+}
+
+fn render_whois(f: &mut Frame, app: &App, area: Rect) {
+    let width = 90.min(area.width);
+    let height = area.height.saturating_sub(4).min(30);
+
+    let popup_area = Rect {
+        x: area.width.saturating_sub(width) / 2,
+        y: area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(format!(" WHOIS {} ([Up/Down] Scroll, [Esc] Close) ", app.whois_target))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(THEME.accent))
+        .bg(THEME.bg);
+
+    let text = match &app.whois_result {
+        None => Paragraph::new(" Querying...").style(Style::default().fg(THEME.muted)),
+        Some(Err(e)) => Paragraph::new(format!(" {}", e)).style(Style::default().fg(THEME.error)),
+        // Skip the comment banners most registries prepend
+        Some(Ok(body)) => Paragraph::new(
+            body.lines()
+                .filter(|l| !l.starts_with('%') && !l.starts_with('#'))
+                .map(|l| Line::from(l.to_string()))
+                .collect::<Vec<_>>(),
+        ).style(Style::default().fg(THEME.fg)).scroll((app.whois_scroll, 0)),
+    };
+    f.render_widget(text.block(block), popup_area);
+}
+
+/// Every change of route in order, then the paths seen with the routers at each TTL.
+fn render_mtr_paths(f: &mut Frame, app: &App, area: Rect) {
+    let width = 90.min(area.width);
+    let height = area.height.saturating_sub(4).min(30);
+    let popup_area = Rect {
+        x: area.width.saturating_sub(width) / 2,
+        y: area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, popup_area);
+
+    let tracker = &app.mtr_paths;
+    let block = Block::default()
+        .title(format!(" Path timeline: {} ([Up/Down] Scroll, [Esc] Close) ", app.mtr_target))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(THEME.accent))
+        .bg(THEME.bg);
+    let host = |ip: Option<std::net::IpAddr>| ip.map_or("???".to_string(), |ip| ip.to_string());
+
+    let mut lines = vec![];
+    if tracker.changes.is_empty() {
+        lines.push(Line::from(Span::styled(" No complete cycle yet", Style::default().fg(THEME.muted))));
+    }
+    for (i, change) in tracker.changes.iter().enumerate() {
+        let what = if i == 0 {
+            "first path".to_string()
+        } else {
+            let before = &tracker.paths[tracker.changes[i - 1].path].hops;
+            let after = &tracker.paths[change.path].hops;
+            change.ttls.iter().map(|&ttl| {
+                let at = ttl as usize - 1;
+                format!("TTL {}: {} → {}", ttl, host(before.get(at).copied().flatten()), host(after.get(at).copied().flatten()))
+            }).collect::<Vec<_>>().join(", ")
+        };
+        let again = if i > 0 && tracker.changes[..i].iter().any(|c| c.path == change.path) { " (seen before)" } else { "" };
+        lines.push(Line::from(vec![
+            Span::styled(format!(" {}  cycle {:<5} ", change.time, change.cycle), Style::default().fg(THEME.muted)),
+            Span::styled(format!("path #{}{}  ", change.path + 1, again), Style::default().fg(THEME.accent)),
+            Span::styled(what, Style::default().fg(THEME.fg)),
+        ]));
+    }
+    for (i, path) in tracker.paths.iter().enumerate() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!(" Path #{}: {} cycles, {} to {}", i + 1, path.cycles, path.first_seen, path.last_seen),
+            Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD),
+        )));
+        for (ttl, hop) in path.hops.iter().enumerate() {
+            // TTLs where this path differs from the first one stand out
+            let differs = i > 0 && tracker.paths[0].hops.get(ttl).copied().flatten().zip(*hop).is_some_and(|(a, b)| a != b);
+            let color = if differs { THEME.accent } else { THEME.fg };
+            lines.push(Line::from(Span::styled(format!("   {:>2}  {}", ttl + 1, host(*hop)), Style::default().fg(color))));
+        }
+    }
+    f.render_widget(Paragraph::new(lines).block(block).scroll((app.mtr_paths_scroll, 0)), popup_area);
+}
+
+fn render_export(f: &mut Frame, app: &App, area: Rect) {
+    let width = 70.min(area.width);
+    let height = 7;
+
+    let popup_area = Rect {
+        x: area.width.saturating_sub(width) / 2,
+        y: area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Export (Enter to Save) ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(THEME.accent))
+        .bg(THEME.bg);
+
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(1), Constraint::Min(0)].as_ref())
+        .split(inner);
+
+    let file_block = Block::default().title(" Filename (.csv or .json) ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
+    f.render_widget(Paragraph::new(app.export_input.value()).block(file_block).style(Style::default().fg(THEME.fg)), chunks[0]);
+    f.set_cursor_position((chunks[0].x + app.export_input.visual_cursor() as u16 + 1, chunks[0].y + 1));
+
+    let status = match &app.export_status {
+        Some(Ok(msg)) => Span::styled(format!(" {}", msg), Style::default().fg(THEME.success)),
+        Some(Err(e)) => Span::styled(format!(" {}", e), Style::default().fg(THEME.error)),
+        None => Span::styled(" [Tab] CSV/JSON  [Esc] Close", Style::default().fg(THEME.muted)),
+    };
+    f.render_widget(Paragraph::new(Line::from(status)), chunks[1]);
+}
+
+fn render_alerts(f: &mut Frame, app: &App, area: Rect) {
+    let width = 80.min(area.width);
+    let height = 24.min(area.height);
+
+    let popup_area = Rect {
+        x: area.width.saturating_sub(width) / 2,
+        y: area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Alert Rules (Enter to Add, Del to Remove) ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(THEME.accent))
+        .bg(THEME.bg);
+
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(1), Constraint::Min(3), Constraint::Length(7)].as_ref())
+        .split(inner);
+
+    let input_block = Block::default().title(" New Rule (e.g. latency > 150ms for 30s, loss > 5%) ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
+    f.render_widget(Paragraph::new(app.alerts_input.value()).block(input_block).style(Style::default().fg(THEME.fg)), chunks[0]);
+    f.set_cursor_position((chunks[0].x + app.alerts_input.visual_cursor() as u16 + 1, chunks[0].y + 1));
+
+    let status = match &app.alerts_status {
+        Some(msg) => Span::styled(format!(" {}", msg), Style::default().fg(THEME.secondary)),
+        None => Span::styled(" Metrics: latency jitter loss wan_rx wan_tx lan_rx lan_tx connections", Style::default().fg(THEME.muted)),
+    };
+    f.render_widget(Paragraph::new(Line::from(status)), chunks[1]);
+
+    let firing: Vec<&str> = app.alert_engine.active().map(|a| a.rule.as_str()).collect();
+    let rules: Vec<ListItem> = app.config.alerts.rules.iter().enumerate().map(|(i, rule)| {
+        let text = rule.to_string();
+        let is_firing = firing.contains(&text.as_str());
+        let mut style = Style::default().fg(if is_firing { THEME.error } else { THEME.fg });
+        if i == app.alerts_selected {
+            style = style.bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD);
+        }
+        ListItem::new(format!(" {} {}", if is_firing { "!" } else { " " }, text)).style(style)
+    }).collect();
+    let rules_title = format!(" Rules ({}) ", app.config.alerts.rules.len());
+    f.render_widget(List::new(rules).block(Block::default().title(rules_title).borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border))), chunks[2]);
+
+    let log_height = chunks[3].height.saturating_sub(2) as usize;
+    let log: Vec<ListItem> = app.alert_log.iter().rev().take(log_height).map(|line| {
+        let col = if line.contains("RAISED") { THEME.error } else { THEME.success };
+        ListItem::new(line.as_str()).style(Style::default().fg(col))
+    }).collect();
+    f.render_widget(List::new(log).block(Block::default().title(" History ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border))), chunks[3]);
+}
+
+fn render_profiles(f: &mut Frame, app: &App, area: Rect) {
+    let width = 80.min(area.width);
+    let height = 20.min(area.height);
+
+    let popup_area = Rect {
+        x: area.width.saturating_sub(width) / 2,
+        y: area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Scan Profiles (Enter to Run, Del to Remove) ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(THEME.accent))
+        .bg(THEME.bg);
+
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(1), Constraint::Min(3)].as_ref())
+        .split(inner);
+
+    let input_title = match ProfileTool::for_screen(app.current_screen) {
+        Some(tool) => format!(" Save current {} target + flags as (type a name, Enter) ", tool.label()),
+        None => " Save: open Ping, Nmap or MTR first ".to_string(),
+    };
+    let input_block = Block::default().title(input_title).borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
+    f.render_widget(Paragraph::new(app.profiles_input.value()).block(input_block).style(Style::default().fg(THEME.fg)), chunks[0]);
+    f.set_cursor_position((chunks[0].x + app.profiles_input.visual_cursor() as u16 + 1, chunks[0].y + 1));
+
+    let status = match &app.profiles_status {
+        Some(msg) => Span::styled(format!(" {}", msg), Style::default().fg(THEME.secondary)),
+        None => Span::styled(" With the name empty, Enter runs the selected profile", Style::default().fg(THEME.muted)),
+    };
+    f.render_widget(Paragraph::new(Line::from(status)), chunks[1]);
+
+    let profiles: Vec<ListItem> = app.config.profiles.iter().enumerate().map(|(i, p)| {
+        let style = if i == app.profiles_selected {
+            Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(THEME.fg)
+        };
+        ListItem::new(format!(" {:<5} {:<28} {}", p.tool.label(), p.name, p.args)).style(style)
+    }).collect();
+    let title = format!(" Profiles ({}) ", app.config.profiles.len());
+    f.render_widget(List::new(profiles).block(Block::default().title(title).borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border))), chunks[2]);
+}
+
+fn render_layout_editor(f: &mut Frame, app: &App, area: Rect) {
+    let rows = &app.config.dashboard.rows;
+    let width = 90.min(area.width);
+    let height = (rows.len() as u16 + 7).min(area.height);
+
+    let popup_area = Rect {
+        x: area.width.saturating_sub(width) / 2,
+        y: area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Dashboard Layout ([Esc] Close) ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(THEME.accent))
+        .bg(THEME.bg);
+
+    let mut lines: Vec<Line> = rows.iter().enumerate().map(|(r, row)| {
+        let mut spans = vec![Span::styled(format!(" Row {} ", r + 1), Style::default().fg(THEME.muted))];
+        for (c, panel) in row.iter().enumerate() {
+            let style = if r == app.layout_cursor.row && c == app.layout_cursor.col {
+                Style::default().bg(THEME.primary).fg(THEME.bg).add_modifier(Modifier::BOLD)
+            } else if panel.is_chart() {
+                Style::default().fg(THEME.fg)
+            } else {
+                Style::default().fg(THEME.secondary)
+            };
+            spans.push(Span::styled(format!(" {} ", panel.title()), style));
+            spans.push(Span::raw(" "));
+        }
+        Line::from(spans)
+    }).collect();
+    if rows.is_empty() {
+        lines.push(Line::from(Span::styled(" (empty) press [r] to add a row", Style::default().fg(THEME.muted))));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        " [Arrows] Select  [Tab] Change panel  [</>] Move  [a] Add  [r] New row  [d] Remove",
+        Style::default().fg(THEME.muted),
+    )));
+    if let Some(status) = &app.layout_status {
+        lines.push(Line::from(Span::styled(format!(" {}", status), Style::default().fg(THEME.secondary))));
+    }
+
+    f.render_widget(Paragraph::new(lines).block(block), popup_area);
+}
+
+fn render_mdns(f: &mut Frame, app: &mut App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1), Constraint::Length(8)].as_ref())
+        .split(area);
+
+    // Status
+    let (status_text, status_col) = if app.mdns_active { ("BROWSING", THEME.success) } else { ("IDLE", THEME.muted) };
+    let info = vec![
+        Span::raw(" Status: "),
+        Span::styled(status_text, Style::default().fg(status_col).add_modifier(Modifier::BOLD)),
+        Span::raw("  Services: "),
+        Span::styled(format!("{}", app.mdns_services.len()), Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD)),
+    ];
+    f.render_widget(Paragraph::new(Line::from(info)).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(if app.mdns_active { THEME.success } else { THEME.border })).title(" mDNS Browser ")), chunks[0]);
+
+    // Services Table
+    use ratatui::widgets::{Table, Row};
+    let header = Row::new(["Type", "Service", "Instance", "Host", "IP", "Port"].iter().map(|h| ratatui::widgets::Cell::from(*h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))))
+        .style(Style::default().bg(THEME.surface)).height(1);
+
+    let rows = app.mdns_services.iter().map(|svc| {
+        let friendly = crate::tools::mdns::friendly_service_name(&svc.service_type);
+        let ip = svc.ips.first().map(|ip| ip.to_string()).unwrap_or_else(|| "-".to_string());
+        Row::new(vec![
+            ratatui::widgets::Cell::from(friendly).style(Style::default().fg(THEME.accent)),
+            ratatui::widgets::Cell::from(svc.service_type.clone()).style(Style::default().fg(THEME.secondary)),
+            ratatui::widgets::Cell::from(svc.instance.clone()),
+            ratatui::widgets::Cell::from(svc.host.clone()),
+            ratatui::widgets::Cell::from(ip),
+            ratatui::widgets::Cell::from(if svc.port > 0 { svc.port.to_string() } else { "-".to_string() }),
+        ]).style(Style::default().fg(THEME.fg))
+    });
+
+    let table = Table::new(rows, [
+        Constraint::Length(13),
+        Constraint::Length(22),
+        Constraint::Min(20),
+        Constraint::Length(24),
+        Constraint::Length(16),
+        Constraint::Length(6),
+    ].as_ref())
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(" Services ").border_style(Style::default().fg(THEME.border)))
+    .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD))
+    .highlight_symbol(">");
+
+    f.render_stateful_widget(table, chunks[1], &mut app.mdns_table_state);
+
+    // TXT Records of selected service
+    let txt_block = Block::default().title(" TXT Records ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
+    let items: Vec<ListItem> = match app.mdns_services.get(app.mdns_selected) {
+        Some(svc) if app.mdns_table_state.selected().is_some() => {
+            let mut items: Vec<ListItem> = svc.txt.iter().map(|t| ListItem::new(Line::from(t.clone()))).collect();
+            if svc.ips.len() > 1 {
+                let all = svc.ips.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(", ");
+                items.insert(0, ListItem::new(Line::from(Span::styled(format!("addrs: {}", all), Style::default().fg(THEME.muted)))));
+            }
+            items
+        }
+        _ => vec![],
+    };
+    f.render_widget(List::new(items).block(txt_block).style(Style::default().fg(THEME.fg)), chunks[2]);
+}
+
+fn render_ssdp(f: &mut Frame, app: &mut App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1), Constraint::Length(8)].as_ref())
+        .split(area);
+
+    // Status
+    let (status_text, status_col) = if app.ssdp_active { ("SEARCHING", THEME.success) } else { ("IDLE", THEME.muted) };
+    let info = vec![
+        Span::raw(" Status: "),
+        Span::styled(status_text, Style::default().fg(status_col).add_modifier(Modifier::BOLD)),
+        Span::raw("  Devices: "),
+        Span::styled(format!("{}", app.ssdp_devices.len()), Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD)),
+    ];
+    f.render_widget(Paragraph::new(Line::from(info)).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(if app.ssdp_active { THEME.success } else { THEME.border })).title(" SSDP / UPnP ")), chunks[0]);
+
+    // Devices Table
+    use ratatui::widgets::{Table, Row};
+    let header = Row::new(["IP Address", "Friendly Name", "Manufacturer", "Model", "Presentation URL"].iter().map(|h| ratatui::widgets::Cell::from(*h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))))
+        .style(Style::default().bg(THEME.surface)).height(1);
+
+    let rows = app.ssdp_devices.iter().map(|d| {
+        let name = if d.friendly_name.is_empty() { "(no description)".to_string() } else { d.friendly_name.clone() };
+        Row::new(vec![
+            ratatui::widgets::Cell::from(d.ip.map(|ip| ip.to_string()).unwrap_or_default()),
+            ratatui::widgets::Cell::from(name).style(Style::default().fg(THEME.accent)),
+            ratatui::widgets::Cell::from(d.manufacturer.clone()),
+            ratatui::widgets::Cell::from(d.model.clone()).style(Style::default().fg(THEME.secondary)),
+            ratatui::widgets::Cell::from(d.presentation_url.clone()),
+        ]).style(Style::default().fg(THEME.fg))
+    });
+
+    let table = Table::new(rows, [
+        Constraint::Length(16),
+        Constraint::Min(20),
+        Constraint::Length(20),
+        Constraint::Length(22),
+        Constraint::Length(30),
+    ].as_ref())
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(" Devices ").border_style(Style::default().fg(THEME.border)))
+    .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD))
+    .highlight_symbol(">");
+
+    f.render_stateful_widget(table, chunks[1], &mut app.ssdp_table_state);
+
+    // Details of selected device
+    let detail_block = Block::default().title(" Details ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
+    let lines: Vec<Line> = match app.ssdp_devices.get(app.ssdp_selected) {
+        Some(d) if app.ssdp_table_state.selected().is_some() => vec![
+            Line::from(vec![Span::styled("Location: ", Style::default().fg(THEME.muted)), Span::raw(d.location.clone())]),
+            Line::from(vec![Span::styled("Server:   ", Style::default().fg(THEME.muted)), Span::raw(d.server.clone())]),
+            Line::from(vec![Span::styled("USN:      ", Style::default().fg(THEME.muted)), Span::raw(d.usn.clone())]),
+            Line::from(vec![Span::styled("Types:    ", Style::default().fg(THEME.muted)), Span::raw(d.service_types.join(", "))]),
+        ],
+        _ => vec![],
+    };
+    f.render_widget(Paragraph::new(lines).block(detail_block).wrap(ratatui::widgets::Wrap { trim: true }).style(Style::default().fg(THEME.fg)), chunks[2]);
+}
+
+fn signal_color(dbm: i32) -> Color {
+    match dbm {
+        -60.. => THEME.success,
+        -70..=-61 => THEME.accent,
+        _ => THEME.error,
+    }
+}
+
+fn render_wifi(f: &mut Frame, app: &mut App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1), Constraint::Length(10)].as_ref())
+        .split(area);
+
+    // Status
+    let (status_text, status_col) = if app.wifi_active { ("SCANNING", THEME.success) } else { ("IDLE", THEME.muted) };
+    let info = vec![
+        Span::raw(" Interface: "),
+        Span::styled(app.wifi_interface.clone().unwrap_or_else(|| "-".to_string()), Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD)),
+        Span::raw("  Status: "),
+        Span::styled(status_text, Style::default().fg(status_col).add_modifier(Modifier::BOLD)),
+        Span::raw("  Networks: "),
+        Span::styled(format!("{}", app.wifi_networks.len()), Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD)),
+    ];
+    f.render_widget(Paragraph::new(Line::from(info)).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(if app.wifi_active { THEME.success } else { THEME.border })).title(" Wi-Fi ")), chunks[0]);
+
+    // Networks Table
+    use ratatui::widgets::{Table, Row};
+    let header = Row::new(["SSID", "BSSID", "Ch", "Band", "RSSI", "Security"].iter().map(|h| ratatui::widgets::Cell::from(*h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))))
+        .style(Style::default().bg(THEME.surface)).height(1);
+
+    let rows = app.wifi_networks.iter().map(|n| {
+        let ssid = if n.ssid.is_empty() { "(hidden)".to_string() } else { n.ssid.clone() };
+        let ssid = if n.connected { format!("* {}", ssid) } else { ssid };
+        let (signal, signal_col) = match n.signal_dbm {
+            Some(dbm) => (format!("{} dBm", dbm), signal_color(dbm)),
+            None => ("-".to_string(), THEME.muted),
+        };
+        Row::new(vec![
+            ratatui::widgets::Cell::from(ssid).style(Style::default().fg(if n.connected { THEME.accent } else { THEME.fg })),
+            ratatui::widgets::Cell::from(n.bssid.clone()).style(Style::default().fg(THEME.muted)),
+            ratatui::widgets::Cell::from(n.channel.map(|c| c.to_string()).unwrap_or_default()),
+            ratatui::widgets::Cell::from(n.band),
+            ratatui::widgets::Cell::from(signal).style(Style::default().fg(signal_col)),
+            ratatui::widgets::Cell::from(n.security.clone()).style(Style::default().fg(THEME.secondary)),
+        ]).style(Style::default().fg(THEME.fg))
+    });
+
+    let table = Table::new(rows, [
+        Constraint::Min(20),
+        Constraint::Length(18),
+        Constraint::Length(4),
+        Constraint::Length(8),
+        Constraint::Length(8),
+        Constraint::Length(20),
+    ].as_ref())
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(" Nearby Networks ").border_style(Style::default().fg(THEME.border)))
+    .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD))
+    .highlight_symbol(">");
+
+    f.render_stateful_widget(table, chunks[1], &mut app.wifi_table_state);
+
+    // RSSI of the connected network
+    let title = match &app.wifi_link {
+        Some(l) => {
+            let signal = l.signal_dbm.map(|s| format!("{} dBm", s)).unwrap_or_else(|| "-".to_string());
+            let channel = l.channel.map(|c| format!(", ch {}", c)).unwrap_or_default();
+            let rate = if l.tx_rate.is_empty() { String::new() } else { format!(", {}", l.tx_rate) };
+            format!(" {} {}{}{} ", if l.ssid.is_empty() { "Connected" } else { &l.ssid }, signal, channel, rate)
+        }
+        None => " Not connected ".to_string(),
+    };
+    let data: Vec<(f64, f64)> = app.wifi_rssi_history.iter().enumerate().map(|(i, &v)| (i as f64, v)).collect();
+    let color = app.wifi_link.as_ref().and_then(|l| l.signal_dbm).map(signal_color).unwrap_or(THEME.primary);
+    let chart = Chart::new(vec![
+        Dataset::default().name("RSSI").marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(color)).data(&data),
+    ])
+    .block(Block::default().title(title).borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)))
+    .x_axis(Axis::default().bounds([0.0, 120.0]).style(Style::default().fg(THEME.muted)))
+    .y_axis(Axis::default().bounds([-100.0, -20.0]).labels(["-100", "-60", "-20"]).style(Style::default().fg(THEME.muted)));
+    f.render_widget(chart, chunks[2]);
+}
+
+fn format_lease(secs: u32) -> String {
+    match secs {
+        u32::MAX => "infinite".to_string(),
+        86400.. => format!("{}d {}h", secs / 86400, secs % 86400 / 3600),
+        3600.. => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+        _ => format!("{}m {}s", secs / 60, secs % 60),
+    }
+}
+
+fn render_dhcp(f: &mut Frame, app: &mut App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1), Constraint::Length(8)].as_ref())
+        .split(area);
+
+    // Controls
+    let iface = app.interfaces.get(app.dhcp_interface_index).map(|i| i.name.as_str()).unwrap_or("None");
+    let (status_text, status_col) = if app.dhcp_active { ("LISTENING", THEME.success) } else { ("IDLE", THEME.muted) };
+    let servers = app.dhcp_servers();
+    let mut info = vec![
+        Span::raw(" Interface: "),
+        Span::styled(iface, Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD)),
+        Span::raw("  Mode: "),
+        Span::styled(if app.dhcp_inform { "INFORM" } else { "DISCOVER" }, Style::default().fg(THEME.secondary).add_modifier(Modifier::BOLD)),
+        Span::raw("  Status: "),
+        Span::styled(status_text, Style::default().fg(status_col).add_modifier(Modifier::BOLD)),
+        Span::raw("  Servers: "),
+        Span::styled(format!("{}", servers.len()), Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD)),
+    ];
+    if servers.len() > 1 {
+        info.push(Span::styled("  Multiple DHCP servers answered: possible rogue!", Style::default().fg(THEME.error).add_modifier(Modifier::BOLD)));
+    }
+    f.render_widget(Paragraph::new(Line::from(info)).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(if app.dhcp_active { THEME.success } else { THEME.border })).title(" DHCP ")), chunks[0]);
+
+    // Replies Table
+    use ratatui::widgets::{Table, Row};
+    let header = Row::new(["Server", "Type", "Offered", "Lease", "Gateway", "DNS"].iter().map(|h| ratatui::widgets::Cell::from(*h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))))
+        .style(Style::default().bg(THEME.surface)).height(1);
+
+    let ip = |ip: Option<std::net::Ipv4Addr>| ip.map(|ip| ip.to_string()).unwrap_or_else(|| "-".to_string());
+    let list = |ips: &[std::net::Ipv4Addr]| ips.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(", ");
+    let rogue = servers.len() > 1;
+    let rows = app.dhcp_replies.iter().map(|r| {
+        let lease = r.lease_secs.map(format_lease).unwrap_or_else(|| "-".to_string());
+        Row::new(vec![
+            ratatui::widgets::Cell::from(ip(r.server())).style(Style::default().fg(if rogue { THEME.error } else { THEME.accent })),
+            ratatui::widgets::Cell::from(r.message_type),
+            ratatui::widgets::Cell::from(ip(r.offered)).style(Style::default().fg(THEME.secondary)),
+            ratatui::widgets::Cell::from(lease),
+            ratatui::widgets::Cell::from(list(&r.routers)),
+            ratatui::widgets::Cell::from(list(&r.dns)),
+        ]).style(Style::default().fg(THEME.fg))
+    });
+
+    let table = Table::new(rows, [
+        Constraint::Length(16),
+        Constraint::Length(6),
+        Constraint::Length(16),
+        Constraint::Length(10),
+        Constraint::Length(16),
+        Constraint::Min(16),
+    ].as_ref())
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(" Replies ").border_style(Style::default().fg(THEME.border)))
+    .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD))
+    .highlight_symbol(">");
+
+    f.render_stateful_widget(table, chunks[1], &mut app.dhcp_table_state);
+
+    // Details of selected reply
+    let detail_block = Block::default().title(" Details ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
+    let lines: Vec<Line> = match app.dhcp_replies.get(app.dhcp_selected) {
+        Some(r) if app.dhcp_table_state.selected().is_some() => vec![
+            Line::from(vec![Span::styled("Sent from:   ", Style::default().fg(THEME.muted)), Span::raw(ip(r.from))]),
+            Line::from(vec![Span::styled("Subnet mask: ", Style::default().fg(THEME.muted)), Span::raw(ip(r.subnet_mask))]),
+            Line::from(vec![Span::styled("Domain:      ", Style::default().fg(THEME.muted)), Span::raw(r.domain.clone())]),
+            Line::from(vec![Span::styled("NTP servers: ", Style::default().fg(THEME.muted)), Span::raw(list(&r.ntp))]),
+        ],
+        _ => vec![],
+    };
+    f.render_widget(Paragraph::new(lines).block(detail_block).wrap(ratatui::widgets::Wrap { trim: true }).style(Style::default().fg(THEME.fg)), chunks[2]);
+}
+
+fn render_asn(f: &mut Frame, app: &mut App, area: Rect) {
+    use ratatui::widgets::{Cell, Row, Table};
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(5), Constraint::Min(5)].as_ref())
+        .split(area);
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Min(1)].as_ref())
+        .split(chunks[0]);
+
+    let active = app.asn_rx.is_some();
+    let input_block = Block::default()
+        .title(" AS Number, IP, Prefix or Organization ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(if active { THEME.success } else { THEME.border }));
+    f.render_widget(Paragraph::new(app.asn_input.value()).block(input_block).style(Style::default().fg(THEME.fg)), top[0]);
+    if !active {
+        f.set_cursor_position((top[0].x + app.asn_input.visual_cursor() as u16 + 1, top[0].y + 1));
+    }
+
+    let (status_text, status_col) = if active { ("QUERYING RIPEstat", THEME.success) } else { ("IDLE", THEME.muted) };
+    let status = Line::from(vec![Span::raw(" "), Span::styled(status_text, Style::default().fg(status_col).add_modifier(Modifier::BOLD))]);
+    f.render_widget(Paragraph::new(status).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)).title(" Status ")), top[1]);
+
+    let label = |s: &'static str| Span::styled(s, Style::default().fg(THEME.muted));
+    let value = |s: String| Span::styled(s, Style::default().fg(THEME.fg).add_modifier(Modifier::BOLD));
+    let summary: Vec<Line> = match (&app.asn_error, &app.asn_result) {
+        (Some(e), _) => vec![Line::from(Span::styled(e.clone(), Style::default().fg(THEME.error)))],
+        (None, Some(asn::AsnResult::As(info))) => vec![
+            Line::from(vec![label(" Network  "), value(format!("AS{} ", info.asn)), Span::styled(info.holder.clone(), Style::default().fg(THEME.secondary))]),
+            Line::from(vec![label(" Prefixes "), value(format!("{} IPv4, {} IPv6", info.v4_count(), info.prefixes.len() - info.v4_count()))]),
+        ],
+        (None, Some(asn::AsnResult::Search(name, matches))) => vec![
+            Line::from(vec![label(" Search   "), value(name.clone())]),
+            Line::from(vec![label(" Matches  "), value(matches.len().to_string()), label("  (Enter on one lists its prefixes)")]),
+        ],
+        (None, Some(asn::AsnResult::Route(ctx))) => {
+            let origins = ctx.origins.iter().map(|(n, holder)| format!("AS{} {}", n, holder)).collect::<Vec<_>>().join(", ");
+            let mut origin = vec![label(" Origin   "), Span::styled(if origins.is_empty() { "-".to_string() } else { origins }, Style::default().fg(THEME.secondary))];
+            if ctx.moas() {
+                origin.push(Span::styled(format!("  {}several origins (anycast, migration or hijack)", theme::mark(false)), Style::default().fg(THEME.error)));
+            }
+            let visibility = match ctx.visibility {
+                Some(v) => {
+                    // A prefix most of the world can't see is usually new, withdrawn or filtered
+                    let col = if v.pct() < 50.0 { THEME.error } else if v.pct() < 90.0 { THEME.accent } else { THEME.success };
+                    Span::styled(format!("{} of {} RIS peers ({:.0}%)", v.seeing, v.total, v.pct()), Style::default().fg(col).add_modifier(Modifier::BOLD))
+                }
+                None => label("-"),
+            };
+            let first_seen = ctx.first_seen.as_ref().map(|t| format!("  first seen {}", t.replace('T', " "))).unwrap_or_default();
+            vec![
+                Line::from(vec![label(" Route    "), value(ctx.resource.clone()), label("  in  "), value(ctx.prefix.clone().unwrap_or_else(|| "-".to_string()))]),
+                Line::from(origin),
+                Line::from(vec![label(" Visible  "), visibility, Span::styled(first_seen, Style::default().fg(THEME.muted))]),
+            ]
+        }
+        (None, None) => vec![Line::from(label(if active { " Looking up..." } else { " Enter an AS number, an IP address or an organization name" }))],
+    };
+    f.render_widget(Paragraph::new(summary).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)).title(" Summary ")), chunks[1]);
+
+    let header_style = Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD);
+    let header = |cells: &[&'static str]| Row::new(cells.iter().map(|h| Cell::from(*h).style(header_style))).style(Style::default().bg(THEME.surface)).height(1);
+    let (title, header, rows, widths): (&str, Row, Vec<Row>, Vec<Constraint>) = match &app.asn_result {
+        Some(asn::AsnResult::As(info)) => (
+            " Announced Prefixes ",
+            header(&["Prefix", "Family"]),
+            info.prefixes.iter().map(|p| Row::new([Cell::from(p.clone()), Cell::from(if p.contains(':') { "IPv6" } else { "IPv4" }).style(Style::default().fg(THEME.muted))])).collect(),
+            vec![Constraint::Min(24), Constraint::Length(8)],
+        ),
+        Some(asn::AsnResult::Search(_, matches)) => (
+            " Networks ",
+            header(&["AS", "Holder"]),
+            matches.iter().map(|(n, holder)| Row::new([Cell::from(format!("AS{}", n)).style(Style::default().fg(THEME.accent)), Cell::from(holder.clone())])).collect(),
+            vec![Constraint::Length(12), Constraint::Min(20)],
+        ),
+        Some(asn::AsnResult::Route(ctx)) => (
+            " AS Paths Seen by Route Collectors ",
+            header(&["Peers", "Hops", "Path (collector peer → origin)", "Seen From"]),
+            ctx.paths.iter().map(|p| Row::new([
+                Cell::from(p.peers.to_string()).style(Style::default().fg(THEME.accent)),
+                Cell::from(p.path.len().to_string()).style(Style::default().fg(THEME.muted)),
+                Cell::from(asn::format_path(&p.path)),
+                Cell::from(p.collectors.join(", ")).style(Style::default().fg(THEME.muted)),
+            ])).collect(),
+            vec![Constraint::Length(6), Constraint::Length(5), Constraint::Percentage(55), Constraint::Min(10)],
+        ),
+        None => (" Results ", header(&[]), vec![], vec![Constraint::Min(1)]),
+    };
+    let table = Table::new(rows.into_iter().map(|r| r.style(Style::default().fg(THEME.fg))), widths)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(title).border_style(Style::default().fg(THEME.border)))
+        .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">");
+    f.render_stateful_widget(table, chunks[2], &mut app.asn_table_state);
+}
+
+fn render_traceroute(f: &mut Frame, app: &mut App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(5)].as_ref())
+        .split(area);
+
+    // Controls
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Min(1)].as_ref())
+        .split(chunks[0]);
+
+    let input_block = Block::default()
+        .title(" Target [-m hops] [-w secs] ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(if app.trace_active { THEME.success } else { THEME.border }));
+    f.render_widget(Paragraph::new(app.trace_input.value()).block(input_block).style(Style::default().fg(THEME.fg)), top[0]);
+    if !app.trace_active {
+        f.set_cursor_position((top[0].x + app.trace_input.visual_cursor() as u16 + 1, top[0].y + 1));
+    }
+
+    let mut info = vec![Span::raw(" ")];
+    match (&app.trace_result, app.trace_active) {
+        (_, true) => info.push(Span::styled("TRACING", Style::default().fg(THEME.success).add_modifier(Modifier::BOLD))),
+        (Some(r), false) => {
+            let (text, col) = if r.reached { ("REACHED", THEME.success) } else { ("NOT REACHED", THEME.accent) };
+            info.push(Span::styled(text, Style::default().fg(col).add_modifier(Modifier::BOLD)));
+            info.push(Span::raw(format!("  {} in {} hops, {:.1}s", r.target, r.hops.len(), r.elapsed.as_secs_f64())));
+            if let Some(note) = &r.note {
+                info.push(Span::styled(format!("  {}", note), Style::default().fg(THEME.accent)));
+            }
+        }
+        (None, false) => info.push(Span::styled("IDLE", Style::default().fg(THEME.muted).add_modifier(Modifier::BOLD))),
+    }
+    f.render_widget(Paragraph::new(Line::from(info)).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)).title(" Status ")), top[1]);
+
+    // Hops Table
+    use ratatui::widgets::{Table, Row};
+    let header = Row::new(["Hop", "Host", "RTT 1", "RTT 2", "RTT 3"].iter().map(|h| ratatui::widgets::Cell::from(*h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))))
+        .style(Style::default().bg(THEME.surface)).height(1);
+
+    let rows = app.trace_result.iter().flat_map(|r| &r.hops).map(|hop| {
+        let mut cells = vec![
+            ratatui::widgets::Cell::from(hop.ttl.to_string()),
+            match hop.host {
+                Some(host) => ratatui::widgets::Cell::from(host.to_string()).style(Style::default().fg(THEME.accent)),
+                None => ratatui::widgets::Cell::from("???").style(Style::default().fg(THEME.muted)),
+            },
+        ];
+        cells.extend(hop.rtts.iter().map(|rtt| match rtt {
+            Some(d) => ratatui::widgets::Cell::from(format!("{:.1} ms", d.as_secs_f64() * 1000.0)),
+            None => ratatui::widgets::Cell::from("*").style(Style::default().fg(THEME.muted)),
+        }));
+        Row::new(cells).style(Style::default().fg(THEME.fg))
+    });
+
+    let table = Table::new(rows, [
+        Constraint::Length(5),
+        Constraint::Min(20),
+        Constraint::Length(12),
+        Constraint::Length(12),
+        Constraint::Length(12),
+    ].as_ref())
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(" Hops ").border_style(Style::default().fg(THEME.border)))
+    .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD))
+    .highlight_symbol(">");
+
+    f.render_stateful_widget(table, chunks[1], &mut app.trace_table_state);
+}
+
+fn render_sweep(f: &mut Frame, app: &mut App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(3), Constraint::Min(5)].as_ref())
+        .split(area);
+
+    // Controls
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Min(1)].as_ref())
+        .split(chunks[0]);
+
+    let input_block = Block::default()
+        .title(" Range (CIDR) [-c n] [-r n/s] [-t secs] ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(if app.sweep_active { THEME.success } else { THEME.border }));
+    f.render_widget(Paragraph::new(app.sweep_input.value()).block(input_block).style(Style::default().fg(THEME.fg)), top[0]);
+    if !app.sweep_active {
+        f.set_cursor_position((top[0].x + app.sweep_input.visual_cursor() as u16 + 1, top[0].y + 1));
+    }
+
+    let (status_text, status_col) = if app.sweep_active { ("SWEEPING", THEME.success) } else { ("IDLE", THEME.muted) };
+    let info = vec![
+        Span::raw(" "),
+        Span::styled(status_text, Style::default().fg(status_col).add_modifier(Modifier::BOLD)),
+        Span::raw("  Alive: "),
+        Span::styled(app.sweep_hosts.len().to_string(), Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD)),
+    ];
+    f.render_widget(Paragraph::new(Line::from(info)).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)).title(" Status ")), top[1]);
+
+    // Progress
+    let ratio = if app.sweep_total == 0 { 0.0 } else { app.sweep_probed as f64 / app.sweep_total as f64 };
+    let gauge = ratatui::widgets::Gauge::default()
+        .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)))
+        .gauge_style(Style::default().fg(THEME.primary).bg(THEME.surface))
+        .ratio(ratio.min(1.0))
+        .label(format!("{}/{} probed", app.sweep_probed, app.sweep_total));
+    f.render_widget(gauge, chunks[1]);
+
+    // Live Hosts Table
+    use ratatui::widgets::{Table, Row};
+    let header = Row::new(["Host", "RTT", "MAC"].iter().map(|h| ratatui::widgets::Cell::from(*h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))))
+        .style(Style::default().bg(THEME.surface)).height(1);
+
+    let rows = app.sweep_hosts.iter().map(|h| {
+        Row::new(vec![
+            ratatui::widgets::Cell::from(h.ip.to_string()).style(Style::default().fg(THEME.accent)),
+            ratatui::widgets::Cell::from(format!("{:.1} ms", h.rtt.as_secs_f64() * 1000.0)),
+            ratatui::widgets::Cell::from(h.mac.clone().unwrap_or_else(|| "-".to_string())).style(Style::default().fg(THEME.muted)),
+        ]).style(Style::default().fg(THEME.fg))
+    });
+
+    let table = Table::new(rows, [
+        Constraint::Length(18),
+        Constraint::Length(12),
+        Constraint::Min(18),
+    ].as_ref())
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(" Live Hosts ").border_style(Style::default().fg(THEME.border)))
+    .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD))
+    .highlight_symbol(">");
+
+    f.render_stateful_widget(table, chunks[2], &mut app.sweep_table_state);
+}
+
+fn syslog_color(severity: u8) -> Color {
+    match severity {
+        0..=3 => THEME.error,
+        4 => THEME.accent,
+        5 => THEME.primary,
+        6 => THEME.fg,
+        _ => THEME.muted,
+    }
+}
+
+fn render_diagnose(f: &mut Frame, app: &App, area: Rect) {
+    use crate::tools::diagnose::{Check, Status};
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(Check::ALL.len() as u16 + 2), Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .split(area);
+
+    let (status_text, status_col) = if app.diagnose_active { ("CHECKING", THEME.success) } else { ("IDLE", THEME.muted) };
+    let info = vec![
+        Span::raw(" "),
+        Span::styled(status_text, Style::default().fg(status_col).add_modifier(Modifier::BOLD)),
+        Span::styled("  Is it the link, the router, DNS or the ISP? ", Style::default().fg(THEME.muted)),
+        Span::styled(format!("[{}] Run", app.keymap.label(Action::Start)), Style::default().fg(THEME.primary)),
+    ];
+    f.render_widget(Paragraph::new(Line::from(info)).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(if app.diagnose_active { THEME.success } else { THEME.border })).title(" Internet Health Check ")), chunks[0]);
+
+    let lines: Vec<Line> = Check::ALL.iter().map(|check| {
+        let result = app.diagnose_results.iter().find(|r| r.check == *check);
+        let (icon, color, detail) = match result {
+            Some(r) => match r.status {
+                Status::Pass => ("✔", THEME.success, r.detail.clone()),
+                Status::Warn => ("!", THEME.accent, r.detail.clone()),
+                Status::Fail => ("✘", THEME.error, r.detail.clone()),
+                Status::Skipped => ("-", THEME.muted, r.detail.clone()),
+            },
+            None if app.diagnose_running == Some(*check) => ("…", THEME.primary, "checking...".to_string()),
+            None => ("·", THEME.muted, String::new()),
+        };
+        Line::from(vec![
+            Span::styled(format!(" {} ", icon), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+            Span::styled(format!("{:<10}", check.name()), Style::default().fg(THEME.fg).add_modifier(Modifier::BOLD)),
+            Span::styled(detail, Style::default().fg(if result.is_some() { THEME.fg } else { THEME.muted })),
+        ])
+    }).collect();
+    f.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)).title(" Checks ")), chunks[1]);
+
+    let worst = if app.diagnose_results.iter().any(|r| r.status == Status::Fail) {
+        THEME.error
+    } else if app.diagnose_results.iter().any(|r| r.status == Status::Warn) {
+        THEME.accent
+    } else {
+        THEME.success
+    };
+    let (verdict, color) = match &app.diagnose_conclusion {
+        Some(c) => (c.clone(), worst),
+        None if app.diagnose_active => ("Checking...".to_string(), THEME.muted),
+        None => ("Not run yet.".to_string(), THEME.muted),
+    };
+    f.render_widget(Paragraph::new(format!(" {}", verdict)).style(Style::default().fg(color).add_modifier(Modifier::BOLD)).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(color)).title(" Verdict ")), chunks[2]);
+}
+
+fn render_syslog(f: &mut Frame, app: &mut App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(5)].as_ref())
+        .split(area);
+
+    // Controls
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(16), Constraint::Percentage(40), Constraint::Min(1)].as_ref())
+        .split(chunks[0]);
+
+    let editing_filter = app.focused(CurrentScreen::Syslog) == Some(Field::SyslogFilter);
+    let port_block = Block::default()
+        .title(" Port ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(if app.syslog_active { THEME.success } else { focus_color(!editing_filter) }));
+    f.render_widget(Paragraph::new(app.syslog_input.value()).block(port_block).style(Style::default().fg(THEME.fg)), top[0]);
+
+    let filter_block = Block::default()
+        .title(" Filter ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(focus_color(editing_filter)));
+    f.render_widget(Paragraph::new(app.syslog_filter_input.value()).block(filter_block).style(Style::default().fg(THEME.fg)), top[1]);
+
+    if editing_filter {
+        f.set_cursor_position((top[1].x + app.syslog_filter_input.visual_cursor() as u16 + 1, top[1].y + 1));
+    } else if !app.syslog_active {
+        f.set_cursor_position((top[0].x + app.syslog_input.visual_cursor() as u16 + 1, top[0].y + 1));
+    }
+
+    let (status_text, status_col) = if app.syslog_active { ("LISTENING", THEME.success) } else { ("IDLE", THEME.muted) };
+    let info = vec![
+        Span::raw(" "),
+        Span::styled(status_text, Style::default().fg(status_col).add_modifier(Modifier::BOLD)),
+        Span::raw("  Received: "),
+        Span::styled(app.syslog_received.to_string(), Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD)),
+        Span::raw("  Showing: "),
+        Span::styled(format!("{}+", crate::tools::syslog::severity_name(app.syslog_min_severity)), Style::default().fg(syslog_color(app.syslog_min_severity))),
+    ];
+    f.render_widget(Paragraph::new(Line::from(info)).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)).title(" Status ")), top[2]);
+
+    // Messages, newest first
+    use ratatui::widgets::{Table, Row};
+    let header = Row::new(["Time", "Host", "Facility", "Severity", "Program", "Message"].iter().map(|h| ratatui::widgets::Cell::from(*h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))))
+        .style(Style::default().bg(THEME.surface)).height(1);
+
+    let visible = app.syslog_visible();
+    let count = visible.len();
+    let rows: Vec<Row> = visible.into_iter().map(|m| {
+        let mut sev_style = Style::default().fg(syslog_color(m.severity));
+        if m.severity <= 2 {
+            sev_style = sev_style.add_modifier(Modifier::BOLD);
+        }
+        Row::new(vec![
+            ratatui::widgets::Cell::from(m.received.clone()).style(Style::default().fg(THEME.muted)),
+            ratatui::widgets::Cell::from(m.hostname.clone()).style(Style::default().fg(THEME.accent)),
+            ratatui::widgets::Cell::from(m.facility_name()),
+            ratatui::widgets::Cell::from(m.severity_name()).style(sev_style),
+            ratatui::widgets::Cell::from(m.app.clone()).style(Style::default().fg(THEME.secondary)),
+            ratatui::widgets::Cell::from(m.text.clone()).style(sev_style),
+        ])
+    }).collect();
+
+    let title = format!(" Messages ({}) ", count);
+    let table = Table::new(rows, [
+        Constraint::Length(9),
+        Constraint::Length(18),
+        Constraint::Length(9),
+        Constraint::Length(8),
+        Constraint::Length(14),
+        Constraint::Min(20),
+    ].as_ref())
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(title).border_style(Style::default().fg(THEME.border)))
+    .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD))
+    .highlight_symbol(">");
+
+    f.render_stateful_widget(table, chunks[1], &mut app.syslog_table_state);
+}
+
+fn render_flows(f: &mut Frame, app: &App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(8), Constraint::Length(10)].as_ref())
+        .split(area);
+
+    // Controls
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(20), Constraint::Min(1)].as_ref())
+        .split(chunks[0]);
+
+    let input_block = Block::default()
+        .title(" UDP Port ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(if app.flows_active { THEME.success } else { THEME.border }));
+    f.render_widget(Paragraph::new(app.flows_input.value()).block(input_block).style(Style::default().fg(THEME.fg)), top[0]);
+    if !app.flows_active {
+        f.set_cursor_position((top[0].x + app.flows_input.visual_cursor() as u16 + 1, top[0].y + 1));
+    }
+
+    let snap = &app.flows_snapshot;
+    let (status_text, status_col) = if app.flows_active { ("LISTENING", THEME.success) } else { ("IDLE", THEME.muted) };
+    let info = vec![
+        Span::raw(" "),
+        Span::styled(status_text, Style::default().fg(status_col).add_modifier(Modifier::BOLD)),
+        Span::raw("  Datagrams: "),
+        Span::styled(format!("{}", snap.total_datagrams), Style::default().fg(THEME.primary)),
+        Span::raw("  Flows: "),
+        Span::styled(format!("{}", snap.total_flows), Style::default().fg(THEME.primary)),
+        Span::raw("  Exporters: "),
+        Span::styled(snap.exporters.iter().map(|(ip, k)| format!("{} ({})", ip, k)).collect::<Vec<_>>().join(", "), Style::default().fg(THEME.secondary)),
+    ];
+    f.render_widget(Paragraph::new(Line::from(info)).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)).title(" Collector ")), top[1]);
+
+    // Top Talkers / Top Ports
+    let mid = Layout::default()
+        .direction(side_by_side(chunks[1]))
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(chunks[1]);
+
+    use ratatui::widgets::{Table, Row};
+    let head_style = Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD);
+
+    let talker_rows = snap.top_talkers.iter().map(|(ip, bytes, pkts)| {
+        Row::new(vec![
+            ratatui::widgets::Cell::from(ip.to_string()),
+            ratatui::widgets::Cell::from(units::bytes(*bytes)).style(Style::default().fg(THEME.success)),
+            ratatui::widgets::Cell::from(format!("{}", pkts)),
+        ]).style(Style::default().fg(THEME.fg))
+    });
+    let talkers = Table::new(talker_rows, [Constraint::Min(16), Constraint::Length(12), Constraint::Length(10)].as_ref())
+        .header(Row::new(["Source", "Bytes", "Packets"].iter().map(|h| ratatui::widgets::Cell::from(*h).style(head_style))).style(Style::default().bg(THEME.surface)))
+        .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(" Top Talkers (60s) ").border_style(Style::default().fg(THEME.border)));
+    f.render_widget(talkers, mid[0]);
+
+    let port_rows = snap.top_ports.iter().map(|(proto, port, bytes, flows)| {
+        Row::new(vec![
+            ratatui::widgets::Cell::from(crate::tools::flows::protocol_name(*proto)).style(Style::default().fg(THEME.secondary)),
+            ratatui::widgets::Cell::from(format!("{}", port)),
+            ratatui::widgets::Cell::from(units::bytes(*bytes)).style(Style::default().fg(THEME.success)),
+            ratatui::widgets::Cell::from(format!("{}", flows)),
+        ]).style(Style::default().fg(THEME.fg))
+    });
+    let ports = Table::new(port_rows, [Constraint::Length(7), Constraint::Length(7), Constraint::Length(12), Constraint::Min(6)].as_ref())
+        .header(Row::new(["Proto", "Port", "Bytes", "Flows"].iter().map(|h| ratatui::widgets::Cell::from(*h).style(head_style))).style(Style::default().bg(THEME.surface)))
+        .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(" Top Ports (60s) ").border_style(Style::default().fg(THEME.border)));
+    f.render_widget(ports, mid[1]);
+
+    // Flow Rate Chart
+    let rate_data: Vec<(f64, f64)> = app.flows_rate_history.iter().enumerate().map(|(i, &v)| (i as f64, v)).collect();
+    let fps_data: Vec<(f64, f64)> = app.flows_fps_history.iter().enumerate().map(|(i, &v)| (i as f64, v)).collect();
+    let max_val = rate_data.iter().chain(fps_data.iter()).map(|(_, v)| *v).fold(1.0f64, f64::max) * 1.2;
+
+    let chart = Chart::new(vec![
+        Dataset::default().name("Mbps").marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(THEME.primary)).data(&rate_data),
+        Dataset::default().name("flows/s").marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(THEME.secondary)).data(&fps_data),
+    ])
+    .block(Block::default().title(format!(" Flow Rate: {}, {:.1} flows/s ", units::rate(snap.bytes_per_sec), snap.flows_per_sec)).borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)))
+    .x_axis(Axis::default().bounds([0.0, 100.0]).style(Style::default().fg(THEME.muted)))
+    .y_axis(Axis::default().bounds([0.0, max_val]).style(Style::default().fg(THEME.muted)));
+    f.render_widget(chart, chunks[2]);
+}
+
+fn render_devices(f: &mut Frame, app: &mut App, area: Rect) {
+    use ratatui::widgets::{Table, Row};
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(6), Constraint::Length(8)].as_ref())
+        .split(area);
+
+    let header = Row::new(["MAC Address", "Vendor", "Hostname", "IP Address", "First Seen", "Last Seen"].iter().map(|h| ratatui::widgets::Cell::from(*h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))))
+        .style(Style::default().bg(THEME.surface)).height(1);
+
+    let hits = search_hits(app, CurrentScreen::Devices);
+    let devices = app.devices();
+    let rows = devices.iter().enumerate().map(|(i, d)| {
+        let style = if hits.contains(&i) {
+            row_style(true)
+        } else if d.new {
+            Style::default().fg(THEME.success).add_modifier(Modifier::BOLD)
+        } else if !d.online() {
+            Style::default().fg(THEME.muted)
+        } else {
+            Style::default().fg(THEME.fg)
+        };
+        let ip = match (d.ip(), d.ips.len()) {
+            (Some(ip), n) if n > 1 => format!("{} (+{})", ip, n - 1),
+            (Some(ip), _) => ip.to_string(),
+            (None, _) => "-".to_string(),
+        };
+        Row::new(vec![
+            ratatui::widgets::Cell::from(format!("{}{}", if d.new { "* " } else { "" }, d.mac)),
+            ratatui::widgets::Cell::from(d.vendor.clone()),
+            ratatui::widgets::Cell::from(d.hostname.clone().unwrap_or_default()),
+            ratatui::widgets::Cell::from(ip),
+            ratatui::widgets::Cell::from(crate::inventory::date_label(d.first_seen)),
+            ratatui::widgets::Cell::from(crate::inventory::ago(d.last_seen)),
+        ]).style(style)
+    });
+
+    let online = devices.iter().filter(|d| d.online()).count();
+    let new = devices.iter().filter(|d| d.new).count();
+    let title = match &app.inventory_error {
+        Some(e) => format!(" Devices ({}) - {} ", devices.len(), e),
+        None if new > 0 => format!(" Devices ({} online, {} total, {} new) - [c] mark seen ", online, devices.len(), new),
+        None => format!(" Devices ({} online, {} total) ", online, devices.len()),
+    };
+    let table = Table::new(rows, [
+        Constraint::Length(19),
+        Constraint::Length(24),
+        Constraint::Length(20),
+        Constraint::Length(22),
+        Constraint::Length(17),
+        Constraint::Min(10),
+    ].as_ref())
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(title).border_style(Style::default().fg(if new > 0 { THEME.success } else { THEME.border })))
+    .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD))
+    .highlight_symbol(">");
+
+    // Address history of the selected device
+    let selected = app.devices_table_state.selected().and_then(|_| devices.get(app.devices_selected));
+    let lines: Vec<Line> = match selected {
+        Some(d) => d.ips.iter().map(|i| Line::from(vec![
+            Span::styled(format!(" {:<40}", i.ip), Style::default().fg(THEME.fg)),
+            Span::styled(format!("{} - {}", crate::inventory::date_label(i.first_seen), crate::inventory::date_label(i.last_seen)), Style::default().fg(THEME.muted)),
+        ])).collect(),
+        None => vec![Line::from(Span::styled(" Select a device to see the addresses it has used.", Style::default().fg(THEME.muted)))],
+    };
+    let detail_title = selected.map(|d| format!(" Addresses of {} ", d.mac)).unwrap_or_else(|| " Addresses ".to_string());
+    let detail = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(detail_title).border_style(Style::default().fg(THEME.border)));
+
+    f.render_widget(detail, chunks[1]);
+    f.render_stateful_widget(table, chunks[0], &mut app.devices_table_state);
+}
+
+fn render_history(f: &mut Frame, app: &mut App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Percentage(45), Constraint::Min(8)].as_ref())
+        .split(area);
+
+    // Filter
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Min(1)].as_ref())
+        .split(chunks[0]);
+
+    let input_block = Block::default().title(" Filter ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
+    f.render_widget(Paragraph::new(app.history_input.value()).block(input_block).style(Style::default().fg(THEME.fg)), top[0]);
+    f.set_cursor_position((top[0].x + app.history_input.visual_cursor() as u16 + 1, top[0].y + 1));
+
+    let mut info = vec![
+        Span::raw(" Results: "),
+        Span::styled(format!("{}", app.history_entries.len()), Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD)),
+    ];
+    if let Some(cmp) = &app.history_compare {
+        info.push(Span::raw("  Comparing with: "));
+        info.push(Span::styled(format!("{} {}", cmp.time_label(), cmp.target), Style::default().fg(THEME.accent)));
+    }
+    if let Some(e) = &app.history_error {
+        info.push(Span::styled(format!("  {}", e), Style::default().fg(THEME.error)));
+    }
+    f.render_widget(Paragraph::new(Line::from(info)).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)).title(" History ")), top[1]);
+
+    // Results Table
+    use ratatui::widgets::{Table, Row};
+    let header = Row::new(["Time (UTC)", "Tool", "Target", "Value", "Summary"].iter().map(|h| ratatui::widgets::Cell::from(*h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))))
+        .style(Style::default().bg(THEME.surface)).height(1);
+
+    let compare_id = app.history_compare.as_ref().map(|c| c.id);
+    let rows = app.history_entries.iter().map(|e| {
+        let marker = if Some(e.id) == compare_id { "* " } else { "" };
+        Row::new(vec![
+            ratatui::widgets::Cell::from(format!("{}{}", marker, e.time_label())),
+            ratatui::widgets::Cell::from(e.kind.as_str()).style(Style::default().fg(THEME.secondary)),
+            ratatui::widgets::Cell::from(e.target.clone()),
+            ratatui::widgets::Cell::from(e.metric.map(|m| format!("{:.1}", m)).unwrap_or_else(|| "-".to_string())).style(Style::default().fg(THEME.success)),
+            ratatui::widgets::Cell::from(e.summary.clone()),
+        ]).style(Style::default().fg(THEME.fg))
+    });
+
+    let table = Table::new(rows, [
+        Constraint::Length(24),
+        Constraint::Length(6),
+        Constraint::Length(30),
+        Constraint::Length(8),
+        Constraint::Min(20),
+    ].as_ref())
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(" Past Results ").border_style(Style::default().fg(THEME.border)))
+    .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD))
+    .highlight_symbol(">> ");
+    f.render_stateful_widget(table, chunks[1], &mut app.history_table_state);
+
+    // Details / Compare / Trend
+    let bottom = Layout::default()
+        .direction(side_by_side(chunks[2]))
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+        .split(chunks[2]);
+
+    let selected = app.history_entries.get(app.history_selected);
+    let detail_block = |title: String| Block::default().title(title).borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
+
+    match (selected, &app.history_compare) {
+        (Some(sel), Some(cmp)) if app.history_scan_diff.is_some() => {
+            let changes = app.history_scan_diff.as_deref().unwrap_or_default();
+            let (old, new) = if (cmp.timestamp, cmp.id) < (sel.timestamp, sel.id) { (cmp, sel) } else { (sel, cmp) };
+            let count = |c: PortChange| changes.iter().filter(|(change, _)| *change == c).count();
+            let mut lines = vec![Line::from(vec![
+                Span::styled(format!(" +{} opened", count(PortChange::Opened)), Style::default().fg(THEME.success).add_modifier(Modifier::BOLD)),
+                Span::styled(format!("  -{} closed", count(PortChange::Closed)), Style::default().fg(THEME.error).add_modifier(Modifier::BOLD)),
+                Span::styled(format!("  {} unchanged", count(PortChange::Unchanged)), Style::default().fg(THEME.muted)),
+            ])];
+            if old.target != new.target {
+                lines.push(Line::from(Span::styled(format!(" Different targets: {} vs {}", old.target, new.target), Style::default().fg(THEME.accent))));
+            }
+            lines.extend(changes.iter().map(|(change, p)| {
+                let (mark, col) = match change {
+                    PortChange::Opened => ("+", THEME.success),
+                    PortChange::Closed => ("-", THEME.error),
+                    PortChange::Unchanged => (" ", THEME.muted),
+                };
+                Line::from(Span::styled(format!(" {} {:>5}/{:<4} {:<12} {}", mark, p.port, p.proto, p.service, p.host), Style::default().fg(col)))
+            }));
+            let title = format!(" Port diff: {} -> {} ", old.time_label(), new.time_label());
+            f.render_widget(Paragraph::new(lines).block(detail_block(title)), bottom[0]);
+        }
+        (Some(sel), Some(cmp)) if cmp.id != sel.id => {
+            let halves = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                .split(bottom[0]);
+            f.render_widget(Paragraph::new(sel.details.as_str()).style(Style::default().fg(THEME.fg)).block(detail_block(format!(" Selected: {} ", sel.time_label()))), halves[0]);
+            f.render_widget(Paragraph::new(cmp.details.as_str()).style(Style::default().fg(THEME.accent)).block(detail_block(format!(" Marked: {} ", cmp.time_label()))), halves[1]);
+        }
+        (Some(sel), _) => {
+            f.render_widget(Paragraph::new(sel.details.as_str()).style(Style::default().fg(THEME.fg)).block(detail_block(format!(" {} {} ", sel.kind.as_str(), sel.target))), bottom[0]);
+        }
+        (None, _) => {
+            f.render_widget(Paragraph::new(" No results yet. Finished pings, lookups, scans and traces appear here.").style(Style::default().fg(THEME.muted)).block(detail_block(" Details ".to_string())), bottom[0]);
+        }
+    }
+
+    // Trend of the selected target's metric across runs
+    let label = selected.and_then(|e| e.kind.metric_label());
+    match label {
+        Some(label) if app.history_trend.len() > 1 => {
+            let data: Vec<(f64, f64)> = app.history_trend.iter().enumerate().map(|(i, (_, v))| (i as f64, *v)).collect();
+            let max_val = data.iter().map(|(_, v)| *v).fold(1.0f64, f64::max) * 1.2;
+            let chart = Chart::new(vec![
+                Dataset::default().marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(THEME.primary)).data(&data),
+            ])
+            .block(detail_block(format!(" Trend: {} ({} runs) ", label, data.len())))
+            .x_axis(Axis::default().bounds([0.0, (data.len() - 1) as f64]).style(Style::default().fg(THEME.muted)))
+            .y_axis(Axis::default().bounds([0.0, max_val]).labels(vec![Span::raw("0"), Span::raw(format!("{:.0}", max_val))]).style(Style::default().fg(THEME.muted)));
+            f.render_widget(chart, bottom[1]);
+        }
+        _ => {
+            f.render_widget(Paragraph::new(" Run the same target again to see a trend.").style(Style::default().fg(THEME.muted)).block(detail_block(" Trend ".to_string())), bottom[1]);
+        }
+    }
+}
+
+fn render_talkers(f: &mut Frame, app: &mut App, area: Rect) {
+    use ratatui::widgets::{Table, Row};
+
+    let header = Row::new(["#", if app.connections_hostnames { "Remote Host" } else { "Remote IP" }, "Country", "Organization", "Bytes", "Rate", "Share"].iter().map(|h| ratatui::widgets::Cell::from(*h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))))
+        .style(Style::default().bg(THEME.bg)).height(1);
+
+    let talkers = app.top_talkers();
+    // Peers drop off as their traffic ages out of the window
+    if app.talkers_selected >= talkers.len() && app.talkers_table_state.selected().is_some() {
+        app.talkers_selected = talkers.len().saturating_sub(1);
+        app.talkers_table_state.select((!talkers.is_empty()).then_some(app.talkers_selected));
+    }
+    let hits = search_hits(app, CurrentScreen::Connections);
+    let span = app.talkers.span(app.talkers_window) as f64;
+    let total = talkers.iter().map(|h| h.bytes).sum::<u64>().max(1) as f64;
+    let rows = talkers.iter().enumerate().map(|(i, h)| {
+        let share = h.bytes as f64 / total;
+        Row::new(vec![
+            ratatui::widgets::Cell::from(format!("{}", i + 1)).style(Style::default().fg(THEME.muted)),
+            ratatui::widgets::Cell::from(app.peer_label(h.ip)),
+            ratatui::widgets::Cell::from(h.country.as_deref().map(|cc| country_label(app, cc)).unwrap_or_else(|| "-".to_string())),
+            ratatui::widgets::Cell::from(h.org.clone()),
+            ratatui::widgets::Cell::from(units::bytes(h.bytes)).style(Style::default().fg(THEME.success)),
+            ratatui::widgets::Cell::from(units::rate(h.bytes as f64 / span)),
+            ratatui::widgets::Cell::from(format!("{:<10} {:>3.0}%", "█".repeat((share * 10.0).round() as usize), share * 100.0)).style(Style::default().fg(THEME.secondary)),
+        ]).style(row_style(hits.contains(&i)))
+    });
+
+    let title = if app.sniffer_active {
+        format!(" Top Talkers (last {}, [Ctrl+N] window) - [v] established ", app.talkers_window.label())
+    } else {
+        " Top Talkers - start the sniffer to count traffic per peer - [v] established ".to_string()
+    };
+    let table = Table::new(rows, [
+        Constraint::Length(3),
+        Constraint::Length(26),
+        Constraint::Length(7),
+        Constraint::Min(20),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(15),
+    ].as_ref())
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(title).border_style(Style::default().fg(THEME.border)))
+    .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD));
+
+    f.render_stateful_widget(table, area, &mut app.talkers_table_state);
+}
+
+fn render_listeners(f: &mut Frame, app: &mut App, area: Rect) {
+    use ratatui::widgets::{Table, Row};
+
+    let header = Row::new(["Proto", "Address", "Port", "PID", "Process"].iter().map(|h| ratatui::widgets::Cell::from(*h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))))
+        .style(Style::default().bg(THEME.bg)).height(1);
+
+    let hits = search_hits(app, CurrentScreen::Connections);
+    let rows = app.listeners.iter().enumerate().map(|(i, l)| {
+        // Wildcard binds are reachable from the network; loopback ones are not
+        let exposed = matches!(l.address.as_str(), "0.0.0.0" | "::" | "*");
+        Row::new(vec![
+            ratatui::widgets::Cell::from(l.protocol.clone()),
+            ratatui::widgets::Cell::from(l.address.clone()).style(Style::default().fg(if exposed { THEME.secondary } else { THEME.muted })),
+            ratatui::widgets::Cell::from(l.port.to_string()),
+            ratatui::widgets::Cell::from(l.pid.map(|p| p.to_string()).unwrap_or_else(|| "-".to_string())),
+            ratatui::widgets::Cell::from(l.process.clone()),
+        ]).style(row_style(hits.contains(&i)))
+    });
+
+    let title = match &app.listeners_error {
+        Some(e) => format!(" Listening Sockets - {} ", e),
+        None => format!(" Listening Sockets ({}) - [v] top talkers ", app.listeners.len()),
+    };
+    let table = Table::new(rows, [
+        Constraint::Length(6),
+        Constraint::Length(40),
+        Constraint::Length(6),
+        Constraint::Length(8),
+        Constraint::Min(12),
+    ].as_ref())
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(title).border_style(Style::default().fg(THEME.border)))
+    .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD));
+
+    f.render_stateful_widget(table, area, &mut app.listeners_table_state);
+}
+
+fn render_connections(f: &mut Frame, app: &mut App, area: Rect) {
+    match app.connections_view {
+        ConnectionsView::Listening => return render_listeners(f, app, area),
+        ConnectionsView::Talkers => return render_talkers(f, app, area),
+        ConnectionsView::Established => {}
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
         .split(area);
         
     use ratatui::widgets::{Table, Row};
     
-    let header_cells = ["Remote IP", "ASN", "Organization", "Protocol", "Packets", "Last Seen"]
-        .iter()
-        .map(|h| ratatui::widgets::Cell::from(*h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD)));
-    let header = Row::new(header_cells).style(Style::default().bg(THEME.bg)).height(1).bottom_margin(0);
+    // Local port, ASN, protocol, process, packets and last seen make way on narrow terminals
+    let reputation = app.config.reputation.enabled();
+    let mut columns = fitting_columns(chunks[0].width.saturating_sub(2), &[
+        (26, false), (9, false), (6, true), (12, false), (if reputation { 8 } else { 0 }, false), (7, false), (10, true),
+        (20, false), (6, true), (18, true), (7, true), (10, false), (TREND_WIDTH as u16, true), (10, true),
+    ]);
+    // Risk only shows with a threat feed configured
+    columns[4] = reputation;
+    let header_cells = [if app.connections_hostnames { "Remote Host" } else { "Remote IP" }, "Port", "Local", "State", "Risk", "Country", "ASN", "Organization", "Protocol", "Process", "Packets", "Rate", "Trend", "Last Seen"]
+        .into_iter()
+        .map(|h| ratatui::widgets::Cell::from(h).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD)));
+    let header = Row::new(keep_columns(header_cells, &columns)).style(Style::default().bg(THEME.bg)).height(1).bottom_margin(0);
     
     // Sort connections by time (most recent first)
-    let mut connections: Vec<&crate::app::ConnectionInfo> = app.active_connections.values().collect();
-    connections.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+    let connections = app.sorted_connections();
+    let hits = search_hits(app, CurrentScreen::Connections);
     
-    let rows = connections.iter().map(|c| {
+    let rows = connections.iter().enumerate().map(|(i, c)| {
         let time_since = std::time::Instant::now().duration_since(c.last_seen).as_secs();
         let time_str = if time_since < 60 {
             format!("{}s ago", time_since)
@@ -549,29 +2884,70 @@ fn render_connections(f: &mut Frame, app: &App, area: Rect) {
              format!("{}m ago", time_since / 60)
         };
         
+        let port = |p: Option<u16>| p.map(|p| p.to_string()).unwrap_or_else(|| "*".to_string());
+        let state = if c.state.is_empty() { "-".to_string() } else { c.state.clone() };
+        let flagged = app.peer_flagged(c.remote_ip);
+        let risk = match app.peer_reputation.get(&c.remote_ip) {
+            Some(Some(r)) => ratatui::widgets::Cell::from(format!("{}{}", theme::mark(!flagged), r.label()))
+                .style(Style::default().fg(if flagged { THEME.error } else if r.score.is_some_and(|s| s > 0) { THEME.accent } else { THEME.muted })),
+            Some(None) => ratatui::widgets::Cell::from("...").style(Style::default().fg(THEME.muted)),
+            None => ratatui::widgets::Cell::from("-").style(Style::default().fg(THEME.muted)),
+        };
         let cells = vec![
-            ratatui::widgets::Cell::from(c.remote_ip.to_string()),
+            ratatui::widgets::Cell::from(app.peer_label(c.remote_ip)),
+            ratatui::widgets::Cell::from(if c.sockets > 1 { format!("{} +{}", port(c.remote_port), c.sockets - 1) } else { port(c.remote_port) }),
+            ratatui::widgets::Cell::from(port(c.local_port)).style(Style::default().fg(THEME.muted)),
+            ratatui::widgets::Cell::from(state).style(Style::default().fg(tcp_state_color(&c.state))),
+            risk,
+            ratatui::widgets::Cell::from(c.country.as_deref().map(|cc| country_label(app, cc)).unwrap_or_else(|| "-".to_string())),
             ratatui::widgets::Cell::from(format!("AS{}", c.asn_num)).style(Style::default().fg(THEME.secondary)),
             ratatui::widgets::Cell::from(c.asn_org.clone()),
             ratatui::widgets::Cell::from(c.protocol.clone()),
+            ratatui::widgets::Cell::from(match (&c.process, c.pid) {
+                (Some(name), Some(pid)) => format!("{} ({})", name, pid),
+                _ => "-".to_string(),
+            }).style(Style::default().fg(THEME.muted)),
             ratatui::widgets::Cell::from(format!("{}", c.packet_count)),
+            ratatui::widgets::Cell::from(units::rate(c.rate_bps)),
+            ratatui::widgets::Cell::from(sparkline::braille(c.rate_history.iter().copied(), TREND_WIDTH)).style(Style::default().fg(THEME.secondary)),
             ratatui::widgets::Cell::from(time_str),
         ];
-        Row::new(cells).style(Style::default().fg(THEME.fg))
+        let style = if flagged && !hits.contains(&i) { Style::default().fg(THEME.error).add_modifier(Modifier::BOLD) } else { row_style(hits.contains(&i)) };
+        Row::new(keep_columns(cells, &columns)).style(style)
     });
     
-    let table = Table::new(rows, [
-        Constraint::Length(16), // IP
+    let geoip_note = match (&app.geoip_reader, &app.geoip_error) {
+        (None, Some(_)) => " - GeoIP database failed to load",
+        (None, None) => " - no GeoIP database, see [geoip] config",
+        (Some(reader), _) if !reader.has_ipv6() && connections.iter().any(|c| c.remote_ip.is_ipv6()) => " - GeoIP database has no IPv6 data",
+        _ => "",
+    };
+
+    // Connections per TCP state, in the table's state colors
+    let mut title = vec![Span::raw(format!(" Active Connections (sort: {}){} ", app.connections_sort.label(), geoip_note))];
+    for (state, count) in &app.connection_states {
+        title.push(Span::styled(format!("{} {} ", state, count), Style::default().fg(tcp_state_color(state))));
+    }
+
+    let table = Table::new(rows, keep_columns([
+        Constraint::Length(26), // IP
+        Constraint::Length(9),  // Port
+        Constraint::Length(6),  // Local port
+        Constraint::Length(12), // State
+        Constraint::Length(8),  // Risk
+        Constraint::Length(7),  // Country
         Constraint::Length(10), // ASN
         Constraint::Min(20),    // Org (reduced)
         Constraint::Length(6),  // Proto
+        Constraint::Length(18), // Process
         Constraint::Length(7), // Packets
+        Constraint::Length(10), // Rate
+        Constraint::Length(TREND_WIDTH as u16), // Trend
         Constraint::Length(10), // Last Seen
-    ].as_ref())
+    ], &columns))
     .header(header)
-    .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(" Active Connections ").border_style(Style::default().fg(THEME.border)));
-    
-    f.render_widget(table, chunks[0]);
+    .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(Line::from(title)).border_style(Style::default().fg(THEME.border)))
+    .row_highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD));
     
     // Map Rendering
     let mut locs = vec![];
@@ -582,8 +2958,17 @@ fn render_connections(f: &mut Frame, app: &App, area: Rect) {
         }
     }
     
+    // The highlight follows the peer, wherever this tick's sort put it
+    app.connections_table_state.select(app.selected_connection_row());
+    f.render_stateful_widget(table, chunks[0], &mut app.connections_table_state);
+
+    if app.connections_globe {
+        render_globe(f, app, &locs, chunks[1]);
+        return;
+    }
+
     let map_block = Block::default()
-        .title(" World Map ")
+        .title(" World Map ([g] Globe) ")
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(THEME.accent));
@@ -609,28 +2994,94 @@ fn render_connections(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(canvas, chunks[1]);
 }
 
+/// Established is healthy; handshakes in progress stand out, and CLOSE_WAIT (the local
+/// side never closed its end) is flagged as the usual sign of a leaking application.
+fn tcp_state_color(state: &str) -> Color {
+    match state {
+        "ESTABLISHED" => THEME.success,
+        "SYN_SENT" | "SYN_RECV" | "SYN_RECEIVED" => THEME.accent,
+        "CLOSE_WAIT" => THEME.error,
+        "TIME_WAIT" | "FIN_WAIT1" | "FIN_WAIT2" | "CLOSING" | "LAST_ACK" | "CLOSED" => THEME.muted,
+        _ => THEME.fg,
+    }
+}
+
+/// `locs` are (lon, lat) map coordinates, as for the flat map.
+fn render_globe(f: &mut Frame, app: &App, locs: &[(f64, f64)], area: Rect) {
+    use ratatui::widgets::canvas::Canvas;
+
+    let block = Block::default()
+        .title(" Globe ([g] Map) ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(THEME.accent));
+
+    // Braille cells are 2x4 dots; widen the x range so the unit circle stays round
+    let inner = block.inner(area);
+    let aspect = inner.width as f64 / (2.0 * inner.height.max(1) as f64);
+    let markers: Vec<(f64, f64)> = locs.iter().map(|&(lon, lat)| (lat, lon)).collect();
+    let home = app.config.geoip.home;
+
+    let canvas = Canvas::default()
+        .block(block)
+        .x_bounds([-1.1 * aspect, 1.1 * aspect])
+        .y_bounds([-1.1, 1.1])
+        .paint(|ctx| {
+            ctx.draw(&globe::Globe {
+                rotation: app.globe_rotation,
+                markers: &markers,
+                home,
+                grid_color: THEME.border,
+                arc_color: THEME.secondary,
+            });
+            ctx.layer();
+            for &(lat, lon) in &markers {
+                if let Some((x, y)) = globe::project(lat, lon, app.globe_rotation) {
+                    ctx.print(x, y, Span::styled("●", Style::default().fg(THEME.error)));
+                }
+            }
+            if let Some((lat, lon)) = home
+                && let Some((x, y)) = globe::project(lat, lon, app.globe_rotation)
+            {
+                ctx.print(x, y, Span::styled("◆", Style::default().fg(THEME.success)));
+            }
+        });
+    f.render_widget(canvas, area);
+}
+
 fn render_dashboard(f: &mut Frame, app: &App, area: Rect) {
-    let chunks = Layout::default()
+    if let Some((name, link)) = &app.iface_detail {
+        render_interface_detail(f, app, name, link, area);
+        return;
+    }
+
+    let rows = &app.config.dashboard.rows;
+    // One panel per row on narrow terminals
+    let stacked: Vec<Vec<Panel>>;
+    let rows = if area.width < NARROW && rows.iter().any(|r| r.len() > 1) {
+        stacked = rows.iter().flatten().map(|p| vec![*p]).collect();
+        &stacked
+    } else {
+        rows
+    };
+    if rows.is_empty() {
+        let hint = format!(" No dashboard panels. Press [{}] to edit the layout.", app.keymap.label(Action::EditLayout));
+        f.render_widget(Paragraph::new(hint).style(Style::default().fg(THEME.muted)), area);
+        return;
+    }
+
+    // Chart rows share the height; rows of only lists get a fixed slice
+    let row_constraints: Vec<Constraint> = rows
+        .iter()
+        .map(|r| if r.iter().any(|p| p.is_chart()) { Constraint::Fill(1) } else { Constraint::Length(10) })
+        .collect();
+    let row_areas = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage(40),
-            Constraint::Percentage(30),
-            Constraint::Min(10)
-        ].as_ref())
+        .constraints(row_constraints)
         .split(area);
 
-    let row1 = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-        .split(chunks[0]);
-        
-    let row2 = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-        .split(chunks[1]);
-
     // Helper to create a unified chart block
-    let draw_chart = |f: &mut Frame, area: Rect, title: &str, data: &[(f64, f64)], data2: Option<&[(f64, f64)]>, color: Color, color2: Option<Color>, value_text: Vec<(&str, String, Color)>| {
+    let draw_chart = |f: &mut Frame, area: Rect, title: &str, lines: &[(&[(f64, f64)], Color)], mirrored: bool, slo: f64, value_text: Vec<(&str, String, Color)>| {
         let block = Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
@@ -649,32 +3100,24 @@ fn render_dashboard(f: &mut Frame, app: &App, area: Rect) {
             stats_spans.push(Span::styled(label, Style::default().fg(THEME.muted)));
             stats_spans.push(Span::raw("  "));
         }
-        f.render_widget(Paragraph::new(Line::from(stats_spans)).alignment(ratatui::layout::Alignment::Right), Rect { x: area.x + 2, y: area.y + 1, width: area.width - 4, height: 1 });
+        f.render_widget(Paragraph::new(Line::from(stats_spans)).alignment(ratatui::layout::Alignment::Right), Rect { x: area.x + 2, y: area.y + 1, width: area.width.saturating_sub(4), height: 1.min(inner_area.height) });
 
         // Chart
-        let chart_area = Rect { x: inner_area.x, y: inner_area.y + 2, width: inner_area.width, height: inner_area.height - 2 };
-        let mut datasets = vec![
+        let chart_area = Rect { x: inner_area.x, y: inner_area.y + 2, width: inner_area.width, height: inner_area.height.saturating_sub(2) };
+        let series: Vec<&[(f64, f64)]> = lines.iter().map(|(data, _)| *data).collect();
+        let overlay = SloOverlay::new(slo, &series, 100.0);
+        let mut datasets: Vec<Dataset> = overlay.iter().flat_map(|o| o.datasets()).collect();
+        datasets.extend(lines.iter().map(|(data, color)| {
             Dataset::default()
                 .marker(symbols::Marker::Braille)
                 .graph_type(GraphType::Line)
-                .style(Style::default().fg(color))
+                .style(Style::default().fg(*color))
                 .data(data)
-        ];
-        if let Some(d2) = data2 {
-            if let Some(c2) = color2 {
-                 datasets.push(
-                    Dataset::default()
-                        .marker(symbols::Marker::Braille)
-                        .graph_type(GraphType::Line)
-                        .style(Style::default().fg(c2))
-                        .data(d2)
-                 );
-            }
-        }
+        }));
         
-        // Dynamic Y-Bound
-        let max_val = data.iter().chain(data2.unwrap_or(&[]).iter()).map(|(_, v)| v.abs()).fold(0.0f64, |a, b| a.max(b)).max(1.0) * 1.2;
-        let min_val = if data2.is_some() { -max_val } else { 0.0 };
+        // Dynamic Y-Bound, keeping the SLO line in view
+        let max_val = lines.iter().flat_map(|(data, _)| data.iter()).map(|(_, v)| v.abs()).fold(slo, |a, b| a.max(b)).max(1.0) * 1.2;
+        let min_val = if mirrored { -max_val } else { 0.0 };
 
         let chart = Chart::new(datasets)
             .x_axis(Axis::default().bounds([0.0, 100.0]).style(Style::default().fg(THEME.muted)))
@@ -683,58 +3126,311 @@ fn render_dashboard(f: &mut Frame, app: &App, area: Rect) {
         f.render_widget(chart, chart_area);
     };
 
-    // 1. Internet Bandwidth (Mirrored)
-    let wan_rx_val = *app.wan_rx_history.back().unwrap_or(&0.0);
-    let wan_tx_val = *app.wan_tx_history.back().unwrap_or(&0.0);
-    let wan_rx_data: Vec<(f64, f64)> = app.wan_rx_history.iter().enumerate().map(|(i, &v)| (i as f64, v)).collect();
-    let wan_tx_data: Vec<(f64, f64)> = app.wan_tx_history.iter().enumerate().map(|(i, &v)| (i as f64, -v)).collect();
+    // Live shows the raw per-tick histories; other ranges use the downsampled tiers
+    let range = app.dash_range;
+    let live = range == TimeRange::Live;
+    let titled = |title: &str| if live { title.to_string() } else { format!("{} [{}]", title, range.label()) };
+    let series = |history: &mut dyn Iterator<Item = f64>, tiered: &crate::series::TieredSeries| -> Vec<(f64, f64)> {
+        if live {
+            history.enumerate().map(|(i, v)| (i as f64, v)).collect()
+        } else {
+            tiered.points(range)
+        }
+    };
 
-    let stats_wan = vec![
-        ("↓", format!("{:.1} Mbps", wan_rx_val), THEME.primary),
-        ("↑", format!("{:.1} Mbps", wan_tx_val), THEME.secondary),
-    ];
-    draw_chart(f, row1[0], "Internet Traffic", &wan_rx_data, Some(&wan_tx_data), THEME.primary, Some(THEME.secondary), stats_wan);
+    for (row, row_area) in rows.iter().zip(row_areas.iter()) {
+        let cells = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Ratio(1, row.len() as u32); row.len()])
+            .split(*row_area);
+
+        for (i, (panel, &cell)) in row.iter().zip(cells.iter()).enumerate() {
+            let last = i + 1 == row.len();
+            match panel {
+                Panel::Bandwidth => {
+                    // Mirrored: download up, upload down
+                    let rx_val = *app.wan_rx_history.back().unwrap_or(&0.0);
+                    let tx_val = *app.wan_tx_history.back().unwrap_or(&0.0);
+                    let rx_data = series(&mut app.wan_rx_history.iter().copied(), &app.dash_wan_rx);
+                    let tx_data: Vec<_> = series(&mut app.wan_tx_history.iter().copied(), &app.dash_wan_tx).into_iter().map(|(x, v)| (x, -v)).collect();
+                    let stats = vec![
+                        ("↓", units::rate_mbps(rx_val), THEME.primary),
+                        ("↑", units::rate_mbps(tx_val), THEME.secondary),
+                    ];
+                    let title = if app.bandwidth_from_os { format!("{} (all interfaces)", panel.title()) } else { panel.title().to_string() };
+                    draw_chart(f, cell, &titled(&title), &[(&rx_data, THEME.primary), (&tx_data, THEME.secondary)], true, 0.0, stats);
+                }
+                Panel::LanTraffic => {
+                    let rx_val = *app.lan_rx_history.back().unwrap_or(&0.0);
+                    let tx_val = *app.lan_tx_history.back().unwrap_or(&0.0);
+                    let rx_data = series(&mut app.lan_rx_history.iter().copied(), &app.dash_lan_rx);
+                    let tx_data: Vec<_> = series(&mut app.lan_tx_history.iter().copied(), &app.dash_lan_tx).into_iter().map(|(x, v)| (x, -v)).collect();
+                    let stats = vec![
+                        ("↓", units::rate_mbps(rx_val), THEME.accent),
+                        ("↑", units::rate_mbps(tx_val), THEME.secondary),
+                    ];
+                    let title = if app.bandwidth_from_os { format!("{} (needs capture)", panel.title()) } else { panel.title().to_string() };
+                    draw_chart(f, cell, &titled(&title), &[(&rx_data, THEME.accent), (&tx_data, THEME.secondary)], true, 0.0, stats);
+                }
+                Panel::Connections => {
+                    let val = *app.connection_count_history.back().unwrap_or(&0);
+                    let data = series(&mut app.connection_count_history.iter().map(|&v| v as f64), &app.dash_conns);
+                    let stats = vec![("Active", format!("{}", val), THEME.success)];
+                    draw_chart(f, cell, &titled(panel.title()), &[(&data, THEME.success)], false, 0.0, stats);
+                }
+                Panel::Latency | Panel::Jitter => {
+                    // One line per target, with the legend in the stats overlay
+                    let latency = *panel == Panel::Latency;
+                    let data: Vec<_> = app.dash_probes.iter().map(|p| {
+                        let (history, tiered) = if latency { (&p.latency, &p.latency_series) } else { (&p.jitter, &p.jitter_series) };
+                        series(&mut history.iter().map(|&v| v as f64), tiered)
+                    }).collect();
+                    let colors = probe_colors();
+                    let lines: Vec<_> = data.iter().zip(colors.iter().cycle()).map(|(d, c)| (d.as_slice(), *c)).collect();
+                    // Over the loss SLO turns a target red as well
+                    let slo = if latency { app.config.slo.latency_ms } else { app.config.slo.jitter_ms };
+                    let max_loss = app.config.slo.loss_percent;
+                    let stats = app.dash_probes.iter().zip(colors.iter().cycle()).map(|(p, &color)| {
+                        let val = if latency { p.last_latency() } else { p.last_jitter() };
+                        let lossy = max_loss > 0.0 && p.loss_ratio() * 100.0 > max_loss;
+                        let color = if lossy { THEME.error } else { slo_color(val as f64, slo, f64::MAX, color) };
+                        (p.target.as_str(), format!("{}ms", val), color)
+                    }).collect();
+                    draw_chart(f, cell, &titled(panel.title()), &lines, false, slo, stats);
+                }
+                Panel::PacketRate => {
+                    let val = *app.traffic_history.back().unwrap_or(&0);
+                    let data = series(&mut app.traffic_history.iter().map(|&v| v as f64), &app.dash_pps);
+                    let stats = vec![("pkt/s", format!("{}", val), THEME.secondary)];
+                    draw_chart(f, cell, &titled(panel.title()), &[(&data, THEME.secondary)], false, 0.0, stats);
+                }
+                Panel::ProtocolMix => render_protocol_mix(f, app, cell),
+                Panel::Interfaces | Panel::TopAsns | Panel::Countries | Panel::Gateway | Panel::TopTalkers => {
+                    let title = match panel {
+                        Panel::TopTalkers => format!(" {} ({}) ", panel.title(), app.talkers_window.label()),
+                        _ => format!(" {} ", panel.title()),
+                    };
+                    let block = Block::default()
+                        .borders(if last { Borders::TOP } else { Borders::TOP | Borders::RIGHT })
+                        .border_style(Style::default().fg(THEME.border))
+                        .bg(THEME.bg)
+                        .title(Span::styled(title, Style::default().fg(THEME.muted)));
+                    let items = match panel {
+                        Panel::Interfaces => interface_items(app),
+                        Panel::TopAsns => asn_items(app),
+                        Panel::Gateway => gateway_items(app),
+                        Panel::TopTalkers => talker_items(app),
+                        _ => country_items(app),
+                    };
+                    f.render_widget(List::new(items).block(block), cell);
+                }
+            }
+        }
+    }
+}
 
-    // 2. Active Connections
-    let conn_val = *app.connection_count_history.back().unwrap_or(&0);
-    let conn_data: Vec<(f64, f64)> = app.connection_count_history.iter().enumerate().map(|(i, &v)| (i as f64, v as f64)).collect();
+/// Line colors for the dashboard's ping targets, in config order.
+fn probe_colors() -> [Color; 4] {
+    [THEME.primary, THEME.accent, THEME.success, THEME.secondary]
+}
 
-    let stats_conn = vec![
-        ("Active", format!("{}", conn_val), THEME.success),
-    ];
-    draw_chart(f, row1[1], "Total Connections", &conn_data, None, THEME.success, None, stats_conn);
+/// A latency or jitter SLO drawn on a chart: the threshold as a line, with a red bar under
+/// every sample over it.
+struct SloOverlay {
+    over: Vec<(f64, f64)>,
+    line: [(f64, f64); 2],
+}
 
-    // 3. Latency
-    let lat_val = *app.db_ping_history.back().unwrap_or(&0);
-    let lat_data: Vec<(f64, f64)> = app.db_ping_history.iter().enumerate().map(|(i, &v)| (i as f64, v as f64)).collect();
-    let stats_lat = vec![
-        ("ms", format!("{}", lat_val), if lat_val > 100 { THEME.error } else { THEME.primary }),
-    ];
-    draw_chart(f, row2[0], "Ping Latency (1.1.1.1)", &lat_data, None, THEME.primary, None, stats_lat);
+impl SloOverlay {
+    /// `None` when the threshold is 0 (off in the config).
+    fn new(threshold: f64, series: &[&[(f64, f64)]], x_max: f64) -> Option<Self> {
+        (threshold > 0.0).then(|| Self {
+            over: series.iter().flat_map(|s| s.iter()).filter(|(_, v)| *v > threshold).copied().collect(),
+            line: [(0.0, threshold), (x_max, threshold)],
+        })
+    }
 
-    // 4. Jitter
-    let jit_val = *app.db_jitter_history.back().unwrap_or(&0);
-    let jit_data: Vec<(f64, f64)> = app.db_jitter_history.iter().enumerate().map(|(i, &v)| (i as f64, v as f64)).collect();
-     let stats_jit = vec![
-        ("ms", format!("{}", jit_val), THEME.accent),
-    ];
-    draw_chart(f, row2[1], "Jitter", &jit_data, None, THEME.accent, None, stats_jit);
+    /// Goes before the chart's own datasets so the samples draw on top.
+    fn datasets(&self) -> [Dataset<'_>; 2] {
+        [
+            Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Bar)
+                .style(Style::default().fg(THEME.error).add_modifier(Modifier::DIM))
+                .data(&self.over),
+            Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(THEME.error))
+                .data(&self.line),
+        ]
+    }
+}
 
-    // -- Bottom Section: Interfaces & Top ASNs --
-    let bottom_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-        .split(chunks[2]);
+/// Latency distribution as bars, from the fastest sample to the 99th percentile; bars past
+/// the latency SLO are red. Percentiles go in the title.
+fn render_histogram(f: &mut Frame, area: Rect, hist: &Histogram, slo: f64) {
+    use ratatui::widgets::{Bar, BarChart, BarGroup};
+    let pct = |p: f64| hist.percentile(p).map_or("-".to_string(), |v| format!("{:.1}", v));
+    let title = format!(" Distribution  p50 {}  p95 {}  p99 {} ms ", pct(50.0), pct(95.0), pct(99.0));
+    let block = Block::default().title(title).borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
+    // 3-wide bars with a gap, so each fits a label of up to 3 digits
+    let bins = hist.bins((area.width.saturating_sub(2) / 4).clamp(1, 24) as usize);
+    let bars: Vec<Bar> = bins.iter().map(|&(start, n)| {
+        let color = if slo > 0.0 && start >= slo { THEME.error } else { THEME.primary };
+        Bar::default()
+            .value(n)
+            .text_value(String::new())
+            .label(Line::from(format!("{:.0}", start)))
+            .style(Style::default().fg(color))
+    }).collect();
+    let chart = BarChart::default()
+        .block(block)
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(3)
+        .bar_gap(1)
+        .label_style(Style::default().fg(THEME.muted));
+    f.render_widget(chart, area);
+}
+
+/// Red past the SLO, amber for anything under it worth a look.
+/// " !" after a value over its SLO in themes with symbols, so it doesn't rely on red alone.
+fn slo_mark(value: f64, threshold: f64) -> &'static str {
+    if THEME.symbols && threshold > 0.0 && value > threshold { " !" } else { "" }
+}
+
+fn slo_color(value: f64, threshold: f64, warn: f64, ok: Color) -> Color {
+    if threshold > 0.0 && value > threshold {
+        THEME.error
+    } else if value > warn {
+        THEME.secondary
+    } else {
+        ok
+    }
+}
+
+/// Share of the last 10 ticks on top, per-protocol pps stacked below (each line
+/// is the running total, so the gaps between lines are the protocols).
+fn render_protocol_mix(f: &mut Frame, app: &App, area: Rect) {
+    const NAMES: [&str; 4] = ["TCP", "UDP", "ICMP", "Other"];
+    let colors = [THEME.primary, THEME.secondary, THEME.accent, THEME.muted];
 
-    // Interfaces List
-    let list_area = bottom_chunks[0];
     let block = Block::default()
-        .borders(Borders::TOP | Borders::RIGHT)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(THEME.border))
         .bg(THEME.bg)
-        .title(Span::styled(" Interfaces ", Style::default().fg(THEME.muted)));
-    
-    let items: Vec<ListItem> = app.interfaces.iter().map(|i| {
+        .title(Span::styled(" Protocol Mix ", Style::default().fg(THEME.fg).add_modifier(Modifier::BOLD)));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    if inner.height < 3 {
+        return;
+    }
+
+    let mut recent = [0u64; 4];
+    for sample in app.protocol_history.iter().rev().take(10) {
+        for (total, n) in recent.iter_mut().zip(sample) {
+            *total += n;
+        }
+    }
+    let sum = recent.iter().sum::<u64>().max(1) as f64;
+    let mut share = vec![];
+    for ((name, n), color) in NAMES.iter().zip(recent).zip(colors) {
+        share.push(Span::styled(format!("{} ", name), Style::default().fg(color).add_modifier(Modifier::BOLD)));
+        share.push(Span::styled(format!("{:.0}%  ", n as f64 * 100.0 / sum), Style::default().fg(THEME.fg)));
+    }
+    f.render_widget(Paragraph::new(Line::from(share)).alignment(ratatui::layout::Alignment::Right), Rect { height: 1, ..inner });
+
+    let stacked: Vec<Vec<(f64, f64)>> = (0..4)
+        .map(|layer| {
+            app.protocol_history.iter().enumerate()
+                .map(|(i, sample)| (i as f64, sample[..=layer].iter().sum::<u64>() as f64))
+                .collect()
+        })
+        .collect();
+    let max_val = stacked[3].iter().map(|(_, v)| *v).fold(0.0f64, f64::max).max(1.0) * 1.2;
+
+    let datasets = stacked.iter().zip(colors).map(|(data, color)| {
+        Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(color))
+            .data(data)
+    }).collect();
+    let chart = Chart::new(datasets)
+        .x_axis(Axis::default().bounds([0.0, 100.0]).style(Style::default().fg(THEME.muted)))
+        .y_axis(Axis::default().bounds([0.0, max_val]).style(Style::default().fg(THEME.muted)));
+    f.render_widget(chart, Rect { y: inner.y + 2, height: inner.height.saturating_sub(2), ..inner });
+}
+
+fn render_interface_detail(f: &mut Frame, app: &App, name: &str, link: &crate::tools::interfaces::LinkInfo, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(9), Constraint::Min(6), Constraint::Min(6)].as_ref())
+        .split(area);
+
+    let iface = app.interfaces.iter().find(|i| i.name == name);
+    let history = app.iface_history.get(name);
+    let c = history.map(|h| h.counters).unwrap_or_default();
+
+    let label = |text: &str| Span::styled(format!(" {:<10}", text), Style::default().fg(THEME.muted));
+    let value = |text: String| Span::styled(text, Style::default().fg(THEME.fg).add_modifier(Modifier::BOLD));
+    let count = |n: u64, bad: bool| Span::styled(format!("{:<14}", n), Style::default().fg(if bad && n > 0 { THEME.error } else { THEME.fg }));
+    let lines = vec![
+        Line::from(vec![
+            label("State"),
+            value(match iface { Some(i) if i.is_up() => "up".to_string(), Some(_) => "down".to_string(), None => "gone".to_string() }),
+        ]),
+        Line::from(vec![label("MAC"), value(iface.and_then(|i| i.mac).map(|m| m.to_string()).unwrap_or_else(|| "-".to_string()))]),
+        Line::from(vec![label("IPs"), value(iface.map(|i| i.ips.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(", ")).unwrap_or_default())]),
+        Line::from(vec![
+            label("MTU"),
+            value(link.mtu.map(|m| m.to_string()).unwrap_or_else(|| "-".to_string())),
+            Span::raw("    "),
+            label("Speed"),
+            value(link.speed_mbps.map(|s| units::rate_mbps(s as f64)).unwrap_or_else(|| "-".to_string())),
+        ]),
+        Line::from(""),
+        Line::from(vec![label(""), Span::styled(format!("{:<14}{:<14}{:<14}{:<14}", "Bytes", "Packets", "Errors", "Drops"), Style::default().fg(THEME.primary))]),
+        Line::from(vec![label("RX"), count(c.rx_bytes, false), count(c.rx_packets, false), count(c.rx_errors, true), count(c.rx_dropped, true)]),
+        Line::from(vec![label("TX"), count(c.tx_bytes, false), count(c.tx_packets, false), count(c.tx_errors, true), count(c.tx_dropped, true)]),
+    ];
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(THEME.accent))
+        .bg(THEME.bg)
+        .title(Span::styled(format!(" Interface {} ([Esc] Back) ", name), Style::default().fg(THEME.fg).add_modifier(Modifier::BOLD)));
+    f.render_widget(Paragraph::new(lines).block(block), chunks[0]);
+
+    let Some(h) = history else {
+        f.render_widget(Paragraph::new(" No OS counters for this interface").style(Style::default().fg(THEME.muted)), chunks[1]);
+        return;
+    };
+
+    // Mirrored charts like the dashboard bandwidth panel: RX up, TX down
+    let pps = |v: f64| format!("{:.1} pkt/s", v);
+    for (area, title, format, rx, tx) in [
+        (chunks[1], "Throughput", &units::rate_mbps as &dyn Fn(f64) -> String, &h.rx_mbps, &h.tx_mbps),
+        (chunks[2], "Packets", &pps, &h.rx_pps, &h.tx_pps),
+    ] {
+        let rx_data: Vec<(f64, f64)> = rx.iter().enumerate().map(|(i, &v)| (i as f64, v)).collect();
+        let tx_data: Vec<(f64, f64)> = tx.iter().enumerate().map(|(i, &v)| (i as f64, -v)).collect();
+        let max_val = rx.iter().chain(tx.iter()).fold(0.0f64, |a, &b| a.max(b)).max(1.0) * 1.2;
+        let title = format!(" {}  ↓ {}  ↑ {} ", title, format(*rx.back().unwrap_or(&0.0)), format(*tx.back().unwrap_or(&0.0)));
+        let chart = Chart::new(vec![
+            Dataset::default().marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(THEME.primary)).data(&rx_data),
+            Dataset::default().marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(THEME.secondary)).data(&tx_data),
+        ])
+        .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)).bg(THEME.bg).title(title))
+        .x_axis(Axis::default().bounds([0.0, 100.0]).style(Style::default().fg(THEME.muted)))
+        .y_axis(Axis::default().bounds([-max_val, max_val]).style(Style::default().fg(THEME.muted)));
+        f.render_widget(chart, area);
+    }
+}
+
+fn interface_items(app: &App) -> Vec<ListItem<'static>> {
+    app.interfaces.iter().enumerate().map(|(idx, i)| {
         let name_color = if i.is_up() { THEME.success } else { THEME.error };
         let status = if i.is_up() { "●" } else { "○" };
         let ips = i.ips.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(", ");
@@ -745,20 +3441,11 @@ fn render_dashboard(f: &mut Frame, app: &App, area: Rect) {
             Span::styled(format!("{:<8}", i.name), Style::default().fg(THEME.fg).add_modifier(Modifier::BOLD)),
             Span::styled(ips, Style::default().fg(THEME.secondary)),
         ]);
-        ListItem::new(content).bg(THEME.bg)
-    }).collect();
-    
-    f.render_widget(List::new(items).block(block), list_area);
-
-    // Top ASNs
-    let asn_area = bottom_chunks[1];
-    let block_asn = Block::default()
-        .borders(Borders::TOP)
-        .border_style(Style::default().fg(THEME.border))
-        .bg(THEME.bg)
-        .title(Span::styled(" Top ASNs ", Style::default().fg(THEME.muted)));
+        ListItem::new(content).bg(if idx == app.dash_iface_selected { THEME.surface } else { THEME.bg })
+    }).collect()
+}
 
-    // Count ASNs
+fn asn_items(app: &App) -> Vec<ListItem<'static>> {
     use std::collections::HashMap;
     let mut asn_counts: HashMap<String, usize> = HashMap::new();
     for c in app.active_connections.values() {
@@ -766,17 +3453,106 @@ fn render_dashboard(f: &mut Frame, app: &App, area: Rect) {
              *asn_counts.entry(c.asn_org.clone()).or_insert(0) += 1;
         }
     }
-    let mut asn_vec: Vec<(&String, &usize)> = asn_counts.iter().collect();
-    asn_vec.sort_by(|a, b| b.1.cmp(a.1));
+    let mut asn_vec: Vec<(String, usize)> = asn_counts.into_iter().collect();
+    asn_vec.sort_by(|a, b| b.1.cmp(&a.1));
 
-    let asn_items: Vec<ListItem> = asn_vec.iter().take(5).map(|(org, count)| {
+    asn_vec.into_iter().take(5).map(|(org, count)| {
         ListItem::new(Line::from(vec![
             Span::styled(format!(" {:<3} ", count), Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD)),
-            Span::styled(format!("{}", org), Style::default().fg(THEME.fg)),
+            Span::styled(org, Style::default().fg(THEME.fg)),
         ]))
-    }).collect();
+    }).collect()
+}
+
+/// Verdict first, then the hops it is based on: router, WAN target, public address and its ISP.
+fn gateway_items(app: &App) -> Vec<ListItem<'static>> {
+    let (health, verdict) = dashboard::diagnose(app.gateway_probe.as_ref(), &app.dash_probes);
+    let (color, mark) = match health {
+        Health::Ok => (THEME.success, theme::mark(true)),
+        Health::Degraded => (THEME.accent, if THEME.symbols { "! " } else { "" }),
+        Health::Down => (THEME.error, theme::mark(false)),
+        Health::Unknown => (THEME.muted, ""),
+    };
+    let mut items = vec![ListItem::new(Span::styled(format!(" {}{}", mark, verdict), Style::default().fg(color).add_modifier(Modifier::BOLD)))];
 
-    f.render_widget(List::new(asn_items).block(block_asn), asn_area);
+    let row = |label: &str, value: String, detail: String| {
+        ListItem::new(Line::from(vec![
+            Span::styled(format!(" {:<7}", label), Style::default().fg(THEME.muted)),
+            Span::styled(value, Style::default().fg(THEME.fg)),
+            Span::styled(format!(" {}", detail), Style::default().fg(THEME.secondary)),
+        ]))
+    };
+    let rtt = |p: &LatencyProbe| match p.recent_loss(10) {
+        Some(loss) if loss > 0.0 => format!("{}ms {:.0}% loss", p.last_latency(), loss * 100.0),
+        Some(_) => format!("{}ms", p.last_latency()),
+        None => String::new(),
+    };
+
+    let Some(info) = &app.gateway_info else { return items };
+    items.push(match (&info.gateway, &app.gateway_probe) {
+        (Ok(ip), Some(probe)) => row("Router", ip.to_string(), rtt(probe)),
+        (Ok(ip), None) => row("Router", ip.to_string(), String::new()),
+        (Err(e), _) => row("Router", e.clone(), String::new()),
+    });
+    if let Some(probe) = app.dash_probes.first() {
+        items.push(row("WAN", probe.target.clone(), rtt(probe)));
+    }
+    match &info.public_ip {
+        Some(Ok(ip)) => {
+            items.push(row("Public", ip.to_string(), String::new()));
+            if let Some(peer) = app.geoip_lookups.get(*ip).filter(|p| p.asn_num != 0) {
+                items.push(row("ISP", format!("AS{}", peer.asn_num), peer.asn_org));
+            }
+        }
+        Some(Err(e)) => items.push(row("Public", e.clone(), String::new())),
+        None => {}
+    }
+    items
+}
+
+/// Peers by sniffed bytes over the Connections screen's talker window.
+fn talker_items(app: &App) -> Vec<ListItem<'static>> {
+    if !app.sniffer_active {
+        return vec![ListItem::new(Span::styled(" Start the sniffer to count traffic", Style::default().fg(THEME.muted)))];
+    }
+    app.top_talkers().into_iter().take(5).map(|h| {
+        let label = if h.org.is_empty() || h.org == "-" || h.org == "Unknown" { h.ip.to_string() } else { format!("{} ({})", h.ip, h.org) };
+        ListItem::new(Line::from(vec![
+            Span::styled(format!(" {:>9} ", units::bytes(h.bytes)), Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD)),
+            Span::styled(label, Style::default().fg(THEME.fg)),
+        ]))
+    }).collect()
+}
+
+fn country_items(app: &App) -> Vec<ListItem<'static>> {
+    use std::collections::HashMap;
+    if app.geoip_country.is_none() {
+        return vec![ListItem::new(Span::styled(" No country database", Style::default().fg(THEME.muted)))];
+    }
+    let mut country_counts: HashMap<&str, usize> = HashMap::new();
+    for c in app.active_connections.values() {
+        if let Some(cc) = &c.country {
+            *country_counts.entry(cc.as_str()).or_insert(0) += 1;
+        }
+    }
+    let mut country_vec: Vec<(&str, usize)> = country_counts.into_iter().collect();
+    country_vec.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+    country_vec.iter().take(5).map(|(cc, count)| {
+        ListItem::new(Line::from(vec![
+            Span::styled(format!(" {:<3} ", count), Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD)),
+            Span::styled(country_label(app, cc), Style::default().fg(THEME.fg)),
+        ]))
+    }).collect()
+}
+
+/// "🇩🇪 DE", or just "DE" with `ui.flags = false`.
+fn country_label(app: &App, code: &str) -> String {
+    if app.config.ui.flags {
+        format!("{} {}", geoip::flag(code), code)
+    } else {
+        code.to_string()
+    }
 }
 
 fn render_ping(f: &mut Frame, app: &App, area: Rect) {
@@ -792,7 +3568,7 @@ fn render_ping(f: &mut Frame, app: &App, area: Rect) {
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(input_border));
     
-    f.render_widget(Paragraph::new(app.ping_input.value()).block(input_block).style(Style::default().fg(THEME.fg)), chunks[0]);
+    f.render_widget(Paragraph::new(input_line(app, CurrentScreen::Ping, app.ping_input.value())).block(input_block).style(Style::default().fg(THEME.fg)), chunks[0]);
     
     if !app.is_pinging {
         f.set_cursor_position((chunks[0].x + app.ping_input.visual_cursor() as u16 + 1, chunks[0].y + 1));
@@ -808,7 +3584,7 @@ fn render_ping(f: &mut Frame, app: &App, area: Rect) {
         .split(content_area);
 
     let top_split = Layout::default()
-        .direction(Direction::Horizontal)
+        .direction(side_by_side(content_split[0]))
         .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
         .split(content_split[0]);
 
@@ -817,20 +3593,31 @@ fn render_ping(f: &mut Frame, app: &App, area: Rect) {
     let items: Vec<ListItem> = app.ping_history.iter().rev().map(|res| {
          match res {
             Ok(r) => {
+                let via = match r.probe {
+                    PingProbe::Icmp => format!("ttl={:<3}", r.ttl),
+                    probe => probe.label(),
+                };
                 ListItem::new(Line::from(vec![
                     Span::styled(format!("seq={:<3}", r.seq), Style::default().fg(THEME.muted)),
                     Span::raw(" "),
-                    Span::styled(format!("ttl={:<3}", r.ttl), Style::default().fg(THEME.muted)),
+                    Span::styled(via, Style::default().fg(THEME.muted)),
                     Span::raw(" "),
-                    Span::styled(format!("{:.2}ms", r.time.as_secs_f64() * 1000.0), Style::default().fg(THEME.success).add_modifier(Modifier::BOLD)),
+                    Span::styled(format!("{}{:.2}ms", theme::mark(true), r.time.as_secs_f64() * 1000.0), Style::default().fg(THEME.success).add_modifier(Modifier::BOLD)),
                 ]))
             },
-            Err(e) => ListItem::new(Span::styled(format!("Error: {}", e), Style::default().fg(THEME.error))),
+            Err(e) => ListItem::new(Span::styled(format!("{}Error: {}", theme::mark(false), e), Style::default().fg(THEME.error))),
         }
     }).collect();
 
+    // Port probes chart connect/answer time rather than echo replies
+    let probe = app.ping_history.iter().rev().find_map(|r| r.as_ref().ok()).map_or(PingProbe::Icmp, |r| r.probe);
+    let list_title = match probe {
+        PingProbe::Icmp => " Echo Replies ".to_string(),
+        PingProbe::Tcp(_) => format!(" TCP Connects ({}) ", probe.label()),
+        PingProbe::Udp(_) => format!(" UDP Replies ({}) ", probe.label()),
+    };
     let list_block = Block::default()
-        .title(" Echo Replies ")
+        .title(list_title)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(THEME.border));
@@ -847,7 +3634,7 @@ fn render_ping(f: &mut Frame, app: &App, area: Rect) {
     let mut total = 0;
     
     // We iterate history to calc stats. 
-    // Note: app.ping_history is limited to 50 items. 
+    // Note: app.ping_history is limited to `[retention] ping` items.
     // This gives "Recent Stats" which is good.
     for res in &app.ping_history {
         total += 1;
@@ -866,6 +3653,7 @@ fn render_ping(f: &mut Frame, app: &App, area: Rect) {
     }
     if count > 0 { avg /= count as f64; } else { min = 0.0; }
     let loss_pct = if total > 0 { (loss as f64 / total as f64) * 100.0 } else { 0.0 };
+    let slo = &app.config.slo;
 
     let stats_block = Block::default()
         .title(" Recent Stats ")
@@ -873,30 +3661,99 @@ fn render_ping(f: &mut Frame, app: &App, area: Rect) {
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(THEME.secondary));
     
-    let stats_text = vec![
+    let mut stats_text = vec![
         Line::from(vec![Span::raw("Sent: "), Span::styled(format!("{}", total), Style::default().fg(THEME.fg).add_modifier(Modifier::BOLD))]),
-        Line::from(vec![Span::raw("Loss: "), Span::styled(format!("{:.1}%", loss_pct), Style::default().fg(if loss > 0 { THEME.error } else { THEME.success }))]),
+        Line::from(vec![Span::raw("Loss: "), Span::styled(format!("{:.1}%{}", loss_pct, slo_mark(loss_pct, slo.loss_percent)), Style::default().fg(slo_color(loss_pct, slo.loss_percent, 0.0, THEME.success)))]),
         Line::from(""),
-        Line::from(vec![Span::raw("Min:  "), Span::styled(format!("{:.1}ms", min), Style::default().fg(THEME.primary))]),
-        Line::from(vec![Span::raw("Avg:  "), Span::styled(format!("{:.1}ms", avg), Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD))]),
-        Line::from(vec![Span::raw("Max:  "), Span::styled(format!("{:.1}ms", max), Style::default().fg(THEME.primary))]),
+        Line::from(vec![Span::raw("Min:  "), Span::styled(format!("{:.1}ms", min), Style::default().fg(slo_color(min, slo.latency_ms, f64::MAX, THEME.primary)))]),
+        Line::from(vec![Span::raw("Avg:  "), Span::styled(format!("{:.1}ms", avg), Style::default().fg(slo_color(avg, slo.latency_ms, f64::MAX, THEME.primary)).add_modifier(Modifier::BOLD))]),
+        Line::from(vec![Span::raw("Max:  "), Span::styled(format!("{:.1}ms", max), Style::default().fg(slo_color(max, slo.latency_ms, f64::MAX, THEME.primary)))]),
     ];
+    // Percentiles cover the whole session, not just the recent replies
+    let rtt = &app.ping_stats.rtt;
+    if rtt.count() > 0 {
+        stats_text.push(Line::from(""));
+        for (label, p) in [("p50:  ", 50.0), ("p95:  ", 95.0), ("p99:  ", 99.0)] {
+            let v = rtt.percentile(p).unwrap_or(0.0);
+            stats_text.push(Line::from(vec![Span::raw(label), Span::styled(format!("{:.1}ms", v), Style::default().fg(slo_color(v, slo.latency_ms, f64::MAX, THEME.accent)))]));
+        }
+    }
     
     f.render_widget(Paragraph::new(stats_text).block(stats_block), stats_area);
 
 
     // Graph
     let ping_data: Vec<(f64, f64)> = app.ping_rtt_history.iter().enumerate().map(|(i, &v)| (i as f64, v)).collect();
-    let ping_max = app.ping_rtt_history.iter().max_by(|a, b| a.total_cmp(b)).unwrap_or(&100.0).max(50.0) * 2.0;
+    let ping_max = app.ping_rtt_history.iter().max_by(|a, b| a.total_cmp(b)).unwrap_or(&100.0).max(50.0).max(slo.latency_ms) * 2.0;
+    let x_max = app.ping_rtt_history.capacity() as f64;
+    let overlay = SloOverlay::new(slo.latency_ms, &[&ping_data], x_max);
+    let mut datasets: Vec<Dataset> = overlay.iter().flat_map(|o| o.datasets()).collect();
+    datasets.push(Dataset::default().marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(THEME.primary)).data(&ping_data));
+    let rtt_title = if slo.latency_ms > 0.0 { format!(" RTT History (SLO {}ms) ", slo.latency_ms) } else { " RTT History ".to_string() };
+
+    let chart = Chart::new(datasets)
+    .block(Block::default().title(rtt_title).borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)))
+    .x_axis(Axis::default().bounds([0.0, x_max]).style(Style::default().fg(THEME.muted)))
+    .y_axis(Axis::default().bounds([0.0, ping_max]).style(Style::default().fg(THEME.muted)));
+
+    let graph_split = Layout::default()
+        .direction(side_by_side(content_split[1]))
+        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)].as_ref())
+        .split(content_split[1]);
+    if app.ping_heatmap_view {
+        render_heatmap(f, graph_split[0], &app.ping_heatmap, slo.latency_ms);
+    } else {
+        f.render_widget(chart, graph_split[0]);
+    }
+    render_histogram(f, graph_split[1], &app.ping_stats.rtt, slo.latency_ms);
+}
 
-    let chart = Chart::new(vec![
-        Dataset::default().marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(THEME.primary)).data(&ping_data)
-    ])
-    .block(Block::default().title(" RTT History ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)))
-    .x_axis(Axis::default().bounds([0.0, 100.0]).style(Style::default().fg(THEME.muted)))
-    .y_axis(Axis::default().bounds([0.0, ping_max as f64]).style(Style::default().fg(THEME.muted)));
-    
-    f.render_widget(chart, content_split[1]);
+/// A row per time bucket, newest at the bottom, and a column per latency range: the darker
+/// the cell, the larger the bucket's share of probes in that range. Lost probes come last.
+fn render_heatmap(f: &mut Frame, area: Rect, heatmap: &LatencyHeatmap, slo: f64) {
+    const LABEL: usize = 9;
+    let title = format!(" Latency Heatmap ({}s rows) ", heatmap.bucket_secs);
+    let block = Block::default().title(title).borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let columns = HEATMAP_BOUNDS.len() + 2;
+    let cell = ((inner.width as usize).saturating_sub(LABEL) / columns).clamp(3, 6);
+    let mut names: Vec<String> = HEATMAP_BOUNDS.iter()
+        .map(|&b| if b >= 1000.0 { format!("<{}s", b / 1000.0) } else { format!("<{}", b) })
+        .collect();
+    names.push(format!("{}s+", HEATMAP_BOUNDS[HEATMAP_BOUNDS.len() - 1] / 1000.0));
+    names.push("loss".to_string());
+    let mut header = vec![Span::styled(format!("{:<LABEL$}", "ms"), Style::default().fg(THEME.muted))];
+    header.extend(names.iter().map(|n| Span::styled(format!("{:<cell$}", n), Style::default().fg(THEME.muted))));
+    let mut lines = vec![Line::from(header)];
+
+    let visible = (inner.height as usize).saturating_sub(1);
+    let skip = heatmap.rows.len().saturating_sub(visible);
+    for row in &heatmap.rows[skip..] {
+        let total = row.total().max(1) as f64;
+        let mut spans = vec![Span::styled(format!("{:<LABEL$}", row.time_label()), Style::default().fg(THEME.muted))];
+        for (i, n) in row.counts.iter().chain([&row.lost]).enumerate() {
+            let share = *n as f64 / total;
+            let shade = match share {
+                _ if *n == 0 => " ",
+                s if s < 0.25 => "░",
+                s if s < 0.5 => "▒",
+                s if s < 0.75 => "▓",
+                _ => "█",
+            };
+            // Over the SLO: the column's fastest reply is already too slow
+            let lost = i > HEATMAP_BOUNDS.len();
+            let over = slo > 0.0 && i > 0 && i <= HEATMAP_BOUNDS.len() && HEATMAP_BOUNDS[i - 1] >= slo;
+            let color = if lost || over { THEME.error } else { THEME.primary };
+            spans.push(Span::styled(format!("{} ", shade.repeat(cell - 1)), Style::default().fg(color)));
+        }
+        lines.push(Line::from(spans));
+    }
+    if heatmap.rows.is_empty() {
+        lines.push(Line::from(Span::styled("No replies yet", Style::default().fg(THEME.muted))));
+    }
+    f.render_widget(Paragraph::new(lines), inner);
 }
 
 fn render_dns(f: &mut Frame, app: &App, area: Rect) {
@@ -905,8 +3762,12 @@ fn render_dns(f: &mut Frame, app: &App, area: Rect) {
         .constraints([Constraint::Length(3), Constraint::Length(3), Constraint::Min(1)].as_ref())
         .split(area);
 
-    let input_block = Block::default().title(" Domain ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
-    f.render_widget(Paragraph::new(app.dns_input.value()).block(input_block).style(Style::default().fg(THEME.fg)), chunks[0]);
+    let focused = app.focused(CurrentScreen::Dns);
+    let input_block = Block::default().title(" Domain ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(focus_color(focused == Some(Field::DnsDomain))));
+    f.render_widget(Paragraph::new(input_line(app, CurrentScreen::Dns, app.dns_input.value())).block(input_block).style(Style::default().fg(THEME.fg)), chunks[0]);
+    if focused == Some(Field::DnsDomain) {
+        f.set_cursor_position((chunks[0].x + app.dns_input.visual_cursor() as u16 + 1, chunks[0].y + 1));
+    }
 
     // Type Selector
     let types = vec!["A", "AAAA", "MX", "TXT", "NS"];
@@ -925,9 +3786,32 @@ fn render_dns(f: &mut Frame, app: &App, area: Rect) {
         type_spans.push(Span::styled(format!(" {} ", t), if is_selected { Style::default().bg(THEME.primary).fg(THEME.bg).add_modifier(Modifier::BOLD) } else { Style::default().fg(THEME.muted).bg(THEME.surface) }));
         type_spans.push(Span::raw(" "));
     }
-    f.render_widget(Paragraph::new(Line::from(type_spans)).block(Block::default().title(" Record Type ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border))), chunks[1]);
-
-    // Results
+    f.render_widget(Paragraph::new(Line::from(type_spans)).block(Block::default().title(" Record Type ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(focus_color(focused == Some(Field::DnsType))))), chunks[1]);
+
+    // Results, beside the resolution times and answer changes while watching
+    let results_area = match &app.dns_watch {
+        Some(watch) => {
+            let split = Layout::default()
+                .direction(side_by_side(chunks[2]))
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                .split(chunks[2]);
+            render_dns_watch(f, watch, split[1]);
+            split[0]
+        }
+        None => chunks[2],
+    };
+    if let Some(scan) = &app.dns_subdomains {
+        render_subdomains(f, scan, focused == Some(Field::DnsResults), app.dns_subdomains_rx.is_some(), results_area);
+        return;
+    }
+    if let Some(check) = &app.dns_blocklist {
+        render_blocklist(f, check, results_area);
+        return;
+    }
+    if app.dns_mail_rx.is_some() || app.dns_mail.is_some() {
+        render_mail_check(f, app.dns_mail.as_ref(), results_area);
+        return;
+    }
     let res_block = Block::default().title(" Results ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
     if let Some(res) = &app.dns_result {
         match res {
@@ -939,13 +3823,170 @@ fn render_dns(f: &mut Frame, app: &App, area: Rect) {
                     DnsResult::TXT(recs) => recs.iter().map(|r| ListItem::new(format!("{} (TTL: {}s)", r.value, r.ttl))).collect(),
                     DnsResult::NS(recs) => recs.iter().map(|r| ListItem::new(format!("{} (TTL: {}s)", r.value, r.ttl))).collect(),
                 };
-                f.render_widget(List::new(lines).block(res_block).style(Style::default().fg(THEME.success)), chunks[2]);
+                f.render_widget(List::new(lines).block(res_block).style(Style::default().fg(THEME.success)), results_area);
             },
             Err(e) => {
-                f.render_widget(Paragraph::new(format!("Error: {}", e)).style(Style::default().fg(THEME.error)).block(res_block), chunks[2]);
+                f.render_widget(Paragraph::new(format!("Error: {}", e)).style(Style::default().fg(THEME.error)).block(res_block), results_area);
             }
         }
     } else {
-        f.render_widget(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(" Results ").style(Style::default().fg(THEME.muted)), chunks[2]);
+        f.render_widget(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(" Results ").style(Style::default().fg(THEME.muted)), results_area);
+    }
+}
+
+fn render_subdomains(f: &mut Frame, scan: &subdomains::SubdomainScan, focused: bool, running: bool, area: Rect) {
+    let status = if running { "searching..." } else { "done" };
+    let mut title = format!(" Subdomains of {}: {} found, {} picked, {} ", scan.domain, scan.found.len(), scan.picked.len(), status);
+    if !scan.wildcard.is_empty() {
+        title.push_str("(wildcard DNS: guesses matching it are hidden) ");
+    }
+    let hint = if focused { " Space: pick, Ctrl+N: scan picked (or all) " } else { " Tab here to pick targets " };
+    let block = Block::default()
+        .title(title)
+        .title_bottom(Line::from(hint).right_aligned())
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(focus_color(focused)));
+
+    let mut items: Vec<ListItem> = scan.errors.iter().map(|e| ListItem::new(format!("Error: {}", e)).style(Style::default().fg(THEME.error))).collect();
+    let name_width = scan.found.iter().map(|s| s.name.len()).max().unwrap_or(0).min(48);
+    items.extend(scan.found.iter().map(|sub| {
+        let check = if scan.picked.contains(&sub.name) { "[x]" } else { "[ ]" };
+        let ips = if sub.ips.is_empty() {
+            "(does not resolve)".to_string()
+        } else {
+            sub.ips.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(", ")
+        };
+        let color = if sub.ips.is_empty() { THEME.muted } else { THEME.fg };
+        ListItem::new(Line::from(vec![
+            Span::styled(format!("{} {:<w$}  ", check, sub.name, w = name_width), Style::default().fg(color)),
+            Span::styled(format!("{:<9}", sub.source), Style::default().fg(THEME.muted)),
+            Span::styled(ips, Style::default().fg(color)),
+        ]))
+    }));
+    let selected = (!scan.found.is_empty()).then_some(scan.errors.len() + scan.selected);
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().bg(THEME.secondary).fg(THEME.bg).add_modifier(Modifier::BOLD));
+    f.render_stateful_widget(list, area, &mut ListState::default().with_selected(selected.filter(|_| focused)));
+}
+
+fn render_blocklist(f: &mut Frame, check: &dnsbl::BlocklistCheck, area: Rect) {
+    use ratatui::widgets::{Cell, Row, Table};
+    let title = match &check.address {
+        None => format!(" Blocklists: resolving {} ", check.target),
+        Some(Err(e)) => format!(" Blocklists: {} ", e),
+        Some(Ok(ip)) if ip.to_string() == check.target => format!(" Blocklists: {} ", ip),
+        Some(Ok(ip)) => format!(" Blocklists: {} ({}) ", check.target, ip),
+    };
+    let answered = check.results.iter().filter(|(_, r)| r.is_some()).count();
+    let summary = format!(" listed on {} of {} ({} answered) ", check.listed(), check.results.len(), answered);
+    let block = Block::default()
+        .title(title)
+        .title(Line::from(summary).right_aligned())
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(if check.listed() > 0 { THEME.error } else { THEME.border }));
+
+    let header = Row::new(["List", "Status", "Time", "Details"]).style(Style::default().fg(THEME.primary).add_modifier(Modifier::BOLD));
+    let rows: Vec<Row> = check.results.iter().map(|(list, result)| {
+        let (status, color, details) = match result.as_ref().map(|r| &r.listing) {
+            None => ("...".to_string(), THEME.muted, String::new()),
+            Some(dnsbl::Listing::Clean) => (format!("{}clean", theme::mark(true)), THEME.success, String::new()),
+            Some(dnsbl::Listing::Listed { codes, reason }) => {
+                let codes = codes.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(", ");
+                let details = reason.as_ref().map(|r| format!("{}: {}", codes, r)).unwrap_or(codes);
+                (format!("{}LISTED", theme::mark(false)), THEME.error, details)
+            }
+            Some(dnsbl::Listing::Refused(why)) => ("refused".to_string(), THEME.accent, why.clone()),
+            Some(dnsbl::Listing::Error(e)) => ("error".to_string(), THEME.muted, e.clone()),
+        };
+        let time = result.as_ref().map(|r| format!("{:.0} ms", r.ms)).unwrap_or_default();
+        Row::new([
+            Cell::from(list.clone()),
+            Cell::from(status).style(Style::default().fg(color)),
+            Cell::from(time),
+            Cell::from(details),
+        ]).style(Style::default().fg(THEME.fg))
+    }).collect();
+    let table = Table::new(rows, [Constraint::Length(26), Constraint::Length(9), Constraint::Length(8), Constraint::Min(10)])
+        .header(header)
+        .block(block);
+    f.render_widget(table, area);
+}
+
+fn render_mail_check(f: &mut Frame, check: Option<&Result<mailcheck::MailCheck, String>>, area: Rect) {
+    let block = Block::default().title(" Email Deliverability ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
+    let check = match check {
+        None => {
+            f.render_widget(Paragraph::new("Checking MX, SPF and DMARC...").style(Style::default().fg(THEME.muted)).block(block), area);
+            return;
+        }
+        Some(Err(e)) => {
+            f.render_widget(Paragraph::new(format!("Error: {}", e)).style(Style::default().fg(THEME.error)).block(block), area);
+            return;
+        }
+        Some(Ok(check)) => check,
+    };
+
+    let label = Style::default().fg(THEME.muted);
+    let mut lines = vec![];
+    let records: [(&str, &[String]); 3] = [("MX", &check.mx), ("SPF", &check.spf), ("DMARC", &check.dmarc)];
+    let dkim = check.dkim.iter().map(|(_, records)| ("DKIM", records.as_slice()));
+    for (name, values) in records.into_iter().chain(dkim) {
+        if values.is_empty() {
+            lines.push(Line::from(vec![Span::styled(format!(" {:<6}", name), label), Span::styled("(none)", label)]));
+        }
+        for (i, value) in values.iter().enumerate() {
+            let name = if i == 0 { name } else { "" };
+            lines.push(Line::from(vec![Span::styled(format!(" {:<6}", name), label), Span::styled(value.clone(), Style::default().fg(THEME.fg))]));
+        }
+    }
+    lines.push(Line::from(""));
+    for finding in &check.findings {
+        let (color, mark) = match finding.severity {
+            mailcheck::Severity::Ok => (THEME.success, theme::mark(true)),
+            mailcheck::Severity::Warning => (THEME.accent, if THEME.symbols { "! " } else { "" }),
+            mailcheck::Severity::Error => (THEME.error, theme::mark(false)),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!(" {:<6}", finding.record), label),
+            Span::styled(format!("{}{}", mark, finding.message), Style::default().fg(color)),
+        ]));
+    }
+    let verdict = match check.worst() {
+        mailcheck::Severity::Ok => " all good ",
+        mailcheck::Severity::Warning => " warnings ",
+        mailcheck::Severity::Error => " problems found ",
+    };
+    let block = block.title(Line::from(format!(" {}:{}", check.domain, verdict)).right_aligned());
+    f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }).block(block), area);
+}
+
+fn render_dns_watch(f: &mut Frame, watch: &dns::DnsWatch, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(area);
+
+    let data: Vec<(f64, f64)> = watch.times_ms.iter().enumerate().map(|(i, &ms)| (i as f64, ms)).collect();
+    let max = watch.times_ms.iter().copied().fold(10.0, f64::max) * 1.2;
+    let last = watch.times_ms.back().map(|ms| format!(", last {:.0} ms", ms)).unwrap_or_default();
+    let title = format!(" Resolution Time (every {}s, {} queries, {} failed{}) ", watch.interval.as_secs_f64(), watch.queries, watch.failures, last);
+    let chart = Chart::new(vec![
+        Dataset::default().marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(THEME.primary)).data(&data),
+    ])
+    .block(Block::default().title(title).borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border)))
+    .x_axis(Axis::default().bounds([0.0, watch.times_ms.capacity() as f64]).style(Style::default().fg(THEME.muted)))
+    .y_axis(Axis::default().bounds([0.0, max]).labels(["0".to_string(), format!("{:.0} ms", max)]).style(Style::default().fg(THEME.muted)));
+    f.render_widget(chart, chunks[0]);
+
+    let block = Block::default().title(" Answer Changes ").borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(THEME.border));
+    if watch.changes.is_empty() {
+        f.render_widget(Paragraph::new("No changes since the first answer").style(Style::default().fg(THEME.muted)).block(block), chunks[1]);
+        return;
     }
+    // Newest first
+    let items: Vec<ListItem> = watch.changes.iter().rev().map(|c| ListItem::new(c.as_str())).collect();
+    f.render_widget(List::new(items).block(block).style(Style::default().fg(THEME.accent)), chunks[1]);
 }
@@ -0,0 +1,93 @@
+use ratatui::style::Color;
+use ratatui::widgets::canvas::{Painter, Shape};
+
+// Tilt the view so the northern hemisphere, where most peers are, faces the viewer
+const CENTER_LAT: f64 = 20.0;
+
+/// Orthographic projection of (lat, lon) in degrees onto the unit disc, for a globe
+/// spun `rotation` radians eastwards. None when the point is on the far side.
+pub fn project(lat: f64, lon: f64, rotation: f64) -> Option<(f64, f64)> {
+    let (phi, phi0) = (lat.to_radians(), CENTER_LAT.to_radians());
+    let dlon = lon.to_radians() + rotation;
+    if phi0.sin() * phi.sin() + phi0.cos() * phi.cos() * dlon.cos() < 0.0 {
+        return None;
+    }
+    let x = phi.cos() * dlon.sin();
+    let y = phi0.cos() * phi.sin() - phi0.sin() * phi.cos() * dlon.cos();
+    Some((x, y))
+}
+
+/// Outline, graticule and great-circle arcs from `home` to each marker. Drawn in
+/// canvas coordinates where the globe is the unit circle; markers themselves are
+/// left to the caller so they can be printed on top.
+pub struct Globe<'a> {
+    pub rotation: f64,
+    pub markers: &'a [(f64, f64)],
+    pub home: Option<(f64, f64)>,
+    pub grid_color: Color,
+    pub arc_color: Color,
+}
+
+impl Globe<'_> {
+    fn plot(&self, painter: &mut Painter, lat: f64, lon: f64, color: Color) {
+        if let Some((x, y)) = project(lat, lon, self.rotation) {
+            dot(painter, x, y, color);
+        }
+    }
+}
+
+impl Shape for Globe<'_> {
+    fn draw(&self, painter: &mut Painter) {
+        for i in 0..360 {
+            let a = (i as f64).to_radians();
+            dot(painter, a.cos(), a.sin(), self.grid_color);
+        }
+
+        // Graticule every 30 degrees
+        for lon in (-180..180).step_by(30) {
+            for lat in (-90..=90).step_by(2) {
+                self.plot(painter, lat as f64, lon as f64, self.grid_color);
+            }
+        }
+        for lat in (-60..=60).step_by(30) {
+            for lon in (-180..180).step_by(2) {
+                self.plot(painter, lat as f64, lon as f64, self.grid_color);
+            }
+        }
+
+        let Some(home) = self.home else { return };
+        for &target in self.markers {
+            for (lat, lon) in great_circle(home, target, 48) {
+                self.plot(painter, lat, lon, self.arc_color);
+            }
+        }
+    }
+}
+
+fn dot(painter: &mut Painter, x: f64, y: f64, color: Color) {
+    if let Some((px, py)) = painter.get_point(x, y) {
+        painter.paint(px, py, color);
+    }
+}
+
+/// `steps + 1` points from `a` to `b` along the shorter great circle (slerp).
+fn great_circle(a: (f64, f64), b: (f64, f64), steps: usize) -> Vec<(f64, f64)> {
+    let to_vec = |(lat, lon): (f64, f64)| {
+        let (lat, lon) = (lat.to_radians(), lon.to_radians());
+        [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+    };
+    let (va, vb) = (to_vec(a), to_vec(b));
+    let omega = (va[0] * vb[0] + va[1] * vb[1] + va[2] * vb[2]).clamp(-1.0, 1.0).acos();
+    if omega < 1e-6 {
+        return vec![a];
+    }
+
+    (0..=steps)
+        .map(|i| {
+            let t = i as f64 / steps as f64;
+            let (wa, wb) = (((1.0 - t) * omega).sin() / omega.sin(), (t * omega).sin() / omega.sin());
+            let v: Vec<f64> = (0..3).map(|k| wa * va[k] + wb * vb[k]).collect();
+            (v[2].atan2(v[0].hypot(v[1])).to_degrees(), v[1].atan2(v[0]).to_degrees())
+        })
+        .collect()
+}
@@ -0,0 +1,20 @@
+// Braille dots filled bottom-up, for the left and the right column of a character
+const LEFT: [u32; 4] = [0x40, 0x04, 0x02, 0x01];
+const RIGHT: [u32; 4] = [0x80, 0x20, 0x10, 0x08];
+
+/// The newest `width * 2` of `values` as a line of braille bars, two values per character,
+/// scaled so the largest is four dots high. Short histories are padded on the left.
+pub fn braille(values: impl DoubleEndedIterator<Item = f64>, width: usize) -> String {
+    let mut newest: Vec<f64> = values.rev().take(width * 2).collect();
+    newest.reverse();
+    let max = newest.iter().copied().fold(0.0, f64::max);
+    let level = |v: f64| if max > 0.0 && v > 0.0 { ((v / max * 4.0).ceil() as usize).clamp(1, 4) } else { 0 };
+
+    let padding = width * 2 - newest.len();
+    let mut levels = vec![0; padding];
+    levels.extend(newest.into_iter().map(level));
+    levels.chunks(2).map(|pair| {
+        let dots = LEFT[..pair[0]].iter().sum::<u32>() + RIGHT[..pair[1]].iter().sum::<u32>();
+        char::from_u32(0x2800 + dots).unwrap_or(' ')
+    }).collect()
+}
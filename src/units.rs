@@ -0,0 +1,65 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+/// How transfer rates are shown; amounts of data are always in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateUnit {
+    /// Kbps, Mbps, Gbps: what links and ISPs are rated in
+    #[default]
+    Bits,
+    /// KB/s, MB/s, GB/s: what downloads show
+    Bytes,
+}
+
+static BYTES: AtomicBool = AtomicBool::new(false);
+static BINARY: AtomicBool = AtomicBool::new(false);
+
+/// Sets the units every formatter below uses, from `[ui]`.
+pub fn configure(rate_unit: RateUnit, binary: bool) {
+    BYTES.store(rate_unit == RateUnit::Bytes, Ordering::Relaxed);
+    BINARY.store(binary, Ordering::Relaxed);
+}
+
+/// Largest prefix that keeps the value at or above 1: SI steps of 1000, binary of 1024.
+fn scaled(value: f64) -> (f64, &'static str) {
+    let (step, prefixes) = if BINARY.load(Ordering::Relaxed) {
+        (1024.0, ["", "Ki", "Mi", "Gi", "Ti"])
+    } else {
+        (1000.0, ["", "K", "M", "G", "T"])
+    };
+    let mut value = value;
+    let mut i = 0;
+    while value.abs() >= step && i + 1 < prefixes.len() {
+        value /= step;
+        i += 1;
+    }
+    (value, prefixes[i])
+}
+
+fn number(value: f64, prefix: &str) -> String {
+    if prefix.is_empty() { format!("{:.0}", value) } else { format!("{:.1}", value) }
+}
+
+/// "12.3 Mbps" or "1.5 MB/s", as configured.
+pub fn rate(bytes_per_sec: f64) -> String {
+    if BYTES.load(Ordering::Relaxed) {
+        let (value, prefix) = scaled(bytes_per_sec);
+        format!("{} {}B/s", number(value, prefix), prefix)
+    } else {
+        let (value, prefix) = scaled(bytes_per_sec * 8.0);
+        format!("{} {}bps", number(value, prefix), prefix)
+    }
+}
+
+/// For the dashboard and interface histories, which are kept in Mbps.
+pub fn rate_mbps(mbps: f64) -> String {
+    rate(mbps * 1_000_000.0 / 8.0)
+}
+
+/// "640 B", "1.2 MB" or "1.1 MiB".
+pub fn bytes(bytes: u64) -> String {
+    let (value, prefix) = scaled(bytes as f64);
+    format!("{} {}B", number(value, prefix), prefix)
+}